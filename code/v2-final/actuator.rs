@@ -0,0 +1,70 @@
+//! # Actuator Dynamics
+//!
+//! The turbulence enhancement (ECRH / gas puff) does not switch instantly:
+//! commands arrive after a latency and the output relaxes exponentially
+//! toward the commanded level with separate rise and fall times.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Actuator {
+    pub latency: f64,   // s, command → response delay
+    pub rise_time: f64, // s, e-folding time when switching on
+    pub fall_time: f64, // s, e-folding time when switching off
+    output: f64,        // 0 = off, 1 = full enhancement
+    target: f64,
+    last_command: bool,
+    pending: VecDeque<(f64, bool)>, // (time the command takes effect, command)
+}
+
+impl Actuator {
+    pub fn new(latency: f64, rise_time: f64, fall_time: f64) -> Self {
+        Actuator {
+            latency,
+            rise_time,
+            fall_time,
+            output: 0.0,
+            target: 0.0,
+            last_command: false,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Instantaneous switching (the original v2 behavior).
+    pub fn ideal() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+
+    /// Current output level in [0, 1].
+    pub fn output(&self) -> f64 {
+        self.output
+    }
+
+    /// Advances the actuator by `dt` given the command issued at `time`.
+    pub fn step(&mut self, command: bool, time: f64, dt: f64) -> f64 {
+        if command != self.last_command {
+            self.pending.push_back((time + self.latency, command));
+            self.last_command = command;
+        }
+        while let Some(&(effective, cmd)) = self.pending.front() {
+            if effective > time {
+                break;
+            }
+            self.target = if cmd { 1.0 } else { 0.0 };
+            self.pending.pop_front();
+        }
+
+        let tau = if self.target > self.output {
+            self.rise_time
+        } else {
+            self.fall_time
+        };
+        if tau > 0.0 {
+            self.output += (self.target - self.output) * (1.0 - (-dt / tau).exp());
+        } else {
+            self.output = self.target;
+        }
+        self.output
+    }
+}
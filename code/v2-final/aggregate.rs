@@ -0,0 +1,340 @@
+//! # Multi-Run Aggregation
+//!
+//! `aggregate` reduces the per-run outputs of a sweep — one directory per
+//! run, or any layout under `[aggregate] directory` — across runs:
+//! - trace files (CSV or binary, recognised by their header) become the
+//!   mean and percentile bands of every channel on a common time grid
+//!   spanning the interval all traces cover;
+//! - run summaries (files named `*summary.json`) become a histogram of the
+//!   pulse count and the Pareto front of confinement loss against mean
+//!   n_Z(0), both minimised.
+//!
+//! Other files (event logs, profiles, crash dumps, tables) are skipped, so
+//! the whole output tree can be pointed at. Parquet traces are not read.
+
+use crate::ensemble::percentile;
+use crate::history::Channel;
+use crate::summary::RunSummary;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AggregateConfig {
+    pub directory: String,     // Searched recursively for run outputs
+    pub trace_interval: f64,   // s between points of the combined traces
+    pub percentiles: Vec<f64>, // in [0, 100]
+    pub traces: String,        // Mean and percentile traces (CSV)
+    pub histogram: String,     // Runs per pulse count (CSV)
+    pub pareto: String,        // Non-dominated runs (CSV)
+}
+
+impl Default for AggregateConfig {
+    fn default() -> Self {
+        AggregateConfig {
+            directory: "runs".to_string(),
+            trace_interval: 0.01,
+            percentiles: vec![5.0, 50.0, 95.0],
+            traces: "aggregate_traces.csv".to_string(),
+            histogram: "aggregate_pulses.csv".to_string(),
+            pareto: "aggregate_pareto.csv".to_string(),
+        }
+    }
+}
+
+/// One run's time trace: `values[c]` is channel `Channel::ALL[c]`.
+pub struct RunTrace {
+    pub path: PathBuf,
+    pub time: Vec<f64>,
+    pub values: Vec<Vec<f64>>,
+}
+
+pub struct Aggregate {
+    pub traces: Vec<PathBuf>,
+    pub summaries: Vec<(PathBuf, RunSummary)>,
+    pub time: Vec<f64>,
+    /// `mean[c][j]`: mean of channel `c` at `time[j]`.
+    pub mean: Vec<Vec<f64>>,
+    /// `bands[c][k][j]`: percentile `k` of channel `c` at `time[j]`.
+    pub bands: Vec<Vec<Vec<f64>>>,
+    /// (pulse count, runs), every count from the lowest to the highest.
+    pub histogram: Vec<(usize, usize)>,
+    /// Indices into `summaries`, by increasing confinement loss.
+    pub pareto: Vec<usize>,
+}
+
+/// Linear interpolation in a monotonic `time`.
+fn interpolate(time: &[f64], values: &[f64], t: f64) -> f64 {
+    let i = time.partition_point(|&s| s < t).clamp(1, time.len() - 1);
+    let (t0, t1) = (time[i - 1], time[i]);
+    if t1 <= t0 {
+        return values[i];
+    }
+    values[i - 1] + (values[i] - values[i - 1]) * (t - t0) / (t1 - t0)
+}
+
+/// Indices of the points not dominated in both coordinates (lower is
+/// better), sorted by the first coordinate.
+pub fn pareto_front(points: &[(f64, f64)]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| points[a].0.total_cmp(&points[b].0).then(points[a].1.total_cmp(&points[b].1)));
+    let mut best = f64::INFINITY;
+    order
+        .into_iter()
+        .filter(|&i| {
+            let keep = points[i].1 < best;
+            if keep {
+                best = points[i].1;
+            }
+            keep
+        })
+        .collect()
+}
+
+/// Combines traces and summaries already read from disk.
+pub fn aggregate(settings: &AggregateConfig, traces: &[RunTrace], summaries: Vec<(PathBuf, RunSummary)>) -> Aggregate {
+    let usable: Vec<&RunTrace> = traces.iter().filter(|t| t.time.len() >= 2).collect();
+    let start = usable.iter().map(|t| t.time[0]).fold(f64::NEG_INFINITY, f64::max);
+    let end = usable.iter().map(|t| t.time[t.time.len() - 1]).fold(f64::INFINITY, f64::min);
+    let time: Vec<f64> = if usable.is_empty() || end < start || settings.trace_interval <= 0.0 {
+        Vec::new()
+    } else {
+        let n = ((end - start) / settings.trace_interval + 1e-9).floor() as usize + 1;
+        (0..n).map(|j| start + j as f64 * settings.trace_interval).collect()
+    };
+
+    let mut mean = Vec::with_capacity(Channel::ALL.len());
+    let mut bands = Vec::with_capacity(Channel::ALL.len());
+    for c in 0..Channel::ALL.len() {
+        let columns: Vec<Vec<f64>> = time
+            .iter()
+            .map(|&t| usable.iter().map(|run| interpolate(&run.time, &run.values[c], t)).collect())
+            .collect();
+        mean.push(columns.iter().map(|v| v.iter().sum::<f64>() / v.len() as f64).collect());
+        bands.push(
+            settings
+                .percentiles
+                .iter()
+                .map(|&p| columns.iter().map(|v| percentile(&mut v.clone(), p)).collect())
+                .collect(),
+        );
+    }
+
+    let counts: Vec<usize> = summaries.iter().map(|(_, s)| s.pulses).collect();
+    let histogram = match (counts.iter().min(), counts.iter().max()) {
+        (Some(&lo), Some(&hi)) => (lo..=hi).map(|n| (n, counts.iter().filter(|&&c| c == n).count())).collect(),
+        _ => Vec::new(),
+    };
+    let points: Vec<(f64, f64)> = summaries
+        .iter()
+        .map(|(_, s)| (s.confinement_loss, s.mean_center_impurity))
+        .collect();
+
+    Aggregate {
+        traces: usable.iter().map(|t| t.path.clone()).collect(),
+        pareto: pareto_front(&points),
+        summaries,
+        time,
+        mean,
+        bands,
+        histogram,
+    }
+}
+
+#[cfg(feature = "fs")]
+mod read {
+    use super::RunTrace;
+    use crate::history::Channel;
+    use crate::summary::RunSummary;
+    use std::io::{self, Read};
+    use std::path::{Path, PathBuf};
+
+    /// Every file under `directory`, in sorted order.
+    pub fn files(directory: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut found = Vec::new();
+        let mut pending = vec![directory.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else {
+                    found.push(path);
+                }
+            }
+        }
+        found.sort();
+        Ok(found)
+    }
+
+    pub fn summary(path: &Path) -> Option<RunSummary> {
+        let name = path.file_name()?.to_str()?;
+        if !name.ends_with("summary.json") {
+            return None;
+        }
+        serde_json::from_reader(io::BufReader::new(std::fs::File::open(path).ok()?)).ok()
+    }
+
+    /// `Ok(None)` for files that are not a trace.
+    pub fn trace(path: &Path) -> io::Result<Option<RunTrace>> {
+        let mut magic = [0u8; 4];
+        let mut file = std::fs::File::open(path)?;
+        if file.read_exact(&mut magic).is_ok() && &magic == b"W7XT" {
+            return binary(path).map(Some);
+        }
+        if path.extension().and_then(|e| e.to_str()) == Some("csv") {
+            return csv(path);
+        }
+        Ok(None)
+    }
+
+    fn invalid(path: &Path, message: impl std::fmt::Display) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), message))
+    }
+
+    fn csv(path: &Path) -> io::Result<Option<RunTrace>> {
+        let text = std::fs::read_to_string(path)?;
+        let mut lines = text.lines().filter(|line| !line.starts_with('#') && !line.trim().is_empty());
+        let header: Vec<&str> = match lines.next() {
+            Some(line) => line.split(',').map(str::trim).collect(),
+            None => return Ok(None),
+        };
+        let columns: Option<Vec<usize>> = Channel::ALL
+            .iter()
+            .map(|c| header.iter().position(|&h| h == c.name()))
+            .collect();
+        let (Some(columns), Some(&"time")) = (columns, header.first()) else {
+            return Ok(None);
+        };
+
+        let mut time = Vec::new();
+        let mut values = vec![Vec::new(); Channel::ALL.len()];
+        for line in lines {
+            let fields: Vec<f64> = line
+                .split(',')
+                .map(|f| f.trim().parse::<f64>())
+                .collect::<Result<_, _>>()
+                .map_err(|e| invalid(path, e))?;
+            if fields.len() != header.len() {
+                return Err(invalid(path, format!("expected {} columns", header.len())));
+            }
+            time.push(fields[0]);
+            for (column, &index) in values.iter_mut().zip(&columns) {
+                column.push(fields[index]);
+            }
+        }
+        Ok(Some(RunTrace { path: path.to_path_buf(), time, values }))
+    }
+
+    fn binary(path: &Path) -> io::Result<RunTrace> {
+        let bytes = std::fs::read(path)?;
+        let word = |at: usize| -> io::Result<usize> {
+            let b = bytes.get(at..at + 4).ok_or_else(|| invalid(path, "truncated header"))?;
+            Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        };
+        let offset = match word(4)? {
+            1 => 12,
+            2 => 16 + word(12)?,
+            version => return Err(invalid(path, format!("unknown trace version {}", version))),
+        };
+        let n_columns = word(8)?;
+        if n_columns != 1 + Channel::ALL.len() {
+            return Err(invalid(path, format!("{} columns", n_columns)));
+        }
+        let data: Vec<f64> = bytes
+            .get(offset..)
+            .unwrap_or_default()
+            .chunks_exact(8)
+            .map(|b| f64::from_le_bytes(b.try_into().expect("8-byte chunk")))
+            .collect();
+        let rows = data.chunks_exact(n_columns);
+        let time = rows.clone().map(|row| row[0]).collect();
+        let values = (1..n_columns).map(|c| rows.clone().map(|row| row[c]).collect()).collect();
+        Ok(RunTrace { path: path.to_path_buf(), time, values })
+    }
+}
+
+/// Reads every trace and summary under `settings.directory` and combines them.
+#[cfg(feature = "fs")]
+pub fn run_aggregate(settings: &AggregateConfig) -> std::io::Result<Aggregate> {
+    let mut traces = Vec::new();
+    let mut summaries = Vec::new();
+    for path in read::files(std::path::Path::new(&settings.directory))? {
+        if let Some(summary) = read::summary(&path) {
+            summaries.push((path, summary));
+        } else if let Some(trace) = read::trace(&path)? {
+            traces.push(trace);
+        }
+    }
+    Ok(aggregate(settings, &traces, summaries))
+}
+
+#[cfg(feature = "fs")]
+impl Aggregate {
+    /// `time`, then `<channel>_mean` and `<channel>_p<q>` for every channel.
+    pub fn write_traces<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        percentiles: &[f64],
+        metadata: &crate::metadata::RunMetadata,
+    ) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        metadata.write_csv_header(&mut writer)?;
+        let mut names = vec!["time".to_string()];
+        for channel in Channel::ALL {
+            names.push(format!("{}_mean", channel.name()));
+            names.extend(percentiles.iter().map(|p| format!("{}_p{}", channel.name(), p)));
+        }
+        writeln!(writer, "{}", names.join(","))?;
+        for (j, t) in self.time.iter().enumerate() {
+            let mut row = vec![format!("{:.6}", t)];
+            for (mean, bands) in self.mean.iter().zip(&self.bands) {
+                row.push(format!("{:.6e}", mean[j]));
+                row.extend(bands.iter().map(|band| format!("{:.6e}", band[j])));
+            }
+            writeln!(writer, "{}", row.join(","))?;
+        }
+        writer.flush()
+    }
+
+    pub fn write_histogram<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        metadata: &crate::metadata::RunMetadata,
+    ) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        metadata.write_csv_header(&mut writer)?;
+        writeln!(writer, "pulses,runs")?;
+        for (pulses, runs) in &self.histogram {
+            writeln!(writer, "{},{}", pulses, runs)?;
+        }
+        writer.flush()
+    }
+
+    pub fn write_pareto<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        metadata: &crate::metadata::RunMetadata,
+    ) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        metadata.write_csv_header(&mut writer)?;
+        writeln!(writer, "run,confinement_loss,mean_center_impurity,pulses,duty_cycle")?;
+        for &i in &self.pareto {
+            let (run, s) = &self.summaries[i];
+            // Quoted: paths may contain commas
+            writeln!(
+                writer,
+                "\"{}\",{:.6},{:.6e},{},{:.4}",
+                run.display().to_string().replace('"', "'"), s.confinement_loss, s.mean_center_impurity,
+                s.pulses, s.duty_cycle
+            )?;
+        }
+        writer.flush()
+    }
+}
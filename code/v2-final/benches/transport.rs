@@ -0,0 +1,135 @@
+//! Transport kernel benchmarks across grid sizes:
+//!
+//! - `radial_rate`: ∂n_Z/∂t over the grid (the 1D transport step without
+//!   the update and clamps), coefficients precomputed; also on grids past
+//!   `PARALLEL_MIN_POINTS`, split into radial chunks with `parallel`.
+//! - `radial_rate_f32`: the same in single precision (`Precision::F32`).
+//! - `transport_coefficients`: pinch and D on the grid, recomputed every step.
+//! - `turbulence_level`: `calculate_turbulence_level` at every grid point.
+//! - `inner_loop`: 1000 closed-loop `Simulation::step`s.
+//!
+//! `cargo bench --bench transport`; compare runs with `-- --save-baseline`
+//! and `--baseline`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ndarray::Array1;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::poloidal::RadialTerms;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::source::SOURCE_RADIUS;
+use w7x_turbulence_control::state::StellaratorState;
+
+const GRIDS: [usize; 3] = [51, 101, 201];
+const LARGE_GRIDS: [usize; 2] = [4097, 16385];
+
+fn config(nr: usize) -> Config {
+    let mut config = Config::default();
+    config.simulation.nr = nr;
+    // Keep the explicit step stable on the finer grids
+    config.simulation.dt *= (100.0 / (nr - 1) as f64).powi(2).min(1.0);
+    config
+}
+
+fn state(nr: usize) -> StellaratorState {
+    let mut state = StellaratorState::from_config(&config(nr));
+    state.verbose = false;
+    state
+}
+
+fn radial_rate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("radial_rate");
+    for nr in GRIDS.into_iter().chain(LARGE_GRIDS) {
+        let state = state(nr);
+        let (velocity, diffusivity) = state.transport_coefficients();
+        let terms = RadialTerms {
+            radius: &state.radius_grid,
+            dr: state.dr,
+            metric: &state.metric,
+            velocity: &velocity,
+            diffusivity: &diffusivity,
+            source: state.impurity_source,
+            source_radius: SOURCE_RADIUS,
+            core: state.core_boundary,
+            edge: state.edge_boundary,
+            convection: state.convection,
+            stencil: state.stencil,
+        };
+        group.bench_with_input(BenchmarkId::from_parameter(nr), &nr, |b, _| {
+            b.iter(|| terms.rates(black_box(state.impurity_density.view())))
+        });
+    }
+    group.finish();
+}
+
+fn radial_rate_f32(c: &mut Criterion) {
+    let mut group = c.benchmark_group("radial_rate_f32");
+    for nr in GRIDS.into_iter().chain(LARGE_GRIDS) {
+        let state = state(nr);
+        let (velocity, diffusivity) = state.transport_coefficients();
+        let single = |x: &Array1<f64>| x.mapv(|value| value as f32);
+        let (velocity, diffusivity) = (single(&velocity), single(&diffusivity));
+        let density = single(&state.impurity_density);
+        let terms = RadialTerms {
+            radius: &state.radius_grid,
+            dr: state.dr,
+            metric: &state.metric,
+            velocity: &velocity,
+            diffusivity: &diffusivity,
+            source: state.impurity_source,
+            source_radius: SOURCE_RADIUS,
+            core: state.core_boundary,
+            edge: state.edge_boundary,
+            convection: state.convection,
+            stencil: state.stencil,
+        };
+        group.bench_with_input(BenchmarkId::from_parameter(nr), &nr, |b, _| {
+            b.iter(|| terms.rates(black_box(density.view())))
+        });
+    }
+    group.finish();
+}
+
+fn transport_coefficients(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transport_coefficients");
+    for nr in GRIDS {
+        let state = state(nr);
+        group.bench_with_input(BenchmarkId::from_parameter(nr), &nr, |b, _| {
+            b.iter(|| black_box(&state).transport_coefficients())
+        });
+    }
+    group.finish();
+}
+
+fn turbulence_level(c: &mut Criterion) {
+    let mut group = c.benchmark_group("turbulence_level");
+    for nr in GRIDS {
+        let state = state(nr);
+        group.bench_with_input(BenchmarkId::from_parameter(nr), &nr, |b, &nr| {
+            b.iter(|| (0..nr).map(|i| black_box(&state).calculate_turbulence_level(i)).sum::<f64>())
+        });
+    }
+    group.finish();
+}
+
+fn inner_loop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("inner_loop_1000_steps");
+    group.sample_size(10);
+    for nr in GRIDS {
+        let config = config(nr);
+        group.bench_with_input(BenchmarkId::from_parameter(nr), &nr, |b, _| {
+            b.iter(|| {
+                let mut sim = Simulation::from_config(&config);
+                sim.state.verbose = false;
+                sim.state.history.recording = false;
+                for _ in 0..1000 {
+                    sim.step();
+                }
+                sim.state.impurity_density[0]
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, radial_rate, radial_rate_f32, transport_coefficients, turbulence_level, inner_loop);
+criterion_main!(benches);
@@ -0,0 +1,205 @@
+//! # Analytic Benchmarks
+//!
+//! Problems with known solutions for the radial transport step (the
+//! discretization `RadialTerms` shares between the 1D and 2D solvers), in
+//! the cylindrical metric with
+//!
+//! ```text
+//! D(r) = D0 (1 + r²),   v(r) = −v0 r
+//! ```
+//!
+//! zero gradient on axis and n(1) = n0:
+//!
+//! - `manufactured`: n(r, t) = n0 [1 + cos(πr/2) g(t)], g = 1 + ½ sin(2πt / period),
+//!   imposed by adding the source S = ∂n/∂t + ∇·Γ it implies.
+//! - `zero_flux`: no source, relaxed from n = n0 to the steady state with
+//!   Γ = 0 everywhere, n = n0 exp(∫₁^r v/D dr) = n0 (2 / (1 + r²))^(v0 / 2D0).
+//!
+//! Runs use dt = courant · dr² / D_max, so the O(dt) time error shrinks
+//! like dr² and the observed order under grid refinement is the spatial
+//! one. `cargo test` checks errors and orders (`tests/benchmarks.rs`).
+//!
+//! `run_with` / `study_with` take the `Convection` scheme of the pinch
+//! term (`run` / `study`: central). Pe = v0 dr / D0 = 4 dr, so every
+//! grid resolves the pinch and the schemes differ only in accuracy:
+//! upwind is first order, van Leer and Koren second order. With
+//! `Stencil::Fourth` (courant below 18/49) `zero_flux` converges at
+//! fourth order and `manufactured` at second, set by the O(dt) time error.
+//!
+//! `zero_flux` converges at second order. `manufactured` converges at
+//! first order only: fluxes are taken at grid points but weighted with V'
+//! at the faces, and the axis cell (r ≤ 0.01, i.e. from nr = 101) uses the
+//! planar divergence, which shows in the max norm.
+
+use crate::boundary::BoundaryCondition;
+use crate::convection::Convection;
+use crate::geometry::Metric;
+use crate::poloidal::RadialTerms;
+use crate::stencil::Stencil;
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+const DENSITY: f64 = 1e18;  // m⁻³, n0
+const DIFFUSIVITY: f64 = 0.5; // m²/s, D0
+const PINCH: f64 = 2.0;     // m/s, v0 (inward); n(0) / n(1) = 4 at Γ = 0
+const PERIOD: f64 = 0.5;    // s, of the manufactured solution
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Benchmark {
+    Manufactured,
+    ZeroFlux,
+}
+
+impl Benchmark {
+    pub const ALL: [Benchmark; 2] = [Benchmark::Manufactured, Benchmark::ZeroFlux];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Benchmark::Manufactured => "manufactured",
+            Benchmark::ZeroFlux => "zero_flux",
+        }
+    }
+
+    /// Time the error is measured at (s).
+    pub fn duration(&self) -> f64 {
+        match self {
+            Benchmark::Manufactured => PERIOD,
+            Benchmark::ZeroFlux => 8.0, // ~15 relaxation times
+        }
+    }
+
+    /// Exact n at (r, t).
+    pub fn exact(&self, r: f64, t: f64) -> f64 {
+        match self {
+            Benchmark::Manufactured => manufactured(r, t).0,
+            Benchmark::ZeroFlux => DENSITY * (2.0 / (1.0 + r * r)).powf(PINCH / (2.0 * DIFFUSIVITY)),
+        }
+    }
+
+    fn initial(&self, r: f64) -> f64 {
+        match self {
+            Benchmark::Manufactured => self.exact(r, 0.0),
+            Benchmark::ZeroFlux => DENSITY,
+        }
+    }
+
+    fn source(&self, r: f64, t: f64) -> f64 {
+        match self {
+            Benchmark::Manufactured => manufactured(r, t).1,
+            Benchmark::ZeroFlux => 0.0,
+        }
+    }
+
+    /// Runs on `nr` points and compares with the exact solution.
+    pub fn run(&self, nr: usize, courant: f64) -> BenchmarkError {
+        self.run_with(nr, courant, Convection::Central, Stencil::Second)
+    }
+
+    /// `run` with the given convection scheme and stencil.
+    pub fn run_with(&self, nr: usize, courant: f64, convection: Convection, stencil: Stencil) -> BenchmarkError {
+        let nr = nr.max(stencil.min_points());
+        let dr = 1.0 / (nr - 1) as f64;
+        let radius = Array1::linspace(0.0, 1.0, nr);
+        let metric = Metric::cylindrical(&radius, dr);
+        let interior = |i: usize| i > 0 && i < nr - 1;
+        let velocity: Array1<f64> =
+            (0..nr).map(|i| if interior(i) { -PINCH * radius[i] } else { 0.0 }).collect();
+        let diffusivity: Array1<f64> = (0..nr)
+            .map(|i| if interior(i) { DIFFUSIVITY * (1.0 + radius[i] * radius[i]) } else { 0.0 })
+            .collect();
+        let terms = RadialTerms {
+            radius: &radius,
+            dr,
+            metric: &metric,
+            velocity: &velocity,
+            diffusivity: &diffusivity,
+            source: 0.0,
+            source_radius: 1.0,
+            core: BoundaryCondition::Neumann { gradient: 0.0 },
+            edge: BoundaryCondition::Dirichlet { value: DENSITY },
+            convection,
+            stencil,
+        };
+
+        let duration = self.duration();
+        let steps = (duration / (courant * dr * dr / (2.0 * DIFFUSIVITY))).ceil().max(1.0) as usize;
+        let dt = duration / steps as f64;
+        let mut n: Array1<f64> = radius.mapv(|r| self.initial(r));
+        for step in 0..steps {
+            let t = step as f64 * dt;
+            let mut next = n.clone();
+            for i in 1..nr - 1 {
+                next[i] = n[i] + (terms.rate(n.view(), i) + self.source(radius[i], t)) * dt;
+            }
+            stencil.close(next.view_mut(), terms.core, terms.edge, dr);
+            n = next;
+        }
+
+        let error: Array1<f64> = (0..nr).map(|i| (n[i] - self.exact(radius[i], duration)) / DENSITY).collect();
+        BenchmarkError {
+            nr,
+            dt,
+            steps,
+            l2: (error.mapv(|e| e * e).sum() / nr as f64).sqrt(),
+            max: error.iter().fold(0.0_f64, |m, e| m.max(e.abs())),
+        }
+    }
+
+    /// Runs on each of `grids` and returns the errors with the observed
+    /// orders (L2, max) between neighbouring grids.
+    pub fn study(&self, grids: &[usize], courant: f64) -> (Vec<BenchmarkError>, Vec<(f64, f64)>) {
+        self.study_with(grids, courant, Convection::Central, Stencil::Second)
+    }
+
+    /// `study` with the given convection scheme and stencil.
+    pub fn study_with(
+        &self,
+        grids: &[usize],
+        courant: f64,
+        convection: Convection,
+        stencil: Stencil,
+    ) -> (Vec<BenchmarkError>, Vec<(f64, f64)>) {
+        let errors: Vec<BenchmarkError> =
+            grids.iter().map(|&nr| self.run_with(nr, courant, convection, stencil)).collect();
+        let orders = errors
+            .windows(2)
+            .map(|pair| {
+                let refinement = ((pair[1].nr - 1) as f64 / (pair[0].nr - 1) as f64).ln();
+                ((pair[0].l2 / pair[1].l2).ln() / refinement, (pair[0].max / pair[1].max).ln() / refinement)
+            })
+            .collect();
+        (errors, orders)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BenchmarkError {
+    pub nr: usize,
+    pub dt: f64,
+    pub steps: usize,
+    pub l2: f64,  // RMS over the grid, relative to n0
+    pub max: f64, // Relative to n0
+}
+
+/// (n, S) of the manufactured solution at (r, t).
+fn manufactured(r: f64, t: f64) -> (f64, f64) {
+    let k = 0.5 * PI;
+    let omega = 2.0 * PI / PERIOD;
+    let (s, c) = (k * r).sin_cos();
+    let g = 1.0 + 0.5 * (omega * t).sin();
+    let dg = 0.5 * omega * (omega * t).cos();
+
+    let n = DENSITY * (1.0 + c * g);
+    let dn = -DENSITY * k * s * g;
+    let d2n = -DENSITY * k * k * c * g;
+    let (d, dd) = (DIFFUSIVITY * (1.0 + r * r), 2.0 * DIFFUSIVITY * r);
+    let (v, dv) = (-PINCH * r, -PINCH);
+
+    let flux = v * n - d * dn;
+    let dflux = dv * n + v * dn - dd * dn - d * d2n;
+    // (1/r) ∂(rΓ)/∂r; Γ/r → Γ'(0) on axis
+    let divergence = if r > 0.0 { dflux + flux / r } else { 2.0 * dflux };
+    (n, DENSITY * c * dg + divergence)
+}
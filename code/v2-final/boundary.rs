@@ -0,0 +1,134 @@
+//! # Plasma Boundary
+//!
+//! n_Z at the grid ends (r = 0 and r = 1) is set from its interior
+//! neighbour by a `BoundaryCondition` after every transport step.
+//!
+//! Impurities leaving through r = 1 are not simply lost: a fraction
+//! `coefficient` is retained by the wall and re-released into the source
+//! region with an e-folding `residence_time`.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BoundaryCondition {
+    /// Fixed n_Z (m⁻³).
+    Dirichlet { value: f64 },
+    /// Fixed dn_Z/dr (m⁻³ per unit normalized radius); 0 = symmetry.
+    Neumann { gradient: f64 },
+    /// dn_Z/dr = -n_Z / decay_length (normalized radius).
+    Robin { decay_length: f64 },
+    /// n_Z = factor · neighbour; the v2 edge used 0.3 (grid dependent).
+    Ratio { factor: f64 },
+}
+
+impl BoundaryCondition {
+    /// Boundary value given its neighbour and `step` = r_boundary − r_neighbour.
+    pub fn value(&self, neighbour: f64, step: f64) -> f64 {
+        match *self {
+            BoundaryCondition::Dirichlet { value } => value,
+            BoundaryCondition::Neumann { gradient } => (neighbour + gradient * step).max(0.0),
+            BoundaryCondition::Robin { decay_length } => {
+                let denominator = decay_length + step;
+                if denominator > 0.0 {
+                    neighbour * decay_length / denominator
+                } else {
+                    0.0
+                }
+            }
+            BoundaryCondition::Ratio { factor } => factor * neighbour,
+        }
+    }
+
+    /// `value` with the gradient taken one-sided through four neighbours
+    /// (nearest first) at fourth order instead of through one.
+    pub fn value_fourth(&self, neighbours: [f64; 4], step: f64) -> f64 {
+        // dn/dr at the boundary = a + b · n_boundary
+        let [n1, n2, n3, n4] = neighbours;
+        let a = (48.0 * n1 - 36.0 * n2 + 16.0 * n3 - 3.0 * n4) / (-12.0 * step);
+        let b = -25.0 / (-12.0 * step);
+        match *self {
+            BoundaryCondition::Dirichlet { value } => value,
+            BoundaryCondition::Neumann { gradient } => ((gradient - a) / b).max(0.0),
+            BoundaryCondition::Robin { decay_length } => {
+                // a + b n = −n / λ
+                let denominator = b * decay_length + 1.0;
+                if decay_length > 0.0 && denominator != 0.0 {
+                    (-a * decay_length / denominator).max(0.0)
+                } else {
+                    0.0
+                }
+            }
+            BoundaryCondition::Ratio { factor } => factor * n1,
+        }
+    }
+
+    /// (α, β) with value = α · neighbour + β, ignoring the clamp at 0.
+    pub fn linear(&self, step: f64) -> (f64, f64) {
+        match *self {
+            BoundaryCondition::Dirichlet { value } => (0.0, value),
+            BoundaryCondition::Neumann { gradient } => (1.0, gradient * step),
+            BoundaryCondition::Robin { decay_length } => {
+                let denominator = decay_length + step;
+                if denominator > 0.0 { (decay_length / denominator, 0.0) } else { (0.0, 0.0) }
+            }
+            BoundaryCondition::Ratio { factor } => (factor, 0.0),
+        }
+    }
+
+    /// Zero gradient on axis (v2).
+    pub fn core_default() -> Self {
+        BoundaryCondition::Neumann { gradient: 0.0 }
+    }
+
+    /// n_Z(1) = 0.3 n_Z(1 − dr) (v2).
+    pub fn edge_default() -> Self {
+        BoundaryCondition::Ratio { factor: 0.3 }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Recycling {
+    pub coefficient: f64,    // R, returned fraction of the outgoing flux
+    pub residence_time: f64, // s, wall e-folding time; 0 = immediate return
+    inventory: f64,          // Retained impurities, ∫ V' n_Z dr units
+}
+
+impl Recycling {
+    pub fn new(coefficient: f64, residence_time: f64) -> Self {
+        Recycling { coefficient, residence_time, inventory: 0.0 }
+    }
+
+    /// No recycling: everything leaving at r = 1 is lost (v2).
+    pub fn none() -> Self {
+        Self::new(0.0, 0.05)
+    }
+
+    /// Impurities currently held by the wall (∫ V' n_Z dr units).
+    pub fn inventory(&self) -> f64 {
+        self.inventory
+    }
+
+    /// Takes up `outflow` (∫ V' n_Z dr units per second) for `dt` and
+    /// returns the re-released source rate (m⁻³/s) over the source region
+    /// of volume `source_volume` (Σ V' dr).
+    pub fn step(&mut self, outflow: f64, dt: f64, source_volume: f64) -> f64 {
+        if self.coefficient <= 0.0 && self.inventory <= 0.0 {
+            return 0.0;
+        }
+        self.inventory += self.coefficient * outflow.max(0.0) * dt;
+        let released = if self.residence_time > 0.0 {
+            self.inventory * (1.0 - (-dt / self.residence_time).exp())
+        } else {
+            self.inventory
+        };
+        self.inventory -= released;
+        released / dt / source_volume
+    }
+}
+
+impl Default for Recycling {
+    fn default() -> Self {
+        Self::none()
+    }
+}
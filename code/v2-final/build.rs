@@ -0,0 +1,44 @@
+//! Records the git commit for `RunMetadata` and generates the C header
+//! `w7x_sim.h` from `ffi.rs` into `OUT_DIR` when the `ffi` feature is on.
+//! The checked-in copy is refreshed by the `ffi` test (`UPDATE_HEADER=1`).
+
+use std::process::Command;
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    if let Some(commit) = git(&["rev-parse", "--short=12", "HEAD"]) {
+        let dirty = git(&["status", "--porcelain", "--untracked-files=no"]).is_some_and(|s| !s.is_empty());
+        println!("cargo:rustc-env=W7X_GIT_COMMIT={}{}", commit, if dirty { "-dirty" } else { "" });
+        if let Some(head) = git(&["rev-parse", "--git-path", "HEAD"]) {
+            println!("cargo:rerun-if-changed={}", head);
+        }
+        if let Some(index) = git(&["rev-parse", "--git-path", "index"]) {
+            println!("cargo:rerun-if-changed={}", index);
+        }
+    }
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=ffi.rs");
+        let config = cbindgen::Config {
+            language: cbindgen::Language::C,
+            include_guard: Some("W7X_SIM_H".to_string()),
+            header: Some("/* Generated by cbindgen from ffi.rs (UPDATE_HEADER=1 cargo test --features ffi --test ffi); do not edit. */".to_string()),
+            cpp_compat: true,
+            usize_is_size_t: true,
+            documentation_style: cbindgen::DocumentationStyle::C99,
+            ..Default::default()
+        };
+        let out_dir = std::env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src("ffi.rs")
+            .generate()
+            .expect("cbindgen could not parse ffi.rs")
+            .write_to_file(std::path::Path::new(&out_dir).join("w7x_sim.h"));
+    }
+}
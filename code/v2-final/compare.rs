@@ -0,0 +1,176 @@
+//! # Counterfactual Comparison
+//!
+//! Runs the configured scenario three ways — without control, with the
+//! configured adaptive controller, and with the turbulence enhancement
+//! held on for the whole discharge — and reports the figures of merit side
+//! by side, plus n_Z(0)(t) and W(t) of all three for plotting.
+//!
+//! "Always on" is one square pulse at `plasma.pulse_amplitude` in the
+//! default window from the first diagnostic sample to `t_max`; the
+//! waveform, shape, and cooldown of the pulsed runs do not apply, and the
+//! `[controller]` settings (escalation, budget) only shape the adaptive
+//! run. All three runs use the same diagnostics seed.
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::controller::{ControlAction, FixedController};
+use crate::pulse::PulseShape;
+use crate::scan::run_quiet_with;
+use crate::simulation::Simulation;
+use crate::summary::RunSummary;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompareConfig {
+    pub trace_interval: f64, // s between points of the traces
+    pub output: String,      // Summary table (CSV)
+    pub traces: String,      // n_Z(0) and W of every strategy over time (CSV)
+}
+
+impl Default for CompareConfig {
+    fn default() -> Self {
+        CompareConfig {
+            trace_interval: 0.01,
+            output: "comparison.csv".to_string(),
+            traces: "comparison_traces.csv".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    NoControl,
+    Adaptive,
+    AlwaysOn,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 3] = [Strategy::NoControl, Strategy::Adaptive, Strategy::AlwaysOn];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::NoControl => "no_control",
+            Strategy::Adaptive => "adaptive",
+            Strategy::AlwaysOn => "always_on",
+        }
+    }
+
+    /// The simulation of `base` under this strategy; `Adaptive` loads a
+    /// `[controller.script]` or `[controller.plugin]` controller.
+    pub fn simulation(&self, base: &Config) -> Result<Simulation> {
+        match self {
+            Strategy::NoControl => {
+                let mut sim = Simulation::from_config(base);
+                sim.controller = Box::new(FixedController(ControlAction::Hold));
+                Ok(sim)
+            }
+            Strategy::Adaptive => Simulation::try_from_config(base),
+            Strategy::AlwaysOn => {
+                let mut config = base.clone();
+                config.plasma.pulse_duration = config.simulation.t_max.max(config.simulation.dt);
+                config.plasma.pulse_shape = PulseShape::Square;
+                config.plasma.pulse_waveform.clear();
+                let mut sim = Simulation::from_config(&config);
+                sim.controller = Box::new(FixedController(ControlAction::TriggerPulse));
+                Ok(sim)
+            }
+        }
+    }
+}
+
+pub struct StrategyRun {
+    pub strategy: Strategy,
+    pub summary: RunSummary,
+    pub center_impurity: Vec<f64>, // m⁻³, every `trace_interval`
+    pub stored_energy: Vec<f64>,   // MJ
+}
+
+pub struct Comparison {
+    pub time: Vec<f64>,
+    pub runs: Vec<StrategyRun>, // In `Strategy::ALL` order
+}
+
+pub fn run_comparison(base: &Config) -> Result<Comparison> {
+    base.validate()?;
+    let interval = base.compare.trace_interval;
+    let run = |strategy: &Strategy| {
+        let mut center_impurity = Vec::new();
+        let mut stored_energy = Vec::new();
+        let mut next_time = 0.0;
+        let summary = run_quiet_with(strategy.simulation(base)?, base, |state| {
+            if state.time >= next_time {
+                center_impurity.push(state.impurity_density[0]);
+                stored_energy.push(state.confinement.stored_energy());
+                next_time += interval;
+            }
+        })?;
+        Ok(StrategyRun { strategy: *strategy, summary, center_impurity, stored_energy })
+    };
+    #[cfg(feature = "parallel")]
+    let runs: Vec<_> = Strategy::ALL.par_iter().map(run).collect::<Result<_>>()?;
+    #[cfg(not(feature = "parallel"))]
+    let runs: Vec<_> = Strategy::ALL.iter().map(run).collect::<Result<_>>()?;
+
+    let n_points = runs.iter().map(|r| r.center_impurity.len()).min().unwrap_or(0);
+    Ok(Comparison {
+        time: (0..n_points).map(|j| j as f64 * interval).collect(),
+        runs,
+    })
+}
+
+#[cfg(feature = "fs")]
+impl Comparison {
+    pub fn write_table<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        metadata: &crate::metadata::RunMetadata,
+    ) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        metadata.write_csv_header(&mut writer)?;
+        writeln!(writer, "strategy,final_center_impurity,mean_center_impurity,peak_center_impurity,time_above_critical,pulses,duty_cycle,ecrh_energy,mean_stored_energy,confinement_loss")?;
+        for run in &self.runs {
+            let s = &run.summary;
+            writeln!(
+                writer,
+                "{},{:.6e},{:.6e},{:.6e},{:.4},{},{:.4},{:.4},{:.6},{:.4}",
+                run.strategy.name(), s.final_center_impurity, s.mean_center_impurity,
+                s.peak_center_impurity, s.time_above_critical, s.pulses, s.duty_cycle,
+                s.ecrh_energy, s.mean_stored_energy, s.confinement_loss
+            )?;
+        }
+        writer.flush()
+    }
+
+    /// Columns `<strategy>_center_impurity` and `<strategy>_stored_energy` per strategy.
+    pub fn write_traces<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        metadata: &crate::metadata::RunMetadata,
+    ) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        metadata.write_csv_header(&mut writer)?;
+        let names: Vec<String> = self
+            .runs
+            .iter()
+            .map(|r| format!("{0}_center_impurity,{0}_stored_energy", r.strategy.name()))
+            .collect();
+        writeln!(writer, "time,{}", names.join(","))?;
+        for (j, t) in self.time.iter().enumerate() {
+            let row: Vec<String> = self
+                .runs
+                .iter()
+                .map(|r| format!("{:.6e},{:.6}", r.center_impurity[j], r.stored_energy[j]))
+                .collect();
+            writeln!(writer, "{:.6},{}", t, row.join(","))?;
+        }
+        writer.flush()
+    }
+}
@@ -0,0 +1,705 @@
+//! # Run Configuration
+//!
+//! TOML file passed with `--config`. Every section is optional and
+//! falls back to the v2 defaults.
+
+use crate::aggregate::AggregateConfig;
+use crate::boundary::BoundaryCondition;
+use crate::confinement::EnergyConfinement;
+use crate::controller::{ControllerConfig, MAX_AMPLITUDE, MIN_AMPLITUDE};
+use crate::converge::ConvergeConfig;
+use crate::daq::DaqConfig;
+use crate::detection::PipelineConfig;
+use crate::diagnostics::ChannelSpec;
+use crate::electric_field::ElectricField;
+use crate::elm::Elms;
+use crate::error::SimError;
+use crate::compare::CompareConfig;
+use crate::precision::{Precision, PrecisionCheckConfig};
+use crate::poloidal::Backend;
+use crate::convection::Convection;
+use crate::splitting::Splitting;
+use crate::stencil::Stencil;
+use crate::ensemble::EnsembleConfig;
+use crate::evolve::EvolveConfig;
+use crate::fit::FitConfig;
+use crate::fuzzy::Input;
+use crate::geometry::{Equilibrium, FluxSurfaces};
+use crate::history::Cadence;
+use crate::lockstep::LockstepConfig;
+use crate::main_ions::MainIons;
+use crate::optimize::OptimizeConfig;
+use crate::output::TraceFormat;
+use crate::pulse::{PulseShape, PulseWindow};
+use crate::ramp::Ramp;
+use crate::neoclassical::{ChargeScaling, Neoclassical};
+use crate::regularization::Regularization;
+use crate::sawtooth::{Sawtooth, SawtoothTrigger};
+use crate::profiles::ProfileConfig;
+use crate::reference::ReferenceConfig;
+use crate::scan::ScanConfig;
+use crate::scenario::Scenario;
+use crate::steady::SteadyStateConfig;
+use crate::termination::Termination;
+use crate::sensitivity::SensitivityConfig;
+use crate::sol::Sol;
+use crate::source::SourceModel;
+use crate::turbulence::{Turbulence, TurbulenceDynamics};
+use crate::units;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Master seed of every random stream (see `rng`); unset keeps the
+    /// per-section seeds.
+    pub seed: Option<u64>,
+    pub simulation: SimulationConfig,
+    pub plasma: PlasmaConfig,
+    pub profiles: ProfileConfig,
+    pub turbulence: Turbulence,
+    pub turbulence_dynamics: TurbulenceDynamics,
+    pub neoclassical: Neoclassical,
+    pub electric_field: ElectricField,
+    pub actuator: ActuatorConfig,
+    pub pellet: PelletConfig,
+    pub ecrh: EcrhConfig,
+    pub confinement: EnergyConfinement,
+    pub sawtooth: Sawtooth,
+    pub elms: Elms,
+    pub termination: Termination,
+    pub ramp: Ramp,
+    pub main_ions: MainIons,
+    pub sol: Sol,
+    pub diagnostics: DiagnosticsConfig,
+    pub daq: DaqConfig,
+    pub detection: PipelineConfig,
+    pub controller: ControllerConfig,
+    pub output: OutputConfig,
+    pub logging: LoggingConfig,
+    pub numerics: NumericsConfig,
+    pub poloidal: PoloidalConfig,
+    pub boundary: BoundaryConfig,
+    pub equilibrium: Equilibrium,
+    /// Metric read from `equilibrium` by `Config::load`.
+    #[serde(skip)]
+    pub flux_surfaces: Option<FluxSurfaces>,
+    pub serve: ServeConfig,
+    pub scan: ScanConfig,
+    pub ensemble: EnsembleConfig,
+    pub sensitivity: SensitivityConfig,
+    pub optimize: OptimizeConfig,
+    pub evolve: EvolveConfig,
+    pub scenario: Scenario,
+    pub steady_state: SteadyStateConfig,
+    pub converge: ConvergeConfig,
+    pub compare: CompareConfig,
+    pub lockstep: LockstepConfig,
+    pub precision_check: PrecisionCheckConfig,
+    pub aggregate: AggregateConfig,
+    pub reference: ReferenceConfig,
+    pub fit: FitConfig,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationConfig {
+    pub nr: usize,   // Radial grid points
+    pub dt: f64,     // s
+    pub t_max: f64,  // s
+    pub geometry: TransportGeometry,
+}
+
+/// Dimensionality of the impurity transport solver.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum TransportGeometry {
+    #[default]
+    #[serde(rename = "1d")]
+    Radial,
+    /// Experimental (r, θ) solver, see `[poloidal]`.
+    #[serde(rename = "2d")]
+    Poloidal,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            nr: 101,
+            dt: 0.00002,
+            t_max: 10.0,
+            geometry: TransportGeometry::Radial,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlasmaConfig {
+    #[serde(deserialize_with = "units::as_f64::diffusivity")]
+    pub d_neo: f64,            // m²/s
+    #[serde(deserialize_with = "units::as_f64::diffusivity")]
+    pub d_turb_base: f64,      // m²/s
+    #[serde(deserialize_with = "units::as_f64::velocity")]
+    pub v_neo: f64,            // m/s, negative = inward pinch
+    pub pulse_duration: f64,   // s
+    pub cooldown: f64,         // s after a pulse before the next may start
+    pub pulse_amplitude: f64,  // D_turb enhancement factor in the pulse region
+    pub pulse_waveform: Vec<f64>,  // Relative amplitude per equal time segment; empty = flat
+    pub pulse_shape: PulseShape,   // Time course of the enhancement within a pulse
+    pub pulse_windows: Vec<PulseWindow>,  // Selectable pulse regions; the first is the default
+    pub impurity_source: f64,  // m⁻³/s, wall source for r > 0.85
+    pub source: SourceModel,   // Constant (impurity_source) or sputtering
+    #[serde(deserialize_with = "units::as_f64::diffusivity")]
+    pub chi_e: f64,            // m²/s, electron heat diffusivity
+    pub temperature_screening: f64,  // H in v ∝ ∇n/n − H ∇T/T, < 1; 0 = constant v_neo
+    pub impurity_charge: f64,  // Z of the impurity species
+    pub charge_scaling: ChargeScaling,
+}
+
+impl Default for PlasmaConfig {
+    fn default() -> Self {
+        PlasmaConfig {
+            d_neo: 0.02,
+            d_turb_base: 1.5,
+            v_neo: -0.5,
+            pulse_duration: 0.2,
+            cooldown: 0.5,
+            pulse_amplitude: 5.0,
+            pulse_waveform: Vec::new(),
+            pulse_shape: PulseShape::Square,
+            pulse_windows: vec![PulseWindow::default()],
+            impurity_source: 2.5e17,
+            source: SourceModel::Constant,
+            chi_e: 1.0,
+            temperature_screening: 0.0,
+            impurity_charge: 26.0,
+            charge_scaling: ChargeScaling::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActuatorConfig {
+    pub latency: f64,    // s
+    pub rise_time: f64,  // s
+    pub fall_time: f64,  // s
+}
+
+impl Default for ActuatorConfig {
+    /// 10ms latency, ECRH-like ramps
+    fn default() -> Self {
+        ActuatorConfig {
+            latency: 0.010,
+            rise_time: 0.020,
+            fall_time: 0.050,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PoloidalConfig {
+    pub ntheta: usize,     // Poloidal grid points
+    #[serde(deserialize_with = "units::as_f64::diffusivity")]
+    pub diffusivity: f64,  // m²/s, poloidal mixing
+    pub asymmetry: f64,    // Outboard excess in n ∝ exp(asymmetry · r · cos θ)
+    pub backend: Backend,  // cpu, or gpu (`gpu` feature)
+}
+
+impl Default for PoloidalConfig {
+    fn default() -> Self {
+        PoloidalConfig {
+            ntheta: 16,
+            diffusivity: 1.0,
+            asymmetry: 0.5,
+            backend: Backend::Cpu,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoundaryConfig {
+    pub core: BoundaryCondition,  // n_Z at r = 0
+    pub edge: BoundaryCondition,  // n_Z at r = 1
+    pub recycling: f64,       // Fraction of the r = 1 outflux the wall returns
+    pub residence_time: f64,  // s, wall retention before re-release
+}
+
+impl Default for BoundaryConfig {
+    fn default() -> Self {
+        BoundaryConfig {
+            core: BoundaryCondition::core_default(),
+            edge: BoundaryCondition::edge_default(),
+            recycling: 0.0,
+            residence_time: 0.05,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PelletConfig {
+    pub density_increment: f64,    // m⁻³, volume-averaged n_e per pellet
+    pub impurity_fraction: f64,    // Impurity atoms deposited per electron
+    pub ablation_coefficient: f64, // Fraction ablated per unit radius at 1 keV, 1e20 m⁻³
+    pub relaxation_time: f64,      // s, n_e decay back to the pre-pellet profile
+    pub min_interval: f64,         // s between pellets
+    pub schedule: Vec<f64>,        // s, injection times; [] = on command only
+}
+
+impl Default for PelletConfig {
+    fn default() -> Self {
+        PelletConfig {
+            density_increment: 5e18,
+            impurity_fraction: 0.0,
+            ablation_coefficient: 1.0,
+            relaxation_time: 0.05,
+            min_interval: 0.1,
+            schedule: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EcrhConfig {
+    pub max_power: f64,         // MW, 0 = no ECRH
+    pub ramp_rate: f64,         // MW/s
+    pub deposition_radius: f64, // Normalized radius of the deposition peak
+    pub deposition_width: f64,  // Gaussian 1/e half-width
+    pub energy_budget: f64,     // MJ per discharge
+    pub plasma_volume: f64,     // m³
+}
+
+impl Default for EcrhConfig {
+    /// Off: pulses only raise D_turb, as in v2
+    fn default() -> Self {
+        EcrhConfig {
+            max_power: 0.0,
+            ramp_rate: 20.0,
+            deposition_radius: 0.8,
+            deposition_width: 0.1,
+            energy_budget: 50.0,
+            plasma_volume: 30.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiagnosticsConfig {
+    pub cadence: Cadence,      // Recording cadence of history and trace files
+    pub sample_interval: f64,  // s, synthetic diagnostic sampling
+    pub seed: u64,
+    pub sxr: ChannelSpec,
+    pub edge: ChannelSpec,
+    pub turbulence: ChannelSpec,
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        DiagnosticsConfig {
+            cadence: Cadence::EveryStep,
+            sample_interval: 1e-4,
+            seed: 42,
+            sxr: ChannelSpec {
+                noise_fraction: 0.02,
+                saturation: 5e19,
+            },
+            edge: ChannelSpec {
+                noise_fraction: 0.05,
+                saturation: 5e19,
+            },
+            turbulence: ChannelSpec {
+                noise_fraction: 0.10,
+                saturation: 20.0,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NumericsConfig {
+    pub regularization: Regularization,
+    pub convection: Convection,       // Face density of the pinch flux
+    pub stencil: Stencil,             // Order of the radial transport step
+    pub max_substeps: usize,          // Transport sub-steps per dt past the explicit limit; 1 = off (v2)
+    pub precision: Precision,         // Float type of the 1D transport rates; f64 (v2) or f32
+    pub splitting: Splitting,         // Radiation vs χ_e step of T_e; lie (v2) or strang
+    pub finite_check_interval: usize, // Steps between NaN/∞ checks of the profiles; 0 = off
+    pub crash_dump: String,           // Written (JSON) before aborting on a non-finite value
+}
+
+impl Default for NumericsConfig {
+    fn default() -> Self {
+        NumericsConfig {
+            regularization: Regularization::default(),
+            convection: Convection::Central,
+            stencil: Stencil::Second,
+            max_substeps: 1,
+            precision: Precision::F64,
+            splitting: Splitting::Lie,
+            finite_check_interval: 100,
+            crash_dump: "w7x_crash.json".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputConfig {
+    pub trace: String,              // Streamed time-trace file
+    pub trace_format: TraceFormat,
+    pub events: String,             // JSON-lines event log; "" = none
+    pub summary: String,            // End-of-run summary (JSON); "" = none
+    #[serde(deserialize_with = "units::as_f64::density")]
+    pub critical_density: f64,      // m⁻³, n_Z(0) counted as dangerous
+    pub keep_history: bool,         // false: don't hold traces in memory
+    pub profile_cadence: f64,  // s between radial profile snapshots
+    pub hdf5: Option<String>,    // Requires the `hdf5` feature
+    pub netcdf: Option<String>,  // Requires the `netcdf` feature
+    pub parquet: Option<String>, // Profile snapshots, long format; requires the `parquet` feature
+    pub plot: Option<String>,       // PNG of the traces and n_Z(r, t); requires the `plot` feature
+    pub animation: Option<String>,  // GIF of n_Z(r) over the run; requires the `plot` feature
+    pub imas: Option<String>,       // IMAS core_profiles / core_transport; .h5/.hdf5 requires the `hdf5` feature, else JSON
+    pub mdsplus: Option<String>,    // MDSplus-style signal tree under W7-X diagnostic node names (JSON)
+    pub operator_log: Option<String>, // Plain-language transcript for the session leader
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        OutputConfig {
+            trace: "w7x_simulation.csv".to_string(),
+            trace_format: TraceFormat::Csv,
+            events: "w7x_events.jsonl".to_string(),
+            summary: "summary.json".to_string(),
+            critical_density: 1e19,
+            keep_history: true,
+            profile_cadence: 0.01,
+            hdf5: None,
+            netcdf: None,
+            parquet: None,
+            plot: None,
+            animation: None,
+            imas: None,
+            mdsplus: None,
+            operator_log: None,
+        }
+    }
+}
+
+/// Console messages go through `tracing`: status lines (target `status`)
+/// and plant events at INFO, numerical warnings at WARN, alarm
+/// transitions at DEBUG, every controller decision at TRACE.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub filter: String,          // tracing directives, e.g. "warn" or "info,status=off"; RUST_LOG overrides
+    pub status_interval: usize,  // Steps between status lines
+    pub file: Option<String>,    // Write log lines here instead of stdout
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig {
+            filter: "info".to_string(),
+            status_interval: 10000,
+            file: None,
+        }
+    }
+}
+
+/// ZeroMQ endpoints for `serve` mode, the `--serve-dashboard` address, and
+/// real-time pacing of single runs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServeConfig {
+    pub publish: String,  // PUB socket: measurements, traces, profiles
+    pub command: String,  // REP socket: JSON control commands
+    pub dashboard: String,  // host:port of the web viewer and its WebSocket
+    pub speed: f64,         // Simulated s per wall-clock s; 0 = unpaced
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        ServeConfig {
+            publish: "tcp://*:5556".to_string(),
+            command: "tcp://*:5557".to_string(),
+            dashboard: "127.0.0.1:8765".to_string(),
+            speed: 0.0,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the TOML file and the data files it points to. Call
+    /// `validate` once presets and command-line overrides are applied.
+    #[cfg(feature = "fs")]
+    pub fn load<P: AsRef<Path>>(path: P) -> crate::error::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let mut config: Config = toml::from_str(&text)?;
+        config.flux_surfaces = config.equilibrium.load()?;
+        config.turbulence.load()?;
+        config.neoclassical.load()?;
+        config.profiles.load()?;
+        config.scenario.load()?;
+        Ok(config)
+    }
+
+    /// Rejects parameters the solver cannot run with or that are unphysical.
+    pub fn validate(&self) -> crate::error::Result<()> {
+        let positive = |name: &'static str, value: f64| {
+            if value > 0.0 && value.is_finite() {
+                Ok(())
+            } else {
+                Err(SimError::invalid(name, format!("{} must be positive and finite", value)))
+            }
+        };
+        let non_negative = |name: &'static str, value: f64| {
+            if value >= 0.0 && value.is_finite() {
+                Ok(())
+            } else {
+                Err(SimError::invalid(name, format!("{} must be ≥ 0 and finite", value)))
+            }
+        };
+
+        let simulation = &self.simulation;
+        let min_points = self.numerics.stencil.min_points();
+        if simulation.nr < min_points {
+            return Err(SimError::invalid(
+                "simulation.nr",
+                format!(
+                    "{} grid points, the {} stencil needs at least {}",
+                    simulation.nr,
+                    self.numerics.stencil.name(),
+                    min_points
+                ),
+            ));
+        }
+        if simulation.geometry == TransportGeometry::Poloidal && self.poloidal.backend == Backend::Gpu {
+            if !cfg!(feature = "gpu") {
+                return Err(SimError::invalid("poloidal.backend", "gpu needs a build with `--features gpu`"));
+            }
+            if self.numerics.stencil != Stencil::Second {
+                return Err(SimError::invalid(
+                    "poloidal.backend",
+                    format!("the gpu kernel has no {} stencil", self.numerics.stencil.name()),
+                ));
+            }
+        }
+        if self.numerics.precision == Precision::F32 && simulation.geometry == TransportGeometry::Poloidal {
+            return Err(SimError::invalid("numerics.precision", "f32 applies to the 1D transport only"));
+        }
+        let controller = &self.controller;
+        let custom = [controller.script.is_some(), controller.plugin.is_some(), controller.fuzzy.is_some()];
+        if custom.iter().filter(|&&set| set).count() > 1 {
+            return Err(SimError::invalid("controller", "set at most one of script, plugin, and fuzzy"));
+        }
+        if controller.script.is_some() && self.detection.model.is_some() {
+            return Err(SimError::invalid("controller.script", "a script controller does not use [detection.model]"));
+        }
+        if controller.plugin.is_some() && self.detection.model.is_some() {
+            return Err(SimError::invalid("controller.plugin", "a plugin controller does not use [detection.model]"));
+        }
+        positive("simulation.dt", simulation.dt)?;
+        non_negative("simulation.t_max", simulation.t_max)?;
+
+        let plasma = &self.plasma;
+        non_negative("plasma.d_neo", plasma.d_neo)?;
+        non_negative("plasma.d_turb_base", plasma.d_turb_base)?;
+        if !plasma.v_neo.is_finite() {
+            return Err(SimError::invalid("plasma.v_neo", format!("{} must be finite", plasma.v_neo)));
+        }
+        positive("plasma.pulse_duration", plasma.pulse_duration)?;
+        non_negative("plasma.cooldown", plasma.cooldown)?;
+        non_negative("plasma.pulse_amplitude", plasma.pulse_amplitude)?;
+        non_negative("plasma.impurity_source", plasma.impurity_source)?;
+        non_negative("plasma.chi_e", plasma.chi_e)?;
+        if !(0.0..1.0).contains(&plasma.temperature_screening) {
+            return Err(SimError::invalid(
+                "plasma.temperature_screening",
+                format!("{} must be in [0, 1)", plasma.temperature_screening),
+            ));
+        }
+        positive("plasma.impurity_charge", plasma.impurity_charge)?;
+        positive("diagnostics.sample_interval", self.diagnostics.sample_interval)?;
+        non_negative("daq.cycle", self.daq.cycle)?;
+        non_negative("daq.latency", self.daq.latency)?;
+        let sawtooth = &self.sawtooth;
+        if sawtooth.trigger != SawtoothTrigger::Off && !(sawtooth.mixing_radius > 0.0 && sawtooth.mixing_radius < 1.0) {
+            return Err(SimError::invalid(
+                "sawtooth.mixing_radius",
+                format!("{} must be in (0, 1)", sawtooth.mixing_radius),
+            ));
+        }
+        if sawtooth.trigger == SawtoothTrigger::Periodic {
+            positive("sawtooth.period", sawtooth.period)?;
+        }
+        non_negative("elms.frequency", self.elms.frequency)?;
+        if self.elms.enabled() {
+            positive("elms.duration", self.elms.duration)?;
+            non_negative("elms.amplitude", self.elms.amplitude)?;
+        }
+        let termination = &self.termination;
+        if termination.enabled {
+            positive("termination.collapse_density", termination.collapse_density)?;
+            positive("termination.cooling_time", termination.cooling_time)?;
+            non_negative("termination.response_time", termination.response_time)?;
+            positive("termination.flush_duration", termination.flush_duration)?;
+            if termination.recovery_density >= termination.collapse_density {
+                return Err(SimError::invalid(
+                    "termination.recovery_density",
+                    format!("{:e} must be below collapse_density", termination.recovery_density),
+                ));
+            }
+        }
+        let ramp = &self.ramp;
+        non_negative("ramp.ramp_up", ramp.ramp_up)?;
+        non_negative("ramp.ramp_down", ramp.ramp_down)?;
+        if ramp.ramp_down > 0.0 && ramp.ramp_down_start < ramp.ramp_up {
+            return Err(SimError::invalid(
+                "ramp.ramp_down_start",
+                format!("{} must not be before the end of the ramp-up at {}", ramp.ramp_down_start, ramp.ramp_up),
+            ));
+        }
+        for (name, fraction) in [
+            ("ramp.heating_fraction", ramp.heating_fraction),
+            ("ramp.density_fraction", ramp.density_fraction),
+            ("ramp.source_fraction", ramp.source_fraction),
+        ] {
+            if !(0.0..=1.0).contains(&fraction) {
+                return Err(SimError::invalid(name, format!("{} must be in [0, 1]", fraction)));
+            }
+        }
+        if ramp.enabled() && ramp.density_fraction <= 0.0 {
+            return Err(SimError::invalid("ramp.density_fraction", "n_e cannot ramp from zero"));
+        }
+        if self.main_ions.enabled {
+            non_negative("main_ions.diffusivity", self.main_ions.diffusivity)?;
+            non_negative("main_ions.turbulent_fraction", self.main_ions.turbulent_fraction)?;
+            non_negative("main_ions.dilution_exponent", self.main_ions.dilution_exponent)?;
+        }
+        let boundary = &self.boundary;
+        if !(0.0..=1.0).contains(&boundary.recycling) {
+            return Err(SimError::invalid(
+                "boundary.recycling",
+                format!("{} must be in [0, 1]", boundary.recycling),
+            ));
+        }
+        non_negative("boundary.residence_time", boundary.residence_time)?;
+        if self.sol.enabled {
+            positive("sol.width", self.sol.width)?;
+            positive("sol.parallel_loss_time", self.sol.parallel_loss_time)?;
+            non_negative("sol.leakage_time", self.sol.leakage_time)?;
+        }
+        if let Some(schedule) = &self.controller.schedule {
+            non_negative("controller.schedule.hysteresis", schedule.hysteresis)?;
+            let invalid = |reason: String| Err(SimError::invalid("controller.schedule", reason));
+            if self.controller.script.is_some() || self.controller.plugin.is_some() {
+                return invalid("script and plugin controllers do not use a schedule".to_string());
+            }
+            let Some((last, rows)) = schedule.regimes.split_last() else {
+                return invalid("no regimes".to_string());
+            };
+            let mut previous = f64::NEG_INFINITY;
+            for row in rows {
+                match row.below {
+                    Some(below) if below > previous && below.is_finite() => previous = below,
+                    Some(below) => return invalid(format!("{}: below = {} is not above the previous row", row.name, below)),
+                    None => return invalid(format!("{}: only the last row may omit `below`", row.name)),
+                }
+            }
+            if last.below.is_some_and(|below| below <= previous || !below.is_finite()) {
+                return invalid(format!("{}: below is not above the previous row", last.name));
+            }
+            for row in &schedule.regimes {
+                if let Some(alarm) = row.thresholds.keys().find(|a| !self.detection.alarms.iter().any(|c| &c.name == *a)) {
+                    return invalid(format!("{}: no alarm {} in [detection]", row.name, alarm));
+                }
+                if row.thresholds.values().any(|t| !t.is_finite()) {
+                    return invalid(format!("{}: thresholds must be finite", row.name));
+                }
+                if row.amplitude.is_some_and(|a| !(MIN_AMPLITUDE..=MAX_AMPLITUDE).contains(&a)) {
+                    return invalid(format!("{}: amplitude outside {}–{}×", row.name, MIN_AMPLITUDE, MAX_AMPLITUDE));
+                }
+                if let Some(window) = row.window.filter(|&w| w >= self.plasma.pulse_windows.len()) {
+                    return invalid(format!("{}: no pulse window {}", row.name, window));
+                }
+            }
+        }
+        if let Some(fuzzy) = &self.controller.fuzzy {
+            positive("controller.fuzzy.rate_window", fuzzy.rate_window)?;
+            non_negative("controller.fuzzy.pulse_above", fuzzy.pulse_above)?;
+            if self.detection.model.is_some() {
+                return Err(SimError::invalid("controller.fuzzy", "the fuzzy controller does not use [detection.model]"));
+            }
+            if self.controller.schedule.is_some() {
+                return Err(SimError::invalid("controller.fuzzy", "the fuzzy controller does not use [controller.schedule]"));
+            }
+            let invalid = |reason: String| Err(SimError::invalid("controller.fuzzy", reason));
+            for input in [Input::CentralSxr, Input::Growth, Input::Turbulence] {
+                if let Some((name, _)) = fuzzy.sets(input).iter().find(|(_, m)| !m.is_valid()) {
+                    return invalid(format!("{}.{}: breakpoints out of order", input.name(), name));
+                }
+            }
+            if let Some((name, _)) = fuzzy.outputs.iter().find(|(_, a)| !(a.is_finite() && **a >= 0.0)) {
+                return invalid(format!("output {} must be ≥ 0 and finite", name));
+            }
+            if fuzzy.rules.is_empty() {
+                return invalid("no rules".to_string());
+            }
+            for (i, rule) in fuzzy.rules.iter().enumerate() {
+                if rule.when.is_empty() {
+                    return invalid(format!("rule {} has no conditions", i + 1));
+                }
+                if let Some((input, set)) = rule.when.iter().find(|(input, set)| !fuzzy.sets(**input).contains_key(*set)) {
+                    return invalid(format!("rule {}: no set {} for {}", i + 1, set, input.name()));
+                }
+                if !fuzzy.outputs.contains_key(&rule.then) {
+                    return invalid(format!("rule {}: no output {}", i + 1, rule.then));
+                }
+                if !(rule.weight >= 0.0 && rule.weight.is_finite()) {
+                    return invalid(format!("rule {}: weight {} must be ≥ 0 and finite", i + 1, rule.weight));
+                }
+            }
+        }
+        non_negative("serve.speed", self.serve.speed)?;
+        let output = &self.output;
+        if !output.keep_history {
+            let traced = [
+                ("hdf5", &output.hdf5),
+                ("netcdf", &output.netcdf),
+                ("imas", &output.imas),
+                ("mdsplus", &output.mdsplus),
+                ("plot", &output.plot),
+            ];
+            if let Some((name, _)) = traced.iter().find(|(_, path)| path.is_some()) {
+                return Err(SimError::invalid(
+                    "output.keep_history",
+                    format!("false leaves no traces for output.{}", name),
+                ));
+            }
+        }
+
+        // Bare numbers in the wrong unit: T_e in eV, n_e in 10¹⁹ m⁻³ or cm⁻³
+        let profiles = &self.profiles;
+        if !(profiles.temperature > 0.0 && profiles.temperature <= 100.0) {
+            return Err(SimError::invalid(
+                "profiles.temperature",
+                format!("{} keV is outside (0, 100]; write \"{} eV\" for eV", profiles.temperature, profiles.temperature),
+            ));
+        }
+        if !(1e15..=1e23).contains(&profiles.density) {
+            return Err(SimError::invalid(
+                "profiles.density",
+                format!("{:e} m⁻³ is outside [1e15, 1e23]; give a unit, e.g. \"8 10^19 m^-3\"", profiles.density),
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+//! # Energy Confinement Proxy
+//!
+//! Stored energy W relaxing toward P τ_E, with τ_E degraded while the
+//! turbulent transport is enhanced:
+//!
+//! ```text
+//! dW/dt = P − W / τ_E,   τ_E = τ_ref · (⟨D_turb⟩ / ⟨D_turb⟩_ref)^(−exponent)
+//! ```
+//!
+//! ⟨·⟩ is the volume average and ⟨D_turb⟩_ref its value at the start of
+//! the discharge; P is `heating_power` plus the ECRH power. W does not
+//! feed back on the impurity transport. It is the price of a pulse: with
+//! it in the cost, pulsing constantly is no longer free.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnergyConfinement {
+    pub reference_time: f64, // τ_ref, s
+    pub exponent: f64,       // 1 = τ_E ∝ 1/⟨D_turb⟩
+    pub heating_power: f64,  // MW, without ECRH
+    reference_turbulence: f64, // m²/s, ⟨D_turb⟩_ref, set by `initialize`
+    stored_energy: f64,        // MJ
+}
+
+impl Default for EnergyConfinement {
+    fn default() -> Self {
+        EnergyConfinement {
+            reference_time: 0.15,
+            exponent: 0.5,
+            heating_power: 5.0,
+            reference_turbulence: 0.0,
+            stored_energy: 0.0,
+        }
+    }
+}
+
+impl EnergyConfinement {
+    /// Starts in steady state, W = P τ_ref, at the current ⟨D_turb⟩.
+    pub fn initialize(&mut self, mean_turbulence: f64) {
+        self.reference_turbulence = mean_turbulence;
+        self.stored_energy = self.reference_energy();
+    }
+
+    /// W (MJ) in steady state at the reference transport without ECRH.
+    pub fn reference_energy(&self) -> f64 {
+        self.heating_power * self.reference_time
+    }
+
+    pub fn stored_energy(&self) -> f64 {
+        self.stored_energy
+    }
+
+    /// τ_E (s) at volume-averaged D_turb `mean_turbulence`.
+    pub fn confinement_time(&self, mean_turbulence: f64) -> f64 {
+        if self.reference_turbulence <= 0.0 || mean_turbulence <= 0.0 {
+            return self.reference_time;
+        }
+        self.reference_time * (mean_turbulence / self.reference_turbulence).powf(-self.exponent)
+    }
+
+    /// Advances W by `dt` with `extra_power` MW (ECRH) on top of the base heating.
+    pub fn step(&mut self, mean_turbulence: f64, extra_power: f64, dt: f64) {
+        let tau = self.confinement_time(mean_turbulence).max(1e-6);
+        let power = self.heating_power + extra_power;
+        // Exact relaxation over the step, stable for any dt / τ_E
+        let target = power * tau;
+        self.stored_energy = target + (self.stored_energy - target) * (-dt / tau).exp();
+    }
+}
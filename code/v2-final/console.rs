@@ -0,0 +1,261 @@
+//! # Interactive Console
+//!
+//! Pauses a single run to inspect it and retune the controller without a
+//! restart. `pause` on stdin, or SIGUSR1 on Unix, holds the run before the
+//! next step; while held, stdin takes further commands:
+//!
+//! ```text
+//! pause                                hold the run (also SIGUSR1)
+//! show                                 time, mode, timers, n_Z, last measurement
+//! params                               current values of the editable parameters
+//! set pulse_duration 0.15              s
+//! set cooldown 0.4                     s
+//! set pulse_amplitude 6                D_turb enhancement factor
+//! set threshold 1.2e18                 alarm `[scan] threshold_alarm`
+//! set threshold central_growth 2e18    a named alarm
+//! resume                               go on (also SIGUSR1 again)
+//! ```
+//!
+//! Edits go through the config and `Config::validate`, so a rejected value
+//! changes nothing, and the outputs written at the end of the run show the
+//! edited config. Changing a threshold rebuilds the controller from
+//! `[detection]` and `[controller]`: detector filters and the actuator
+//! budget start over, and it is refused with a `[detection.model]`, a
+//! `[controller.script]`, or a `[controller.plugin]`, which the rebuild
+//! would replace.
+
+use crate::config::Config;
+use crate::detection::DetectionPipeline;
+use crate::simulation::Simulation;
+use crate::state::ConfinementMode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often a held run checks for SIGUSR1.
+const WAIT: Duration = Duration::from_millis(100);
+const HELP: &str = "commands: pause, show, params, set <name> <value>, resume";
+
+/// A parameter `set` can change during a run.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Setting {
+    PulseDuration,
+    Cooldown,
+    PulseAmplitude,
+    /// Threshold of the named alarm; `None` = `[scan] threshold_alarm`.
+    Threshold(Option<String>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    Pause,
+    Resume,
+    Show,
+    Params,
+    Set(Setting, f64),
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |text: &str| text.parse::<f64>().map_err(|_| format!("{} is not a number", text));
+        match words.as_slice() {
+            ["pause"] => Ok(Command::Pause),
+            ["resume"] => Ok(Command::Resume),
+            ["show"] => Ok(Command::Show),
+            ["params"] => Ok(Command::Params),
+            ["set", "pulse_duration", value] => Ok(Command::Set(Setting::PulseDuration, number(value)?)),
+            ["set", "cooldown", value] => Ok(Command::Set(Setting::Cooldown, number(value)?)),
+            ["set", "pulse_amplitude", value] => Ok(Command::Set(Setting::PulseAmplitude, number(value)?)),
+            ["set", "threshold", value] => Ok(Command::Set(Setting::Threshold(None), number(value)?)),
+            ["set", "threshold", alarm, value] => {
+                Ok(Command::Set(Setting::Threshold(Some(alarm.to_string())), number(value)?))
+            }
+            ["set", ..] => Err("set pulse_duration | cooldown | pulse_amplitude | threshold [alarm] <value>".to_string()),
+            _ => Err(format!("unknown command {:?}; {}", line.trim(), HELP)),
+        }
+    }
+}
+
+/// Applies `value` to `config` and to the running simulation. Returns a
+/// description of the change; on error neither is touched.
+pub fn set(sim: &mut Simulation, config: &mut Config, setting: &Setting, value: f64) -> Result<String, String> {
+    let mut edited = config.clone();
+    let (name, before) = match setting {
+        Setting::PulseDuration => ("pulse_duration".to_string(), std::mem::replace(&mut edited.plasma.pulse_duration, value)),
+        Setting::Cooldown => ("cooldown".to_string(), std::mem::replace(&mut edited.plasma.cooldown, value)),
+        Setting::PulseAmplitude => ("pulse_amplitude".to_string(), std::mem::replace(&mut edited.plasma.pulse_amplitude, value)),
+        Setting::Threshold(alarm) => {
+            if config.detection.model.is_some() {
+                return Err("thresholds belong to the alarm pipeline; a [detection.model] is in use".to_string());
+            }
+            if config.controller.script.is_some() {
+                return Err("thresholds belong to the threshold controller; a [controller.script] is in use".to_string());
+            }
+            if config.controller.plugin.is_some() {
+                return Err("thresholds belong to the threshold controller; a [controller.plugin] is in use".to_string());
+            }
+            let name = alarm.as_ref().unwrap_or(&config.scan.threshold_alarm);
+            let alarm = edited
+                .detection
+                .alarms
+                .iter_mut()
+                .find(|a| &a.name == name)
+                .ok_or_else(|| format!("no alarm named {:?}", name))?;
+            (format!("{} threshold", name), std::mem::replace(&mut alarm.threshold, value))
+        }
+    };
+    edited.validate().map_err(|e| e.to_string())?;
+
+    match setting {
+        Setting::PulseDuration => sim.state.pulse_duration = value,
+        Setting::Cooldown => sim.state.cooldown_duration = value,
+        Setting::PulseAmplitude => sim.state.pulse_amplitude = value,
+        Setting::Threshold(_) => {
+            let detector = Box::new(DetectionPipeline::new(&edited.detection));
+            sim.controller = edited.controller.build(detector, edited.plasma.pulse_duration);
+        }
+    }
+    *config = edited;
+    Ok(format!("t={:.3}s: {} {} → {}", sim.state.time, name, number(before), number(value)))
+}
+
+/// `value` in plain notation, or scientific for densities and the like.
+fn number(value: f64) -> String {
+    if value != 0.0 && !(1e-3..1e4).contains(&value.abs()) {
+        format!("{:e}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// The current state, as `show` prints it.
+pub fn describe_state(sim: &Simulation) -> Vec<String> {
+    let state = &sim.state;
+    let mut lines = vec![format!("t = {:.3} s, mode {:?}", state.time, state.confinement_mode)];
+    match (state.confinement_mode, state.pulse_start_time, state.last_pulse_end_time) {
+        (ConfinementMode::TurbulencePulse, Some(start), _) => {
+            lines.push(format!("pulse {:.3} / {:.3} s", state.time - start, state.pulse_duration));
+        }
+        (ConfinementMode::Normal, _, Some(end)) if state.time - end < state.cooldown_duration => {
+            lines.push(format!("cooldown {:.3} s left", state.cooldown_duration - (state.time - end)));
+        }
+        _ => lines.push("ready to pulse".to_string()),
+    }
+    let (zeff, _) = state.charge_balance(0);
+    lines.push(format!(
+        "n_Z(0) = {:.2e} m⁻³, n_Z(edge) = {:.2e} m⁻³, Z_eff(0) = {:.2}",
+        state.impurity_density[0],
+        state.impurity_density[state.nr - 1],
+        zeff
+    ));
+    if let Some(m) = sim.last_measurement() {
+        lines.push(format!(
+            "measured: SXR={:.2e} | edge={:.2e} | D_turb={:.2}",
+            m.central_sxr, m.edge_density, m.turbulence
+        ));
+    }
+    lines
+}
+
+/// The editable parameters, as `params` prints them.
+pub fn describe_parameters(sim: &Simulation, config: &Config) -> Vec<String> {
+    let state = &sim.state;
+    let mut lines = vec![
+        format!("pulse_duration = {} s", state.pulse_duration),
+        format!("cooldown = {} s", state.cooldown_duration),
+        format!("pulse_amplitude = {}", state.pulse_amplitude),
+    ];
+    if config.detection.model.is_none() {
+        lines.extend(config.detection.alarms.iter().map(|a| format!("threshold {} = {}", a.name, number(a.threshold))));
+    }
+    lines
+}
+
+/// Commands from stdin (read on a background thread) and the SIGUSR1 flag.
+pub struct Console {
+    lines: Receiver<String>,
+    signal: Arc<AtomicBool>,
+    paused: bool,
+}
+
+impl Console {
+    /// Starts reading stdin and, on Unix, catching SIGUSR1.
+    pub fn start() -> std::io::Result<Self> {
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let signal = Arc::new(AtomicBool::new(false));
+        #[cfg(unix)]
+        signal_hook::flag::register(signal_hook::consts::SIGUSR1, Arc::clone(&signal))?;
+        Ok(Console::new(lines, signal))
+    }
+
+    /// A console fed by `lines`; setting `signal` toggles the pause.
+    pub fn new(lines: Receiver<String>, signal: Arc<AtomicBool>) -> Self {
+        Console { lines, signal, paused: false }
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Handles the commands that arrived since the last step, writing
+    /// replies to `reply`. Blocks while the run is held, and returns
+    /// whether it was. Once stdin has ended, only SIGUSR1 resumes.
+    pub fn poll(&mut self, sim: &mut Simulation, config: &mut Config, mut reply: impl FnMut(&str)) -> bool {
+        while let Ok(line) = self.lines.try_recv() {
+            self.handle(&line, sim, config, &mut reply);
+        }
+        let mut held = false;
+        loop {
+            if self.signal.swap(false, Ordering::Relaxed) {
+                self.toggle(!self.paused, sim, &mut reply);
+            }
+            if !self.paused {
+                return held;
+            }
+            held = true;
+            match self.lines.recv_timeout(WAIT) {
+                Ok(line) => self.handle(&line, sim, config, &mut reply),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => std::thread::sleep(WAIT),
+            }
+        }
+    }
+
+    fn handle(&mut self, line: &str, sim: &mut Simulation, config: &mut Config, reply: &mut impl FnMut(&str)) {
+        if line.trim().is_empty() {
+            return;
+        }
+        match Command::parse(line) {
+            Ok(Command::Pause) => self.toggle(true, sim, reply),
+            Ok(Command::Resume) => self.toggle(false, sim, reply),
+            Ok(Command::Show) => describe_state(sim).iter().for_each(|l| reply(l)),
+            Ok(Command::Params) => describe_parameters(sim, config).iter().for_each(|l| reply(l)),
+            Ok(Command::Set(setting, value)) => match set(sim, config, &setting, value) {
+                Ok(change) => reply(&format!("🎛️ {}", change)),
+                Err(e) => reply(&format!("❌ {}", e)),
+            },
+            Err(e) => reply(&format!("❌ {}", e)),
+        }
+    }
+
+    fn toggle(&mut self, paused: bool, sim: &Simulation, reply: &mut impl FnMut(&str)) {
+        if paused == self.paused {
+            return;
+        }
+        self.paused = paused;
+        if paused {
+            reply(&format!("⏸️ Paused at t={:.3}s; {}", sim.state.time, HELP));
+        } else {
+            reply(&format!("▶️ Resumed at t={:.3}s", sim.state.time));
+        }
+    }
+}
@@ -0,0 +1,489 @@
+//! # Control Logic
+//!
+//! Controllers only see diagnostic `Measurement`s, never the true state.
+//! Pulse timing and cooldown stay with the plant (`StellaratorState`).
+
+use crate::detection::{DetectionPipeline, Detector, PipelineConfig};
+use crate::diagnostics::Measurement;
+use crate::events::Event;
+use crate::fuzzy::{FuzzyConfig, FuzzyController};
+use crate::gain_schedule::{GainSchedule, Regime, ScheduleTracker, ScheduledGains};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io;
+
+/// Range of commandable D_turb enhancement factors.
+pub const MIN_AMPLITUDE: f64 = 1.0;
+pub const MAX_AMPLITUDE: f64 = 10.0;
+
+/// Part of the stable API; match it with a wildcard arm, as new kinds of
+/// action are not breaking.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum ControlAction {
+    Hold,
+    /// Pulse with the plant's defaults.
+    TriggerPulse,
+    /// Pulse with explicitly chosen parameters.
+    Pulse(PulseCommand),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[non_exhaustive]
+pub struct PulseCommand {
+    pub window: usize,          // Index into `plasma.pulse_windows`
+    pub amplitude: Option<f64>, // Enhancement factor, clamped to 1–10×; None = plasma.pulse_amplitude
+}
+
+impl PulseCommand {
+    pub fn new(window: usize, amplitude: Option<f64>) -> Self {
+        PulseCommand { window, amplitude }
+    }
+}
+
+impl ControlAction {
+    /// True for any pulse request.
+    pub fn requests_pulse(&self) -> bool {
+        !matches!(self, ControlAction::Hold)
+    }
+}
+
+pub trait Controller {
+    fn decide(&mut self, measurement: &Measurement) -> ControlAction;
+
+    /// Events raised since the last call, e.g. by the detector.
+    fn take_events(&mut self) -> Vec<Event> {
+        Vec::new()
+    }
+
+    /// Called when the plant started a pulse of `duration` s on a request.
+    fn pulse_started(&mut self, _time: f64, _duration: f64) {}
+
+    /// Operating point, given before each decision (see `gain_schedule`).
+    fn regime(&mut self, _regime: &Regime) {}
+
+    /// Run state for a checkpoint (detector, budget used, escalation);
+    /// `None` when there is none to keep.
+    fn save_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Continues from what `save_state` returned.
+    fn restore_state(&mut self, _state: serde_json::Value) -> serde_json::Result<()> {
+        Ok(())
+    }
+}
+
+/// Settings of the built-in controller (`[controller]`).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControllerConfig {
+    pub window: usize, // Pulse window to request; 0 = plant default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escalation: Option<Escalation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetConfig>,
+    /// Thresholds and gains by plasma regime (see `gain_schedule`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<GainSchedule>,
+    /// Fuzzy inference in place of the threshold controller (see `fuzzy`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fuzzy: Option<FuzzyConfig>,
+    /// Rhai control law replacing the threshold controller (`scripting`
+    /// feature, see `script`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<ScriptConfig>,
+    /// Controller from a shared library (`plugins` feature, see `plugin`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugin: Option<PluginConfig>,
+}
+
+impl ControllerConfig {
+    /// `FuzzyController` with `[controller.fuzzy]`, else `ThresholdController`
+    /// around `detector`; behind a `BudgetGuard` when a budget is configured.
+    pub fn build(&self, detector: Box<dyn Detector>, pulse_duration: f64) -> Box<dyn Controller> {
+        match &self.fuzzy {
+            Some(fuzzy) => self.guarded(Box::new(FuzzyController::new(fuzzy, self.window)), pulse_duration),
+            None => self.guarded(
+                Box::new(ThresholdController::with_detector(detector).configured(self)),
+                pulse_duration,
+            ),
+        }
+    }
+
+    /// The `[controller.script]` or `[controller.plugin]` controller behind
+    /// `guarded`; `None` when neither is set. Fails when it does not load
+    /// or the build lacks its feature.
+    pub fn load(&self, pulse_duration: f64) -> io::Result<Option<Box<dyn Controller>>> {
+        let controller = match (&self.script, &self.plugin) {
+            (Some(script), _) => load_script(script)?,
+            (None, Some(plugin)) => load_plugin(plugin)?,
+            (None, None) => return Ok(None),
+        };
+        Ok(Some(self.guarded(controller, pulse_duration)))
+    }
+
+    /// `controller` behind a `BudgetGuard` for pulses of `pulse_duration`
+    /// (`plasma.pulse_duration`) when a budget is configured.
+    pub fn guarded(&self, controller: Box<dyn Controller>, pulse_duration: f64) -> Box<dyn Controller> {
+        match self.budget {
+            Some(budget) => Box::new(BudgetGuard::new(controller, budget, pulse_duration)),
+            None => controller,
+        }
+    }
+}
+
+#[cfg(feature = "scripting")]
+fn load_script(script: &ScriptConfig) -> io::Result<Box<dyn Controller>> {
+    let controller = crate::script::ScriptController::load(script)
+        .map_err(|e| io::Error::new(e.kind(), format!("control script {}", e)))?;
+    Ok(Box::new(controller))
+}
+
+#[cfg(not(feature = "scripting"))]
+fn load_script(script: &ScriptConfig) -> io::Result<Box<dyn Controller>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("control script {}: rebuild with `--features scripting`", script.path),
+    ))
+}
+
+#[cfg(feature = "plugins")]
+fn load_plugin(plugin: &PluginConfig) -> io::Result<Box<dyn Controller>> {
+    let controller = crate::plugin::PluginController::load(plugin)
+        .map_err(|e| io::Error::new(e.kind(), format!("plugin controller {}", e)))?;
+    Ok(Box::new(controller))
+}
+
+#[cfg(not(feature = "plugins"))]
+fn load_plugin(plugin: &PluginConfig) -> io::Result<Box<dyn Controller>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("plugin controller {}: rebuild with `--features plugins`", plugin.path),
+    ))
+}
+
+/// Script file of a scripted controller.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptConfig {
+    pub path: String,
+    pub reload: bool,        // Recompile when the file changes on disk
+    pub max_operations: u64, // Per `decide` call; 0 = unlimited
+}
+
+impl Default for ScriptConfig {
+    fn default() -> Self {
+        ScriptConfig {
+            path: "control.rhai".to_string(),
+            reload: true,
+            max_operations: 100_000,
+        }
+    }
+}
+
+/// Shared library of a plugin controller.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PluginConfig {
+    pub path: String,
+    #[serde(default)]
+    pub parameters: String, // Passed to `w7x_controller_create` as is
+}
+
+/// Operational limits of the actuator. The plant's cooldown still applies
+/// on top of `min_off_time`.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_pulses: Option<usize>, // Pulses per `pulse_window`
+    pub pulse_window: f64,         // s, sliding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_pulse_time: Option<f64>, // s of pulsing per discharge
+    pub min_off_time: f64,           // s from the end of a pulse to the next start
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        BudgetConfig {
+            max_pulses: None,
+            pulse_window: 10.0,
+            max_pulse_time: None,
+            min_off_time: 0.0,
+        }
+    }
+}
+
+/// Raises the commanded amplitude while accumulation persists: a pulse
+/// that did not clear the alarm is followed by a stronger one.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Escalation {
+    pub initial: f64,  // Amplitude of the first pulse
+    pub factor: f64,   // Multiplier per escalation step
+    pub interval: f64, // s of continuous detection per step (just under pulse + cooldown)
+}
+
+impl Default for Escalation {
+    fn default() -> Self {
+        Escalation {
+            initial: 5.0,
+            factor: 1.5,
+            interval: 0.6,
+        }
+    }
+}
+
+/// Requests a pulse whenever its detector reports accumulation.
+pub struct ThresholdController {
+    detector: Box<dyn Detector>,
+    window: usize, // Pulse window to request; 0 = plant default
+    escalation: Option<Escalation>,
+    escalated: Option<(f64, f64)>, // (time of last step, amplitude) while detection persists
+    schedule: Option<GainSchedule>,
+    scheduled: ScheduleTracker,
+    base_window: usize,                 // `window` outside the schedule
+    base_thresholds: Vec<(String, f64)>, // Detector thresholds before the schedule retuned them
+    amplitude: Option<f64>,             // Scheduled pulse amplitude
+    events: Vec<Event>,
+}
+
+impl ThresholdController {
+    /// The v2 trigger: level + growth-rate alarms.
+    pub fn new() -> Self {
+        Self::with_detector(Box::new(DetectionPipeline::new(&PipelineConfig::default())))
+    }
+
+    pub fn with_detector(detector: Box<dyn Detector>) -> Self {
+        ThresholdController {
+            detector,
+            window: 0,
+            escalation: None,
+            escalated: None,
+            schedule: None,
+            scheduled: ScheduleTracker::default(),
+            base_window: 0,
+            base_thresholds: Vec::new(),
+            amplitude: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Window, escalation, and gain schedule from `[controller]`.
+    pub fn configured(self, config: &ControllerConfig) -> Self {
+        let mut controller = self.with_window(config.window);
+        controller.escalation = config.escalation;
+        controller.schedule = config.schedule.clone();
+        controller
+    }
+
+    /// Requests pulses in `plasma.pulse_windows[window]` instead of the first.
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window;
+        self.base_window = window;
+        self
+    }
+
+    /// Applies a schedule row on top of the configured settings.
+    fn apply(&mut self, gains: &ScheduledGains) {
+        for (alarm, threshold) in &self.base_thresholds {
+            self.detector.set_threshold(alarm, *threshold);
+        }
+        for (alarm, &threshold) in &gains.thresholds {
+            match self.detector.set_threshold(alarm, threshold) {
+                Some(previous) => {
+                    if !self.base_thresholds.iter().any(|(name, _)| name == alarm) {
+                        self.base_thresholds.push((alarm.clone(), previous));
+                    }
+                }
+                None => tracing::warn!("⚠️ Gain schedule {}: the detector has no alarm {}", gains.name, alarm),
+            }
+        }
+        self.window = gains.window.unwrap_or(self.base_window);
+        self.amplitude = gains.amplitude;
+    }
+}
+
+/// Holds back pulse requests of any controller that would exceed the
+/// actuator budget. A blocked request is recorded once per episode (until
+/// a request passes or the controller stops asking).
+pub struct BudgetGuard {
+    inner: Box<dyn Controller>,
+    budget: BudgetConfig,
+    pulse_duration: f64,               // s, length of the next pulse
+    starts: VecDeque<f64>,             // Pulse start times within `pulse_window`
+    pulse_time: f64,                   // s used so far
+    last_pulse: Option<(f64, f64)>,    // (start, end)
+    blocked: bool,
+    events: Vec<Event>,
+}
+
+impl BudgetGuard {
+    /// `pulse_duration` is the length of the pulses it lets through, so a
+    /// pulse that would run past `max_pulse_time` is held back; it follows
+    /// the pulses the plant starts.
+    pub fn new(inner: Box<dyn Controller>, budget: BudgetConfig, pulse_duration: f64) -> Self {
+        BudgetGuard {
+            inner,
+            budget,
+            pulse_duration,
+            starts: VecDeque::new(),
+            pulse_time: 0.0,
+            last_pulse: None,
+            blocked: false,
+            events: Vec::new(),
+        }
+    }
+
+    /// The first constraint a pulse starting at `time` would violate.
+    fn violation(&mut self, time: f64) -> Option<&'static str> {
+        while self.starts.front().is_some_and(|&t| time - t >= self.budget.pulse_window) {
+            self.starts.pop_front();
+        }
+        if self.budget.max_pulses.is_some_and(|max| self.starts.len() >= max) {
+            return Some("max_pulses");
+        }
+        if self.budget.max_pulse_time.is_some_and(|max| self.pulse_time + self.pulse_duration > max) {
+            return Some("max_pulse_time");
+        }
+        if self.last_pulse.is_some_and(|(_, end)| time - end < self.budget.min_off_time) {
+            return Some("min_off_time");
+        }
+        None
+    }
+}
+
+impl Controller for BudgetGuard {
+    fn decide(&mut self, measurement: &Measurement) -> ControlAction {
+        let action = self.inner.decide(measurement);
+        let t = measurement.time;
+        let pulsing = self.last_pulse.is_some_and(|(_, end)| t < end);
+        if !action.requests_pulse() || pulsing {
+            self.blocked = false;
+            return action;
+        }
+        match self.violation(t) {
+            Some(constraint) => {
+                if !self.blocked {
+                    self.blocked = true;
+                    self.events.push(Event::PulseBlocked { constraint: constraint.to_string() });
+                }
+                ControlAction::Hold
+            }
+            None => {
+                self.blocked = false;
+                action
+            }
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<Event> {
+        let mut events = self.inner.take_events();
+        events.append(&mut self.events);
+        events
+    }
+
+    fn pulse_started(&mut self, time: f64, duration: f64) {
+        self.pulse_duration = duration;
+        self.starts.push_back(time);
+        self.pulse_time += duration;
+        self.last_pulse = Some((time, time + duration));
+        self.inner.pulse_started(time, duration);
+    }
+
+    fn regime(&mut self, regime: &Regime) {
+        self.inner.regime(regime);
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        let used = (self.pulse_duration, &self.starts, self.pulse_time, self.last_pulse, self.blocked);
+        serde_json::to_value((used, self.inner.save_state())).ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> serde_json::Result<()> {
+        let (used, inner): (_, Option<serde_json::Value>) = serde_json::from_value(state)?;
+        (self.pulse_duration, self.starts, self.pulse_time, self.last_pulse, self.blocked) = used;
+        match inner {
+            Some(inner) => self.inner.restore_state(inner),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for ThresholdController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Controller for ThresholdController {
+    fn decide(&mut self, measurement: &Measurement) -> ControlAction {
+        if !self.detector.update(measurement) {
+            self.escalated = None;
+            return ControlAction::Hold;
+        }
+        let scheduled = self.amplitude;
+        let amplitude = self.escalation.map(|escalation| {
+            let t = measurement.time;
+            let (since, amplitude) = match self.escalated {
+                None => (t, scheduled.unwrap_or(escalation.initial)),
+                Some((since, amplitude)) if t - since >= escalation.interval => {
+                    (t, amplitude * escalation.factor)
+                }
+                Some(step) => step,
+            };
+            let amplitude = amplitude.clamp(MIN_AMPLITUDE, MAX_AMPLITUDE);
+            self.escalated = Some((since, amplitude));
+            amplitude
+        })
+        .or(scheduled);
+        if self.window == 0 && amplitude.is_none() {
+            ControlAction::TriggerPulse
+        } else {
+            ControlAction::Pulse(PulseCommand { window: self.window, amplitude })
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<Event> {
+        let mut events = self.detector.take_events();
+        events.append(&mut self.events);
+        events
+    }
+
+    fn regime(&mut self, regime: &Regime) {
+        let Some(schedule) = &self.schedule else {
+            return;
+        };
+        let indicator = schedule.indicator.read(regime);
+        let Some(gains) = self.scheduled.update(schedule, regime).cloned() else {
+            return;
+        };
+        self.apply(&gains);
+        self.events.push(Event::GainsScheduled { regime: gains.name, indicator });
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        let scheduled = (self.scheduled, self.window, &self.base_thresholds, self.amplitude);
+        serde_json::to_value((self.escalated, scheduled, self.detector.save_state())).ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> serde_json::Result<()> {
+        let (escalated, scheduled, detector): (_, _, Option<serde_json::Value>) = serde_json::from_value(state)?;
+        self.escalated = escalated;
+        (self.scheduled, self.window, self.base_thresholds, self.amplitude) = scheduled;
+        match detector {
+            Some(detector) => self.detector.restore_state(detector),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Makes the same decision at every sample: `Hold` for an uncontrolled
+/// plant, `TriggerPulse` to pulse whenever the plant allows.
+pub struct FixedController(pub ControlAction);
+
+impl Controller for FixedController {
+    fn decide(&mut self, _measurement: &Measurement) -> ControlAction {
+        self.0
+    }
+}
@@ -0,0 +1,164 @@
+//! # Grid Convergence Study
+//!
+//! Runs the scenario at `levels` successively refined resolutions and
+//! compares the n_Z profiles at `t_max` on the coarsest grid. With the
+//! differences e_k = ‖u_k − u_{k+1}‖ between neighbouring levels, the
+//! observed order is
+//!
+//! ```text
+//! p = log(e_k / e_{k+1}) / log(ratio)
+//! ```
+//!
+//! `vary` picks what is refined: `nr` (dr / ratio per level, dt fixed),
+//! `dt` (dt / ratio, nr fixed), or `both` (dr / ratio and dt / ratio²,
+//! which keeps the explicit diffusion number constant; p is then the
+//! order in dr). Each run takes round(t_max / dt) steps so all levels end
+//! at the same time. The controller is off unless `control = true`: pulse
+//! starts snap to diagnostic samples, so closed-loop runs do not converge
+//! smoothly.
+
+use crate::config::Config;
+use crate::controller::{ControlAction, FixedController};
+use crate::error::Result;
+use crate::simulation::Simulation;
+use ndarray::Array1;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Refinement {
+    Nr,
+    Dt,
+    Both,
+}
+
+impl Refinement {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Refinement::Nr => "nr",
+            Refinement::Dt => "dt",
+            Refinement::Both => "nr and dt",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConvergeConfig {
+    pub levels: usize, // Resolutions, the configured one first
+    pub ratio: usize,  // Refinement factor per level
+    pub vary: Refinement,
+    pub t_max: f64,    // s, shorter than a full discharge: the finest level is expensive
+    pub control: bool,
+    pub output: String, // Per-level table (CSV)
+}
+
+impl Default for ConvergeConfig {
+    fn default() -> Self {
+        ConvergeConfig {
+            levels: 3,
+            ratio: 2,
+            vary: Refinement::Both,
+            t_max: 0.1,
+            control: false,
+            output: "convergence.csv".to_string(),
+        }
+    }
+}
+
+impl ConvergeConfig {
+    /// (nr, dt) of level `k`.
+    pub fn resolution(&self, base: &Config, k: usize) -> (usize, f64) {
+        let factor = self.ratio.max(1).pow(k as u32);
+        let nr = base.simulation.nr;
+        let dt = base.simulation.dt;
+        match self.vary {
+            Refinement::Nr => ((nr - 1) * factor + 1, dt),
+            Refinement::Dt => (nr, dt / factor as f64),
+            Refinement::Both => ((nr - 1) * factor + 1, dt / (factor * factor) as f64),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Level {
+    pub nr: usize,
+    pub dt: f64,
+    pub steps: usize,
+    pub profile: Array1<f64>,             // n_Z at t_max on the coarsest grid
+    pub difference: Option<(f64, f64)>,   // (L2, max) relative to the next finer level
+    pub order: Option<(f64, f64)>,        // Observed order from this and the next difference
+}
+
+pub fn run_convergence(base: &Config) -> Result<Vec<Level>> {
+    base.validate()?;
+    let settings = &base.converge;
+    let coarse = base.simulation.nr;
+    let run = |k: &usize| {
+        let (nr, dt) = settings.resolution(base, *k);
+        let mut config = base.clone();
+        config.simulation.nr = nr;
+        config.simulation.dt = dt;
+        let mut sim = Simulation::try_from_config(&config)?;
+        sim.state.verbose = false;
+        sim.state.history.recording = false;
+        if !settings.control {
+            sim.controller = Box::new(FixedController(ControlAction::Hold));
+        }
+        let steps = (settings.t_max / dt).round() as usize;
+        for _ in 0..steps {
+            sim.step();
+            sim.check_finite()?;
+        }
+        // Coarse nodes are every stride-th fine node
+        let stride = (nr - 1) / (coarse - 1).max(1);
+        let profile = (0..coarse).map(|j| sim.state.impurity_density[j * stride]).collect();
+        Ok(Level { nr, dt, steps, profile, difference: None, order: None })
+    };
+    let indices: Vec<usize> = (0..settings.levels).collect();
+    #[cfg(feature = "parallel")]
+    let mut levels: Vec<Level> = indices.par_iter().map(run).collect::<Result<_>>()?;
+    #[cfg(not(feature = "parallel"))]
+    let mut levels: Vec<Level> = indices.iter().map(run).collect::<Result<_>>()?;
+
+    for k in 0..levels.len().saturating_sub(1) {
+        let difference = &levels[k].profile - &levels[k + 1].profile;
+        let scale = levels[k + 1].profile.iter().fold(0.0_f64, |m, v| m.max(v.abs())).max(1e-300);
+        let l2 = (difference.mapv(|d| d * d).sum() / difference.len() as f64).sqrt() / scale;
+        let max = difference.iter().fold(0.0_f64, |m, d| m.max(d.abs())) / scale;
+        levels[k].difference = Some((l2, max));
+    }
+    let log_ratio = (settings.ratio.max(2) as f64).ln();
+    for k in 0..levels.len().saturating_sub(2) {
+        if let (Some(e0), Some(e1)) = (levels[k].difference, levels[k + 1].difference) {
+            levels[k].order = Some(((e0.0 / e1.0).ln() / log_ratio, (e0.1 / e1.1).ln() / log_ratio));
+        }
+    }
+    Ok(levels)
+}
+
+#[cfg(feature = "fs")]
+pub fn write_levels<P: AsRef<std::path::Path>>(
+    path: P,
+    levels: &[Level],
+    metadata: &crate::metadata::RunMetadata,
+) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    metadata.write_csv_header(&mut writer)?;
+    writeln!(writer, "level,nr,dt,steps,center_impurity,l2_difference,max_difference,l2_order,max_order")?;
+    let field = |value: Option<f64>| value.map(|v| format!("{:.6e}", v)).unwrap_or_default();
+    for (k, level) in levels.iter().enumerate() {
+        writeln!(
+            writer,
+            "{},{},{:.6e},{},{:.6e},{},{},{},{}",
+            k, level.nr, level.dt, level.steps, level.profile[0],
+            field(level.difference.map(|d| d.0)), field(level.difference.map(|d| d.1)),
+            field(level.order.map(|p| p.0)), field(level.order.map(|p| p.1))
+        )?;
+    }
+    writer.flush()
+}
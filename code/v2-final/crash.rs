@@ -0,0 +1,102 @@
+//! # Non-finite Guard
+//!
+//! Checks every `interval` steps that no profile has gone to NaN or ±∞.
+//! Once the explicit step blows up the whole state follows within a few
+//! steps, so a sparse check only costs those steps of latency. The guard
+//! keeps the scalar channels of the last `RECENT_SAMPLES` steps (whatever
+//! the history cadence) for the crash dump: the fault, those samples, the
+//! run metadata (with the configuration), and the full state (non-finite
+//! values written as `null`).
+
+#[cfg(feature = "fs")]
+use crate::metadata::RunMetadata;
+use crate::history::Sample;
+use crate::state::StellaratorState;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+pub const RECENT_SAMPLES: usize = 1000;
+
+/// First non-finite value found.
+#[derive(Clone, Debug, Serialize)]
+pub struct NonFinite {
+    pub profile: &'static str,
+    pub index: usize, // Radial grid point
+    pub value: f64,
+    pub time: f64,    // s, of the check that found it
+    pub step: u64,
+}
+
+impl std::fmt::Display for NonFinite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}[{}] = {} at t = {:.6}s (step {})",
+            self.profile, self.index, self.value, self.time, self.step
+        )
+    }
+}
+
+pub struct FiniteGuard {
+    pub interval: usize, // Steps between checks; 0 = off
+    step: u64,
+    recent: VecDeque<Sample>,
+}
+
+impl FiniteGuard {
+    pub fn new(interval: usize) -> Self {
+        FiniteGuard { interval, step: 0, recent: VecDeque::with_capacity(RECENT_SAMPLES) }
+    }
+
+    /// Call once per step, after the update.
+    pub fn observe(&mut self, state: &StellaratorState) -> Option<NonFinite> {
+        self.step += 1;
+        if self.interval == 0 {
+            return None;
+        }
+        if self.recent.len() == RECENT_SAMPLES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(*state.last_sample());
+        if !self.step.is_multiple_of(self.interval as u64) {
+            return None;
+        }
+        state.first_non_finite().map(|(profile, index, value)| NonFinite {
+            profile,
+            index,
+            value,
+            time: state.time,
+            step: self.step,
+        })
+    }
+
+    pub fn recent(&self) -> &VecDeque<Sample> {
+        &self.recent
+    }
+}
+
+#[cfg(feature = "fs")]
+#[derive(Serialize)]
+struct CrashDump<'a> {
+    message: String, // The fault as text: JSON has no NaN or ∞
+    fault: &'a NonFinite,
+    recent: &'a VecDeque<Sample>,
+    metadata: &'a RunMetadata,
+    state: &'a StellaratorState,
+}
+
+#[cfg(feature = "fs")]
+pub fn write_crash_dump<P: AsRef<std::path::Path>>(
+    path: P,
+    fault: &NonFinite,
+    guard: &FiniteGuard,
+    metadata: &RunMetadata,
+    state: &StellaratorState,
+) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let dump = CrashDump { message: fault.to_string(), fault, recent: guard.recent(), metadata, state };
+    serde_json::to_writer_pretty(&mut writer, &dump).map_err(std::io::Error::other)?;
+    writer.flush()
+}
@@ -0,0 +1,79 @@
+//! # Data Acquisition and Control Cycle
+//!
+//! Sits between the synthetic diagnostic and the controller. A real
+//! control system does not act on every diagnostic sample the moment it is
+//! taken: the DAQ reads the channels once per control `cycle`, and the
+//! detection and decision take `latency` before the command reaches the
+//! actuator. A detector judged at the solver step therefore looks faster
+//! than it can be on the machine.
+//!
+//! At each cycle tick the newest diagnostic sample since the last tick is
+//! acquired and handed to the controller `latency` later; the controller
+//! acts then, on data as old as the latency. Measurements keep the time
+//! they were sampled. A tick without a new sample passes nothing on. With
+//! both settings 0 (the default) every sample goes to the controller in the
+//! step it is taken, as in v2.
+
+use crate::diagnostics::Measurement;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DaqConfig {
+    pub cycle: f64,   // s between acquisitions; 0 = every diagnostic sample
+    pub latency: f64, // s from acquisition to the controller's decision
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Daq {
+    pub cycle: f64,
+    pub latency: f64,
+    next_cycle: f64,
+    held: Option<Measurement>,               // Newest sample since the last tick
+    in_flight: VecDeque<(f64, Measurement)>, // (deliver at, measurement)
+}
+
+impl Daq {
+    pub fn new(config: &DaqConfig) -> Self {
+        Daq {
+            cycle: config.cycle,
+            latency: config.latency,
+            next_cycle: 0.0,
+            held: None,
+            in_flight: VecDeque::new(),
+        }
+    }
+
+    /// Takes this step's diagnostic sample, if any, and returns the
+    /// measurement the controller acts on at `time`, if one is due. Of
+    /// several due at once (latency spread below a solver step) only the
+    /// newest is returned.
+    pub fn transfer(&mut self, sample: Option<Measurement>, time: f64) -> Option<Measurement> {
+        if sample.is_some() {
+            self.held = sample;
+        }
+        if time >= self.next_cycle {
+            if self.cycle > 0.0 {
+                self.next_cycle += self.cycle;
+                // Like the diagnostic: no burst of ticks after a time jump
+                if self.next_cycle < time {
+                    self.next_cycle = time + self.cycle;
+                }
+            }
+            if let Some(measurement) = self.held.take() {
+                self.in_flight.push_back((time + self.latency, measurement));
+            }
+        }
+        let mut due = None;
+        while self.in_flight.front().is_some_and(|&(at, _)| at <= time) {
+            due = self.in_flight.pop_front().map(|(_, m)| m);
+        }
+        due
+    }
+
+    /// Measurements acquired but not yet delivered.
+    pub fn pending(&self) -> usize {
+        self.in_flight.len()
+    }
+}
@@ -0,0 +1,563 @@
+//! # Accumulation Detection
+//!
+//! A `Detector` turns the measurement stream into a trigger decision.
+//! `DetectionPipeline` builds detectors from configurable stages:
+//!
+//! ```text
+//! signal → filter → feature → threshold → latch/reset → alarm
+//! ```
+//!
+//! Several alarms can run side by side. By default the pipeline fires
+//! while any of them is active; `voting` can instead require all of them,
+//! a minimum count, or a weighted score, and other detectors (the learned
+//! model) can join the vote. Latched alarms stay active until their reset
+//! condition is met, even if the signal drops back below threshold.
+//!
+//! Against chatter near the threshold, an alarm can require `persistence`
+//! consecutive samples above threshold before it sets (debounce) and a
+//! lower `release` level before it clears (hysteresis).
+
+use crate::diagnostics::Measurement;
+use crate::events::Event;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+pub trait Detector {
+    /// Feeds one measurement; returns true while accumulation is detected.
+    fn update(&mut self, measurement: &Measurement) -> bool;
+
+    /// Events raised since the last call (alarm transitions).
+    fn take_events(&mut self) -> Vec<Event> {
+        Vec::new()
+    }
+
+    /// Retunes the alarm named `alarm`; returns its previous threshold, or
+    /// `None` if there is no such alarm.
+    fn set_threshold(&mut self, _alarm: &str, _threshold: f64) -> Option<f64> {
+        None
+    }
+
+    /// Run state for a checkpoint (filters, windows, latches); `None` when
+    /// there is none to keep.
+    fn save_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Continues from what `save_state` returned.
+    fn restore_state(&mut self, _state: serde_json::Value) -> serde_json::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Signal {
+    CentralSxr,
+    EdgeDensity,
+    Turbulence,
+}
+
+impl Signal {
+    pub fn read(&self, m: &Measurement) -> f64 {
+        match self {
+            Signal::CentralSxr => m.central_sxr,
+            Signal::EdgeDensity => m.edge_density,
+            Signal::Turbulence => m.turbulence,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FilterConfig {
+    None,
+    MovingAverage { samples: usize },
+    LowPass { time_constant: f64 }, // s
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeatureConfig {
+    Level,
+    Rate { window: f64 }, // s, slope over this span
+    /// Deviation from the signal's own recent behaviour, (x − μ) / σ with
+    /// μ and σ² exponentially weighted over `time_constant`; the threshold
+    /// is then in standard deviations rather than signal units.
+    Anomaly { time_constant: f64, warmup: f64 }, // s, s before the first value
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResetCondition {
+    /// Only `DetectionPipeline::reset` clears the alarm.
+    Manual,
+    /// Feature stays below `level` for `hold` seconds.
+    Below { level: f64, hold: f64 },
+    /// Alarm clears `duration` seconds after it was set.
+    Timeout { duration: f64 },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LatchConfig {
+    /// Alarm follows the threshold comparison directly.
+    None,
+    Latched { reset: ResetCondition },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AlarmConfig {
+    pub name: String,
+    pub signal: Signal,
+    pub filter: FilterConfig,
+    pub feature: FeatureConfig,
+    pub threshold: f64, // Alarm condition: feature > threshold
+    /// Unlatched alarms clear once the feature is ≤ this; None = `threshold`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<f64>,
+    /// Consecutive samples above threshold needed to set the alarm (0 or 1 = at once).
+    #[serde(default)]
+    pub persistence: usize,
+    pub latch: LatchConfig,
+    /// Vote weight under `weighted` voting; None = 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+}
+
+/// How the active alarms (and added detectors) combine into the trigger.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Voting {
+    /// Any one active voter triggers (v2).
+    #[default]
+    Any,
+    All,
+    AtLeast { count: usize },
+    /// Sum of the active voters' weights reaches `threshold`.
+    Weighted { threshold: f64 },
+}
+
+impl Voting {
+    /// Combines (active, weight) votes.
+    pub fn decide(&self, votes: &[(bool, f64)]) -> bool {
+        let active = votes.iter().filter(|(a, _)| *a).count();
+        match *self {
+            Voting::Any => active > 0,
+            Voting::All => active > 0 && active == votes.len(),
+            Voting::AtLeast { count } => active >= count.max(1),
+            Voting::Weighted { threshold } => {
+                let score: f64 = votes.iter().filter(|(a, _)| *a).map(|(_, w)| w).sum();
+                active > 0 && score >= threshold
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineConfig {
+    pub alarms: Vec<AlarmConfig>,
+    pub voting: Voting,
+    /// Learned detector that replaces the alarms when set (`onnx` feature),
+    /// or votes alongside them with `vote = true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<ModelConfig>,
+}
+
+/// ONNX classifier over a sliding window of measurements. The model gets
+/// a `[1, window, signals.len()]` f32 tensor (oldest sample first, each
+/// signal divided by its `scale`) and returns the intervention probability
+/// as the first element of its first output.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelConfig {
+    pub path: String,
+    pub window: usize,         // Measurements per inference
+    pub signals: Vec<Signal>,  // Input channels, in model order
+    pub scale: Vec<f64>,       // Divisor per signal, same order
+    pub threshold: f64,        // Trigger while probability > threshold
+    pub vote: bool,            // Join the alarms' vote instead of replacing them
+    pub weight: f64,           // Vote weight under `weighted` voting
+}
+
+impl Default for ModelConfig {
+    fn default() -> Self {
+        ModelConfig {
+            path: "detector.onnx".to_string(),
+            window: 20,
+            signals: vec![Signal::CentralSxr, Signal::EdgeDensity, Signal::Turbulence],
+            scale: vec![1e18, 1e18, 1.0],
+            threshold: 0.5,
+            vote: false,
+            weight: 1.0,
+        }
+    }
+}
+
+impl Default for PipelineConfig {
+    /// The v2 logic: n_Z(0) above 8e17, or growing faster than 1.5e18 /s.
+    fn default() -> Self {
+        PipelineConfig {
+            alarms: vec![
+                AlarmConfig {
+                    name: "central_level".to_string(),
+                    signal: Signal::CentralSxr,
+                    filter: FilterConfig::None,
+                    feature: FeatureConfig::Level,
+                    threshold: 8e17,
+                    release: None,
+                    persistence: 0,
+                    latch: LatchConfig::None,
+                    weight: None,
+                },
+                AlarmConfig {
+                    name: "central_growth".to_string(),
+                    signal: Signal::CentralSxr,
+                    filter: FilterConfig::None,
+                    feature: FeatureConfig::Rate { window: 0.002 },
+                    threshold: 1.5e18,
+                    release: None,
+                    persistence: 0,
+                    latch: LatchConfig::None,
+                    weight: None,
+                },
+            ],
+            voting: Voting::Any,
+            model: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum FilterState {
+    None,
+    MovingAverage { samples: usize, buffer: VecDeque<f64> },
+    LowPass { time_constant: f64, value: Option<(f64, f64)> }, // (time, filtered)
+}
+
+impl FilterState {
+    fn new(config: &FilterConfig) -> Self {
+        match *config {
+            FilterConfig::None => FilterState::None,
+            FilterConfig::MovingAverage { samples } => FilterState::MovingAverage {
+                samples: samples.max(1),
+                buffer: VecDeque::new(),
+            },
+            FilterConfig::LowPass { time_constant } => FilterState::LowPass {
+                time_constant,
+                value: None,
+            },
+        }
+    }
+
+    fn apply(&mut self, time: f64, x: f64) -> f64 {
+        match self {
+            FilterState::None => x,
+            FilterState::MovingAverage { samples, buffer } => {
+                buffer.push_back(x);
+                if buffer.len() > *samples {
+                    buffer.pop_front();
+                }
+                buffer.iter().sum::<f64>() / buffer.len() as f64
+            }
+            FilterState::LowPass { time_constant, value } => {
+                let y = match *value {
+                    Some((t_prev, y_prev)) if *time_constant > 0.0 => {
+                        let alpha = 1.0 - (-(time - t_prev) / *time_constant).exp();
+                        y_prev + alpha * (x - y_prev)
+                    }
+                    _ => x,
+                };
+                *value = Some((time, y));
+                y
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum FeatureState {
+    Level,
+    Rate { window: f64, samples: VecDeque<(f64, f64)> },
+    Anomaly {
+        time_constant: f64,
+        warmup: f64,
+        stats: Option<Ewma>,
+    },
+}
+
+/// Exponentially weighted mean and variance of an irregularly sampled signal.
+#[derive(Serialize, Deserialize)]
+struct Ewma {
+    start: f64,
+    time: f64,
+    mean: f64,
+    variance: f64,
+}
+
+impl Ewma {
+    /// Score of `x` against the statistics so far, then folds `x` in.
+    fn update(&mut self, time: f64, x: f64, time_constant: f64) -> f64 {
+        let sigma = self.variance.sqrt().max(1e-12 * self.mean.abs().max(1.0));
+        let score = (x - self.mean) / sigma;
+        let alpha = if time_constant > 0.0 {
+            1.0 - (-(time - self.time) / time_constant).exp()
+        } else {
+            1.0
+        };
+        let diff = x - self.mean;
+        self.mean += alpha * diff;
+        self.variance = (1.0 - alpha) * (self.variance + alpha * diff * diff);
+        self.time = time;
+        score
+    }
+}
+
+impl FeatureState {
+    fn new(config: &FeatureConfig) -> Self {
+        match *config {
+            FeatureConfig::Level => FeatureState::Level,
+            FeatureConfig::Rate { window } => FeatureState::Rate {
+                window,
+                samples: VecDeque::new(),
+            },
+            FeatureConfig::Anomaly { time_constant, warmup } => FeatureState::Anomaly {
+                time_constant,
+                warmup,
+                stats: None,
+            },
+        }
+    }
+
+    /// `None` until enough samples exist to compute the feature.
+    fn apply(&mut self, time: f64, x: f64) -> Option<f64> {
+        match self {
+            FeatureState::Level => Some(x),
+            FeatureState::Rate { window, samples } => {
+                samples.push_back((time, x));
+                while samples.len() > 2 && time - samples[1].0 >= *window {
+                    samples.pop_front();
+                }
+                let (t0, x0) = samples[0];
+                let span = time - t0;
+                if span >= *window && span > 0.0 {
+                    Some((x - x0) / span)
+                } else {
+                    None
+                }
+            }
+            FeatureState::Anomaly { time_constant, warmup, stats } => {
+                let Some(stats) = stats else {
+                    *stats = Some(Ewma { start: time, time, mean: x, variance: 0.0 });
+                    return None;
+                };
+                let score = stats.update(time, x, *time_constant);
+                (time - stats.start >= *warmup).then_some(score)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Alarm {
+    config: AlarmConfig,
+    filter: FilterState,
+    feature: FeatureState,
+    active: bool,
+    set_time: f64,
+    below_since: Option<f64>,
+    above_count: usize, // Consecutive samples above threshold
+    last_feature: f64,  // Latest feature value
+}
+
+impl Alarm {
+    fn new(config: AlarmConfig) -> Self {
+        Alarm {
+            filter: FilterState::new(&config.filter),
+            feature: FeatureState::new(&config.feature),
+            config,
+            active: false,
+            set_time: 0.0,
+            below_since: None,
+            above_count: 0,
+            last_feature: 0.0,
+        }
+    }
+
+    fn update(&mut self, m: &Measurement) -> bool {
+        let raw = self.config.signal.read(m);
+        let filtered = self.filter.apply(m.time, raw);
+        let Some(feature) = self.feature.apply(m.time, filtered) else {
+            return self.active;
+        };
+        self.last_feature = feature;
+        let above = feature > self.config.threshold;
+        self.above_count = if above { self.above_count + 1 } else { 0 };
+        let condition = above && self.above_count >= self.config.persistence.max(1);
+
+        match &self.config.latch {
+            LatchConfig::None => {
+                self.active = if self.active {
+                    feature > self.config.release.unwrap_or(self.config.threshold)
+                } else {
+                    condition
+                };
+            }
+            LatchConfig::Latched { reset } => {
+                if !self.active {
+                    if condition {
+                        self.active = true;
+                        self.set_time = m.time;
+                        self.below_since = None;
+                    }
+                } else {
+                    match *reset {
+                        ResetCondition::Manual => {}
+                        ResetCondition::Below { level, hold } => {
+                            if feature < level {
+                                let since = *self.below_since.get_or_insert(m.time);
+                                if m.time - since >= hold {
+                                    self.active = false;
+                                }
+                            } else {
+                                self.below_since = None;
+                            }
+                        }
+                        ResetCondition::Timeout { duration } => {
+                            if m.time - self.set_time >= duration {
+                                self.active = condition;
+                                self.set_time = m.time;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        self.active
+    }
+}
+
+/// Detector other than an alarm taking part in the vote.
+struct Voter {
+    name: String,
+    detector: Box<dyn Detector>,
+    weight: f64,
+    active: bool,
+}
+
+pub struct DetectionPipeline {
+    alarms: Vec<Alarm>,
+    voters: Vec<Voter>,
+    voting: Voting,
+    events: Vec<Event>,
+}
+
+impl DetectionPipeline {
+    pub fn new(config: &PipelineConfig) -> Self {
+        DetectionPipeline {
+            alarms: config.alarms.iter().cloned().map(Alarm::new).collect(),
+            voters: Vec::new(),
+            voting: config.voting,
+            events: Vec::new(),
+        }
+    }
+
+    /// Adds a detector that votes alongside the alarms with `weight`.
+    pub fn add_detector(&mut self, name: &str, detector: Box<dyn Detector>, weight: f64) {
+        self.voters.push(Voter {
+            name: name.to_string(),
+            detector,
+            weight,
+            active: false,
+        });
+    }
+
+    /// Names of the alarms and added detectors currently active.
+    pub fn active_alarms(&self) -> Vec<&str> {
+        self.alarms
+            .iter()
+            .filter(|a| a.active)
+            .map(|a| a.config.name.as_str())
+            .chain(self.voters.iter().filter(|v| v.active).map(|v| v.name.as_str()))
+            .collect()
+    }
+
+    /// Explicitly clears a latched alarm. Returns false if no alarm has that name.
+    pub fn reset(&mut self, name: &str) -> bool {
+        match self.alarms.iter_mut().find(|a| a.config.name == name) {
+            Some(alarm) => {
+                if alarm.active {
+                    self.events.push(Event::AlarmCleared { alarm: alarm.config.name.clone() });
+                }
+                alarm.active = false;
+                alarm.below_since = None;
+                alarm.above_count = 0;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Detector for DetectionPipeline {
+    fn update(&mut self, measurement: &Measurement) -> bool {
+        // Every voter must see every sample, so no short-circuiting
+        let mut votes = Vec::with_capacity(self.alarms.len() + self.voters.len());
+        for alarm in &mut self.alarms {
+            let was_active = alarm.active;
+            let active = alarm.update(measurement);
+            if active != was_active {
+                let name = alarm.config.name.clone();
+                self.events.push(if active {
+                    Event::ThresholdCrossed {
+                        alarm: name,
+                        value: alarm.last_feature,
+                        threshold: alarm.config.threshold,
+                    }
+                } else {
+                    Event::AlarmCleared { alarm: name }
+                });
+            }
+            votes.push((active, alarm.config.weight.unwrap_or(1.0)));
+        }
+        for voter in &mut self.voters {
+            voter.active = voter.detector.update(measurement);
+            votes.push((voter.active, voter.weight));
+        }
+        self.voting.decide(&votes)
+    }
+
+    fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// An explicit `release` moves with the threshold.
+    fn set_threshold(&mut self, alarm: &str, threshold: f64) -> Option<f64> {
+        let alarm = self.alarms.iter_mut().find(|a| a.config.name == alarm)?;
+        let previous = alarm.config.threshold;
+        if let Some(release) = &mut alarm.config.release {
+            *release += threshold - previous;
+        }
+        alarm.config.threshold = threshold;
+        Some(previous)
+    }
+
+    /// The alarms whole (a scheduled threshold included) and each added
+    /// detector's own state.
+    fn save_state(&self) -> Option<serde_json::Value> {
+        let voters: Vec<_> = self.voters.iter().map(|v| (v.active, v.detector.save_state())).collect();
+        serde_json::to_value((&self.alarms, voters)).ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> serde_json::Result<()> {
+        let (alarms, voters): (Vec<Alarm>, Vec<(bool, Option<serde_json::Value>)>) = serde_json::from_value(state)?;
+        self.alarms = alarms;
+        for (voter, (active, state)) in self.voters.iter_mut().zip(voters) {
+            voter.active = active;
+            if let Some(state) = state {
+                voter.detector.restore_state(state)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+//! # Radial Electric Field
+//!
+//! Ambipolar E_r from the ion-root force balance with T_i = T_e,
+//!
+//! ```text
+//! E_r = −(T_e / e) (1/L_n + α / L_T)
+//! ```
+//!
+//! (negative in the gradient region, as in most W7-X scenarios), plus a
+//! shift ramping up across the pulse region while the actuator is on: the
+//! pulse also changes the edge E_r. The E×B shearing rate
+//! γ_E = |d(E_r/B)/dr| quenches D_turb through a configurable rule.
+
+use crate::turbulence::{LocalProfiles, MINOR_RADIUS};
+use serde::{Deserialize, Serialize};
+
+const MAGNETIC_FIELD: f64 = 2.5; // T, on axis
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ElectricField {
+    /// No E_r; D_turb unaffected (v2).
+    #[default]
+    Off,
+    Ambipolar(AmbipolarModel),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShearQuench {
+    /// D_turb · max(0, 1 − γ_E / γ_crit)
+    #[default]
+    Waltz,
+    /// D_turb / (1 + (γ_E / γ_crit)²)
+    Lorentzian,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AmbipolarModel {
+    pub temperature_coefficient: f64, // α
+    pub pulse_shift: f64,             // kV/m at the edge at full actuator output
+    pub quench: ShearQuench,
+    pub critical_shearing_rate: f64,  // γ_crit, 1/s
+}
+
+impl Default for AmbipolarModel {
+    fn default() -> Self {
+        AmbipolarModel {
+            temperature_coefficient: 0.5,
+            pulse_shift: 5.0,
+            quench: ShearQuench::Waltz,
+            critical_shearing_rate: 1e5,
+        }
+    }
+}
+
+impl ElectricField {
+    /// E_r (kV/m) at an interior grid point. `pulse_ramp` is the actuator
+    /// output times the position across the pulse region (0 inside it,
+    /// 1 at the edge).
+    pub fn field(&self, local: &LocalProfiles, pulse_ramp: f64) -> f64 {
+        match self {
+            ElectricField::Off => 0.0,
+            ElectricField::Ambipolar(model) => {
+                let drive = local.inverse_density_length()
+                    + model.temperature_coefficient * local.inverse_temperature_length();
+                -local.electron_temp * drive / MINOR_RADIUS + model.pulse_shift * pulse_ramp
+            }
+        }
+    }
+
+    /// γ_E (1/s) from E_r (kV/m) at the neighbouring points, `dr` apart in
+    /// normalized radius.
+    pub fn shearing_rate(field_outer: f64, field_inner: f64, dr: f64) -> f64 {
+        ((field_outer - field_inner) * 1e3 / MAGNETIC_FIELD / (2.0 * dr * MINOR_RADIUS)).abs()
+    }
+
+    /// D_turb after E×B shear suppression.
+    pub fn suppress(&self, d_turb: f64, shearing_rate: f64) -> f64 {
+        match self {
+            ElectricField::Off => d_turb,
+            ElectricField::Ambipolar(model) => {
+                let x = shearing_rate / model.critical_shearing_rate.max(1e-12);
+                match model.quench {
+                    ShearQuench::Waltz => d_turb * (1.0 - x).max(0.0),
+                    ShearQuench::Lorentzian => d_turb / (1.0 + x * x),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! # Edge-Localized Bursts
+//!
+//! ELM-like disturbance: short, large enhancements of D_turb at
+//! r > `inner`, starting at random times (a Poisson process of mean rate
+//! `frequency`). A burst multiplies the edge D_turb by `amplitude` for
+//! `duration` s; the blend with a pulse in the same region is
+//! multiplicative. `frequency = 0` switches them off (v2).
+//!
+//! The gaps between bursts are exponential draws from the `elms` random
+//! stream (`rng`), one derived seed per burst, so the schedule is a pure
+//! function of the seed and survives a checkpoint without storing a
+//! generator.
+//!
+//! A burst dumps edge impurities and so dips the edge channels, which the
+//! detector can mistake for (or hide) the signature it is looking for.
+//! The run summary counts bursts and the pulses that start within
+//! `attribution_window` after one (`pulses_after_elm`).
+
+use crate::rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Elms {
+    pub frequency: f64,          // Hz, mean burst rate; 0 = off
+    pub duration: f64,           // s
+    pub amplitude: f64,          // D_turb multiplier during a burst
+    pub inner: f64,              // Normalized radius, bursts act outside
+    pub attribution_window: f64, // s after a burst in which a pulse start is attributed to it
+    pub seed: u64,               // Overridden by a master seed, see `rng`
+    bursts: u64,
+    next_onset: Option<f64>,     // s
+    last_onset: Option<f64>,     // s
+    burst_end: Option<f64>,      // s, onset + duration of the last burst
+}
+
+impl Default for Elms {
+    fn default() -> Self {
+        Elms {
+            frequency: 0.0,
+            duration: 5e-4,
+            amplitude: 20.0,
+            inner: 0.9,
+            attribution_window: 0.01,
+            seed: 13,
+            bursts: 0,
+            next_onset: None,
+            last_onset: None,
+            burst_end: None,
+        }
+    }
+}
+
+impl Elms {
+    pub fn enabled(&self) -> bool {
+        self.frequency > 0.0
+    }
+
+    /// Bursts started so far.
+    pub fn bursts(&self) -> u64 {
+        self.bursts
+    }
+
+    pub fn last_onset(&self) -> Option<f64> {
+        self.last_onset
+    }
+
+    /// Exponential gap before burst `k` (0-based).
+    fn interval(&self, k: u64) -> f64 {
+        let bits = rng::derive(self.seed, &format!("elm {}", k));
+        // Uniform on (0, 1]
+        let u = ((bits >> 11) + 1) as f64 / (1u64 << 53) as f64;
+        -u.ln() / self.frequency
+    }
+
+    /// Advances the schedule to `time`; true when a burst starts now.
+    pub fn step(&mut self, time: f64) -> bool {
+        if !self.enabled() {
+            return false;
+        }
+        let next = match self.next_onset {
+            Some(next) => next,
+            None => {
+                let next = time + self.interval(0);
+                self.next_onset = Some(next);
+                next
+            }
+        };
+        if time < next {
+            return false;
+        }
+        self.bursts += 1;
+        self.last_onset = Some(time);
+        self.burst_end = Some(time + self.duration);
+        self.next_onset = Some(time + self.interval(self.bursts));
+        true
+    }
+
+    pub fn active(&self, time: f64) -> bool {
+        // Against the stored end: time − onset rounds differently
+        self.burst_end.is_some_and(|end| time < end)
+    }
+
+    /// D_turb multiplier at normalized radius `r`.
+    pub fn factor(&self, r: f64, time: f64) -> f64 {
+        if r > self.inner && self.active(time) {
+            self.amplitude
+        } else {
+            1.0
+        }
+    }
+}
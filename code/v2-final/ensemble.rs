@@ -0,0 +1,192 @@
+//! # Monte Carlo Ensembles
+//!
+//! Runs `replicas` copies of the closed loop with uncertain plasma
+//! parameters drawn from `[ensemble]` distributions and reduces them to
+//! percentile bands of n_Z(0)(t) plus intervention statistics.
+//!
+//! All draws come from the `ensemble` random stream before any replica
+//! starts, so the result does not depend on how many threads run the
+//! replicas. Replica `i` is seeded by `rng::seed_batch_member(…, i)`.
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::rng::{self, RngRegistry, Stream, StreamRng};
+use crate::scan::run_quiet;
+use crate::summary::RunSummary;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use rand::Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+
+/// How one parameter varies around its `[plasma]` value.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Perturbation {
+    #[default]
+    Fixed,
+    /// base + sigma · N(0, 1)
+    Normal { sigma: f64 },
+    /// Uniform on [low, high], ignoring the base value
+    Uniform { low: f64, high: f64 },
+    /// base · exp(sigma · N(0, 1)); keeps the sign of the base value
+    LogNormal { sigma: f64 },
+}
+
+impl Perturbation {
+    pub fn sample(&self, base: f64, rng: &mut StreamRng) -> f64 {
+        match *self {
+            Perturbation::Fixed => base,
+            Perturbation::Normal { sigma } => {
+                let z: f64 = rng.sample(StandardNormal);
+                base + sigma * z
+            }
+            Perturbation::Uniform { low, high } => rng.gen_range(low..=high),
+            Perturbation::LogNormal { sigma } => {
+                let z: f64 = rng.sample(StandardNormal);
+                base * (sigma * z).exp()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnsembleConfig {
+    pub replicas: usize,
+    pub seed: u64,
+    pub d_neo: Perturbation,
+    pub v_neo: Perturbation,
+    pub impurity_source: Perturbation,
+    pub band_interval: f64,    // s between points of the percentile bands
+    pub percentiles: Vec<f64>, // in [0, 100]
+    pub output: String,        // Percentile bands (CSV)
+}
+
+impl Default for EnsembleConfig {
+    fn default() -> Self {
+        EnsembleConfig {
+            replicas: 32,
+            seed: 7,
+            d_neo: Perturbation::LogNormal { sigma: 0.3 },
+            v_neo: Perturbation::Normal { sigma: 0.1 },
+            impurity_source: Perturbation::LogNormal { sigma: 0.3 },
+            band_interval: 0.01,
+            percentiles: vec![5.0, 25.0, 50.0, 75.0, 95.0],
+            output: "ensemble_bands.csv".to_string(),
+        }
+    }
+}
+
+/// Drawn parameters of one replica.
+#[derive(Clone, Copy, Debug)]
+pub struct Replica {
+    pub d_neo: f64,
+    pub v_neo: f64,
+    pub impurity_source: f64,
+    pub index: usize,  // Position in the batch, seeds the replica's random streams
+}
+
+pub struct EnsembleResult {
+    pub replicas: Vec<Replica>,
+    pub summaries: Vec<RunSummary>,
+    pub time: Vec<f64>,
+    /// `bands[k][j]`: percentile `k` of n_Z(0) at `time[j]`.
+    pub bands: Vec<Vec<f64>>,
+}
+
+impl EnsembleConfig {
+    pub fn draw(&self, base: &Config) -> Vec<Replica> {
+        let mut rng = RngRegistry::new(base).rng(Stream::Ensemble);
+        (0..self.replicas)
+            .map(|i| Replica {
+                d_neo: self.d_neo.sample(base.plasma.d_neo, &mut rng),
+                v_neo: self.v_neo.sample(base.plasma.v_neo, &mut rng),
+                impurity_source: self.impurity_source.sample(base.plasma.impurity_source, &mut rng),
+                index: i,
+            })
+            .collect()
+    }
+}
+
+pub fn run_ensemble(base: &Config) -> Result<EnsembleResult> {
+    let settings = &base.ensemble;
+    let replicas = settings.draw(base);
+
+    let run = |replica: &Replica| {
+        let mut config = base.clone();
+        // Negative diffusivity or source would be unphysical, not uncertain
+        config.plasma.d_neo = replica.d_neo.max(0.0);
+        config.plasma.v_neo = replica.v_neo;
+        config.plasma.impurity_source = replica.impurity_source.max(0.0);
+        rng::seed_batch_member(&mut config, replica.index);
+
+        let mut trace = Vec::new();
+        let mut next_time = 0.0;
+        let summary = run_quiet(&config, |state| {
+            if state.time >= next_time {
+                trace.push(state.impurity_density[0]);
+                next_time += settings.band_interval;
+            }
+        })?;
+        Ok((summary, trace))
+    };
+    #[cfg(feature = "parallel")]
+    let runs: Vec<_> = replicas.par_iter().map(run).collect::<Result<_>>()?;
+    #[cfg(not(feature = "parallel"))]
+    let runs: Vec<_> = replicas.iter().map(run).collect::<Result<_>>()?;
+
+    let n_points = runs.iter().map(|(_, trace)| trace.len()).min().unwrap_or(0);
+    let time = (0..n_points).map(|j| j as f64 * settings.band_interval).collect();
+    let bands = settings
+        .percentiles
+        .iter()
+        .map(|&p| {
+            (0..n_points)
+                .map(|j| {
+                    let mut column: Vec<f64> = runs.iter().map(|(_, trace)| trace[j]).collect();
+                    percentile(&mut column, p)
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(EnsembleResult {
+        replicas,
+        summaries: runs.into_iter().map(|(summary, _)| summary).collect(),
+        time,
+        bands,
+    })
+}
+
+/// Linear-interpolated percentile `p` (0–100); sorts `values` in place.
+pub fn percentile(values: &mut [f64], p: f64) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let rank = (p / 100.0).clamp(0.0, 1.0) * (values.len() - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    values[lo] + (values[hi] - values[lo]) * (rank - lo as f64)
+}
+
+#[cfg(feature = "fs")]
+pub fn write_bands<P: AsRef<std::path::Path>>(
+    path: P,
+    percentiles: &[f64],
+    result: &EnsembleResult,
+    metadata: &crate::metadata::RunMetadata,
+) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    metadata.write_csv_header(&mut writer)?;
+    let names: Vec<String> = percentiles.iter().map(|p| format!("p{}", p)).collect();
+    writeln!(writer, "time,{}", names.join(","))?;
+    for (j, t) in result.time.iter().enumerate() {
+        let row: Vec<String> = result.bands.iter().map(|band| format!("{:.6e}", band[j])).collect();
+        writeln!(writer, "{:.6},{}", t, row.join(","))?;
+    }
+    writer.flush()
+}
@@ -0,0 +1,60 @@
+//! # Errors
+//!
+//! `SimError` is what the fallible library entry points return: loading
+//! and validating a configuration, checkpoints, and the `run` / `run_quiet`
+//! drivers behind scans and ensembles. Plain writers (CSV tables, logs)
+//! keep `std::io::Result`; `?` converts.
+
+use crate::crash::NonFinite;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SimError {
+    /// Configuration that cannot be parsed or does not fit together.
+    Config(String),
+    /// A single parameter outside its valid range.
+    InvalidParameter { name: &'static str, reason: String },
+    /// A profile went to NaN or ±∞.
+    NumericalInstability(NonFinite),
+    Io(std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, SimError>;
+
+impl SimError {
+    pub fn invalid(name: &'static str, reason: impl Into<String>) -> Self {
+        SimError::InvalidParameter { name, reason: reason.into() }
+    }
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimError::Config(message) => write!(f, "configuration: {}", message),
+            SimError::InvalidParameter { name, reason } => write!(f, "invalid {}: {}", name, reason),
+            SimError::NumericalInstability(fault) => write!(f, "non-finite value: {}", fault),
+            SimError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SimError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SimError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SimError {
+    fn from(e: std::io::Error) -> Self {
+        SimError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for SimError {
+    fn from(e: toml::de::Error) -> Self {
+        SimError::Config(e.to_string())
+    }
+}
@@ -0,0 +1,195 @@
+//! # Event Log
+//!
+//! Typed record of what happened during a run: pulses, alarm threshold
+//! crossings, scenario steps, blocked requests, pellets, sawtooth crashes, edge bursts,
+//! collapse and termination, discharge phases, numerical warnings. The plant
+//! collects events with their time (`StellaratorState::drain_events`); the
+//! log messages are rendered from them, and `EventLog` writes them as JSON lines next to
+//! the trace, one object per event after a `RunMetadata` line:
+//!
+//! ```text
+//! {"metadata":{"crate_version":"2.0.0","git_commit":"…",…}}
+//! {"time":0.198,"event":"pulse_started","window":0,"inner":0.7,"outer":1.0,"amplitude":null}
+//! {"time":0.4,"event":"pulse_ended","cooldown":0.5}
+//! ```
+
+#[cfg(feature = "fs")]
+use crate::metadata::RunMetadata;
+use crate::ramp::Phase;
+use crate::regularization::Regularization;
+use crate::scenario::ScenarioAction;
+use crate::state::ConfinementMode;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+#[cfg(feature = "fs")]
+use std::fs::{File, OpenOptions};
+#[cfg(feature = "fs")]
+use std::io::{BufWriter, Write};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    PulseStarted {
+        window: usize,          // Index into `plasma.pulse_windows`
+        inner: f64,             // Normalized radius
+        outer: f64,
+        amplitude: Option<f64>, // Commanded enhancement; None = plasma.pulse_amplitude
+    },
+    PulseEnded { cooldown: f64 }, // s
+    /// A detection alarm set; `value` is its feature at that sample.
+    ThresholdCrossed { alarm: String, value: f64, threshold: f64 },
+    AlarmCleared { alarm: String },
+    PelletInjected { penetration: f64, ablated: f64 }, // Normalized radius, fraction
+    Regularized { method: Regularization, cells: u64 }, // Cells since the last report
+    EcrhBudgetExhausted { energy: f64 },                // MJ
+    /// A scripted change from the scenario.
+    Scenario { action: ScenarioAction },
+    /// A pulse request held back by the actuator budget (`BudgetConfig` field name).
+    PulseBlocked { constraint: String },
+    /// The explicit transport step exceeds its stability limit.
+    CflViolation { dt: f64, limit: f64 }, // s, s
+    /// Core relaxation flattened n_Z and T_e inside `mixing_radius`.
+    SawtoothCrash { mixing_radius: f64, center_impurity_before: f64, center_impurity_after: f64 }, // m⁻³
+    /// An edge burst started (`elm`).
+    ElmBurst { amplitude: f64, duration: f64 }, // D_turb multiplier, s
+    /// A collapse, flush, recovery, or termination transition (`termination`).
+    ModeChanged { mode: ConfinementMode, reason: String },
+    /// Ramp-up, flat top, or ramp-down reached (`ramp`).
+    PhaseStarted { phase: Phase },
+    /// The gain schedule switched rows (`gain_schedule`).
+    GainsScheduled { regime: String, indicator: f64 },
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TimedEvent {
+    pub time: f64,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+impl TimedEvent {
+    /// Logs the message: numerical warnings at WARN, alarm transitions and
+    /// edge bursts at DEBUG, everything else at INFO.
+    pub fn log(&self) {
+        let message = self.message();
+        match self.event {
+            Event::ThresholdCrossed { .. } | Event::AlarmCleared { .. } | Event::ElmBurst { .. } => {
+                debug!("{}", message)
+            }
+            Event::Regularized { .. }
+            | Event::EcrhBudgetExhausted { .. }
+            | Event::PulseBlocked { .. }
+            | Event::CflViolation { .. }
+            | Event::ModeChanged { .. } => warn!("{}", message),
+            _ => info!("{}", message),
+        }
+    }
+
+    /// Human-readable line for the event.
+    pub fn message(&self) -> String {
+        let t = self.time;
+        match &self.event {
+            Event::PulseStarted { window, inner, outer, amplitude } => {
+                let mut detail = String::new();
+                if *window != 0 {
+                    detail += &format!(" in {:.2} < r < {:.2}", inner, outer);
+                }
+                if let Some(amplitude) = amplitude {
+                    detail += &format!(" at {:.1}×", amplitude);
+                }
+                format!("⚠️ t={:.3}s: Impurity accumulation! Starting pulse{}", t, detail)
+            }
+            Event::PulseEnded { cooldown } => {
+                format!("✅ t={:.3}s: Return to normal (cooldown {:.1}s)", t, cooldown)
+            }
+            Event::ThresholdCrossed { alarm, value, threshold } => {
+                format!("🔔 t={:.3}s: Alarm {} set ({:.3e} > {:.3e})", t, alarm, value, threshold)
+            }
+            Event::AlarmCleared { alarm } => format!("🔕 t={:.3}s: Alarm {} cleared", t, alarm),
+            Event::PelletInjected { penetration, ablated } => format!(
+                "🧊 t={:.3}s: Pellet injected, penetration to r={:.2} ({:.0}% ablated)",
+                t, penetration, ablated * 100.0
+            ),
+            Event::Regularized { method, cells } => format!(
+                "🩹 t={:.3}s: {:?} regularization active ({} cells since last report)",
+                t, method, cells
+            ),
+            Event::EcrhBudgetExhausted { energy } => {
+                format!("🔋 t={:.3}s: ECRH energy budget exhausted ({:.1} MJ)", t, energy)
+            }
+            Event::Scenario { action } => format!("📜 t={:.3}s: Scenario: {}", t, action.describe()),
+            Event::PulseBlocked { constraint } => {
+                format!("⛔ t={:.3}s: Pulse request blocked by actuator budget ({})", t, constraint)
+            }
+            Event::CflViolation { dt, limit } => format!(
+                "⏱️ t={:.3}s: dt = {:.2e}s exceeds the explicit stability limit {:.2e}s",
+                t, dt, limit
+            ),
+            Event::SawtoothCrash { mixing_radius, center_impurity_before, center_impurity_after } => format!(
+                "🪚 t={:.3}s: Sawtooth crash inside r={:.2}, n_Z(0) {:.2e} → {:.2e} m⁻³",
+                t, mixing_radius, center_impurity_before, center_impurity_after
+            ),
+            Event::ElmBurst { amplitude, duration } => format!(
+                "💥 t={:.4}s: Edge burst, D_turb ×{:.0} for {:.1} ms",
+                t, amplitude, duration * 1e3
+            ),
+            Event::ModeChanged { mode, reason } => {
+                let (icon, what) = match mode {
+                    ConfinementMode::RadiativeCollapse => ("🔥", "Radiative collapse"),
+                    ConfinementMode::EmergencyFlush => ("🚨", "Emergency flush"),
+                    ConfinementMode::Terminated => ("🛑", "Discharge terminated"),
+                    ConfinementMode::Normal => ("✅", "Recovered to normal"),
+                    ConfinementMode::TurbulencePulse => ("⚠️", "Pulse"),
+                };
+                format!("{} t={:.3}s: {}: {}", icon, t, what, reason)
+            }
+            Event::PhaseStarted { phase } => {
+                let icon = match phase {
+                    Phase::RampUp | Phase::FlatTop => "📈",
+                    Phase::RampDown | Phase::Ended => "📉",
+                };
+                format!("{} t={:.3}s: Discharge phase: {}", icon, t, phase.name())
+            }
+            Event::GainsScheduled { regime, indicator } => {
+                format!("🎚️ t={:.3}s: Gains scheduled for {} (indicator {:.3e})", t, regime, indicator)
+            }
+        }
+    }
+}
+
+/// JSON-lines event file.
+#[cfg(feature = "fs")]
+pub struct EventLog {
+    writer: BufWriter<File>,
+}
+
+#[cfg(feature = "fs")]
+impl EventLog {
+    /// Appends when resuming a run, like the trace sinks; the metadata
+    /// line is written only to a new file.
+    pub fn create<P: AsRef<Path>>(path: P, append: bool, metadata: &RunMetadata) -> std::io::Result<Self> {
+        let file = if append {
+            OpenOptions::new().create(true).append(true).open(path)?
+        } else {
+            File::create(path)?
+        };
+        let is_new = file.metadata()?.len() == 0;
+        let mut writer = BufWriter::new(file);
+        if is_new {
+            serde_json::to_writer(&mut writer, &serde_json::json!({ "metadata": metadata }))?;
+            writeln!(writer)?;
+        }
+        Ok(EventLog { writer })
+    }
+
+    pub fn write(&mut self, event: &TimedEvent) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.writer, event)?;
+        writeln!(self.writer)
+    }
+
+    pub fn finish(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,244 @@
+//! # Pulse Waveform Evolution
+//!
+//! Genetic algorithm over pulse shapes. A genome is
+//!
+//! ```text
+//! [a_1 .. a_n]       amplification factor per equal time segment of the pulse
+//! pulse_duration     s
+//! inner_radius       pulse region is r > inner_radius
+//! ```
+//!
+//! Each generation is evaluated in parallel; selection is by tournament,
+//! crossover is uniform per gene, mutation is Gaussian (scaled to each
+//! gene's range), and the best `elite` genomes survive unchanged. Fitness
+//! is the [`CostConfig`] run cost (lower is better).
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::optimize::CostConfig;
+use crate::pulse::PulseWindow;
+use crate::rng::{RngRegistry, Stream, StreamRng};
+use crate::scan::run_quiet;
+use crate::summary::RunSummary;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use rand::Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvolveConfig {
+    pub population: usize,
+    pub generations: usize,
+    pub segments: usize,              // Time segments per pulse waveform
+    pub max_amplitude: f64,           // Upper bound per segment
+    pub pulse_duration: [f64; 2],     // s, [low, high]
+    pub inner_radius: [f64; 2],       // [low, high]
+    pub tournament: usize,
+    pub elite: usize,
+    pub mutation_rate: f64,           // Per-gene probability
+    pub mutation_sigma: f64,          // Fraction of the gene's range
+    pub seed: u64,
+    pub cost: CostConfig,
+    pub output: String,               // Per-generation log (CSV)
+}
+
+impl Default for EvolveConfig {
+    fn default() -> Self {
+        EvolveConfig {
+            population: 24,
+            generations: 20,
+            segments: 8,
+            max_amplitude: 10.0,
+            pulse_duration: [0.05, 0.4],
+            inner_radius: [0.3, 0.9],
+            tournament: 3,
+            elite: 2,
+            mutation_rate: 0.2,
+            mutation_sigma: 0.1,
+            seed: 5,
+            cost: CostConfig::default(),
+            output: "evolve_history.csv".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Genome {
+    pub waveform: Vec<f64>,
+    pub pulse_duration: f64,
+    pub inner_radius: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Individual {
+    pub genome: Genome,
+    pub summary: RunSummary,
+    pub cost: f64,
+}
+
+/// Best and mean cost of one generation.
+#[derive(Clone, Debug)]
+pub struct GenerationStats {
+    pub generation: usize,
+    pub best: Individual,
+    pub mean_cost: f64,
+}
+
+impl EvolveConfig {
+    /// (low, high) of gene `i` in the flat genome layout.
+    fn bounds(&self, i: usize) -> (f64, f64) {
+        match i.checked_sub(self.segments) {
+            None => (0.0, self.max_amplitude),
+            Some(0) => (self.pulse_duration[0], self.pulse_duration[1]),
+            Some(_) => (self.inner_radius[0], self.inner_radius[1]),
+        }
+    }
+
+    fn genes(&self) -> usize {
+        self.segments + 2
+    }
+
+    fn decode(&self, genes: &[f64]) -> Genome {
+        Genome {
+            waveform: genes[..self.segments].to_vec(),
+            pulse_duration: genes[self.segments],
+            inner_radius: genes[self.segments + 1],
+        }
+    }
+
+    fn encode(&self, genome: &Genome) -> Vec<f64> {
+        let mut genes = genome.waveform.clone();
+        genes.push(genome.pulse_duration);
+        genes.push(genome.inner_radius);
+        genes
+    }
+}
+
+pub struct Evolution<'a> {
+    base: &'a Config,
+    settings: &'a EvolveConfig,
+    rng: StreamRng,
+    generation: usize,
+    population: Vec<Individual>,
+}
+
+impl<'a> Evolution<'a> {
+    pub fn new(base: &'a Config) -> Self {
+        Evolution {
+            base,
+            settings: &base.evolve,
+            rng: RngRegistry::new(base).rng(Stream::Evolve),
+            generation: 0,
+            population: Vec::new(),
+        }
+    }
+
+    /// Breeds (or, first time, randomly creates) and evaluates the next generation.
+    pub fn step(&mut self) -> Result<GenerationStats> {
+        let settings = self.settings;
+        // Elites carry over with their known cost
+        let elite = settings.elite.min(self.population.len());
+        let survivors: Vec<Individual> = self.population[..elite].to_vec();
+        let genomes: Vec<Vec<f64>> = if self.population.is_empty() {
+            (0..settings.population)
+                .map(|_| {
+                    (0..settings.genes())
+                        .map(|i| {
+                            let (low, high) = settings.bounds(i);
+                            self.rng.gen_range(low..=high)
+                        })
+                        .collect()
+                })
+                .collect()
+        } else {
+            self.breed(settings.population.saturating_sub(elite))
+        };
+
+        let evaluate = |genes: &Vec<f64>| self.evaluate(settings.decode(genes));
+        #[cfg(feature = "parallel")]
+        let children: Vec<Individual> = genomes.par_iter().map(evaluate).collect::<Result<_>>()?;
+        #[cfg(not(feature = "parallel"))]
+        let children: Vec<Individual> = genomes.iter().map(evaluate).collect::<Result<_>>()?;
+        let mut population = survivors;
+        population.extend(children);
+        population.sort_by(|a, b| a.cost.total_cmp(&b.cost));
+
+        let mean_cost = population.iter().map(|i| i.cost).sum::<f64>() / population.len().max(1) as f64;
+        self.population = population;
+        self.generation += 1;
+        Ok(GenerationStats {
+            generation: self.generation,
+            best: self.population[0].clone(),
+            mean_cost,
+        })
+    }
+
+    /// `count` children bred from the current (cost-sorted) population.
+    fn breed(&mut self, count: usize) -> Vec<Vec<f64>> {
+        let settings = self.settings;
+        let mut next = Vec::with_capacity(count);
+        while next.len() < count {
+            let a = settings.encode(&self.tournament().genome);
+            let b = settings.encode(&self.tournament().genome);
+            let child = (0..settings.genes())
+                .map(|i| {
+                    let mut gene = if self.rng.gen_bool(0.5) { a[i] } else { b[i] };
+                    if self.rng.gen_bool(settings.mutation_rate.clamp(0.0, 1.0)) {
+                        let (low, high) = settings.bounds(i);
+                        let z: f64 = self.rng.sample(StandardNormal);
+                        gene = (gene + z * settings.mutation_sigma * (high - low)).clamp(low, high);
+                    }
+                    gene
+                })
+                .collect();
+            next.push(child);
+        }
+        next
+    }
+
+    fn tournament(&mut self) -> &Individual {
+        let n = self.population.len();
+        let best = (0..self.settings.tournament.max(1))
+            .map(|_| self.rng.gen_range(0..n))
+            .min() // Population is sorted by cost
+            .expect("tournament size ≥ 1");
+        &self.population[best]
+    }
+
+    fn evaluate(&self, genome: Genome) -> Result<Individual> {
+        let mut config = self.base.clone();
+        // Segments carry absolute amplification
+        config.plasma.pulse_amplitude = 1.0;
+        config.plasma.pulse_waveform = genome.waveform.clone();
+        config.plasma.pulse_duration = genome.pulse_duration;
+        config.plasma.pulse_windows = vec![PulseWindow::edge(genome.inner_radius)];
+        let summary = run_quiet(&config, |_| {})?;
+        let cost = self.settings.cost.cost(&summary);
+        Ok(Individual { genome, summary, cost })
+    }
+}
+
+#[cfg(feature = "fs")]
+pub fn write_history<P: AsRef<std::path::Path>>(
+    path: P,
+    generations: &[GenerationStats],
+    metadata: &crate::metadata::RunMetadata,
+) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    metadata.write_csv_header(&mut writer)?;
+    writeln!(writer, "generation,best_cost,mean_cost,pulse_duration,inner_radius,waveform")?;
+    for g in generations {
+        let waveform: Vec<String> = g.best.genome.waveform.iter().map(|a| format!("{:.3}", a)).collect();
+        writeln!(
+            writer,
+            "{},{:.6},{:.6},{:.4},{:.3},{}",
+            g.generation, g.best.cost, g.mean_cost, g.best.genome.pulse_duration,
+            g.best.genome.inner_radius, waveform.join(" ")
+        )?;
+    }
+    writer.flush()
+}
@@ -0,0 +1,46 @@
+/*
+ * The simulator as a plant model behind the C ABI: a 100 Hz threshold
+ * controller over a 10 s discharge, printing n_Z(0) once per second.
+ *
+ *   cargo build --release --features ffi
+ *   cc examples/ffi_plant.c -I. -Ltarget/release -lw7x_turbulence_control -o ffi_plant
+ *   LD_LIBRARY_PATH=target/release ./ffi_plant [w7x.toml]
+ */
+#include <stdio.h>
+#include "w7x_sim.h"
+
+#define CYCLE 0.01      /* s, controller period */
+#define DISCHARGE 10.0  /* s */
+#define SXR_LIMIT 8e17  /* m^-3 */
+
+int main(int argc, char **argv) {
+    W7xSim *sim = w7x_sim_create(argc > 1 ? argv[1] : NULL);
+    if (!sim) {
+        fprintf(stderr, "w7x_sim_create: %s\n", w7x_last_error());
+        return 1;
+    }
+    size_t nr = w7x_sim_get_profile(sim, W7X_PROFILE_IMPURITY_DENSITY, NULL, 0);
+    double n_z[nr];
+    int pulses = 0, was_active = 0;
+
+    for (int cycle = 1; cycle * CYCLE <= DISCHARGE; cycle++) {
+        if (w7x_sim_step(sim, CYCLE) != 0) {
+            fprintf(stderr, "w7x_sim_step: %s\n", w7x_last_error());
+            return 1;
+        }
+        W7xObservation obs;
+        w7x_sim_observe(sim, &obs);
+        if (obs.central_sxr > SXR_LIMIT)
+            w7x_sim_apply_action(sim, W7X_ACTION_PULSE, 0, 0.0);
+        w7x_sim_observe(sim, &obs);
+        pulses += obs.pulse_active && !was_active;
+        was_active = obs.pulse_active;
+        if (cycle % 100 == 0) {
+            w7x_sim_get_profile(sim, W7X_PROFILE_IMPURITY_DENSITY, n_z, nr);
+            printf("t=%5.2fs  n_Z(0)=%.2e  n_Z(edge)=%.2e  SXR=%.2e  pulses=%d\n",
+                   obs.time, n_z[0], n_z[nr - 1], obs.central_sxr, pulses);
+        }
+    }
+    w7x_sim_destroy(sim);
+    return 0;
+}
@@ -0,0 +1,78 @@
+//! Planner-in-the-loop demo over a simulated 30 s discharge.
+//!
+//! A toy trajectory planner drives the plant through three phases:
+//! - 0–5 s   start-up: no pulses allowed (heating still ramping)
+//! - 5–27 s  flat-top: pulse whenever the SXR proxy exceeds the limit
+//! - 27–30 s pre-termination: one forced flush to leave a clean wall
+//!
+//! ```bash
+//! cargo run --release --example planner_in_the_loop
+//! ```
+
+use w7x_turbulence_control::{ConfinementMode, Config, ControlAction, Plant};
+
+const CYCLE: f64 = 0.01;        // Planner runs at 100 Hz
+const DISCHARGE: f64 = 30.0;    // s
+const SXR_LIMIT: f64 = 8e17;    // m⁻³
+
+enum Phase {
+    StartUp,
+    FlatTop,
+    PreTermination,
+}
+
+fn phase_at(t: f64) -> Phase {
+    if t < 5.0 {
+        Phase::StartUp
+    } else if t < 27.0 {
+        Phase::FlatTop
+    } else {
+        Phase::PreTermination
+    }
+}
+
+fn main() {
+    let mut plant = Plant::new(&Config::default());
+    let mut pulses = 0;
+    let mut forced_flush_done = false;
+    let mut last_mode = ConfinementMode::Normal;
+    let mut next_report = 0.0;
+
+    println!("Planner-in-the-loop: {:.0} s discharge, {:.0} ms planner cycle", DISCHARGE, CYCLE * 1e3);
+
+    while plant.time() < DISCHARGE {
+        plant.step(CYCLE);
+        let obs = plant.observe();
+
+        let action = match phase_at(obs.time) {
+            Phase::StartUp => ControlAction::Hold,
+            Phase::FlatTop if obs.measurement.central_sxr > SXR_LIMIT => ControlAction::TriggerPulse,
+            Phase::FlatTop => ControlAction::Hold,
+            // Asked again every cycle: the plant ignores requests while
+            // it is pulsing or cooling down
+            Phase::PreTermination if !forced_flush_done => ControlAction::TriggerPulse,
+            Phase::PreTermination => ControlAction::Hold,
+        };
+        plant.actuate(action);
+
+        let mode = plant.observe().mode;
+        if mode == ConfinementMode::TurbulencePulse && last_mode == ConfinementMode::Normal {
+            pulses += 1;
+            if matches!(phase_at(obs.time), Phase::PreTermination) {
+                forced_flush_done = true;
+            }
+        }
+        last_mode = mode;
+
+        if obs.time >= next_report {
+            println!(
+                "planner t={:5.1}s | SXR={:.2e} | mode={:?} | pulses so far: {}",
+                obs.time, obs.measurement.central_sxr, obs.mode, pulses
+            );
+            next_report += 1.0;
+        }
+    }
+
+    println!("Discharge complete: {} pulses, final SXR {:.2e} m⁻³",
+             pulses, plant.observe().measurement.central_sxr);
+}
@@ -0,0 +1,83 @@
+/*
+ * Example plugin with both families: a threshold controller that waits
+ * for the previous pulse to end, and a critical-gradient turbulence model
+ * in a/L_T.
+ *
+ *   cc -shared -fPIC -O2 -Iexamples examples/plugin.c -o libw7x_plugin.so
+ *   cargo run --release --features plugins -- w7x.toml
+ *
+ * with, in w7x.toml,
+ *
+ *   [controller]
+ *   plugin = { path = "./libw7x_plugin.so", parameters = "sxr_limit=8e17" }
+ *
+ *   [turbulence]
+ *   type = "plugin"
+ *   path = "./libw7x_plugin.so"
+ *   parameters = "critical=2.0 stiffness=0.5"
+ */
+#include <stdio.h>
+#include <stdlib.h>
+#include "w7x_plugin.h"
+
+uint32_t w7x_plugin_abi_version(void) { return W7X_PLUGIN_ABI_VERSION; }
+
+typedef struct {
+    double sxr_limit;  /* m^-3 */
+    double pulse_end;  /* s */
+} Controller;
+
+void *w7x_controller_create(const char *parameters) {
+    Controller *c = malloc(sizeof *c);
+    if (!c)
+        return NULL;
+    c->sxr_limit = 8e17;
+    c->pulse_end = -1.0;
+    if (*parameters && sscanf(parameters, "sxr_limit=%lf", &c->sxr_limit) != 1) {
+        free(c);
+        return NULL;
+    }
+    return c;
+}
+
+int w7x_controller_decide(void *controller, const W7xPluginMeasurement *m, W7xPluginAction *action) {
+    Controller *c = controller;
+    if (m->central_sxr > c->sxr_limit && m->time >= c->pulse_end)
+        action->kind = W7X_PLUGIN_PULSE;
+    return 0;
+}
+
+void w7x_controller_pulse_started(void *controller, double time, double duration) {
+    ((Controller *)controller)->pulse_end = time + duration;
+}
+
+void w7x_controller_destroy(void *controller) { free(controller); }
+
+typedef struct {
+    double critical;   /* a/L_T */
+    double stiffness;
+} Model;
+
+void *w7x_turbulence_create(const char *parameters) {
+    Model *model = malloc(sizeof *model);
+    if (!model)
+        return NULL;
+    model->critical = 2.0;
+    model->stiffness = 0.5;
+    if (*parameters && sscanf(parameters, "critical=%lf stiffness=%lf", &model->critical, &model->stiffness) != 2) {
+        free(model);
+        return NULL;
+    }
+    return model;
+}
+
+/* Reads the model only, so it is safe to call concurrently. */
+int w7x_turbulence_evaluate(const void *model, const W7xPluginProfiles *local, W7xPluginTransport *out) {
+    const Model *m = model;
+    double excess = local->inverse_temperature_length - m->critical;
+    out->diffusivity = local->d_turb_base * (0.3 + (excess > 0.0 ? m->stiffness * excess : 0.0));
+    out->pinch = 0.0;
+    return 0;
+}
+
+void w7x_turbulence_destroy(void *model) { free(model); }
@@ -0,0 +1,43 @@
+//! One episode of the RL environment under two fixed policies, to give
+//! a learned agent something to beat.
+//!
+//! ```bash
+//! cargo run --release --example rl_episode
+//! ```
+
+use w7x_turbulence_control::rl_env::{RlAction, RlConfig, RlEnv};
+use w7x_turbulence_control::{Config, Observation};
+
+const SXR_LIMIT: f64 = 8e17; // m⁻³
+
+fn run_episode(env: &mut RlEnv, policy: impl Fn(&Observation) -> RlAction) -> (f64, usize) {
+    let mut obs = env.reset();
+    let mut total = 0.0;
+    let mut steps = 0;
+    loop {
+        let (next, reward, done) = env.step(policy(&obs));
+        total += reward;
+        steps += 1;
+        obs = next;
+        if done {
+            return (total, steps);
+        }
+    }
+}
+
+fn main() {
+    let mut env = RlEnv::new(Config::default(), RlConfig::default());
+
+    let never = |_: &Observation| RlAction { pulse: false, amplitude: 5.0 };
+    let threshold = |obs: &Observation| RlAction {
+        pulse: obs.measurement.central_sxr > SXR_LIMIT,
+        amplitude: 5.0,
+    };
+
+    for (name, (total, steps)) in [
+        ("never pulse", run_episode(&mut env, never)),
+        ("v2 threshold", run_episode(&mut env, threshold)),
+    ] {
+        println!("{:<13} return {:9.3} over {} steps", name, total, steps);
+    }
+}
@@ -0,0 +1,60 @@
+/*
+ * Plugin ABI of the W7-X turbulence control simulator (plugin.rs), for
+ * controllers and turbulence models built as shared libraries and
+ * selected with [controller.plugin] or [turbulence] type = "plugin".
+ */
+#ifndef W7X_PLUGIN_H
+#define W7X_PLUGIN_H
+
+#include <stdint.h>
+
+#define W7X_PLUGIN_ABI_VERSION 1
+
+#define W7X_PLUGIN_HOLD 0
+#define W7X_PLUGIN_PULSE 1
+
+typedef struct {
+    double time;          /* s */
+    double central_sxr;   /* measured n_Z(0), m^-3 */
+    double edge_density;  /* measured edge n_Z, m^-3 */
+    double turbulence;    /* measured edge D_turb, m^2/s */
+} W7xPluginMeasurement;
+
+typedef struct {
+    int kind;             /* W7X_PLUGIN_HOLD or W7X_PLUGIN_PULSE */
+    uint32_t window;      /* pulse window index, 0 = plant default */
+    double amplitude;     /* 0 = plant default */
+} W7xPluginAction;
+
+typedef struct {
+    double radius;                      /* r / a */
+    double electron_density;            /* m^-3 */
+    double density_gradient;            /* m^-3 per unit r / a */
+    double electron_temp;               /* keV */
+    double temperature_gradient;        /* keV per unit r / a */
+    double d_turb_base;                 /* nominal D_turb, m^2/s */
+    double pulse_level;                 /* actuator output, 0 outside the pulse region */
+    double inverse_density_length;      /* a / L_n */
+    double inverse_temperature_length;  /* a / L_T */
+    double collisionality;              /* nu*_e */
+} W7xPluginProfiles;
+
+typedef struct {
+    double diffusivity;   /* D_turb, m^2/s, >= 0 */
+    double pinch;         /* m/s, negative inward */
+} W7xPluginTransport;
+
+uint32_t w7x_plugin_abi_version(void);
+
+/* Controllers: called from one thread. */
+void *w7x_controller_create(const char *parameters);
+int w7x_controller_decide(void *controller, const W7xPluginMeasurement *m, W7xPluginAction *action);
+void w7x_controller_pulse_started(void *controller, double time, double duration); /* optional */
+void w7x_controller_destroy(void *controller);
+
+/* Turbulence models: evaluate must be safe to call concurrently. */
+void *w7x_turbulence_create(const char *parameters);
+int w7x_turbulence_evaluate(const void *model, const W7xPluginProfiles *local, W7xPluginTransport *out);
+void w7x_turbulence_destroy(void *model);
+
+#endif
@@ -0,0 +1,268 @@
+//! # C Interface (feature `ffi`)
+//!
+//! A C ABI over [`Plant`] so the C++ real-time control prototype can embed
+//! the simulator as its plant model; the caller's controller decides the
+//! pulses. `cargo build --release --features ffi` builds the shared library
+//! (`libw7x_turbulence_control.so`); the header `w7x_sim.h` is generated
+//! with cbindgen, and `UPDATE_HEADER=1 cargo test --features ffi --test ffi`
+//! refreshes the checked-in copy (see `examples/ffi_plant.c`):
+//!
+//! ```c
+//! W7xSim *sim = w7x_sim_create("w7x.toml");   /* NULL: default config */
+//! double n_z[128];
+//! while (w7x_sim_time(sim) < 30.0) {
+//!     w7x_sim_step(sim, 0.01);
+//!     W7xObservation obs;
+//!     w7x_sim_observe(sim, &obs);
+//!     if (obs.central_sxr > 8e17)
+//!         w7x_sim_apply_action(sim, W7X_ACTION_PULSE, 0, 0.0);
+//!     size_t nr = w7x_sim_get_profile(sim, W7X_PROFILE_IMPURITY_DENSITY, n_z, 128);
+//! }
+//! w7x_sim_destroy(sim);
+//! ```
+//!
+//! Functions returning `int` give 0 on success and -1 on error, with the
+//! message from `w7x_last_error()` (per thread). A handle must only be used
+//! from one thread at a time. A panic inside the simulator is caught and
+//! reported the same way; destroy the handle after one. The plant keeps no
+//! history (nothing could read it through this interface), whatever
+//! `[output] keep_history` says.
+
+use crate::config::{Config, OutputConfig};
+use crate::controller::{ControlAction, PulseCommand};
+use crate::plant::Plant;
+use crate::state::ConfinementMode;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::AssertUnwindSafe;
+
+/// `w7x_sim_get_profile` quantities, each on the radius grid: r / a,
+/// n_Z and n_e (m⁻³), T_e (keV), D_turb (m²/s).
+pub const W7X_PROFILE_RADIUS: c_int = 0;
+pub const W7X_PROFILE_IMPURITY_DENSITY: c_int = 1;
+pub const W7X_PROFILE_ELECTRON_DENSITY: c_int = 2;
+pub const W7X_PROFILE_ELECTRON_TEMP: c_int = 3;
+pub const W7X_PROFILE_TURBULENCE: c_int = 4;
+
+/// `w7x_sim_apply_action` kinds.
+pub const W7X_ACTION_HOLD: c_int = 0;
+pub const W7X_ACTION_PULSE: c_int = 1;
+
+/// Opaque simulator handle.
+pub struct W7xSim {
+    plant: Plant,
+}
+
+/// Latest diagnostic sample and plant mode.
+#[repr(C)]
+pub struct W7xObservation {
+    /// s
+    pub time: f64,
+    /// Measured n_Z(0) (m⁻³)
+    pub central_sxr: f64,
+    /// Measured edge n_Z (m⁻³)
+    pub edge_density: f64,
+    /// Measured edge D_turb (m²/s)
+    pub turbulence: f64,
+    pub pulse_active: bool,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn fail(message: impl ToString) {
+    let message = CString::new(message.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+}
+
+/// `body`'s result, or `on_panic` with the panic message set, so a
+/// simulator bug cannot unwind into (and abort) the C host.
+fn guarded<T>(on_panic: T, body: impl FnOnce() -> T) -> T {
+    std::panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|panic| {
+        let message = match panic.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => panic.downcast_ref::<String>().cloned().unwrap_or_default(),
+        };
+        fail(format!("simulator panicked: {}", message));
+        on_panic
+    })
+}
+
+/// Message of the last failed call on this thread, "" if none. Valid until
+/// the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn w7x_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
+
+/// Simulator from the run configuration at `config_path` (a `w7x.toml`),
+/// or the defaults for NULL. NULL on error.
+///
+/// # Safety
+/// `config_path` is NULL or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn w7x_sim_create(config_path: *const c_char) -> *mut W7xSim {
+    let config = if config_path.is_null() {
+        Ok(Config::default())
+    } else {
+        match CStr::from_ptr(config_path).to_str() {
+            Ok(path) => Config::load(path).map_err(|e| format!("could not load config {}: {}", path, e)),
+            Err(_) => Err("config path is not UTF-8".to_string()),
+        }
+    };
+    let mut config = match config {
+        Ok(config) => config,
+        Err(e) => {
+            fail(e);
+            return std::ptr::null_mut();
+        }
+    };
+    // The plant writes no files
+    config.output = OutputConfig { keep_history: false, ..OutputConfig::default() };
+    match guarded(None, || Some(Plant::try_new(&config))) {
+        Some(Ok(plant)) => Box::into_raw(Box::new(W7xSim { plant })),
+        Some(Err(e)) => {
+            fail(e);
+            std::ptr::null_mut()
+        }
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Frees a simulator from `w7x_sim_create`; NULL is ignored.
+///
+/// # Safety
+/// `sim` is NULL or a live handle, not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn w7x_sim_destroy(sim: *mut W7xSim) {
+    if !sim.is_null() {
+        drop(Box::from_raw(sim));
+    }
+}
+
+/// Advances the plasma by `duration` s of simulated time.
+///
+/// # Safety
+/// `sim` is a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn w7x_sim_step(sim: *mut W7xSim, duration: f64) -> c_int {
+    let Some(sim) = sim.as_mut() else {
+        fail("null simulator");
+        return -1;
+    };
+    if !(duration >= 0.0 && duration.is_finite()) {
+        fail(format!("step duration {} must be ≥ 0 and finite", duration));
+        return -1;
+    }
+    guarded(-1, || {
+        sim.plant.step(duration);
+        0
+    })
+}
+
+/// Simulated time (s); NaN for NULL.
+///
+/// # Safety
+/// `sim` is NULL or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn w7x_sim_time(sim: *const W7xSim) -> f64 {
+    sim.as_ref().map_or(f64::NAN, |sim| sim.plant.time())
+}
+
+/// Writes the latest observation to `out`.
+///
+/// # Safety
+/// `sim` is a live handle and `out` points to a writable `W7xObservation`.
+#[no_mangle]
+pub unsafe extern "C" fn w7x_sim_observe(sim: *const W7xSim, out: *mut W7xObservation) -> c_int {
+    let (Some(sim), false) = (sim.as_ref(), out.is_null()) else {
+        fail("null simulator or output");
+        return -1;
+    };
+    let observation = sim.plant.observe();
+    out.write(W7xObservation {
+        time: observation.time,
+        central_sxr: observation.measurement.central_sxr,
+        edge_density: observation.measurement.edge_density,
+        turbulence: observation.measurement.turbulence,
+        pulse_active: observation.mode == ConfinementMode::TurbulencePulse,
+    });
+    0
+}
+
+/// Copies up to `len` points of a `W7X_PROFILE_*` quantity, axis first,
+/// into `out`. Returns the number of grid points (call with `len` 0 to
+/// size the buffer), or 0 on error.
+///
+/// # Safety
+/// `sim` is a live handle and `out` has room for `len` doubles (or `len`
+/// is 0).
+#[no_mangle]
+pub unsafe extern "C" fn w7x_sim_get_profile(sim: *const W7xSim, profile: c_int, out: *mut f64, len: usize) -> usize {
+    let Some(sim) = sim.as_ref() else {
+        fail("null simulator");
+        return 0;
+    };
+    let state = sim.plant.state();
+    let turbulence;
+    let values = match profile {
+        W7X_PROFILE_RADIUS => &state.radius_grid,
+        W7X_PROFILE_IMPURITY_DENSITY => &state.impurity_density,
+        W7X_PROFILE_ELECTRON_DENSITY => &state.electron_density,
+        W7X_PROFILE_ELECTRON_TEMP => &state.electron_temp,
+        W7X_PROFILE_TURBULENCE => {
+            turbulence = state.turbulence_profile();
+            &turbulence
+        }
+        _ => {
+            fail(format!("unknown profile {}", profile));
+            return 0;
+        }
+    };
+    if len > 0 {
+        if out.is_null() {
+            fail("null output");
+            return 0;
+        }
+        let out = std::slice::from_raw_parts_mut(out, len);
+        for (slot, &value) in out.iter_mut().zip(values) {
+            *slot = value;
+        }
+    }
+    values.len()
+}
+
+/// Requests a `W7X_ACTION_*`. A pulse goes to `window` (index into
+/// `plasma.pulse_windows`; unknown = the default) at `amplitude` × D_turb
+/// (clamped to 1–10×; 0 = `plasma.pulse_amplitude`). The plant still
+/// enforces its pulse length and cooldown: check `pulse_active`.
+///
+/// # Safety
+/// `sim` is a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn w7x_sim_apply_action(sim: *mut W7xSim, action: c_int, window: c_int, amplitude: f64) -> c_int {
+    let Some(sim) = sim.as_mut() else {
+        fail("null simulator");
+        return -1;
+    };
+    let action = match action {
+        W7X_ACTION_HOLD => ControlAction::Hold,
+        W7X_ACTION_PULSE if amplitude.is_nan() || amplitude < 0.0 => {
+            fail(format!("invalid amplitude {}", amplitude));
+            return -1;
+        }
+        W7X_ACTION_PULSE => ControlAction::Pulse(PulseCommand {
+            window: window.max(0) as usize,
+            amplitude: (amplitude > 0.0).then_some(amplitude),
+        }),
+        _ => {
+            fail(format!("unknown action {}", action));
+            return -1;
+        }
+    };
+    guarded(-1, || {
+        sim.plant.actuate(action);
+        0
+    })
+}
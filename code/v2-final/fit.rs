@@ -0,0 +1,325 @@
+//! # Transport-Coefficient Fitting
+//!
+//! Inverse problem on top of `validate`: adjusts D_neo(r), v_neo(r), and
+//! the source strength until the simulated `[reference] channel` best
+//! reproduces the measured trace. The coefficients are piecewise linear
+//! between `knots` (ρ), i.e. a `[neoclassical] type = "table"`:
+//! ```text
+//! parameters  ln D_neo(ρ_k), v_neo(ρ_k) / velocity_scale, ln source
+//! cost        Σ (simulated − measured)² / Σ measured²    (normalized RMSE²)
+//! ```
+//! Minimized with Nelder–Mead, which needs no derivatives of the solver
+//! and tolerates the cost steps that pulses introduce. The start point is
+//! the configured model sampled at the knots; the initial simplex spans
+//! `initial_step` in every parameter. It stops after `max_evaluations`
+//! runs or once the costs across the simplex differ by less than
+//! `tolerance`. Runs that fail (e.g. numerical instability) cost ∞.
+//!
+//! The fitted table is written as `rho,d_neo,v_neo`, so it loads directly
+//! as `[neoclassical] type = "table", path = "<table>"`. "Source strength"
+//! is `plasma.impurity_source` for the constant source and
+//! `fuel_influx` for sputtering.
+
+use crate::config::Config;
+use crate::error::{Result, SimError};
+use crate::neoclassical::{Neoclassical, NeoclassicalTable};
+use crate::reference::{self, ReferenceTrace};
+use crate::source::SourceModel;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FitConfig {
+    pub knots: Vec<f64>,       // ρ of the fitted D_neo / v_neo values, ascending
+    pub fit_source: bool,      // Also fit the source strength
+    pub velocity_scale: f64,   // m/s per unit of the v_neo parameters
+    pub d_neo_min: f64,        // m²/s
+    pub d_neo_max: f64,        // m²/s
+    pub initial_step: f64,     // Initial simplex size, in parameter units
+    pub max_evaluations: usize,
+    pub tolerance: f64,        // Cost spread across the simplex at convergence
+    pub table: String,         // Fitted rho,d_neo,v_neo (CSV)
+    pub history: String,       // Every evaluation (CSV)
+}
+
+impl Default for FitConfig {
+    fn default() -> Self {
+        FitConfig {
+            knots: vec![0.0, 0.4, 0.7, 1.0],
+            fit_source: true,
+            velocity_scale: 0.5,
+            d_neo_min: 1e-4,
+            d_neo_max: 10.0,
+            initial_step: 0.3,
+            max_evaluations: 300,
+            tolerance: 1e-6,
+            table: "fitted_neoclassical.csv".to_string(),
+            history: "fit_history.csv".to_string(),
+        }
+    }
+}
+
+/// D_neo and v_neo at the knots, and the source strength.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Coefficients {
+    pub d_neo: Vec<f64>, // m²/s
+    pub v_neo: Vec<f64>, // m/s
+    pub source: f64,     // m⁻³/s
+}
+
+#[derive(Clone, Debug)]
+pub struct FitEvaluation {
+    pub coefficients: Coefficients,
+    pub cost: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Fit {
+    pub knots: Vec<f64>,
+    pub initial: FitEvaluation,
+    pub best: FitEvaluation,
+    pub evaluations: Vec<FitEvaluation>,
+    pub converged: bool,
+}
+
+pub fn source_strength(config: &Config) -> f64 {
+    match &config.plasma.source {
+        SourceModel::Constant => config.plasma.impurity_source,
+        SourceModel::Sputtering(s) => s.fuel_influx,
+    }
+}
+
+fn set_source_strength(config: &mut Config, value: f64) {
+    match &mut config.plasma.source {
+        SourceModel::Constant => config.plasma.impurity_source = value,
+        SourceModel::Sputtering(s) => s.fuel_influx = value,
+    }
+}
+
+impl Coefficients {
+    /// The configured model at `knots`. The collisionality-regime model
+    /// depends on the evolving profiles, so it starts from its reference
+    /// values.
+    pub fn from_config(config: &Config, knots: &[f64]) -> Self {
+        let (d_neo, v_neo) = match &config.neoclassical {
+            Neoclassical::Table(table) if !table.rho.is_empty() => (
+                knots.iter().map(|&r| table.interpolate(&table.d_neo, r)).collect(),
+                knots.iter().map(|&r| table.interpolate(&table.v_neo, r)).collect(),
+            ),
+            _ => (vec![config.plasma.d_neo; knots.len()], vec![config.plasma.v_neo; knots.len()]),
+        };
+        Coefficients { d_neo, v_neo, source: source_strength(config) }
+    }
+
+    /// `config` with these coefficients as a neoclassical table at `knots`.
+    pub fn apply(&self, config: &Config, knots: &[f64], table_path: &str) -> Config {
+        let mut config = config.clone();
+        config.neoclassical = Neoclassical::Table(NeoclassicalTable {
+            path: table_path.to_string(),
+            rho: knots.to_vec(),
+            d_neo: self.d_neo.clone(),
+            v_neo: self.v_neo.clone(),
+        });
+        set_source_strength(&mut config, self.source);
+        config
+    }
+}
+
+/// Maps between coefficients and the unconstrained parameter vector.
+struct Parameters<'a> {
+    settings: &'a FitConfig,
+    source: f64, // Held fixed unless `fit_source`
+}
+
+impl Parameters<'_> {
+    fn encode(&self, c: &Coefficients) -> Vec<f64> {
+        let mut x: Vec<f64> = c.d_neo.iter().map(|d| d.max(self.settings.d_neo_min).ln()).collect();
+        x.extend(c.v_neo.iter().map(|v| v / self.settings.velocity_scale));
+        if self.settings.fit_source {
+            x.push(c.source.max(1e-30).ln());
+        }
+        x
+    }
+
+    fn decode(&self, x: &[f64]) -> Coefficients {
+        let n = self.settings.knots.len();
+        let (d_min, d_max) = (self.settings.d_neo_min, self.settings.d_neo_max);
+        Coefficients {
+            d_neo: x[..n].iter().map(|p| p.exp().clamp(d_min, d_max)).collect(),
+            v_neo: x[n..2 * n].iter().map(|p| p * self.settings.velocity_scale).collect(),
+            source: if self.settings.fit_source { x[2 * n].exp() } else { self.source },
+        }
+    }
+}
+
+/// Normalized RMSE² of `coefficients` against the reference; ∞ if the
+/// run fails.
+pub fn cost(
+    config: &Config,
+    knots: &[f64],
+    coefficients: &Coefficients,
+    reference: &ReferenceTrace,
+) -> f64 {
+    let settings = &config.reference;
+    let config = coefficients.apply(config, knots, &config.fit.table);
+    match reference::simulate(&config, reference, settings.channel, settings.scale) {
+        Ok(simulated) => {
+            let normalized_rmse = reference::mismatch(reference, &simulated, 0.0).normalized_rmse;
+            if normalized_rmse.is_finite() { normalized_rmse.powi(2) } else { f64::INFINITY }
+        }
+        Err(_) => f64::INFINITY,
+    }
+}
+
+/// Nelder–Mead minimization of `f` from `x0`, with an initial simplex of
+/// size `step` along every axis. Returns every evaluated point and
+/// whether the simplex collapsed below `tolerance` before
+/// `max_evaluations`.
+pub fn nelder_mead<F: FnMut(&[f64]) -> f64>(
+    mut f: F,
+    x0: &[f64],
+    step: f64,
+    max_evaluations: usize,
+    tolerance: f64,
+) -> (Vec<(Vec<f64>, f64)>, bool) {
+    let n = x0.len();
+    let mut evaluations = Vec::new();
+    let mut evaluate = |x: Vec<f64>, evaluations: &mut Vec<(Vec<f64>, f64)>| {
+        let cost = f(&x);
+        evaluations.push((x.clone(), cost));
+        (x, cost)
+    };
+    let mut simplex: Vec<(Vec<f64>, f64)> = vec![evaluate(x0.to_vec(), &mut evaluations)];
+    for i in 0..n {
+        let mut x = x0.to_vec();
+        x[i] += step;
+        simplex.push(evaluate(x, &mut evaluations));
+    }
+
+    let combine = |a: &[f64], b: &[f64], t: f64| -> Vec<f64> {
+        a.iter().zip(b).map(|(a, b)| a + t * (b - a)).collect()
+    };
+    loop {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (best, worst) = (simplex[0].1, simplex[n].1);
+        if worst - best < tolerance {
+            return (evaluations, true);
+        }
+        if evaluations.len() >= max_evaluations {
+            return (evaluations, false);
+        }
+
+        let centroid: Vec<f64> = (0..n)
+            .map(|j| simplex[..n].iter().map(|(x, _)| x[j]).sum::<f64>() / n as f64)
+            .collect();
+        let reflected = evaluate(combine(&centroid, &simplex[n].0, -1.0), &mut evaluations);
+        if reflected.1 < best {
+            let expanded = evaluate(combine(&centroid, &simplex[n].0, -2.0), &mut evaluations);
+            simplex[n] = if expanded.1 < reflected.1 { expanded } else { reflected };
+        } else if reflected.1 < simplex[n - 1].1 {
+            simplex[n] = reflected;
+        } else {
+            // Contract towards the better of the worst point and its reflection
+            let outside = reflected.1 < worst;
+            let toward = if outside { &reflected.0 } else { &simplex[n].0 };
+            let contracted = evaluate(combine(&centroid, toward, 0.5), &mut evaluations);
+            if contracted.1 < reflected.1.min(worst) {
+                simplex[n] = contracted;
+            } else {
+                let best_point = simplex[0].0.clone();
+                for vertex in simplex.iter_mut().skip(1) {
+                    *vertex = evaluate(combine(&best_point, &vertex.0, 0.5), &mut evaluations);
+                }
+            }
+        }
+    }
+}
+
+/// Fits `config.fit` coefficients to `reference`.
+pub fn fit(config: &Config, reference: &ReferenceTrace) -> Result<Fit> {
+    let settings = &config.fit;
+    let knots = &settings.knots;
+    if knots.is_empty() || !knots.windows(2).all(|w| w[1] > w[0]) {
+        return Err(SimError::invalid("fit.knots", "needs at least one ρ, ascending"));
+    }
+    if settings.d_neo_min <= 0.0 || settings.d_neo_max < settings.d_neo_min {
+        return Err(SimError::invalid("fit.d_neo_min", "needs 0 < d_neo_min ≤ d_neo_max"));
+    }
+    if settings.velocity_scale <= 0.0 {
+        return Err(SimError::invalid("fit.velocity_scale", "must be positive"));
+    }
+    config.validate()?;
+
+    let start = Coefficients::from_config(config, knots);
+    let parameters = Parameters { settings, source: start.source };
+    let (points, converged) = nelder_mead(
+        |x| cost(config, knots, &parameters.decode(x), reference),
+        &parameters.encode(&start),
+        settings.initial_step,
+        settings.max_evaluations,
+        settings.tolerance,
+    );
+    let evaluations: Vec<FitEvaluation> = points
+        .iter()
+        .map(|(x, cost)| FitEvaluation { coefficients: parameters.decode(x), cost: *cost })
+        .collect();
+    let best = evaluations
+        .iter()
+        .min_by(|a, b| a.cost.total_cmp(&b.cost))
+        .cloned()
+        .expect("Nelder–Mead evaluates the start point");
+    if !best.cost.is_finite() {
+        return Err(SimError::invalid("fit", "every evaluation failed; check the [fit] bounds"));
+    }
+    Ok(Fit { knots: knots.clone(), initial: evaluations[0].clone(), best, evaluations, converged })
+}
+
+impl Fit {
+    /// `rho,d_neo,v_neo`, loadable as a neoclassical table.
+    #[cfg(feature = "fs")]
+    pub fn write_table<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        metadata: &crate::metadata::RunMetadata,
+    ) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        metadata.write_csv_header(&mut writer)?;
+        writeln!(writer, "rho,d_neo,v_neo")?;
+        let c = &self.best.coefficients;
+        for ((rho, d), v) in self.knots.iter().zip(&c.d_neo).zip(&c.v_neo) {
+            writeln!(writer, "{},{:.6e},{:.6e}", rho, d, v)?;
+        }
+        writer.flush()
+    }
+
+    /// One row per evaluation: `evaluation,cost,source,d_neo_<k>...,v_neo_<k>...`.
+    #[cfg(feature = "fs")]
+    pub fn write_history<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        metadata: &crate::metadata::RunMetadata,
+    ) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        metadata.write_csv_header(&mut writer)?;
+        write!(writer, "evaluation,cost,source")?;
+        for prefix in ["d_neo", "v_neo"] {
+            for rho in &self.knots {
+                write!(writer, ",{}_{}", prefix, rho)?;
+            }
+        }
+        writeln!(writer)?;
+        for (i, e) in self.evaluations.iter().enumerate() {
+            let c = &e.coefficients;
+            write!(writer, "{},{:.6e},{:.6e}", i, e.cost, c.source)?;
+            for value in c.d_neo.iter().chain(&c.v_neo) {
+                write!(writer, ",{:.6e}", value)?;
+            }
+            writeln!(writer)?;
+        }
+        writer.flush()
+    }
+}
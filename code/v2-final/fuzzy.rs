@@ -0,0 +1,233 @@
+//! # Fuzzy-Logic Control
+//!
+//! A fuzzy inference controller (`[controller.fuzzy]`) between the
+//! threshold logic and model-based control: instead of a pulse at a hard
+//! threshold, overlapping fuzzy sets over n_Z(0), its growth rate, and the
+//! edge D_turb fire a rule base that grades the response. Each rule is
+//!
+//! ```toml
+//! { when = { central_sxr = "high", growth = "rising" }, then = "strong", weight = 1.0 }
+//! ```
+//!
+//! with its conditions combined by AND (minimum). The rules' conclusions
+//! are pulse amplitudes (`outputs`, 0 = no pulse) and are defuzzified as
+//! the firing-strength weighted mean (zero-order Sugeno). At or above
+//! `pulse_above` the controller requests a pulse at that amplitude,
+//! clamped to 1–10×; below, or when no rule fires, it holds. The default
+//! rule base pulses from about n_Z(0) = 6.2e17 m⁻³, gently at first and
+//! at full strength above 1.2e18 m⁻³ or on fast growth.
+
+use crate::controller::{ControlAction, Controller, PulseCommand, MAX_AMPLITUDE, MIN_AMPLITUDE};
+use crate::diagnostics::Measurement;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Input {
+    CentralSxr, // n_Z(0), m⁻³
+    Growth,     // dn_Z(0)/dt, m⁻³/s
+    Turbulence, // Edge D_turb, m²/s
+}
+
+impl Input {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Input::CentralSxr => "central_sxr",
+            Input::Growth => "growth",
+            Input::Turbulence => "turbulence",
+        }
+    }
+}
+
+/// Degree of membership of a crisp value in a fuzzy set. A shoulder is a
+/// trapezoid with `a = b = -inf` or `c = d = inf`.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Membership {
+    /// 0 outside (a, c), 1 at b.
+    Triangle { a: f64, b: f64, c: f64 },
+    /// 0 outside (a, d), 1 on [b, c].
+    Trapezoid { a: f64, b: f64, c: f64, d: f64 },
+    Gaussian { center: f64, width: f64 },
+}
+
+impl Membership {
+    pub fn degree(&self, x: f64) -> f64 {
+        match *self {
+            Membership::Triangle { a, b, c } => Membership::Trapezoid { a, b, c: b, d: c }.degree(x),
+            Membership::Trapezoid { a, b, c, d } => {
+                if x < a || x > d {
+                    0.0
+                } else if x < b {
+                    (x - a) / (b - a)
+                } else if x <= c {
+                    1.0
+                } else {
+                    (d - x) / (d - c)
+                }
+            }
+            Membership::Gaussian { center, width } => (-0.5 * ((x - center) / width).powi(2)).exp(),
+        }
+    }
+
+    /// Breakpoints in order and widths positive.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Membership::Triangle { a, b, c } => a <= b && b <= c,
+            Membership::Trapezoid { a, b, c, d } => a <= b && b <= c && c <= d,
+            Membership::Gaussian { center, width } => center.is_finite() && width > 0.0 && width.is_finite(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Rule {
+    pub when: BTreeMap<Input, String>, // Input → set name, all must hold
+    pub then: String,                  // Output name
+    #[serde(default = "unit_weight")]
+    pub weight: f64,
+}
+
+fn unit_weight() -> f64 {
+    1.0
+}
+
+/// Fuzzy sets, outputs, and rule base (`[controller.fuzzy]`).
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FuzzyConfig {
+    pub central_sxr: BTreeMap<String, Membership>,
+    pub growth: BTreeMap<String, Membership>,
+    pub turbulence: BTreeMap<String, Membership>,
+    pub outputs: BTreeMap<String, f64>, // Name → pulse amplitude, 0 = none
+    pub rules: Vec<Rule>,
+    pub rate_window: f64, // s, span of the n_Z(0) slope for `growth`
+    pub pulse_above: f64, // Defuzzified amplitude that triggers a pulse
+}
+
+impl Default for FuzzyConfig {
+    fn default() -> Self {
+        let set = |a: f64, b: f64, c: f64, d: f64| Membership::Trapezoid { a, b, c, d };
+        let rule = |when: &[(Input, &str)], then: &str| Rule {
+            when: when.iter().map(|&(input, set)| (input, set.to_string())).collect(),
+            then: then.to_string(),
+            weight: 1.0,
+        };
+        FuzzyConfig {
+            central_sxr: BTreeMap::from([
+                ("low".to_string(), set(f64::NEG_INFINITY, f64::NEG_INFINITY, 4e17, 7e17)),
+                ("medium".to_string(), Membership::Triangle { a: 4e17, b: 8e17, c: 1.2e18 }),
+                ("high".to_string(), set(8e17, 1.2e18, f64::INFINITY, f64::INFINITY)),
+            ]),
+            growth: BTreeMap::from([
+                ("steady".to_string(), set(f64::NEG_INFINITY, f64::NEG_INFINITY, 5e17, 1.5e18)),
+                ("rising".to_string(), set(5e17, 1.5e18, f64::INFINITY, f64::INFINITY)),
+            ]),
+            turbulence: BTreeMap::from([
+                ("quiet".to_string(), set(f64::NEG_INFINITY, f64::NEG_INFINITY, 2.0, 4.0)),
+                ("enhanced".to_string(), set(2.0, 4.0, f64::INFINITY, f64::INFINITY)),
+            ]),
+            outputs: BTreeMap::from([
+                ("none".to_string(), 0.0),
+                ("weak".to_string(), 3.0),
+                ("strong".to_string(), 8.0),
+            ]),
+            rules: vec![
+                rule(&[(Input::CentralSxr, "low"), (Input::Growth, "steady")], "none"),
+                rule(&[(Input::CentralSxr, "medium")], "weak"),
+                rule(&[(Input::CentralSxr, "medium"), (Input::Turbulence, "enhanced")], "none"),
+                rule(&[(Input::CentralSxr, "high")], "strong"),
+                rule(&[(Input::Growth, "rising")], "strong"),
+            ],
+            rate_window: 0.002,
+            pulse_above: 2.0,
+        }
+    }
+}
+
+impl FuzzyConfig {
+    pub fn sets(&self, input: Input) -> &BTreeMap<String, Membership> {
+        match input {
+            Input::CentralSxr => &self.central_sxr,
+            Input::Growth => &self.growth,
+            Input::Turbulence => &self.turbulence,
+        }
+    }
+
+    /// Defuzzified amplitude for crisp inputs; `None` when no rule fires.
+    pub fn infer(&self, central_sxr: f64, growth: f64, turbulence: f64) -> Option<f64> {
+        let (mut weighted, mut total) = (0.0, 0.0);
+        for rule in &self.rules {
+            let strength = rule.when.iter().fold(1.0_f64, |strength, (input, set)| {
+                let x = match input {
+                    Input::CentralSxr => central_sxr,
+                    Input::Growth => growth,
+                    Input::Turbulence => turbulence,
+                };
+                let degree = self.sets(*input).get(set).map_or(0.0, |m| m.degree(x));
+                strength.min(degree)
+            }) * rule.weight;
+            if strength > 0.0 {
+                weighted += strength * self.outputs.get(&rule.then).copied().unwrap_or(0.0);
+                total += strength;
+            }
+        }
+        (total > 0.0).then(|| weighted / total)
+    }
+}
+
+pub struct FuzzyController {
+    config: FuzzyConfig,
+    window: usize,                 // Pulse window to request; 0 = plant default
+    samples: VecDeque<(f64, f64)>, // (time, n_Z(0)) over `rate_window`
+}
+
+impl FuzzyController {
+    pub fn new(config: &FuzzyConfig, window: usize) -> Self {
+        FuzzyController {
+            config: config.clone(),
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Slope of n_Z(0) over the last `rate_window`; 0 until it is covered.
+    fn growth(&mut self, time: f64, central_sxr: f64) -> f64 {
+        self.samples.push_back((time, central_sxr));
+        while self.samples.len() > 2 && time - self.samples[1].0 >= self.config.rate_window {
+            self.samples.pop_front();
+        }
+        let (t0, x0) = self.samples[0];
+        let span = time - t0;
+        if span >= self.config.rate_window && span > 0.0 {
+            (central_sxr - x0) / span
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Controller for FuzzyController {
+    fn decide(&mut self, measurement: &Measurement) -> ControlAction {
+        let growth = self.growth(measurement.time, measurement.central_sxr);
+        let output = self.config.infer(measurement.central_sxr, growth, measurement.turbulence);
+        tracing::trace!(time = measurement.time, growth, ?output, "fuzzy inference");
+        match output {
+            Some(amplitude) if amplitude >= self.config.pulse_above => ControlAction::Pulse(PulseCommand {
+                window: self.window,
+                amplitude: Some(amplitude.clamp(MIN_AMPLITUDE, MAX_AMPLITUDE)),
+            }),
+            _ => ControlAction::Hold,
+        }
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(&self.samples).ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> serde_json::Result<()> {
+        self.samples = serde_json::from_value(state)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+//! # Gain Scheduling
+//!
+//! Thresholds tuned for one operating point do not transfer to others:
+//! the n_Z(0) that calls for a pulse in a low-density discharge is not the
+//! one of a high-density discharge. A `GainSchedule`
+//! (`[controller.schedule]`) picks a row of a table by a regime indicator
+//! and retunes the threshold controller with it:
+//!
+//! ```toml
+//! [controller.schedule]
+//! indicator = "density"
+//! regimes = [
+//!     { name = "low_density", below = 3e19, thresholds = { central_level = 5e17 } },
+//!     { name = "high_density", thresholds = { central_level = 1.2e18 }, amplitude = 6.0 },
+//! ]
+//! ```
+//!
+//! Rows are in increasing order of `below`; the first whose bound is above
+//! the indicator applies, and the last has no bound. Alarms a row does not
+//! name keep their `[detection]` thresholds, and `window` and `amplitude`
+//! fall back to `[controller]`. The indicators are operating parameters,
+//! not the impurity state under control: ν*_e at mid-radius, line-averaged
+//! n_e (interferometer), and the programmed heating power. Leaving a row
+//! takes the indicator past the row's bound by the relative `hysteresis`,
+//! so noise at a boundary does not toggle the gains. Scripted, plugin, and
+//! fuzzy controllers take no schedule.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Operating point passed to the controller before each decision.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Regime {
+    pub collisionality: f64, // ν*_e at mid-radius
+    pub density: f64,        // Line-averaged n_e, m⁻³
+    pub heating_power: f64,  // MW, programmed (scenario), without ECRH pulses
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Indicator {
+    #[default]
+    Density,
+    Collisionality,
+    HeatingPower,
+}
+
+impl Indicator {
+    pub fn read(&self, regime: &Regime) -> f64 {
+        match self {
+            Indicator::Density => regime.density,
+            Indicator::Collisionality => regime.collisionality,
+            Indicator::HeatingPower => regime.heating_power,
+        }
+    }
+}
+
+/// One row of the schedule.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduledGains {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub below: Option<f64>, // Upper bound of the indicator; None on the last row
+    pub thresholds: BTreeMap<String, f64>, // Alarm name → threshold
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<usize>, // Pulse window to request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amplitude: Option<f64>, // Pulse amplitude (first step with escalation)
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GainSchedule {
+    pub indicator: Indicator,
+    pub hysteresis: f64, // Relative margin past a bound before switching
+    pub regimes: Vec<ScheduledGains>,
+}
+
+impl Default for GainSchedule {
+    fn default() -> Self {
+        GainSchedule {
+            indicator: Indicator::Density,
+            hysteresis: 0.05,
+            regimes: Vec::new(),
+        }
+    }
+}
+
+impl GainSchedule {
+    /// Index of the row for `value`, without hysteresis.
+    pub fn lookup(&self, value: f64) -> usize {
+        self.regimes
+            .iter()
+            .position(|row| !row.below.is_some_and(|below| value >= below))
+            .unwrap_or(self.regimes.len().saturating_sub(1))
+    }
+}
+
+/// The row of a `GainSchedule` a controller has applied.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct ScheduleTracker {
+    active: Option<usize>,
+}
+
+impl ScheduleTracker {
+    /// Row currently applied; `None` before the first `update`.
+    pub fn active<'a>(&self, schedule: &'a GainSchedule) -> Option<&'a ScheduledGains> {
+        self.active.map(|i| &schedule.regimes[i])
+    }
+
+    /// Follows the indicator. Returns the row to apply when it changes,
+    /// including on the first call.
+    pub fn update<'a>(&mut self, schedule: &'a GainSchedule, regime: &Regime) -> Option<&'a ScheduledGains> {
+        let value = schedule.indicator.read(regime);
+        if schedule.regimes.is_empty() || !value.is_finite() {
+            return None;
+        }
+        let next = match self.active {
+            None => schedule.lookup(value),
+            Some(current) => {
+                let up = schedule.lookup(value / (1.0 + schedule.hysteresis));
+                let down = schedule.lookup(value * (1.0 + schedule.hysteresis));
+                if up > current {
+                    up
+                } else if down < current {
+                    down
+                } else {
+                    return None;
+                }
+            }
+        };
+        self.active = Some(next);
+        Some(&schedule.regimes[next])
+    }
+}
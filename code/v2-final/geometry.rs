@@ -0,0 +1,250 @@
+//! # Flux-Surface Geometry
+//!
+//! The transport equation in flux coordinates ρ = √(s):
+//!
+//! ```text
+//! ∂n/∂t = −(1/V') ∂/∂ρ [V' (⟨|∇ρ|⟩ v n − ⟨|∇ρ|²⟩ D ∂n/∂ρ)] + S
+//! ```
+//!
+//! The cylinder (V' ∝ ρ, ⟨|∇ρ|⟩ = ⟨|∇ρ|²⟩ = 1) is the v2 model. The
+//! `[equilibrium]` section can instead supply the metric from a table or a
+//! VMEC wout file (feature `netcdf`); D and v then keep their physical
+//! units and ⟨|∇ρ|²⟩ carries the 1/a² of the real device. Pellet, ECRH,
+//! and recycling volume integrals remain cylindrical.
+
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::io;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Equilibrium {
+    #[default]
+    Cylindrical,
+    /// CSV with header `rho,vprime,grad_rho,grad_rho2`, ρ ascending.
+    Table { path: String },
+    /// VMEC `wout_*.nc` (stellarator-symmetric), needs the `netcdf` feature.
+    Vmec { path: String },
+}
+
+/// Surface-averaged metric on a ρ grid of the equilibrium.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FluxSurfaces {
+    pub rho: Vec<f64>,
+    pub vprime: Vec<f64>,    // dV/dρ (m³); only its shape matters
+    pub grad_rho: Vec<f64>,  // ⟨|∇ρ|⟩ (1/m)
+    pub grad_rho2: Vec<f64>, // ⟨|∇ρ|²⟩ (1/m²)
+}
+
+impl FluxSurfaces {
+    /// Linear interpolation, constant beyond the ends of the table.
+    fn interpolate(&self, values: &[f64], rho: f64) -> f64 {
+        let n = self.rho.len();
+        if rho <= self.rho[0] {
+            return values[0];
+        }
+        if rho >= self.rho[n - 1] {
+            return values[n - 1];
+        }
+        let j = self.rho.partition_point(|&x| x <= rho);
+        let w = (rho - self.rho[j - 1]) / (self.rho[j] - self.rho[j - 1]);
+        values[j - 1] + w * (values[j] - values[j - 1])
+    }
+
+    /// V'(ρ), extrapolated linearly to 0 on axis as for any regular equilibrium.
+    fn vprime_at(&self, rho: f64) -> f64 {
+        if rho < self.rho[0] && self.rho[0] > 0.0 {
+            return self.vprime[0] * rho / self.rho[0];
+        }
+        self.interpolate(&self.vprime, rho)
+    }
+
+    #[cfg(feature = "fs")]
+    fn validate(self) -> io::Result<Self> {
+        let n = self.rho.len();
+        let consistent = n >= 2
+            && self.vprime.len() == n
+            && self.grad_rho.len() == n
+            && self.grad_rho2.len() == n
+            && self.rho.windows(2).all(|w| w[1] > w[0]);
+        if consistent {
+            Ok(self)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "flux-surface table needs at least two rows with ascending rho",
+            ))
+        }
+    }
+}
+
+impl Equilibrium {
+    /// Reads the equilibrium file; `None` for the built-in cylinder.
+    #[cfg(feature = "fs")]
+    pub fn load(&self) -> io::Result<Option<FluxSurfaces>> {
+        match self {
+            Equilibrium::Cylindrical => Ok(None),
+            Equilibrium::Table { path } => read_table(path).map(Some),
+            Equilibrium::Vmec { path } => read_vmec(path).map(Some),
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+fn read_table<P: AsRef<Path>>(path: P) -> io::Result<FluxSurfaces> {
+    let text = std::fs::read_to_string(path)?;
+    let mut surfaces = FluxSurfaces {
+        rho: Vec::new(),
+        vprime: Vec::new(),
+        grad_rho: Vec::new(),
+        grad_rho2: Vec::new(),
+    };
+    for (line_no, line) in text.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<f64> = line
+            .split(',')
+            .map(|f| f.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line_no + 1, e)))?;
+        let [rho, vprime, grad_rho, grad_rho2] = fields[..] else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: expected 4 columns", line_no + 1),
+            ));
+        };
+        surfaces.rho.push(rho);
+        surfaces.vprime.push(vprime);
+        surfaces.grad_rho.push(grad_rho);
+        surfaces.grad_rho2.push(grad_rho2);
+    }
+    surfaces.validate()
+}
+
+/// Surface averages from the Fourier representation of R and Z:
+/// √g = R (R_θ Z_s − R_s Z_θ), |∇s|² = (g_θθ g_ζζ − g_θζ²) / g.
+#[cfg(feature = "netcdf")]
+fn read_vmec<P: AsRef<Path>>(path: P) -> io::Result<FluxSurfaces> {
+    use std::f64::consts::PI;
+
+    let file = netcdf::open(path).map_err(io::Error::other)?;
+    let read = |name: &str| -> io::Result<Vec<f64>> {
+        file.variable(name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("wout has no `{}`", name)))?
+            .get_values::<f64, _>(..)
+            .map_err(io::Error::other)
+    };
+    let xm = read("xm")?;
+    let xn = read("xn")?;
+    let rmnc = read("rmnc")?;
+    let zmns = read("zmns")?;
+    let nfp = read("nfp")?.first().copied().unwrap_or(1.0);
+    let mnmax = xm.len();
+    let ns = rmnc.len() / mnmax.max(1);
+    if ns < 4 || xn.len() != mnmax || zmns.len() != rmnc.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "inconsistent wout dimensions"));
+    }
+
+    const NTHETA: usize = 64;
+    const NZETA: usize = 32;
+    let ds = 1.0 / (ns - 1) as f64;
+    // R, Z and their angular derivatives on one surface at (θ, ζ)
+    let evaluate = |j: usize, theta: f64, zeta: f64| {
+        let (mut r, mut z, mut r_t, mut z_t, mut r_z, mut z_z) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        for k in 0..mnmax {
+            let arg = xm[k] * theta - xn[k] * zeta;
+            let (sin, cos) = arg.sin_cos();
+            let (rc, zs) = (rmnc[j * mnmax + k], zmns[j * mnmax + k]);
+            r += rc * cos;
+            z += zs * sin;
+            r_t -= xm[k] * rc * sin;
+            z_t += xm[k] * zs * cos;
+            r_z += xn[k] * rc * sin;
+            z_z -= xn[k] * zs * cos;
+        }
+        (r, z, r_t, z_t, r_z, z_z)
+    };
+
+    let mut surfaces = FluxSurfaces {
+        rho: Vec::new(),
+        vprime: Vec::new(),
+        grad_rho: Vec::new(),
+        grad_rho2: Vec::new(),
+    };
+    let d_area = (2.0 * PI / NTHETA as f64) * (2.0 * PI / nfp / NZETA as f64);
+    for j in 1..ns - 1 {
+        let s = j as f64 * ds;
+        let (mut jacobian, mut grad, mut grad2) = (0.0, 0.0, 0.0);
+        for it in 0..NTHETA {
+            let theta = 2.0 * PI * it as f64 / NTHETA as f64;
+            for iz in 0..NZETA {
+                let zeta = 2.0 * PI / nfp * iz as f64 / NZETA as f64;
+                let (r, _, r_t, z_t, r_z, z_z) = evaluate(j, theta, zeta);
+                let (r_p, z_p, ..) = evaluate(j + 1, theta, zeta);
+                let (r_m, z_m, ..) = evaluate(j - 1, theta, zeta);
+                let r_s = (r_p - r_m) / (2.0 * ds);
+                let z_s = (z_p - z_m) / (2.0 * ds);
+                let sqrt_g = (r * (r_t * z_s - r_s * z_t)).abs();
+                let g_tt = r_t * r_t + z_t * z_t;
+                let g_zz = r_z * r_z + r * r + z_z * z_z;
+                let g_tz = r_t * r_z + z_t * z_z;
+                let grad_s2 = (g_tt * g_zz - g_tz * g_tz) / (sqrt_g * sqrt_g).max(1e-30);
+                jacobian += sqrt_g;
+                grad += sqrt_g * grad_s2.sqrt();
+                grad2 += sqrt_g * grad_s2;
+            }
+        }
+        let rho = s.sqrt();
+        // dV/ds = nfp ∮ √g dθ dζ over one period; ρ = √s gives the 2ρ and 1/(4s)
+        surfaces.rho.push(rho);
+        surfaces.vprime.push(nfp * jacobian * d_area * 2.0 * rho);
+        surfaces.grad_rho.push(grad / jacobian / (2.0 * rho));
+        surfaces.grad_rho2.push(grad2 / jacobian / (4.0 * s));
+    }
+    surfaces.validate()
+}
+
+#[cfg(all(feature = "fs", not(feature = "netcdf")))]
+fn read_vmec<P: AsRef<Path>>(_path: P) -> io::Result<FluxSurfaces> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "VMEC equilibria need `--features netcdf`",
+    ))
+}
+
+/// Metric coefficients on the transport grid.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Metric {
+    pub vprime: Array1<f64>,       // V' at grid points
+    pub vprime_outer: Array1<f64>, // V' at r + dr/2
+    pub vprime_inner: Array1<f64>, // V' at r − dr/2
+    pub grad_rho: Array1<f64>,     // ⟨|∇ρ|⟩, multiplies the pinch
+    pub grad_rho2: Array1<f64>,    // ⟨|∇ρ|²⟩, multiplies the diffusivity
+}
+
+impl Metric {
+    /// V' = ρ, unit gradients (v2).
+    pub fn cylindrical(radius: &Array1<f64>, dr: f64) -> Self {
+        Metric {
+            vprime: radius.clone(),
+            vprime_outer: radius.mapv(|r| r + 0.5 * dr),
+            vprime_inner: radius.mapv(|r| r - 0.5 * dr),
+            grad_rho: Array1::ones(radius.len()),
+            grad_rho2: Array1::ones(radius.len()),
+        }
+    }
+
+    pub fn from_surfaces(surfaces: &FluxSurfaces, radius: &Array1<f64>, dr: f64) -> Self {
+        Metric {
+            vprime: radius.mapv(|r| surfaces.vprime_at(r)),
+            vprime_outer: radius.mapv(|r| surfaces.vprime_at(r + 0.5 * dr)),
+            vprime_inner: radius.mapv(|r| surfaces.vprime_at((r - 0.5 * dr).max(0.0))),
+            grad_rho: radius.mapv(|r| surfaces.interpolate(&surfaces.grad_rho, r)),
+            grad_rho2: radius.mapv(|r| surfaces.interpolate(&surfaces.grad_rho2, r)),
+        }
+    }
+}
@@ -0,0 +1,370 @@
+//! # GPU Transport Kernel
+//!
+//! `[poloidal] backend = "gpu"` (`gpu` feature) runs the two grid-wide
+//! parts of the 2D step on a GPU through wgpu (Vulkan, Metal, DX12, or
+//! OpenGL): the radial flux divergence of every θ column, and the implicit
+//! poloidal update, a dense ntheta × ntheta matrix per radius that
+//! dominates the cost at high ntheta (nr · ntheta² per step). Boundary
+//! closing, the poloidal average, and everything outside the 2D solver
+//! (coefficients, diagnostics, control) stay on the CPU, so n_Z(r, θ) makes
+//! a round trip every step.
+//!
+//! The kernel computes in f32 (portable shaders have no f64) and
+//! implements the second-order stencil with every `convection` scheme.
+//! Without a usable adapter the solver warns once and continues on the
+//! CPU. `tests/gpu.rs` checks it against the CPU solver (on Mesa's
+//! llvmpipe in CI-like setups); it has not been timed on GPU hardware.
+
+use crate::convection::Convection;
+use crate::poloidal::RadialTerms;
+use crate::state::MAX_IMPURITY_DENSITY;
+use ndarray::Array2;
+use std::fmt;
+
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_GROUPS_PER_DIMENSION: u32 = 65535;
+
+const SHADER: &str = r#"
+struct Params {
+    nr: u32,
+    ntheta: u32,
+    convection: u32,
+    dt: f32,
+    max_density: f32,
+    two_dr: f32,
+    _padding: vec2<f32>,
+}
+
+// Per radius: pinch, D, V'-weights of the inner and outer face flux
+// (1 / dr for the planar axis cell), wall source
+struct Cell {
+    velocity: f32,
+    diffusivity: f32,
+    inner: f32,
+    outer: f32,
+    source: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> cells: array<Cell>;
+@group(0) @binding(2) var<storage, read> density: array<f32>;
+@group(0) @binding(3) var<storage, read_write> next: array<f32>;
+@group(0) @binding(4) var<storage, read> implicit: array<f32>;
+@group(0) @binding(5) var<storage, read_write> result: array<f32>;
+
+fn n(i: u32, j: u32) -> f32 {
+    return density[i * params.ntheta + j];
+}
+
+// `Convection::face_density` with the central value n_i
+fn face_density(i: u32, j: u32, v: f32) -> f32 {
+    let central = n(i, j);
+    if params.convection == 0u {
+        return central;
+    }
+    var upwind: f32;
+    var downwind: f32;
+    var behind = 0.0;
+    var has_behind: bool;
+    if v >= 0.0 {
+        upwind = n(i, j);
+        downwind = n(i + 1u, j);
+        has_behind = i >= 1u;
+        if has_behind {
+            behind = n(i - 1u, j);
+        }
+    } else {
+        upwind = n(i + 1u, j);
+        downwind = n(i, j);
+        has_behind = i + 2u < params.nr;
+        if has_behind {
+            behind = n(i + 2u, j);
+        }
+    }
+    if params.convection == 1u || !has_behind {
+        return upwind;
+    }
+    let local = downwind - upwind;
+    if local == 0.0 {
+        return upwind;
+    }
+    let theta = (upwind - behind) / local;
+    var psi: f32;
+    if params.convection == 2u {
+        psi = (theta + abs(theta)) / (1.0 + abs(theta));
+    } else {
+        psi = min(max(min(2.0 * theta, (1.0 + 2.0 * theta) / 3.0), 0.0), 2.0);
+    }
+    return upwind + min(psi, 1.0) * (central - upwind);
+}
+
+fn flux(i: u32, j: u32) -> f32 {
+    if i == 0u || i >= params.nr - 1u {
+        return 0.0;
+    }
+    let gradient = (n(i + 1u, j) - n(i - 1u, j)) / params.two_dr;
+    let cell = cells[i];
+    return cell.velocity * face_density(i, j, cell.velocity) - cell.diffusivity * gradient;
+}
+
+fn cell_index(id: vec3<u32>, groups: vec3<u32>) -> u32 {
+    return id.x + id.y * groups.x * 64u;
+}
+
+@compute @workgroup_size(64)
+fn radial(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {
+    let index = cell_index(id, groups);
+    if index >= params.nr * params.ntheta {
+        return;
+    }
+    let i = index / params.ntheta;
+    let j = index % params.ntheta;
+    if i == 0u || i == params.nr - 1u {
+        next[index] = density[index];
+        return;
+    }
+    let cell = cells[i];
+    let divergence = cell.outer * flux(i, j) - cell.inner * flux(i - 1u, j);
+    next[index] = density[index] + (cell.source - divergence) * params.dt;
+}
+
+@compute @workgroup_size(64)
+fn poloidal(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {
+    let index = cell_index(id, groups);
+    if index >= params.nr * params.ntheta {
+        return;
+    }
+    let i = index / params.ntheta;
+    let j = index % params.ntheta;
+    if i == 0u || i == params.nr - 1u {
+        result[index] = next[index];
+        return;
+    }
+    let row = i * params.ntheta;
+    let matrix = (row + j) * params.ntheta;
+    var sum = 0.0;
+    for (var k = 0u; k < params.ntheta; k++) {
+        sum += implicit[matrix + k] * next[row + k];
+    }
+    result[index] = clamp(sum, 0.0, params.max_density);
+}
+"#;
+
+/// Device, pipelines, and buffers for one (nr, ntheta) grid.
+pub struct PoloidalKernel {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    radial: wgpu::ComputePipeline,
+    poloidal: wgpu::ComputePipeline,
+    radial_group: wgpu::BindGroup,
+    poloidal_group: wgpu::BindGroup,
+    params: wgpu::Buffer,
+    cells: wgpu::Buffer,
+    density: wgpu::Buffer,
+    implicit: wgpu::Buffer,
+    result: wgpu::Buffer,
+    staging: wgpu::Buffer,
+    adapter: String,
+    nr: usize,
+    ntheta: usize,
+}
+
+impl PoloidalKernel {
+    /// Picks the default adapter; errors if there is none or the grid
+    /// does not fit its storage limits.
+    pub fn new(nr: usize, ntheta: usize) -> Result<Self, String> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .map_err(|e| e.to_string())?;
+        let limits = adapter.limits();
+        let implicit_size = (nr * ntheta * ntheta * 4) as u64;
+        if implicit_size > limits.max_storage_buffer_binding_size as u64 {
+            return Err(format!(
+                "{} MB of poloidal matrices exceed the {} MB storage limit of {}",
+                implicit_size >> 20,
+                limits.max_storage_buffer_binding_size >> 20,
+                adapter.get_info().name
+            ));
+        }
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("poloidal transport"),
+            required_limits: limits,
+            ..Default::default()
+        }))
+        .map_err(|e| e.to_string())?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("poloidal transport"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = |entry_point: &str| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: None,
+                module: &module,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let (radial, poloidal) = (pipeline("radial"), pipeline("poloidal"));
+
+        let storage = wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST;
+        let buffer = |label: &str, size: usize, usage: wgpu::BufferUsages| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: size as u64,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        let cells_size = nr * ntheta * 4;
+        let params = buffer("params", 32, wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST);
+        let cells = buffer("cells", nr * 5 * 4, storage);
+        let density = buffer("density", cells_size, storage);
+        let next = buffer("next", cells_size, wgpu::BufferUsages::STORAGE);
+        let implicit = buffer("implicit", implicit_size as usize, storage);
+        let result = buffer("result", cells_size, wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC);
+        let staging = buffer("staging", cells_size, wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST);
+
+        let bind_group = |pipeline: &wgpu::ComputePipeline, entries: &[(u32, &wgpu::Buffer)]| {
+            let entries: Vec<_> = entries
+                .iter()
+                .map(|&(binding, buffer)| wgpu::BindGroupEntry { binding, resource: buffer.as_entire_binding() })
+                .collect();
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &entries,
+            })
+        };
+        let radial_group = bind_group(&radial, &[(0, &params), (1, &cells), (2, &density), (3, &next)]);
+        let poloidal_group = bind_group(&poloidal, &[(0, &params), (3, &next), (4, &implicit), (5, &result)]);
+
+        Ok(PoloidalKernel {
+            device,
+            queue,
+            radial,
+            poloidal,
+            radial_group,
+            poloidal_group,
+            params,
+            cells,
+            density,
+            implicit,
+            result,
+            staging,
+            adapter: format!("{} ({:?})", adapter.get_info().name, adapter.get_info().backend),
+            nr,
+            ntheta,
+        })
+    }
+
+    /// Name and backend of the adapter in use.
+    pub fn adapter(&self) -> &str {
+        &self.adapter
+    }
+
+    /// Uploads (I − dt L_θ)⁻¹ of every radius.
+    pub fn set_implicit(&self, implicit: &[Array2<f64>]) {
+        let values: Vec<f32> = implicit.iter().flat_map(|a| a.iter().map(|&x| x as f32)).collect();
+        self.queue.write_buffer(&self.implicit, 0, &bytes(&values));
+    }
+
+    /// The radial step of every column followed by the implicit poloidal
+    /// step and the clamp to [0, MAX_IMPURITY_DENSITY]; rows 0 and nr − 1
+    /// are returned unchanged for the caller to close.
+    pub fn advance(&self, terms: &RadialTerms, density: &Array2<f64>, dt: f64) -> Array2<f64> {
+        let (nr, ntheta) = (self.nr, self.ntheta);
+        let convection = match terms.convection {
+            Convection::Central => 0u32,
+            Convection::Upwind => 1,
+            Convection::VanLeer => 2,
+            Convection::Koren => 3,
+        };
+        let mut params = Vec::with_capacity(32);
+        for value in [nr as u32, ntheta as u32, convection] {
+            params.extend(value.to_ne_bytes());
+        }
+        for value in [dt as f32, MAX_IMPURITY_DENSITY as f32, (2.0 * terms.dr) as f32, 0.0, 0.0] {
+            params.extend(value.to_ne_bytes());
+        }
+        self.queue.write_buffer(&self.params, 0, &params);
+        self.queue.write_buffer(&self.cells, 0, &bytes(&cells(terms)));
+        let values: Vec<f32> = density.iter().map(|&x| x as f32).collect();
+        self.queue.write_buffer(&self.density, 0, &bytes(&values));
+
+        let groups = (nr * ntheta).div_ceil(WORKGROUP_SIZE as usize) as u32;
+        let (x, y) = (groups.min(MAX_GROUPS_PER_DIMENSION), groups.div_ceil(MAX_GROUPS_PER_DIMENSION));
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(&self.radial);
+            pass.set_bind_group(0, &self.radial_group, &[]);
+            pass.dispatch_workgroups(x, y, 1);
+            pass.set_pipeline(&self.poloidal);
+            pass.set_bind_group(0, &self.poloidal_group, &[]);
+            pass.dispatch_workgroups(x, y, 1);
+        }
+        encoder.copy_buffer_to_buffer(&self.result, 0, &self.staging, 0, self.staging.size());
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = self.staging.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        self.device.poll(wgpu::PollType::Wait).expect("GPU lost during the poloidal step");
+        let next = {
+            let mapped = slice.get_mapped_range();
+            let values = mapped.chunks_exact(4).map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]) as f64);
+            Array2::from_shape_vec((nr, ntheta), values.collect()).expect("nr × ntheta values")
+        };
+        self.staging.unmap();
+        next
+    }
+}
+
+/// Per radius (v, D, inner weight, outer weight, source) as laid out in
+/// the shader's `Cell`.
+fn cells(terms: &RadialTerms) -> Vec<f32> {
+    let metric = terms.metric;
+    let dr = terms.dr;
+    let mut cells = Vec::with_capacity(terms.radius.len() * 5);
+    for (i, &r) in terms.radius.iter().enumerate() {
+        // Same planar axis cell as `RadialTerms::rate`
+        let (inner, outer) = if r > 0.01 {
+            let volume = metric.vprime[i] * dr;
+            (metric.vprime_inner[i] / volume, metric.vprime_outer[i] / volume)
+        } else {
+            (1.0 / dr, 1.0 / dr)
+        };
+        let source = if r > terms.source_radius { terms.source } else { 0.0 };
+        cells.extend([terms.velocity[i], terms.diffusivity[i], inner, outer, source].map(|x| x as f32));
+    }
+    cells
+}
+
+fn bytes(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|x| x.to_ne_bytes()).collect()
+}
+
+/// Lazily created kernel of a `PoloidalTransport`; clones start without
+/// one, so they never share device buffers.
+#[derive(Default)]
+pub struct KernelSlot(pub Option<PoloidalKernel>);
+
+impl Clone for KernelSlot {
+    fn clone(&self) -> Self {
+        KernelSlot(None)
+    }
+}
+
+impl fmt::Debug for KernelSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(kernel) => write!(f, "KernelSlot({}, {} × {})", kernel.adapter(), kernel.nr, kernel.ntheta),
+            None => write!(f, "KernelSlot(None)"),
+        }
+    }
+}
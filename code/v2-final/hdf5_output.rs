@@ -0,0 +1,70 @@
+//! # HDF5 Output (feature `hdf5`)
+//!
+//! Layout:
+//! ```text
+//! /traces/time, /traces/<channel>          every recorded step
+//! /profiles/radius                         [nr]
+//! /profiles/time                           [n_snap]
+//! /profiles/<field>                        [n_snap, nr]
+//! ```
+//! Every dataset carries a `units` attribute; the root carries
+//! `run_metadata` (JSON).
+
+use crate::history::{Channel, History};
+use crate::metadata::RunMetadata;
+use crate::snapshots::ProfileSnapshots;
+use hdf5::types::VarLenUnicode;
+use std::path::Path;
+
+fn set_units(dataset: &hdf5::Dataset, units: &str) -> hdf5::Result<()> {
+    let value: VarLenUnicode = units.parse().map_err(|e| hdf5::Error::from(format!("{:?}", e)))?;
+    dataset
+        .new_attr::<VarLenUnicode>()
+        .create("units")?
+        .write_scalar(&value)
+}
+
+pub fn write_hdf5<P: AsRef<Path>>(
+    path: P,
+    history: &History,
+    snapshots: &ProfileSnapshots,
+    metadata: &RunMetadata,
+) -> hdf5::Result<()> {
+    let file = hdf5::File::create(path)?;
+    let json: VarLenUnicode = metadata.to_json().parse().map_err(|e| hdf5::Error::from(format!("{:?}", e)))?;
+    file.new_attr::<VarLenUnicode>().create("run_metadata")?.write_scalar(&json)?;
+
+    let traces = file.create_group("traces")?;
+    let range = history.export_range(f64::NEG_INFINITY, f64::INFINITY, &Channel::ALL, 1);
+    let ds = traces.new_dataset_builder().with_data(range.time.as_slice()).create("time")?;
+    set_units(&ds, "s")?;
+    for (channel, values) in range.channels.iter().zip(&range.values) {
+        let ds = traces
+            .new_dataset_builder()
+            .with_data(values.as_slice())
+            .create(channel.name())?;
+        set_units(&ds, channel.units())?;
+    }
+
+    let profiles = file.create_group("profiles")?;
+    let ds = profiles
+        .new_dataset_builder()
+        .with_data(snapshots.radius.as_slice())
+        .create("radius")?;
+    set_units(&ds, "1")?;
+    let ds = profiles
+        .new_dataset_builder()
+        .with_data(snapshots.time.as_slice())
+        .create("time")?;
+    set_units(&ds, "s")?;
+
+    let nr = snapshots.radius.len();
+    for field in snapshots.fields() {
+        let flat: Vec<f64> = field.rows.iter().flatten().copied().collect();
+        // Written flat: hdf5-metno links its own ndarray, not ours
+        let ds = profiles.new_dataset::<f64>().shape((field.rows.len(), nr)).create(field.name)?;
+        ds.write_raw(flat.as_slice())?;
+        set_units(&ds, field.units)?;
+    }
+    Ok(())
+}
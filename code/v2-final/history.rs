@@ -0,0 +1,226 @@
+//! # Time-Trace History
+//!
+//! Scalar time traces recorded every step, with windowed export so
+//! callers can pull the interval around a pulse instead of full-run arrays.
+
+use serde::{Deserialize, Serialize};
+
+/// Recorded scalar channels.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    CenterImpurity,
+    EdgeImpurity,
+    Turbulence,
+    CenterZeff,
+    CenterDilution,
+    StoredEnergy,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 6] = [
+        Channel::CenterImpurity,
+        Channel::EdgeImpurity,
+        Channel::Turbulence,
+        Channel::CenterZeff,
+        Channel::CenterDilution,
+        Channel::StoredEnergy,
+    ];
+
+    /// Column name used in output files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::CenterImpurity => "center_impurity",
+            Channel::EdgeImpurity => "edge_impurity",
+            Channel::Turbulence => "turbulence",
+            Channel::CenterZeff => "center_zeff",
+            Channel::CenterDilution => "center_dilution",
+            Channel::StoredEnergy => "stored_energy",
+        }
+    }
+
+    pub fn long_name(&self) -> &'static str {
+        match self {
+            Channel::CenterImpurity => "impurity density on axis",
+            Channel::EdgeImpurity => "impurity density at the last closed flux surface",
+            Channel::Turbulence => "edge turbulent diffusivity",
+            Channel::CenterZeff => "effective charge on axis",
+            Channel::CenterDilution => "main-ion dilution n_i/n_e on axis",
+            Channel::StoredEnergy => "plasma stored energy (confinement proxy)",
+        }
+    }
+
+    /// UDUNITS-compatible unit string.
+    pub fn units(&self) -> &'static str {
+        match self {
+            Channel::CenterImpurity | Channel::EdgeImpurity => "m-3",
+            Channel::Turbulence => "m2 s-1",
+            Channel::CenterZeff | Channel::CenterDilution => "1",
+            Channel::StoredEnergy => "MJ",
+        }
+    }
+}
+
+/// One step's worth of scalar channels.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Sample {
+    pub time: f64,
+    pub center_impurity: f64,
+    pub edge_impurity: f64,
+    pub turbulence: f64,
+    pub center_zeff: f64,
+    pub center_dilution: f64,
+    pub stored_energy: f64,
+}
+
+impl Sample {
+    pub fn get(&self, channel: Channel) -> f64 {
+        match channel {
+            Channel::CenterImpurity => self.center_impurity,
+            Channel::EdgeImpurity => self.edge_impurity,
+            Channel::Turbulence => self.turbulence,
+            Channel::CenterZeff => self.center_zeff,
+            Channel::CenterDilution => self.center_dilution,
+            Channel::StoredEnergy => self.stored_energy,
+        }
+    }
+}
+
+/// How often samples are recorded (history and streamed outputs).
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Cadence {
+    #[default]
+    EveryStep,
+    /// Every N solver steps.
+    Steps { every: u64 },
+    /// Every `dt` seconds of simulation time.
+    Interval { dt: f64 },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct History {
+    /// When false, `push` stores nothing: long runs stream to an `OutputSink`
+    /// instead of holding every step in memory.
+    pub recording: bool,
+    pub cadence: Cadence,
+    steps_seen: u64,  // Position within the current `Steps` cycle
+    next_time: Option<f64>,
+    time: Vec<f64>,
+    center_impurity: Vec<f64>,
+    edge_impurity: Vec<f64>,
+    turbulence: Vec<f64>,
+    center_zeff: Vec<f64>,
+    center_dilution: Vec<f64>,
+    stored_energy: Vec<f64>,
+}
+
+/// Column-major slice of the history returned by `export_range`.
+pub struct ExportRange {
+    pub time: Vec<f64>,
+    pub channels: Vec<Channel>,
+    pub values: Vec<Vec<f64>>, // One column per entry in `channels`
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            recording: true,
+            cadence: Cadence::EveryStep,
+            steps_seen: 0,
+            next_time: None,
+            time: Vec::new(),
+            center_impurity: Vec::new(),
+            edge_impurity: Vec::new(),
+            turbulence: Vec::new(),
+            center_zeff: Vec::new(),
+            center_dilution: Vec::new(),
+            stored_energy: Vec::new(),
+        }
+    }
+}
+
+impl History {
+    /// Offers one step's sample; returns whether it was due under the cadence
+    /// (and therefore should also go to the streamed outputs).
+    pub fn push(&mut self, sample: &Sample) -> bool {
+        let due = match self.cadence {
+            Cadence::EveryStep => true,
+            Cadence::Steps { every } => {
+                let due = self.steps_seen == 0;
+                self.steps_seen = (self.steps_seen + 1) % every.max(1);
+                due
+            }
+            Cadence::Interval { dt } => {
+                // Tolerance absorbs round-off in the accumulated solver time
+                let due = self.next_time.is_none_or(|next| sample.time >= next - 1e-6 * dt);
+                if due {
+                    let next = self.next_time.map_or(sample.time, |next| next + dt);
+                    self.next_time = Some(if next <= sample.time { sample.time + dt } else { next });
+                }
+                due
+            }
+        };
+
+        if due && self.recording {
+            self.time.push(sample.time);
+            self.center_impurity.push(sample.center_impurity);
+            self.edge_impurity.push(sample.edge_impurity);
+            self.turbulence.push(sample.turbulence);
+            self.center_zeff.push(sample.center_zeff);
+            self.center_dilution.push(sample.center_dilution);
+            self.stored_energy.push(sample.stored_energy);
+        }
+        due
+    }
+
+    pub fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.time.is_empty()
+    }
+
+    pub fn channel(&self, channel: Channel) -> &[f64] {
+        match channel {
+            Channel::CenterImpurity => &self.center_impurity,
+            Channel::EdgeImpurity => &self.edge_impurity,
+            Channel::Turbulence => &self.turbulence,
+            Channel::CenterZeff => &self.center_zeff,
+            Channel::CenterDilution => &self.center_dilution,
+            Channel::StoredEnergy => &self.stored_energy,
+        }
+    }
+
+    /// Samples with t0 <= t <= t1, keeping every `stride`-th one.
+    ///
+    /// Time is monotonic, so the window is located by binary search.
+    pub fn export_range(&self, t0: f64, t1: f64, channels: &[Channel], stride: usize) -> ExportRange {
+        let stride = stride.max(1);
+        let start = self.time.partition_point(|&t| t < t0);
+        let end = self.time.partition_point(|&t| t <= t1).max(start);
+
+        let time = self.time[start..end].iter().step_by(stride).copied().collect();
+        let values = channels
+            .iter()
+            .map(|&c| self.channel(c)[start..end].iter().step_by(stride).copied().collect())
+            .collect();
+
+        ExportRange {
+            time,
+            channels: channels.to_vec(),
+            values,
+        }
+    }
+}
+
+impl ExportRange {
+    pub fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.time.is_empty()
+    }
+}
@@ -0,0 +1,233 @@
+//! # IMAS Export
+//!
+//! Maps the profile snapshots onto the subset of the IMAS Data Dictionary
+//! (3.x names) this model fills, so results load into IMASPy/OMAS-based
+//! tooling next to measured discharges:
+//! ```text
+//! core_profiles   time                                   snapshot times (s)
+//!                 global_quantities.energy_diamagnetic   J, stored energy at each snapshot
+//!                 profiles_1d[i].grid.rho_tor_norm       r / a
+//!                 profiles_1d[i].electrons.density       m⁻³
+//!                 profiles_1d[i].electrons.temperature   eV
+//!                 profiles_1d[i].zeff
+//!                 profiles_1d[i].ion[0]                  H, density n_i (quasi-neutrality)
+//!                 profiles_1d[i].ion[1]                  impurity, density n_Z
+//! core_transport  time                                   snapshot times (s)
+//!                 model[0]  combined (1)      ion[0].particles.d = D_neo + D_turb, .v = pinch
+//!                 model[1]  neoclassical (5)  ion[0].particles.d = D_neo
+//!                 model[2]  anomalous (6)     ion[0].particles.d = D_turb
+//! ```
+//! Both IDSs use `homogeneous_time = 1` and carry the run metadata in
+//! `ids_properties.creation_date` and `code` (`parameters` holds the
+//! resolved config as JSON where the Access Layer expects XML). Transport coefficients are for
+//! the impurity only; the pinch is not split between the models. The JSON
+//! file holds `{"core_profiles": …, "core_transport": …}`; the HDF5 file
+//! (feature `hdf5`) mirrors that tree with groups, arrays of structures as
+//! groups named `0`, `1`, …, number arrays as datasets and scalars and
+//! strings as attributes. It is not the Access Layer's own HDF5 backend.
+
+use crate::history::{Channel, History};
+use crate::metadata::RunMetadata;
+use crate::snapshots::ProfileSnapshots;
+use serde_json::{json, Value};
+
+/// Data Dictionary `core_transport.model[].identifier` indices.
+const MODEL_COMBINED: i64 = 1;
+const MODEL_NEOCLASSICAL: i64 = 5;
+const MODEL_ANOMALOUS: i64 = 6;
+/// Access Layer marker for an unset float.
+const EMPTY_FLOAT: f64 = -9e40;
+
+/// IDS label of an impurity of charge `z`.
+fn element_label(z: f64) -> String {
+    match z.round() as i64 {
+        6 => "C".to_string(),
+        7 => "N".to_string(),
+        8 => "O".to_string(),
+        10 => "Ne".to_string(),
+        18 => "Ar".to_string(),
+        26 => "Fe".to_string(),
+        74 => "W".to_string(),
+        z => format!("Z{}", z),
+    }
+}
+
+fn ids_properties(comment: &str) -> Value {
+    json!({
+        "homogeneous_time": 1,
+        "comment": comment,
+        "provider": concat!("w7x-turbulence-control ", env!("CARGO_PKG_VERSION")),
+    })
+}
+
+/// Recorded value at or just before each of `times`; `EMPTY_FLOAT` before
+/// the first sample.
+fn sample_at(time: &[f64], values: &[f64], times: &[f64]) -> Vec<f64> {
+    times
+        .iter()
+        .map(|&t| match time.partition_point(|&s| s <= t) {
+            0 => EMPTY_FLOAT,
+            n => values[n - 1],
+        })
+        .collect()
+}
+
+/// `core_profiles` for the snapshots, with the stored energy from
+/// `history` (`EMPTY_FLOAT` where it has no sample yet).
+pub fn core_profiles(history: &History, snapshots: &ProfileSnapshots, impurity_charge: f64) -> Value {
+    let stored = history.export_range(f64::NEG_INFINITY, f64::INFINITY, &[Channel::StoredEnergy], 1);
+    let energy: Vec<f64> = sample_at(&stored.time, &stored.values[0], &snapshots.time)
+        .into_iter()
+        .map(|mj| if mj == EMPTY_FLOAT { mj } else { mj * 1e6 })
+        .collect();
+    let profiles: Vec<Value> = (0..snapshots.len())
+        .map(|i| {
+            let n_e = &snapshots.electron_density[i];
+            let n_i: Vec<f64> = n_e.iter().zip(&snapshots.dilution[i]).map(|(n, d)| n * d).collect();
+            let t_e: Vec<f64> = snapshots.electron_temp[i].iter().map(|kev| kev * 1e3).collect();
+            json!({
+                "time": snapshots.time[i],
+                "grid": { "rho_tor_norm": snapshots.radius },
+                "electrons": { "density": n_e, "temperature": t_e },
+                "zeff": snapshots.zeff[i],
+                "ion": [
+                    {
+                        "label": "H",
+                        "z_ion": 1.0,
+                        "element": [{ "a": 1.0, "z_n": 1.0, "atoms_n": 1 }],
+                        "density": n_i,
+                    },
+                    {
+                        "label": element_label(impurity_charge),
+                        "z_ion": impurity_charge,
+                        "element": [{ "z_n": impurity_charge, "atoms_n": 1 }],
+                        "density": snapshots.impurity_density[i],
+                    },
+                ],
+            })
+        })
+        .collect();
+    json!({
+        "ids_properties": ids_properties("W7-X impurity transport simulation: plasma profiles"),
+        "time": snapshots.time,
+        "global_quantities": { "energy_diamagnetic": energy },
+        "profiles_1d": profiles,
+    })
+}
+
+/// `core_transport` for the impurity in the snapshots.
+pub fn core_transport(snapshots: &ProfileSnapshots, impurity_charge: f64) -> Value {
+    let model = |index: i64, name: &str, description: &str, diffusivity: &dyn Fn(usize) -> Vec<f64>, pinch: bool| {
+        let profiles: Vec<Value> = (0..snapshots.len())
+            .map(|i| {
+                let mut particles = json!({ "d": diffusivity(i) });
+                if pinch {
+                    particles["v"] = json!(snapshots.pinch[i]);
+                }
+                json!({
+                    "time": snapshots.time[i],
+                    "grid_d": { "rho_tor_norm": snapshots.radius },
+                    "grid_v": { "rho_tor_norm": snapshots.radius },
+                    "ion": [{
+                        "label": element_label(impurity_charge),
+                        "z_ion": impurity_charge,
+                        "element": [{ "z_n": impurity_charge, "atoms_n": 1 }],
+                        "particles": particles,
+                    }],
+                })
+            })
+            .collect();
+        json!({
+            "identifier": { "index": index, "name": name, "description": description },
+            "profiles_1d": profiles,
+        })
+    };
+    let combined = |i: usize| -> Vec<f64> {
+        snapshots.neoclassical[i].iter().zip(&snapshots.turbulence[i]).map(|(neo, turb)| neo + turb).collect()
+    };
+    json!({
+        "ids_properties": ids_properties("W7-X impurity transport simulation: impurity transport coefficients"),
+        "time": snapshots.time,
+        "model": [
+            model(MODEL_COMBINED, "combined", "D_neo + D_turb and total pinch", &combined, true),
+            model(MODEL_NEOCLASSICAL, "neoclassical", "D_neo", &|i| snapshots.neoclassical[i].clone(), false),
+            model(MODEL_ANOMALOUS, "anomalous", "D_turb, including turbulence pulses", &|i| snapshots.turbulence[i].clone(), false),
+        ],
+    })
+}
+
+/// Stamps an IDS with the run's provenance.
+fn stamp(ids: &mut Value, metadata: &RunMetadata) {
+    ids["ids_properties"]["creation_date"] = json!(metadata.started);
+    ids["code"] = json!({
+        "name": "w7x-turbulence-control",
+        "version": metadata.crate_version,
+        "commit": metadata.git_commit,
+        "parameters": metadata.to_json(),
+    });
+}
+
+/// `{"core_profiles": …, "core_transport": …}`.
+pub fn ids(history: &History, snapshots: &ProfileSnapshots, impurity_charge: f64, metadata: &RunMetadata) -> Value {
+    let mut profiles = core_profiles(history, snapshots, impurity_charge);
+    let mut transport = core_transport(snapshots, impurity_charge);
+    stamp(&mut profiles, metadata);
+    stamp(&mut transport, metadata);
+    json!({
+        "core_profiles": profiles,
+        "core_transport": transport,
+    })
+}
+
+#[cfg(feature = "fs")]
+pub fn write_json<P: AsRef<std::path::Path>>(path: P, ids: &Value) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    serde_json::to_writer_pretty(&mut writer, ids).map_err(std::io::Error::other)?;
+    writeln!(writer)?;
+    writer.flush()
+}
+
+#[cfg(feature = "hdf5")]
+pub fn write_hdf5<P: AsRef<std::path::Path>>(path: P, ids: &Value) -> hdf5::Result<()> {
+    let file = hdf5::File::create(path)?;
+    match ids {
+        Value::Object(fields) => write_group(&file, fields),
+        _ => Err(hdf5::Error::from("IDS tree must be an object")),
+    }
+}
+
+#[cfg(feature = "hdf5")]
+fn write_group(group: &hdf5::Group, fields: &serde_json::Map<String, Value>) -> hdf5::Result<()> {
+    use hdf5::types::VarLenUnicode;
+    for (name, value) in fields {
+        match value {
+            Value::Object(fields) => write_group(&group.create_group(name)?, fields)?,
+            Value::Array(items) if items.iter().all(Value::is_number) => {
+                let data: Vec<f64> = items.iter().filter_map(Value::as_f64).collect();
+                group.new_dataset_builder().with_data(data.as_slice()).create(name.as_str())?;
+            }
+            Value::Array(items) => {
+                let array = group.create_group(name)?;
+                for (i, item) in items.iter().enumerate() {
+                    let Value::Object(fields) = item else {
+                        return Err(hdf5::Error::from(format!("{}[{}] is not a structure", name, i)));
+                    };
+                    write_group(&array.create_group(&i.to_string())?, fields)?;
+                }
+            }
+            Value::Number(n) => match n.as_i64() {
+                Some(n) => group.new_attr::<i64>().create(name.as_str())?.write_scalar(&n)?,
+                None => group.new_attr::<f64>().create(name.as_str())?.write_scalar(&n.as_f64().unwrap_or(f64::NAN))?,
+            },
+            Value::String(s) => {
+                let s: VarLenUnicode = s.parse().map_err(|e| hdf5::Error::from(format!("{:?}", e)))?;
+                group.new_attr::<VarLenUnicode>().create(name.as_str())?.write_scalar(&s)?;
+            }
+            Value::Bool(_) | Value::Null => {
+                return Err(hdf5::Error::from(format!("{}: no HDF5 mapping for {}", name, value)));
+            }
+        }
+    }
+    Ok(())
+}
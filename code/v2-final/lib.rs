@@ -0,0 +1,147 @@
+//! # W7-X Adaptive Turbulence Control — simulation core
+//!
+//! The plasma model, diagnostics, and controllers behind the
+//! `w7x-turbulence-control` binary, usable from other tools.
+//!
+//! ## Stable API
+//! The items re-exported at the crate root — [`Plant`] (with `try_new`,
+//! `step`, `observe`, `actuate`), [`Observation`], [`Measurement`],
+//! [`ControlAction`], [`ConfinementMode`], [`Config`], and [`SimError`] —
+//! follow semver: breaking changes only with a major version bump.
+//! [`Observation`] and [`Measurement`] are `#[non_exhaustive]`, so new
+//! fields are not breaking; build them with `new`. [`ControlAction`] is
+//! too: a new variant, like `Pulse`, is not breaking either, nor is a new
+//! field of its `PulseCommand`. [`ConfinementMode`] is `#[non_exhaustive]`
+//! as well: new modes may be added, like the `[termination]` ones, so
+//! match it with a wildcard arm.
+//! Everything reached through the individual modules is internal and may
+//! change.
+//!
+//! See `examples/planner_in_the_loop.rs` for a planner-driven discharge and
+//! `examples/rl_episode.rs` for the reinforcement-learning environment.
+//!
+//! ## Features
+//! - `fs` (default): file output, checkpoints, and `Config::load`.
+//! - `parallel` (default): scans, ensembles, sensitivity analyses, GA
+//!   generations, and the optimizer's initial design run on all cores via rayon.
+//! - `hdf5`, `netcdf`: extra export formats (imply `fs`).
+//! - `parquet`: columnar traces (`trace_format = "parquet"`) and profile
+//!   snapshots (`[output] parquet`) for polars / pyarrow.
+//! - `zmq`: `serve` mode, streaming over ZeroMQ with external control.
+//! - `onnx`: learned accumulation detector (`[detection.model]`).
+//! - `scripting`: control laws written as Rhai scripts, hot-reloaded
+//!   (`[controller.script]`).
+//! - `plugins`: controllers and turbulence models loaded from shared
+//!   libraries through a C ABI (`[controller.plugin]`, `type = "plugin"`).
+//! - `gpu`: wgpu backend for the 2D transport step (`[poloidal] backend`).
+//! - `plot`: end-of-run PNG figure and GIF of n_Z(r) (`[output] plot`,
+//!   `animation`), replacing `plot_results.py`.
+//! - `tui`: live terminal dashboard for single runs (`--tui`).
+//! - `websocket`: browser viewer streaming a run over WebSocket
+//!   (`--serve-dashboard`).
+//! - `ffi`: C ABI over [`Plant`] (`w7x_sim_*`, header `w7x_sim.h`) for
+//!   embedding in C/C++ control software.
+//! - `wasm`: [`wasm::WasmSimulator`] bindings for browser demos; build with
+//!   `wasm-pack build --target web --no-default-features --features wasm`.
+
+pub mod actuator;
+pub mod aggregate;
+pub mod balance;
+pub mod benchmark;
+pub mod boundary;
+pub mod compare;
+pub mod config;
+pub mod confinement;
+pub mod console;
+pub mod controller;
+pub mod convection;
+pub mod converge;
+pub mod crash;
+pub mod daq;
+pub mod detection;
+pub mod diagnostics;
+pub mod ecrh;
+pub mod electric_field;
+pub mod elm;
+pub mod error;
+pub mod ensemble;
+pub mod events;
+pub mod evolve;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fit;
+pub mod fuzzy;
+pub mod gain_schedule;
+pub mod geometry;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+#[cfg(feature = "hdf5")]
+pub mod hdf5_output;
+pub mod history;
+pub mod imas;
+pub mod lockstep;
+pub mod main_ions;
+pub mod mdsplus;
+pub mod metadata;
+pub mod neoclassical;
+pub mod numerics;
+#[cfg(feature = "onnx")]
+pub mod onnx_detector;
+#[cfg(feature = "netcdf")]
+pub mod netcdf_output;
+pub mod operator_log;
+pub mod optimize;
+pub mod output;
+pub mod pacing;
+#[cfg(feature = "parquet")]
+pub mod parquet_output;
+pub mod pellet;
+pub mod plant;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+#[cfg(feature = "plot")]
+pub mod plot;
+pub mod poloidal;
+pub mod precision;
+pub mod preset;
+pub mod profiles;
+pub mod pulse;
+pub mod ramp;
+pub mod reference;
+pub mod regularization;
+pub mod rl_env;
+pub mod rng;
+pub mod sawtooth;
+pub mod scenario;
+pub mod scan;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod sensitivity;
+#[cfg(feature = "zmq")]
+pub mod server;
+pub mod simulation;
+pub mod snapshots;
+pub mod sol;
+pub mod source;
+pub mod splitting;
+pub mod state;
+pub mod steady;
+pub mod stencil;
+pub mod summary;
+pub mod surrogate;
+pub mod termination;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod turbulence;
+pub mod units;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "websocket")]
+pub mod web_dashboard;
+
+pub use config::Config;
+pub use controller::ControlAction;
+pub use diagnostics::Measurement;
+pub use error::SimError;
+pub use plant::{Observation, Plant};
+pub use state::{ConfinementMode, StellaratorState};
@@ -0,0 +1,277 @@
+//! # Lock-Step Controller Comparison
+//!
+//! Runs several controllers (`[lockstep] contenders`) against the same
+//! discharge side by side. Comparing controllers across independently
+//! noisy runs confounds the controller with the noise; here every
+//! contender of a realization gets the same seeds, so the diagnostic
+//! noise, the ELM schedule, and the scenario are one and the same
+//! realization for all of them, and the runs advance together step by
+//! step, so every contender is sampled at the same instants with the same
+//! noise draws. Only the controller (and its `[detection]`) differs.
+//!
+//! ```toml
+//! [lockstep]
+//! realizations = 4
+//! contenders = [
+//!     { name = "threshold" },
+//!     { name = "escalating", controller = { escalation = { initial = 3.0 } } },
+//!     { name = "fuzzy", controller = { fuzzy = {} } },
+//! ]
+//! ```
+//!
+//! A contender without `controller` or `detection` uses the configured
+//! one. With `baseline`, an uncontrolled `no_control` run joins. Realization
+//! `k` is seeded as replica `k` of an ensemble. A contender whose discharge
+//! terminates keeps its final values in the traces.
+
+use crate::config::Config;
+use crate::controller::{ControlAction, ControllerConfig, FixedController};
+use crate::detection::PipelineConfig;
+use crate::error::{Result, SimError};
+use crate::rng;
+use crate::simulation::Simulation;
+use crate::state::ConfinementMode;
+use crate::summary::{RunSummary, SummaryTracker};
+use serde::{Deserialize, Serialize};
+
+/// Name of the uncontrolled run added by `baseline`.
+pub const BASELINE: &str = "no_control";
+
+/// One controller under comparison.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Contender {
+    pub name: String,
+    /// Replaces `[controller]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub controller: Option<ControllerConfig>,
+    /// Replaces `[detection]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detection: Option<PipelineConfig>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LockstepConfig {
+    pub contenders: Vec<Contender>,
+    pub baseline: bool,       // Add an uncontrolled run
+    pub realizations: usize,  // Noise realizations, each shared by all contenders
+    pub trace_interval: f64,  // s between points of the traces
+    pub output: String,       // Metrics per realization and contender (CSV)
+    pub traces: String,       // n_Z(0), W, and pulsing of every contender over time (CSV)
+}
+
+impl Default for LockstepConfig {
+    fn default() -> Self {
+        LockstepConfig {
+            contenders: vec![Contender { name: "configured".to_string(), ..Contender::default() }],
+            baseline: true,
+            realizations: 1,
+            trace_interval: 0.01,
+            output: "lockstep.csv".to_string(),
+            traces: "lockstep_traces.csv".to_string(),
+        }
+    }
+}
+
+/// A contender's run of one realization, before it starts.
+pub struct Entrant {
+    pub name: String,
+    pub config: Config, // `base` with the contender's controller and the realization's seeds
+    pub simulation: Simulation,
+}
+
+/// The contenders of realization `realization`, checked and seeded alike;
+/// the baseline first. Callers may replace a simulation's controller
+/// (e.g. a script) before `race`.
+pub fn entrants(base: &Config, realization: usize) -> Result<Vec<Entrant>> {
+    let settings = &base.lockstep;
+    if settings.contenders.is_empty() {
+        return Err(SimError::invalid("lockstep.contenders", "no contenders"));
+    }
+    if !(settings.trace_interval > 0.0 && settings.trace_interval.is_finite()) {
+        return Err(SimError::invalid("lockstep.trace_interval", format!("{} must be positive and finite", settings.trace_interval)));
+    }
+    let mut seeded = base.clone();
+    rng::seed_batch_member(&mut seeded, realization);
+    let mut names: Vec<&str> = Vec::new();
+    let mut entrants = Vec::new();
+    if settings.baseline {
+        names.push(BASELINE);
+        let mut simulation = Simulation::try_from_config(&seeded)?;
+        simulation.controller = Box::new(FixedController(ControlAction::Hold));
+        entrants.push(Entrant { name: BASELINE.to_string(), config: seeded.clone(), simulation });
+    }
+    for contender in &settings.contenders {
+        if contender.name.is_empty() || names.contains(&contender.name.as_str()) {
+            return Err(SimError::invalid("lockstep.contenders", format!("name {:?} is empty or taken", contender.name)));
+        }
+        names.push(&contender.name);
+        let mut config = seeded.clone();
+        if let Some(controller) = &contender.controller {
+            config.controller = controller.clone();
+        }
+        if let Some(detection) = &contender.detection {
+            config.detection = detection.clone();
+        }
+        let simulation = Simulation::try_from_config(&config)?;
+        entrants.push(Entrant { name: contender.name.clone(), config, simulation });
+    }
+    Ok(entrants)
+}
+
+pub struct ContenderRun {
+    pub name: String,
+    pub summary: RunSummary,
+    pub center_impurity: Vec<f64>, // m⁻³, every `trace_interval`
+    pub stored_energy: Vec<f64>,   // MJ
+    pub pulsing: Vec<bool>,
+}
+
+pub struct Race {
+    pub realization: usize,
+    pub time: Vec<f64>,
+    pub runs: Vec<ContenderRun>, // In `entrants` order
+}
+
+struct Runner {
+    entrant: Entrant,
+    tracker: SummaryTracker,
+    center_impurity: Vec<f64>,
+    stored_energy: Vec<f64>,
+    pulsing: Vec<bool>,
+}
+
+impl Runner {
+    fn running(&self, t_max: f64) -> bool {
+        let state = &self.entrant.simulation.state;
+        state.time < t_max && !state.terminated()
+    }
+}
+
+/// Steps all entrants together until each reaches `t_max` or terminates.
+pub fn race(entrants: Vec<Entrant>, realization: usize, trace_interval: f64) -> Result<Race> {
+    let mut runners: Vec<Runner> = entrants
+        .into_iter()
+        .map(|mut entrant| {
+            let state = &mut entrant.simulation.state;
+            state.verbose = false;
+            state.history.recording = false;
+            let tracker = SummaryTracker::new(state, entrant.config.output.critical_density);
+            Runner {
+                entrant,
+                tracker,
+                center_impurity: Vec::new(),
+                stored_energy: Vec::new(),
+                pulsing: Vec::new(),
+            }
+        })
+        .collect();
+    let t_max = runners.first().map_or(0.0, |r| r.entrant.config.simulation.t_max);
+
+    let mut time = Vec::new();
+    let mut next_time = 0.0;
+    loop {
+        let mut clock = None;
+        for runner in runners.iter_mut().filter(|r| r.running(t_max)) {
+            let sim = &mut runner.entrant.simulation;
+            sim.step();
+            sim.check_finite()?;
+            runner.tracker.observe(&sim.state, sim.dt);
+            clock = Some(sim.state.time);
+        }
+        // Same dt everywhere, so every runner still going is at `clock`
+        let Some(clock) = clock else { break };
+        if clock >= next_time {
+            time.push(clock);
+            for runner in &mut runners {
+                let state = &runner.entrant.simulation.state;
+                runner.center_impurity.push(state.impurity_density[0]);
+                runner.stored_energy.push(state.confinement.stored_energy());
+                runner.pulsing.push(state.confinement_mode == ConfinementMode::TurbulencePulse);
+            }
+            next_time += trace_interval;
+        }
+    }
+
+    let runs = runners
+        .into_iter()
+        .map(|runner| {
+            let sim = &runner.entrant.simulation;
+            ContenderRun {
+                summary: runner.tracker.finish(&sim.state, sim.dt),
+                name: runner.entrant.name,
+                center_impurity: runner.center_impurity,
+                stored_energy: runner.stored_energy,
+                pulsing: runner.pulsing,
+            }
+        })
+        .collect();
+    Ok(Race { realization, time, runs })
+}
+
+/// Every realization of `[lockstep]` with the configured controllers.
+pub fn run_lockstep(base: &Config) -> Result<Vec<Race>> {
+    (0..base.lockstep.realizations.max(1))
+        .map(|k| race(entrants(base, k)?, k, base.lockstep.trace_interval))
+        .collect()
+}
+
+#[cfg(feature = "fs")]
+pub fn write_table<P: AsRef<std::path::Path>>(
+    path: P,
+    races: &[Race],
+    metadata: &crate::metadata::RunMetadata,
+) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    metadata.write_csv_header(&mut writer)?;
+    writeln!(writer, "realization,controller,final_center_impurity,mean_center_impurity,peak_center_impurity,time_above_critical,pulses,duty_cycle,ecrh_energy,mean_stored_energy,confinement_loss")?;
+    for race in races {
+        for run in &race.runs {
+            let s = &run.summary;
+            writeln!(
+                writer,
+                "{},{},{:.6e},{:.6e},{:.6e},{:.4},{},{:.4},{:.4},{:.6},{:.4}",
+                race.realization, run.name, s.final_center_impurity, s.mean_center_impurity,
+                s.peak_center_impurity, s.time_above_critical, s.pulses, s.duty_cycle,
+                s.ecrh_energy, s.mean_stored_energy, s.confinement_loss
+            )?;
+        }
+    }
+    writer.flush()
+}
+
+/// Columns `<name>_center_impurity`, `<name>_stored_energy`, and
+/// `<name>_pulsing` (0/1) per contender, one block of rows per realization.
+#[cfg(feature = "fs")]
+pub fn write_traces<P: AsRef<std::path::Path>>(
+    path: P,
+    races: &[Race],
+    metadata: &crate::metadata::RunMetadata,
+) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    metadata.write_csv_header(&mut writer)?;
+    let Some(first) = races.first() else {
+        return writer.flush();
+    };
+    let names: Vec<String> = first
+        .runs
+        .iter()
+        .map(|r| format!("{0}_center_impurity,{0}_stored_energy,{0}_pulsing", r.name))
+        .collect();
+    writeln!(writer, "realization,time,{}", names.join(","))?;
+    for race in races {
+        for (j, t) in race.time.iter().enumerate() {
+            let row: Vec<String> = race
+                .runs
+                .iter()
+                .map(|r| format!("{:.6e},{:.6},{}", r.center_impurity[j], r.stored_energy[j], u8::from(r.pulsing[j])))
+                .collect();
+            writeln!(writer, "{},{:.6},{}", race.realization, t, row.join(","))?;
+        }
+    }
+    writer.flush()
+}
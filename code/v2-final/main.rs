@@ -9,273 +9,1362 @@
 //! - 1D radial transport with neoclassical + turbulent diffusion
 //! - ITG-based turbulence model
 //! - Adaptive control with cooldown mechanism
+//! - Actuator latency and rise/fall dynamics
+//! - Synthetic diagnostics (noise, sampling rate, saturation)
 //! - Stable sawtooth pattern (6-10×10¹⁸ m⁻³)
 //! 
 //! ## Usage
 //! ```bash
 //! cargo run --release
+//! cargo run --release -- --config w7x.toml
+//! cargo run --release -- --preset op12_standard       # also high_mirror, pellet_high_performance
+//! cargo run --release -- --checkpoint run.ckpt        # snapshot every 1 s
+//! cargo run --release -- --resume run.ckpt --t-max 20
+//! cargo run --release -- --speed 1                   # simulated time paced to the wall clock ([serve] speed)
+//! cargo run --release -- ensemble --seed 2024          # master seed of every random stream (top-level seed)
+//! cargo run --release --features tui -- --tui         # live dashboard instead of status lines
+//! kill -USR1 <pid>                                    # or `pause` on stdin: hold a run, then show / params / set / resume
+//! cargo run --release --features websocket -- --serve-dashboard   # browser viewer on [serve] dashboard
+//! RUST_LOG=warn cargo run --release                  # warnings only ([logging] filter)
+//! cargo run --release --features zmq -- serve --config w7x.toml
+//! cargo run --release -- scan --config w7x.toml          # [scan] grid, all cores
+//! cargo run --release -- ensemble --config w7x.toml      # [ensemble] Monte Carlo
+//! cargo run --release -- sensitivity --config w7x.toml   # [sensitivity] Sobol indices
+//! cargo run --release -- optimize --config w7x.toml      # [optimize] controller tuning
+//! cargo run --release -- evolve --config w7x.toml        # [evolve] GA over pulse waveforms
+//! cargo run --release -- compare --config w7x.toml       # no control vs adaptive vs always on
+//! cargo run --release -- lockstep --config w7x.toml      # [lockstep] controllers on identical noise
+//! cargo run --release -- steady --config w7x.toml        # steady-state n_Z(r), no time stepping
+//! cargo run --release -- converge --config w7x.toml      # [converge] refinement study
+//! cargo run --release -- precision --config w7x.toml     # f32 vs f64 transport, [precision_check]
+//! cargo run --release -- aggregate --config w7x.toml     # statistics over the runs in [aggregate] directory
+//! cargo run --release -- validate --config w7x.toml      # mismatch against the [reference] measured trace
+//! cargo run --release -- fit --config w7x.toml           # [fit] D_neo(r), v_neo(r), source to the reference
+//! cargo run --release --features plot -- --config w7x.toml   # [output] plot / animation
 //! python plot_results.py
 //! ```
 
+use w7x_turbulence_control::config::{Config, LoggingConfig};
+use w7x_turbulence_control::console::Console;
+use w7x_turbulence_control::crash;
+use w7x_turbulence_control::detection::{ModelConfig, Voting};
+use w7x_turbulence_control::events::{Event, EventLog};
+use w7x_turbulence_control::metadata::{self, RunMetadata};
+use w7x_turbulence_control::operator_log::OperatorLog;
+use w7x_turbulence_control::output::create_sink;
+use w7x_turbulence_control::pacing::Pacer;
+use w7x_turbulence_control::preset::Preset;
+use w7x_turbulence_control::pulse::{PulseShape, PulseWindow};
+use w7x_turbulence_control::regularization::Regularization;
+use w7x_turbulence_control::rng::{RngRegistry, Stream};
+use w7x_turbulence_control::optimize::{self, Evaluation, Optimizer};
+use w7x_turbulence_control::evolve::{self, Evolution};
+use w7x_turbulence_control::{aggregate, compare, converge, ensemble, fit, lockstep, precision, reference, scan, sensitivity};
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::snapshots::ProfileSnapshots;
+use w7x_turbulence_control::steady;
+use w7x_turbulence_control::summary::SummaryTracker;
+#[cfg(feature = "tui")]
+use w7x_turbulence_control::tui::Dashboard;
+#[cfg(feature = "websocket")]
+use w7x_turbulence_control::web_dashboard::WebDashboard;
+use w7x_turbulence_control::{ControlAction, SimError, StellaratorState};
+use std::sync::Mutex;
+use tracing::info;
+use tracing_subscriber::EnvFilter;
 
-use ndarray::Array1;
-use std::fs::File;
-use std::io::{BufWriter, Write};
-
-#[derive(Clone, Copy, PartialEq, Debug)]
-enum ConfinementMode {
-    Normal,
-    TurbulencePulse,
-}
-
-struct StellaratorState {
-    radius_grid: Array1<f64>,
-    dr: f64,
-    nr: usize,
-    impurity_density: Array1<f64>,
-    electron_density: Array1<f64>,
-    electron_temp: Array1<f64>,
-    d_neo: f64,
-    d_turb_base: f64,
-    v_neo: f64,
-    confinement_mode: ConfinementMode,
-    time: f64,
-    pulse_start_time: Option<f64>,
-    last_pulse_end_time: Option<f64>,  // ⭐ Added
-    cooldown_duration: f64,            // ⭐ Added
-    center_impurity_history: Vec<f64>,
-    edge_impurity_history: Vec<f64>,
-    turbulence_history: Vec<f64>,
-    time_history: Vec<f64>,
-}
-
-impl StellaratorState {
-    fn new(nr: usize) -> Self {
-        let dr = 1.0 / (nr - 1) as f64;
-        let radius_grid = Array1::linspace(0.0, 1.0, nr);
-
-        let mut state = StellaratorState {
-            radius_grid,
-            dr,
-            nr,
-            impurity_density: Array1::zeros(nr),
-            electron_density: Array1::zeros(nr),
-            electron_temp: Array1::zeros(nr),
-            d_neo: 0.02,
-            d_turb_base: 1.5,  // ⭐ 1.0 → 1.5
-            v_neo: -0.5,       // ⭐ -0.8 → -0.5 (weaker)
-            confinement_mode: ConfinementMode::Normal,
-            time: 0.0,
-            pulse_start_time: None,
-            last_pulse_end_time: None,     // ⭐
-            cooldown_duration: 0.5,        // ⭐ 500ms
-            center_impurity_history: Vec::new(),
-            edge_impurity_history: Vec::new(),
-            turbulence_history: Vec::new(),
-            time_history: Vec::new(),
-        };
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Run,
+    Serve,  // External control over ZeroMQ instead of the built-in controller
+    Scan,      // Parameter grid from [scan], summary table only
+    Ensemble,  // Monte Carlo replicas from [ensemble], percentile bands
+    Sensitivity,  // Sobol indices over [sensitivity] ranges
+    Optimize,     // Bayesian optimization of [optimize] parameters
+    Evolve,       // Genetic search over pulse waveforms from [evolve]
+    Compare,      // Same scenario without control, adaptive, and always on
+    Lockstep,     // [lockstep] controllers side by side on shared noise realizations
+    Steady,       // Steady-state n_Z profile of the initial transport
+    Converge,     // Observed order under nr / dt refinement from [converge]
+    Precision,    // f32 vs f64 transport rates, divergence of n_Z(0)
+    Aggregate,    // Statistics over finished runs in [aggregate] directory
+    Validate,     // RMSE and lag against the [reference] measured trace
+    Fit,          // Nelder–Mead fit of transport coefficients to the reference
+}
+
+struct Options {
+    mode: Mode,
+    config: Option<String>,
+    preset: Option<Preset>,
+    resume: Option<String>,
+    checkpoint: Option<String>,
+    checkpoint_interval: f64,
+    t_max: Option<f64>,
+    speed: Option<f64>,  // Overrides [serve] speed
+    seed: Option<u64>,   // Overrides the top-level master seed
+    tui: bool,
+    serve_dashboard: bool,
+}
+
+fn parse_args() -> Options {
+    let mut options = Options {
+        mode: Mode::Run,
+        config: None,
+        preset: None,
+        resume: None,
+        checkpoint: None,
+        checkpoint_interval: 1.0,
+        t_max: None,
+        speed: None,
+        seed: None,
+        tui: false,
+        serve_dashboard: false,
+    };
 
-        state.initialize_profiles();
-        state
+    let mut args = std::env::args().skip(1).peekable();
+    match args.peek().map(String::as_str) {
+        Some("serve") => options.mode = Mode::Serve,
+        Some("scan") => options.mode = Mode::Scan,
+        Some("ensemble") => options.mode = Mode::Ensemble,
+        Some("sensitivity") => options.mode = Mode::Sensitivity,
+        Some("optimize") => options.mode = Mode::Optimize,
+        Some("evolve") => options.mode = Mode::Evolve,
+        Some("compare") => options.mode = Mode::Compare,
+        Some("lockstep") => options.mode = Mode::Lockstep,
+        Some("steady") => options.mode = Mode::Steady,
+        Some("converge") => options.mode = Mode::Converge,
+        Some("precision") => options.mode = Mode::Precision,
+        Some("aggregate") => options.mode = Mode::Aggregate,
+        Some("validate") => options.mode = Mode::Validate,
+        Some("fit") => options.mode = Mode::Fit,
+        _ => {}
     }
+    if options.mode != Mode::Run {
+        args.next();
+    }
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next().unwrap_or_else(|| {
+                eprintln!("❌ Missing value for {}", arg);
+                std::process::exit(2);
+            })
+        };
+        match arg.as_str() {
+            "--config" => options.config = Some(value()),
+            "--preset" => {
+                let name = value();
+                options.preset = Some(Preset::from_name(&name).unwrap_or_else(|| {
+                    let names: Vec<&str> = Preset::ALL.iter().map(Preset::name).collect();
+                    eprintln!("❌ Unknown preset {}; available: {}", name, names.join(", "));
+                    std::process::exit(2);
+                }));
+            }
+            "--resume" => options.resume = Some(value()),
+            "--checkpoint" => options.checkpoint = Some(value()),
+            "--checkpoint-interval" => options.checkpoint_interval = parse_number(&value()),
+            "--t-max" => options.t_max = Some(parse_number(&value())),
+            "--speed" => options.speed = Some(parse_number(&value())),
+            "--seed" => {
+                let text = value();
+                options.seed = Some(text.parse().unwrap_or_else(|_| {
+                    eprintln!("❌ Not a seed (unsigned integer): {}", text);
+                    std::process::exit(2);
+                }));
+            }
+            "--tui" => options.tui = true,
+            "--serve-dashboard" => options.serve_dashboard = true,
+            _ => {
+                eprintln!("❌ Unknown argument: {}", arg);
+                std::process::exit(2);
+            }
+        }
+    }
+    if options.speed.is_some() && !matches!(options.mode, Mode::Run | Mode::Serve) {
+        eprintln!("❌ --speed is for single runs");
+        std::process::exit(2);
+    }
+    if options.tui && !matches!(options.mode, Mode::Run | Mode::Serve) {
+        eprintln!("❌ --tui is for single runs");
+        std::process::exit(2);
+    }
+    if options.tui && !cfg!(feature = "tui") {
+        eprintln!("❌ --tui needs a build with `--features tui`");
+        std::process::exit(2);
+    }
+    if options.serve_dashboard && !matches!(options.mode, Mode::Run | Mode::Serve) {
+        eprintln!("❌ --serve-dashboard is for single runs");
+        std::process::exit(2);
+    }
+    if options.serve_dashboard && !cfg!(feature = "websocket") {
+        eprintln!("❌ --serve-dashboard needs a build with `--features websocket`");
+        std::process::exit(2);
+    }
+    options
+}
 
-    fn initialize_profiles(&mut self) {
-        for (i, &r) in self.radius_grid.iter().enumerate() {
-            self.electron_density[i] = 8e19 * (1.0 - r.powi(2));
-            self.electron_temp[i] = 8.0 * (1.0 - r.powi(2));
-            self.impurity_density[i] = 1e18 * (0.2 + 0.8 * r.powi(2));
+/// Installs the `tracing` subscriber: plain message lines (no timestamp,
+/// level, or target) filtered by `RUST_LOG` or `[logging] filter`. With
+/// the dashboard up they go only to `[logging] file`, if set.
+fn init_logging(config: &LoggingConfig, tui: bool) {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(&config.filter))
+        .unwrap_or_else(|e| {
+            eprintln!("❌ Invalid log filter {:?}: {}", config.filter, e);
+            std::process::exit(2);
+        });
+    let format = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .without_time()
+        .with_level(false)
+        .with_target(false);
+    match &config.file {
+        Some(path) => {
+            let file = std::fs::File::create(path).unwrap_or_else(|e| {
+                eprintln!("❌ Could not open {}: {}", path, e);
+                std::process::exit(1);
+            });
+            format.with_ansi(false).with_writer(Mutex::new(file)).init();
         }
+        None if tui => format.with_writer(std::io::sink).init(),
+        None => format.init(),
+    }
+}
+
+fn parse_number(text: &str) -> f64 {
+    text.parse().unwrap_or_else(|_| {
+        eprintln!("❌ Not a number: {}", text);
+        std::process::exit(2);
+    })
+}
+
+fn main() {
+    let options = parse_args();
+    let mut config = match &options.config {
+        Some(path) => Config::load(path).unwrap_or_else(|e| {
+            eprintln!("❌ Could not load config {}: {}", path, e);
+            std::process::exit(1);
+        }),
+        None => Config::default(),
+    };
+    if let Some(preset) = options.preset {
+        preset.apply(&mut config);
     }
+    if let Some(speed) = options.speed {
+        config.serve.speed = speed;
+    }
+    if options.seed.is_some() {
+        config.seed = options.seed;
+    }
+    if let Err(e) = config.validate() {
+        eprintln!("❌ {}", e);
+        std::process::exit(2);
+    }
+    init_logging(&config.logging, options.tui);
 
-    fn calculate_turbulence_level(&self, r_idx: usize) -> f64 {
-        let r = self.radius_grid[r_idx];
-        if r < 0.02 || r > 0.98 {
-            return 0.05;
+    println!("🌟 W7-X Adaptive Turbulence Control Simulator v3.0 (Cooldown Added)");
+    println!("{}", "=".repeat(60));
+    if let Some(preset) = options.preset {
+        println!("🧭 Preset {}: {}", preset.name(), preset.description());
+    }
+
+    match options.mode {
+        Mode::Scan => return run_scan(&options, &config),
+        Mode::Ensemble => return run_ensemble(&options, &config),
+        Mode::Sensitivity => return run_sensitivity(&options, &config),
+        Mode::Optimize => return run_optimize(&options, &config),
+        Mode::Evolve => return run_evolve(&options, &config),
+        Mode::Compare => return run_compare(&options, &config),
+        Mode::Lockstep => return run_lockstep(&options, &config),
+        Mode::Steady => return run_steady(&config),
+        Mode::Converge => return run_converge(&options, &config),
+        Mode::Precision => return run_precision(&options, &config),
+        Mode::Aggregate => return run_aggregate(&config),
+        Mode::Validate => return run_validate(&config),
+        Mode::Fit => return run_fit(&config),
+        Mode::Run | Mode::Serve => {}
+    }
+    if let Some(t_max) = options.t_max {
+        config.simulation.t_max = t_max;
+    }
+    let metadata = RunMetadata::collect(&config);
+
+    let (mut state, saved_loop) = match &options.resume {
+        Some(path) => match Simulation::load_checkpoint(path) {
+            Ok((state, saved)) => {
+                println!("♻️ Resumed from {} at t={:.3}s", path, state.time);
+                (state, Some(saved))
+            }
+            Err(e) => {
+                eprintln!("❌ Could not load checkpoint {}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        None => (StellaratorState::from_config(&config), None),
+    };
+    state.history.recording = config.output.keep_history;
+    let mut sim = Simulation::with_state(state, &config);
+    let resuming = options.resume.is_some();
+    let mut sink = create_sink(config.output.trace_format, &config.output.trace, resuming, &metadata)
+        .unwrap_or_else(|e| {
+            eprintln!("❌ Could not open {}: {}", config.output.trace, e);
+            std::process::exit(1);
+        });
+    let mut events = (!config.output.events.is_empty()).then(|| {
+        EventLog::create(&config.output.events, resuming, &metadata).unwrap_or_else(|e| {
+            eprintln!("❌ Could not open {}: {}", config.output.events, e);
+            std::process::exit(1);
+        })
+    });
+    if let Some(model) = &config.detection.model {
+        use_model_detector(&mut sim, model, &config);
+    }
+    use_custom_controller(&mut sim, &config);
+    if let (Some(saved), Some(path)) = (saved_loop, &options.resume) {
+        if let Err(e) = sim.restore(saved) {
+            eprintln!("❌ Could not restore the control loop from {}: {}", path, e);
+            std::process::exit(1);
         }
+    }
+    let mut server = start_server(&options, &config);
+    let mut operator_log = OperatorLog::new(config.output.critical_density);
+    let mut tracker = SummaryTracker::new(&sim.state, config.output.critical_density);
+    let mut snapshots = ProfileSnapshots::new(config.output.profile_cadence);
+    let mut pulses: Vec<(f64, f64)> = Vec::new(); // (start, end) for the plot
 
-        let dn_dr = (self.electron_density[r_idx + 1] - self.electron_density[r_idx - 1]) 
-                    / (2.0 * self.dr);
-        let dt_dr = (self.electron_temp[r_idx + 1] - self.electron_temp[r_idx - 1]) 
-                    / (2.0 * self.dr);
+    let t_max = config.simulation.t_max;
+    let mut next_checkpoint = sim.state.time + options.checkpoint_interval;
+    let mut step = 0;
+    let status_interval = config.logging.status_interval;
 
-        let ln = (self.electron_density[r_idx] / dn_dr.abs().max(1e-10)).abs();
-        let lt = (self.electron_temp[r_idx] / dt_dr.abs().max(1e-10)).abs();
-        let eta = (ln / lt).max(0.1).min(10.0);
+    println!("Simulation parameters:");
+    println!("  dt = {:.6}s, dr = {:.4}, nr = {}", sim.dt, sim.state.dr, sim.state.nr);
+    println!("  D_neo = {:.2}, D_turb = {:.2}, v_neo = {:.2}", 
+             sim.state.d_neo, sim.state.d_turb_base, sim.state.v_neo);
+    println!("  Pulse: {}ms, Cooldown: {}ms", (sim.state.pulse_duration * 1000.0) as u32,
+             (sim.state.cooldown_duration * 1000.0) as u32);
+    if sim.state.pulse_shape != PulseShape::Square {
+        println!("  Pulse shape: {:?}", sim.state.pulse_shape);
+    }
+    println!("  Actuator: latency {:.0}ms, rise {:.0}ms, fall {:.0}ms",
+             sim.state.actuator.latency * 1000.0, sim.state.actuator.rise_time * 1000.0,
+             sim.state.actuator.fall_time * 1000.0);
+    if sim.state.ecrh.max_power > 0.0 {
+        println!("  ECRH: {:.1}MW at r={:.2}, ramp {:.0}MW/s, budget {:.0}MJ",
+                 sim.state.ecrh.max_power, sim.state.ecrh.deposition_radius,
+                 sim.state.ecrh.ramp_rate, sim.state.ecrh.energy_budget);
+    }
+    println!("  Detection alarms: {}", config.detection.alarms.iter()
+             .map(|a| a.name.as_str()).collect::<Vec<_>>().join(", "));
+    if config.detection.voting != Voting::Any {
+        println!("  Detection voting: {:?}", config.detection.voting);
+    }
+    if let Some(fuzzy) = &config.controller.fuzzy {
+        println!("  Fuzzy controller: {} rules, pulse above {:.1}×", fuzzy.rules.len(), fuzzy.pulse_above);
+    }
+    if let Some(schedule) = &config.controller.schedule {
+        println!("  Gain schedule by {:?}: {}", schedule.indicator, schedule.regimes.iter()
+                 .map(|r| r.name.as_str()).collect::<Vec<_>>().join(", "));
+    }
+    println!("  Diagnostics: {:.1}ms sampling, SXR noise {:.0}%",
+             config.diagnostics.sample_interval * 1000.0,
+             config.diagnostics.sxr.noise_fraction * 100.0);
+    if config.daq.cycle > 0.0 || config.daq.latency > 0.0 {
+        println!("  DAQ: {:.2}ms control cycle, {:.2}ms latency",
+                 config.daq.cycle * 1000.0, config.daq.latency * 1000.0);
+    }
+    println!("  Recording cadence: {:?}", sim.state.history.cadence);
+    if config.serve.speed > 0.0 {
+        println!("  Pacing: {}× real time", config.serve.speed);
+    }
+    println!("{}", "=".repeat(60));
+
+    operator_log.note(sim.state.time, &format!(
+        "Run started: {:.1} s planned, central impurity {:.2e} m⁻³.",
+        t_max, sim.state.impurity_density[0]
+    ));
+
+    // The dashboard owns the terminal and has its own pause key
+    let mut console = match options.tui {
+        true => None,
+        false => match Console::start() {
+            Ok(console) => {
+                println!("⏯️ Type `pause` or send SIGUSR1 (pid {}) to hold the run", std::process::id());
+                Some(console)
+            }
+            Err(e) => {
+                eprintln!("❌ Console not available: {}", e);
+                None
+            }
+        },
+    };
+    let mut web = start_web_dashboard(&options, &config, &sim, t_max);
+    let mut dashboard = options.tui.then(|| {
+        Dashboard::start(t_max, config.output.critical_density).unwrap_or_else(|e| {
+            eprintln!("❌ Could not start the dashboard: {}", e);
+            std::process::exit(1);
+        })
+    });
 
-        let factor = match self.confinement_mode {
-            ConfinementMode::Normal => {
-                if eta > 0.8 && eta < 1.2 {
-                    0.3
-                } else {
-                    1.0
+    let mut pacer = (config.serve.speed > 0.0).then(|| Pacer::new(config.serve.speed, sim.state.time));
+
+    while sim.state.time < t_max {
+        if let Some(console) = &mut console {
+            let held = console.poll(&mut sim, &mut config, |line| println!("{}", line));
+            if let Some(pacer) = pacer.as_mut().filter(|_| held) {
+                pacer.restart(sim.state.time);
+            }
+        }
+        let action = match &mut server {
+            Some(server) => serve_step(server, &mut sim),
+            None => sim.step(),
+        };
+        if let Err(SimError::NumericalInstability(fault)) = sim.check_finite() {
+            leave(&mut dashboard);
+            let path = &config.numerics.crash_dump;
+            eprintln!("❌ Non-finite value: {}", fault);
+            match crash::write_crash_dump(path, &fault, &sim.guard, &metadata, &sim.state) {
+                Ok(()) => eprintln!("   State, parameters, and last {} steps written to {}", sim.guard.recent().len(), path),
+                Err(e) => eprintln!("   Crash dump {} failed: {}", path, e),
+            }
+            eprintln!("   Check simulation.dt = {:.2e}s against the explicit stability limit.", sim.dt);
+            std::process::exit(1);
+        }
+        if let Some(sample) = sim.state.recorded_sample() {
+            if let Err(e) = sink.write_sample(sample) {
+                leave(&mut dashboard);
+                eprintln!("❌ Trace write failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        for event in sim.state.drain_events() {
+            if let Some(log) = &mut events {
+                if let Err(e) = log.write(&event) {
+                    leave(&mut dashboard);
+                    eprintln!("❌ Event log write failed: {}", e);
+                    std::process::exit(1);
                 }
             }
-            ConfinementMode::TurbulencePulse => {
-                if r > 0.7 { 
-                    5.0  // ⭐ 3.0 → 5.0
-                } else { 
-                    1.0 
+            if let Some(dashboard) = &mut dashboard {
+                dashboard.note(&event);
+            }
+            if let Some(web) = &mut web {
+                web.note(&event);
+            }
+            match event.event {
+                Event::PulseStarted { .. } => pulses.push((event.time, f64::INFINITY)),
+                Event::PulseEnded { .. } => {
+                    if let Some(pulse) = pulses.last_mut() {
+                        pulse.1 = event.time;
+                    }
                 }
+                _ => {}
             }
-        };
+        }
+        operator_log.observe(&sim.state, action);
+        snapshots.record(&sim.state);
+        tracker.observe(&sim.state, sim.dt);
+        if let Some(web) = &mut web {
+            web.refresh(&sim);
+        }
+        if let Some(live) = &mut dashboard {
+            match live.refresh(&sim) {
+                Ok(true) => {}
+                Ok(false) => {
+                    leave(&mut dashboard);
+                    println!("⏹️ Run ended from the dashboard at t={:.3}s", sim.state.time);
+                    break;
+                }
+                Err(e) => {
+                    leave(&mut dashboard);
+                    eprintln!("❌ Dashboard failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
 
-        self.d_turb_base * factor
+        if sim.state.terminated() {
+            leave(&mut dashboard);
+            println!("🛑 Discharge terminated at t={:.3}s", sim.state.time);
+            break;
+        }
+
+        if status_interval > 0 && step % status_interval == 0 {
+            info!(
+                target: "status",
+                "t={:.2}s | n_Z(0)={:.2e} | Mode={:?}",
+                sim.state.time, sim.state.impurity_density[0], sim.state.confinement_mode
+            );
+            if let Some(m) = sim.last_measurement() {
+                info!(
+                    target: "status",
+                    "         measured: SXR={:.2e} | edge={:.2e} | D_turb={:.2}",
+                    m.central_sxr, m.edge_density, m.turbulence
+                );
+            }
+        }
+
+        if let Some(path) = &options.checkpoint {
+            if sim.state.time >= next_checkpoint {
+                if let Err(e) = sim.save_checkpoint(path) {
+                    eprintln!("❌ Checkpoint failed: {}", e);
+                }
+                next_checkpoint += options.checkpoint_interval;
+            }
+        }
+        if let Some(pacer) = &mut pacer {
+            pacer.wait(sim.state.time);
+        }
+        step += 1;
+    }
+    leave(&mut dashboard);
+    if let Some(web) = web {
+        web.finish(&sim);
     }
 
-    fn calculate_flux(&self, r_idx: usize) -> f64 {
-        if r_idx == 0 || r_idx >= self.nr - 1 {
-            return 0.0;
+    println!("{}", "=".repeat(60));
+    let summary = tracker.finish(&sim.state, sim.dt);
+    println!("📊 Final statistics:");
+    println!("  Center impurity: {:.2e} m⁻³", sim.state.impurity_density[0]);
+    println!("  Edge impurity: {:.2e} m⁻³", sim.state.impurity_density[sim.state.nr-1]);
+    let (zeff, dilution) = sim.state.charge_balance(0);
+    println!("  Z_eff(0) = {:.2}, n_i/n_e(0) = {:.2}", zeff, dilution);
+    println!("  Particle balance error: {:.2e} (inventory {:.3e}, in {:.3e}, out {:.3e})",
+             summary.conservation_error, summary.final_inventory, summary.source_input, summary.outflow);
+    println!("  Pulses: {} ({:.1}% duty), {:.2}s above {:.1e} m⁻³", summary.pulses,
+             summary.duty_cycle * 100.0, summary.time_above_critical, summary.critical_density);
+    println!("  Stored energy: ⟨W⟩ = {:.2} MJ ({:.1}% confinement loss)",
+             summary.mean_stored_energy, summary.confinement_loss * 100.0);
+    if let Some(poloidal) = &sim.state.poloidal {
+        let mid = sim.state.nr / 2;
+        println!("  Poloidal asymmetry at r={:.2}: outboard/inboard = {:.2}",
+                 sim.state.radius_grid[mid], poloidal.in_out_ratio(mid));
+    }
+    if summary.elm_bursts > 0 {
+        println!("  Edge bursts: {}, followed within {:.0} ms by {} of the pulses",
+                 summary.elm_bursts, sim.state.elms.attribution_window * 1e3, summary.pulses_after_elm);
+    }
+    if sim.state.sol.config.enabled {
+        println!("  SOL: {:.2e} held at the end, {:.2e} exhausted to the divertor (∫ V' n_Z dr)",
+                 summary.sol_inventory, summary.divertor_exhaust);
+    }
+    if sim.state.ramp.enabled() {
+        println!("  Pulses during ramp-up/ramp-down: {}", summary.pulses_in_ramps);
+    }
+    if sim.state.ecrh.max_power > 0.0 {
+        println!("  ECRH energy: {:.1} / {:.0} MJ", sim.state.ecrh.energy_used(), sim.state.ecrh.energy_budget);
+    }
+    if sim.state.regularization != Regularization::None {
+        println!("  Regularized cells: {}", sim.state.regularized_cells);
+    }
+    
+    if let Some(path) = &options.checkpoint {
+        match sim.save_checkpoint(path) {
+            Ok(()) => println!("💾 Checkpoint: {}", path),
+            Err(e) => eprintln!("❌ Checkpoint failed: {}", e),
         }
+    }
 
-        let n_z = self.impurity_density[r_idx];
-        let dn_z_dr = (self.impurity_density[r_idx + 1] - self.impurity_density[r_idx - 1]) 
-                      / (2.0 * self.dr);
+    if let Err(e) = sink.finish() {
+        eprintln!("❌ Save failed: {}", e);
+    } else {
+        println!("💾 Save complete: {}", config.output.trace);
+    }
+    if let Some(log) = &mut events {
+        match log.finish() {
+            Ok(()) => println!("💾 Event log: {}", config.output.events),
+            Err(e) => eprintln!("❌ Event log save failed: {}", e),
+        }
+    }
 
-        let d_total = self.d_neo + self.calculate_turbulence_level(r_idx);
+    if !config.output.summary.is_empty() {
+        match summary.write_json(&config.output.summary, &metadata) {
+            Ok(()) => println!("💾 Run summary: {}", config.output.summary),
+            Err(e) => eprintln!("❌ Run summary save failed: {}", e),
+        }
+    }
 
-        self.v_neo * n_z - d_total * dn_z_dr
+    if let Some(path) = &config.output.hdf5 {
+        save_hdf5(path, &sim.state, &snapshots, &metadata);
+    }
+    if let Some(path) = &config.output.netcdf {
+        save_netcdf(path, &sim.state, &snapshots, &metadata);
+    }
+    if let Some(path) = &config.output.parquet {
+        save_parquet(path, &snapshots, &metadata);
+    }
+    if let Some(path) = &config.output.plot {
+        save_plot(path, &sim.state, &snapshots, &pulses, config.output.critical_density);
+    }
+    if let Some(path) = &config.output.animation {
+        save_animation(path, &snapshots);
+    }
+    if let Some(path) = &config.output.imas {
+        save_imas(path, &sim.state, &snapshots, &metadata);
+    }
+    if let Some(path) = &config.output.mdsplus {
+        let tree = w7x_turbulence_control::mdsplus::tree(&sim.state.history, &snapshots, &metadata);
+        match w7x_turbulence_control::mdsplus::write_json(path, &tree) {
+            Ok(()) => println!("💾 MDSplus signal tree: {}", path),
+            Err(e) => eprintln!("❌ MDSplus tree save failed: {}", e),
+        }
     }
 
-    fn detect_impurity_accumulation(&self) -> bool {
-        let center_nz = self.impurity_density[0];
-        
-        if center_nz > 8e17 {  // ⭐ 5e17 → 8e17 (higher threshold)
-            return true;
+    if let Some(path) = &config.output.operator_log {
+        operator_log.note(sim.state.time, &format!(
+            "Run ended after {} pulses, central impurity {:.2e} m⁻³.",
+            operator_log.pulse_count(), sim.state.impurity_density[0]
+        ));
+        match operator_log.write(path, &metadata) {
+            Ok(()) => println!("📝 Operator log: {}", path),
+            Err(e) => eprintln!("❌ Operator log save failed: {}", e),
         }
+    }
+}
 
-        if self.center_impurity_history.len() > 100 {
-            let last = self.center_impurity_history.len() - 1;
-            let prev = last - 100;
-            let rate = (self.center_impurity_history[last] - self.center_impurity_history[prev])
-                / (self.time_history[last] - self.time_history[prev]);
-            if rate > 1.5e18 {  // ⭐ Higher growth rate
-                return true;
-            }
+fn run_scan(options: &Options, config: &Config) {
+    let mut config = config.clone();
+    if let Some(t_max) = options.t_max {
+        config.simulation.t_max = t_max;
+    }
+    let metadata = RunMetadata::collect(&config);
+    let points = config.scan.points(&config).unwrap_or_else(|e| {
+        eprintln!("❌ Invalid scan: {}", e);
+        std::process::exit(2);
+    });
+    println!("🔬 Scan: {} runs of {:.1}s", points.len(), config.simulation.t_max);
+
+    let results = scan::run_scan(&config).unwrap_or_else(|e| {
+        eprintln!("❌ Scan failed: {}", e);
+        std::process::exit(1);
+    });
+    println!("{:>4} {:>10} {:>9} {:>9} {:>7} {:>11} {:>7} {:>6}",
+             "run", "threshold", "pulse[s]", "cool[s]", "v_neo", "n_Z(0)", "pulses", "duty");
+    for (i, r) in results.iter().enumerate() {
+        print!("{:>4} {:>10.2e} {:>9.3} {:>9.3} {:>7.2} ",
+               i, r.point.threshold, r.point.pulse_duration, r.point.cooldown, r.point.v_neo);
+        match &r.summary {
+            Ok(s) => println!("{:>11.2e} {:>7} {:>5.1}%", s.final_center_impurity, s.pulses, s.duty_cycle * 100.0),
+            Err(e) => println!("❌ {}", e),
         }
-        false
-    }
-
-    fn update(&mut self, dt: f64) {
-        // ⭐ Cooldown control logic
-        match self.confinement_mode {
-            ConfinementMode::Normal => {
-                // Check cooldown
-                let can_pulse = if let Some(last_end) = self.last_pulse_end_time {
-                    self.time - last_end > self.cooldown_duration
-                } else {
-                    true
-                };
-                
-                if can_pulse && self.detect_impurity_accumulation() {
-                    println!("⚠️ t={:.3}s: Impurity accumulation! Starting pulse", self.time);
-                    self.confinement_mode = ConfinementMode::TurbulencePulse;
-                    self.pulse_start_time = Some(self.time);
-                }
-            }
-            ConfinementMode::TurbulencePulse => {
-                if let Some(start) = self.pulse_start_time {
-                    if self.time - start > 0.2 {  // ⭐ 0.1 → 0.2s
-                        println!("✅ t={:.3}s: Return to normal (cooldown {:.1}s)", 
-                                 self.time, self.cooldown_duration);
-                        self.confinement_mode = ConfinementMode::Normal;
-                        self.last_pulse_end_time = Some(self.time);  // ⭐
-                        self.pulse_start_time = None;
-                    }
+    }
+    let failures = results.iter().filter(|r| r.summary.is_err()).count();
+    if failures > 0 {
+        eprintln!("⚠️ {} of {} runs failed", failures, results.len());
+    }
+
+    match scan::write_summary(&config.scan.output, &results, &metadata) {
+        Ok(()) => println!("💾 Scan summary: {}", config.scan.output),
+        Err(e) => eprintln!("❌ Scan summary save failed: {}", e),
+    }
+}
+
+fn run_ensemble(options: &Options, config: &Config) {
+    let mut config = config.clone();
+    if let Some(t_max) = options.t_max {
+        config.simulation.t_max = t_max;
+    }
+    let metadata = RunMetadata::collect(&config);
+    let settings = &config.ensemble;
+    println!("🎲 Ensemble: {} replicas of {:.1}s, seed {}",
+             settings.replicas, config.simulation.t_max, RngRegistry::new(&config).seed(Stream::Ensemble));
+
+    let result = ensemble::run_ensemble(&config).unwrap_or_else(|e| {
+        eprintln!("❌ Ensemble failed: {}", e);
+        std::process::exit(1);
+    });
+    if result.summaries.is_empty() {
+        eprintln!("❌ Ensemble has no replicas");
+        std::process::exit(2);
+    }
+
+    let stat = |mut values: Vec<f64>| {
+        let mut p = |q| ensemble::percentile(&mut values, q);
+        (p(5.0), p(50.0), p(95.0))
+    };
+    let densities = [
+        ("final n_Z(0)", stat(result.summaries.iter().map(|s| s.final_center_impurity).collect())),
+        ("peak n_Z(0)", stat(result.summaries.iter().map(|s| s.peak_center_impurity).collect())),
+    ];
+    let counts = [
+        ("pulses", stat(result.summaries.iter().map(|s| s.pulses as f64).collect())),
+        ("duty cycle", stat(result.summaries.iter().map(|s| s.duty_cycle).collect())),
+    ];
+    println!("{:>14} {:>10} {:>10} {:>10}", "", "p5", "p50", "p95");
+    for (name, (p5, p50, p95)) in densities {
+        println!("{:>14} {:>10.3e} {:>10.3e} {:>10.3e}", name, p5, p50, p95);
+    }
+    for (name, (p5, p50, p95)) in counts {
+        println!("{:>14} {:>10.2} {:>10.2} {:>10.2}", name, p5, p50, p95);
+    }
+
+    match ensemble::write_bands(&settings.output, &settings.percentiles, &result, &metadata) {
+        Ok(()) => println!("💾 Percentile bands ({} points): {}", result.time.len(), settings.output),
+        Err(e) => eprintln!("❌ Band save failed: {}", e),
+    }
+}
+
+fn run_sensitivity(options: &Options, config: &Config) {
+    let mut config = config.clone();
+    if let Some(t_max) = options.t_max {
+        config.simulation.t_max = t_max;
+    }
+    let metadata = RunMetadata::collect(&config);
+    let settings = &config.sensitivity;
+    if settings.samples == 0 || settings.parameters.is_empty() {
+        eprintln!("❌ Sensitivity analysis needs samples > 0 and at least one parameter");
+        std::process::exit(2);
+    }
+    println!("📐 Sobol analysis: {} runs of {:.1}s",
+             settings.samples * (settings.parameters.len() + 2), config.simulation.t_max);
+
+    let result = sensitivity::run_sensitivity(&config).unwrap_or_else(|e| {
+        eprintln!("❌ Sensitivity analysis failed: {}", e);
+        std::process::exit(1);
+    });
+    let report = result.report(settings.samples);
+    print!("{}", report);
+    let text = format!("{}{}\n{}", metadata::CSV_PREFIX, metadata.to_json(), report);
+    match std::fs::write(&settings.output, text) {
+        Ok(()) => println!("💾 Sensitivity report: {}", settings.output),
+        Err(e) => eprintln!("❌ Report save failed: {}", e),
+    }
+}
+
+fn run_optimize(options: &Options, config: &Config) {
+    let mut config = config.clone();
+    if let Some(t_max) = options.t_max {
+        config.simulation.t_max = t_max;
+    }
+    let metadata = RunMetadata::collect(&config);
+    let settings = &config.optimize;
+    if settings.parameters.is_empty() || settings.initial_samples == 0 {
+        eprintln!("❌ Optimization needs at least one parameter and initial_samples > 0");
+        std::process::exit(2);
+    }
+    let names: Vec<&str> = settings.parameters.iter().map(|p| p.parameter.name()).collect();
+    println!("🎯 Optimizing {} over {} + {} runs of {:.1}s",
+             names.join(", "), settings.initial_samples, settings.iterations,
+             config.simulation.t_max);
+
+    let print = |i: usize, e: &Evaluation| {
+        let values: Vec<String> = e.values.iter().map(|v| format!("{:.3e}", v)).collect();
+        println!("  #{:<3} cost {:8.3} | {} | ⟨n_Z(0)⟩={:.2e} duty={:.1}%",
+                 i, e.cost, values.join(" "), e.summary.mean_center_impurity,
+                 e.summary.duty_cycle * 100.0);
+    };
+    fn failed<T>(e: SimError) -> T {
+        eprintln!("❌ Optimization failed: {}", e);
+        std::process::exit(1)
+    }
+    let mut optimizer = Optimizer::new(&config);
+    for (i, e) in optimizer.initialize().unwrap_or_else(failed).iter().enumerate() {
+        print(i, e);
+    }
+    for i in 0..settings.iterations {
+        print(settings.initial_samples + i, optimizer.iterate().unwrap_or_else(failed));
+    }
+
+    if let Some(best) = optimizer.best() {
+        println!("🏆 Best cost {:.3}:", best.cost);
+        for (name, value) in names.iter().zip(&best.values) {
+            println!("  {} = {:.4e}", name, value);
+        }
+    }
+    match optimize::write_history(&settings.output, &settings.parameters, &optimizer.evaluations, &metadata) {
+        Ok(()) => println!("💾 Optimization history: {}", settings.output),
+        Err(e) => eprintln!("❌ History save failed: {}", e),
+    }
+}
+
+fn run_evolve(options: &Options, config: &Config) {
+    let mut config = config.clone();
+    if let Some(t_max) = options.t_max {
+        config.simulation.t_max = t_max;
+    }
+    let metadata = RunMetadata::collect(&config);
+    let settings = &config.evolve;
+    if settings.population == 0 || settings.segments == 0 {
+        eprintln!("❌ Evolution needs population > 0 and segments > 0");
+        std::process::exit(2);
+    }
+    println!("🧬 Evolving {}-segment pulse waveforms: {} generations × {} runs of {:.1}s",
+             settings.segments, settings.generations, settings.population,
+             config.simulation.t_max);
+
+    let mut evolution = Evolution::new(&config);
+    let mut history = Vec::new();
+    for _ in 0..settings.generations {
+        let stats = evolution.step().unwrap_or_else(|e| {
+            eprintln!("❌ Evolution failed: {}", e);
+            std::process::exit(1);
+        });
+        println!("  gen {:>3} | best {:8.3} | mean {:8.3} | {:.0} ms, r > {:.2}",
+                 stats.generation, stats.best.cost, stats.mean_cost,
+                 stats.best.genome.pulse_duration * 1000.0, stats.best.genome.inner_radius);
+        history.push(stats);
+    }
+
+    if let Some(best) = history.iter().map(|g| &g.best).min_by(|a, b| a.cost.total_cmp(&b.cost)) {
+        let waveform: Vec<String> = best.genome.waveform.iter().map(|a| format!("{:.2}", a)).collect();
+        println!("🏆 Best waveform (cost {:.3}), as [plasma] settings:", best.cost);
+        println!("  pulse_amplitude = 1.0");
+        println!("  pulse_waveform = [{}]", waveform.join(", "));
+        println!("  pulse_duration = {:.4}", best.genome.pulse_duration);
+        let window = PulseWindow::edge(best.genome.inner_radius);
+        println!("  pulse_windows = [{{ center = {:.3}, width = {:.3} }}]", window.center, window.width);
+    }
+    match evolve::write_history(&settings.output, &history, &metadata) {
+        Ok(()) => println!("💾 Evolution history: {}", settings.output),
+        Err(e) => eprintln!("❌ History save failed: {}", e),
+    }
+}
+
+fn run_compare(options: &Options, config: &Config) {
+    let mut config = config.clone();
+    if let Some(t_max) = options.t_max {
+        config.simulation.t_max = t_max;
+    }
+    let metadata = RunMetadata::collect(&config);
+    println!("⚖️ Comparison: no control / adaptive / always on, {:.1}s each", config.simulation.t_max);
+
+    let comparison = compare::run_comparison(&config).unwrap_or_else(|e| {
+        eprintln!("❌ Comparison failed: {}", e);
+        std::process::exit(1);
+    });
+    println!("{:>11} {:>11} {:>11} {:>11} {:>9} {:>7} {:>6} {:>8}",
+             "strategy", "final n_Z", "mean n_Z", "peak n_Z", "above[s]", "pulses", "duty", "W loss");
+    for run in &comparison.runs {
+        let s = &run.summary;
+        println!("{:>11} {:>11.2e} {:>11.2e} {:>11.2e} {:>9.2} {:>7} {:>5.1}% {:>7.1}%",
+                 run.strategy.name(), s.final_center_impurity, s.mean_center_impurity,
+                 s.peak_center_impurity, s.time_above_critical, s.pulses, s.duty_cycle * 100.0,
+                 s.confinement_loss * 100.0);
+    }
+
+    let settings = &config.compare;
+    match comparison.write_table(&settings.output, &metadata) {
+        Ok(()) => println!("💾 Comparison table: {}", settings.output),
+        Err(e) => eprintln!("❌ Comparison table save failed: {}", e),
+    }
+    match comparison.write_traces(&settings.traces, &metadata) {
+        Ok(()) => println!("💾 Comparison traces ({} points): {}", comparison.time.len(), settings.traces),
+        Err(e) => eprintln!("❌ Comparison trace save failed: {}", e),
+    }
+}
+
+fn run_lockstep(options: &Options, config: &Config) {
+    let mut config = config.clone();
+    if let Some(t_max) = options.t_max {
+        config.simulation.t_max = t_max;
+    }
+    let metadata = RunMetadata::collect(&config);
+    let settings = &config.lockstep;
+    let names: Vec<&str> = settings.contenders.iter().map(|c| c.name.as_str()).collect();
+    println!("🏁 Lock-step: {}{} × {} realization(s), {:.1}s each",
+             if settings.baseline { "no_control / " } else { "" }, names.join(" / "),
+             settings.realizations.max(1), config.simulation.t_max);
+
+    let mut races = Vec::new();
+    for k in 0..settings.realizations.max(1) {
+        let race = lockstep::entrants(&config, k).and_then(|mut entrants| {
+            for entrant in entrants.iter_mut().filter(|e| e.name != lockstep::BASELINE) {
+                let (sim, config) = (&mut entrant.simulation, &entrant.config);
+                if let Some(model) = &config.detection.model {
+                    use_model_detector(sim, model, config);
                 }
             }
+            lockstep::race(entrants, k, settings.trace_interval)
+        });
+        races.push(race.unwrap_or_else(|e| {
+            eprintln!("❌ Lock-step realization {} failed: {}", k, e);
+            std::process::exit(1);
+        }));
+    }
+    println!("{:>4} {:>14} {:>11} {:>11} {:>11} {:>9} {:>7} {:>6} {:>8}",
+             "real", "controller", "final n_Z", "mean n_Z", "peak n_Z", "above[s]", "pulses", "duty", "W loss");
+    for race in &races {
+        for run in &race.runs {
+            let s = &run.summary;
+            println!("{:>4} {:>14} {:>11.2e} {:>11.2e} {:>11.2e} {:>9.2} {:>7} {:>5.1}% {:>7.1}%",
+                     race.realization, run.name, s.final_center_impurity, s.mean_center_impurity,
+                     s.peak_center_impurity, s.time_above_critical, s.pulses, s.duty_cycle * 100.0,
+                     s.confinement_loss * 100.0);
         }
+    }
 
-        // Transport equation
-        let mut new_nz = self.impurity_density.clone();
-        for i in 1..self.nr - 1 {
-            let r = self.radius_grid[i];
-            let flux_p = self.calculate_flux(i);
-            let flux_m = self.calculate_flux(i - 1);
+    match lockstep::write_table(&settings.output, &races, &metadata) {
+        Ok(()) => println!("💾 Lock-step table: {}", settings.output),
+        Err(e) => eprintln!("❌ Lock-step table save failed: {}", e),
+    }
+    match lockstep::write_traces(&settings.traces, &races, &metadata) {
+        Ok(()) => println!("💾 Lock-step traces: {}", settings.traces),
+        Err(e) => eprintln!("❌ Lock-step trace save failed: {}", e),
+    }
+}
 
-            let r_p = r + 0.5 * self.dr;
-            let r_m = r - 0.5 * self.dr;
+fn run_steady(config: &Config) {
+    let metadata = RunMetadata::collect(config);
+    let state = StellaratorState::from_config(config);
+    let settings = &config.steady_state;
+    let Some(steady) = steady::solve(&state, settings) else {
+        eprintln!("❌ Steady-state operator is singular (no particle loss at either boundary?)");
+        std::process::exit(1);
+    };
+    if !steady.bounded {
+        println!("♾️ No bounded steady state: the pinch outruns the losses and n_Z accumulates");
+    } else if steady.converged {
+        println!("🎯 Steady state after {} iteration(s)", steady.iterations);
+    } else {
+        eprintln!("⚠️ Steady state not converged after {} iterations (change {:.2e})",
+                  steady.iterations, steady.change);
+    }
+    let n = &steady.impurity_density;
+    println!("  n_Z(0) = {:.3e} m⁻³, n_Z(edge) = {:.3e} m⁻³", n[0], n[state.nr - 1]);
+    println!("  Peaking n_Z(0)/⟨n_Z⟩ = {:.2}", steady.peaking(&state));
+    println!("  Wall source = {:.3e} m⁻³/s", steady.wall_source);
 
-            let div_flux = if r > 0.01 {
-                (r_p * flux_p - r_m * flux_m) / (r * self.dr)
-            } else {
-                (flux_p - flux_m) / self.dr
-            };
-            
-            let source = if r > 0.85 { 2.5e17 } else { 0.0 };  // ⭐ Moderate value
+    let mut csv = format!("{}{}\nradius,impurity_density\n", metadata::CSV_PREFIX, metadata.to_json());
+    for (r, n) in state.radius_grid.iter().zip(n) {
+        csv += &format!("{:.6},{:.6e}\n", r, n);
+    }
+    match std::fs::write(&settings.output, csv) {
+        Ok(()) => println!("💾 Steady-state profile: {}", settings.output),
+        Err(e) => eprintln!("❌ Profile save failed: {}", e),
+    }
+}
+
+fn run_converge(options: &Options, config: &Config) {
+    let mut config = config.clone();
+    if let Some(t_max) = options.t_max {
+        config.converge.t_max = t_max;
+    }
+    let metadata = RunMetadata::collect(&config);
+    let settings = &config.converge;
+    if settings.levels < 2 || settings.ratio < 2 || config.simulation.nr < 3 {
+        eprintln!("❌ Convergence study needs levels ≥ 2, ratio ≥ 2, and nr ≥ 3");
+        std::process::exit(2);
+    }
+    let (nr, dt) = settings.resolution(&config, settings.levels - 1);
+    println!("📏 Convergence: {} levels refining {} by {} up to nr = {}, dt = {:.2e}s, {:.2}s each",
+             settings.levels, settings.vary.name(), settings.ratio, nr, dt, settings.t_max);
+
+    let levels = converge::run_convergence(&config).unwrap_or_else(|e| {
+        eprintln!("❌ Convergence study failed: {}", e);
+        std::process::exit(1);
+    });
+    let show = |value: Option<f64>, digits: usize| match value {
+        Some(v) => format!("{:.*e}", digits, v),
+        None => "-".to_string(),
+    };
+    println!("{:>5} {:>6} {:>10} {:>11} {:>10} {:>10} {:>7} {:>7}",
+             "level", "nr", "dt[s]", "n_Z(0)", "ΔL2", "Δmax", "p(L2)", "p(max)");
+    for (k, level) in levels.iter().enumerate() {
+        let order = |p: Option<f64>| p.map(|p| format!("{:.2}", p)).unwrap_or_else(|| "-".to_string());
+        println!("{:>5} {:>6} {:>10.2e} {:>11.3e} {:>10} {:>10} {:>7} {:>7}",
+                 k, level.nr, level.dt, level.profile[0],
+                 show(level.difference.map(|d| d.0), 2), show(level.difference.map(|d| d.1), 2),
+                 order(level.order.map(|p| p.0)), order(level.order.map(|p| p.1)));
+    }
+    match converge::write_levels(&settings.output, &levels, &metadata) {
+        Ok(()) => println!("💾 Convergence table: {}", settings.output),
+        Err(e) => eprintln!("❌ Convergence table save failed: {}", e),
+    }
+}
+
+fn run_precision(options: &Options, config: &Config) {
+    let mut config = config.clone();
+    if let Some(t_max) = options.t_max {
+        config.simulation.t_max = t_max;
+    }
+    let metadata = RunMetadata::collect(&config);
+    let settings = &config.precision_check;
+    if settings.trace_interval <= 0.0 {
+        eprintln!("❌ Precision check needs trace_interval > 0");
+        std::process::exit(2);
+    }
+    println!("🔬 Precision: f64 vs f32 transport rates, {:.1}s each, {}",
+             config.simulation.t_max, if settings.control { "closed loop" } else { "no control" });
+
+    let check = precision::run_precision_check(&config).unwrap_or_else(|e| {
+        eprintln!("❌ Precision check failed: {}", e);
+        std::process::exit(1);
+    });
+    println!("{:>9} {:>11} {:>11} {:>7}", "precision", "final n_Z", "peak n_Z", "pulses");
+    for (precision, s) in precision::Precision::ALL.iter().zip(&check.summaries) {
+        println!("{:>9} {:>11.4e} {:>11.4e} {:>7}",
+                 precision.name(), s.final_center_impurity, s.peak_center_impurity, s.pulses);
+    }
+    println!("  Max relative n_Z(0) difference: {:.2e}", check.max_relative_difference());
+    match check.diverged_at() {
+        Some(t) => println!("⚠️ Diverged past {:.1e} at t={:.3}s", check.tolerance, t),
+        None => println!("✅ Within {:.1e} throughout", check.tolerance),
+    }
+    match check.write_traces(&settings.output, &metadata) {
+        Ok(()) => println!("💾 Precision traces ({} points): {}", check.time.len(), settings.output),
+        Err(e) => eprintln!("❌ Precision trace save failed: {}", e),
+    }
+}
 
-            new_nz[i] = (self.impurity_density[i] + (-div_flux + source) * dt).max(0.0);
-            new_nz[i] = new_nz[i].min(1e20);
+fn run_aggregate(config: &Config) {
+    let metadata = RunMetadata::collect(config);
+    let settings = &config.aggregate;
+    let result = aggregate::run_aggregate(settings).unwrap_or_else(|e| {
+        eprintln!("❌ Could not read runs in {}: {}", settings.directory, e);
+        std::process::exit(1);
+    });
+    println!("📊 Aggregate of {}: {} traces, {} summaries",
+             settings.directory, result.traces.len(), result.summaries.len());
+    if result.traces.is_empty() && result.summaries.is_empty() {
+        eprintln!("❌ No traces or *summary.json files under {}", settings.directory);
+        std::process::exit(2);
+    }
+
+    if !result.histogram.is_empty() {
+        println!("{:>7} {:>5}", "pulses", "runs");
+        for (pulses, runs) in &result.histogram {
+            println!("{:>7} {:>5} {}", pulses, runs, "█".repeat(*runs));
+        }
+        println!("🏆 Pareto front ({} of {} runs):", result.pareto.len(), result.summaries.len());
+        for &i in &result.pareto {
+            let (path, s) = &result.summaries[i];
+            println!("  W loss {:>5.1}%  ⟨n_Z(0)⟩ {:.2e}  {}",
+                     s.confinement_loss * 100.0, s.mean_center_impurity, path.display());
         }
+        match result.write_histogram(&settings.histogram, &metadata) {
+            Ok(()) => println!("💾 Pulse histogram: {}", settings.histogram),
+            Err(e) => eprintln!("❌ Histogram save failed: {}", e),
+        }
+        match result.write_pareto(&settings.pareto, &metadata) {
+            Ok(()) => println!("💾 Pareto front: {}", settings.pareto),
+            Err(e) => eprintln!("❌ Pareto front save failed: {}", e),
+        }
+    }
+    if !result.time.is_empty() {
+        match result.write_traces(&settings.traces, &settings.percentiles, &metadata) {
+            Ok(()) => println!("💾 Combined traces ({} points): {}", result.time.len(), settings.traces),
+            Err(e) => eprintln!("❌ Combined trace save failed: {}", e),
+        }
+    } else if !result.traces.is_empty() {
+        eprintln!("⚠️ Traces share no common time interval; no combined traces written");
+    }
+}
+
+fn run_validate(config: &Config) {
+    let settings = &config.reference;
+    let measured = reference::load(settings).unwrap_or_else(|e| {
+        eprintln!("❌ Could not load reference {}: {}", settings.path, e);
+        std::process::exit(1);
+    });
+    let mut config = config.clone();
+    config.simulation.t_max = measured.time[measured.len() - 1];
+    let metadata = RunMetadata::collect(&config);
+    println!("📏 Validating {} against {} ({} points, t = {:.3}–{:.3}s)",
+             settings.channel.name(), settings.path, measured.len(),
+             measured.time[0], config.simulation.t_max);
+
+    let simulated = reference::simulate(&config, &measured, settings.channel, settings.scale)
+        .unwrap_or_else(|e| {
+            eprintln!("❌ Simulation failed: {}", e);
+            std::process::exit(1);
+        });
+    let m = reference::mismatch(&measured, &simulated, settings.max_lag);
+    println!("  RMSE:            {:.4e} ({:.1}% of RMS measured)", m.rmse, m.normalized_rmse * 100.0);
+    println!("  Bias:            {:+.4e}", m.bias);
+    println!("  Correlation:     {:.3}", m.correlation);
+    println!("  Best lag:        {:+.4}s (correlation {:.3}, positive = simulation late)",
+             m.lag, m.lag_correlation);
+    match reference::write_traces(&settings.output, &measured, &simulated, &metadata) {
+        Ok(()) => println!("💾 Measured vs simulated: {}", settings.output),
+        Err(e) => eprintln!("❌ Validation trace save failed: {}", e),
+    }
+}
+
+fn run_fit(config: &Config) {
+    let settings = &config.fit;
+    let measured = reference::load(&config.reference).unwrap_or_else(|e| {
+        eprintln!("❌ Could not load reference {}: {}", config.reference.path, e);
+        std::process::exit(1);
+    });
+    let metadata = RunMetadata::collect(config);
+    println!("🎯 Fitting D_neo, v_neo at ρ = {:?}{} to {} ({} points, ≤ {} runs)",
+             settings.knots, if settings.fit_source { " and the source" } else { "" },
+             config.reference.path, measured.len(), settings.max_evaluations);
 
-        new_nz[0] = new_nz[1];
-        new_nz[self.nr - 1] = 0.3 * new_nz[self.nr - 2];
+    let result = fit::fit(config, &measured).unwrap_or_else(|e| {
+        eprintln!("❌ Fit failed: {}", e);
+        std::process::exit(1);
+    });
+    let (initial, best) = (&result.initial, &result.best);
+    println!("  Normalized RMSE: {:.4} → {:.4} after {} runs{}",
+             initial.cost.sqrt(), best.cost.sqrt(), result.evaluations.len(),
+             if result.converged { "" } else { " (evaluation limit reached)" });
+    println!("{:>6} {:>11} {:>11}", "ρ", "D_neo m²/s", "v_neo m/s");
+    for ((rho, d), v) in result.knots.iter().zip(&best.coefficients.d_neo).zip(&best.coefficients.v_neo) {
+        println!("{:>6.2} {:>11.4e} {:>11.4}", rho, d, v);
+    }
+    println!("  Source: {:.3e} → {:.3e} m⁻³/s", initial.coefficients.source, best.coefficients.source);
+    match result.write_table(&settings.table, &metadata) {
+        Ok(()) => println!("💾 Fitted table: {} ([neoclassical] type = \"table\", path = \"{}\")",
+                           settings.table, settings.table),
+        Err(e) => eprintln!("❌ Fitted table save failed: {}", e),
+    }
+    match result.write_history(&settings.history, &metadata) {
+        Ok(()) => println!("💾 Fit history: {}", settings.history),
+        Err(e) => eprintln!("❌ Fit history save failed: {}", e),
+    }
+}
 
-        self.impurity_density = new_nz;
+/// Restores the terminal if the dashboard is up.
+fn leave(dashboard: &mut Option<Dashboard>) {
+    if let Some(dashboard) = dashboard.take() {
+        dashboard.finish();
+    }
+}
 
-        self.center_impurity_history.push(self.impurity_density[0]);
-        self.edge_impurity_history.push(self.impurity_density[self.nr - 1]);
-        self.turbulence_history.push(self.calculate_turbulence_level(self.nr - 2));
-        self.time_history.push(self.time);
+/// Stands in for `tui::Dashboard`; `--tui` is rejected without the feature.
+#[cfg(not(feature = "tui"))]
+struct Dashboard;
 
-        self.time += dt;
+#[cfg(not(feature = "tui"))]
+impl Dashboard {
+    fn start(_t_max: f64, _critical_density: f64) -> std::io::Result<Self> {
+        unreachable!("--tui without the tui feature")
     }
 
-    fn save_to_csv(&self, filename: &str) -> std::io::Result<()> {
-        let file = File::create(filename)?;
-        let mut writer = BufWriter::new(file);
+    fn note(&mut self, _event: &w7x_turbulence_control::events::TimedEvent) {}
+
+    fn refresh(&mut self, _sim: &Simulation) -> std::io::Result<bool> {
+        Ok(true)
+    }
 
-        writeln!(writer, "time,center_impurity,edge_impurity,turbulence")?;
-        for i in 0..self.time_history.len() {
-            writeln!(
-                writer,
-                "{:.6},{:.6e},{:.6e},{:.4}",
-                self.time_history[i],
-                self.center_impurity_history[i],
-                self.edge_impurity_history[i],
-                self.turbulence_history[i]
-            )?;
+    fn finish(self) {}
+}
+
+#[cfg(feature = "websocket")]
+fn start_web_dashboard(options: &Options, config: &Config, sim: &Simulation, t_max: f64) -> Option<WebDashboard> {
+    if !options.serve_dashboard {
+        return None;
+    }
+    let address = &config.serve.dashboard;
+    match WebDashboard::bind(address, sim, t_max, config.output.critical_density) {
+        Ok(web) => {
+            println!("🌐 Dashboard: http://{}/", web.address());
+            Some(web)
+        }
+        Err(e) => {
+            eprintln!("❌ Could not serve the dashboard on {}: {}", address, e);
+            std::process::exit(1);
         }
-        Ok(())
     }
 }
 
-fn main() {
-    println!("🌟 W7-X Adaptive Turbulence Control Simulator v3.0 (Cooldown Added)");
-    println!("{}", "=".repeat(60));
+/// `--serve-dashboard` is rejected without the feature.
+#[cfg(not(feature = "websocket"))]
+fn start_web_dashboard(_options: &Options, _config: &Config, _sim: &Simulation, _t_max: f64) -> Option<WebDashboard> {
+    None
+}
 
-    let mut state = StellaratorState::new(101);
+#[cfg(not(feature = "websocket"))]
+enum WebDashboard {}
 
-    let dt = 0.00002;
-    let t_max = 10.0;
-    let mut step = 0;
+#[cfg(not(feature = "websocket"))]
+impl WebDashboard {
+    fn note(&mut self, _event: &w7x_turbulence_control::events::TimedEvent) {
+        match *self {}
+    }
 
-    println!("Simulation parameters:");
-    println!("  dt = {:.6}s, dr = {:.4}, nr = {}", dt, state.dr, state.nr);
-    println!("  D_neo = {:.2}, D_turb = {:.2}, v_neo = {:.2}", 
-             state.d_neo, state.d_turb_base, state.v_neo);
-    println!("  Pulse: 200ms, Cooldown: {}ms", (state.cooldown_duration * 1000.0) as u32);
-    println!("{}", "=".repeat(60));
+    fn refresh(&mut self, _sim: &Simulation) {
+        match *self {}
+    }
 
-    while state.time < t_max {
-        state.update(dt);
+    fn finish(self, _sim: &Simulation) {
+        match self {}
+    }
+}
 
-        if step % 10000 == 0 {
-            println!(
-                "t={:.2}s | n_Z(0)={:.2e} | Mode={:?}",
-                state.time, state.impurity_density[0], state.confinement_mode
-            );
+#[cfg(feature = "hdf5")]
+fn save_hdf5(path: &str, state: &StellaratorState, snapshots: &ProfileSnapshots, metadata: &RunMetadata) {
+    match w7x_turbulence_control::hdf5_output::write_hdf5(path, &state.history, snapshots, metadata) {
+        Ok(()) => println!("💾 HDF5 ({} profile snapshots): {}", snapshots.len(), path),
+        Err(e) => eprintln!("❌ HDF5 save failed: {}", e),
+    }
+}
+
+#[cfg(not(feature = "hdf5"))]
+fn save_hdf5(path: &str, _state: &StellaratorState, _snapshots: &ProfileSnapshots, _metadata: &RunMetadata) {
+    eprintln!("❌ {} not written: rebuild with `--features hdf5`", path);
+}
+
+#[cfg(feature = "netcdf")]
+fn save_netcdf(path: &str, state: &StellaratorState, snapshots: &ProfileSnapshots, metadata: &RunMetadata) {
+    match w7x_turbulence_control::netcdf_output::write_netcdf(path, &state.history, snapshots, metadata) {
+        Ok(()) => println!("💾 NetCDF ({} profile snapshots): {}", snapshots.len(), path),
+        Err(e) => eprintln!("❌ NetCDF save failed: {}", e),
+    }
+}
+
+#[cfg(not(feature = "netcdf"))]
+fn save_netcdf(path: &str, _state: &StellaratorState, _snapshots: &ProfileSnapshots, _metadata: &RunMetadata) {
+    eprintln!("❌ {} not written: rebuild with `--features netcdf`", path);
+}
+
+#[cfg(feature = "parquet")]
+fn save_parquet(path: &str, snapshots: &ProfileSnapshots, metadata: &RunMetadata) {
+    match w7x_turbulence_control::parquet_output::write_profiles(path, snapshots, metadata) {
+        Ok(()) => println!("💾 Parquet profiles ({} snapshots): {}", snapshots.len(), path),
+        Err(e) => eprintln!("❌ Parquet save failed: {}", e),
+    }
+}
+
+#[cfg(not(feature = "parquet"))]
+fn save_parquet(path: &str, _snapshots: &ProfileSnapshots, _metadata: &RunMetadata) {
+    eprintln!("❌ {} not written: rebuild with `--features parquet`", path);
+}
+
+#[cfg(feature = "plot")]
+fn save_plot(path: &str, state: &StellaratorState, snapshots: &ProfileSnapshots, pulses: &[(f64, f64)], critical: f64) {
+    match w7x_turbulence_control::plot::write_plot(path, &state.history, snapshots, pulses, critical) {
+        Ok(()) => println!("💾 Plot: {}", path),
+        Err(e) => eprintln!("❌ Plot failed: {}", e),
+    }
+}
+
+#[cfg(not(feature = "plot"))]
+fn save_plot(path: &str, _state: &StellaratorState, _snapshots: &ProfileSnapshots, _pulses: &[(f64, f64)], _critical: f64) {
+    eprintln!("❌ {} not written: rebuild with `--features plot`", path);
+}
+
+#[cfg(feature = "plot")]
+fn save_animation(path: &str, snapshots: &ProfileSnapshots) {
+    match w7x_turbulence_control::plot::write_animation(path, snapshots) {
+        Ok(()) => println!("💾 Profile animation ({} snapshots): {}", snapshots.len(), path),
+        Err(e) => eprintln!("❌ Animation failed: {}", e),
+    }
+}
+
+#[cfg(not(feature = "plot"))]
+fn save_animation(path: &str, _snapshots: &ProfileSnapshots) {
+    eprintln!("❌ {} not written: rebuild with `--features plot`", path);
+}
+
+fn save_imas(path: &str, state: &StellaratorState, snapshots: &ProfileSnapshots, metadata: &RunMetadata) {
+    let ids = w7x_turbulence_control::imas::ids(&state.history, snapshots, state.impurity_charge, metadata);
+    if path.ends_with(".h5") || path.ends_with(".hdf5") {
+        return save_imas_hdf5(path, &ids, snapshots);
+    }
+    match w7x_turbulence_control::imas::write_json(path, &ids) {
+        Ok(()) => println!("💾 IMAS IDSs ({} profile snapshots): {}", snapshots.len(), path),
+        Err(e) => eprintln!("❌ IMAS save failed: {}", e),
+    }
+}
+
+#[cfg(feature = "hdf5")]
+fn save_imas_hdf5(path: &str, ids: &serde_json::Value, snapshots: &ProfileSnapshots) {
+    match w7x_turbulence_control::imas::write_hdf5(path, ids) {
+        Ok(()) => println!("💾 IMAS IDSs, HDF5 ({} profile snapshots): {}", snapshots.len(), path),
+        Err(e) => eprintln!("❌ IMAS save failed: {}", e),
+    }
+}
+
+#[cfg(not(feature = "hdf5"))]
+fn save_imas_hdf5(path: &str, _ids: &serde_json::Value, _snapshots: &ProfileSnapshots) {
+    eprintln!("❌ {} not written: rebuild with `--features hdf5`", path);
+}
+
+#[cfg(feature = "zmq")]
+type Server = w7x_turbulence_control::server::Server;
+
+#[cfg(not(feature = "zmq"))]
+enum Server {}
+
+#[cfg(feature = "zmq")]
+fn start_server(options: &Options, config: &Config) -> Option<Server> {
+    if options.mode != Mode::Serve {
+        return None;
+    }
+    match Server::bind(&config.serve, config.output.profile_cadence) {
+        Ok(server) => {
+            println!("📡 Serving: publish {}, commands {}", config.serve.publish, config.serve.command);
+            Some(server)
+        }
+        Err(e) => {
+            eprintln!("❌ Could not start server: {}", e);
+            std::process::exit(1);
         }
-        step += 1;
     }
+}
 
-    println!("{}", "=".repeat(60));
-    println!("📊 Final statistics:");
-    println!("  Center impurity: {:.2e} m⁻³", state.impurity_density[0]);
-    println!("  Edge impurity: {:.2e} m⁻³", state.impurity_density[state.nr-1]);
-    
-    if let Err(e) = state.save_to_csv("w7x_simulation.csv") {
-        eprintln!("❌ Save failed: {}", e);
-    } else {
-        println!("💾 Save complete: w7x_simulation.csv");
+#[cfg(not(feature = "zmq"))]
+fn start_server(options: &Options, _config: &Config) -> Option<Server> {
+    if options.mode == Mode::Serve {
+        eprintln!("❌ serve mode not available: rebuild with `--features zmq`");
+        std::process::exit(2);
+    }
+    None
+}
+
+#[cfg(feature = "zmq")]
+fn serve_step(server: &mut Server, sim: &mut Simulation) -> Option<ControlAction> {
+    server.step(sim).unwrap_or_else(|e| {
+        eprintln!("❌ Server error: {}", e);
+        std::process::exit(1);
+    })
+}
+
+#[cfg(not(feature = "zmq"))]
+fn serve_step(server: &mut Server, _sim: &mut Simulation) -> Option<ControlAction> {
+    match *server {}
+}
+
+#[cfg(feature = "onnx")]
+fn use_model_detector(sim: &mut Simulation, model: &ModelConfig, config: &Config) {
+    use w7x_turbulence_control::detection::{DetectionPipeline, Detector};
+    use w7x_turbulence_control::onnx_detector::OnnxDetector;
+
+    match OnnxDetector::load(model) {
+        Ok(detector) => {
+            println!("🧠 Learned detector: {} (window {}, p > {})",
+                     model.path, model.window, model.threshold);
+            let detector: Box<dyn Detector> = if model.vote {
+                println!("🗳️  Voting with the alarms: {:?}", config.detection.voting);
+                let mut pipeline = DetectionPipeline::new(&config.detection);
+                pipeline.add_detector("model", Box::new(detector), model.weight);
+                Box::new(pipeline)
+            } else {
+                Box::new(detector)
+            };
+            sim.controller = config.controller.build(detector, config.plasma.pulse_duration);
+        }
+        Err(e) => {
+            eprintln!("❌ Could not load detector model {}: {}", model.path, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(not(feature = "onnx"))]
+fn use_model_detector(_sim: &mut Simulation, model: &ModelConfig, _config: &Config) {
+    eprintln!("❌ {} not loaded: rebuild with `--features onnx`", model.path);
+    std::process::exit(2);
+}
+
+/// The `[controller.script]` or `[controller.plugin]` controller in place
+/// of the built-in one; batch modes get it from `Simulation::try_from_config`.
+fn use_custom_controller(sim: &mut Simulation, config: &Config) {
+    match config.controller.load(config.plasma.pulse_duration) {
+        Ok(Some(controller)) => {
+            if let Some(script) = &config.controller.script {
+                println!("📜 Control script: {}{}", script.path, if script.reload { " (hot reload)" } else { "" });
+            }
+            if let Some(plugin) = &config.controller.plugin {
+                println!("🔌 Plugin controller: {}", plugin.path);
+            }
+            sim.controller = controller;
+        }
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("❌ Could not load the controller: {}", e);
+            std::process::exit(if e.kind() == std::io::ErrorKind::Unsupported { 2 } else { 1 });
+        }
     }
 }
@@ -0,0 +1,129 @@
+//! # MDSplus-Style Signal Tree
+//!
+//! Files the traces and profile snapshots under the diagnostic codes the
+//! W7-X archive uses for the measured counterparts, so comparison scripts
+//! only swap their data source:
+//! ```text
+//! \QTB::TOP.PROFILES:NE         m-3     [time, rho]   Thomson scattering
+//! \QTB::TOP.PROFILES:TE         keV     [time, rho]
+//! \QSK::TOP.PROFILES:NZ         m-3     [time, rho]   XICS impurity density
+//! \QSK::TOP.TRACES:NZ_CORE      m-3     [time]
+//! \QSK::TOP.TRACES:NZ_EDGE      m-3     [time]
+//! \QMJ::TOP.TRACES:WDIA         J       [time]        diamagnetic energy
+//! \SIM::TOP.PROFILES:ZEFF       1       [time, rho]   model-only quantities
+//! \SIM::TOP.PROFILES:DILUTION   1       [time, rho]
+//! \SIM::TOP.PROFILES:D_TURB     m2 s-1  [time, rho]
+//! \SIM::TOP.PROFILES:D_NEO      m2 s-1  [time, rho]
+//! \SIM::TOP.PROFILES:PINCH      m s-1   [time, rho]
+//! \SIM::TOP.TRACES:ZEFF_CORE    1       [time]
+//! \SIM::TOP.TRACES:D_TURB_EDGE  m2 s-1  [time]
+//! \SIM::TOP.RUN:METADATA        TEXT                  RunMetadata JSON
+//! ```
+//! The JSON file nests one object per tree and structure node; a signal
+//! node holds `usage = "SIGNAL"`, `units`, `help`, `data`, and `dim_of`.
+//! As with `Connection.get(...).data()` in the MDSplus Python API, `data`
+//! is row-major `[time][rho]` and `dim_of[0]` is the fastest axis: `rho`
+//! (r / a) for profiles, time (s) for traces; `dim_of[1]` is the snapshot
+//! time of a profile.
+
+use crate::history::{Channel, History};
+use crate::metadata::RunMetadata;
+use crate::snapshots::ProfileSnapshots;
+use serde_json::{json, Map, Value};
+
+/// Where each recorded quantity lives in the tree.
+pub const TRACE_NODES: [(&str, Channel); 5] = [
+    ("\\QSK::TOP.TRACES:NZ_CORE", Channel::CenterImpurity),
+    ("\\QSK::TOP.TRACES:NZ_EDGE", Channel::EdgeImpurity),
+    ("\\QMJ::TOP.TRACES:WDIA", Channel::StoredEnergy),
+    ("\\SIM::TOP.TRACES:ZEFF_CORE", Channel::CenterZeff),
+    ("\\SIM::TOP.TRACES:D_TURB_EDGE", Channel::Turbulence),
+];
+
+/// Tree path for each `ProfileSnapshots::fields` name.
+pub fn profile_node(field: &str) -> Option<&'static str> {
+    Some(match field {
+        "electron_density" => "\\QTB::TOP.PROFILES:NE",
+        "electron_temp" => "\\QTB::TOP.PROFILES:TE",
+        "impurity_density" => "\\QSK::TOP.PROFILES:NZ",
+        "zeff" => "\\SIM::TOP.PROFILES:ZEFF",
+        "dilution" => "\\SIM::TOP.PROFILES:DILUTION",
+        "turbulence" => "\\SIM::TOP.PROFILES:D_TURB",
+        "d_neo" => "\\SIM::TOP.PROFILES:D_NEO",
+        "pinch" => "\\SIM::TOP.PROFILES:PINCH",
+        _ => return None,
+    })
+}
+
+/// Splits `\TREE::TOP.A.B:SIGNAL` into `["TREE", "A", "B", "SIGNAL"]`.
+fn path_segments(path: &str) -> Vec<&str> {
+    let (tree, rest) = path.trim_start_matches('\\').split_once("::").unwrap_or(("", path));
+    let rest = rest.strip_prefix("TOP").unwrap_or(rest);
+    std::iter::once(tree)
+        .chain(rest.split(['.', ':']).filter(|s| !s.is_empty()))
+        .collect()
+}
+
+fn insert(root: &mut Map<String, Value>, path: &str, node: Value) {
+    let segments = path_segments(path);
+    let (signal, structure) = segments.split_last().expect("tree path has a signal name");
+    let mut parent = root;
+    for name in structure {
+        parent = parent
+            .entry(name.to_string())
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .expect("structure node is an object");
+    }
+    parent.insert(signal.to_string(), node);
+}
+
+fn signal(units: &str, help: &str, data: Value, dims: Vec<Value>) -> Value {
+    json!({
+        "usage": "SIGNAL",
+        "units": units,
+        "help": help,
+        "data": data,
+        "dim_of": dims,
+    })
+}
+
+/// The whole tree: every trace in `history`, every snapshot field, and
+/// the run metadata.
+pub fn tree(history: &History, snapshots: &ProfileSnapshots, metadata: &RunMetadata) -> Value {
+    let mut root = Map::new();
+    insert(&mut root, "\\SIM::TOP.RUN:METADATA", json!({ "usage": "TEXT", "data": metadata.to_json() }));
+
+    let channels: Vec<Channel> = TRACE_NODES.iter().map(|&(_, channel)| channel).collect();
+    let range = history.export_range(f64::NEG_INFINITY, f64::INFINITY, &channels, 1);
+    for ((path, channel), values) in TRACE_NODES.iter().zip(&range.values) {
+        let (units, data): (&str, Vec<f64>) = match channel {
+            Channel::StoredEnergy => ("J", values.iter().map(|mj| mj * 1e6).collect()),
+            _ => (channel.units(), values.clone()),
+        };
+        insert(&mut root, path, signal(units, channel.long_name(), json!(data), vec![json!(range.time)]));
+    }
+
+    for field in snapshots.fields() {
+        let Some(path) = profile_node(field.name) else {
+            continue;
+        };
+        let dims = vec![json!(snapshots.radius), json!(snapshots.time)];
+        insert(&mut root, path, signal(field.units, field.long_name, json!(field.rows), dims));
+    }
+    Value::Object(root)
+}
+
+/// Follows a `\TREE::TOP.A:B` path through `tree`.
+pub fn get<'a>(tree: &'a Value, path: &str) -> Option<&'a Value> {
+    path_segments(path).into_iter().try_fold(tree, |node, name| node.get(name))
+}
+
+#[cfg(feature = "fs")]
+pub fn write_json<P: AsRef<std::path::Path>>(path: P, tree: &Value) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    serde_json::to_writer(&mut writer, tree).map_err(std::io::Error::other)?;
+    writeln!(writer)?;
+    writer.flush()
+}
@@ -0,0 +1,119 @@
+//! # Run Metadata
+//!
+//! Provenance stamped into every output file, so a result can be matched
+//! to the code and parameters that produced it: crate version, git commit
+//! of the build (`-dirty` with uncommitted changes), start time (UTC,
+//! RFC 3339), hostname, the seed of every random stream (`rng`), and the
+//! fully resolved config after presets and command-line overrides.
+//!
+//! How each format carries it:
+//! ```text
+//! CSV, sensitivity report             first line  # run_metadata: {json}
+//! binary trace                         u32 length + JSON after the header
+//! JSON (summary, crash dump)           "metadata" field
+//! event log (JSON lines)               first line {"metadata": …}
+//! operator log                         readable line, then # run_metadata: {json}
+//! HDF5, NetCDF                         root / global attribute run_metadata (JSON)
+//! Parquet                              key-value metadata run_metadata (JSON)
+//! IMAS                                 ids_properties.creation_date + code
+//! MDSplus tree                         \SIM::TOP.RUN:METADATA (TEXT)
+//! ```
+//! Checkpoints are not stamped: they restore a state, and already hold the
+//! run's parameters. PNG / GIF figures carry none either.
+//!
+//! pandas reads the CSVs with `comment="#"`, polars with `comment_prefix="#"`.
+
+use crate::config::Config;
+use crate::rng::{RngRegistry, Stream};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Prefix of the metadata line in CSV outputs.
+pub const CSV_PREFIX: &str = "# run_metadata: ";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunMetadata {
+    pub crate_version: String,
+    pub git_commit: String,  // "unknown" when built outside a git checkout
+    pub started: String,     // UTC, RFC 3339
+    pub hostname: String,
+    pub seed: u64,           // Master seed, or the [diagnostics] seed without one
+    #[serde(default)]
+    pub streams: BTreeMap<String, u64>,  // Seed of each random stream
+    pub config: serde_json::Value,
+}
+
+impl RunMetadata {
+    /// Stamps `config` with this build, this host, and the current time.
+    #[cfg(feature = "fs")]
+    pub fn collect(config: &Config) -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self::new(config, rfc3339(now), hostname())
+    }
+
+    pub fn new(config: &Config, started: String, hostname: String) -> Self {
+        let registry = RngRegistry::new(config);
+        RunMetadata {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: option_env!("W7X_GIT_COMMIT").unwrap_or("unknown").to_string(),
+            started,
+            hostname,
+            seed: config.seed.unwrap_or(config.diagnostics.seed),
+            streams: Stream::ALL.iter().map(|s| (s.name().to_string(), registry.seed(*s))).collect(),
+            config: serde_json::to_value(config).unwrap_or(serde_json::Value::Null),
+        }
+    }
+
+    /// Single-line JSON, for attributes and comment lines.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
+    }
+
+    /// Writes the `# run_metadata: …` line that heads every CSV output.
+    pub fn write_csv_header<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writeln!(writer, "{}{}", CSV_PREFIX, self.to_json())
+    }
+
+    /// Parses the metadata line of a CSV output.
+    pub fn from_csv_line(line: &str) -> Option<Self> {
+        serde_json::from_str(line.strip_prefix(CSV_PREFIX)?).ok()
+    }
+}
+
+/// `YYYY-MM-DDThh:mm:ssZ` for seconds since the Unix epoch.
+#[cfg(feature = "fs")]
+fn rfc3339(seconds: u64) -> String {
+    let (days, rem) = (seconds / 86_400, seconds % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, rem / 3600, rem / 60 % 60, rem % 60
+    )
+}
+
+#[cfg(feature = "fs")]
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
@@ -0,0 +1,199 @@
+//! # Neoclassical Transport Coefficients
+//!
+//! D_neo(r) and v_neo(r) on the transport grid. `plasma.d_neo` and
+//! `plasma.v_neo` stay the reference values (and the scan/ensemble
+//! parameters); the model here decides their radial shape:
+//!
+//! - `constant` (v2): the reference values everywhere.
+//! - `regimes`: stellarator collisionality regimes of the local ν*_e —
+//!   1/ν (D ∝ 1/ν*), plateau (constant), Pfirsch–Schlüter (D ∝ ν*) — with
+//!   the convection also scaled by a/L_n, so the inward pinch sits in the
+//!   density-gradient region rather than everywhere.
+//! - `table`: D_neo and v_neo from DKES/SFINCS output, absolute values
+//!   (the reference values are then unused).
+//!
+//! Temperature screening (`plasma.temperature_screening`) acts on top of
+//! all three, in `StellaratorState::pinch`. The impurity charge rescales
+//! the result through `ChargeScaling`; turbulent transport is taken as
+//! independent of Z.
+
+use crate::turbulence::LocalProfiles;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::io;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Neoclassical {
+    #[default]
+    Constant,
+    Regimes(RegimeModel),
+    Table(NeoclassicalTable),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegimeModel {
+    pub reference_collisionality: f64, // ν*_e where D_neo = d_neo in the 1/ν regime
+    pub plateau_low: f64,              // ν*_e, 1/ν regime below
+    pub plateau_high: f64,             // ν*_e, Pfirsch–Schlüter above
+    pub reference_gradient: f64,       // a/L_n where v_neo has its reference value
+    pub max_factor: f64,               // Clamp on D_neo / d_neo (and its inverse)
+}
+
+impl Default for RegimeModel {
+    fn default() -> Self {
+        RegimeModel {
+            reference_collisionality: 1e-3,
+            plateau_low: 0.01,
+            plateau_high: 1.0,
+            reference_gradient: 2.0,
+            max_factor: 10.0,
+        }
+    }
+}
+
+impl RegimeModel {
+    /// D_neo / d_neo at the local collisionality, continuous across regimes.
+    pub fn diffusion_factor(&self, collisionality: f64) -> f64 {
+        let nu = collisionality.max(1e-30);
+        let low = self.plateau_low.max(1e-30);
+        let factor = if nu < low {
+            self.reference_collisionality / nu
+        } else if nu <= self.plateau_high {
+            self.reference_collisionality / low
+        } else {
+            self.reference_collisionality / low * nu / self.plateau_high.max(low)
+        };
+        let max = self.max_factor.max(1.0);
+        factor.clamp(1.0 / max, max)
+    }
+
+    /// (D_neo / d_neo, v_neo(r) / v_neo).
+    fn factors(&self, local: &LocalProfiles) -> (f64, f64) {
+        let diffusion = self.diffusion_factor(local.collisionality());
+        let gradient = local.inverse_density_length() / self.reference_gradient.max(1e-12);
+        (diffusion, diffusion * gradient)
+    }
+}
+
+/// Z dependence of the neoclassical coefficients relative to the species
+/// they were given for: D ∝ Z^diffusion_exponent, v ∝ Z^convection_exponent
+/// (v ∝ Z in the trace-impurity limit, so heavier ions accumulate faster).
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChargeScaling {
+    pub reference_charge: f64, // Z at which d_neo / v_neo (or the table) apply
+    pub convection_exponent: f64,
+    pub diffusion_exponent: f64,
+}
+
+impl Default for ChargeScaling {
+    fn default() -> Self {
+        ChargeScaling {
+            reference_charge: 26.0,
+            convection_exponent: 1.0,
+            diffusion_exponent: 0.0,
+        }
+    }
+}
+
+impl ChargeScaling {
+    /// (D factor, v factor) for an impurity of charge `charge`.
+    pub fn factors(&self, charge: f64) -> (f64, f64) {
+        let ratio = charge / self.reference_charge.max(1e-12);
+        (ratio.powf(self.diffusion_exponent), ratio.powf(self.convection_exponent))
+    }
+}
+
+/// CSV with header `rho,d_neo,v_neo` (m²/s, m/s), ρ ascending; read by
+/// `Neoclassical::load`. Lines starting with `#` are skipped, so a table
+/// written by `fit` loads as is.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct NeoclassicalTable {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rho: Vec<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub d_neo: Vec<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub v_neo: Vec<f64>,
+}
+
+impl NeoclassicalTable {
+    /// Linear interpolation, constant beyond the ends of the table.
+    pub fn interpolate(&self, values: &[f64], rho: f64) -> f64 {
+        let n = self.rho.len();
+        if rho <= self.rho[0] {
+            return values[0];
+        }
+        if rho >= self.rho[n - 1] {
+            return values[n - 1];
+        }
+        let j = self.rho.partition_point(|&x| x <= rho);
+        let w = (rho - self.rho[j - 1]) / (self.rho[j] - self.rho[j - 1]);
+        values[j - 1] + w * (values[j] - values[j - 1])
+    }
+
+    #[cfg(feature = "fs")]
+    fn read(&mut self) -> io::Result<()> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let text = std::fs::read_to_string(Path::new(&self.path))?;
+        let (mut rho, mut d_neo, mut v_neo) = (Vec::new(), Vec::new(), Vec::new());
+        for (line_no, line) in text.lines().enumerate().filter(|(_, l)| !l.starts_with('#')).skip(1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<f64> = line
+                .split(',')
+                .map(|f| f.trim().parse::<f64>())
+                .collect::<Result<_, _>>()
+                .map_err(|e| invalid(format!("line {}: {}", line_no + 1, e)))?;
+            let [r, d, v] = fields[..] else {
+                return Err(invalid(format!("line {}: expected 3 columns", line_no + 1)));
+            };
+            rho.push(r);
+            d_neo.push(d);
+            v_neo.push(v);
+        }
+        if rho.is_empty() || !rho.windows(2).all(|w| w[1] > w[0]) {
+            return Err(invalid("needs at least one row, rho ascending".to_string()));
+        }
+        self.rho = rho;
+        self.d_neo = d_neo;
+        self.v_neo = v_neo;
+        Ok(())
+    }
+}
+
+impl Neoclassical {
+    /// Reads the DKES/SFINCS table, if one is configured.
+    #[cfg(feature = "fs")]
+    pub fn load(&mut self) -> io::Result<()> {
+        match self {
+            Neoclassical::Table(table) => table.read().map_err(|e| {
+                io::Error::new(e.kind(), format!("neoclassical table {}: {}", table.path, e))
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// D_neo(r) and v_neo(r) at an interior grid point from the reference
+    /// values `d_neo`, `v_neo`. An unloaded table falls back to them.
+    pub fn coefficients(&self, local: &LocalProfiles, d_neo: f64, v_neo: f64) -> (f64, f64) {
+        match self {
+            Neoclassical::Constant => (d_neo, v_neo),
+            Neoclassical::Regimes(model) => {
+                let (d, v) = model.factors(local);
+                (d_neo * d, v_neo * v)
+            }
+            Neoclassical::Table(table) if !table.rho.is_empty() => (
+                table.interpolate(&table.d_neo, local.radius).max(0.0),
+                table.interpolate(&table.v_neo, local.radius),
+            ),
+            Neoclassical::Table(_) => (d_neo, v_neo),
+        }
+    }
+}
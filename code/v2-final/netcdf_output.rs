@@ -0,0 +1,65 @@
+//! # NetCDF Output (feature `netcdf`)
+//!
+//! CF-style file that xarray opens directly:
+//! ```text
+//! dimensions: time, profile_time, radius
+//! time(time), <channel>(time)
+//! profile_time(profile_time), radius(radius)
+//! <field>(profile_time, radius)
+//! ```
+//! Every variable carries `units` and `long_name` attributes; the global
+//! `run_metadata` attribute holds the `RunMetadata` JSON.
+
+use crate::history::{Channel, History};
+use crate::metadata::RunMetadata;
+use crate::snapshots::ProfileSnapshots;
+use std::path::Path;
+
+fn add_variable(
+    file: &mut netcdf::FileMut,
+    name: &str,
+    dims: &[&str],
+    values: &[f64],
+    units: &str,
+    long_name: &str,
+) -> netcdf::Result<()> {
+    let mut var = file.add_variable::<f64>(name, dims)?;
+    var.put_values(values, ..)?;
+    var.put_attribute("units", units)?;
+    var.put_attribute("long_name", long_name)?;
+    Ok(())
+}
+
+pub fn write_netcdf<P: AsRef<Path>>(
+    path: P,
+    history: &History,
+    snapshots: &ProfileSnapshots,
+    metadata: &RunMetadata,
+) -> netcdf::Result<()> {
+    let mut file = netcdf::create(path)?;
+    file.add_attribute("Conventions", "CF-1.8")?;
+    file.add_attribute("title", "W7-X adaptive turbulence control simulation")?;
+    file.add_attribute("source", concat!("w7x-turbulence-control ", env!("CARGO_PKG_VERSION")))?;
+    file.add_attribute("run_metadata", metadata.to_json().as_str())?;
+
+    let range = history.export_range(f64::NEG_INFINITY, f64::INFINITY, &Channel::ALL, 1);
+    file.add_dimension("time", range.len())?;
+    file.add_dimension("profile_time", snapshots.len())?;
+    file.add_dimension("radius", snapshots.radius.len())?;
+
+    add_variable(&mut file, "time", &["time"], &range.time, "s", "time")?;
+    for (channel, values) in range.channels.iter().zip(&range.values) {
+        add_variable(&mut file, channel.name(), &["time"], values, channel.units(), channel.long_name())?;
+    }
+
+    add_variable(&mut file, "profile_time", &["profile_time"], &snapshots.time, "s",
+                 "time of profile snapshot")?;
+    add_variable(&mut file, "radius", &["radius"], &snapshots.radius, "1",
+                 "normalized minor radius r/a")?;
+    for field in snapshots.fields() {
+        let flat: Vec<f64> = field.rows.iter().flatten().copied().collect();
+        add_variable(&mut file, field.name, &["profile_time", "radius"], &flat,
+                     field.units, field.long_name)?;
+    }
+    Ok(())
+}
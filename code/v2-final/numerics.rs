@@ -0,0 +1,145 @@
+//! # Linear Solvers
+//!
+//! `Tridiagonal` systems solved by the Thomas algorithm (Gaussian
+//! elimination without pivoting, O(n)), the building block for implicit
+//! transport steps. It needs no pivoting for diagonally dominant
+//! matrices, which backward-Euler diffusion operators are; a zero pivot
+//! returns `None`.
+//!
+//! `DiffusionOperator` assembles the radial diffusion term in
+//! conservative (finite-volume) form on a flux-surface metric,
+//!
+//! ```text
+//! (L n)_i = [V'₊ D₊ (n_{i+1} − n_i) − V'₋ D₋ (n_i − n_{i−1})] / (V'_i dr²),   D± = (D_i + D_{i±1}) / 2
+//! ```
+//!
+//! with the grid ends closed by `BoundaryCondition::linear`
+//! (n_b = α n_nb + β), so any of the boundary types gives a tridiagonal
+//! row. Second order in dr for smooth D; a zero-gradient axis row adds a
+//! log factor (the half cell around r = 0 is not balanced).
+
+use crate::boundary::BoundaryCondition;
+use crate::geometry::Metric;
+use ndarray::Array1;
+
+/// Row i is lower[i] x_{i−1} + diagonal[i] x_i + upper[i] x_{i+1};
+/// lower[0] and upper[n−1] are unused.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tridiagonal {
+    pub lower: Array1<f64>,
+    pub diagonal: Array1<f64>,
+    pub upper: Array1<f64>,
+}
+
+impl Tridiagonal {
+    pub fn zeros(n: usize) -> Self {
+        Tridiagonal { lower: Array1::zeros(n), diagonal: Array1::zeros(n), upper: Array1::zeros(n) }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        Tridiagonal { diagonal: Array1::ones(n), ..Self::zeros(n) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.diagonal.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagonal.is_empty()
+    }
+
+    /// A x.
+    pub fn multiply(&self, x: &Array1<f64>) -> Array1<f64> {
+        let n = self.len();
+        (0..n)
+            .map(|i| {
+                let mut sum = self.diagonal[i] * x[i];
+                if i > 0 {
+                    sum += self.lower[i] * x[i - 1];
+                }
+                if i + 1 < n {
+                    sum += self.upper[i] * x[i + 1];
+                }
+                sum
+            })
+            .collect()
+    }
+
+    /// x with A x = rhs, or `None` at a (numerically) zero pivot.
+    pub fn solve(&self, rhs: &Array1<f64>) -> Option<Array1<f64>> {
+        let n = self.len();
+        if n == 0 {
+            return Some(Array1::zeros(0));
+        }
+        // Forward sweep: upper' and rhs' of the unit upper-bidiagonal system
+        let mut upper = Array1::zeros(n);
+        let mut x = Array1::zeros(n);
+        let mut pivot = self.diagonal[0];
+        for i in 0..n {
+            if i > 0 {
+                pivot = self.diagonal[i] - self.lower[i] * upper[i - 1];
+            }
+            if pivot.is_nan() || pivot.abs() < 1e-300 {
+                return None;
+            }
+            if i + 1 < n {
+                upper[i] = self.upper[i] / pivot;
+            }
+            let previous = if i > 0 { self.lower[i] * x[i - 1] } else { 0.0 };
+            x[i] = (rhs[i] - previous) / pivot;
+        }
+        // Back substitution
+        for i in (0..n - 1).rev() {
+            x[i] -= upper[i] * x[i + 1];
+        }
+        Some(x)
+    }
+}
+
+/// ∇·(D ∇n) on the radial grid with its boundary conditions.
+pub struct DiffusionOperator<'a> {
+    pub metric: &'a Metric,
+    pub dr: f64,
+    pub diffusivity: &'a Array1<f64>, // m²/s, D × ⟨|∇ρ|²⟩ at the grid points
+    pub core: BoundaryCondition,
+    pub edge: BoundaryCondition,
+}
+
+impl DiffusionOperator<'_> {
+    /// Backward-Euler matrix I − dt L on the interior rows; the boundary
+    /// rows are n_b − α n_nb (= β, see `rhs`).
+    pub fn implicit_matrix(&self, dt: f64) -> Tridiagonal {
+        let n = self.diffusivity.len();
+        let dr = self.dr;
+        let metric = self.metric;
+        let d = self.diffusivity;
+        let mut matrix = Tridiagonal::identity(n);
+        for i in 1..n - 1 {
+            let volume = metric.vprime[i].max(1e-12) * dr * dr;
+            let outer = metric.vprime_outer[i] * 0.5 * (d[i] + d[i + 1]) / volume;
+            let inner = metric.vprime_inner[i].max(0.0) * 0.5 * (d[i] + d[i - 1]) / volume;
+            matrix.lower[i] = -dt * inner;
+            matrix.diagonal[i] = 1.0 + dt * (inner + outer);
+            matrix.upper[i] = -dt * outer;
+        }
+        let (core_slope, _) = self.core.linear(-dr);
+        let (edge_slope, _) = self.edge.linear(dr);
+        matrix.upper[0] = -core_slope;
+        matrix.lower[n - 1] = -edge_slope;
+        matrix
+    }
+
+    /// Right-hand side n + dt S with the boundary offsets β.
+    pub fn rhs(&self, n: &Array1<f64>, source: &Array1<f64>, dt: f64) -> Array1<f64> {
+        let mut rhs = n + &(source * dt);
+        let last = rhs.len() - 1;
+        rhs[0] = self.core.linear(-self.dr).1;
+        rhs[last] = self.edge.linear(self.dr).1;
+        rhs
+    }
+
+    /// n after one backward-Euler step of ∂n/∂t = L n + S.
+    pub fn step(&self, n: &Array1<f64>, source: &Array1<f64>, dt: f64) -> Option<Array1<f64>> {
+        self.implicit_matrix(dt).solve(&self.rhs(n, source, dt))
+    }
+}
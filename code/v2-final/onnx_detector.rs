@@ -0,0 +1,108 @@
+//! # Learned Detector (ONNX)
+//!
+//! Runs an offline-trained classifier in place of the alarm pipeline.
+//! Inference goes through `tract`, so no ONNX runtime has to be installed.
+//! The input layout is described on [`ModelConfig`].
+
+use crate::detection::{Detector, ModelConfig};
+use crate::diagnostics::Measurement;
+use std::collections::VecDeque;
+use std::io;
+use tract_onnx::prelude::*;
+
+pub struct OnnxDetector {
+    model: TypedRunnableModel<TypedModel>,
+    config: ModelConfig,
+    window: VecDeque<Vec<f32>>,
+    probability: Option<f64>,
+    failure_reported: bool,
+}
+
+impl OnnxDetector {
+    pub fn load(config: &ModelConfig) -> io::Result<Self> {
+        if config.window == 0 || config.signals.len() != config.scale.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "model needs window > 0 and one scale per signal",
+            ));
+        }
+        let shape = [1, config.window, config.signals.len()];
+        let model = tract_onnx::onnx()
+            .model_for_path(&config.path)
+            .and_then(|m| m.with_input_fact(0, f32::fact(shape).into()))
+            .and_then(|m| m.into_optimized())
+            .and_then(|m| m.into_runnable())
+            .map_err(io::Error::other)?;
+        Ok(OnnxDetector {
+            model,
+            config: config.clone(),
+            window: VecDeque::with_capacity(config.window),
+            probability: None,
+            failure_reported: false,
+        })
+    }
+
+    /// Output of the latest inference; `None` until the window has filled.
+    pub fn probability(&self) -> Option<f64> {
+        self.probability
+    }
+
+    fn infer(&self) -> TractResult<f64> {
+        let data: Vec<f32> = self.window.iter().flatten().copied().collect();
+        let input = tract_ndarray::Array3::from_shape_vec(
+            (1, self.config.window, self.config.signals.len()),
+            data,
+        )?;
+        let outputs = self.model.run(tvec!(Tensor::from(input).into()))?;
+        let probability = outputs[0]
+            .to_array_view::<f32>()?
+            .iter()
+            .next()
+            .copied()
+            .ok_or_else(|| TractError::msg("model returned an empty output"))?;
+        Ok(probability as f64)
+    }
+}
+
+impl Detector for OnnxDetector {
+    fn update(&mut self, measurement: &Measurement) -> bool {
+        let row = self
+            .config
+            .signals
+            .iter()
+            .zip(&self.config.scale)
+            .map(|(signal, scale)| (signal.read(measurement) / scale) as f32)
+            .collect();
+        if self.window.len() == self.config.window {
+            self.window.pop_front();
+        }
+        self.window.push_back(row);
+        if self.window.len() < self.config.window {
+            return false;
+        }
+
+        // A failed inference must not silently disable protection
+        let probability = match self.infer() {
+            Ok(p) => p,
+            Err(e) => {
+                if !self.failure_reported {
+                    self.failure_reported = true;
+                    tracing::error!("❌ Detector inference failed at t={:.4}s, requesting pulses: {}",
+                                    measurement.time, e);
+                }
+                1.0
+            }
+        };
+        self.probability = Some(probability);
+        self.probability.is_some_and(|p| p > self.config.threshold)
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value((&self.window, self.probability, self.failure_reported)).ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> serde_json::Result<()> {
+        (self.window, self.probability, self.failure_reported) = serde_json::from_value(state)?;
+        Ok(())
+    }
+}
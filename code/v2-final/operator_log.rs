@@ -0,0 +1,144 @@
+//! # Operator Log
+//!
+//! Plain-language, chronological transcript of a run (mode changes,
+//! alarms, how well each pulse worked), written for session leaders
+//! rather than for post-processing scripts.
+
+use crate::controller::ControlAction;
+#[cfg(feature = "fs")]
+use crate::metadata::RunMetadata;
+use crate::state::{ConfinementMode, StellaratorState};
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::{BufWriter, Write};
+
+pub struct OperatorLog {
+    pub alarm_level: f64, // m⁻³, central impurity considered dangerous
+    entries: Vec<String>,
+    last_mode: ConfinementMode,
+    pulse_count: usize,
+    pulse_start: Option<(f64, f64)>, // (time, n_Z(0) at start)
+    alarm_active: bool,
+    inhibit_reported: bool,
+}
+
+impl OperatorLog {
+    pub fn new(alarm_level: f64) -> Self {
+        OperatorLog {
+            alarm_level,
+            entries: Vec::new(),
+            last_mode: ConfinementMode::Normal,
+            pulse_count: 0,
+            pulse_start: None,
+            alarm_active: false,
+            inhibit_reported: false,
+        }
+    }
+
+    /// Free-form entry (run start, configuration notes, ...).
+    pub fn note(&mut self, time: f64, text: &str) {
+        self.entries.push(format!("[{:8.3} s] {}", time, text));
+    }
+
+    /// Call once per step after the state has been updated.
+    /// `action` is the controller decision of this step, if one was made.
+    pub fn observe(&mut self, state: &StellaratorState, action: Option<ControlAction>) {
+        let t = state.time;
+        let center = state.impurity_density[0];
+
+        if state.confinement_mode != self.last_mode {
+            match state.confinement_mode {
+                ConfinementMode::TurbulencePulse => {
+                    self.pulse_count += 1;
+                    self.pulse_start = Some((t, center));
+                    self.inhibit_reported = false;
+                    self.note(t, &format!(
+                        "Pulse #{} started: accumulation detected, central impurity {:.2e} m⁻³.",
+                        self.pulse_count, center
+                    ));
+                }
+                ConfinementMode::RadiativeCollapse => {
+                    self.pulse_start = None;
+                    self.note(t, &format!(
+                        "RADIATIVE COLLAPSE: central impurity {:.2e} m⁻³, core T_e {:.2} keV and falling.",
+                        center, state.electron_temp[0]
+                    ));
+                }
+                ConfinementMode::EmergencyFlush => {
+                    self.note(t, &format!(
+                        "Emergency flush by machine protection at {:.0}× D_turb.",
+                        state.termination.flush_amplitude
+                    ));
+                }
+                ConfinementMode::Terminated => {
+                    self.note(t, &format!(
+                        "DISCHARGE TERMINATED: core T_e {:.2} keV, central impurity {:.2e} m⁻³.",
+                        state.electron_temp[0], center
+                    ));
+                }
+                ConfinementMode::Normal if self.last_mode == ConfinementMode::EmergencyFlush => {
+                    self.note(t, &format!("Recovered from the emergency flush, central impurity {:.2e} m⁻³.", center));
+                }
+                ConfinementMode::Normal => {
+                    if let Some((t0, n0)) = self.pulse_start.take() {
+                        let change = (center - n0) / n0.max(1.0) * 100.0;
+                        let verdict = if change < -10.0 {
+                            "effective"
+                        } else if change < 0.0 {
+                            "marginal"
+                        } else {
+                            "ineffective, impurity kept rising"
+                        };
+                        self.note(t, &format!(
+                            "Pulse #{} ended after {:.0} ms: central impurity {:.2e} → {:.2e} m⁻³ ({:+.0}%), {}. Cooldown {:.0} ms.",
+                            self.pulse_count, (t - t0) * 1000.0, n0, center, change, verdict,
+                            state.cooldown_duration * 1000.0
+                        ));
+                    }
+                }
+            }
+            self.last_mode = state.confinement_mode;
+        }
+
+        if action.is_some_and(|a| a.requests_pulse())
+            && state.confinement_mode == ConfinementMode::Normal
+            && !self.inhibit_reported
+        {
+            self.inhibit_reported = true;
+            self.note(t, "Accumulation still detected but pulse inhibited by cooldown.");
+        }
+
+        if !self.alarm_active && center > self.alarm_level {
+            self.alarm_active = true;
+            self.note(t, &format!(
+                "ALARM: central impurity {:.2e} m⁻³ exceeds {:.1e} m⁻³ (radiation risk).",
+                center, self.alarm_level
+            ));
+        } else if self.alarm_active && center < 0.9 * self.alarm_level {
+            self.alarm_active = false;
+            self.note(t, &format!("Alarm cleared: central impurity back to {:.2e} m⁻³.", center));
+        }
+    }
+
+    pub fn pulse_count(&self) -> usize {
+        self.pulse_count
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn write(&self, filename: &str, metadata: &RunMetadata) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(filename)?);
+        writeln!(writer, "W7-X Adaptive Turbulence Control — Operator Log")?;
+        writeln!(
+            writer,
+            "Run started {} on {}, version {} ({}), seed {}.",
+            metadata.started, metadata.hostname, metadata.crate_version, metadata.git_commit, metadata.seed
+        )?;
+        metadata.write_csv_header(&mut writer)?;
+        writeln!(writer, "{}", "=".repeat(60))?;
+        for entry in &self.entries {
+            writeln!(writer, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,331 @@
+//! # Controller Tuning (Bayesian Optimization)
+//!
+//! Minimizes a run cost over the `[optimize]` parameter ranges with a
+//! Gaussian-process surrogate and expected-improvement acquisition:
+//!
+//! 1. `initial_samples` random points (run in parallel),
+//! 2. then per iteration: fit a GP (squared-exponential kernel on the
+//!    unit cube) to all costs so far, pick the best of `candidates`
+//!    random points by expected improvement, run it.
+//!
+//! Cost of one run:
+//! ```text
+//! cost = w_Z · ⟨n_Z(0)⟩_t / 1e18  +  w_duty · duty_cycle  +  w_pulse · pulses
+//! ```
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::rng::{RngRegistry, Stream, StreamRng};
+use crate::scan::{run_quiet, Parameter, ParameterRange};
+use crate::summary::RunSummary;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CostConfig {
+    pub impurity_weight: f64, // per 1e18 m⁻³ of time-averaged n_Z(0)
+    pub duty_weight: f64,     // per unit duty cycle
+    pub pulse_weight: f64,    // per pulse
+    pub energy_weight: f64,   // per MJ of ECRH energy
+    pub confinement_weight: f64, // per unit confinement loss (1 − ⟨W⟩/W_ref)
+}
+
+impl Default for CostConfig {
+    fn default() -> Self {
+        CostConfig {
+            impurity_weight: 1.0,
+            duty_weight: 10.0,
+            pulse_weight: 0.0,
+            energy_weight: 0.0,
+            confinement_weight: 0.0,
+        }
+    }
+}
+
+impl CostConfig {
+    pub fn cost(&self, summary: &RunSummary) -> f64 {
+        self.impurity_weight * summary.mean_center_impurity / 1e18
+            + self.duty_weight * summary.duty_cycle
+            + self.pulse_weight * summary.pulses as f64
+            + self.energy_weight * summary.ecrh_energy
+            + self.confinement_weight * summary.confinement_loss
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OptimizeConfig {
+    pub parameters: Vec<ParameterRange>,
+    pub cost: CostConfig,
+    pub initial_samples: usize,
+    pub iterations: usize,
+    pub candidates: usize,    // Random points scored per acquisition
+    pub length_scale: f64,    // GP kernel, in units of each parameter's range
+    pub seed: u64,
+    pub output: String,       // Every evaluation (CSV)
+}
+
+impl Default for OptimizeConfig {
+    fn default() -> Self {
+        let range = |parameter, low, high| ParameterRange { parameter, low, high };
+        OptimizeConfig {
+            parameters: vec![
+                range(Parameter::Threshold, 5e17, 1.5e18),
+                range(Parameter::PulseDuration, 0.05, 0.4),
+                range(Parameter::Cooldown, 0.2, 1.0),
+                range(Parameter::PulseAmplitude, 2.0, 10.0),
+            ],
+            cost: CostConfig::default(),
+            initial_samples: 8,
+            iterations: 24,
+            candidates: 2000,
+            length_scale: 0.2,
+            seed: 3,
+            output: "optimize_history.csv".to_string(),
+        }
+    }
+}
+
+/// One evaluated point, parameters in physical units.
+#[derive(Clone, Debug)]
+pub struct Evaluation {
+    pub values: Vec<f64>,
+    pub summary: RunSummary,
+    pub cost: f64,
+}
+
+pub struct Optimizer<'a> {
+    base: &'a Config,
+    settings: &'a OptimizeConfig,
+    rng: StreamRng,
+    unit: Vec<Vec<f64>>, // Evaluated points on the unit cube
+    pub evaluations: Vec<Evaluation>,
+}
+
+impl<'a> Optimizer<'a> {
+    pub fn new(base: &'a Config) -> Self {
+        Optimizer {
+            base,
+            settings: &base.optimize,
+            rng: RngRegistry::new(base).rng(Stream::Optimize),
+            unit: Vec::new(),
+            evaluations: Vec::new(),
+        }
+    }
+
+    /// Runs the random initial design.
+    pub fn initialize(&mut self) -> Result<&[Evaluation]> {
+        let points: Vec<Vec<f64>> = (0..self.settings.initial_samples)
+            .map(|_| self.random_point())
+            .collect();
+        #[cfg(feature = "parallel")]
+        let evaluations: Vec<Evaluation> = points.par_iter().map(|u| self.evaluate(u)).collect::<Result<_>>()?;
+        #[cfg(not(feature = "parallel"))]
+        let evaluations: Vec<Evaluation> = points.iter().map(|u| self.evaluate(u)).collect::<Result<_>>()?;
+        let start = self.evaluations.len();
+        self.unit.extend(points);
+        self.evaluations.extend(evaluations);
+        Ok(&self.evaluations[start..])
+    }
+
+    /// One acquisition + evaluation.
+    pub fn iterate(&mut self) -> Result<&Evaluation> {
+        let candidates: Vec<Vec<f64>> =
+            (0..self.settings.candidates.max(1)).map(|_| self.random_point()).collect();
+        let next = match GaussianProcess::fit(&self.unit, &self.costs(), self.settings.length_scale) {
+            Some(gp) => {
+                let best = self.best().map_or(f64::INFINITY, |e| e.cost);
+                candidates
+                    .into_iter()
+                    .map(|u| (gp.expected_improvement(&u, best), u))
+                    .max_by(|a, b| a.0.total_cmp(&b.0))
+                    .map(|(_, u)| u)
+                    .expect("at least one candidate")
+            }
+            // Singular or empty history: fall back to random search
+            None => candidates.into_iter().next().expect("at least one candidate"),
+        };
+        let evaluation = self.evaluate(&next)?;
+        self.unit.push(next);
+        self.evaluations.push(evaluation);
+        Ok(self.evaluations.last().expect("just pushed"))
+    }
+
+    pub fn best(&self) -> Option<&Evaluation> {
+        self.evaluations.iter().min_by(|a, b| a.cost.total_cmp(&b.cost))
+    }
+
+    fn costs(&self) -> Vec<f64> {
+        self.evaluations.iter().map(|e| e.cost).collect()
+    }
+
+    fn random_point(&mut self) -> Vec<f64> {
+        (0..self.settings.parameters.len()).map(|_| self.rng.gen::<f64>()).collect()
+    }
+
+    fn evaluate(&self, unit: &[f64]) -> Result<Evaluation> {
+        let values: Vec<f64> = self
+            .settings
+            .parameters
+            .iter()
+            .zip(unit)
+            .map(|(range, u)| range.low + u * (range.high - range.low))
+            .collect();
+        let mut config = self.base.clone();
+        for (range, &value) in self.settings.parameters.iter().zip(&values) {
+            range.parameter.apply(&mut config, value);
+        }
+        let summary = run_quiet(&config, |_| {})?;
+        let cost = self.settings.cost.cost(&summary);
+        Ok(Evaluation { values, summary, cost })
+    }
+}
+
+/// GP regression with a squared-exponential kernel on standardized costs.
+struct GaussianProcess<'a> {
+    points: &'a [Vec<f64>],
+    length_scale: f64,
+    chol: Vec<Vec<f64>>, // Lower Cholesky factor of K + σ²I
+    alpha: Vec<f64>,     // (K + σ²I)⁻¹ y
+    mean: f64,
+    scale: f64,
+}
+
+impl<'a> GaussianProcess<'a> {
+    const NOISE: f64 = 1e-6; // Relative nugget for numerical stability
+
+    fn fit(points: &'a [Vec<f64>], costs: &[f64], length_scale: f64) -> Option<Self> {
+        let n = points.len();
+        if n == 0 {
+            return None;
+        }
+        let mean = costs.iter().sum::<f64>() / n as f64;
+        let var = costs.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / n as f64;
+        let scale = if var > 0.0 { var.sqrt() } else { 1.0 };
+        let y: Vec<f64> = costs.iter().map(|c| (c - mean) / scale).collect();
+
+        let mut k = vec![vec![0.0; n]; n];
+        for i in 0..n {
+            for j in 0..=i {
+                let value = kernel(&points[i], &points[j], length_scale);
+                k[i][j] = value;
+                k[j][i] = value;
+            }
+            k[i][i] += Self::NOISE;
+        }
+        let chol = cholesky(&k)?;
+        let alpha = cholesky_solve(&chol, &y);
+        Some(GaussianProcess { points, length_scale, chol, alpha, mean, scale })
+    }
+
+    /// Posterior mean and standard deviation, in cost units.
+    fn predict(&self, x: &[f64]) -> (f64, f64) {
+        let k_star: Vec<f64> = self.points.iter().map(|p| kernel(p, x, self.length_scale)).collect();
+        let mu: f64 = k_star.iter().zip(&self.alpha).map(|(k, a)| k * a).sum();
+        let v = forward_substitute(&self.chol, &k_star);
+        let var = (1.0 + Self::NOISE - v.iter().map(|x| x * x).sum::<f64>()).max(0.0);
+        (self.mean + self.scale * mu, self.scale * var.sqrt())
+    }
+
+    /// Expected reduction below `best` (minimization).
+    fn expected_improvement(&self, x: &[f64], best: f64) -> f64 {
+        let (mu, sigma) = self.predict(x);
+        if sigma <= 0.0 {
+            return (best - mu).max(0.0);
+        }
+        let z = (best - mu) / sigma;
+        (best - mu) * normal_cdf(z) + sigma * normal_pdf(z)
+    }
+}
+
+fn kernel(a: &[f64], b: &[f64], length_scale: f64) -> f64 {
+    let d2: f64 = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum();
+    (-0.5 * d2 / (length_scale * length_scale)).exp()
+}
+
+fn cholesky(a: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    let mut l = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| l[i][k] * l[j][k]).sum();
+            if i == j {
+                let d = a[i][i] - sum;
+                if d <= 0.0 {
+                    return None;
+                }
+                l[i][j] = d.sqrt();
+            } else {
+                l[i][j] = (a[i][j] - sum) / l[j][j];
+            }
+        }
+    }
+    Some(l)
+}
+
+/// Solves L y = b.
+fn forward_substitute(l: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let mut y = vec![0.0; b.len()];
+    for i in 0..b.len() {
+        let sum: f64 = (0..i).map(|k| l[i][k] * y[k]).sum();
+        y[i] = (b[i] - sum) / l[i][i];
+    }
+    y
+}
+
+/// Solves L Lᵀ x = b.
+fn cholesky_solve(l: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let y = forward_substitute(l, b);
+    let n = y.len();
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let sum: f64 = (i + 1..n).map(|k| l[k][i] * x[k]).sum();
+        x[i] = (y[i] - sum) / l[i][i];
+    }
+    x
+}
+
+fn normal_pdf(z: f64) -> f64 {
+    (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / std::f64::consts::SQRT_2))
+}
+
+/// Abramowitz & Stegun 7.1.26, |error| < 1.5e-7.
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t * (0.254_829_592
+        + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    if x >= 0.0 { y } else { -y }
+}
+
+#[cfg(feature = "fs")]
+pub fn write_history<P: AsRef<std::path::Path>>(
+    path: P,
+    parameters: &[ParameterRange],
+    evaluations: &[Evaluation],
+    metadata: &crate::metadata::RunMetadata,
+) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    metadata.write_csv_header(&mut writer)?;
+    let names: Vec<&str> = parameters.iter().map(|p| p.parameter.name()).collect();
+    writeln!(writer, "evaluation,{},mean_center_impurity,duty_cycle,pulses,cost", names.join(","))?;
+    for (i, e) in evaluations.iter().enumerate() {
+        let values: Vec<String> = e.values.iter().map(|v| format!("{:.6e}", v)).collect();
+        writeln!(
+            writer,
+            "{},{},{:.6e},{:.4},{},{:.6}",
+            i, values.join(","), e.summary.mean_center_impurity, e.summary.duty_cycle,
+            e.summary.pulses, e.cost
+        )?;
+    }
+    writer.flush()
+}
@@ -0,0 +1,154 @@
+//! # Streaming Output
+//!
+//! `OutputSink`s receive every recorded sample while the run is in
+//! progress, so nothing has to wait for (or fit into) the in-memory history.
+//!
+//! CSV traces start with the `# run_metadata: …` line (see `metadata`).
+//!
+//! Binary trace layout (little-endian):
+//! ```text
+//! b"W7XT" | u32 version = 2 | u32 n_columns = 7 | u32 n_meta | n_meta bytes of RunMetadata JSON
+//! then per sample: f64 time, center_impurity, edge_impurity, turbulence,
+//!                  center_zeff, center_dilution, stored_energy
+//! ```
+//! Load with `n_meta = numpy.fromfile(path, dtype="<u4", count=4)[3]` and
+//! `numpy.fromfile(path, dtype="<f8", offset=16 + n_meta).reshape(-1, 7)`.
+//!
+//! `Parquet` traces (feature `parquet`) are written by
+//! `parquet_output::ParquetSink`.
+
+#[cfg(feature = "fs")]
+use crate::history::Channel;
+use crate::history::Sample;
+#[cfg(feature = "fs")]
+use crate::metadata::RunMetadata;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::fs::{File, OpenOptions};
+#[cfg(feature = "fs")]
+use std::io::{BufWriter, Write};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+pub trait OutputSink {
+    fn write_sample(&mut self, sample: &Sample) -> std::io::Result<()>;
+    /// Flushes buffered data; call once at the end of the run.
+    fn finish(&mut self) -> std::io::Result<()>;
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceFormat {
+    #[default]
+    Csv,
+    Binary,
+    Parquet, // Requires the `parquet` feature
+}
+
+/// Opens `path` for streaming, appending when resuming a run so the
+/// earlier part of the trace is kept.
+#[cfg(feature = "fs")]
+fn open(path: &Path, append: bool) -> std::io::Result<(File, bool)> {
+    if append {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let is_new = file.metadata()?.len() == 0;
+        Ok((file, is_new))
+    } else {
+        Ok((File::create(path)?, true))
+    }
+}
+
+#[cfg(feature = "fs")]
+pub struct CsvSink {
+    writer: BufWriter<File>,
+}
+
+#[cfg(feature = "fs")]
+impl CsvSink {
+    pub fn create<P: AsRef<Path>>(path: P, append: bool, metadata: &RunMetadata) -> std::io::Result<Self> {
+        let (file, is_new) = open(path.as_ref(), append)?;
+        let mut writer = BufWriter::new(file);
+        if is_new {
+            metadata.write_csv_header(&mut writer)?;
+            let names: Vec<&str> = Channel::ALL.iter().map(|c| c.name()).collect();
+            writeln!(writer, "time,{}", names.join(","))?;
+        }
+        Ok(CsvSink { writer })
+    }
+}
+
+#[cfg(feature = "fs")]
+impl OutputSink for CsvSink {
+    fn write_sample(&mut self, s: &Sample) -> std::io::Result<()> {
+        writeln!(
+            self.writer,
+            "{:.6},{:.6e},{:.6e},{:.4},{:.4},{:.4},{:.4}",
+            s.time, s.center_impurity, s.edge_impurity, s.turbulence, s.center_zeff, s.center_dilution,
+            s.stored_energy
+        )
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(feature = "fs")]
+pub struct BinarySink {
+    writer: BufWriter<File>,
+}
+
+#[cfg(feature = "fs")]
+impl BinarySink {
+    pub fn create<P: AsRef<Path>>(path: P, append: bool, metadata: &RunMetadata) -> std::io::Result<Self> {
+        let (file, is_new) = open(path.as_ref(), append)?;
+        let mut writer = BufWriter::new(file);
+        if is_new {
+            let json = metadata.to_json();
+            writer.write_all(b"W7XT")?;
+            writer.write_all(&2u32.to_le_bytes())?;
+            writer.write_all(&(1 + Channel::ALL.len() as u32).to_le_bytes())?;
+            writer.write_all(&(json.len() as u32).to_le_bytes())?;
+            writer.write_all(json.as_bytes())?;
+        }
+        Ok(BinarySink { writer })
+    }
+}
+
+#[cfg(feature = "fs")]
+impl OutputSink for BinarySink {
+    fn write_sample(&mut self, s: &Sample) -> std::io::Result<()> {
+        self.writer.write_all(&s.time.to_le_bytes())?;
+        for channel in Channel::ALL {
+            self.writer.write_all(&s.get(channel).to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Builds the sink for the configured trace format.
+#[cfg(feature = "fs")]
+pub fn create_sink<P: AsRef<Path>>(
+    format: TraceFormat,
+    path: P,
+    append: bool,
+    metadata: &RunMetadata,
+) -> std::io::Result<Box<dyn OutputSink>> {
+    Ok(match format {
+        TraceFormat::Csv => Box::new(CsvSink::create(path, append, metadata)?),
+        TraceFormat::Binary => Box::new(BinarySink::create(path, append, metadata)?),
+        #[cfg(feature = "parquet")]
+        TraceFormat::Parquet => Box::new(crate::parquet_output::ParquetSink::create(path, append, metadata)?),
+        #[cfg(not(feature = "parquet"))]
+        TraceFormat::Parquet => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Parquet traces need a build with `--features parquet`",
+            ))
+        }
+    })
+}
@@ -0,0 +1,64 @@
+//! # Real-Time Pacing
+//!
+//! `[serve] speed` (or `--speed`) ties simulated time to wall-clock time,
+//! so control software polling the `serve` stream, or someone watching a
+//! dashboard, sees the plasma evolve at a realistic rate:
+//!
+//! ```text
+//! t_sim − t_sim(start) ≤ speed · (t_wall − t_wall(start))
+//! ```
+//!
+//! 1 is real time, 0.1 ten times slower, 0 (default) as fast as the solver
+//! goes. The run sleeps whenever it is more than `SLACK` ahead. A run that
+//! falls more than `MAX_LAG` of wall time behind (solver too slow, paused)
+//! restarts the clock at the current step instead of racing to catch up;
+//! the first time that happens without a pause it is logged as a warning.
+
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Lead over the wall clock below which the run does not sleep.
+pub const SLACK: Duration = Duration::from_millis(1);
+/// Lag after which the clock restarts.
+pub const MAX_LAG: Duration = Duration::from_millis(200);
+
+pub struct Pacer {
+    speed: f64, // Simulated s per wall-clock s
+    start: Instant,
+    start_time: f64, // s, simulated time at `start`
+    warned: bool,
+}
+
+impl Pacer {
+    /// Paces from simulated time `time`, now; `speed` must be positive.
+    pub fn new(speed: f64, time: f64) -> Self {
+        Pacer { speed, start: Instant::now(), start_time: time, warned: false }
+    }
+
+    /// Restarts the clock at `time`, e.g. after the run was held.
+    pub fn restart(&mut self, time: f64) {
+        self.start = Instant::now();
+        self.start_time = time;
+    }
+
+    /// Sleeps until the wall clock has caught up with simulated `time`.
+    pub fn wait(&mut self, time: f64) {
+        let due = Duration::from_secs_f64(((time - self.start_time) / self.speed).max(0.0));
+        let elapsed = self.start.elapsed();
+        if due > elapsed + SLACK {
+            std::thread::sleep(due - elapsed);
+        } else if elapsed > due + MAX_LAG {
+            let lag = (elapsed - due).as_secs_f64();
+            if self.warned {
+                debug!("⏱️ t={:.3}s: {:.2}s behind the wall clock at speed {}", time, lag, self.speed);
+            } else {
+                warn!(
+                    "⏱️ t={:.3}s: {:.2}s behind the wall clock at speed {}; the solver cannot keep up, pacing restarts",
+                    time, lag, self.speed
+                );
+                self.warned = true;
+            }
+            self.restart(time);
+        }
+    }
+}
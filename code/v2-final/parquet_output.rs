@@ -0,0 +1,141 @@
+//! # Parquet Output (feature `parquet`)
+//!
+//! Columnar files for polars / pyarrow, each column carrying `units` and
+//! `long_name` in its Arrow field metadata and the file carrying
+//! `run_metadata` (JSON) in its key-value metadata:
+//! ```text
+//! traces    time, <channel>...                    one row per recorded sample
+//! profiles  time, radius, <field>...              one row per (snapshot, radius)
+//! ```
+//! The trace sink writes a row group every `ROW_GROUP` samples, so memory
+//! stays bounded on long runs. Parquet files cannot be appended to:
+//! resuming a run with `trace_format = "parquet"` is refused.
+//!
+//! `pl.scan_parquet("w7x_simulation.parquet")` reads traces lazily; profiles
+//! are long-format, so `df.pivot(on="radius", index="time", values="zeff")`
+//! gives the `[time, radius]` matrix.
+
+use crate::history::{Channel, Sample};
+use crate::metadata::RunMetadata;
+use crate::output::OutputSink;
+use crate::snapshots::ProfileSnapshots;
+use arrow::array::{ArrayRef, Float64Array};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Samples per trace row group.
+const ROW_GROUP: usize = 65_536;
+
+fn field(name: &str, units: &str, long_name: &str) -> Field {
+    Field::new(name, DataType::Float64, false).with_metadata(HashMap::from([
+        ("units".to_string(), units.to_string()),
+        ("long_name".to_string(), long_name.to_string()),
+    ]))
+}
+
+fn writer(path: &Path, schema: SchemaRef, metadata: &RunMetadata) -> std::io::Result<ArrowWriter<File>> {
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_created_by(concat!("w7x-turbulence-control ", env!("CARGO_PKG_VERSION")).to_string())
+        .set_key_value_metadata(Some(vec![KeyValue::new("run_metadata".to_string(), metadata.to_json())]))
+        .build();
+    ArrowWriter::try_new(File::create(path)?, schema, Some(properties)).map_err(std::io::Error::other)
+}
+
+fn batch(schema: &SchemaRef, columns: Vec<Vec<f64>>) -> std::io::Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = columns.into_iter().map(|c| Arc::new(Float64Array::from(c)) as ArrayRef).collect();
+    RecordBatch::try_new(schema.clone(), columns).map_err(std::io::Error::other)
+}
+
+pub struct ParquetSink {
+    schema: SchemaRef,
+    writer: Option<ArrowWriter<File>>,
+    columns: Vec<Vec<f64>>, // time, then one per `Channel::ALL`
+}
+
+impl ParquetSink {
+    pub fn create<P: AsRef<Path>>(path: P, append: bool, metadata: &RunMetadata) -> std::io::Result<Self> {
+        if append {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Parquet traces cannot be appended to; resume with trace_format = \"csv\" or \"binary\"",
+            ));
+        }
+        let fields: Vec<Field> = std::iter::once(field("time", "s", "time"))
+            .chain(Channel::ALL.iter().map(|c| field(c.name(), c.units(), c.long_name())))
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+        Ok(ParquetSink {
+            writer: Some(writer(path.as_ref(), schema.clone(), metadata)?),
+            columns: vec![Vec::new(); 1 + Channel::ALL.len()],
+            schema,
+        })
+    }
+
+    fn flush_row_group(&mut self) -> std::io::Result<()> {
+        if self.columns[0].is_empty() {
+            return Ok(());
+        }
+        let columns = self.columns.iter_mut().map(std::mem::take).collect();
+        let batch = batch(&self.schema, columns)?;
+        let writer = self.writer.as_mut().ok_or_else(|| std::io::Error::other("Parquet trace already finished"))?;
+        writer.write(&batch).map_err(std::io::Error::other)?;
+        writer.flush().map_err(std::io::Error::other)
+    }
+}
+
+impl OutputSink for ParquetSink {
+    fn write_sample(&mut self, s: &Sample) -> std::io::Result<()> {
+        self.columns[0].push(s.time);
+        for (column, channel) in self.columns[1..].iter_mut().zip(Channel::ALL) {
+            column.push(s.get(channel));
+        }
+        if self.columns[0].len() >= ROW_GROUP {
+            self.flush_row_group()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.flush_row_group()?;
+        match self.writer.take() {
+            Some(writer) => writer.close().map(drop).map_err(std::io::Error::other),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Profile snapshots in long format: one row per snapshot and radius.
+pub fn write_profiles<P: AsRef<Path>>(
+    path: P,
+    snapshots: &ProfileSnapshots,
+    metadata: &RunMetadata,
+) -> std::io::Result<()> {
+    let fields = snapshots.fields();
+    let schema: SchemaRef = Arc::new(Schema::new(
+        [field("time", "s", "time of profile snapshot"), field("radius", "1", "normalized minor radius r/a")]
+            .into_iter()
+            .chain(fields.iter().map(|f| field(f.name, f.units, f.long_name)))
+            .collect::<Vec<_>>(),
+    ));
+
+    let nr = snapshots.radius.len();
+    let time: Vec<f64> = snapshots.time.iter().flat_map(|&t| std::iter::repeat_n(t, nr)).collect();
+    let radius: Vec<f64> = snapshots.radius.iter().copied().cycle().take(nr * snapshots.len()).collect();
+    let columns = [time, radius]
+        .into_iter()
+        .chain(fields.iter().map(|f| f.rows.iter().flatten().copied().collect()))
+        .collect();
+
+    let mut writer = writer(path.as_ref(), schema.clone(), metadata)?;
+    writer.write(&batch(&schema, columns)?).map_err(std::io::Error::other)?;
+    writer.close().map(drop).map_err(std::io::Error::other)
+}
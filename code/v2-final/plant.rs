@@ -0,0 +1,125 @@
+//! # Plant API
+//!
+//! Minimal, semver-stable interface for embedding the simulator as a plant
+//! model in external tools (e.g. the W7-X discharge-trajectory planner):
+//!
+//! ```no_run
+//! use w7x_turbulence_control::{Config, ControlAction, Plant};
+//!
+//! let mut plant = Plant::try_new(&Config::default()).expect("valid config");
+//! while plant.time() < 30.0 {
+//!     plant.step(0.01);
+//!     let obs = plant.observe();
+//!     if obs.measurement.central_sxr > 8e17 {
+//!         plant.actuate(ControlAction::TriggerPulse);
+//!     }
+//! }
+//! ```
+//!
+//! The caller owns the control loop; the plant only integrates physics,
+//! samples its diagnostics, and executes commanded actions (subject to the
+//! plant's own pulse length and cooldown). Measurements pass through the
+//! `[daq]` cycle and latency before `observe` reports them.
+
+use crate::config::Config;
+use crate::controller::ControlAction;
+use crate::daq::Daq;
+use crate::diagnostics::{Diagnostic, Measurement, SyntheticDiagnostic};
+use crate::error::Result;
+use crate::rng::{RngRegistry, Stream};
+use crate::state::{ConfinementMode, StellaratorState};
+
+/// What the plant reports back to the caller.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct Observation {
+    pub time: f64,                 // s
+    pub measurement: Measurement,  // Latest diagnostic sample
+    pub mode: ConfinementMode,
+}
+
+impl Observation {
+    pub fn new(time: f64, measurement: Measurement, mode: ConfinementMode) -> Self {
+        Observation { time, measurement, mode }
+    }
+}
+
+pub struct Plant {
+    state: StellaratorState,
+    diagnostic: SyntheticDiagnostic,
+    daq: Daq,
+    dt: f64,
+    last_measurement: Measurement,
+}
+
+impl Plant {
+    /// Panics on a config `Config::validate` rejects; see `try_new`.
+    pub fn new(config: &Config) -> Self {
+        let mut state = StellaratorState::from_config(config);
+        state.history.recording = config.output.keep_history;
+        let seed = RngRegistry::new(config).seed(Stream::Diagnostics);
+        let mut diagnostic = SyntheticDiagnostic::new(&config.diagnostics, seed);
+        let last_measurement = diagnostic
+            .observe(&state)
+            .expect("diagnostics always sample at t = 0");
+        Plant {
+            state,
+            diagnostic,
+            daq: Daq::new(&config.daq),
+            dt: config.simulation.dt,
+            last_measurement,
+        }
+    }
+
+    /// `new` after `Config::validate`.
+    pub fn try_new(config: &Config) -> Result<Self> {
+        config.validate()?;
+        Ok(Plant::new(config))
+    }
+
+    /// Advances the plasma by `duration` seconds of simulated time.
+    pub fn step(&mut self, duration: f64) {
+        let t_end = self.state.time + duration;
+        while self.state.time < t_end - 0.5 * self.dt {
+            self.state.update(self.dt);
+            let sample = self.diagnostic.observe(&self.state);
+            if let Some(m) = self.daq.transfer(sample, self.state.time) {
+                self.last_measurement = m;
+            }
+        }
+        // Events are not part of the plant API; don't let them pile up
+        self.state.drain_events();
+    }
+
+    pub fn observe(&self) -> Observation {
+        Observation {
+            time: self.state.time,
+            measurement: self.last_measurement,
+            mode: self.state.confinement_mode,
+        }
+    }
+
+    pub fn actuate(&mut self, action: ControlAction) {
+        self.state.apply_action(action);
+    }
+
+    /// D_turb enhancement factor applied at the edge during pulses.
+    pub fn set_pulse_amplitude(&mut self, amplitude: f64) {
+        self.state.pulse_amplitude = amplitude;
+    }
+
+    /// Injects a pellet. False if the injector is still reloading.
+    pub fn fire_pellet(&mut self) -> bool {
+        self.state.fire_pellet()
+    }
+
+    pub fn time(&self) -> f64 {
+        self.state.time
+    }
+
+    /// Full internal state, for logging and plotting. Not part of the
+    /// stable API surface: field layout may change between minor versions.
+    pub fn state(&self) -> &StellaratorState {
+        &self.state
+    }
+}
@@ -0,0 +1,228 @@
+//! # Plots (feature `plot`)
+//!
+//! End-of-run figures rendered with plotters, in place of
+//! `plot_results.py`:
+//!
+//! - `[output] plot` (PNG): n_Z(0) against the critical density, edge n_Z,
+//!   and edge D_turb over the run with the pulses shaded, above a
+//!   waterfall of n_Z(r, t) (time across, radius up, viridis colour scale).
+//! - `[output] animation` (GIF): n_Z(r) at each profile snapshot, at most
+//!   `MAX_FRAMES` frames.
+//!
+//! Both need the recorded history (`keep_history = true`) or snapshots
+//! (`profile_cadence`); the traces are drawn at most two points per pixel.
+
+use crate::history::{Channel, History};
+use crate::snapshots::ProfileSnapshots;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::error::Error;
+use std::path::Path;
+
+pub const WIDTH: u32 = 1600;
+pub const HEIGHT: u32 = 1600;
+/// Largest number of GIF frames; longer runs skip snapshots.
+pub const MAX_FRAMES: usize = 200;
+/// Delay between GIF frames (ms).
+pub const FRAME_DELAY: u32 = 50;
+/// Densities are plotted in these units (m⁻³).
+const DENSITY_UNIT: f64 = 1e18;
+const FONT: &str = "sans-serif";
+const LABEL_SIZE: u32 = 16;
+/// Width kept for the colour bar, right of every panel so the time axes align.
+const BAR_WIDTH: u32 = 140;
+
+type PlotResult = Result<(), Box<dyn Error>>;
+type Area<'a> = DrawingArea<BitMapBackend<'a>, Shift>;
+
+/// Bounds of `values` with 5% margin, never empty.
+fn range(values: impl Iterator<Item = f64>) -> std::ops::Range<f64> {
+    let (low, high) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(l, h), v| (l.min(v), h.max(v)));
+    if !low.is_finite() {
+        return 0.0..1.0;
+    }
+    let margin = ((high - low) * 0.05).max(high.abs() * 0.05).max(1e-12);
+    (low - margin)..(high + margin)
+}
+
+/// One trace panel with the pulse intervals shaded and an optional
+/// dashed reference level.
+fn trace_panel(
+    area: &Area,
+    label: &str,
+    time: &[f64],
+    values: &[f64],
+    color: RGBColor,
+    pulses: &[(f64, f64)],
+    level: Option<(f64, &str)>,
+) -> PlotResult {
+    let t_end = time.last().copied().unwrap_or(1.0).max(time[0] + 1e-9);
+    let y = range(values.iter().copied().chain(level.map(|(v, _)| v)));
+    let mut chart = ChartBuilder::on(area)
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(90)
+        .build_cartesian_2d(time[0]..t_end, y.clone())?;
+    chart
+        .configure_mesh()
+        .x_desc("t (s)")
+        .y_desc(label)
+        .label_style((FONT, LABEL_SIZE))
+        .light_line_style(WHITE)
+        .draw()?;
+
+    chart.draw_series(pulses.iter().map(|&(start, end)| {
+        Rectangle::new([(start, y.start), (end.min(t_end), y.end)], YELLOW.mix(0.25).filled())
+    }))?;
+    // 1 px: wider polylines are mitred, and the joins of a noisy trace
+    // reach far outside the panel
+    let stride = (time.len() / (2 * WIDTH as usize)).max(1);
+    chart
+        .draw_series(LineSeries::new(
+            time.iter().zip(values).step_by(stride).map(|(&t, &v)| (t, v)),
+            color,
+        ))?
+        .label(label)
+        .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+    if let Some((value, name)) = level {
+        chart
+            .draw_series(DashedLineSeries::new([(time[0], value), (t_end, value)], 10, 6, RED.mix(0.6).into()))?
+            .label(name)
+            .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], RED.mix(0.6)));
+    }
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperRight)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .label_font((FONT, LABEL_SIZE))
+        .draw()?;
+    Ok(())
+}
+
+/// n_Z(r, t) as coloured cells, with a colour bar on the right.
+fn waterfall(area: &Area, snapshots: &ProfileSnapshots) -> PlotResult {
+    let time = &snapshots.time;
+    let radius = &snapshots.radius;
+    let peak = snapshots.impurity_density.iter().flatten().fold(0.0_f64, |m, &v| m.max(v)).max(1e-30);
+    let t_end = time[time.len() - 1] + snapshots.cadence.max(1e-9);
+    let (map, bar) = area.split_horizontally(WIDTH - BAR_WIDTH);
+
+    let mut chart = ChartBuilder::on(&map)
+        .margin(10)
+        .caption("n_Z(r, t)", (FONT, 24))
+        .x_label_area_size(40)
+        .y_label_area_size(90)
+        .build_cartesian_2d(time[0]..t_end, 0.0..1.0)?;
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_desc("t (s)")
+        .y_desc("r / a")
+        .label_style((FONT, LABEL_SIZE))
+        .draw()?;
+    // Cell edges halfway between grid points
+    let edge = |j: usize| if j == 0 { 0.0 } else { 0.5 * (radius[j - 1] + radius[j]) };
+    chart.draw_series(snapshots.impurity_density.iter().enumerate().flat_map(|(i, profile)| {
+        let (t0, t1) = (time[i], time.get(i + 1).copied().unwrap_or(t_end));
+        profile.iter().enumerate().map(move |(j, &n)| {
+            let (r0, r1) = (edge(j), if j + 1 == radius.len() { 1.0 } else { edge(j + 1) });
+            Rectangle::new([(t0, r0), (t1, r1)], ViridisRGB::get_color_normalized(n, 0.0, peak).filled())
+        })
+    }))?;
+
+    let top = peak / DENSITY_UNIT;
+    let mut scale = ChartBuilder::on(&bar)
+        .margin(10)
+        .margin_top(44)
+        .x_label_area_size(40)
+        .y_label_area_size(90)
+        .build_cartesian_2d(0.0..1.0, 0.0..top)?;
+    scale
+        .configure_mesh()
+        .disable_mesh()
+        .disable_x_axis()
+        .y_desc("10¹⁸ m⁻³")
+        .label_style((FONT, LABEL_SIZE))
+        .draw()?;
+    let levels = 100;
+    scale.draw_series((0..levels).map(|k| {
+        let (low, high) = (top * k as f64 / levels as f64, top * (k + 1) as f64 / levels as f64);
+        let color = ViridisRGB::get_color_normalized(low, 0.0, top);
+        Rectangle::new([(0.0, low), (1.0, high)], color.filled())
+    }))?;
+    Ok(())
+}
+
+/// Writes the traces and the n_Z(r, t) waterfall to `path` (PNG).
+/// `pulses` are the (start, end) times of the turbulence pulses.
+pub fn write_plot<P: AsRef<Path>>(
+    path: P,
+    history: &History,
+    snapshots: &ProfileSnapshots,
+    pulses: &[(f64, f64)],
+    critical_density: f64,
+) -> PlotResult {
+    let channels = [Channel::CenterImpurity, Channel::EdgeImpurity, Channel::Turbulence];
+    let traces = history.export_range(f64::NEG_INFINITY, f64::INFINITY, &channels, 1);
+    if traces.is_empty() {
+        return Err("no recorded history (keep_history = false)".into());
+    }
+    if snapshots.is_empty() {
+        return Err("no profile snapshots".into());
+    }
+    let scaled = |values: &[f64]| values.iter().map(|v| v / DENSITY_UNIT).collect::<Vec<_>>();
+
+    let root = BitMapBackend::new(path.as_ref(), (WIDTH, HEIGHT)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let root = root.titled("W7-X Adaptive Turbulence Control", (FONT, 32))?;
+    let (traces_area, map) = root.split_vertically(HEIGHT * 3 / 5);
+    let (traces_area, _) = traces_area.split_horizontally(WIDTH - BAR_WIDTH);
+    let panels = traces_area.split_evenly((3, 1));
+    trace_panel(
+        &panels[0],
+        "center n_Z (10¹⁸ m⁻³)",
+        &traces.time,
+        &scaled(&traces.values[0]),
+        BLUE,
+        pulses,
+        Some((critical_density / DENSITY_UNIT, "critical")),
+    )?;
+    trace_panel(&panels[1], "edge n_Z (10¹⁸ m⁻³)", &traces.time, &scaled(&traces.values[1]), RED, pulses, None)?;
+    trace_panel(&panels[2], "edge D_turb (m²/s)", &traces.time, &traces.values[2], full_palette::GREEN_800, pulses, None)?;
+    waterfall(&map, snapshots)?;
+    root.present()?;
+    Ok(())
+}
+
+/// Writes n_Z(r) at every snapshot (at most `MAX_FRAMES`) as an
+/// animated GIF on a fixed scale.
+pub fn write_animation<P: AsRef<Path>>(path: P, snapshots: &ProfileSnapshots) -> PlotResult {
+    if snapshots.is_empty() {
+        return Err("no profile snapshots".into());
+    }
+    let stride = snapshots.len().div_ceil(MAX_FRAMES);
+    let peak = snapshots.impurity_density.iter().flatten().fold(0.0_f64, |m, &v| m.max(v)) / DENSITY_UNIT;
+    let root = BitMapBackend::gif(path.as_ref(), (800, 500), FRAME_DELAY)?.into_drawing_area();
+    for (time, profile) in snapshots.time.iter().zip(&snapshots.impurity_density).step_by(stride) {
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .margin(10)
+            .caption(format!("n_Z(r) at t = {:.3} s", time), (FONT, 24))
+            .x_label_area_size(40)
+            .y_label_area_size(90)
+            .build_cartesian_2d(0.0..1.0, 0.0..peak.max(1e-12) * 1.05)?;
+        chart
+            .configure_mesh()
+            .x_desc("r / a")
+            .y_desc("n_Z (10¹⁸ m⁻³)")
+            .label_style((FONT, LABEL_SIZE))
+            .draw()?;
+        chart.draw_series(LineSeries::new(
+            snapshots.radius.iter().zip(profile).map(|(&r, &n)| (r, n / DENSITY_UNIT)),
+            &BLUE,
+        ))?;
+        root.present()?;
+    }
+    Ok(())
+}
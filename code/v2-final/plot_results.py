@@ -22,7 +22,7 @@ import numpy as np
 
 
 
-df = pd.read_csv('w7x_simulation.csv')
+df = pd.read_csv('w7x_simulation.csv', comment='#')  # Skips the run_metadata line
 
 
 
@@ -0,0 +1,335 @@
+//! # Plugins (feature `plugins`)
+//!
+//! Controllers and turbulence models loaded from shared libraries at run
+//! time, so proprietary or experimental implementations can be evaluated
+//! against the simulator without being compiled into the crate. A plugin
+//! is selected with `[controller.plugin]` or `[turbulence] type = "plugin"`
+//! and exports C functions of this ABI (declared in
+//! `examples/w7x_plugin.h`, implemented by `examples/plugin.c`):
+//!
+//! ```c
+//! uint32_t w7x_plugin_abi_version(void);  /* W7X_PLUGIN_ABI_VERSION */
+//!
+//! void *w7x_controller_create(const char *parameters);
+//! int w7x_controller_decide(void *controller, const W7xPluginMeasurement *m,
+//!                           W7xPluginAction *action);
+//! void w7x_controller_pulse_started(void *controller, double time,
+//!                                   double duration);  /* optional */
+//! void w7x_controller_destroy(void *controller);
+//!
+//! void *w7x_turbulence_create(const char *parameters);
+//! int w7x_turbulence_evaluate(const void *model, const W7xPluginProfiles *local,
+//!                             W7xPluginTransport *out);
+//! void w7x_turbulence_destroy(void *model);
+//! ```
+//!
+//! A library implements either family or both. `parameters` is the string
+//! from the config, passed on verbatim; `create` returns NULL to refuse it.
+//! `decide` and `evaluate` return 0 on success. On any other value, or on
+//! invalid output, the controller holds and the model falls back to its
+//! nominal transport (D = `d_turb_base`, no pinch), reported once per
+//! failure streak. A controller is only called from one thread, but one
+//! turbulence model is shared by the runs of a scan, so `evaluate` must be
+//! safe to call concurrently. Libraries built for another ABI version are
+//! refused at load.
+//!
+//! Loading a library runs its code with the simulator's privileges: only
+//! load plugins you trust.
+
+use crate::controller::{ControlAction, Controller, PluginConfig, PulseCommand};
+use crate::diagnostics::Measurement;
+use crate::turbulence::LocalProfiles;
+use libloading::Library;
+use std::ffi::{c_char, c_int, c_void, CString};
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Returned by `w7x_plugin_abi_version`; bumped on any change to the
+/// functions or structs below.
+pub const W7X_PLUGIN_ABI_VERSION: u32 = 1;
+
+/// `W7xPluginAction::kind` values.
+pub const W7X_PLUGIN_HOLD: c_int = 0;
+pub const W7X_PLUGIN_PULSE: c_int = 1;
+
+/// Diagnostic sample handed to `w7x_controller_decide`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct W7xPluginMeasurement {
+    /// s
+    pub time: f64,
+    /// Measured n_Z(0) (m⁻³)
+    pub central_sxr: f64,
+    /// Measured edge n_Z (m⁻³)
+    pub edge_density: f64,
+    /// Measured edge D_turb (m²/s)
+    pub turbulence: f64,
+}
+
+/// Decision filled in by `w7x_controller_decide`; zeroed before the call.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct W7xPluginAction {
+    /// `W7X_PLUGIN_HOLD` or `W7X_PLUGIN_PULSE`
+    pub kind: c_int,
+    /// Pulse window index; 0 = plant default
+    pub window: u32,
+    /// Pulse amplitude; 0 = plant default
+    pub amplitude: f64,
+}
+
+/// One grid point handed to `w7x_turbulence_evaluate`: the `LocalProfiles`
+/// and the derived gradient lengths and collisionality.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct W7xPluginProfiles {
+    /// r / a
+    pub radius: f64,
+    /// n_e (m⁻³)
+    pub electron_density: f64,
+    /// ∂n_e/∂(r/a) (m⁻³)
+    pub density_gradient: f64,
+    /// T_e (keV)
+    pub electron_temp: f64,
+    /// ∂T_e/∂(r/a) (keV)
+    pub temperature_gradient: f64,
+    /// Nominal D_turb (m²/s)
+    pub d_turb_base: f64,
+    /// Actuator output inside the pulse region, 0 elsewhere
+    pub pulse_level: f64,
+    /// a / L_n
+    pub inverse_density_length: f64,
+    /// a / L_T
+    pub inverse_temperature_length: f64,
+    /// ν*_e
+    pub collisionality: f64,
+}
+
+/// Transport filled in by `w7x_turbulence_evaluate`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct W7xPluginTransport {
+    /// D_turb (m²/s), ≥ 0
+    pub diffusivity: f64,
+    /// Turbulent convection (m/s), negative inward
+    pub pinch: f64,
+}
+
+type CreateFn = unsafe extern "C" fn(*const c_char) -> *mut c_void;
+type DestroyFn = unsafe extern "C" fn(*mut c_void);
+type DecideFn = unsafe extern "C" fn(*mut c_void, *const W7xPluginMeasurement, *mut W7xPluginAction) -> c_int;
+type PulseStartedFn = unsafe extern "C" fn(*mut c_void, f64, f64);
+type EvaluateFn = unsafe extern "C" fn(*const c_void, *const W7xPluginProfiles, *mut W7xPluginTransport) -> c_int;
+
+/// Controller implemented by a plugin library (`[controller.plugin]`).
+pub struct PluginController {
+    handle: *mut c_void,
+    decide: DecideFn,
+    pulse_started: Option<PulseStartedFn>,
+    destroy: DestroyFn,
+    path: String,
+    failure_reported: bool,
+    _library: Library, // Last: unloaded after the handle is destroyed
+}
+
+impl PluginController {
+    pub fn load(config: &PluginConfig) -> io::Result<Self> {
+        let path = &config.path;
+        let library = open(path)?;
+        let create: CreateFn = symbol(&library, path, "w7x_controller_create")?;
+        let decide = symbol(&library, path, "w7x_controller_decide")?;
+        let destroy = symbol(&library, path, "w7x_controller_destroy")?;
+        let pulse_started = symbol(&library, path, "w7x_controller_pulse_started").ok();
+        let handle = create_handle(create, path, &config.parameters)?;
+        Ok(PluginController {
+            handle,
+            decide,
+            pulse_started,
+            destroy,
+            path: path.clone(),
+            failure_reported: false,
+            _library: library,
+        })
+    }
+}
+
+impl Controller for PluginController {
+    fn decide(&mut self, measurement: &Measurement) -> ControlAction {
+        let sample = W7xPluginMeasurement {
+            time: measurement.time,
+            central_sxr: measurement.central_sxr,
+            edge_density: measurement.edge_density,
+            turbulence: measurement.turbulence,
+        };
+        let mut decision = W7xPluginAction::default();
+        // SAFETY: the handle is live until drop; both pointers outlive the call.
+        let status = unsafe { (self.decide)(self.handle, &sample, &mut decision) };
+        match action(status, decision) {
+            Ok(action) => {
+                self.failure_reported = false;
+                action
+            }
+            Err(e) => {
+                if !self.failure_reported {
+                    self.failure_reported = true;
+                    tracing::error!("❌ Plugin {} failed at t={:.4}s, holding: {}", self.path, measurement.time, e);
+                }
+                ControlAction::Hold
+            }
+        }
+    }
+
+    fn pulse_started(&mut self, time: f64, duration: f64) {
+        if let Some(pulse_started) = self.pulse_started {
+            // SAFETY: the handle is live until drop.
+            unsafe { pulse_started(self.handle, time, duration) }
+        }
+    }
+}
+
+impl Drop for PluginController {
+    fn drop(&mut self) {
+        // SAFETY: created by this library's `create` and not used afterwards.
+        unsafe { (self.destroy)(self.handle) }
+    }
+}
+
+/// Turbulence model implemented by a plugin library, shared by the
+/// `PluginModel`s cloned from one config.
+pub struct TurbulencePlugin {
+    handle: *mut c_void,
+    evaluate: EvaluateFn,
+    destroy: DestroyFn,
+    path: String,
+    failing: AtomicBool,
+    _library: Library, // Last: unloaded after the handle is destroyed
+}
+
+// SAFETY: the ABI requires `w7x_turbulence_evaluate` to be thread-safe, and
+// the handle is only destroyed on drop, when no other reference is left.
+unsafe impl Send for TurbulencePlugin {}
+unsafe impl Sync for TurbulencePlugin {}
+
+impl TurbulencePlugin {
+    pub fn load(path: &str, parameters: &str) -> io::Result<Self> {
+        let library = open(path)?;
+        let create: CreateFn = symbol(&library, path, "w7x_turbulence_create")?;
+        let evaluate = symbol(&library, path, "w7x_turbulence_evaluate")?;
+        let destroy = symbol(&library, path, "w7x_turbulence_destroy")?;
+        let handle = create_handle(create, path, parameters)?;
+        Ok(TurbulencePlugin {
+            handle,
+            evaluate,
+            destroy,
+            path: path.to_string(),
+            failing: AtomicBool::new(false),
+            _library: library,
+        })
+    }
+
+    /// D_turb (m²/s) and V (m/s) at one grid point; `None` if the plugin
+    /// failed there.
+    pub fn evaluate(&self, local: &LocalProfiles) -> Option<(f64, f64)> {
+        let profiles = W7xPluginProfiles {
+            radius: local.radius,
+            electron_density: local.electron_density,
+            density_gradient: local.density_gradient,
+            electron_temp: local.electron_temp,
+            temperature_gradient: local.temperature_gradient,
+            d_turb_base: local.d_turb_base,
+            pulse_level: local.pulse_level,
+            inverse_density_length: local.inverse_density_length(),
+            inverse_temperature_length: local.inverse_temperature_length(),
+            collisionality: local.collisionality(),
+        };
+        let mut out = W7xPluginTransport::default();
+        // SAFETY: the handle is live until drop; both pointers outlive the call.
+        let status = unsafe { (self.evaluate)(self.handle, &profiles, &mut out) };
+        if status == 0 && out.diffusivity >= 0.0 && out.diffusivity.is_finite() && out.pinch.is_finite() {
+            if self.failing.load(Ordering::Relaxed) {
+                self.failing.store(false, Ordering::Relaxed);
+            }
+            return Some((out.diffusivity, out.pinch));
+        }
+        if !self.failing.swap(true, Ordering::Relaxed) {
+            tracing::error!(
+                "❌ Plugin {} failed at r/a={:.3} (status {}, D={}, V={}), using nominal transport",
+                self.path, local.radius, status, out.diffusivity, out.pinch
+            );
+        }
+        None
+    }
+}
+
+impl Drop for TurbulencePlugin {
+    fn drop(&mut self) {
+        // SAFETY: created by this library's `create` and not used afterwards.
+        unsafe { (self.destroy)(self.handle) }
+    }
+}
+
+impl fmt::Debug for TurbulencePlugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TurbulencePlugin").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
+/// Same library file; the parameters are compared by `PluginModel`.
+impl PartialEq for TurbulencePlugin {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+fn error(path: &str, message: impl fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, message))
+}
+
+/// Opens `path` and checks its ABI version.
+fn open(path: &str) -> io::Result<Library> {
+    // SAFETY: runs the library's initializers; plugins are trusted (see above).
+    let library = unsafe { Library::new(path) }.map_err(|e| error(path, e))?;
+    let version: unsafe extern "C" fn() -> u32 = symbol(&library, path, "w7x_plugin_abi_version")?;
+    // SAFETY: declared by the ABI with this signature.
+    let version = unsafe { version() };
+    if version != W7X_PLUGIN_ABI_VERSION {
+        return Err(error(path, format!("plugin ABI version {}, expected {}", version, W7X_PLUGIN_ABI_VERSION)));
+    }
+    Ok(library)
+}
+
+/// Function `name` of the ABI; the caller names its signature.
+fn symbol<T: Copy>(library: &Library, path: &str, name: &str) -> io::Result<T> {
+    // SAFETY: `T` is the ABI's signature of `name`; the pointer is only used
+    // while `library` is loaded.
+    unsafe { library.get::<T>(name.as_bytes()) }.map(|f| *f).map_err(|e| error(path, e))
+}
+
+fn create_handle(create: CreateFn, path: &str, parameters: &str) -> io::Result<*mut c_void> {
+    let parameters = CString::new(parameters).map_err(|_| error(path, "parameters contain a NUL byte"))?;
+    // SAFETY: `parameters` is NUL-terminated and outlives the call.
+    let handle = unsafe { create(parameters.as_ptr()) };
+    if handle.is_null() {
+        return Err(error(path, format!("refused parameters {:?}", parameters)));
+    }
+    Ok(handle)
+}
+
+/// Reads a `decide` result.
+fn action(status: c_int, decision: W7xPluginAction) -> Result<ControlAction, String> {
+    if status != 0 {
+        return Err(format!("decide returned {}", status));
+    }
+    match decision.kind {
+        W7X_PLUGIN_HOLD => Ok(ControlAction::Hold),
+        W7X_PLUGIN_PULSE if decision.amplitude.is_nan() || decision.amplitude < 0.0 => {
+            Err(format!("invalid amplitude {}", decision.amplitude))
+        }
+        W7X_PLUGIN_PULSE => Ok(ControlAction::Pulse(PulseCommand {
+            window: decision.window as usize,
+            amplitude: (decision.amplitude > 0.0).then_some(decision.amplitude),
+        })),
+        kind => Err(format!("unknown action kind {}", kind)),
+    }
+}
@@ -0,0 +1,380 @@
+//! # Poloidal Transport (experimental)
+//!
+//! 2D (r, θ) impurity density for heavy impurities whose friction with the
+//! main ions drives a poloidal asymmetry. Each θ column sees the radial
+//! D(r) and v(r) of the 1D model; along θ, impurities diffuse toward the
+//! parallel equilibrium n ∝ exp(ψ), ψ = asymmetry · r · cos θ (θ = 0 on the
+//! outboard side), treated implicitly since the poloidal time scale near
+//! the axis is far shorter than the step.
+//!
+//! The poloidal average is written back to `impurity_density` every step,
+//! so diagnostics and outputs work unchanged. Changes made to the 1D
+//! profile from outside (pellets, checkpoints) rescale the columns.
+//! Profile regularization is not applied in 2D.
+//!
+//! `backend = "gpu"` moves the radial and poloidal updates to a GPU
+//! (`gpu.rs`) for high-resolution runs.
+
+use crate::boundary::BoundaryCondition;
+use crate::convection::Convection;
+use crate::geometry::Metric;
+use crate::precision::Real;
+use crate::state::MAX_IMPURITY_DENSITY;
+use crate::stencil::{self, Stencil};
+use ndarray::{Array1, Array2, ArrayView1, Axis};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Grids at least this large get their transport rates in parallel
+/// radial chunks (`parallel` feature); on smaller ones the rayon overhead
+/// outweighs the work.
+pub const PARALLEL_MIN_POINTS: usize = 2048;
+
+/// Radial coefficients and boundary data for one step; rates are
+/// computed in `T` (`Precision`).
+pub struct RadialTerms<'a, T: Real = f64> {
+    pub radius: &'a Array1<f64>,
+    pub dr: f64,
+    pub metric: &'a Metric,
+    pub velocity: &'a Array1<T>,    // m/s, pinch × ⟨|∇ρ|⟩; 0 at the grid ends
+    pub diffusivity: &'a Array1<T>, // m²/s, D × ⟨|∇ρ|²⟩
+    pub source: f64,                // m⁻³/s in r > source_radius
+    pub source_radius: f64,
+    pub core: BoundaryCondition,
+    pub edge: BoundaryCondition,
+    pub convection: Convection,
+    pub stencil: Stencil,
+}
+
+impl<T: Real> RadialTerms<'_, T> {
+    /// Γ = v n − D ∂n/∂r through the face between `i` and `i + 1`, n of
+    /// the convective part per `convection`. `Second`: taken at grid
+    /// point i, 0 at the grid ends.
+    pub fn flux(&self, n: ArrayView1<T>, i: usize) -> T {
+        let nr = self.radius.len();
+        match self.stencil {
+            Stencil::Second => {
+                if i == 0 || i >= nr - 1 {
+                    return T::zero();
+                }
+                let gradient = (n[i + 1] - n[i - 1]) / T::of(2.0 * self.dr);
+                let v = self.velocity[i];
+                v * self.convection.face_density(n, i, v, n[i]) - self.diffusivity[i] * gradient
+            }
+            Stencil::Fourth => {
+                // n to the grid ends, D and v (0 there) from the interior
+                let points = |x: ArrayView1<T>, first: usize, last: usize| {
+                    [-1, 0, 1, 2].map(|k| stencil::extended(x, i as isize + k, first, last))
+                };
+                let density = points(n, 0, nr - 1);
+                let d = stencil::face_value(points(self.diffusivity.view(), 1, nr - 2));
+                let v = stencil::face_value(points(self.velocity.view(), 1, nr - 2));
+                let n_face = self.convection.face_density(n, i, v, stencil::face_value(density));
+                v * n_face - d * stencil::face_gradient(density, self.dr)
+            }
+        }
+    }
+
+    /// ∂n/∂t = −∇·Γ + S in interior cell `i`.
+    pub fn rate(&self, n: ArrayView1<T>, i: usize) -> T {
+        let r = self.radius[i];
+        let metric = self.metric;
+        let div_flux = match self.stencil {
+            Stencil::Second => {
+                let flux_p = self.flux(n, i);
+                let flux_m = self.flux(n, i - 1);
+                // V' at the faces and the cell; r ± dr/2 and r in the cylinder
+                if r > 0.01 {
+                    (T::of(metric.vprime_outer[i]) * flux_p - T::of(metric.vprime_inner[i]) * flux_m)
+                        / T::of(metric.vprime[i] * self.dr)
+                } else {
+                    (flux_p - flux_m) / T::of(self.dr)
+                }
+            }
+            Stencil::Fourth => {
+                // F = V' Γ on faces i − 3/2 ..= i + 3/2, cubic beyond the grid
+                let last = self.radius.len() - 2;
+                let face = |k: usize| T::of(metric.vprime_outer[k]) * self.flux(n, k);
+                let outside = |f: [T; 4]| T::of(4.0) * f[0] - T::of(6.0) * f[1] + T::of(4.0) * f[2] - f[3];
+                let inner2 = if i >= 2 { face(i - 2) } else { outside([0, 1, 2, 3].map(face)) };
+                let outer2 = if i < last { face(i + 1) } else { outside([0, 1, 2, 3].map(|k| face(last - k))) };
+                let (inner, outer) = (face(i - 1), face(i));
+                (T::of(27.0) * (outer - inner) - (outer2 - inner2)) / T::of(24.0 * self.dr * metric.vprime[i])
+            }
+        };
+        let source = if r > self.source_radius { T::of(self.source) } else { T::zero() };
+        source - div_flux
+    }
+
+    /// `rate` at every interior point; 0 at the grid ends.
+    pub fn rates(&self, n: ArrayView1<T>) -> Array1<T> {
+        let nr = self.radius.len();
+        let rate = |i: usize| if i == 0 || i == nr - 1 { T::zero() } else { self.rate(n, i) };
+        #[cfg(feature = "parallel")]
+        if nr >= PARALLEL_MIN_POINTS {
+            let rates: Vec<T> = (0..nr).into_par_iter().with_min_len(PARALLEL_MIN_POINTS / 4).map(rate).collect();
+            return Array1::from(rates);
+        }
+        (0..nr).map(rate).collect()
+    }
+}
+
+/// Where the 2D update runs.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    #[default]
+    Cpu,
+    /// wgpu compute shaders, f32 (`gpu` feature).
+    Gpu,
+}
+
+impl Backend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Cpu => "cpu",
+            Backend::Gpu => "gpu",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PoloidalTransport {
+    pub diffusivity: f64, // m²/s, poloidal mixing
+    pub asymmetry: f64,   // Outboard excess: ψ = asymmetry · r · cos θ
+    #[serde(default)]
+    pub backend: Backend,
+    density: Array2<f64>, // (radius, θ), m⁻³
+    #[serde(skip)]
+    implicit: Vec<Array2<f64>>, // (I − dt L_θ)⁻¹ per radius
+    #[serde(skip)]
+    implicit_dt: f64,
+    #[cfg(feature = "gpu")]
+    #[serde(skip)]
+    kernel: crate::gpu::KernelSlot,
+}
+
+impl PoloidalTransport {
+    /// Starts from `profile` distributed along θ in parallel equilibrium.
+    pub fn new(ntheta: usize, diffusivity: f64, asymmetry: f64, radius: &Array1<f64>, profile: &Array1<f64>) -> Self {
+        let ntheta = ntheta.max(4);
+        let mut transport = PoloidalTransport {
+            diffusivity,
+            asymmetry,
+            backend: Backend::Cpu,
+            density: Array2::zeros((radius.len(), ntheta)),
+            implicit: Vec::new(),
+            implicit_dt: 0.0,
+            #[cfg(feature = "gpu")]
+            kernel: Default::default(),
+        };
+        for (i, &r) in radius.iter().enumerate() {
+            let weights: Vec<f64> = (0..ntheta).map(|j| transport.psi(r, j).exp()).collect();
+            let mean = weights.iter().sum::<f64>() / ntheta as f64;
+            for (j, w) in weights.iter().enumerate() {
+                transport.density[[i, j]] = profile[i] * w / mean;
+            }
+        }
+        transport
+    }
+
+    pub fn ntheta(&self) -> usize {
+        self.density.ncols()
+    }
+
+    fn dtheta(&self) -> f64 {
+        2.0 * PI / self.ntheta() as f64
+    }
+
+    fn psi(&self, r: f64, j: usize) -> f64 {
+        let theta = 2.0 * PI * j as f64 / self.density.ncols() as f64;
+        self.asymmetry * r * theta.cos()
+    }
+
+    /// n_Z(r, θ), rows are radii and columns poloidal angles from the outboard midplane.
+    pub fn density(&self) -> &Array2<f64> {
+        &self.density
+    }
+
+    /// Flux-surface (poloidal) average.
+    pub fn average(&self) -> Array1<f64> {
+        self.density.mean_axis(ndarray::Axis(1)).expect("ntheta > 0")
+    }
+
+    /// Outboard / inboard midplane density at grid index `i`.
+    pub fn in_out_ratio(&self, i: usize) -> f64 {
+        let inboard = self.density[[i, self.ntheta() / 2]];
+        if inboard > 0.0 {
+            self.density[[i, 0]] / inboard
+        } else {
+            1.0
+        }
+    }
+
+    /// Rescales columns where the 1D profile was changed from outside.
+    pub fn sync(&mut self, radius: &Array1<f64>, profile: &Array1<f64>) {
+        let average = self.average();
+        let ntheta = self.ntheta();
+        for i in 0..profile.len() {
+            let target = profile[i];
+            if (target - average[i]).abs() <= 1e-12 * target.abs().max(1.0) {
+                continue;
+            }
+            if average[i] > 0.0 {
+                let scale = target / average[i];
+                self.density.row_mut(i).mapv_inplace(|n| n * scale);
+            } else {
+                let weights: Vec<f64> = (0..ntheta).map(|j| self.psi(radius[i], j).exp()).collect();
+                let mean = weights.iter().sum::<f64>() / ntheta as f64;
+                for (j, w) in weights.iter().enumerate() {
+                    self.density[[i, j]] = target * w / mean;
+                }
+            }
+        }
+    }
+
+    /// Builds (I − dt L_θ)⁻¹ for every radius. L_θ is the exponentially
+    /// fitted poloidal operator whose null space is n ∝ exp(ψ).
+    fn prepare(&mut self, radius: &Array1<f64>, dt: f64) {
+        let ntheta = self.ntheta();
+        let dtheta = self.dtheta();
+        self.implicit = radius
+            .iter()
+            .map(|&r| {
+                let mut a = Array2::eye(ntheta);
+                if r <= 0.0 {
+                    return a;
+                }
+                let rate = self.diffusivity / (r * r * dtheta * dtheta);
+                for j in 0..ntheta {
+                    for k in [(j + 1) % ntheta, (j + ntheta - 1) % ntheta] {
+                        let psi_face = 0.5 * (self.psi(r, j) + self.psi(r, k));
+                        let c = rate * psi_face.exp();
+                        a[[j, k]] -= dt * c * (-self.psi(r, k)).exp();
+                        a[[j, j]] += dt * c * (-self.psi(r, j)).exp();
+                    }
+                }
+                invert(a)
+            })
+            .collect();
+        self.implicit_dt = dt;
+    }
+
+    /// Advances n_Z(r, θ) by `dt` and returns the new poloidal average.
+    pub fn step(&mut self, terms: &RadialTerms, dt: f64) -> Array1<f64> {
+        let prepared = self.implicit_dt != dt || self.implicit.len() != terms.radius.len();
+        if prepared {
+            self.prepare(terms.radius, dt);
+        }
+        let dr = terms.dr;
+
+        #[cfg(feature = "gpu")]
+        let mut next = match self.advance_gpu(terms, dt, prepared) {
+            Some(next) => next,
+            None => self.advance(terms, dt),
+        };
+        #[cfg(not(feature = "gpu"))]
+        let mut next = self.advance(terms, dt);
+
+        // Edge per column, core from the poloidal average
+        let ntheta = self.ntheta();
+        for j in 0..ntheta {
+            terms.stencil.close(next.column_mut(j), terms.core, terms.edge, dr);
+        }
+        let mut average = next.mean_axis(Axis(1)).expect("ntheta ≥ 4");
+        terms.stencil.close(average.view_mut(), terms.core, terms.edge, dr);
+        next.row_mut(0).fill(average[0]);
+
+        self.density = next;
+        self.average()
+    }
+
+    /// Radial then implicit poloidal update of the interior radii.
+    fn advance(&self, terms: &RadialTerms, dt: f64) -> Array2<f64> {
+        let nr = terms.radius.len();
+
+        // Radial step per θ column, same discretization as the 1D solver
+        let mut next = self.density.clone();
+        for j in 0..self.ntheta() {
+            let rates = terms.rates(self.density.column(j));
+            for i in 1..nr - 1 {
+                next[[i, j]] = self.density[[i, j]] + rates[i] * dt;
+            }
+        }
+
+        // Implicit poloidal step per radius
+        for i in 1..nr - 1 {
+            let row = self.implicit[i].dot(&next.row(i));
+            next.row_mut(i).assign(&row.mapv(|n: f64| n.clamp(0.0, MAX_IMPURITY_DENSITY)));
+        }
+        next
+    }
+
+    /// `advance` on the GPU; `None` for the CPU backend, a stencil the
+    /// kernel lacks, or no usable adapter (then the CPU from here on).
+    #[cfg(feature = "gpu")]
+    fn advance_gpu(&mut self, terms: &RadialTerms, dt: f64, prepared: bool) -> Option<Array2<f64>> {
+        if self.backend != Backend::Gpu || terms.stencil != Stencil::Second {
+            return None;
+        }
+        let kernel = match &self.kernel.0 {
+            Some(kernel) => {
+                if prepared {
+                    kernel.set_implicit(&self.implicit);
+                }
+                kernel
+            }
+            None => match crate::gpu::PoloidalKernel::new(terms.radius.len(), self.ntheta()) {
+                Ok(kernel) => {
+                    tracing::info!("🖥️ 2D transport on {}", kernel.adapter());
+                    kernel.set_implicit(&self.implicit);
+                    self.kernel.0.insert(kernel)
+                }
+                Err(e) => {
+                    tracing::warn!("⚠️ GPU backend unavailable ({}); 2D transport on the CPU", e);
+                    self.backend = Backend::Cpu;
+                    return None;
+                }
+            },
+        };
+        Some(kernel.advance(terms, &self.density, dt))
+    }
+}
+
+/// Gauss-Jordan inverse with partial pivoting (small, diagonally dominant systems).
+fn invert(mut a: Array2<f64>) -> Array2<f64> {
+    let n = a.nrows();
+    let mut inverse = Array2::eye(n);
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&x, &y| a[[x, col]].abs().total_cmp(&a[[y, col]].abs()))
+            .unwrap_or(col);
+        if pivot != col {
+            for k in 0..n {
+                a.swap([col, k], [pivot, k]);
+                inverse.swap([col, k], [pivot, k]);
+            }
+        }
+        let diagonal = a[[col, col]];
+        for k in 0..n {
+            a[[col, k]] /= diagonal;
+            inverse[[col, k]] /= diagonal;
+        }
+        for row in 0..n {
+            if row == col {
+                continue;
+            }
+            let factor = a[[row, col]];
+            if factor == 0.0 {
+                continue;
+            }
+            for k in 0..n {
+                a[[row, k]] -= factor * a[[col, k]];
+                inverse[[row, k]] -= factor * inverse[[col, k]];
+            }
+        }
+    }
+    inverse
+}
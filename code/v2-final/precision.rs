@@ -0,0 +1,174 @@
+//! # Floating-Point Precision
+//!
+//! `[numerics] precision = "f32"` computes the 1D n_Z transport rates
+//! (`RadialTerms`) in single precision and adds them to the f64 profile.
+//! Coefficients, boundaries, temperature, turbulence, and the 2D solver
+//! stay f64, so `Config::validate` refuses f32 in the poloidal geometry.
+//!
+//! It is not faster on a CPU yet: the rate kernel is a scalar loop bound by
+//! indexing and branches rather than float width (`radial_rate_f32` in
+//! `cargo bench --bench transport` is as fast as `radial_rate` or slower),
+//! and it is ~15 % of a step at nr = 101, so even a free kernel could not
+//! double scan throughput. The option and the check below are the
+//! groundwork for a vectorized or GPU kernel.
+//!
+//! `cargo run --release -- precision --config w7x.toml` runs the scenario
+//! in f64 and f32 from the same seed and compares n_Z(0) every
+//! `trace_interval`. Without control the two differ by ~1e-7; closed loop,
+//! a detection or pulse landing one sample apart separates the traces for
+//! a while (10–30 % at the default settings) even though the pulse counts
+//! and final densities agree to ~1e-3.
+
+use crate::config::Config;
+use crate::controller::{ControlAction, FixedController};
+use crate::error::Result;
+use crate::scan::run_quiet_with;
+use crate::simulation::Simulation;
+use crate::summary::RunSummary;
+use num_traits::Float;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Floating-point type of the transport kernel.
+pub trait Real: Float + Send + Sync + fmt::Debug + 'static {
+    fn of(value: f64) -> Self;
+}
+
+impl Real for f64 {
+    fn of(value: f64) -> Self {
+        value
+    }
+}
+
+impl Real for f32 {
+    fn of(value: f64) -> Self {
+        value as f32
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Precision {
+    #[default]
+    F64,
+    F32,
+}
+
+impl Precision {
+    pub const ALL: [Precision; 2] = [Precision::F64, Precision::F32];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Precision::F64 => "f64",
+            Precision::F32 => "f32",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrecisionCheckConfig {
+    pub trace_interval: f64, // s between compared n_Z(0) samples
+    pub tolerance: f64,      // Relative n_Z(0) difference reported as divergence
+    pub control: bool,       // false: controller held off (no pulses)
+    pub output: String,      // n_Z(0) in both precisions over time (CSV)
+}
+
+impl Default for PrecisionCheckConfig {
+    fn default() -> Self {
+        PrecisionCheckConfig {
+            trace_interval: 0.01,
+            tolerance: 1e-3,
+            control: false,
+            output: "precision.csv".to_string(),
+        }
+    }
+}
+
+pub struct PrecisionCheck {
+    pub time: Vec<f64>,
+    pub reference: Vec<f64>, // m⁻³, n_Z(0) in f64
+    pub reduced: Vec<f64>,   // m⁻³, n_Z(0) in f32
+    pub summaries: [RunSummary; 2], // In `Precision::ALL` order
+    pub tolerance: f64,
+}
+
+impl PrecisionCheck {
+    /// |f32 − f64| / f64 of n_Z(0) at every sample.
+    pub fn relative_difference(&self) -> Vec<f64> {
+        self.reference
+            .iter()
+            .zip(&self.reduced)
+            .map(|(a, b)| (b - a).abs() / a.abs().max(1e-300))
+            .collect()
+    }
+
+    pub fn max_relative_difference(&self) -> f64 {
+        self.relative_difference().into_iter().fold(0.0, f64::max)
+    }
+
+    /// Time of the first sample past `tolerance`, if any.
+    pub fn diverged_at(&self) -> Option<f64> {
+        let difference = self.relative_difference();
+        difference.iter().position(|&d| d > self.tolerance).map(|j| self.time[j])
+    }
+}
+
+/// Runs `base` in both precisions and compares n_Z(0).
+pub fn run_precision_check(base: &Config) -> Result<PrecisionCheck> {
+    base.validate()?;
+    let settings = &base.precision_check;
+    let run = |precision: &Precision| {
+        let mut config = base.clone();
+        config.numerics.precision = *precision;
+        let mut sim = Simulation::try_from_config(&config)?;
+        if !settings.control {
+            sim.controller = Box::new(FixedController(ControlAction::Hold));
+        }
+        let mut trace = Vec::new();
+        let mut next_time = 0.0;
+        let summary = run_quiet_with(sim, &config, |state| {
+            if state.time >= next_time {
+                trace.push(state.impurity_density[0]);
+                next_time += settings.trace_interval;
+            }
+        })?;
+        Ok((summary, trace))
+    };
+    #[cfg(feature = "parallel")]
+    let runs: Vec<_> = Precision::ALL.par_iter().map(run).collect::<Result<_>>()?;
+    #[cfg(not(feature = "parallel"))]
+    let runs: Vec<_> = Precision::ALL.iter().map(run).collect::<Result<_>>()?;
+
+    let [(reference_summary, reference), (reduced_summary, reduced)]: [_; 2] =
+        runs.try_into().unwrap_or_else(|_| unreachable!("one run per precision"));
+    let n_points = reference.len().min(reduced.len());
+    Ok(PrecisionCheck {
+        time: (0..n_points).map(|j| j as f64 * settings.trace_interval).collect(),
+        reference: reference[..n_points].to_vec(),
+        reduced: reduced[..n_points].to_vec(),
+        summaries: [reference_summary, reduced_summary],
+        tolerance: settings.tolerance,
+    })
+}
+
+#[cfg(feature = "fs")]
+impl PrecisionCheck {
+    pub fn write_traces<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        metadata: &crate::metadata::RunMetadata,
+    ) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        metadata.write_csv_header(&mut writer)?;
+        writeln!(writer, "time,center_impurity_f64,center_impurity_f32,relative_difference")?;
+        for (j, difference) in self.relative_difference().iter().enumerate() {
+            writeln!(writer, "{:.6},{:.6e},{:.6e},{:.3e}", self.time[j], self.reference[j], self.reduced[j], difference)?;
+        }
+        writer.flush()
+    }
+}
@@ -0,0 +1,118 @@
+//! # Scenario Presets
+//!
+//! Named W7-X operating points selected with `--preset`. Each sets a
+//! consistent grid and time step (within the explicit stability limit
+//! during pulses), initial profiles, transport coefficients, impurity
+//! source, and heating; everything else, including a measured-profile
+//! file, keeps its value from the config file or the defaults. The numbers are representative of the published
+//! OP1.2 discharges, not fits to a particular shot.
+//!
+//! - `op12_standard`: standard magnetic configuration, ECRH, gas fuelling;
+//!   flat n_e, peaked T_e, ITG-dominated transport.
+//! - `high_mirror`: high-mirror configuration; lower neoclassical
+//!   transport, weaker pinch, better energy confinement.
+//! - `pellet_high_performance`: pellet-fuelled phase with peaked density
+//!   and reduced turbulence, where the inward pinch drives accumulation.
+
+use crate::config::Config;
+use crate::profiles::ProfileConfig;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    Op12Standard,
+    HighMirror,
+    PelletHighPerformance,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Op12Standard, Preset::HighMirror, Preset::PelletHighPerformance];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Op12Standard => "op12_standard",
+            Preset::HighMirror => "high_mirror",
+            Preset::PelletHighPerformance => "pellet_high_performance",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Preset> {
+        Preset::ALL.into_iter().find(|p| p.name() == name)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Preset::Op12Standard => "OP1.2 standard configuration, ECRH-heated, gas-fuelled",
+            Preset::HighMirror => "OP1.2 high-mirror configuration",
+            Preset::PelletHighPerformance => "OP1.2 pellet-fuelled high-performance phase",
+        }
+    }
+
+    /// Overwrites the settings this preset defines.
+    pub fn apply(&self, config: &mut Config) {
+        config.simulation.nr = 101;
+        config.plasma.impurity_charge = 26.0;
+        match self {
+            Preset::Op12Standard => {
+                config.simulation.dt = 5e-6;
+                config.profiles = ProfileConfig {
+                    density: 6e19,
+                    density_peaking: 0.5,
+                    temperature: 5.0,
+                    temperature_peaking: 1.5,
+                    impurity_density: 5e17,
+                    impurity_shape: vec![0.2, 0.8],
+                    ..config.profiles.clone()
+                };
+                config.plasma.d_neo = 0.03;
+                config.plasma.d_turb_base = 1.0;
+                config.plasma.v_neo = -0.3;
+                config.plasma.impurity_source = 2e17;
+                config.plasma.chi_e = 1.0;
+                config.confinement.heating_power = 4.5;
+                config.confinement.reference_time = 0.15;
+            }
+            Preset::HighMirror => {
+                config.simulation.dt = 5e-6;
+                config.profiles = ProfileConfig {
+                    density: 5e19,
+                    density_peaking: 0.5,
+                    temperature: 6.0,
+                    temperature_peaking: 1.5,
+                    impurity_density: 5e17,
+                    impurity_shape: vec![0.2, 0.8],
+                    ..config.profiles.clone()
+                };
+                config.plasma.d_neo = 0.02;
+                config.plasma.d_turb_base = 0.8;
+                config.plasma.v_neo = -0.2;
+                config.plasma.impurity_source = 2e17;
+                config.plasma.chi_e = 0.8;
+                config.confinement.heating_power = 4.5;
+                config.confinement.reference_time = 0.2;
+            }
+            Preset::PelletHighPerformance => {
+                config.simulation.dt = 1e-5;
+                config.profiles = ProfileConfig {
+                    density: 8e19,
+                    density_peaking: 1.5,
+                    temperature: 3.5,
+                    temperature_peaking: 1.0,
+                    impurity_density: 5e17,
+                    impurity_shape: vec![0.2, 0.8],
+                    ..config.profiles.clone()
+                };
+                config.plasma.d_neo = 0.02;
+                config.plasma.d_turb_base = 0.5;
+                config.plasma.v_neo = -0.8;
+                config.plasma.impurity_source = 2.5e17;
+                config.plasma.chi_e = 0.5;
+                config.pellet.density_increment = 5e18;
+                config.pellet.schedule = (0..20).map(|i| 1.0 + 0.1 * i as f64).collect();
+                config.confinement.heating_power = 5.0;
+                config.confinement.reference_time = 0.25;
+            }
+        }
+    }
+}
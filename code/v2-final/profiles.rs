@@ -0,0 +1,219 @@
+//! # Initial Profiles
+//!
+//! n_e, T_e, and n_Z at the start of a run (`[profiles]`):
+//!
+//! ```text
+//! n_e = density · (1 − r²)^density_peaking
+//! T_e = temperature · (1 − r²)^temperature_peaking
+//! n_Z = impurity_density · Σ_k impurity_shape[k] · r^(2k)
+//! ```
+//!
+//! The defaults are the v2 parabolas with a hollow impurity profile,
+//! n_Z(0) = 0.2 n_Z(edge).
+//!
+//! `file` replaces them with measured profiles (e.g. Thomson-scattering
+//! fits), interpolated linearly onto the grid and held constant beyond the
+//! ends of the data. Columns: `rho` (normalized radius, ascending) and any
+//! of `n_e` (m⁻³), `t_e` (keV), `n_z` (m⁻³); quantities not in the file
+//! keep their analytic profile. The format follows the extension:
+//!
+//! - `.csv`: header row naming the columns; other columns are ignored.
+//! - `.json`: `{"rho": [...], "n_e": [...], ...}`.
+//! - `.h5` / `.hdf5`: 1-D datasets of the same names (`hdf5` feature).
+
+use crate::units;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::io;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    #[serde(deserialize_with = "units::as_f64::density")]
+    pub density: f64,             // m⁻³, n_e on axis
+    pub density_peaking: f64,
+    #[serde(deserialize_with = "units::as_f64::temperature")]
+    pub temperature: f64,         // keV, T_e on axis
+    pub temperature_peaking: f64,
+    #[serde(deserialize_with = "units::as_f64::density")]
+    pub impurity_density: f64,    // m⁻³
+    pub impurity_shape: Vec<f64>, // Coefficients of r⁰, r², r⁴, …
+    pub impurity_steady_state: bool, // Start n_Z from the steady state of the initial transport, if bounded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Profiles read from `file` by `Config::load`.
+    #[serde(skip)]
+    pub measured: Option<MeasuredProfiles>,
+}
+
+/// Profiles on the radii of a data file.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct MeasuredProfiles {
+    pub rho: Vec<f64>,
+    pub n_e: Option<Vec<f64>>, // m⁻³
+    pub t_e: Option<Vec<f64>>, // keV
+    pub n_z: Option<Vec<f64>>, // m⁻³
+}
+
+impl Default for ProfileConfig {
+    fn default() -> Self {
+        ProfileConfig {
+            density: 8e19,
+            density_peaking: 1.0,
+            temperature: 8.0,
+            temperature_peaking: 1.0,
+            impurity_density: 1e18,
+            impurity_shape: vec![0.2, 0.8],
+            impurity_steady_state: false,
+            file: None,
+            measured: None,
+        }
+    }
+}
+
+impl ProfileConfig {
+    /// (n_e, T_e, n_Z) at normalized radius `r`.
+    pub fn evaluate(&self, r: f64) -> (f64, f64, f64) {
+        let parabola = (1.0 - r * r).max(0.0);
+        let r2 = r * r;
+        let impurity = self.impurity_shape.iter().rev().fold(0.0, |sum, c| sum * r2 + c);
+        let analytic = (
+            self.density * parabola.powf(self.density_peaking),
+            self.temperature * parabola.powf(self.temperature_peaking),
+            self.impurity_density * impurity,
+        );
+        let Some(measured) = &self.measured else {
+            return analytic;
+        };
+        let at = |values: &Option<Vec<f64>>, fallback| match values {
+            Some(values) => interpolate(&measured.rho, values, r),
+            None => fallback,
+        };
+        (
+            at(&measured.n_e, analytic.0),
+            at(&measured.t_e, analytic.1),
+            at(&measured.n_z, analytic.2),
+        )
+    }
+
+    /// Reads `file`, if set.
+    #[cfg(feature = "fs")]
+    pub fn load(&mut self) -> io::Result<()> {
+        let Some(path) = &self.file else {
+            return Ok(());
+        };
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let measured = match extension.as_str() {
+            "csv" => read_csv(path),
+            "json" => read_json(path),
+            "h5" | "hdf5" => read_hdf5(path),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "expected .csv, .json, .h5, or .hdf5")),
+        }
+        .and_then(MeasuredProfiles::validate)
+        .map_err(|e| io::Error::new(e.kind(), format!("profile file {}: {}", path, e)))?;
+        self.measured = Some(measured);
+        Ok(())
+    }
+}
+
+/// Linear interpolation, constant beyond the ends of the data.
+fn interpolate(rho: &[f64], values: &[f64], r: f64) -> f64 {
+    let n = rho.len();
+    if r <= rho[0] {
+        return values[0];
+    }
+    if r >= rho[n - 1] {
+        return values[n - 1];
+    }
+    let j = rho.partition_point(|&x| x <= r);
+    let w = (r - rho[j - 1]) / (rho[j] - rho[j - 1]);
+    values[j - 1] + w * (values[j] - values[j - 1])
+}
+
+#[cfg(feature = "fs")]
+impl MeasuredProfiles {
+    fn validate(self) -> io::Result<Self> {
+        let n = self.rho.len();
+        let columns = [&self.n_e, &self.t_e, &self.n_z];
+        let invalid = |message: &str| Err(io::Error::new(io::ErrorKind::InvalidData, message.to_string()));
+        if n < 2 || !self.rho.windows(2).all(|w| w[1] > w[0]) {
+            return invalid("needs at least two points with ascending rho");
+        }
+        if columns.iter().all(|c| c.is_none()) {
+            return invalid("has none of n_e, t_e, n_z");
+        }
+        if columns.iter().flat_map(|c| c.iter()).any(|c| c.len() != n) {
+            return invalid("columns differ in length from rho");
+        }
+        if columns.iter().flat_map(|c| c.iter()).flatten().any(|v| !v.is_finite() || *v < 0.0) {
+            return invalid("has negative or non-finite values");
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "fs")]
+fn read_csv(path: &str) -> io::Result<MeasuredProfiles> {
+    let text = std::fs::read_to_string(path)?;
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let header: Vec<&str> = match lines.next() {
+        Some((_, line)) => line.split(',').map(str::trim).collect(),
+        None => return Err(io::Error::new(io::ErrorKind::InvalidData, "empty file")),
+    };
+    let column = |name: &str| header.iter().position(|&h| h == name);
+    let rho_column = column("rho")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no `rho` column"))?;
+    let mut columns: [(Option<usize>, Vec<f64>); 3] =
+        [(column("n_e"), Vec::new()), (column("t_e"), Vec::new()), (column("n_z"), Vec::new())];
+    let mut rho = Vec::new();
+    for (line_no, line) in lines {
+        let fields: Vec<f64> = line
+            .split(',')
+            .map(|f| f.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line_no + 1, e)))?;
+        if fields.len() != header.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: expected {} columns", line_no + 1, header.len()),
+            ));
+        }
+        rho.push(fields[rho_column]);
+        for (index, values) in columns.iter_mut() {
+            if let Some(index) = index {
+                values.push(fields[*index]);
+            }
+        }
+    }
+    let [n_e, t_e, n_z] = columns.map(|(index, values)| index.map(|_| values));
+    Ok(MeasuredProfiles { rho, n_e, t_e, n_z })
+}
+
+#[cfg(feature = "fs")]
+fn read_json(path: &str) -> io::Result<MeasuredProfiles> {
+    let file = std::fs::File::open(path)?;
+    serde_json::from_reader(io::BufReader::new(file)).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(feature = "hdf5")]
+fn read_hdf5(path: &str) -> io::Result<MeasuredProfiles> {
+    let file = hdf5::File::open(path).map_err(io::Error::other)?;
+    let read = |name: &str| -> io::Result<Option<Vec<f64>>> {
+        if !file.link_exists(name) {
+            return Ok(None);
+        }
+        let values = file.dataset(name).and_then(|d| d.read_raw::<f64>()).map_err(io::Error::other)?;
+        Ok(Some(values))
+    };
+    let rho = read("rho")?.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no `rho` dataset"))?;
+    Ok(MeasuredProfiles { rho, n_e: read("n_e")?, t_e: read("t_e")?, n_z: read("n_z")? })
+}
+
+#[cfg(all(feature = "fs", not(feature = "hdf5")))]
+fn read_hdf5(_path: &str) -> io::Result<MeasuredProfiles> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "HDF5 profiles need `--features hdf5`"))
+}
@@ -0,0 +1,91 @@
+//! # Pulse Shapes and Windows
+//!
+//! Time course of the turbulence enhancement within a pulse. The shape
+//! value s ∈ [0, 1] scales the enhancement above the unperturbed level,
+//! pulse factor = 1 + (amplitude · waveform − 1) · s, so `square` (s = 1)
+//! is the v2 step. `pulse_waveform` segments combine with any shape.
+//!
+//! Where the pulse acts is a radial window; the plant holds a list of
+//! predefined windows and each pulse command picks one (the first by default).
+
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PulseShape {
+    #[default]
+    Square,
+    /// Trapezoid: linear rise over `rise`, linear fall over the last `fall` seconds.
+    Ramp { rise: f64, fall: f64 },
+    /// Gaussian centred on the middle of the pulse, standard deviation `width` (s).
+    Gaussian { width: f64 },
+    /// Oscillation between 1 − depth and 1 at `frequency`, starting at the minimum.
+    Sinusoidal { frequency: f64, depth: f64 },
+}
+
+/// Radial pulse region center ± width/2 (normalized radius, open interval).
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PulseWindow {
+    pub center: f64,
+    pub width: f64,
+}
+
+impl Default for PulseWindow {
+    /// The v2 edge region, r > 0.7.
+    fn default() -> Self {
+        PulseWindow {
+            center: 0.85,
+            width: 0.3,
+        }
+    }
+}
+
+impl PulseWindow {
+    /// Window from `inner_radius` to the edge.
+    pub fn edge(inner_radius: f64) -> Self {
+        PulseWindow {
+            center: 0.5 * (1.0 + inner_radius),
+            width: 1.0 - inner_radius,
+        }
+    }
+
+    pub fn inner(&self) -> f64 {
+        self.center - 0.5 * self.width
+    }
+
+    pub fn outer(&self) -> f64 {
+        self.center + 0.5 * self.width
+    }
+
+    pub fn contains(&self, r: f64) -> bool {
+        r > self.inner() && r < self.outer()
+    }
+}
+
+impl PulseShape {
+    /// Shape value `elapsed` seconds into a pulse of length `duration`.
+    pub fn value(&self, elapsed: f64, duration: f64) -> f64 {
+        let t = elapsed.clamp(0.0, duration.max(0.0));
+        match *self {
+            PulseShape::Square => 1.0,
+            PulseShape::Ramp { rise, fall } => {
+                let up = if rise > 0.0 { t / rise } else { 1.0 };
+                let down = if fall > 0.0 { (duration - t) / fall } else { 1.0 };
+                up.min(down).clamp(0.0, 1.0)
+            }
+            PulseShape::Gaussian { width } => {
+                if width <= 0.0 {
+                    return 1.0;
+                }
+                let x = (t - 0.5 * duration) / width;
+                (-0.5 * x * x).exp()
+            }
+            PulseShape::Sinusoidal { frequency, depth } => {
+                let depth = depth.clamp(0.0, 1.0);
+                let wave = 0.5 * (1.0 - (2.0 * PI * frequency * t).cos());
+                1.0 - depth + depth * wave
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! # Ramp-Up and Ramp-Down
+//!
+//! Discharge phases instead of a flat top from t = 0. Over `ramp_up` s,
+//! heating, n_e, and the wall source rise linearly from their start
+//! fractions to the flat-top values; from `ramp_down_start` they fall
+//! back linearly over `ramp_down` s to the same fractions:
+//! ```text
+//!          ┌────────────────┐
+//!         ╱   flat top       ╲
+//! start  ╱                    ╲  end
+//!   0  ramp_up      ramp_down_start  + ramp_down
+//! ```
+//! - heating: the background balance that holds T_e, so T_e relaxes
+//!   towards T_edge + heating · (T_e,flat − T_edge) on the χ_e time scale;
+//! - density: n_e and the profile pellets relax to, as a multiplicative
+//!   ramp on top of scenario density ramps;
+//! - source: the constant wall source (sputtering follows T_e by itself).
+//!
+//! With ramps, a run starts from the ramp-start state: T_e, n_e, and n_Z
+//! are the configured profiles scaled to the start fractions. Both ramps
+//! at 0 (the default) keep the v2 flat top.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ramp {
+    pub ramp_up: f64,          // s; 0 = start at the flat top
+    pub ramp_down_start: f64,  // s
+    pub ramp_down: f64,        // s; 0 = no ramp-down
+    pub heating_fraction: f64, // Background heating at the ends, fraction of flat top
+    pub density_fraction: f64, // n_e at the ends, fraction of flat top
+    pub source_fraction: f64,  // Wall source (and initial n_Z) at the ends, fraction of flat top
+}
+
+impl Default for Ramp {
+    fn default() -> Self {
+        Ramp {
+            ramp_up: 0.0,
+            ramp_down_start: 0.0,
+            ramp_down: 0.0,
+            heating_fraction: 0.3,
+            density_fraction: 0.3,
+            source_fraction: 0.1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    RampUp,
+    FlatTop,
+    RampDown,
+    Ended,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::RampUp => "ramp-up",
+            Phase::FlatTop => "flat top",
+            Phase::RampDown => "ramp-down",
+            Phase::Ended => "end of ramp-down",
+        }
+    }
+}
+
+/// Heating, density, and source as fractions of their flat-top values.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Levels {
+    pub heating: f64,
+    pub density: f64,
+    pub source: f64,
+}
+
+impl Ramp {
+    pub fn enabled(&self) -> bool {
+        self.ramp_up > 0.0 || self.ramp_down > 0.0
+    }
+
+    pub fn phase(&self, time: f64) -> Phase {
+        if time < self.ramp_up {
+            Phase::RampUp
+        } else if self.ramp_down <= 0.0 || time < self.ramp_down_start {
+            Phase::FlatTop
+        } else if time < self.ramp_down_start + self.ramp_down {
+            Phase::RampDown
+        } else {
+            Phase::Ended
+        }
+    }
+
+    /// 0 at the ends, 1 on the flat top.
+    pub fn progress(&self, time: f64) -> f64 {
+        match self.phase(time) {
+            Phase::RampUp => time.max(0.0) / self.ramp_up,
+            Phase::FlatTop => 1.0,
+            Phase::RampDown => 1.0 - (time - self.ramp_down_start) / self.ramp_down,
+            Phase::Ended => 0.0,
+        }
+    }
+
+    pub fn levels(&self, time: f64) -> Levels {
+        let p = self.progress(time);
+        // Exactly 1 on the flat top
+        let level = |start: f64| 1.0 - (1.0 - start) * (1.0 - p);
+        Levels {
+            heating: level(self.heating_fraction),
+            density: level(self.density_fraction),
+            source: level(self.source_fraction),
+        }
+    }
+}
+
+/// What a run has applied of its `Ramp` so far.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct RampTracker {
+    applied_density: f64, // n_e fraction currently applied
+    phase: Phase,
+}
+
+impl Default for RampTracker {
+    fn default() -> Self {
+        RampTracker { applied_density: 1.0, phase: Phase::FlatTop }
+    }
+}
+
+impl RampTracker {
+    /// The ramp at `time` as already applied to n_e (run start).
+    pub fn new(ramp: &Ramp, time: f64) -> Self {
+        RampTracker { applied_density: ramp.levels(time).density, phase: ramp.phase(time) }
+    }
+
+    /// Advances to `time`: the factor to scale n_e by since the last step,
+    /// and the phase entered, if it changed.
+    pub fn step(&mut self, ramp: &Ramp, time: f64) -> (f64, Option<Phase>) {
+        if !ramp.enabled() {
+            return (1.0, None);
+        }
+        let density = ramp.levels(time).density;
+        let factor = density / self.applied_density;
+        self.applied_density = density;
+        let phase = ramp.phase(time);
+        let entered = (phase != self.phase).then_some(phase);
+        self.phase = phase;
+        (factor, entered)
+    }
+}
@@ -0,0 +1,249 @@
+//! # Validation Against Reference Traces
+//!
+//! `validate` loads a measured time trace — e.g. core impurity radiation
+//! from a W7-X shot — runs the configured discharge over the same
+//! interval, and scores the simulated channel against it:
+//! ```text
+//! simulated(t) = scale · <channel>(t - time_offset)     on the reference time base
+//! rmse             √⟨(simulated − measured)²⟩
+//! normalized_rmse  rmse / √⟨measured²⟩
+//! bias             ⟨simulated − measured⟩
+//! correlation      Pearson coefficient at zero lag
+//! lag              shift within ±max_lag maximising the cross-correlation;
+//!                  positive when the simulation runs late
+//! ```
+//! `scale` converts the model quantity into the measured one (a radiated
+//! power coefficient for bolometry, 1 for a density). Lags are whole
+//! samples of the reference's median spacing, so the reference should be
+//! close to uniformly sampled. Reference points before t = 0 in simulation
+//! time are dropped.
+//!
+//! The reference is a CSV with a `time` column (s) and the measurement in
+//! `column` (lines starting with `#` are skipped), or JSON
+//! `{"time": [...], "value": [...]}`.
+
+use crate::config::Config;
+use crate::error::{Result, SimError};
+use crate::history::Channel;
+use crate::scan::run_quiet;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReferenceConfig {
+    pub path: String,      // Measured trace (CSV or JSON)
+    pub column: String,    // CSV column holding the measurement
+    pub channel: Channel,  // Simulated quantity compared with it
+    pub scale: f64,        // Measured units per simulated unit
+    pub time_offset: f64,  // s, added to the reference time to get simulation time
+    pub max_lag: f64,      // s, cross-correlation searched over ±max_lag
+    pub output: String,    // Measured and simulated traces on the reference time base (CSV)
+}
+
+impl Default for ReferenceConfig {
+    fn default() -> Self {
+        ReferenceConfig {
+            path: "reference.csv".to_string(),
+            column: "value".to_string(),
+            channel: Channel::CenterImpurity,
+            scale: 1.0,
+            time_offset: 0.0,
+            max_lag: 0.05,
+            output: "validation.csv".to_string(),
+        }
+    }
+}
+
+/// A measured trace in simulation time.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReferenceTrace {
+    pub time: Vec<f64>,
+    pub value: Vec<f64>,
+}
+
+impl ReferenceTrace {
+    /// Shifts to simulation time, drops points before t = 0, and checks
+    /// the trace is usable.
+    pub fn prepare(mut self, time_offset: f64) -> Result<Self> {
+        if self.time.len() != self.value.len() {
+            return Err(SimError::invalid("reference", "time and value differ in length"));
+        }
+        let keep: Vec<bool> = self.time.iter().map(|&t| t + time_offset >= 0.0).collect();
+        let mut flags = keep.iter();
+        self.value.retain(|_| *flags.next().unwrap_or(&false));
+        self.time = self.time.iter().filter(|&&t| t + time_offset >= 0.0).map(|&t| t + time_offset).collect();
+        if self.time.len() < 2 {
+            return Err(SimError::invalid("reference", "fewer than 2 points at t ≥ 0"));
+        }
+        if self.time.windows(2).any(|w| w[1] <= w[0]) {
+            return Err(SimError::invalid("reference", "time is not strictly increasing"));
+        }
+        if self.value.iter().any(|v| !v.is_finite()) {
+            return Err(SimError::invalid("reference", "has non-finite values"));
+        }
+        Ok(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.time.is_empty()
+    }
+
+    /// Median spacing of the samples.
+    pub fn spacing(&self) -> f64 {
+        let mut steps: Vec<f64> = self.time.windows(2).map(|w| w[1] - w[0]).collect();
+        steps.sort_by(f64::total_cmp);
+        steps.get(steps.len() / 2).copied().unwrap_or(0.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Mismatch {
+    pub rmse: f64,
+    pub normalized_rmse: f64,
+    pub bias: f64,
+    pub correlation: f64,
+    pub lag: f64,             // s
+    pub lag_correlation: f64, // Pearson coefficient at `lag`
+}
+
+/// Pearson correlation of two equally long slices; 0 if either is constant.
+fn pearson(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let (mean_a, mean_b) = (a.iter().sum::<f64>() / n, b.iter().sum::<f64>() / n);
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    if var_a <= 0.0 || var_b <= 0.0 {
+        return 0.0;
+    }
+    cov / (var_a * var_b).sqrt()
+}
+
+/// Scores `simulated`, already on the reference time base.
+pub fn mismatch(reference: &ReferenceTrace, simulated: &[f64], max_lag: f64) -> Mismatch {
+    let measured = &reference.value;
+    let n = measured.len() as f64;
+    let residual: Vec<f64> = simulated.iter().zip(measured).map(|(s, m)| s - m).collect();
+    let rmse = (residual.iter().map(|r| r * r).sum::<f64>() / n).sqrt();
+    let rms = (measured.iter().map(|m| m * m).sum::<f64>() / n).sqrt();
+
+    let spacing = reference.spacing();
+    let max_shift = if spacing > 0.0 { (max_lag / spacing).round() as isize } else { 0 };
+    // At least half the samples must overlap for a shift to count
+    let max_shift = max_shift.min(measured.len() as isize / 2);
+    let (mut lag, mut lag_correlation) = (0.0, f64::NEG_INFINITY);
+    for shift in -max_shift..=max_shift {
+        // simulated[i + shift] against measured[i]
+        let (m, s) = if shift >= 0 {
+            (&measured[..measured.len() - shift as usize], &simulated[shift as usize..])
+        } else {
+            (&measured[(-shift) as usize..], &simulated[..simulated.len() - (-shift) as usize])
+        };
+        let r = pearson(m, s);
+        if r > lag_correlation {
+            lag_correlation = r;
+            lag = shift as f64 * spacing;
+        }
+    }
+
+    Mismatch {
+        rmse,
+        normalized_rmse: if rms > 0.0 { rmse / rms } else { f64::INFINITY },
+        bias: residual.iter().sum::<f64>() / n,
+        correlation: pearson(simulated, measured),
+        lag,
+        lag_correlation,
+    }
+}
+
+/// Runs `config` to the end of the reference and returns `scale · channel`
+/// at every reference time, interpolated between solver steps.
+pub fn simulate(config: &Config, reference: &ReferenceTrace, channel: Channel, scale: f64) -> Result<Vec<f64>> {
+    let mut config = config.clone();
+    config.simulation.t_max = reference.time[reference.len() - 1] + config.simulation.dt;
+    let mut simulated = Vec::with_capacity(reference.len());
+    let mut previous: Option<(f64, f64)> = None;
+    run_quiet(&config, |state| {
+        let sample = state.last_sample();
+        let (t1, v1) = (sample.time, sample.get(channel));
+        while simulated.len() < reference.len() && reference.time[simulated.len()] <= t1 {
+            let t = reference.time[simulated.len()];
+            let value = match previous {
+                Some((t0, v0)) if t1 > t0 && t > t0 => v0 + (v1 - v0) * (t - t0) / (t1 - t0),
+                Some((_, v0)) if t < t1 => v0,
+                _ => v1,
+            };
+            simulated.push(scale * value);
+        }
+        previous = Some((t1, v1));
+    })?;
+    // A reference ending inside the last step
+    while simulated.len() < reference.len() {
+        simulated.push(scale * previous.map_or(0.0, |(_, v)| v));
+    }
+    Ok(simulated)
+}
+
+#[cfg(feature = "fs")]
+pub fn load(settings: &ReferenceConfig) -> Result<ReferenceTrace> {
+    let path = &settings.path;
+    let trace = if path.ends_with(".json") {
+        let file = std::fs::File::open(path)?;
+        serde_json::from_reader(std::io::BufReader::new(file))
+            .map_err(|e| SimError::Config(format!("{}: {}", path, e)))?
+    } else {
+        read_csv(path, &settings.column)?
+    };
+    trace.prepare(settings.time_offset)
+}
+
+#[cfg(feature = "fs")]
+fn read_csv(path: &str, column: &str) -> Result<ReferenceTrace> {
+    let text = std::fs::read_to_string(path)?;
+    let invalid = |message: String| SimError::Config(format!("{}: {}", path, message));
+    let mut lines = text.lines().enumerate().filter(|(_, l)| !l.starts_with('#') && !l.trim().is_empty());
+    let header: Vec<&str> = match lines.next() {
+        Some((_, line)) => line.split(',').map(str::trim).collect(),
+        None => return Err(invalid("empty file".to_string())),
+    };
+    let position = |name: &str| header.iter().position(|&h| h == name);
+    let time_column = position("time").ok_or_else(|| invalid("no `time` column".to_string()))?;
+    let value_column = position(column).ok_or_else(|| invalid(format!("no `{}` column", column)))?;
+    let mut trace = ReferenceTrace::default();
+    for (line_no, line) in lines {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let field = |index: usize| -> Result<f64> {
+            let text = fields.get(index).ok_or_else(|| invalid(format!("line {}: too few columns", line_no + 1)))?;
+            text.parse().map_err(|e| invalid(format!("line {}: {}", line_no + 1, e)))
+        };
+        trace.time.push(field(time_column)?);
+        trace.value.push(field(value_column)?);
+    }
+    Ok(trace)
+}
+
+/// `time,measured,simulated,residual`.
+#[cfg(feature = "fs")]
+pub fn write_traces<P: AsRef<std::path::Path>>(
+    path: P,
+    reference: &ReferenceTrace,
+    simulated: &[f64],
+    metadata: &crate::metadata::RunMetadata,
+) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    metadata.write_csv_header(&mut writer)?;
+    writeln!(writer, "time,measured,simulated,residual")?;
+    for ((t, m), s) in reference.time.iter().zip(&reference.value).zip(simulated) {
+        writeln!(writer, "{:.6},{:.6e},{:.6e},{:.6e}", t, m, s, s - m)?;
+    }
+    writer.flush()
+}
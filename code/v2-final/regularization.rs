@@ -0,0 +1,57 @@
+//! # Profile Regularization
+//!
+//! Optional post-step cleanup of grid-scale (odd-even) oscillations in
+//! n_Z(r). Only cells sitting in a zigzag — two consecutive local extrema —
+//! are touched, so smooth peaks such as the on-axis maximum survive.
+
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Regularization {
+    #[default]
+    None,
+    /// Clip zigzag cells into the range spanned by their neighbours.
+    MonotonicLimiter,
+    /// Local second-difference smoothing, 0 < coefficient <= 0.5.
+    Dissipation { coefficient: f64 },
+}
+
+/// Relative jump below which sign changes are treated as round-off.
+const TOLERANCE: f64 = 1e-9;
+
+fn is_zigzag(n: &Array1<f64>, i: usize) -> bool {
+    let left = n[i] - n[i - 1];
+    let right = n[i + 1] - n[i];
+    let next = n[i + 2] - n[i + 1];
+    let scale = TOLERANCE * n[i].abs().max(n[i + 1].abs());
+    right.abs() > scale && left * right < 0.0 && right * next < 0.0
+}
+
+impl Regularization {
+    /// Applies the regularization in place; returns the number of cells changed.
+    pub fn apply(&self, n: &mut Array1<f64>) -> usize {
+        if *self == Regularization::None || n.len() < 4 {
+            return 0;
+        }
+
+        let flagged: Vec<usize> = (1..n.len() - 2).filter(|&i| is_zigzag(n, i)).collect();
+        let original = n.clone();
+        for &i in &flagged {
+            match *self {
+                Regularization::None => {}
+                Regularization::MonotonicLimiter => {
+                    let lo = original[i - 1].min(original[i + 1]);
+                    let hi = original[i - 1].max(original[i + 1]);
+                    n[i] = original[i].clamp(lo, hi);
+                }
+                Regularization::Dissipation { coefficient } => {
+                    let lap = original[i + 1] - 2.0 * original[i] + original[i - 1];
+                    n[i] = (original[i] + coefficient * lap).max(0.0);
+                }
+            }
+        }
+        flagged.len()
+    }
+}
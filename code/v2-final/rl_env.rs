@@ -0,0 +1,95 @@
+//! # Reinforcement-Learning Environment
+//!
+//! Gym-style wrapper around [`Plant`]: the agent acts once per control
+//! interval and receives the diagnostic [`Observation`], a reward, and a
+//! done flag.
+//!
+//! Reward per interval (always ≤ 0):
+//! ```text
+//! r = −Δt · (w_Z · n_Z(0) / 1e18  +  w_act · actuator_output · amplitude)
+//! ```
+//! so the agent trades central impurity against actuator usage. The
+//! impurity term uses the true n_Z(0), not the noisy measurement the
+//! agent sees.
+
+use crate::config::Config;
+use crate::controller::ControlAction;
+use crate::plant::{Observation, Plant};
+use serde::{Deserialize, Serialize};
+
+/// One agent decision.
+#[derive(Clone, Copy, Debug)]
+pub struct RlAction {
+    /// Request a pulse. Subject to the plant's cooldown; `false` does not
+    /// cut short a pulse that is already running.
+    pub pulse: bool,
+    /// D_turb enhancement factor at the edge, clamped to [0, max_amplitude].
+    pub amplitude: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RlConfig {
+    pub control_interval: f64, // s between agent decisions
+    pub episode_length: f64,   // s
+    pub impurity_weight: f64,  // per 1e18 m⁻³ of n_Z(0), per second
+    pub actuator_weight: f64,  // per unit output × amplitude, per second
+    pub max_amplitude: f64,
+}
+
+impl Default for RlConfig {
+    fn default() -> Self {
+        RlConfig {
+            control_interval: 0.01,
+            episode_length: 10.0,
+            impurity_weight: 1.0,
+            actuator_weight: 0.2,
+            max_amplitude: 10.0,
+        }
+    }
+}
+
+pub struct RlEnv {
+    plant_config: Config,
+    config: RlConfig,
+    plant: Plant,
+}
+
+impl RlEnv {
+    pub fn new(plant_config: Config, config: RlConfig) -> Self {
+        let plant = Plant::new(&plant_config);
+        RlEnv { plant_config, config, plant }
+    }
+
+    /// Starts a fresh episode from the initial profiles.
+    pub fn reset(&mut self) -> Observation {
+        self.plant = Plant::new(&self.plant_config);
+        self.plant.observe()
+    }
+
+    /// Applies `action`, advances one control interval, and returns
+    /// `(observation, reward, done)`.
+    pub fn step(&mut self, action: RlAction) -> (Observation, f64, bool) {
+        let amplitude = action.amplitude.clamp(0.0, self.config.max_amplitude);
+        self.plant.set_pulse_amplitude(amplitude);
+        if action.pulse {
+            self.plant.actuate(ControlAction::TriggerPulse);
+        }
+        self.plant.step(self.config.control_interval);
+
+        let state = self.plant.state();
+        let impurity = state.impurity_density[0] / 1e18;
+        let usage = state.actuator.output() * amplitude;
+        let reward = -self.config.control_interval
+            * (self.config.impurity_weight * impurity + self.config.actuator_weight * usage);
+
+        // A diverged solution ends the episode rather than poisoning the return
+        let done = self.plant.time() >= self.config.episode_length - 0.5 * self.config.control_interval
+            || !impurity.is_finite();
+        (self.plant.observe(), reward, done)
+    }
+
+    pub fn plant(&self) -> &Plant {
+        &self.plant
+    }
+}
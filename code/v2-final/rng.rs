@@ -0,0 +1,142 @@
+//! # Random Number Streams
+//!
+//! Every stochastic component draws from its own `StreamRng`, seeded through
+//! the `RngRegistry`, so the same config gives bit-identical runs
+//! regardless of thread count or which other components are active:
+//! ```text
+//! stream        used by                               seed without a master seed
+//! diagnostics   synthetic sensor noise                [diagnostics] seed
+//! ensemble      Monte Carlo parameter draws           [ensemble] seed
+//! sensitivity   Sobol sample matrices                 [sensitivity] seed
+//! optimize      random design and candidates          [optimize] seed
+//! evolve        GA initialization, mutation           [evolve] seed
+//! elms          edge burst times                      [elms] seed
+//! ```
+//! With the top-level `seed` set, every stream seed is derived from it
+//! (SplitMix64 of the master seed mixed with the stream name), and the
+//! per-section seeds are ignored. Without it, each stream keeps its v2
+//! section seed.
+//!
+//! `StreamRng` is ChaCha12 named directly rather than `StdRng`, whose
+//! algorithm `rand` may change between releases (it is the same stream
+//! `StdRng` gives in rand 0.8). With the seed derivation fixed too, a
+//! recorded master seed reproduces a run on any platform, as long as the
+//! physics, the draws each component makes, and the `rand_distr` sampling
+//! code are unchanged.
+//!
+//! Batches (ensemble replicas) give run `i` its own master seed derived
+//! from the batch's, or `diagnostics.seed + i` and `elms.seed + i`
+//! without one.
+
+use crate::config::Config;
+use rand::SeedableRng;
+
+/// Generator behind every stream.
+pub type StreamRng = rand_chacha::ChaCha12Rng;
+
+/// A named random stream.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stream {
+    Diagnostics,
+    Ensemble,
+    Sensitivity,
+    Optimize,
+    Evolve,
+    Elms,
+}
+
+impl Stream {
+    pub const ALL: [Stream; 6] = [
+        Stream::Diagnostics,
+        Stream::Ensemble,
+        Stream::Sensitivity,
+        Stream::Optimize,
+        Stream::Evolve,
+        Stream::Elms,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stream::Diagnostics => "diagnostics",
+            Stream::Ensemble => "ensemble",
+            Stream::Sensitivity => "sensitivity",
+            Stream::Optimize => "optimize",
+            Stream::Evolve => "evolve",
+            Stream::Elms => "elms",
+        }
+    }
+
+    /// The `[section] seed` used without a master seed.
+    fn section_seed(&self, config: &Config) -> u64 {
+        match self {
+            Stream::Diagnostics => config.diagnostics.seed,
+            Stream::Ensemble => config.ensemble.seed,
+            Stream::Sensitivity => config.sensitivity.seed,
+            Stream::Optimize => config.optimize.seed,
+            Stream::Evolve => config.evolve.seed,
+            Stream::Elms => config.elms.seed,
+        }
+    }
+}
+
+/// SplitMix64 finalizer.
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// FNV-1a of `label`, so stream ids do not depend on enum order.
+fn label_hash(label: &str) -> u64 {
+    label.bytes().fold(0xCBF2_9CE4_8422_2325, |h, b| (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01B3))
+}
+
+/// Seed of stream `label` under `master`.
+pub fn derive(master: u64, label: &str) -> u64 {
+    mix(master ^ mix(label_hash(label)))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RngRegistry {
+    master: Option<u64>,
+    seeds: [u64; Stream::ALL.len()],
+}
+
+impl RngRegistry {
+    pub fn new(config: &Config) -> Self {
+        let mut seeds = [0; Stream::ALL.len()];
+        for (seed, stream) in seeds.iter_mut().zip(Stream::ALL) {
+            *seed = match config.seed {
+                Some(master) => derive(master, stream.name()),
+                None => stream.section_seed(config),
+            };
+        }
+        RngRegistry { master: config.seed, seeds }
+    }
+
+    /// The top-level seed, if set.
+    pub fn master(&self) -> Option<u64> {
+        self.master
+    }
+
+    pub fn seed(&self, stream: Stream) -> u64 {
+        self.seeds[stream as usize]
+    }
+
+    /// A fresh generator at the start of `stream`.
+    pub fn rng(&self, stream: Stream) -> StreamRng {
+        StreamRng::seed_from_u64(self.seed(stream))
+    }
+}
+
+/// Seeds `config` as run `index` of a batch started from it.
+pub fn seed_batch_member(config: &mut Config, index: usize) {
+    match config.seed {
+        Some(master) => config.seed = Some(derive(master, &format!("run {}", index))),
+        None => {
+            config.diagnostics.seed = config.diagnostics.seed.wrapping_add(index as u64);
+            config.elms.seed = config.elms.seed.wrapping_add(index as u64);
+        }
+    }
+}
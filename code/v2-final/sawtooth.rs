@@ -0,0 +1,119 @@
+//! # Sawtooth Crashes
+//!
+//! Crude core-relaxation model: at a crash, n_Z and T_e inside
+//! `mixing_radius` are replaced by their volume average over that region
+//! (full Kadomtsev mixing, content conserved), flushing the central
+//! impurity peak outward in one step. T_e then reheats towards its
+//! background profile by χ_e diffusion, like a sawtooth ramp.
+//!
+//! Triggers:
+//! - `off` (v2).
+//! - `periodic`: every `period` s, first at t = `period`.
+//! - `peaking`: when T_e(0) / T_e(mixing_radius) exceeds
+//!   `peaking_threshold`, at most every `min_interval` s. The threshold
+//!   must lie below the background peaking or the core never crashes;
+//!   the recovery time of the ramp then sets the period.
+//!
+//! The controller sees the crash only through the diagnostics: a sudden
+//! drop of the central SXR signal it did not cause.
+
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SawtoothTrigger {
+    #[default]
+    Off,
+    Periodic,
+    Peaking,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sawtooth {
+    pub trigger: SawtoothTrigger,
+    pub period: f64,            // s, periodic trigger
+    pub peaking_threshold: f64, // T_e(0) / T_e(mixing_radius), peaking trigger
+    pub min_interval: f64,      // s between crashes, peaking trigger
+    pub mixing_radius: f64,     // Normalized radius of the flattened core
+    last_crash: Option<f64>,    // s
+}
+
+impl Default for Sawtooth {
+    fn default() -> Self {
+        Sawtooth {
+            trigger: SawtoothTrigger::Off,
+            period: 0.1,
+            peaking_threshold: 1.5,
+            min_interval: 0.02,
+            mixing_radius: 0.3,
+            last_crash: None,
+        }
+    }
+}
+
+/// Outcome of one crash.
+#[derive(Clone, Copy, Debug)]
+pub struct Crash {
+    pub center_impurity_before: f64, // m⁻³
+    pub center_impurity_after: f64,  // m⁻³
+}
+
+impl Sawtooth {
+    pub fn last_crash(&self) -> Option<f64> {
+        self.last_crash
+    }
+
+    /// Index of the last grid point inside the mixing radius.
+    fn mixing_index(&self, radius: &Array1<f64>) -> usize {
+        radius.iter().rposition(|&r| r <= self.mixing_radius).unwrap_or(0)
+    }
+
+    /// Whether a crash is due at `time` with the current T_e.
+    pub fn due(&self, time: f64, radius: &Array1<f64>, electron_temp: &Array1<f64>) -> bool {
+        match self.trigger {
+            SawtoothTrigger::Off => false,
+            SawtoothTrigger::Periodic => {
+                let previous = self.last_crash.unwrap_or(0.0);
+                time - previous >= self.period
+            }
+            SawtoothTrigger::Peaking => {
+                if self.last_crash.is_some_and(|t| time - t < self.min_interval) {
+                    return false;
+                }
+                let edge_of_core = electron_temp[self.mixing_index(radius)];
+                edge_of_core > 0.0 && electron_temp[0] / edge_of_core > self.peaking_threshold
+            }
+        }
+    }
+
+    /// Flattens `electron_temp` and `impurity_density` inside the mixing
+    /// radius, conserving Σ x_i V'_i.
+    pub fn crash(
+        &mut self,
+        time: f64,
+        radius: &Array1<f64>,
+        vprime: &Array1<f64>,
+        electron_temp: &mut Array1<f64>,
+        impurity_density: &mut Array1<f64>,
+    ) -> Crash {
+        self.last_crash = Some(time);
+        let before = impurity_density[0];
+        let end = self.mixing_index(radius);
+        mix(electron_temp, vprime, end);
+        mix(impurity_density, vprime, end);
+        Crash { center_impurity_before: before, center_impurity_after: impurity_density[0] }
+    }
+}
+
+/// Replaces `profile[..=end]` by its V'-weighted mean. The axis, where
+/// V' = 0, takes the mean too.
+fn mix(profile: &mut Array1<f64>, vprime: &Array1<f64>, end: usize) {
+    let volume: f64 = (0..=end).map(|i| vprime[i]).sum();
+    if volume <= 0.0 {
+        return;
+    }
+    let mean = (0..=end).map(|i| profile[i] * vprime[i]).sum::<f64>() / volume;
+    profile.iter_mut().take(end + 1).for_each(|x| *x = mean);
+}
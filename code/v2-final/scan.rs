@@ -0,0 +1,234 @@
+//! # Parameter Scans
+//!
+//! Runs every combination of the `[scan]` parameter lists as an
+//! independent closed-loop simulation (in parallel with the `parallel`
+//! feature) and summarizes each run. An empty list keeps the base value
+//! from the rest of the config. Scans always use the alarm pipeline;
+//! a `[detection.model]` is ignored. A run that fails (invalid point,
+//! numerical blow-up) keeps its error in the result; the others go on.
+
+use crate::config::Config;
+use crate::error::{Result, SimError};
+use crate::simulation::Simulation;
+use crate::state::StellaratorState;
+use crate::summary::{RunSummary, SummaryTracker};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanConfig {
+    pub threshold_alarm: String,  // Alarm whose threshold `thresholds` replaces
+    pub thresholds: Vec<f64>,
+    pub pulse_duration: Vec<f64>, // s
+    pub cooldown: Vec<f64>,       // s
+    pub v_neo: Vec<f64>,          // m/s
+    pub output: String,           // Summary table (CSV)
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        ScanConfig {
+            threshold_alarm: "central_level".to_string(),
+            thresholds: Vec::new(),
+            pulse_duration: Vec::new(),
+            cooldown: Vec::new(),
+            v_neo: Vec::new(),
+            output: "scan_summary.csv".to_string(),
+        }
+    }
+}
+
+/// A scalar input that scans, sensitivity analyses, and optimizers vary.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Parameter {
+    DNeo,
+    DTurbBase,
+    VNeo,
+    ImpuritySource,
+    PulseDuration,
+    Cooldown,
+    PulseAmplitude,
+    /// Impurity charge Z
+    ImpurityCharge,
+    /// ECRH power during pulses (MW)
+    EcrhPower,
+    /// Threshold of the alarm named by `scan.threshold_alarm`
+    Threshold,
+}
+
+impl Parameter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Parameter::DNeo => "d_neo",
+            Parameter::DTurbBase => "d_turb_base",
+            Parameter::VNeo => "v_neo",
+            Parameter::ImpuritySource => "impurity_source",
+            Parameter::PulseDuration => "pulse_duration",
+            Parameter::Cooldown => "cooldown",
+            Parameter::PulseAmplitude => "pulse_amplitude",
+            Parameter::ImpurityCharge => "impurity_charge",
+            Parameter::EcrhPower => "ecrh_power",
+            Parameter::Threshold => "threshold",
+        }
+    }
+
+    pub fn apply(&self, config: &mut Config, value: f64) {
+        match self {
+            Parameter::DNeo => config.plasma.d_neo = value,
+            Parameter::DTurbBase => config.plasma.d_turb_base = value,
+            Parameter::VNeo => config.plasma.v_neo = value,
+            Parameter::ImpuritySource => config.plasma.impurity_source = value,
+            Parameter::PulseDuration => config.plasma.pulse_duration = value,
+            Parameter::Cooldown => config.plasma.cooldown = value,
+            Parameter::PulseAmplitude => config.plasma.pulse_amplitude = value,
+            Parameter::ImpurityCharge => config.plasma.impurity_charge = value,
+            Parameter::EcrhPower => config.ecrh.max_power = value,
+            Parameter::Threshold => {
+                let name = config.scan.threshold_alarm.clone();
+                for alarm in config.detection.alarms.iter_mut().filter(|a| a.name == name) {
+                    alarm.threshold = value;
+                }
+            }
+        }
+    }
+}
+
+/// Inclusive range a parameter is varied over.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ParameterRange {
+    pub parameter: Parameter,
+    pub low: f64,
+    pub high: f64,
+}
+
+/// Parameter values of one run.
+#[derive(Clone, Copy, Debug)]
+pub struct ScanPoint {
+    pub threshold: f64,
+    pub pulse_duration: f64,
+    pub cooldown: f64,
+    pub v_neo: f64,
+}
+
+#[derive(Debug)]
+pub struct ScanResult {
+    pub point: ScanPoint,
+    pub summary: Result<RunSummary>,
+}
+
+impl ScanConfig {
+    /// Cartesian product of the parameter lists.
+    pub fn points(&self, base: &Config) -> Result<Vec<ScanPoint>> {
+        let base_threshold = base
+            .detection
+            .alarms
+            .iter()
+            .find(|a| a.name == self.threshold_alarm)
+            .map(|a| a.threshold);
+        let thresholds = match (base_threshold, self.thresholds.is_empty()) {
+            (_, false) if base_threshold.is_none() => {
+                return Err(SimError::invalid(
+                    "scan.thresholds",
+                    format!("given but no alarm named {:?}", self.threshold_alarm),
+                ))
+            }
+            (_, false) => self.thresholds.clone(),
+            (Some(t), true) => vec![t],
+            (None, true) => vec![f64::NAN],
+        };
+        let or_base = |values: &[f64], base: f64| {
+            if values.is_empty() { vec![base] } else { values.to_vec() }
+        };
+        let pulse_durations = or_base(&self.pulse_duration, base.plasma.pulse_duration);
+        let cooldowns = or_base(&self.cooldown, base.plasma.cooldown);
+        let v_neos = or_base(&self.v_neo, base.plasma.v_neo);
+
+        let mut points = Vec::new();
+        for &threshold in &thresholds {
+            for &pulse_duration in &pulse_durations {
+                for &cooldown in &cooldowns {
+                    for &v_neo in &v_neos {
+                        points.push(ScanPoint { threshold, pulse_duration, cooldown, v_neo });
+                    }
+                }
+            }
+        }
+        Ok(points)
+    }
+}
+
+/// Runs the whole scan described by `base.scan`, results in point order.
+pub fn run_scan(base: &Config) -> Result<Vec<ScanResult>> {
+    let points = base.scan.points(base)?;
+    #[cfg(feature = "parallel")]
+    let results = points.par_iter().map(|p| run_point(base, p)).collect();
+    #[cfg(not(feature = "parallel"))]
+    let results = points.iter().map(|p| run_point(base, p)).collect();
+    Ok(results)
+}
+
+pub fn run_point(base: &Config, point: &ScanPoint) -> ScanResult {
+    let mut config = base.clone();
+    Parameter::PulseDuration.apply(&mut config, point.pulse_duration);
+    Parameter::Cooldown.apply(&mut config, point.cooldown);
+    Parameter::VNeo.apply(&mut config, point.v_neo);
+    Parameter::Threshold.apply(&mut config, point.threshold);
+
+    ScanResult {
+        point: *point,
+        summary: run_quiet(&config, |_| {}),
+    }
+}
+
+/// Validates `config` and runs it to `t_max` without console output or
+/// in-memory history, calling `observe` after every step.
+pub fn run_quiet(config: &Config, observe: impl FnMut(&StellaratorState)) -> Result<RunSummary> {
+    run_quiet_with(Simulation::try_from_config(config)?, config, observe)
+}
+
+/// `run_quiet` for a simulation prepared by the caller, e.g. with its own controller.
+pub fn run_quiet_with(
+    mut sim: Simulation,
+    config: &Config,
+    mut observe: impl FnMut(&StellaratorState),
+) -> Result<RunSummary> {
+    sim.state.verbose = false;
+    sim.state.history.recording = false;
+
+    let mut tracker = SummaryTracker::new(&sim.state, config.output.critical_density);
+    let dt = sim.dt;
+    sim.run(config.simulation.t_max, |state| {
+        observe(state);
+        tracker.observe(state, dt);
+    })?;
+    Ok(tracker.finish(&sim.state, sim.dt))
+}
+
+#[cfg(feature = "fs")]
+pub fn write_summary<P: AsRef<std::path::Path>>(
+    path: P,
+    results: &[ScanResult],
+    metadata: &crate::metadata::RunMetadata,
+) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    metadata.write_csv_header(&mut writer)?;
+    writeln!(writer, "run,threshold,pulse_duration,cooldown,v_neo,final_center_impurity,peak_center_impurity,pulses,duty_cycle,error")?;
+    for (i, r) in results.iter().enumerate() {
+        write!(writer, "{},{:.4e},{},{},{},", i, r.point.threshold, r.point.pulse_duration, r.point.cooldown, r.point.v_neo)?;
+        match &r.summary {
+            Ok(s) => writeln!(
+                writer,
+                "{:.6e},{:.6e},{},{:.4},",
+                s.final_center_impurity, s.peak_center_impurity, s.pulses, s.duty_cycle
+            )?,
+            // Quoted: messages contain commas
+            Err(e) => writeln!(writer, ",,,,\"{}\"", e.to_string().replace('"', "'"))?,
+        }
+    }
+    writer.flush()
+}
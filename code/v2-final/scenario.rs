@@ -0,0 +1,185 @@
+//! # Discharge Scenarios
+//!
+//! Timed changes to the plant that reproduce a discharge timeline instead
+//! of steady conditions. Events come from `[[scenario.events]]` in the
+//! config and from the file `scenario.file`, which holds the same tables
+//! as `[[events]]`:
+//!
+//! ```toml
+//! [[events]]
+//! time = 3.0
+//! type = "source_strength"
+//! value = 5e17
+//!
+//! [[events]]
+//! time = 5.0
+//! type = "density_ramp"
+//! factor = 1.5
+//! duration = 1.0
+//! ```
+//!
+//! The `ScenarioPlayer` applies every event whose time has been reached
+//! before the simulation step, in time order, and records it as a
+//! `scenario` event. Forced pulses bypass the controller and its budget.
+
+use crate::controller::PulseCommand;
+use crate::events::Event;
+use crate::state::StellaratorState;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::io;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScenarioAction {
+    /// Sets the constant wall source (m⁻³/s); no effect with sputtering.
+    SourceStrength { value: f64 },
+    /// Sets the base heating power of the confinement proxy (MW).
+    Heating { power: f64 },
+    /// Starts a pulse now, ignoring the cooldown; none if one is running.
+    Pulse {
+        #[serde(default)]
+        window: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        amplitude: Option<f64>,
+    },
+    /// Scales n_e linearly in time to `factor` × its current value over
+    /// `duration` s (0 = step).
+    DensityRamp { factor: f64, duration: f64 },
+}
+
+impl ScenarioAction {
+    pub fn describe(&self) -> String {
+        match self {
+            ScenarioAction::SourceStrength { value } => format!("impurity source {:.2e} m⁻³/s", value),
+            ScenarioAction::Heating { power } => format!("heating power {:.1} MW", power),
+            ScenarioAction::Pulse { window, amplitude } => match amplitude {
+                Some(amplitude) => format!("forced pulse in window {} at {:.1}×", window, amplitude),
+                None => format!("forced pulse in window {}", window),
+            },
+            ScenarioAction::DensityRamp { factor, duration } => {
+                format!("n_e ramp ×{:.2} over {:.2}s", factor, duration)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ScenarioEvent {
+    pub time: f64, // s
+    #[serde(flatten)]
+    pub action: ScenarioAction,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scenario {
+    pub file: String, // Extra events (`[[events]]` tables); empty = none
+    pub events: Vec<ScenarioEvent>,
+}
+
+/// Layout of a scenario file.
+#[cfg(feature = "fs")]
+#[derive(Deserialize)]
+struct ScenarioFile {
+    #[serde(default)]
+    events: Vec<ScenarioEvent>,
+}
+
+impl Scenario {
+    /// Appends the events of `file`.
+    #[cfg(feature = "fs")]
+    pub fn load(&mut self) -> io::Result<()> {
+        if self.file.is_empty() {
+            return Ok(());
+        }
+        let text = std::fs::read_to_string(&self.file)
+            .map_err(|e| io::Error::new(e.kind(), format!("scenario {}: {}", self.file, e)))?;
+        let parsed: ScenarioFile = toml::from_str(&text).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("scenario {}: {}", self.file, e))
+        })?;
+        self.events.extend(parsed.events);
+        Ok(())
+    }
+}
+
+/// A density ramp in progress.
+#[derive(Clone, Copy, Debug)]
+struct Ramp {
+    start: f64,
+    duration: f64,
+    factor: f64,
+    applied: f64, // Factor reached so far
+}
+
+/// Steps through a scenario during a run.
+#[derive(Clone, Debug, Default)]
+pub struct ScenarioPlayer {
+    events: Vec<ScenarioEvent>, // Sorted by time
+    next: usize,
+    ramp: Option<Ramp>,
+}
+
+impl ScenarioPlayer {
+    /// Events before `start_time` (e.g. when resuming a checkpoint) are
+    /// skipped; a ramp running at that time is not resumed.
+    pub fn new(scenario: &Scenario, start_time: f64) -> Self {
+        let mut events = scenario.events.clone();
+        events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        let next = events.iter().take_while(|e| e.time < start_time).count();
+        ScenarioPlayer { events, next, ramp: None }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next >= self.events.len() && self.ramp.is_none()
+    }
+
+    /// Applies the events due at `state.time` and advances a running ramp.
+    pub fn advance(&mut self, state: &mut StellaratorState) {
+        while let Some(event) = self.events.get(self.next).filter(|e| e.time <= state.time) {
+            let action = event.action;
+            self.next += 1;
+            state.record_event(Event::Scenario { action });
+            self.apply(action, state);
+        }
+        self.step_ramp(state);
+    }
+
+    fn apply(&mut self, action: ScenarioAction, state: &mut StellaratorState) {
+        match action {
+            ScenarioAction::SourceStrength { value } => state.impurity_source = value.max(0.0),
+            ScenarioAction::Heating { power } => state.confinement.heating_power = power.max(0.0),
+            ScenarioAction::Pulse { window, amplitude } => {
+                state.force_pulse(PulseCommand { window, amplitude });
+            }
+            ScenarioAction::DensityRamp { factor, duration } => {
+                // A new ramp replaces the running one from the density reached
+                self.ramp = Some(Ramp {
+                    start: state.time,
+                    duration,
+                    factor: factor.max(0.0),
+                    applied: 1.0,
+                });
+            }
+        }
+    }
+
+    fn step_ramp(&mut self, state: &mut StellaratorState) {
+        let Some(ramp) = &mut self.ramp else {
+            return;
+        };
+        let progress = if ramp.duration > 0.0 {
+            ((state.time - ramp.start) / ramp.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let target = 1.0 + (ramp.factor - 1.0) * progress;
+        if ramp.applied > 0.0 {
+            state.scale_electron_density(target / ramp.applied);
+        }
+        ramp.applied = target;
+        if progress >= 1.0 {
+            self.ramp = None;
+        }
+    }
+}
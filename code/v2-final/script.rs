@@ -0,0 +1,248 @@
+//! # Scripted Control Laws (Rhai)
+//!
+//! A [Rhai](https://rhai.rs) script in place of the threshold controller
+//! (`[controller.script]`), so a control law can be changed without
+//! recompiling. The script defines
+//!
+//! ```text
+//! fn decide(obs) {
+//!     if obs.central_sxr > 8e17 && !obs.pulsing { #{ window: 0, amplitude: 4.0 } } else { false }
+//! }
+//! ```
+//!
+//! `obs` is a map with `time`, `central_sxr`, `edge_density`, `turbulence`
+//! (the `Measurement`), `pulsing` and `last_pulse_end` (`()` before the
+//! first pulse). The result is `false`, `()` or `"hold"` to hold; `true`
+//! or `"pulse"` for a pulse with the plant's defaults; or a map with
+//! optional `window` and `amplitude` for a `PulseCommand`. `this` is a map
+//! kept between calls (and in checkpoints) for the script's own state.
+//! Top-level statements run once per (re)load; the constants they define
+//! stay visible to every call as `global::NAME`.
+//!
+//! The engine is sandboxed: `import` resolves no modules, so a script
+//! cannot read files, Rhai has no network or process access, `eval` is
+//! disabled, and the operations per call and the sizes of strings, arrays
+//! and maps are capped, so a runaway script fails the call instead of
+//! hanging the run. A failing call holds and is reported once per failure
+//! streak. With `reload`, the file is recompiled when it changes on disk
+//! (checked at most once per wall-clock second); a script that no longer
+//! compiles is reported and the previous one stays in use.
+
+use crate::controller::{ControlAction, Controller, PulseCommand, ScriptConfig};
+use crate::diagnostics::Measurement;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+use std::io;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Wall-clock time between checks of the script's modification time.
+const RELOAD_POLL: Duration = Duration::from_secs(1);
+
+pub struct ScriptController {
+    engine: Engine,
+    ast: AST,
+    calls: AST,                    // Functions of `ast` behind its constants, see `initialize`
+    scope: Scope<'static>,         // Left by the top-level statements
+    config: ScriptConfig,
+    modified: Option<SystemTime>,
+    checked: Instant,              // Last look at `modified`
+    memory: Dynamic,               // `this` of the script
+    last_pulse: Option<(f64, f64)>, // (start, end)
+    failure_reported: bool,
+}
+
+impl ScriptController {
+    pub fn load(config: &ScriptConfig) -> io::Result<Self> {
+        let engine = sandbox(config.max_operations);
+        let ast = compile(&engine, &config.path)?;
+        let mut controller = ScriptController {
+            engine,
+            calls: AST::empty(),
+            ast,
+            scope: Scope::new(),
+            config: config.clone(),
+            modified: modified(&config.path),
+            checked: Instant::now(),
+            memory: Dynamic::from_map(Map::new()),
+            last_pulse: None,
+            failure_reported: false,
+        };
+        controller.initialize()?;
+        Ok(controller)
+    }
+
+    /// Runs the top-level statements of the current script and keeps
+    /// the scope they leave for the calls. Rhai fills `global::` only while
+    /// top-level statements run, so each call re-declares the constants
+    /// from that scope (`const NAME = NAME;`) instead of rerunning them.
+    fn initialize(&mut self) -> io::Result<()> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+        let mut scope = Scope::new();
+        self.engine.run_ast_with_scope(&mut scope, &self.ast).map_err(|e| invalid(e.to_string()))?;
+        let constants: String = scope
+            .iter_raw()
+            .filter(|&(_, constant, _)| constant)
+            .map(|(name, ..)| format!("const {0} = {0};\n", name))
+            .collect();
+        let prelude = self.engine.compile(constants).map_err(|e| invalid(e.to_string()))?;
+        self.calls = prelude.merge(&self.ast.clone_functions_only());
+        self.scope = scope;
+        Ok(())
+    }
+
+    /// Recompiles the script if its file changed since the last load.
+    fn reload_if_changed(&mut self) {
+        if self.checked.elapsed() < RELOAD_POLL {
+            return;
+        }
+        self.checked = Instant::now();
+        let modified = modified(&self.config.path);
+        if modified.is_none() || modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+        match compile(&self.engine, &self.config.path) {
+            Ok(ast) => {
+                let previous = std::mem::replace(&mut self.ast, ast);
+                match self.initialize() {
+                    Ok(()) => tracing::info!("🔄 Reloaded control script {}", self.config.path),
+                    Err(e) => {
+                        self.ast = previous;
+                        tracing::error!("❌ {}: {}, keeping the previous script", self.config.path, e);
+                    }
+                }
+            }
+            Err(e) => tracing::error!("❌ {}, keeping the previous script", e),
+        }
+    }
+
+    fn observation(&self, measurement: &Measurement) -> Map {
+        let t = measurement.time;
+        let mut obs = Map::new();
+        obs.insert("time".into(), Dynamic::from_float(t));
+        obs.insert("central_sxr".into(), Dynamic::from_float(measurement.central_sxr));
+        obs.insert("edge_density".into(), Dynamic::from_float(measurement.edge_density));
+        obs.insert("turbulence".into(), Dynamic::from_float(measurement.turbulence));
+        let pulsing = self.last_pulse.is_some_and(|(start, end)| t >= start && t < end);
+        obs.insert("pulsing".into(), Dynamic::from_bool(pulsing));
+        let last_end = self.last_pulse.map_or(Dynamic::UNIT, |(_, end)| Dynamic::from_float(end));
+        obs.insert("last_pulse_end".into(), last_end);
+        obs
+    }
+
+    fn call(&mut self, measurement: &Measurement) -> Result<ControlAction, String> {
+        let obs = self.observation(measurement);
+        let options = CallFnOptions::new().bind_this_ptr(&mut self.memory);
+        let result: Dynamic = self
+            .engine
+            .call_fn_with_options(options, &mut self.scope, &self.calls, "decide", (obs,))
+            .map_err(|e| e.to_string())?;
+        action(result)
+    }
+}
+
+impl Controller for ScriptController {
+    fn decide(&mut self, measurement: &Measurement) -> ControlAction {
+        if self.config.reload {
+            self.reload_if_changed();
+        }
+        match self.call(measurement) {
+            Ok(action) => {
+                self.failure_reported = false;
+                action
+            }
+            Err(e) => {
+                if !self.failure_reported {
+                    self.failure_reported = true;
+                    tracing::error!("❌ Control script failed at t={:.4}s, holding: {}", measurement.time, e);
+                }
+                ControlAction::Hold
+            }
+        }
+    }
+
+    fn pulse_started(&mut self, time: f64, duration: f64) {
+        self.last_pulse = Some((time, time + duration));
+    }
+
+    /// `this` and the last pulse; the script itself is reloaded from its
+    /// file, and its top-level statements run again.
+    fn save_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value((&self.memory, self.last_pulse)).ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) -> serde_json::Result<()> {
+        (self.memory, self.last_pulse) = serde_json::from_value(state)?;
+        Ok(())
+    }
+}
+
+/// Engine without `eval` or `import` and with resource limits; `print`
+/// and `debug` go to the log.
+fn sandbox(max_operations: u64) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(max_operations);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(10_000);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(1_000);
+    engine.disable_symbol("eval");
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.on_print(|text| tracing::info!("📜 {}", text));
+    engine.on_debug(|text, _, position| tracing::debug!("📜 {} ({})", text, position));
+    engine
+}
+
+fn compile(engine: &Engine, path: &str) -> io::Result<AST> {
+    let ast = engine
+        .compile_file(path.into())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, e)))?;
+    if !ast.iter_functions().any(|f| f.name == "decide" && f.params.len() == 1) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}: no fn decide(obs)", path)));
+    }
+    Ok(ast)
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reads a `decide` result.
+fn action(result: Dynamic) -> Result<ControlAction, String> {
+    if result.is_unit() {
+        return Ok(ControlAction::Hold);
+    }
+    if let Ok(pulse) = result.as_bool() {
+        return Ok(if pulse { ControlAction::TriggerPulse } else { ControlAction::Hold });
+    }
+    if result.is_string() {
+        let text = result.into_string().unwrap_or_default();
+        return match text.as_str() {
+            "hold" => Ok(ControlAction::Hold),
+            "pulse" => Ok(ControlAction::TriggerPulse),
+            other => Err(format!("decide returned {:?}, expected \"hold\" or \"pulse\"", other)),
+        };
+    }
+    let type_name = result.type_name();
+    let Some(map) = result.try_cast::<Map>() else {
+        return Err(format!("decide returned a {}, expected bool, string or map", type_name));
+    };
+    let number = |key: &str| -> Result<Option<f64>, String> {
+        match map.get(key) {
+            None => Ok(None),
+            Some(value) if value.is_unit() => Ok(None),
+            Some(value) => value
+                .as_float()
+                .or_else(|_| value.as_int().map(|i| i as f64))
+                .map(Some)
+                .map_err(|_| format!("{} must be a number", key)),
+        }
+    };
+    let window = match number("window")? {
+        Some(w) if w >= 0.0 && w.fract() == 0.0 => w as usize,
+        Some(w) => return Err(format!("window {} is not an index", w)),
+        None => 0,
+    };
+    Ok(ControlAction::Pulse(PulseCommand { window, amplitude: number("amplitude")? }))
+}
@@ -0,0 +1,169 @@
+//! # Serve Mode
+//!
+//! Runs the plasma as a ZeroMQ endpoint so real control software can be
+//! tested in the loop. The built-in controller is bypassed: pulses only
+//! start when a client asks for them.
+//!
+//! PUB socket, two-frame messages `[topic, JSON]`:
+//! ```text
+//! measurement  {"time", "central_sxr", "edge_density", "turbulence"}  every diagnostic sample
+//! trace        {"time", "center_impurity", "edge_impurity", "turbulence",
+//!               "center_zeff", "center_dilution", "stored_energy"}  history cadence
+//! profiles     {"time", "radius", "impurity_density", "electron_density",
+//!               "electron_temp", "turbulence", "zeff", "dilution"}  output.profile_cadence
+//! ```
+//!
+//! REP socket, one JSON request → one JSON reply:
+//! ```text
+//! {"command": "trigger_pulse"}                 → {"ok": true, "pulse_active": true}
+//! {"command": "trigger_pulse", "window": 1}    → pulse in plasma.pulse_windows[1]
+//! {"command": "trigger_pulse", "amplitude": 8} → this pulse at 8× (clamped to 1–10×)
+//! {"command": "set_amplitude", "value": 3.0}   → {"ok": true, "pulse_active": false}
+//! {"command": "fire_pellet"}                   → {"ok": true, "pulse_active": false}
+//! ```
+
+use crate::config::ServeConfig;
+use crate::controller::{ControlAction, PulseCommand};
+use crate::simulation::Simulation;
+use crate::state::{ConfinementMode, StellaratorState};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    /// Request a pulse; the plant still enforces its cooldown.
+    TriggerPulse {
+        #[serde(default)]
+        window: usize,
+        #[serde(default)]
+        amplitude: Option<f64>,
+    },
+    /// D_turb enhancement factor applied at the edge during pulses.
+    SetAmplitude { value: f64 },
+    /// Inject a pellet; rejected while the injector is reloading.
+    FirePellet,
+}
+
+#[derive(Serialize)]
+struct ProfileMessage {
+    time: f64,
+    radius: Vec<f64>,
+    impurity_density: Vec<f64>,
+    electron_density: Vec<f64>,
+    electron_temp: Vec<f64>,
+    turbulence: Vec<f64>,
+    zeff: Vec<f64>,
+    dilution: Vec<f64>,
+}
+
+pub struct Server {
+    _context: zmq::Context,
+    publisher: zmq::Socket,
+    commands: zmq::Socket,
+    profile_cadence: f64, // s between profile messages
+    next_profile: f64,
+}
+
+impl Server {
+    pub fn bind(config: &ServeConfig, profile_cadence: f64) -> io::Result<Self> {
+        let context = zmq::Context::new();
+        let publisher = context.socket(zmq::PUB).map_err(io::Error::other)?;
+        publisher.bind(&config.publish).map_err(io::Error::other)?;
+        let commands = context.socket(zmq::REP).map_err(io::Error::other)?;
+        commands.bind(&config.command).map_err(io::Error::other)?;
+        Ok(Server {
+            _context: context,
+            publisher,
+            commands,
+            profile_cadence,
+            next_profile: 0.0,
+        })
+    }
+
+    /// One serve-mode step: apply pending client commands, advance the
+    /// plant, publish what is due. Returns `TriggerPulse` if a client
+    /// requested one this step.
+    pub fn step(&mut self, sim: &mut Simulation) -> io::Result<Option<ControlAction>> {
+        let action = self.handle_commands(&mut sim.state)?;
+
+        if let Some(measurement) = sim.step_open_loop() {
+            self.publish("measurement", &measurement)?;
+        }
+        if let Some(sample) = sim.state.recorded_sample() {
+            self.publish("trace", sample)?;
+        }
+        if sim.state.time >= self.next_profile {
+            self.next_profile = sim.state.time + self.profile_cadence;
+            self.publish("profiles", &profile_message(&sim.state))?;
+        }
+        Ok(action)
+    }
+
+    /// Drains the command socket without blocking.
+    fn handle_commands(&mut self, state: &mut StellaratorState) -> io::Result<Option<ControlAction>> {
+        let mut action = None;
+        loop {
+            let request = match self.commands.recv_bytes(zmq::DONTWAIT) {
+                Ok(bytes) => bytes,
+                Err(zmq::Error::EAGAIN) => return Ok(action),
+                Err(e) => return Err(io::Error::other(e)),
+            };
+            let reply = match serde_json::from_slice::<Command>(&request) {
+                Ok(Command::TriggerPulse { window, amplitude }) => {
+                    let requested = if window == 0 && amplitude.is_none() {
+                        ControlAction::TriggerPulse
+                    } else {
+                        ControlAction::Pulse(PulseCommand { window, amplitude })
+                    };
+                    state.apply_action(requested);
+                    action = Some(requested);
+                    ok_reply(state)
+                }
+                Ok(Command::SetAmplitude { value }) if value.is_finite() && value >= 0.0 => {
+                    tracing::info!("🎛️ t={:.3}s: Pulse amplitude {} → {}", state.time, state.pulse_amplitude, value);
+                    state.pulse_amplitude = value;
+                    ok_reply(state)
+                }
+                Ok(Command::SetAmplitude { value }) => {
+                    serde_json::json!({ "ok": false, "error": format!("invalid amplitude {}", value) })
+                }
+                Ok(Command::FirePellet) if state.fire_pellet() => ok_reply(state),
+                Ok(Command::FirePellet) => {
+                    serde_json::json!({ "ok": false, "error": "pellet injector reloading" })
+                }
+                Err(e) => serde_json::json!({ "ok": false, "error": e.to_string() }),
+            };
+            self.commands
+                .send(reply.to_string().as_bytes(), 0)
+                .map_err(io::Error::other)?;
+        }
+    }
+
+    fn publish<T: Serialize>(&self, topic: &str, payload: &T) -> io::Result<()> {
+        let body = serde_json::to_vec(payload).map_err(io::Error::other)?;
+        self.publisher
+            .send_multipart([topic.as_bytes(), body.as_slice()], 0)
+            .map_err(io::Error::other)
+    }
+}
+
+fn ok_reply(state: &StellaratorState) -> serde_json::Value {
+    serde_json::json!({
+        "ok": true,
+        "pulse_active": state.confinement_mode == ConfinementMode::TurbulencePulse,
+    })
+}
+
+fn profile_message(state: &StellaratorState) -> ProfileMessage {
+    ProfileMessage {
+        time: state.time,
+        radius: state.radius_grid.to_vec(),
+        impurity_density: state.impurity_density.to_vec(),
+        electron_density: state.electron_density.to_vec(),
+        electron_temp: state.electron_temp.to_vec(),
+        turbulence: state.turbulence_profile().to_vec(),
+        zeff: state.zeff_profile().to_vec(),
+        dilution: state.dilution_profile().to_vec(),
+    }
+}
@@ -0,0 +1,317 @@
+//! # Closed-Loop Simulation
+//!
+//! Plant + diagnostic + DAQ + controller wired together. `step()` is one
+//! solver step: scenario → sample → acquire → regime → decide → actuate →
+//! integrate; the controller runs only in steps the DAQ delivers a
+//! measurement (see `daq`).
+//! Output handling (sinks, logs, snapshots) is left to the caller. `run`
+//! steps to an end time and stops with `SimError::NumericalInstability`
+//! once a profile goes non-finite. `SimulationBuilder` assembles one from
+//! the defaults and checks it before the first step.
+//!
+//! A checkpoint of a `Simulation` holds the plant state and the loop
+//! around it: the diagnostic's sample clock and noise stream, the DAQ
+//! queue, and the controller's detector, escalation, and budget used. A
+//! resumed run continues the interrupted one step for step. Script
+//! controllers keep their `this` map and last pulse; plugin controllers
+//! start afresh.
+
+use crate::config::Config;
+use crate::controller::{ControlAction, Controller};
+use crate::crash::FiniteGuard;
+use crate::daq::Daq;
+use crate::detection::DetectionPipeline;
+use crate::profiles::ProfileConfig;
+use crate::units::{Diffusivity, Velocity};
+use crate::diagnostics::{Diagnostic, Measurement, SyntheticDiagnostic};
+use crate::error::{Result, SimError};
+use crate::rng::{RngRegistry, Stream};
+use crate::scenario::ScenarioPlayer;
+use crate::state::{ConfinementMode, StellaratorState};
+#[cfg(feature = "fs")]
+use crate::state::{read_checkpoint, write_checkpoint};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+pub struct Simulation {
+    pub state: StellaratorState,
+    pub diagnostic: Box<dyn Diagnostic>,
+    pub daq: Daq,
+    pub controller: Box<dyn Controller>,
+    pub scenario: ScenarioPlayer,
+    pub dt: f64,
+    pub guard: FiniteGuard,
+    last_measurement: Option<Measurement>,
+}
+
+/// The loop around the plant, saved beside it in a checkpoint.
+#[derive(Serialize, Deserialize)]
+pub struct LoopState {
+    diagnostic: Option<serde_json::Value>,
+    daq: Daq,
+    controller: Option<serde_json::Value>,
+    last_measurement: Option<Measurement>,
+}
+
+impl Simulation {
+    pub fn from_config(config: &Config) -> Self {
+        Self::with_state(StellaratorState::from_config(config), config)
+    }
+
+    /// `from_config` after `Config::validate`, with the `[controller.script]`
+    /// or `[controller.plugin]` controller loaded (`from_config` leaves the
+    /// built-in one).
+    pub fn try_from_config(config: &Config) -> Result<Self> {
+        config.validate()?;
+        let mut sim = Self::from_config(config);
+        if let Some(controller) = config.controller.load(config.plasma.pulse_duration)? {
+            sim.controller = controller;
+        }
+        Ok(sim)
+    }
+
+    /// Wraps an existing state (e.g. loaded from a checkpoint).
+    pub fn with_state(mut state: StellaratorState, config: &Config) -> Self {
+        state.history.cadence = config.diagnostics.cadence;
+        let scenario = ScenarioPlayer::new(&config.scenario, state.time);
+        let detector = Box::new(DetectionPipeline::new(&config.detection));
+        let controller = config.controller.build(detector, state.pulse_duration);
+        Simulation {
+            state,
+            diagnostic: Box::new(SyntheticDiagnostic::new(
+                &config.diagnostics,
+                RngRegistry::new(config).seed(Stream::Diagnostics),
+            )),
+            daq: Daq::new(&config.daq),
+            controller,
+            scenario,
+            dt: config.simulation.dt,
+            guard: FiniteGuard::new(config.numerics.finite_check_interval),
+            last_measurement: None,
+        }
+    }
+
+    /// Advances one solver step. Returns the controller decision if the
+    /// DAQ delivered a measurement this step.
+    pub fn step(&mut self) -> Option<ControlAction> {
+        self.scenario.advance(&mut self.state);
+        let mut action = None;
+        let sample = self.diagnostic.observe(&self.state);
+        if let Some(measurement) = self.daq.transfer(sample, self.state.time) {
+            self.controller.regime(&self.state.regime());
+            let decision = self.controller.decide(&measurement);
+            tracing::trace!(time = measurement.time, ?decision, "controller decision");
+            for event in self.controller.take_events() {
+                self.state.record_event(event);
+            }
+            let was_pulsing = self.state.confinement_mode == ConfinementMode::TurbulencePulse;
+            self.state.apply_action(decision);
+            if !was_pulsing && self.state.confinement_mode == ConfinementMode::TurbulencePulse {
+                self.controller.pulse_started(self.state.time, self.state.pulse_duration);
+            }
+            action = Some(decision);
+            self.last_measurement = Some(measurement);
+        }
+        self.state.update(self.dt);
+        action
+    }
+
+    /// Advances one step without consulting the controller, for callers
+    /// that command the plant themselves. Returns the measurement if the
+    /// DAQ delivered one this step.
+    pub fn step_open_loop(&mut self) -> Option<Measurement> {
+        self.scenario.advance(&mut self.state);
+        let sample = self.diagnostic.observe(&self.state);
+        let measurement = self.daq.transfer(sample, self.state.time);
+        if measurement.is_some() {
+            self.last_measurement = measurement;
+        }
+        self.state.update(self.dt);
+        measurement
+    }
+
+    /// Feeds the non-finite guard; call once per step when stepping by hand.
+    pub fn check_finite(&mut self) -> Result<()> {
+        match self.guard.observe(&self.state) {
+            Some(fault) => Err(SimError::NumericalInstability(fault)),
+            None => Ok(()),
+        }
+    }
+
+    /// Closed-loop steps until `t_max` or termination of the discharge,
+    /// calling `observe` after each.
+    pub fn run(&mut self, t_max: f64, mut observe: impl FnMut(&StellaratorState)) -> Result<()> {
+        while self.state.time < t_max && !self.state.terminated() {
+            self.step();
+            self.check_finite()?;
+            observe(&self.state);
+        }
+        Ok(())
+    }
+
+    pub fn last_measurement(&self) -> Option<&Measurement> {
+        self.last_measurement.as_ref()
+    }
+
+    /// Run state of the diagnostic, DAQ, and controller.
+    pub fn loop_state(&self) -> LoopState {
+        LoopState {
+            diagnostic: self.diagnostic.save_state(),
+            daq: self.daq.clone(),
+            controller: self.controller.save_state(),
+            last_measurement: self.last_measurement,
+        }
+    }
+
+    /// Continues the loop from `saved`. Call after the controller and
+    /// diagnostic are in place, since it restores into them.
+    pub fn restore(&mut self, saved: LoopState) -> serde_json::Result<()> {
+        if let Some(diagnostic) = saved.diagnostic {
+            self.diagnostic.restore_state(diagnostic)?;
+        }
+        if let Some(controller) = saved.controller {
+            self.controller.restore_state(controller)?;
+        }
+        self.daq = saved.daq;
+        self.last_measurement = saved.last_measurement;
+        Ok(())
+    }
+
+    /// Writes the state and the loop around it; JSON for `.json` paths,
+    /// CBOR otherwise (see `StellaratorState::save_checkpoint`).
+    #[cfg(feature = "fs")]
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        write_checkpoint(&(&self.state, self.loop_state()), path.as_ref())
+    }
+
+    /// Reads a `save_checkpoint` file: the state to build the simulation
+    /// around, and the loop to `restore` into it.
+    #[cfg(feature = "fs")]
+    pub fn load_checkpoint<P: AsRef<Path>>(path: P) -> std::io::Result<(StellaratorState, LoopState)> {
+        let (mut state, saved): (StellaratorState, LoopState) = read_checkpoint(path.as_ref())?;
+        state.turbulence.load_plugins()?;
+        Ok((state, saved))
+    }
+}
+
+/// Builds a `Simulation` from the v2 defaults (or a given config), with
+/// the common settings as methods; everything else keeps the config value.
+///
+/// ```
+/// use w7x_turbulence_control::simulation::SimulationBuilder;
+/// use w7x_turbulence_control::units::{Diffusivity, Velocity};
+///
+/// let sim = SimulationBuilder::new()
+///     .grid(51)
+///     .time_step(5e-5)
+///     .transport(Diffusivity(0.05), Velocity(-1.0))
+///     .build()
+///     .expect("valid setup");
+/// assert_eq!(sim.state.nr, 51);
+/// ```
+pub struct SimulationBuilder {
+    config: Config,
+    controller: Option<Box<dyn Controller>>,
+}
+
+impl Default for SimulationBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulationBuilder {
+    pub fn new() -> Self {
+        Self::from_config(Config::default())
+    }
+
+    pub fn from_config(config: Config) -> Self {
+        SimulationBuilder { config, controller: None }
+    }
+
+    /// Radial grid points.
+    pub fn grid(mut self, nr: usize) -> Self {
+        self.config.simulation.nr = nr;
+        self
+    }
+
+    /// Solver step (s).
+    pub fn time_step(mut self, dt: f64) -> Self {
+        self.config.simulation.dt = dt;
+        self
+    }
+
+    /// Neoclassical D and v (negative = inward).
+    pub fn transport(mut self, d_neo: Diffusivity, v_neo: Velocity) -> Self {
+        self.config.plasma.d_neo = d_neo.0;
+        self.config.plasma.v_neo = v_neo.0;
+        self
+    }
+
+    /// Replaces the controller `[controller]` would build.
+    pub fn controller(mut self, controller: Box<dyn Controller>) -> Self {
+        self.controller = Some(controller);
+        self
+    }
+
+    /// Initial n_e, T_e, and n_Z; `file` profiles must already be loaded.
+    pub fn initial_profiles(mut self, profiles: ProfileConfig) -> Self {
+        self.config.profiles = profiles;
+        self
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// `Config::validate`, then the initial state: profiles finite and
+    /// non-negative, and dt within the explicit stability limit times
+    /// `numerics.max_substeps`. Pulses raise D_turb beyond that; the
+    /// transport step is then sub-cycled up to `max_substeps`, and any
+    /// remaining excess is reported at run time (`Event::CflViolation`).
+    pub fn validate(&self) -> Result<()> {
+        self.initial_state().map(|_| ())
+    }
+
+    pub fn build(self) -> Result<Simulation> {
+        let state = self.initial_state()?;
+        let mut sim = Simulation::with_state(state, &self.config);
+        if let Some(controller) = self.controller {
+            sim.controller = controller;
+        } else if let Some(controller) = self.config.controller.load(self.config.plasma.pulse_duration)? {
+            sim.controller = controller;
+        }
+        Ok(sim)
+    }
+
+    fn initial_state(&self) -> Result<StellaratorState> {
+        self.config.validate()?;
+        let state = StellaratorState::from_config(&self.config);
+        if let Some((profile, i, value)) = state.first_non_finite() {
+            return Err(SimError::invalid("profiles", format!("initial {}[{}] = {}", profile, i, value)));
+        }
+        let profiles = [
+            ("impurity_density", &state.impurity_density),
+            ("electron_density", &state.electron_density),
+            ("electron_temp", &state.electron_temp),
+        ];
+        for (profile, values) in profiles {
+            if let Some((i, value)) = values.iter().enumerate().find(|(_, &v)| v < 0.0) {
+                return Err(SimError::invalid("profiles", format!("initial {}[{}] = {:e} < 0", profile, i, value)));
+            }
+        }
+        let (dt, limit) = (self.config.simulation.dt, state.stability_limit());
+        let substeps = self.config.numerics.max_substeps.max(1);
+        if dt > limit * substeps as f64 {
+            return Err(SimError::invalid(
+                "simulation.dt",
+                format!(
+                    "{:.2e}s exceeds the explicit stability limit {:.2e}s of the initial state ({} sub-steps)",
+                    dt, limit, substeps
+                ),
+            ));
+        }
+        Ok(state)
+    }
+}
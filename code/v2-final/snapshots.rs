@@ -0,0 +1,132 @@
+//! # Radial Profile Snapshots
+//!
+//! Full n_Z(r), n_e(r), T_e(r), D_turb(r), Z_eff(r), n_i/n_e(r), D_neo(r),
+//! and pinch velocity v(r) profiles recorded at a fixed simulation-time
+//! cadence for the profile-aware output backends.
+
+use crate::state::StellaratorState;
+
+/// One profile quantity: `rows[snapshot][radius]`.
+pub struct ProfileField<'a> {
+    pub name: &'static str,
+    pub long_name: &'static str,
+    pub units: &'static str,
+    pub rows: &'a [Vec<f64>],
+}
+
+pub struct ProfileSnapshots {
+    pub cadence: f64, // s between snapshots
+    next_time: f64,
+    pub radius: Vec<f64>,
+    pub time: Vec<f64>,
+    pub impurity_density: Vec<Vec<f64>>,
+    pub electron_density: Vec<Vec<f64>>,
+    pub electron_temp: Vec<Vec<f64>>,
+    pub turbulence: Vec<Vec<f64>>,
+    pub zeff: Vec<Vec<f64>>,
+    pub dilution: Vec<Vec<f64>>,
+    pub neoclassical: Vec<Vec<f64>>, // m²/s, D_neo(r)
+    pub pinch: Vec<Vec<f64>>,        // m/s, total convective velocity
+}
+
+impl ProfileSnapshots {
+    pub fn new(cadence: f64) -> Self {
+        ProfileSnapshots {
+            cadence,
+            next_time: 0.0,
+            radius: Vec::new(),
+            time: Vec::new(),
+            impurity_density: Vec::new(),
+            electron_density: Vec::new(),
+            electron_temp: Vec::new(),
+            turbulence: Vec::new(),
+            zeff: Vec::new(),
+            dilution: Vec::new(),
+            neoclassical: Vec::new(),
+            pinch: Vec::new(),
+        }
+    }
+
+    /// Stores a snapshot if one is due at the current state time.
+    pub fn record(&mut self, state: &StellaratorState) {
+        if state.time < self.next_time {
+            return;
+        }
+        self.next_time = state.time + self.cadence;
+
+        if self.radius.is_empty() {
+            self.radius = state.radius_grid.to_vec();
+        }
+        self.time.push(state.time);
+        self.impurity_density.push(state.impurity_density.to_vec());
+        self.electron_density.push(state.electron_density.to_vec());
+        self.electron_temp.push(state.electron_temp.to_vec());
+        self.turbulence.push(state.turbulence_profile().to_vec());
+        self.zeff.push(state.zeff_profile().to_vec());
+        self.dilution.push(state.dilution_profile().to_vec());
+        self.neoclassical.push(state.d_neo_profile.to_vec());
+        self.pinch.push((0..state.nr).map(|i| state.pinch(i)).collect());
+    }
+
+    pub fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.time.is_empty()
+    }
+
+    /// Every recorded profile with its output metadata.
+    pub fn fields(&self) -> [ProfileField<'_>; 8] {
+        [
+            ProfileField {
+                name: "impurity_density",
+                long_name: "impurity density",
+                units: "m-3",
+                rows: &self.impurity_density,
+            },
+            ProfileField {
+                name: "electron_density",
+                long_name: "electron density",
+                units: "m-3",
+                rows: &self.electron_density,
+            },
+            ProfileField {
+                name: "electron_temp",
+                long_name: "electron temperature",
+                units: "keV",
+                rows: &self.electron_temp,
+            },
+            ProfileField {
+                name: "turbulence",
+                long_name: "turbulent diffusivity",
+                units: "m2 s-1",
+                rows: &self.turbulence,
+            },
+            ProfileField {
+                name: "zeff",
+                long_name: "effective charge",
+                units: "1",
+                rows: &self.zeff,
+            },
+            ProfileField {
+                name: "dilution",
+                long_name: "main-ion dilution n_i/n_e",
+                units: "1",
+                rows: &self.dilution,
+            },
+            ProfileField {
+                name: "d_neo",
+                long_name: "neoclassical diffusivity",
+                units: "m2 s-1",
+                rows: &self.neoclassical,
+            },
+            ProfileField {
+                name: "pinch",
+                long_name: "impurity convective velocity",
+                units: "m s-1",
+                rows: &self.pinch,
+            },
+        ]
+    }
+}
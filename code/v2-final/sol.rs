@@ -0,0 +1,110 @@
+//! # Scrape-Off Layer Reservoir
+//!
+//! A 0D impurity reservoir outside r = 1 instead of the edge boundary
+//! condition. Everything crossing r = 1 enters the SOL; the SOL empties
+//! along the field to the divertor (`parallel_loss_time`) and leaks back
+//! across the separatrix into the source region (`leakage_time`):
+//! ```text
+//! dN_SOL/dt = Γ_out − N_SOL / τ_∥ − N_SOL / τ_leak
+//! ```
+//! n_Z(1) is the SOL density N_SOL / V_SOL (a Dirichlet edge), with
+//! V_SOL the shell 1 < r < 1 + `width` (V' taken ∝ r across it). A full SOL flattens the edge
+//! gradient and so throttles the outflow: a pulse only flushes
+//! impurities for good if τ_∥ is short against τ_leak. Wall recycling
+//! (`boundary.recycling`) then acts on the divertor flux.
+//!
+//! Inventories and fluxes are in ∫ V' n_Z dr units, as for recycling.
+//! The reservoir starts filled to the initial edge n_Z. Disabled (the
+//! default), the `boundary.edge` condition applies as in v2; the
+//! steady-state solver always uses it.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sol {
+    pub enabled: bool,
+    pub width: f64,              // Normalized radius, SOL extent beyond r = 1
+    pub parallel_loss_time: f64, // s, exhaust to the divertor
+    pub leakage_time: f64,       // s, return into the confined plasma; 0 = none
+}
+
+impl Default for Sol {
+    fn default() -> Self {
+        Sol {
+            enabled: false,
+            width: 0.05,
+            parallel_loss_time: 0.005,
+            leakage_time: 0.05,
+        }
+    }
+}
+
+/// What left the SOL during one step.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SolExhaust {
+    pub leakage: f64,  // m⁻³/s, source over the source region
+    pub parallel: f64, // ∫ V' n_Z dr per second, to the divertor
+}
+
+impl Sol {
+    /// ∫ V' dr over the SOL shell, V' growing ∝ r from `vprime_edge` at r = 1.
+    pub fn volume(&self, vprime_edge: f64) -> f64 {
+        0.5 * vprime_edge * ((1.0 + self.width).powi(2) - 1.0)
+    }
+}
+
+/// The reservoir of a run, empty until filled.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct SolReservoir {
+    pub config: Sol,
+    volume: f64,    // ∫ V' dr over the shell
+    inventory: f64,
+    exhausted: f64, // Total sent to the divertor
+}
+
+impl SolReservoir {
+    /// An empty reservoir outside a plasma with V' = `vprime_edge` at r = 1.
+    pub fn new(config: Sol, vprime_edge: f64) -> Self {
+        SolReservoir { config, volume: config.volume(vprime_edge), inventory: 0.0, exhausted: 0.0 }
+    }
+
+    /// Impurities held in the SOL (∫ V' n_Z dr units).
+    pub fn inventory(&self) -> f64 {
+        self.inventory
+    }
+
+    /// Impurities sent to the divertor so far (∫ V' n_Z dr units).
+    pub fn exhausted(&self) -> f64 {
+        self.exhausted
+    }
+
+    /// m⁻³, the edge n_Z.
+    pub fn density(&self) -> f64 {
+        self.inventory / self.volume
+    }
+
+    /// Sets the inventory to a SOL density of `density` m⁻³.
+    pub fn fill(&mut self, density: f64) {
+        self.inventory = density.max(0.0) * self.volume;
+    }
+
+    /// Takes in `outflow` (∫ V' n_Z dr units per second, negative when
+    /// the SOL feeds the plasma through r = 1) for `dt`, then drains,
+    /// leaking into a source region of volume `source_volume` (Σ V' dr).
+    pub fn step(&mut self, outflow: f64, dt: f64, source_volume: f64) -> SolExhaust {
+        self.inventory = (self.inventory + outflow * dt).max(0.0);
+        let parallel_rate = 1.0 / self.config.parallel_loss_time;
+        let leakage_rate = if self.config.leakage_time > 0.0 { 1.0 / self.config.leakage_time } else { 0.0 };
+        let rate = parallel_rate + leakage_rate;
+        let removed = self.inventory * (1.0 - (-rate * dt).exp());
+        let leaked = removed * leakage_rate / rate;
+        let parallel = removed - leaked;
+        self.inventory -= removed;
+        self.exhausted += parallel;
+        SolExhaust {
+            leakage: leaked / dt / source_volume,
+            parallel: parallel / dt,
+        }
+    }
+}
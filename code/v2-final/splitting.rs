@@ -0,0 +1,36 @@
+//! # Operator Splitting
+//!
+//! How the impurity radiation of a collapse (`termination.rs`) is combined
+//! with the χ_e step of T_e (`[numerics] splitting`):
+//!
+//! - `lie` (v2): the χ_e step over dt, then radiation as an explicit Euler
+//!   step over dt. First order in dt, and past k dt = 1 (k = (n_Z /
+//!   collapse_density) / cooling_time) the radiation overshoots and T_e
+//!   clamps to zero.
+//! - `strang`: radiation over ½ dt, the χ_e step over dt, radiation over
+//!   ½ dt. Radiation is local and linear in T_e, so each half step is
+//!   exact per cell, T_e ← T_e e^{−k dt/2}: stable at any dt, and the
+//!   splitting error is second order in dt. The χ_e step itself stays
+//!   explicit.
+//!
+//! Radiation is the only stiff local term; the model has one n_Z without
+//! charge states, so there is no ionization or recombination to split off.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Splitting {
+    #[default]
+    Lie,
+    Strang,
+}
+
+impl Splitting {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Splitting::Lie => "lie",
+            Splitting::Strang => "strang",
+        }
+    }
+}
@@ -0,0 +1,1175 @@
+//! # Plasma State
+//!
+//! 1D radial impurity transport with neoclassical + turbulent diffusion
+//! and the Normal / TurbulencePulse confinement state machine.
+
+use crate::actuator::Actuator;
+use crate::balance::ParticleBalance;
+use crate::boundary::{BoundaryCondition, Recycling};
+use crate::confinement::EnergyConfinement;
+use crate::config::{Config, TransportGeometry};
+use crate::controller::{ControlAction, PulseCommand, MAX_AMPLITUDE, MIN_AMPLITUDE};
+use crate::ecrh::EcrhActuator;
+use crate::electric_field::ElectricField;
+use crate::elm::Elms;
+use crate::events::{Event, TimedEvent};
+use crate::gain_schedule::Regime;
+use crate::geometry::Metric;
+#[cfg(feature = "fs")]
+use crate::history::Channel;
+use crate::history::{History, Sample};
+use crate::main_ions::MainIons;
+use crate::neoclassical::{ChargeScaling, Neoclassical};
+use crate::pellet::PelletActuator;
+use crate::poloidal::{PoloidalTransport, RadialTerms};
+use crate::profiles::ProfileConfig;
+use crate::pulse::{PulseShape, PulseWindow};
+use crate::ramp::{Ramp, RampTracker};
+use crate::regularization::Regularization;
+use crate::rng::{RngRegistry, Stream};
+use crate::sawtooth::Sawtooth;
+use crate::convection::Convection;
+use crate::splitting::Splitting;
+use crate::stencil::Stencil;
+use crate::precision::{Precision, Real};
+use crate::sol::SolReservoir;
+use crate::source::{SourceModel, SOURCE_RADIUS};
+use crate::steady;
+use crate::termination::{Observed, Termination};
+use crate::turbulence::{LocalProfiles, Turbulence, TurbulenceDynamics, TurbulenceModel};
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::{BufReader, BufWriter, Write};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// m⁻³, cap on n_Z in the transport step.
+pub const MAX_IMPURITY_DENSITY: f64 = 1e20;
+
+/// Confinement state machine; the last three only with `[termination]`
+/// enabled, see `termination`. More modes may follow.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum ConfinementMode {
+    Normal,
+    TurbulencePulse,
+    RadiativeCollapse,  // Core radiating, T_e falling; protection not yet acting
+    EmergencyFlush,     // Machine-protection pulse until recovery
+    Terminated,         // Discharge lost; profiles frozen
+}
+
+impl ConfinementMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfinementMode::Normal => "normal",
+            ConfinementMode::TurbulencePulse => "turbulence_pulse",
+            ConfinementMode::RadiativeCollapse => "radiative_collapse",
+            ConfinementMode::EmergencyFlush => "emergency_flush",
+            ConfinementMode::Terminated => "terminated",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StellaratorState {
+    pub radius_grid: Array1<f64>,
+    pub dr: f64,
+    pub nr: usize,
+    pub metric: Metric,
+    pub poloidal: Option<PoloidalTransport>,  // Set for the 2D solver
+    pub impurity_density: Array1<f64>,
+    pub electron_density: Array1<f64>,
+    pub electron_temp: Array1<f64>,
+    pub d_neo: f64,
+    pub d_turb_base: f64,
+    pub turbulence: Turbulence,  // Normal-mode D_turb / d_turb_base from the profiles
+    pub turbulence_dynamics: TurbulenceDynamics,
+    turbulence_field: Array1<f64>,      // m²/s, D_turb when the dynamics are enabled
+    pub electric_field: ElectricField,  // E_r model and E×B shear quench of D_turb
+    pub radial_field: Array1<f64>,      // kV/m, E_r(r)
+    pub v_neo: f64,
+    pub neoclassical: Neoclassical,  // Radial shape of D_neo and v_neo
+    pub impurity_charge: f64,        // Z
+    pub charge_scaling: ChargeScaling,
+    pub d_neo_profile: Array1<f64>,  // m²/s, D_neo(r)
+    pub v_neo_profile: Array1<f64>,  // m/s, v_neo(r) before temperature screening
+    pub confinement_mode: ConfinementMode,
+    pub mode_since: f64,  // s, time the current mode was entered
+    pub time: f64,
+    pub pulse_start_time: Option<f64>,
+    pub last_pulse_end_time: Option<f64>,  // ⭐ Added
+    pub cooldown_duration: f64,            // ⭐ Added
+    pub pulse_duration: f64,   // s
+    pub pulse_amplitude: f64,  // D_turb enhancement factor in the pulse region
+    pub commanded_amplitude: Option<f64>,  // Overrides pulse_amplitude for the current pulse
+    pub pulse_waveform: Vec<f64>,  // Relative amplitude per equal time segment; empty = flat
+    pub pulse_shape: PulseShape,
+    pub pulse_windows: Vec<PulseWindow>,  // Selectable pulse regions; the first is the default
+    pub pulse_window: PulseWindow,        // Region of the current (or last) pulse
+    pub impurity_source: f64,  // m⁻³/s, wall source for r > 0.85
+    pub source_model: SourceModel,
+    pub core_boundary: BoundaryCondition,
+    pub edge_boundary: BoundaryCondition,
+    pub recycling: Recycling,
+    pub chi_e: f64,            // m²/s, electron heat diffusivity
+    pub temperature_screening: f64,  // H in v ∝ ∇n/n − H ∇T/T, < 1
+    pub verbose: bool,         // Log events (mode changes, warnings) as they are recorded
+    pub actuator: Actuator,
+    pub pellet: PelletActuator,
+    pub ecrh: EcrhActuator,
+    pub confinement: EnergyConfinement,
+    pub sawtooth: Sawtooth,
+    pub elms: Elms,
+    pub termination: Termination,
+    pub ramp: Ramp,
+    #[serde(default)]
+    ramp_tracker: RampTracker,
+    pub main_ions: MainIons,
+    pub sol: SolReservoir,
+    temperature_balance: Array1<f64>,  // keV/s per unit χ_e holding the initial T_e steady
+    #[serde(default)]
+    main_ion_balance: Array1<f64>,     // m⁻³/s, fuelling holding the initial n_i steady
+    pub regularization: Regularization,
+    pub convection: Convection,      // Face density of the pinch flux
+    pub stencil: Stencil,            // Order of the radial transport step
+    pub max_substeps: usize,         // Transport sub-steps per dt past the explicit limit; 1 = off
+    pub precision: Precision,        // Float type of the 1D transport rates
+    #[serde(default)]
+    pub splitting: Splitting,        // Radiation vs χ_e step of T_e
+    pub regularized_cells: u64,  // Total cells touched by the regularization
+    regularization_reported: (Option<f64>, u64),  // (time, regularized_cells) at last log line
+    pub balance: ParticleBalance,                 // Impurity input/output since the start
+    stability_checked: (Option<f64>, bool),       // (time, limit exceeded) at the last check
+    #[serde(skip)]
+    events: Vec<TimedEvent>,                      // Not yet drained
+    pub history: History,
+    last_sample: Sample,
+    last_sample_due: bool,
+}
+
+impl StellaratorState {
+    /// v2 defaults on `nr` points, unchecked; `SimulationBuilder`
+    /// validates the setup.
+    pub fn new(nr: usize) -> Self {
+        let dr = 1.0 / (nr - 1) as f64;
+        let radius_grid = Array1::linspace(0.0, 1.0, nr);
+        let metric = Metric::cylindrical(&radius_grid, dr);
+
+        let mut state = StellaratorState {
+            radius_grid,
+            dr,
+            nr,
+            metric,
+            poloidal: None,
+            impurity_density: Array1::zeros(nr),
+            electron_density: Array1::zeros(nr),
+            electron_temp: Array1::zeros(nr),
+            d_neo: 0.02,
+            d_turb_base: 1.5,  // ⭐ 1.0 → 1.5
+            turbulence: Turbulence::default(),
+            turbulence_dynamics: TurbulenceDynamics::default(),
+            turbulence_field: Array1::zeros(nr),
+            electric_field: ElectricField::Off,
+            radial_field: Array1::zeros(nr),
+            v_neo: -0.5,       // ⭐ -0.8 → -0.5 (weaker)
+            neoclassical: Neoclassical::Constant,
+            impurity_charge: 26.0,
+            charge_scaling: ChargeScaling::default(),
+            d_neo_profile: Array1::from_elem(nr, 0.02),
+            v_neo_profile: Array1::from_elem(nr, -0.5),
+            confinement_mode: ConfinementMode::Normal,
+            mode_since: 0.0,
+            time: 0.0,
+            pulse_start_time: None,
+            last_pulse_end_time: None,     // ⭐
+            cooldown_duration: 0.5,        // ⭐ 500ms
+            pulse_duration: 0.2,           // ⭐ 0.1 → 0.2s
+            pulse_amplitude: 5.0,          // ⭐ 3.0 → 5.0
+            commanded_amplitude: None,
+            pulse_waveform: Vec::new(),
+            pulse_shape: PulseShape::Square,
+            pulse_windows: vec![PulseWindow::default()],
+            pulse_window: PulseWindow::default(),
+            impurity_source: 2.5e17,       // ⭐ Moderate value
+            source_model: SourceModel::Constant,
+            core_boundary: BoundaryCondition::core_default(),
+            edge_boundary: BoundaryCondition::edge_default(),
+            recycling: Recycling::none(),
+            chi_e: 1.0,
+            temperature_screening: 0.0,
+            verbose: true,
+            actuator: Actuator::ideal(),
+            pellet: PelletActuator::default(),
+            ecrh: EcrhActuator::off(),
+            confinement: EnergyConfinement::default(),
+            sawtooth: Sawtooth::default(),
+            elms: Elms::default(),
+            termination: Termination::default(),
+            ramp: Ramp::default(),
+            ramp_tracker: RampTracker::default(),
+            main_ions: MainIons::default(),
+            sol: SolReservoir::default(),
+            temperature_balance: Array1::zeros(nr),
+            main_ion_balance: Array1::zeros(nr),
+            regularization: Regularization::None,
+            convection: Convection::Central,
+            stencil: Stencil::Second,
+            max_substeps: 1,
+            precision: Precision::F64,
+            splitting: Splitting::Lie,
+            regularized_cells: 0,
+            regularization_reported: (None, 0),
+            balance: ParticleBalance::default(),
+            stability_checked: (None, false),
+            events: Vec::new(),
+            history: History::default(),
+            last_sample: Sample::default(),
+            last_sample_due: false,
+        };
+
+        state.initialize_profiles(&ProfileConfig::default());
+        state.temperature_balance = -state.temperature_diffusion();
+        state.turbulence_field = state.target_turbulence_profile();
+        state.balance = ParticleBalance::new(state.impurity_inventory());
+        let mean_turbulence = state.mean_turbulence();
+        state.confinement.initialize(mean_turbulence);
+        state
+    }
+
+    /// Grid, plasma parameters, actuator, and numerics taken from a run
+    /// configuration.
+    pub fn from_config(config: &Config) -> Self {
+        let mut state = StellaratorState::new(config.simulation.nr);
+        if let Some(surfaces) = &config.flux_surfaces {
+            state.metric = Metric::from_surfaces(surfaces, &state.radius_grid, state.dr);
+        }
+        state.initialize_profiles(&config.profiles);
+        state.temperature_balance = -state.temperature_diffusion();
+        state.d_neo = config.plasma.d_neo;
+        state.d_turb_base = config.plasma.d_turb_base;
+        state.turbulence = config.turbulence.clone();
+        state.turbulence_dynamics = config.turbulence_dynamics;
+        state.electric_field = config.electric_field;
+        state.v_neo = config.plasma.v_neo;
+        state.neoclassical = config.neoclassical.clone();
+        state.impurity_charge = config.plasma.impurity_charge;
+        state.charge_scaling = config.plasma.charge_scaling;
+        state.pulse_duration = config.plasma.pulse_duration;
+        state.cooldown_duration = config.plasma.cooldown;
+        state.pulse_amplitude = config.plasma.pulse_amplitude;
+        state.pulse_waveform = config.plasma.pulse_waveform.clone();
+        state.pulse_shape = config.plasma.pulse_shape;
+        state.pulse_windows = config.plasma.pulse_windows.clone();
+        state.pulse_window = state.pulse_windows.first().copied().unwrap_or_default();
+        state.impurity_source = config.plasma.impurity_source;
+        state.source_model = config.plasma.source;
+        state.core_boundary = config.boundary.core;
+        state.edge_boundary = config.boundary.edge;
+        state.recycling = Recycling::new(config.boundary.recycling, config.boundary.residence_time);
+        state.chi_e = config.plasma.chi_e;
+        state.temperature_screening = config.plasma.temperature_screening;
+        state.actuator = Actuator::new(
+            config.actuator.latency,
+            config.actuator.rise_time,
+            config.actuator.fall_time,
+        );
+        let pellet = &config.pellet;
+        state.pellet = PelletActuator::new(
+            pellet.density_increment,
+            pellet.impurity_fraction,
+            pellet.ablation_coefficient,
+            pellet.relaxation_time,
+            pellet.min_interval,
+            pellet.schedule.clone(),
+        );
+        let ecrh = &config.ecrh;
+        state.confinement = config.confinement;
+        state.sawtooth = config.sawtooth;
+        state.elms = config.elms;
+        state.termination = config.termination;
+        state.ramp = config.ramp;
+        state.main_ions = config.main_ions;
+        state.sol = SolReservoir::new(config.sol, state.metric.vprime[state.nr - 1]);
+        state.elms.seed = RngRegistry::new(config).seed(Stream::Elms);
+        state.ecrh = EcrhActuator::new(
+            ecrh.max_power,
+            ecrh.ramp_rate,
+            ecrh.deposition_radius,
+            ecrh.deposition_width,
+            ecrh.energy_budget,
+            ecrh.plasma_volume,
+        );
+        state.regularization = config.numerics.regularization;
+        state.convection = config.numerics.convection;
+        state.stencil = config.numerics.stencil;
+        state.max_substeps = config.numerics.max_substeps;
+        state.precision = config.numerics.precision;
+        state.splitting = config.numerics.splitting;
+        if state.ramp.enabled() {
+            state.start_ramp();
+        }
+        state.update_neoclassical();
+        state.update_electric_field();
+        state.turbulence_field = state.target_turbulence_profile();
+        if config.profiles.impurity_steady_state {
+            match steady::solve(&state, &config.steady_state) {
+                Some(steady) if steady.bounded => state.impurity_density = steady.impurity_density,
+                _ => tracing::warn!("⚠️ No bounded steady-state n_Z; keeping the initial profile"),
+            }
+        }
+        if config.simulation.geometry == TransportGeometry::Poloidal {
+            let mut poloidal = PoloidalTransport::new(
+                config.poloidal.ntheta,
+                config.poloidal.diffusivity,
+                config.poloidal.asymmetry,
+                &state.radius_grid,
+                &state.impurity_density,
+            );
+            poloidal.backend = config.poloidal.backend;
+            state.poloidal = Some(poloidal);
+        }
+        if state.sol.config.enabled {
+            let edge = state.impurity_density[state.nr - 1];
+            state.sol.fill(edge);
+        }
+        if state.main_ions.enabled {
+            state.main_ion_balance = -state.main_ion_rates(&state.main_ion_density());
+        }
+        state.balance = ParticleBalance::new(state.impurity_inventory());
+        let mean_turbulence = state.mean_turbulence();
+        state.confinement.initialize(mean_turbulence);
+        state
+    }
+
+    /// Scales the flat-top profiles to the start of the ramp-up: T_e
+    /// towards T_edge by the heating level, n_e and n_Z by the density and
+    /// source levels.
+    fn start_ramp(&mut self) {
+        self.ramp_tracker = RampTracker::new(&self.ramp, self.time);
+        let levels = self.ramp.levels(self.time);
+        let edge = self.electron_temp[self.nr - 1];
+        self.electron_temp.mapv_inplace(|t| edge + levels.heating * (t - edge));
+        self.scale_electron_density(levels.density);
+        self.impurity_density.mapv_inplace(|n| n * levels.source);
+    }
+
+    fn initialize_profiles(&mut self, profiles: &ProfileConfig) {
+        for (i, &r) in self.radius_grid.iter().enumerate() {
+            let (n_e, t_e, n_z) = profiles.evaluate(r);
+            self.electron_density[i] = n_e;
+            self.electron_temp[i] = t_e;
+            self.impurity_density[i] = n_z;
+        }
+    }
+
+    /// D_turb (m²/s) at a grid point: the relaxing field when
+    /// `turbulence_dynamics` is enabled, otherwise the model value.
+    pub fn calculate_turbulence_level(&self, r_idx: usize) -> f64 {
+        if self.turbulence_dynamics.enabled() {
+            self.turbulence_field[r_idx]
+        } else {
+            self.target_turbulence_level(r_idx)
+        }
+    }
+
+    /// D_turb the turbulence model, pulse, and E×B shear call for now.
+    pub fn target_turbulence_level(&self, r_idx: usize) -> f64 {
+        let r = self.radius_grid[r_idx];
+        if !(0.02..=0.98).contains(&r) {
+            return 0.05;
+        }
+
+        let dilution = self.charge_balance(r_idx).1;
+        let normal_factor = self.turbulence.factor(&self.local_profiles(r_idx)) * self.main_ions.drive(dilution);
+        let factor = if self.turbulence.resolves_pulse() {
+            normal_factor
+        } else {
+            let pulse_factor = if self.pulse_window.contains(r) { 
+                self.pulse_factor()
+            } else { 
+                1.0 
+            };
+
+            // Blend by actuator output so latency and rise/fall times show up in D_turb
+            let level = self.actuator.output();
+            normal_factor + (pulse_factor - normal_factor) * level
+        };
+        let factor = factor * self.elms.factor(r, self.time);
+
+        let shearing_rate = ElectricField::shearing_rate(
+            self.radial_field[r_idx + 1],
+            self.radial_field[r_idx - 1],
+            self.dr,
+        );
+        self.electric_field.suppress(self.d_turb_base * factor, shearing_rate)
+    }
+
+    /// Recomputes E_r(r) from the current profiles and actuator output.
+    pub fn update_electric_field(&mut self) {
+        if self.electric_field == ElectricField::Off {
+            return;
+        }
+        let nr = self.nr;
+        let level = self.actuator.output();
+        let window = self.pulse_window;
+        for i in 1..nr - 1 {
+            let r = self.radius_grid[i];
+            let ramp = level * ((r - window.inner()) / window.width.max(1e-6)).clamp(0.0, 1.0);
+            self.radial_field[i] = self.electric_field.field(&self.local_profiles(i), ramp);
+        }
+        self.radial_field[0] = 0.0;  // Regular on axis
+        self.radial_field[nr - 1] = self.radial_field[nr - 2];
+    }
+
+    /// n_e, T_e and their gradients at an interior grid point.
+    pub fn local_profiles(&self, r_idx: usize) -> LocalProfiles {
+        LocalProfiles {
+            radius: self.radius_grid[r_idx],
+            electron_density: self.electron_density[r_idx],
+            density_gradient: (self.electron_density[r_idx + 1] - self.electron_density[r_idx - 1]) 
+                              / (2.0 * self.dr),
+            electron_temp: self.electron_temp[r_idx],
+            temperature_gradient: (self.electron_temp[r_idx + 1] - self.electron_temp[r_idx - 1]) 
+                                  / (2.0 * self.dr),
+            d_turb_base: self.d_turb_base,
+            pulse_level: if self.pulse_window.contains(self.radius_grid[r_idx]) {
+                self.actuator.output()
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Recomputes D_neo(r) and v_neo(r) from the current profiles and the
+    /// impurity charge; the grid ends take their neighbours' values.
+    pub fn update_neoclassical(&mut self) {
+        let nr = self.nr;
+        let (d_charge, v_charge) = self.charge_scaling.factors(self.impurity_charge);
+        for i in 1..nr - 1 {
+            let (d, v) = self.neoclassical.coefficients(&self.local_profiles(i), self.d_neo, self.v_neo);
+            self.d_neo_profile[i] = d * d_charge;
+            self.v_neo_profile[i] = v * v_charge;
+        }
+        for (end, neighbour) in [(0, 1), (nr - 1, nr - 2)] {
+            self.d_neo_profile[end] = self.d_neo_profile[neighbour];
+            self.v_neo_profile[end] = self.v_neo_profile[neighbour];
+        }
+    }
+
+    /// Neoclassical pinch ∝ ∇n/n − H ∇T/T = (1 − H η) ∇n/n, normalized to
+    /// v_neo(r) at η = 1: a flatter T_e (low η) strengthens it, a T_e
+    /// steepened relative to n_e (e.g. by ECRH) screens it and reverses it
+    /// above η = 1/H. The turbulence model's convection is added on top.
+    pub(crate) fn pinch(&self, r_idx: usize) -> f64 {
+        let r = self.radius_grid[r_idx];
+        let v_neo = self.v_neo_profile[r_idx];
+        if !(0.02..=0.98).contains(&r) {
+            return v_neo;
+        }
+        let local = self.local_profiles(r_idx);
+        let h = self.temperature_screening;
+        let neoclassical = if h == 0.0 {
+            v_neo
+        } else {
+            v_neo * (1.0 - h * local.eta()) / (1.0 - h)
+        };
+        neoclassical + self.turbulence.pinch(&local)
+    }
+
+    /// Current `pulse_waveform` value: the active segment during a pulse,
+    /// the final segment afterwards (while the actuator decays).
+    pub fn pulse_envelope(&self) -> f64 {
+        let Some(&last) = self.pulse_waveform.last() else {
+            return 1.0;
+        };
+        match self.pulse_start_time {
+            Some(start) if self.pulse_duration > 0.0 => {
+                let phase = (self.time - start) / self.pulse_duration;
+                let segment = (phase * self.pulse_waveform.len() as f64) as usize;
+                self.pulse_waveform.get(segment).copied().unwrap_or(last)
+            }
+            _ => last,
+        }
+    }
+
+    /// D_turb enhancement in the pulse region: amplitude and waveform,
+    /// shaped in time by `pulse_shape` (held at its end value afterwards).
+    pub fn pulse_factor(&self) -> f64 {
+        let amplitude = self.commanded_amplitude.unwrap_or(self.pulse_amplitude);
+        let enhancement = amplitude * self.pulse_envelope();
+        if self.pulse_shape == PulseShape::Square {
+            return enhancement;
+        }
+        let elapsed = match self.pulse_start_time {
+            Some(start) => self.time - start,
+            None => self.pulse_duration,
+        };
+        1.0 + (enhancement - 1.0) * self.pulse_shape.value(elapsed, self.pulse_duration)
+    }
+
+    /// Scalar channels of the most recent `update`.
+    pub fn last_sample(&self) -> &Sample {
+        &self.last_sample
+    }
+
+    /// The most recent sample if it was due under the history cadence.
+    pub fn recorded_sample(&self) -> Option<&Sample> {
+        self.last_sample_due.then_some(&self.last_sample)
+    }
+
+    /// (profile, radial index, value) of the first NaN or ±∞ in the
+    /// evolved profiles, checked in a fixed order.
+    pub fn first_non_finite(&self) -> Option<(&'static str, usize, f64)> {
+        let profiles: [(&'static str, &Array1<f64>); 5] = [
+            ("impurity_density", &self.impurity_density),
+            ("electron_density", &self.electron_density),
+            ("electron_temp", &self.electron_temp),
+            ("turbulence_field", &self.turbulence_field),
+            ("radial_field", &self.radial_field),
+        ];
+        for (name, profile) in profiles {
+            if let Some((i, &v)) = profile.iter().enumerate().find(|(_, v)| !v.is_finite()) {
+                return Some((name, i, v));
+            }
+        }
+        let poloidal = self.poloidal.as_ref()?;
+        poloidal
+            .density()
+            .indexed_iter()
+            .find(|(_, v)| !v.is_finite())
+            .map(|((i, _), &v)| ("poloidal_density", i, v))
+    }
+
+    /// D_turb(r) on the full grid.
+    pub fn turbulence_profile(&self) -> Array1<f64> {
+        (0..self.nr).map(|i| self.calculate_turbulence_level(i)).collect()
+    }
+
+    /// (Z_eff, n_i / n_e) at grid point `i` for the impurity of charge Z in
+    /// a hydrogen plasma, n_i = n_e − Z n_Z by quasi-neutrality (floored at
+    /// 0 once the impurity carries all electrons). Both are 1 where n_e = 0.
+    pub fn charge_balance(&self, i: usize) -> (f64, f64) {
+        let n_e = self.electron_density[i];
+        if n_e <= 0.0 {
+            return (1.0, 1.0);
+        }
+        let z = self.impurity_charge;
+        let n_z = self.impurity_density[i];
+        let n_i = (n_e - z * n_z).max(0.0);
+        ((n_i + z * z * n_z) / n_e, n_i / n_e)
+    }
+
+    /// Volume-averaged D_turb (m²/s) over the interior control volumes.
+    pub fn mean_turbulence(&self) -> f64 {
+        let (weighted, volume) = (1..self.nr - 1).fold((0.0, 0.0), |(w, v), i| {
+            let dv = self.metric.vprime[i] * self.dr;
+            (w + self.calculate_turbulence_level(i) * dv, v + dv)
+        });
+        if volume > 0.0 { weighted / volume } else { 0.0 }
+    }
+
+    /// Operating point for gain scheduling: ν*_e at mid-radius,
+    /// line-averaged n_e, and the programmed heating power.
+    pub fn regime(&self) -> Regime {
+        Regime {
+            collisionality: self.local_profiles(self.nr / 2).collisionality(),
+            density: self.electron_density.mean().unwrap_or(0.0),
+            heating_power: self.confinement.heating_power,
+        }
+    }
+
+    /// n_i = n_e − Z n_Z (m⁻³), clipped at zero.
+    pub fn main_ion_density(&self) -> Array1<f64> {
+        let z = self.impurity_charge;
+        (0..self.nr).map(|i| (self.electron_density[i] - z * self.impurity_density[i]).max(0.0)).collect()
+    }
+
+    /// ∂n_i/∂t (m⁻³/s) from main-ion transport at the current D_turb.
+    fn main_ion_rates(&self, main_ions: &Array1<f64>) -> Array1<f64> {
+        let turbulence: Array1<f64> = (0..self.nr).map(|i| self.calculate_turbulence_level(i)).collect();
+        self.main_ions.rates(&self.radius_grid, self.dr, main_ions, &turbulence)
+    }
+
+    fn main_ion_stability_limit(&self) -> f64 {
+        let turbulence: Array1<f64> = (0..self.nr).map(|i| self.calculate_turbulence_level(i)).collect();
+        self.main_ions.stability_limit(self.dr, &turbulence)
+    }
+
+    pub fn zeff_profile(&self) -> Array1<f64> {
+        (0..self.nr).map(|i| self.charge_balance(i).0).collect()
+    }
+
+    pub fn dilution_profile(&self) -> Array1<f64> {
+        (0..self.nr).map(|i| self.charge_balance(i).1).collect()
+    }
+
+    fn target_turbulence_profile(&self) -> Array1<f64> {
+        (0..self.nr).map(|i| self.target_turbulence_level(i)).collect()
+    }
+
+    /// Impurity radiation over `h` seconds, exact per cell (Strang half step).
+    fn radiate(&mut self, h: f64) {
+        for i in 0..self.nr - 1 {
+            self.electron_temp[i] = self.termination.radiate(self.electron_temp[i], self.impurity_density[i], h);
+        }
+    }
+
+    /// (1/r) ∂/∂r (r ∂T_e/∂r) on the grid; the edge value is held fixed.
+    fn temperature_diffusion(&self) -> Array1<f64> {
+        let t = &self.electron_temp;
+        let mut lap = Array1::zeros(self.nr);
+        // On axis the cylindrical Laplacian is 2 ∂²T/∂r² with ∂T/∂r = 0
+        lap[0] = 4.0 * (t[1] - t[0]) / (self.dr * self.dr);
+        for i in 1..self.nr - 1 {
+            let r = self.radius_grid[i];
+            let grad_p = (t[i + 1] - t[i]) / self.dr;
+            let grad_m = (t[i] - t[i - 1]) / self.dr;
+            lap[i] = ((r + 0.5 * self.dr) * grad_p - (r - 0.5 * self.dr) * grad_m) / (r * self.dr);
+        }
+        lap
+    }
+
+    fn calculate_flux(&self, r_idx: usize) -> f64 {
+        if r_idx == 0 || r_idx >= self.nr - 1 {
+            return 0.0;
+        }
+
+        let dn_z_dr = (self.impurity_density[r_idx + 1] - self.impurity_density[r_idx - 1]) 
+                      / (2.0 * self.dr);
+
+        let d_total = self.d_neo_profile[r_idx] + self.calculate_turbulence_level(r_idx);
+
+        let metric = &self.metric;
+        let v = self.pinch(r_idx) * metric.grad_rho[r_idx];
+        let n_z = self.convection.face_density(self.impurity_density.view(), r_idx, v, self.impurity_density[r_idx]);
+        v * n_z - d_total * metric.grad_rho2[r_idx] * dn_z_dr
+    }
+
+    /// Impurity content ∫ n_Z V' dρ over the interior control volumes
+    /// V'_i dρ, the volumes the transport step conserves.
+    pub fn impurity_inventory(&self) -> f64 {
+        (1..self.nr - 1)
+            .map(|i| self.impurity_density[i] * self.metric.vprime[i] * self.dr)
+            .sum()
+    }
+
+    /// Relative particle-balance error of the current inventory.
+    pub fn conservation_error(&self) -> f64 {
+        self.balance.error(self.impurity_inventory())
+    }
+
+    /// Largest stable explicit step (s) for the current D and v:
+    /// `Stencil::diffusive_limit` for diffusion, dr / |v| for convection;
+    /// with main ions enabled, also that of the n_i step.
+    pub fn stability_limit(&self) -> f64 {
+        let metric = &self.metric;
+        let main_ions = if self.main_ions.enabled {
+            self.main_ion_stability_limit()
+        } else {
+            f64::INFINITY
+        };
+        (1..self.nr - 1)
+            .map(|i| {
+                let d = (self.d_neo_profile[i] + self.calculate_turbulence_level(i)) * metric.grad_rho2[i];
+                let v = (self.pinch(i) * metric.grad_rho[i]).abs();
+                let diffusive = self.stencil.diffusive_limit(self.dr, d);
+                let convective = if v > 0.0 { self.dr / v } else { f64::INFINITY };
+                diffusive.min(convective)
+            })
+            .fold(main_ions, f64::min)
+    }
+
+    /// Constant-model wall source (m⁻³/s) at the current ramp level.
+    pub fn constant_source(&self) -> f64 {
+        self.impurity_source * self.ramp.levels(self.time).source
+    }
+
+    /// Current wall source rate (m⁻³/s) in the source region.
+    pub fn wall_source(&self) -> f64 {
+        match self.source_model {
+            SourceModel::Constant => self.constant_source(),
+            SourceModel::Sputtering(sputtering) => {
+                let edge = self.nr - 2;
+                sputtering.rate(
+                    self.electron_temp[edge],
+                    self.calculate_turbulence_level(edge) / self.d_turb_base.max(1e-12),
+                    self.calculate_flux(edge),
+                )
+            }
+        }
+    }
+
+    /// Records `event` at the current time and logs it when verbose.
+    pub fn record_event(&mut self, event: Event) {
+        let event = TimedEvent { time: self.time, event };
+        if self.verbose {
+            event.log();
+        }
+        self.events.push(event);
+    }
+
+    /// Events recorded since the last call, oldest first.
+    pub fn drain_events(&mut self) -> Vec<TimedEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn apply_action(&mut self, action: ControlAction) {
+        let command = match action {
+            ControlAction::Hold => return,
+            ControlAction::TriggerPulse => PulseCommand::default(),
+            ControlAction::Pulse(command) => command,
+        };
+        if self.confinement_mode != ConfinementMode::Normal {
+            return;
+        }
+
+        // ⭐ Cooldown check
+        let can_pulse = if let Some(last_end) = self.last_pulse_end_time {
+            self.time - last_end > self.cooldown_duration
+        } else {
+            true
+        };
+
+        if can_pulse {
+            self.force_pulse(command);
+        }
+    }
+
+    /// Starts a pulse regardless of the cooldown; no effect during a pulse.
+    pub fn force_pulse(&mut self, command: PulseCommand) {
+        if self.confinement_mode != ConfinementMode::Normal {
+            return;
+        }
+        // Unknown window indices fall back to the default window
+        let index = if command.window < self.pulse_windows.len() { command.window } else { 0 };
+        self.pulse_window = self.pulse_windows.get(index).copied().unwrap_or_default();
+        self.commanded_amplitude = command.amplitude.map(|a| a.clamp(MIN_AMPLITUDE, MAX_AMPLITUDE));
+        self.record_event(Event::PulseStarted {
+            window: index,
+            inner: self.pulse_window.inner(),
+            outer: self.pulse_window.outer(),
+            amplitude: self.commanded_amplitude,
+        });
+        self.confinement_mode = ConfinementMode::TurbulencePulse;
+        self.mode_since = self.time;
+        self.pulse_start_time = Some(self.time);
+    }
+
+    pub fn terminated(&self) -> bool {
+        self.confinement_mode == ConfinementMode::Terminated
+    }
+
+    /// Collapse, flush, and termination transitions (`termination`).
+    fn update_protection(&mut self) {
+        let observed = Observed {
+            center_impurity: self.impurity_density[0],
+            center_temp: self.electron_temp[0],
+            time_in_mode: self.time - self.mode_since,
+        };
+        let Some((mode, reason)) = self.termination.transition(self.confinement_mode, observed) else {
+            return;
+        };
+        match mode {
+            ConfinementMode::EmergencyFlush => {
+                self.pulse_window = self.pulse_windows.first().copied().unwrap_or_default();
+                self.commanded_amplitude = Some(self.termination.flush_amplitude);
+                self.pulse_start_time = Some(self.time);
+            }
+            _ if self.pulse_start_time.is_some() => {
+                self.last_pulse_end_time = Some(self.time);
+                self.pulse_start_time = None;
+            }
+            _ => {}
+        }
+        self.record_event(Event::ModeChanged { mode, reason });
+        self.confinement_mode = mode;
+        self.mode_since = self.time;
+    }
+
+    /// Multiplies n_e, and the profile pellets relax back to, by `factor`.
+    pub fn scale_electron_density(&mut self, factor: f64) {
+        self.electron_density.mapv_inplace(|n| n * factor);
+        self.pellet.scale_target(factor);
+    }
+
+    /// Injects a pellet now. Returns false while `pellet.min_interval`
+    /// since the previous one has not yet elapsed.
+    pub fn fire_pellet(&mut self) -> bool {
+        let before = self.impurity_inventory();
+        let Some(injection) = self.pellet.fire(
+            self.time,
+            &self.radius_grid,
+            &self.electron_temp,
+            &mut self.electron_density,
+            &mut self.impurity_density,
+        ) else {
+            return false;
+        };
+        self.balance.source += self.impurity_inventory() - before;
+        self.record_event(Event::PelletInjected {
+            penetration: injection.penetration,
+            ablated: injection.deposited,
+        });
+        true
+    }
+
+    /// Flattens n_Z and T_e inside the sawtooth mixing radius now,
+    /// whatever the trigger.
+    pub fn sawtooth_crash(&mut self) {
+        let crash = self.sawtooth.crash(
+            self.time,
+            &self.radius_grid,
+            &self.metric.vprime,
+            &mut self.electron_temp,
+            &mut self.impurity_density,
+        );
+        self.record_event(Event::SawtoothCrash {
+            mixing_radius: self.sawtooth.mixing_radius,
+            center_impurity_before: crash.center_impurity_before,
+            center_impurity_after: crash.center_impurity_after,
+        });
+    }
+
+    /// Pinch × ⟨|∇ρ|⟩ and D × ⟨|∇ρ|²⟩ on the grid as the transport step
+    /// uses them; 0 at the grid ends.
+    pub fn transport_coefficients(&self) -> (Array1<f64>, Array1<f64>) {
+        let nr = self.nr;
+        let interior = |i: usize| i > 0 && i < nr - 1;
+        let velocity = (0..nr)
+            .map(|i| if interior(i) { self.pinch(i) * self.metric.grad_rho[i] } else { 0.0 })
+            .collect();
+        let diffusivity = (0..nr)
+            .map(|i| {
+                if interior(i) {
+                    (self.d_neo_profile[i] + self.calculate_turbulence_level(i)) * self.metric.grad_rho2[i]
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        (velocity, diffusivity)
+    }
+
+    fn radial_terms<'a, T: Real>(
+        &'a self,
+        velocity: &'a Array1<T>,
+        diffusivity: &'a Array1<T>,
+        wall_source: f64,
+    ) -> RadialTerms<'a, T> {
+        RadialTerms {
+            radius: &self.radius_grid,
+            dr: self.dr,
+            metric: &self.metric,
+            velocity,
+            diffusivity,
+            source: wall_source,
+            source_radius: SOURCE_RADIUS,
+            core: self.core_boundary,
+            edge: self.edge_condition(),
+            convection: self.convection,
+            stencil: self.stencil,
+        }
+    }
+
+    /// n_Z at r = 1: the SOL density with the reservoir, else `edge_boundary`.
+    fn edge_condition(&self) -> BoundaryCondition {
+        if self.sol.config.enabled {
+            BoundaryCondition::Dirichlet { value: self.sol.density() }
+        } else {
+            self.edge_boundary
+        }
+    }
+
+    /// 1D radial transport of n_Z.
+    fn radial_step(&mut self, wall_source: f64, dt: f64) {
+        let (velocity, diffusivity) = self.transport_coefficients();
+        let rates = match self.precision {
+            Precision::F64 => self.radial_terms(&velocity, &diffusivity, wall_source).rates(self.impurity_density.view()),
+            Precision::F32 => {
+                // Rates in f32, accumulated into the f64 profile
+                let single = |x: &Array1<f64>| x.mapv(|value| value as f32);
+                let (velocity, diffusivity) = (single(&velocity), single(&diffusivity));
+                let n = single(&self.impurity_density);
+                self.radial_terms(&velocity, &diffusivity, wall_source).rates(n.view()).mapv(f64::from)
+            }
+        };
+        let mut new_nz = self.impurity_density.clone();
+        for i in 1..self.nr - 1 {
+            new_nz[i] = (self.impurity_density[i] + rates[i] * dt).max(0.0);
+            new_nz[i] = new_nz[i].min(MAX_IMPURITY_DENSITY);
+        }
+
+        let touched = self.regularization.apply(&mut new_nz);
+        self.regularized_cells += touched as u64;
+        // Log at most every 100 ms so a persistent wiggle doesn't flood stdout
+        let (last_time, last_count) = self.regularization_reported;
+        if touched > 0 && last_time.is_none_or(|t| self.time - t >= 0.1) {
+            self.record_event(Event::Regularized {
+                method: self.regularization,
+                cells: self.regularized_cells - last_count,
+            });
+            self.regularization_reported = (Some(self.time), self.regularized_cells);
+        }
+
+        self.stencil.close(new_nz.view_mut(), self.core_boundary, self.edge_condition(), self.dr);
+
+        self.impurity_density = new_nz;
+    }
+
+    /// 2D (r, θ) transport; writes the poloidal average to `impurity_density`.
+    fn poloidal_step(&mut self, wall_source: f64, dt: f64) {
+        let (velocity, diffusivity) = self.transport_coefficients();
+        let terms = RadialTerms {
+            radius: &self.radius_grid,
+            dr: self.dr,
+            metric: &self.metric,
+            velocity: &velocity,
+            diffusivity: &diffusivity,
+            source: wall_source,
+            source_radius: SOURCE_RADIUS,
+            core: self.core_boundary,
+            edge: self.edge_condition(),
+            convection: self.convection,
+            stencil: self.stencil,
+        };
+        let poloidal = self.poloidal.as_mut().expect("2D solver enabled");
+        poloidal.sync(&self.radius_grid, &self.impurity_density);
+        self.impurity_density = poloidal.step(&terms, dt);
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        if self.terminated() {
+            // Only the clock runs, so loops bounded by t_max still end
+            self.time += dt;
+            return;
+        }
+
+        let (density_factor, phase) = self.ramp_tracker.step(&self.ramp, self.time);
+        if density_factor != 1.0 {
+            self.scale_electron_density(density_factor);
+        }
+        if let Some(phase) = phase {
+            self.record_event(Event::PhaseStarted { phase });
+        }
+
+        // Pulse termination (start is handled by apply_action)
+        if self.confinement_mode == ConfinementMode::TurbulencePulse {
+            if let Some(start) = self.pulse_start_time {
+                if self.time - start > self.pulse_duration {
+                    self.record_event(Event::PulseEnded { cooldown: self.cooldown_duration });
+                    self.confinement_mode = ConfinementMode::Normal;
+                    self.mode_since = self.time;
+                    self.last_pulse_end_time = Some(self.time);  // ⭐
+                    self.pulse_start_time = None;
+                }
+            }
+        }
+        self.update_protection();
+
+        let pulse_commanded = matches!(
+            self.confinement_mode,
+            ConfinementMode::TurbulencePulse | ConfinementMode::EmergencyFlush
+        );
+        self.actuator.step(pulse_commanded, self.time, dt);
+
+        // ECRH follows the pulse command; heating relaxes by χ_e diffusion
+        // against the background balance that holds the initial profile
+        let had_budget = !self.ecrh.exhausted();
+        self.ecrh.step(pulse_commanded, dt);
+        if had_budget && self.ecrh.exhausted() {
+            self.record_event(Event::EcrhBudgetExhausted { energy: self.ecrh.energy_used() });
+        }
+        let radiating = matches!(self.confinement_mode, ConfinementMode::RadiativeCollapse | ConfinementMode::EmergencyFlush);
+        let strang = radiating && self.splitting == Splitting::Strang;
+        if strang {
+            self.radiate(0.5 * dt);
+        }
+        let heating = self.ecrh.heating_profile(&self.radius_grid, &self.electron_density);
+        let diffusion = self.temperature_diffusion();
+        let background = self.ramp.levels(self.time).heating;
+        for i in 0..self.nr - 1 {
+            let dt_dt = self.chi_e * (diffusion[i] + background * self.temperature_balance[i]) + heating[i];
+            self.electron_temp[i] = (self.electron_temp[i] + dt_dt * dt).max(0.0);
+        }
+        if strang {
+            self.radiate(0.5 * dt);
+        } else if radiating {
+            for i in 0..self.nr - 1 {
+                let cooling = self.termination.cooling_rate(self.electron_temp[i], self.impurity_density[i]);
+                self.electron_temp[i] = (self.electron_temp[i] + cooling * dt).max(0.0);
+            }
+        }
+
+        if self.pellet.scheduled(self.time) {
+            self.fire_pellet();
+        }
+        self.pellet.relax(&mut self.electron_density, dt);
+        if self.sawtooth.due(self.time, &self.radius_grid, &self.electron_temp) {
+            self.sawtooth_crash();
+        }
+        if self.elms.step(self.time) {
+            self.record_event(Event::ElmBurst { amplitude: self.elms.amplitude, duration: self.elms.duration });
+        }
+        self.update_neoclassical();
+        self.update_electric_field();
+        if self.turbulence_dynamics.enabled() {
+            let target = self.target_turbulence_profile();
+            let field = self.turbulence_field.as_slice_mut().expect("contiguous");
+            self.turbulence_dynamics.step(field, target.as_slice().expect("contiguous"), self.dr, dt);
+        }
+
+        let mean_turbulence = self.mean_turbulence();
+        self.confinement.step(mean_turbulence, self.ecrh.power(), dt);
+
+        // Transport sub-steps: enough to stay within the explicit limit
+        // (a pulse raises D_turb well above its Normal-mode value), at
+        // most max_substeps
+        let substeps = if self.max_substeps > 1 {
+            ((dt / self.stability_limit()).ceil() as usize).clamp(1, self.max_substeps)
+        } else {
+            1
+        };
+        let transport_dt = dt / substeps as f64;
+
+        // Explicit-step stability, checked every 100 ms and reported when
+        // the limit is first exceeded rather than on every check
+        if self.stability_checked.0.is_none_or(|t| self.time - t >= 0.1) {
+            let limit = self.stability_limit();
+            let exceeded = transport_dt > limit;
+            if exceeded && !self.stability_checked.1 {
+                self.record_event(Event::CflViolation { dt: transport_dt, limit });
+            }
+            self.stability_checked = (Some(self.time), exceeded);
+        }
+
+        // Transport equation
+        let edge = self.nr - 2;
+        let source_volume: f64 = (1..self.nr - 1)
+            .filter(|&i| self.radius_grid[i] > SOURCE_RADIUS)
+            .map(|i| self.metric.vprime[i] * self.dr)
+            .sum();
+        let mut main_ions = self.main_ions.enabled.then(|| self.main_ion_density());
+        // n_i sub-cycles within each transport sub-step when max_substeps
+        // caps the count below its own limit
+        let main_ion_cycles = if self.main_ions.enabled {
+            ((transport_dt / self.main_ion_stability_limit()).ceil() as usize).max(1)
+        } else {
+            1
+        };
+        let main_ion_dt = transport_dt / main_ion_cycles as f64;
+        for _ in 0..substeps {
+            // Outflow of the last interior cell through its outer face
+            let outflow = self.metric.vprime_outer[edge] * self.calculate_flux(edge);
+            let returned = if self.sol.config.enabled {
+                let exhaust = self.sol.step(outflow, transport_dt, source_volume);
+                exhaust.leakage + self.recycling.step(exhaust.parallel, transport_dt, source_volume)
+            } else {
+                self.recycling.step(outflow, transport_dt, source_volume)
+            };
+            let wall_source = self.wall_source() + returned;
+            self.balance.source += wall_source * source_volume * transport_dt;
+            self.balance.outflow += outflow * transport_dt;
+            if self.poloidal.is_some() {
+                self.poloidal_step(wall_source, transport_dt);
+            } else {
+                self.radial_step(wall_source, transport_dt);
+            }
+            if let Some(n_i) = &mut main_ions {
+                for _ in 0..main_ion_cycles {
+                    let rates = self.main_ion_rates(n_i) + &self.main_ion_balance;
+                    for i in 0..self.nr - 1 {
+                        n_i[i] = (n_i[i] + rates[i] * main_ion_dt).max(0.0);
+                    }
+                }
+            }
+        }
+        // Quasineutrality
+        if let Some(n_i) = main_ions {
+            let z = self.impurity_charge;
+            self.electron_density = n_i + &self.impurity_density.mapv(|n| z * n);
+        }
+
+        self.last_sample = Sample {
+            time: self.time,
+            center_impurity: self.impurity_density[0],
+            edge_impurity: self.impurity_density[self.nr - 1],
+            turbulence: self.calculate_turbulence_level(self.nr - 2),
+            center_zeff: self.charge_balance(0).0,
+            center_dilution: self.charge_balance(0).1,
+            stored_energy: self.confinement.stored_energy(),
+        };
+        self.last_sample_due = self.history.push(&self.last_sample);
+
+        self.time += dt;
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn save_to_csv(&self, filename: &str) -> std::io::Result<()> {
+        let file = File::create(filename)?;
+        let mut writer = BufWriter::new(file);
+
+        let range = self.history.export_range(f64::NEG_INFINITY, f64::INFINITY, &Channel::ALL, 1);
+        let names: Vec<&str> = range.channels.iter().map(|c| c.name()).collect();
+        writeln!(writer, "time,{}", names.join(","))?;
+        for i in 0..range.len() {
+            writeln!(
+                writer,
+                "{:.6},{:.6e},{:.6e},{:.4},{:.4},{:.4},{:.4}",
+                range.time[i],
+                range.values[0][i],
+                range.values[1][i],
+                range.values[2][i],
+                range.values[3][i],
+                range.values[4][i],
+                range.values[5][i]
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Snapshot of the full state (profiles, mode, timers, history).
+    /// `.json` paths are written as JSON, anything else as CBOR (binary,
+    /// but self-describing, as the internally tagged enums need).
+    #[cfg(feature = "fs")]
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        write_checkpoint(self, path.as_ref())
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn load_checkpoint<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let mut state: Self = read_checkpoint(path.as_ref())?;
+        state.turbulence.load_plugins()?;
+        Ok(state)
+    }
+}
+
+/// Writes `value` as JSON to `.json` paths, as CBOR to anything else.
+#[cfg(feature = "fs")]
+pub(crate) fn write_checkpoint<T: Serialize>(value: &T, path: &Path) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    if is_json(path) {
+        serde_json::to_writer(&mut writer, value).map_err(std::io::Error::other)?;
+    } else {
+        ciborium::into_writer(value, &mut writer).map_err(std::io::Error::other)?;
+    }
+    writer.flush()
+}
+
+/// Reads what `write_checkpoint` wrote to `path`.
+#[cfg(feature = "fs")]
+pub(crate) fn read_checkpoint<T: serde::de::DeserializeOwned>(path: &Path) -> std::io::Result<T> {
+    let reader = BufReader::new(File::open(path)?);
+    if is_json(path) {
+        serde_json::from_reader(reader).map_err(std::io::Error::other)
+    } else {
+        ciborium::from_reader(reader).map_err(std::io::Error::other)
+    }
+}
+
+#[cfg(feature = "fs")]
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
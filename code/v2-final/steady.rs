@@ -0,0 +1,221 @@
+//! # Steady-State Impurity Profile
+//!
+//! n_Z(r) with ∂n_Z/∂t = 0 for the transport coefficients of a state as
+//! they are (D, v, and the pulse level frozen), solved directly instead of
+//! by time stepping. The discretization is that of the 1D transport step,
+//!
+//! ```text
+//! (V'₊ Γ_i − V'₋ Γ_{i−1}) / (V' dr) = S_i,   Γ_i = v_i n_i − D_i (n_{i+1} − n_{i−1}) / 2dr
+//! ```
+//!
+//! with the boundary conditions as linear relations n_b = α n_nb + β, so
+//! a converged profile is also a fixed point of the time stepper. The
+//! matrix does not depend on n_Z; the wall source can (sputtering, and
+//! recycling, which returns `coefficient` × the edge outflow in steady
+//! state), so the source is updated by damped fixed-point iteration with
+//! the factorization reused. A constant source converges in one step.
+//!
+//! A solution with negative densities is not physical: the inward pinch
+//! outruns the losses, and a time-dependent run accumulates without bound
+//! (until the n_Z clamp) instead of settling. That is the accumulation
+//! side of the boundary, reported as `bounded = false`. Clamps,
+//! regularization, `numerics.convection` and `numerics.stencil` (always
+//! central, second order here), and the poloidal solver are not modelled.
+
+use crate::source::{SourceModel, SOURCE_RADIUS};
+use crate::state::StellaratorState;
+use ndarray::{Array1, Array2};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SteadyStateConfig {
+    pub tolerance: f64,        // Relative max-norm change of n_Z between iterations
+    pub max_iterations: usize,
+    pub relaxation: f64,       // Fraction of each update applied, (0, 1]
+    pub output: String,        // Profile written by the `steady` subcommand (CSV)
+}
+
+impl Default for SteadyStateConfig {
+    fn default() -> Self {
+        SteadyStateConfig {
+            tolerance: 1e-8,
+            max_iterations: 100,
+            relaxation: 1.0,
+            output: "steady_state.csv".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SteadyState {
+    pub impurity_density: Array1<f64>, // m⁻³
+    pub wall_source: f64,              // m⁻³/s, including recycling
+    pub iterations: usize,
+    pub change: f64,                   // Relative change in the last iteration
+    pub converged: bool,
+    pub bounded: bool,                 // No negative densities
+}
+
+impl SteadyState {
+    /// n_Z(0) / ⟨n_Z⟩, the core accumulation measure (1 = flat).
+    pub fn peaking(&self, state: &StellaratorState) -> f64 {
+        let n = &self.impurity_density;
+        let (mut integral, mut volume) = (0.0, 0.0);
+        for i in 1..state.nr - 1 {
+            let weight = state.metric.vprime[i] * state.dr;
+            integral += n[i] * weight;
+            volume += weight;
+        }
+        if integral > 0.0 { n[0] * volume / integral } else { f64::NAN }
+    }
+}
+
+/// Steady-state n_Z for `state`. `None` if the discrete operator is
+/// singular (e.g. no loss at either boundary).
+pub fn solve(state: &StellaratorState, settings: &SteadyStateConfig) -> Option<SteadyState> {
+    let nr = state.nr;
+    let dr = state.dr;
+    let metric = &state.metric;
+
+    // Γ_i = advection[i] n_i − diffusion[i] (n_{i+1} − n_{i−1}) / 2dr; Γ_0 = Γ_{nr−1} = 0
+    let (advection, diffusion) = state.transport_coefficients();
+    // Adds weight · Γ_j to row `row`
+    let add_flux = |matrix: &mut Array2<f64>, row: usize, j: usize, weight: f64| {
+        if j == 0 || j >= nr - 1 {
+            return;
+        }
+        let gradient = weight * diffusion[j] / (2.0 * dr);
+        matrix[[row, j]] += weight * advection[j];
+        matrix[[row, j + 1]] -= gradient;
+        matrix[[row, j - 1]] += gradient;
+    };
+
+    let mut matrix = Array2::zeros((nr, nr));
+    let (core_slope, core_offset) = state.core_boundary.linear(-dr);
+    let (edge_slope, edge_offset) = state.edge_boundary.linear(dr);
+    matrix[[0, 0]] = 1.0;
+    matrix[[0, 1]] = -core_slope;
+    matrix[[nr - 1, nr - 1]] = 1.0;
+    matrix[[nr - 1, nr - 2]] = -edge_slope;
+    for i in 1..nr - 1 {
+        let (outer, inner) = if state.radius_grid[i] > 0.01 {
+            let cell = metric.vprime[i] * dr;
+            (metric.vprime_outer[i] / cell, metric.vprime_inner[i] / cell)
+        } else {
+            (1.0 / dr, 1.0 / dr)
+        };
+        add_flux(&mut matrix, i, i, outer);
+        add_flux(&mut matrix, i, i - 1, -inner);
+    }
+    let lu = Lu::factor(matrix)?;
+
+    let in_source = |i: usize| state.radius_grid[i] > SOURCE_RADIUS;
+    let source_volume: f64 = (1..nr - 1).filter(|&i| in_source(i)).map(|i| metric.vprime[i] * dr).sum();
+    let edge = nr - 2;
+    let edge_flux = |n: &Array1<f64>| {
+        advection[edge] * n[edge] - diffusion[edge] * (n[edge + 1] - n[edge - 1]) / (2.0 * dr)
+    };
+    let wall_source = |n: &Array1<f64>| {
+        let flux = edge_flux(&n.mapv(|v| v.max(0.0)));
+        let wall = match state.source_model {
+            SourceModel::Constant => state.constant_source(),
+            SourceModel::Sputtering(sputtering) => sputtering.rate(
+                state.electron_temp[edge],
+                state.calculate_turbulence_level(edge) / state.d_turb_base.max(1e-12),
+                flux,
+            ),
+        };
+        // Recycling releases what it takes up once its inventory is steady
+        let outflow = metric.vprime_outer[edge] * flux;
+        let recycled = state.recycling.coefficient * outflow.max(0.0) / source_volume;
+        wall + recycled
+    };
+
+    let relaxation = settings.relaxation.clamp(1e-3, 1.0);
+    let mut n = state.impurity_density.clone();
+    let mut result = SteadyState {
+        impurity_density: n.clone(),
+        wall_source: wall_source(&n),
+        iterations: 0,
+        change: f64::INFINITY,
+        converged: false,
+        bounded: true,
+    };
+    for iteration in 1..=settings.max_iterations.max(1) {
+        let source = wall_source(&n);
+        let mut rhs = Array1::zeros(nr);
+        rhs[0] = core_offset;
+        rhs[nr - 1] = edge_offset;
+        for i in (1..nr - 1).filter(|&i| in_source(i)) {
+            rhs[i] = source;
+        }
+        let next = lu.solve(rhs);
+        let scale = next.iter().fold(0.0_f64, |m, v| m.max(v.abs())).max(1e-300);
+        let change = (&next - &n).iter().fold(0.0_f64, |m, v| m.max(v.abs())) / scale;
+        n = &n + &((&next - &n) * relaxation);
+        result = SteadyState {
+            impurity_density: n.clone(),
+            wall_source: source,
+            iterations: iteration,
+            change,
+            converged: change < settings.tolerance,
+            bounded: n.iter().all(|&v| v >= 0.0),
+        };
+        if result.converged {
+            break;
+        }
+    }
+    Some(result)
+}
+
+/// Dense LU factorization with partial pivoting.
+struct Lu {
+    lu: Array2<f64>,
+    pivots: Vec<usize>,
+}
+
+impl Lu {
+    fn factor(mut a: Array2<f64>) -> Option<Self> {
+        let n = a.nrows();
+        let mut pivots = Vec::with_capacity(n);
+        for k in 0..n {
+            let p = (k..n).max_by(|&i, &j| a[[i, k]].abs().total_cmp(&a[[j, k]].abs()))?;
+            if a[[p, k]].abs() < 1e-300 {
+                return None;
+            }
+            if p != k {
+                for j in 0..n {
+                    a.swap([k, j], [p, j]);
+                }
+            }
+            pivots.push(p);
+            for i in k + 1..n {
+                let factor = a[[i, k]] / a[[k, k]];
+                a[[i, k]] = factor;
+                if factor != 0.0 {
+                    for j in k + 1..n {
+                        a[[i, j]] -= factor * a[[k, j]];
+                    }
+                }
+            }
+        }
+        Some(Lu { lu: a, pivots })
+    }
+
+    fn solve(&self, mut b: Array1<f64>) -> Array1<f64> {
+        let n = b.len();
+        for (k, &p) in self.pivots.iter().enumerate() {
+            b.swap(k, p);
+        }
+        for i in 0..n {
+            let sum: f64 = (0..i).map(|j| self.lu[[i, j]] * b[j]).sum();
+            b[i] -= sum;
+        }
+        for i in (0..n).rev() {
+            let sum: f64 = (i + 1..n).map(|j| self.lu[[i, j]] * b[j]).sum();
+            b[i] = (b[i] - sum) / self.lu[[i, i]];
+        }
+        b
+    }
+}
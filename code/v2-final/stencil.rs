@@ -0,0 +1,112 @@
+//! # Spatial Stencil
+//!
+//! Order of the radial transport discretization (`[numerics] stencil`):
+//!
+//! - `second` (v2): Γ at grid point i from the three-point gradient,
+//!   differenced as the flux through the face between i and i + 1, with
+//!   the axis cell in planar form. Second order for steady profiles, first
+//!   order in time-dependent problems (`benchmark.rs`); nr ≈ 400 for
+//!   per-cent accuracy on peaked profiles.
+//! - `fourth`: Γ on the faces i ± ½ from four-point stencils,
+//!
+//!   ```text
+//!   n_{i+½} = (−n_{i−1} + 9 n_i + 9 n_{i+1} − n_{i+2}) / 16
+//!   ∂n/∂r   = (n_{i−1} − 27 n_i + 27 n_{i+1} − n_{i+2}) / 24 dr
+//!   ```
+//!
+//!   (D and v interpolated like n), and the divergence of F = V' Γ as
+//!   [27 (F_{i+½} − F_{i−½}) − (F_{i+3/2} − F_{i−3/2})] / 24 dr. Points
+//!   and faces beyond the grid are cubic extrapolations, and the boundary
+//!   values use one-sided fourth-order gradients
+//!   (`BoundaryCondition::value_fourth`). Fourth order in dr; nr ≈ 50
+//!   matches `second` at nr ≈ 400. Needs nr ≥ 6 and an explicit step
+//!   ~27 % shorter (`diffusive_limit`).
+//!
+//! Only the diffusive part is fourth order with an upwind or limited
+//! `convection` scheme, which blends towards n_{i+½} instead of n_i.
+//! The balance diagnostics (`calculate_flux`) keep the three-point form.
+
+use crate::boundary::BoundaryCondition;
+use crate::precision::Real;
+use ndarray::{ArrayView1, ArrayViewMut1};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stencil {
+    #[default]
+    Second,
+    Fourth,
+}
+
+impl Stencil {
+    /// Smallest grid the stencil works on.
+    pub const fn min_points(&self) -> usize {
+        match self {
+            Stencil::Second => 3,
+            Stencil::Fourth => 6,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stencil::Second => "second",
+            Stencil::Fourth => "fourth",
+        }
+    }
+
+    /// Largest stable explicit step (s) for diffusivity `d`: dr² / (2 D)
+    /// (v2), and 18 dr² / (49 D) at fourth order, whose Laplacian reaches
+    /// (56 / 24)² / dr² = 49 / 9 dr² at the grid scale instead of 4 / dr².
+    pub fn diffusive_limit(&self, dr: f64, d: f64) -> f64 {
+        if d <= 0.0 {
+            return f64::INFINITY;
+        }
+        match self {
+            Stencil::Second => dr * dr / (2.0 * d),
+            Stencil::Fourth => 18.0 * dr * dr / (49.0 * d),
+        }
+    }
+
+    /// Sets n at both grid ends from the interior.
+    pub fn close(&self, mut n: ArrayViewMut1<f64>, core: BoundaryCondition, edge: BoundaryCondition, dr: f64) {
+        let last = n.len() - 1;
+        match self {
+            Stencil::Second => {
+                n[0] = core.value(n[1], -dr);
+                n[last] = edge.value(n[last - 1], dr);
+            }
+            Stencil::Fourth => {
+                n[0] = core.value_fourth([1, 2, 3, 4].map(|k| n[k]), -dr);
+                n[last] = edge.value_fourth([1, 2, 3, 4].map(|k| n[last - k]), dr);
+            }
+        }
+    }
+}
+
+/// x at index `j` (possibly off the grid), with points outside
+/// `first..=last` extrapolated by the cubic through the nearest four.
+pub fn extended<T: Real>(x: ArrayView1<T>, j: isize, first: usize, last: usize) -> T {
+    if j >= first as isize && j <= last as isize {
+        return x[j as usize];
+    }
+    let start = if j < first as isize { first } else { last - 3 };
+    let t = (j - start as isize) as f64;
+    let weights = [
+        -(t - 1.0) * (t - 2.0) * (t - 3.0) / 6.0,
+        t * (t - 2.0) * (t - 3.0) / 2.0,
+        -t * (t - 1.0) * (t - 3.0) / 2.0,
+        t * (t - 1.0) * (t - 2.0) / 6.0,
+    ];
+    (0..4).fold(T::zero(), |sum, k| sum + T::of(weights[k]) * x[start + k])
+}
+
+/// Value half-way between the middle two of four equally spaced points.
+pub fn face_value<T: Real>(x: [T; 4]) -> T {
+    (-x[0] + T::of(9.0) * x[1] + T::of(9.0) * x[2] - x[3]) / T::of(16.0)
+}
+
+/// Derivative half-way between the middle two of four points `dr` apart.
+pub fn face_gradient<T: Real>(x: [T; 4], dr: f64) -> T {
+    (x[0] - T::of(27.0) * x[1] + T::of(27.0) * x[2] - x[3]) / T::of(24.0 * dr)
+}
@@ -0,0 +1,182 @@
+//! # Run Summary
+//!
+//! Control-performance figures of merit accumulated step by step: pulse
+//! count and duty cycle, time-averaged and peak n_Z(0), time spent above
+//! a critical central density, and the impurity inventory ∫ n_Z V' dρ at
+//! the start and end of the run with the input and edge outflow that
+//! should account for the difference (`balance`). Alongside: the
+//! confinement lost to the pulses (`confinement`), the edge bursts with
+//! the pulses that followed them (`elm`), whether the discharge collapsed
+//! or was lost (`termination`), the pulses started during ramp-up and
+//! ramp-down (`ramp`), and where the flushed impurities went (`sol`). Scans, ensembles, and the optimizers
+//! compare runs by these; a single run writes them to `output.summary`.
+
+use crate::balance::ParticleBalance;
+#[cfg(feature = "fs")]
+use crate::metadata::RunMetadata;
+use crate::ramp::Phase;
+use crate::state::{ConfinementMode, StellaratorState};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::io::{self, BufWriter, Write};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// Figures of merit of one closed-loop run.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub duration: f64,              // s simulated
+    pub final_center_impurity: f64, // m⁻³
+    pub peak_center_impurity: f64,  // m⁻³
+    pub mean_center_impurity: f64,  // m⁻³, time average
+    pub pulses: usize,
+    pub duty_cycle: f64,            // Fraction of time in TurbulencePulse
+    pub critical_density: f64,      // m⁻³
+    pub time_above_critical: f64,   // s with n_Z(0) > critical_density
+    pub initial_inventory: f64,     // ∫ n_Z V' dρ, V' as given by the metric
+    pub final_inventory: f64,
+    pub source_input: f64,          // Same units, entered during the run
+    pub outflow: f64,               // Same units, lost through the edge
+    pub conservation_error: f64,    // Relative, over the whole discharge
+    pub ecrh_energy: f64,           // MJ of ECRH heating consumed
+    pub mean_stored_energy: f64,    // MJ, time average
+    pub confinement_loss: f64,      // 1 − mean stored energy / reference
+    #[serde(default)]
+    pub elm_bursts: u64,
+    #[serde(default)]
+    pub pulses_after_elm: usize,    // Pulses started within elms.attribution_window of a burst
+    #[serde(default)]
+    pub collapses: usize,           // Entries into RadiativeCollapse
+    #[serde(default)]
+    pub terminated_at: Option<f64>, // s, time the discharge was terminated
+    #[serde(default)]
+    pub pulses_in_ramps: usize,     // Pulses started outside the flat top
+    #[serde(default)]
+    pub sol_inventory: f64,         // ∫ V' n_Z dr held in the SOL at the end
+    #[serde(default)]
+    pub divertor_exhaust: f64,      // Same units, sent to the divertor during the run
+}
+
+impl RunSummary {
+    /// The figures of merit with a `metadata` field alongside.
+    #[cfg(feature = "fs")]
+    pub fn write_json<P: AsRef<Path>>(&self, path: P, metadata: &RunMetadata) -> io::Result<()> {
+        #[derive(Serialize)]
+        struct Stamped<'a> {
+            metadata: &'a RunMetadata,
+            #[serde(flatten)]
+            summary: &'a RunSummary,
+        }
+        let mut writer = BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, &Stamped { metadata, summary: self }).map_err(io::Error::other)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+}
+
+/// Accumulates a `RunSummary`; call `observe` after every step.
+pub struct SummaryTracker {
+    critical_density: f64,
+    start_time: f64,
+    initial_inventory: f64,
+    start_balance: ParticleBalance,
+    peak: f64,
+    integral: f64,
+    pulses: usize,
+    pulses_after_elm: usize,
+    start_elms: u64,
+    collapses: usize,
+    terminated_at: Option<f64>,
+    pulses_in_ramps: usize,
+    start_exhausted: f64,
+    pulse_time: f64,
+    time_above_critical: f64,
+    energy_integral: f64,
+    last_mode: ConfinementMode,
+}
+
+impl SummaryTracker {
+    pub fn new(state: &StellaratorState, critical_density: f64) -> Self {
+        SummaryTracker {
+            critical_density,
+            start_time: state.time,
+            initial_inventory: state.impurity_inventory(),
+            start_balance: state.balance,
+            peak: state.impurity_density[0],
+            integral: 0.0,
+            pulses: 0,
+            pulses_after_elm: 0,
+            start_elms: state.elms.bursts(),
+            collapses: 0,
+            terminated_at: None,
+            pulses_in_ramps: 0,
+            start_exhausted: state.sol.exhausted(),
+            pulse_time: 0.0,
+            time_above_critical: 0.0,
+            energy_integral: 0.0,
+            last_mode: state.confinement_mode,
+        }
+    }
+
+    /// Folds in the state after a step of length `dt`.
+    pub fn observe(&mut self, state: &StellaratorState, dt: f64) {
+        let center = state.impurity_density[0];
+        self.peak = self.peak.max(center);
+        self.integral += center * dt;
+        self.energy_integral += state.confinement.stored_energy() * dt;
+        if center > self.critical_density {
+            self.time_above_critical += dt;
+        }
+        let mode = state.confinement_mode;
+        if mode == ConfinementMode::TurbulencePulse {
+            self.pulse_time += dt;
+            if self.last_mode == ConfinementMode::Normal {
+                self.pulses += 1;
+                let elms = &state.elms;
+                if elms.last_onset().is_some_and(|onset| state.time - onset <= elms.attribution_window) {
+                    self.pulses_after_elm += 1;
+                }
+                if state.ramp.phase(state.time) != Phase::FlatTop {
+                    self.pulses_in_ramps += 1;
+                }
+            }
+        }
+        if mode != self.last_mode {
+            match mode {
+                ConfinementMode::RadiativeCollapse => self.collapses += 1,
+                ConfinementMode::Terminated => self.terminated_at = Some(state.time),
+                _ => {}
+            }
+        }
+        self.last_mode = mode;
+    }
+
+    pub fn finish(&self, state: &StellaratorState, dt: f64) -> RunSummary {
+        let duration = (state.time - self.start_time).max(dt);
+        RunSummary {
+            duration,
+            final_center_impurity: state.impurity_density[0],
+            peak_center_impurity: self.peak,
+            mean_center_impurity: self.integral / duration,
+            pulses: self.pulses,
+            duty_cycle: self.pulse_time / duration,
+            critical_density: self.critical_density,
+            time_above_critical: self.time_above_critical,
+            initial_inventory: self.initial_inventory,
+            final_inventory: state.impurity_inventory(),
+            source_input: state.balance.source - self.start_balance.source,
+            outflow: state.balance.outflow - self.start_balance.outflow,
+            conservation_error: state.conservation_error(),
+            ecrh_energy: state.ecrh.energy_used(),
+            mean_stored_energy: self.energy_integral / duration,
+            confinement_loss: 1.0 - self.energy_integral / duration / state.confinement.reference_energy().max(1e-12),
+            elm_bursts: state.elms.bursts() - self.start_elms,
+            pulses_after_elm: self.pulses_after_elm,
+            collapses: self.collapses,
+            terminated_at: self.terminated_at,
+            pulses_in_ramps: self.pulses_in_ramps,
+            sol_inventory: state.sol.inventory(),
+            divertor_exhaust: state.sol.exhausted() - self.start_exhausted,
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! # Neural-Network Surrogate
+//!
+//! Small fully connected network (e.g. a QuaLiKiz-style transport
+//! surrogate) evaluated in pure Rust, cheap enough to run at every grid
+//! point and step. Weights are read from JSON:
+//!
+//! ```text
+//! {
+//!   "input_mean":  [4 values],       // optional, default 0
+//!   "input_scale": [4 values],       // optional, default 1
+//!   "layers": [
+//!     { "weights": [[...], ...], "bias": [...], "activation": "relu" },
+//!     ...
+//!     { "weights": [[...], [...]], "bias": [d, v] }  // linear output
+//!   ]
+//! }
+//! ```
+//!
+//! `weights` is row-major (outputs × inputs). The inputs are
+//! (a/L_n, a/L_T, ln ν*_e, pulse level), each normalized as
+//! (x − mean) / scale; the outputs are D (m²/s) and V (m/s).
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::io;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+pub const INPUTS: usize = 4;
+pub const OUTPUTS: usize = 2;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Activation {
+    #[default]
+    Linear,
+    Relu,
+    Tanh,
+    Softplus,
+}
+
+impl Activation {
+    fn apply(&self, x: f64) -> f64 {
+        match self {
+            Activation::Linear => x,
+            Activation::Relu => x.max(0.0),
+            Activation::Tanh => x.tanh(),
+            Activation::Softplus => {
+                if x > 30.0 {
+                    x
+                } else {
+                    x.exp().ln_1p()
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Layer {
+    pub weights: Vec<Vec<f64>>, // outputs × inputs
+    pub bias: Vec<f64>,
+    #[serde(default)]
+    pub activation: Activation,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Mlp {
+    #[serde(default)]
+    pub input_mean: Vec<f64>,
+    #[serde(default)]
+    pub input_scale: Vec<f64>,
+    pub layers: Vec<Layer>,
+}
+
+impl Mlp {
+    /// Forward pass.
+    pub fn evaluate(&self, inputs: [f64; INPUTS]) -> [f64; OUTPUTS] {
+        let mut x: Vec<f64> = inputs
+            .iter()
+            .enumerate()
+            .map(|(k, &v)| {
+                let mean = self.input_mean.get(k).copied().unwrap_or(0.0);
+                let scale = self.input_scale.get(k).copied().unwrap_or(1.0);
+                (v - mean) / scale
+            })
+            .collect();
+        for layer in &self.layers {
+            x = layer
+                .weights
+                .iter()
+                .zip(&layer.bias)
+                .map(|(row, b)| {
+                    let sum: f64 = row.iter().zip(&x).map(|(w, xi)| w * xi).sum();
+                    layer.activation.apply(sum + b)
+                })
+                .collect();
+        }
+        [x[0], x[1]]
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let mlp: Mlp = serde_json::from_str(&text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        mlp.validate()
+    }
+
+    /// Checks that the layer shapes chain from `INPUTS` to `OUTPUTS`.
+    #[cfg(feature = "fs")]
+    fn validate(self) -> io::Result<Self> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        for (name, values) in [("input_mean", &self.input_mean), ("input_scale", &self.input_scale)] {
+            if !values.is_empty() && values.len() != INPUTS {
+                return Err(invalid(format!("{} needs {} values", name, INPUTS)));
+            }
+        }
+        if self.input_scale.contains(&0.0) {
+            return Err(invalid("input_scale must be nonzero".to_string()));
+        }
+        let mut width = INPUTS;
+        for (k, layer) in self.layers.iter().enumerate() {
+            if layer.weights.is_empty()
+                || layer.bias.len() != layer.weights.len()
+                || layer.weights.iter().any(|row| row.len() != width)
+            {
+                return Err(invalid(format!(
+                    "layer {}: expected {} inputs and one bias per output",
+                    k, width
+                )));
+            }
+            width = layer.weights.len();
+        }
+        if width != OUTPUTS {
+            return Err(invalid(format!("network has {} outputs, expected D and V", width)));
+        }
+        Ok(self)
+    }
+}
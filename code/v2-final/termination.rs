@@ -0,0 +1,109 @@
+//! # Radiative Collapse and Safe Abort
+//!
+//! What happens when control fails. With `enabled`, the confinement state
+//! machine gains three states beyond Normal / TurbulencePulse:
+//! ```text
+//! Normal, TurbulencePulse ──n_Z(0) > collapse_density──▶ RadiativeCollapse
+//! RadiativeCollapse ──response_time elapsed──▶ EmergencyFlush
+//! EmergencyFlush ──n_Z(0) < recovery_density──▶ Normal
+//! EmergencyFlush ──flush_duration elapsed──▶ Terminated
+//! RadiativeCollapse, EmergencyFlush ──T_e(0) < termination_temperature──▶ Terminated
+//! ```
+//! During a collapse and the flush, impurity radiation cools T_e,
+//! `dT_e/dt = −T_e · (n_Z / collapse_density) / cooling_time`, against the
+//! χ_e reheating (split from it as set by `[numerics] splitting`, see
+//! `splitting.rs`). The flush is machine protection rather than the
+//! controller: a pulse at `flush_amplitude` in the default window, held
+//! until recovery or termination; controller requests are ignored from
+//! the collapse until the return to Normal. `Terminated` is absorbing:
+//! the profiles freeze, and `Simulation::run` and single runs stop there.
+//!
+//! Disabled (the default), the v2 two-state machine runs unchanged.
+
+use crate::state::ConfinementMode;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Termination {
+    pub enabled: bool,
+    pub collapse_density: f64,        // m⁻³, n_Z(0) at which radiation takes over
+    pub cooling_time: f64,            // s, T_e radiative cooling time at collapse_density
+    pub response_time: f64,           // s, collapse to emergency flush (protection latency)
+    pub flush_amplitude: f64,         // D_turb enhancement of the flush
+    pub flush_duration: f64,          // s, flush allowed before termination
+    pub recovery_density: f64,        // m⁻³, n_Z(0) that ends the flush
+    pub termination_temperature: f64, // keV, T_e(0) of a lost discharge
+}
+
+impl Default for Termination {
+    fn default() -> Self {
+        Termination {
+            enabled: false,
+            collapse_density: 3e19,
+            cooling_time: 0.05,
+            response_time: 0.01,
+            flush_amplitude: 10.0,
+            flush_duration: 0.5,
+            recovery_density: 1e19,
+            termination_temperature: 0.1,
+        }
+    }
+}
+
+/// What the transition checks look at.
+#[derive(Clone, Copy, Debug)]
+pub struct Observed {
+    pub center_impurity: f64, // m⁻³
+    pub center_temp: f64,     // keV
+    pub time_in_mode: f64,    // s since entering the current mode
+}
+
+impl Termination {
+    /// The next mode and the reason for leaving `mode`, or `None` to stay.
+    pub fn transition(&self, mode: ConfinementMode, observed: Observed) -> Option<(ConfinementMode, String)> {
+        if !self.enabled {
+            return None;
+        }
+        let Observed { center_impurity, center_temp, time_in_mode } = observed;
+        match mode {
+            ConfinementMode::Normal | ConfinementMode::TurbulencePulse if center_impurity > self.collapse_density => {
+                Some((
+                    ConfinementMode::RadiativeCollapse,
+                    format!("n_Z(0) = {:.2e} m⁻³ above {:.1e} m⁻³", center_impurity, self.collapse_density),
+                ))
+            }
+            ConfinementMode::RadiativeCollapse | ConfinementMode::EmergencyFlush
+                if center_temp < self.termination_temperature =>
+            {
+                Some((
+                    ConfinementMode::Terminated,
+                    format!("T_e(0) = {:.3} keV below {:.3} keV", center_temp, self.termination_temperature),
+                ))
+            }
+            ConfinementMode::RadiativeCollapse if time_in_mode >= self.response_time => {
+                Some((ConfinementMode::EmergencyFlush, "machine protection response".to_string()))
+            }
+            ConfinementMode::EmergencyFlush if center_impurity < self.recovery_density => Some((
+                ConfinementMode::Normal,
+                format!("n_Z(0) = {:.2e} m⁻³ recovered below {:.1e} m⁻³", center_impurity, self.recovery_density),
+            )),
+            ConfinementMode::EmergencyFlush if time_in_mode >= self.flush_duration => Some((
+                ConfinementMode::Terminated,
+                format!("no recovery within {:.2} s of flushing", self.flush_duration),
+            )),
+            _ => None,
+        }
+    }
+
+    /// dT_e/dt (keV/s) from impurity radiation at one grid point.
+    pub fn cooling_rate(&self, electron_temp: f64, impurity_density: f64) -> f64 {
+        -electron_temp * (impurity_density / self.collapse_density) / self.cooling_time
+    }
+
+    /// T_e (keV) after radiating for `h` seconds at fixed n_Z: the exact
+    /// solution of `cooling_rate`, stable at any `h`.
+    pub fn radiate(&self, electron_temp: f64, impurity_density: f64, h: f64) -> f64 {
+        electron_temp * (-(impurity_density / self.collapse_density) / self.cooling_time * h).exp()
+    }
+}
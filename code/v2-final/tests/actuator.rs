@@ -0,0 +1,59 @@
+//! Actuator latency and rise / fall dynamics.
+
+use w7x_turbulence_control::actuator::Actuator;
+
+const DT: f64 = 1e-4;
+
+/// Outputs after each step of `DT` from t = 0 under `command(t)`.
+fn outputs(actuator: &mut Actuator, steps: usize, command: impl Fn(f64) -> bool) -> Vec<(f64, f64)> {
+    (0..steps)
+        .map(|k| {
+            let time = k as f64 * DT;
+            (time, actuator.step(command(time), time, DT))
+        })
+        .collect()
+}
+
+#[test]
+fn ideal_switches_instantly() {
+    let mut actuator = Actuator::ideal();
+    assert_eq!(actuator.step(true, 0.0, DT), 1.0);
+    assert_eq!(actuator.step(false, DT, DT), 0.0);
+}
+
+/// Nothing until the latency has passed, then 1 − e^(−t / rise_time).
+#[test]
+fn output_waits_for_the_latency_then_rises() {
+    let (latency, rise_time) = (0.01, 0.005);
+    let mut actuator = Actuator::new(latency, rise_time, 0.02);
+    let outputs = outputs(&mut actuator, 400, |_| true);
+
+    let start = outputs.iter().position(|&(_, output)| output > 0.0).unwrap();
+    assert!(outputs[start].0 >= latency - 1e-9 && outputs[start].0 - DT < latency + 1e-9);
+    assert!(outputs[..start].iter().all(|&(_, output)| output == 0.0));
+
+    for n in [1, 10, 50, 100, 200] {
+        let expected = 1.0 - (-(n as f64) * DT / rise_time).exp();
+        let (_, output) = outputs[start + n - 1];
+        assert!((output - expected).abs() < 1e-9, "after {n} steps: {output} vs {expected}");
+    }
+    // One rise time in: 1 − 1/e
+    let (_, output) = outputs[start + (rise_time / DT).round() as usize - 1];
+    assert!((output - (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+}
+
+/// Switching off is delayed by the same latency and decays with the fall time.
+#[test]
+fn output_falls_with_the_fall_time() {
+    let (latency, fall_time) = (0.002, 0.02);
+    let mut actuator = Actuator::new(latency, 0.0, fall_time);
+    let outputs = outputs(&mut actuator, 600, |t| t < 0.03);
+
+    let off = outputs.iter().position(|&(_, output)| output < 1.0 && output > 0.0).unwrap();
+    assert!(outputs[off].0 >= 0.03 + latency - 1e-9 && outputs[off].0 - DT < 0.03 + latency + 1e-9);
+    assert!(outputs[..off].iter().skip_while(|&&(_, output)| output == 0.0).all(|&(_, output)| output == 1.0));
+
+    let n = (fall_time / DT).round() as usize;
+    let (_, output) = outputs[off + n - 1];
+    assert!((output - (-1.0f64).exp()).abs() < 1e-9);
+}
@@ -0,0 +1,65 @@
+//! Statistics across finished runs.
+
+use w7x_turbulence_control::aggregate::{self, AggregateConfig};
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::history::Channel;
+use w7x_turbulence_control::metadata::RunMetadata;
+use w7x_turbulence_control::output::{create_sink, TraceFormat};
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::summary::SummaryTracker;
+
+/// Writes a run's trace and summary into `dir` like a single run does.
+fn write_run(dir: &std::path::Path, impurity_source: f64, format: TraceFormat) {
+    std::fs::create_dir_all(dir).unwrap();
+    let mut config = Config::default();
+    config.simulation.t_max = 0.1;
+    config.plasma.impurity_source = impurity_source;
+    let metadata = RunMetadata::collect(&config);
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    let name = if format == TraceFormat::Csv { "trace.csv" } else { "trace.bin" };
+    let mut sink = create_sink(format, dir.join(name), false, &metadata).unwrap();
+    let mut tracker = SummaryTracker::new(&sim.state, config.output.critical_density);
+    while sim.state.time < config.simulation.t_max {
+        sim.step();
+        tracker.observe(&sim.state, sim.dt);
+        if let Some(sample) = sim.state.recorded_sample() {
+            sink.write_sample(sample).unwrap();
+        }
+    }
+    sink.finish().unwrap();
+    tracker.finish(&sim.state, sim.dt).write_json(dir.join("summary.json"), &metadata).unwrap();
+}
+
+/// CSV and binary traces are both read; other files are skipped.
+#[test]
+fn combines_runs_in_a_directory() {
+    let root = std::env::temp_dir().join(format!("w7x_aggregate_{}", std::process::id()));
+    write_run(&root.join("low"), 1e17, TraceFormat::Csv);
+    write_run(&root.join("high"), 1e18, TraceFormat::Binary);
+    std::fs::write(root.join("notes.csv"), "a,b\n1,2\n").unwrap();
+
+    let settings = AggregateConfig {
+        directory: root.display().to_string(),
+        ..AggregateConfig::default()
+    };
+    let result = aggregate::run_aggregate(&settings).unwrap();
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(result.traces.len(), 2);
+    assert_eq!(result.summaries.len(), 2);
+    assert!(result.time.len() >= 10);
+    assert_eq!(result.histogram.iter().map(|&(_, runs)| runs).sum::<usize>(), 2);
+
+    let c = Channel::ALL.iter().position(|&c| c == Channel::EdgeImpurity).unwrap();
+    let j = result.time.len() - 1;
+    let (low, high) = (result.bands[c][0][j], result.bands[c][2][j]);
+    assert!(low < result.mean[c][j] && result.mean[c][j] < high);
+}
+
+/// Only points no other point beats on both axes survive.
+#[test]
+fn pareto_front_drops_dominated_points() {
+    let points = [(0.1, 5.0), (0.2, 3.0), (0.3, 4.0), (0.05, 9.0), (0.4, 1.0)];
+    assert_eq!(aggregate::pareto_front(&points), vec![3, 0, 1, 4]);
+}
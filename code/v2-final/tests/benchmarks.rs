@@ -0,0 +1,128 @@
+//! Analytic benchmarks of the radial transport step (see `benchmark.rs`).
+
+use ndarray::Array1;
+use w7x_turbulence_control::benchmark::Benchmark;
+use w7x_turbulence_control::boundary::BoundaryCondition;
+use w7x_turbulence_control::convection::Convection;
+use w7x_turbulence_control::geometry::Metric;
+use w7x_turbulence_control::poloidal::RadialTerms;
+use w7x_turbulence_control::stencil::Stencil;
+
+const GRIDS: [usize; 3] = [26, 51, 101];
+const COURANT: f64 = 0.5;
+/// Below the fourth-order limit, 18/49 of the second-order one
+const COURANT_FOURTH: f64 = 0.3;
+
+#[test]
+fn manufactured_solution_converges_at_first_order() {
+    let (errors, orders) = Benchmark::Manufactured.study(&GRIDS, COURANT);
+    let finest = errors.last().unwrap();
+    assert!(finest.l2 < 4e-3, "L2 error {:.3e} on nr = {}", finest.l2, finest.nr);
+    assert!(finest.max < 1.5e-2, "max error {:.3e} on nr = {}", finest.max, finest.nr);
+    for (l2, _) in &orders {
+        assert!(*l2 > 0.75, "observed L2 order {:.2}", l2);
+    }
+}
+
+#[test]
+fn zero_flux_steady_state_converges_at_second_order() {
+    let (errors, orders) = Benchmark::ZeroFlux.study(&GRIDS, COURANT);
+    let finest = errors.last().unwrap();
+    assert!(finest.l2 < 6e-3, "L2 error {:.3e} on nr = {}", finest.l2, finest.nr);
+    assert!(finest.max < 1e-2, "max error {:.3e} on nr = {}", finest.max, finest.nr);
+    for (l2, max) in &orders {
+        assert!(*l2 > 1.8 && *max > 1.7, "observed orders {:.2} (L2), {:.2} (max)", l2, max);
+    }
+}
+
+#[test]
+fn upwind_converges_at_first_order_and_limiters_at_second() {
+    let expected = [(Convection::Upwind, 0.9, 8e-2), (Convection::VanLeer, 1.8, 4e-3), (Convection::Koren, 1.8, 4e-3)];
+    for (convection, order, l2) in expected {
+        let (errors, orders) = Benchmark::ZeroFlux.study_with(&GRIDS, COURANT, convection, Stencil::Second);
+        let finest = errors.last().unwrap();
+        assert!(finest.l2 < l2, "{:?}: L2 error {:.3e} on nr = {}", convection, finest.l2, finest.nr);
+        for (observed, _) in &orders {
+            assert!(*observed > order, "{:?}: observed L2 order {:.2}", convection, observed);
+        }
+    }
+}
+
+/// Planar slab with |v| dr / D = 2.5: explicit steps with the downwind
+/// (central) pinch blow up; upwind and the limited schemes stay bounded
+/// below by the edge value and peak on axis.
+#[test]
+fn strong_pinch_is_bounded_except_central() {
+    let nr = 21;
+    let dr = 1.0 / (nr - 1) as f64;
+    let radius = Array1::linspace(0.0, 1.0, nr);
+    let ones = Array1::ones(nr);
+    let metric = Metric {
+        vprime: ones.clone(),
+        vprime_outer: ones.clone(),
+        vprime_inner: ones.clone(),
+        grad_rho: ones.clone(),
+        grad_rho2: ones,
+    };
+    let velocity = Array1::from_elem(nr, -1.0);
+    let diffusivity = Array1::from_elem(nr, 0.02);
+    let relax = |convection: Convection| {
+        let terms = RadialTerms {
+            radius: &radius,
+            dr,
+            metric: &metric,
+            velocity: &velocity,
+            diffusivity: &diffusivity,
+            source: 0.0,
+            source_radius: 1.0,
+            core: BoundaryCondition::Neumann { gradient: 0.0 },
+            edge: BoundaryCondition::Dirichlet { value: 1.0 },
+            convection,
+            stencil: Stencil::Second,
+        };
+        let mut n = Array1::<f64>::ones(nr);
+        for _ in 0..20_000 {
+            let rates: Vec<f64> = (1..nr - 1).map(|i| terms.rate(n.view(), i)).collect();
+            for i in 1..nr - 1 {
+                n[i] += 5e-3 * rates[i - 1];
+            }
+            terms.stencil.close(n.view_mut(), terms.core, terms.edge, dr);
+        }
+        n
+    };
+    assert!(relax(Convection::Central).iter().any(|v| !v.is_finite()));
+    for convection in [Convection::Upwind, Convection::VanLeer, Convection::Koren] {
+        let n = relax(convection);
+        let bounded = n.iter().all(|v| v.is_finite() && *v >= 1.0 - 1e-12);
+        assert!(bounded && n[0] > 100.0 * n[nr / 2], "{:?}: {:?}", convection, n);
+    }
+}
+
+#[test]
+fn fourth_order_stencil_converges_at_fourth_order() {
+    let (errors, orders) =
+        Benchmark::ZeroFlux.study_with(&[21, 41], COURANT_FOURTH, Convection::Central, Stencil::Fourth);
+    let finest = errors.last().unwrap();
+    assert!(finest.max < 2e-5, "max error {:.3e} on nr = {}", finest.max, finest.nr);
+    for (l2, max) in &orders {
+        assert!(*l2 > 3.7 && *max > 3.5, "observed orders {:.2} (L2), {:.2} (max)", l2, max);
+    }
+}
+
+/// The coarse grid scans can afford: nr = 51 at fourth order against
+/// nr = 101 at second order (the manufactured error is O(dt) in time, so
+/// the gain there is smaller than for the steady state).
+#[test]
+fn fourth_order_on_a_coarse_grid_beats_second_order_on_a_fine_one() {
+    for benchmark in Benchmark::ALL {
+        let coarse = benchmark.run_with(51, COURANT_FOURTH, Convection::Central, Stencil::Fourth);
+        let fine = benchmark.run(101, COURANT);
+        assert!(
+            coarse.max < fine.max,
+            "{}: {:.3e} (fourth, nr = 51) vs {:.3e} (second, nr = 101)",
+            benchmark.name(),
+            coarse.max,
+            fine.max
+        );
+    }
+}
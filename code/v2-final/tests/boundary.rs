@@ -0,0 +1,103 @@
+//! Boundary conditions at the axis and the edge.
+
+use w7x_turbulence_control::boundary::BoundaryCondition;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::simulation::Simulation;
+
+const STEP: f64 = 0.1;
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-12 * a.abs().max(b.abs()).max(1.0)
+}
+
+#[test]
+fn each_variant_sets_the_boundary_value() {
+    let neighbour = 2e17;
+
+    let dirichlet = BoundaryCondition::Dirichlet { value: 5e16 };
+    assert_eq!(dirichlet.value(neighbour, STEP), 5e16);
+
+    let neumann = BoundaryCondition::Neumann { gradient: -1e18 };
+    assert!(close(neumann.value(neighbour, STEP), neighbour - 1e17));
+    assert_eq!(BoundaryCondition::core_default().value(neighbour, -STEP), neighbour);
+    // Clamped at zero
+    assert_eq!(BoundaryCondition::Neumann { gradient: -1e19 }.value(neighbour, STEP), 0.0);
+
+    // (n_b − n) / step = −n_b / λ
+    let robin = BoundaryCondition::Robin { decay_length: 0.3 };
+    let value = robin.value(neighbour, STEP);
+    assert!(close(value, 0.75 * neighbour));
+    assert!(close((value - neighbour) / STEP, -value / 0.3));
+    assert_eq!(BoundaryCondition::Robin { decay_length: 0.0 }.value(neighbour, STEP), 0.0);
+
+    let ratio = BoundaryCondition::Ratio { factor: 0.3 };
+    assert!(close(ratio.value(neighbour, STEP), 0.3 * neighbour));
+    assert_eq!(BoundaryCondition::edge_default(), ratio);
+}
+
+/// `linear` is `value` away from the clamp.
+#[test]
+fn linear_form_matches_the_value() {
+    let neighbour = 2e17;
+    for condition in [
+        BoundaryCondition::Dirichlet { value: 5e16 },
+        BoundaryCondition::Neumann { gradient: -1e18 },
+        BoundaryCondition::Robin { decay_length: 0.3 },
+        BoundaryCondition::Ratio { factor: 0.3 },
+    ] {
+        let (alpha, beta) = condition.linear(STEP);
+        assert!(close(alpha * neighbour + beta, condition.value(neighbour, STEP)), "{condition:?}");
+    }
+}
+
+/// On a cubic profile the four-point one-sided gradient is exact.
+#[test]
+fn fourth_order_values_are_exact_for_cubics() {
+    let step = 0.05;
+    let (a, b, c, d) = (4e17, -2e17, 3e17, -2e17);
+    let n = |x: f64| a + b * x + c * x * x + d * x * x * x;
+    let gradient = |x: f64| b + 2.0 * c * x + 3.0 * d * x * x;
+    // Edge at x = 1, neighbours inward
+    let neighbours = [1, 2, 3, 4].map(|k| n(1.0 - k as f64 * step));
+
+    let neumann = BoundaryCondition::Neumann { gradient: gradient(1.0) };
+    assert!(close(neumann.value_fourth(neighbours, step), n(1.0)));
+
+    let decay_length = -n(1.0) / gradient(1.0);
+    let robin = BoundaryCondition::Robin { decay_length };
+    assert!(decay_length > 0.0);
+    assert!(close(robin.value_fourth(neighbours, step), n(1.0)));
+
+    let dirichlet = BoundaryCondition::Dirichlet { value: 5e16 };
+    assert_eq!(dirichlet.value_fourth(neighbours, step), 5e16);
+    let ratio = BoundaryCondition::Ratio { factor: 0.3 };
+    assert!(close(ratio.value_fourth(neighbours, step), 0.3 * neighbours[0]));
+}
+
+fn stepped(core: BoundaryCondition, edge: BoundaryCondition) -> Simulation {
+    let mut config = Config::default();
+    config.simulation.nr = 11;
+    config.boundary.core = core;
+    config.boundary.edge = edge;
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.state.update(config.simulation.dt);
+    sim
+}
+
+#[test]
+fn fixed_value_edge_on_a_small_grid() {
+    let sim = stepped(BoundaryCondition::core_default(), BoundaryCondition::Dirichlet { value: 1e16 });
+    let n = &sim.state.impurity_density;
+    assert_eq!(n[sim.state.nr - 1], 1e16);
+    assert_eq!(n[0], n[1]);
+}
+
+#[test]
+fn fixed_gradient_edge_on_a_small_grid() {
+    let gradient = -1e15;
+    let sim = stepped(BoundaryCondition::core_default(), BoundaryCondition::Neumann { gradient });
+    let (n, nr) = (&sim.state.impurity_density, sim.state.nr);
+    assert!(n[nr - 1] > 0.0);
+    assert!(close((n[nr - 1] - n[nr - 2]) / sim.state.dr, gradient));
+}
@@ -0,0 +1,68 @@
+//! Actuator budget enforced on top of a controller.
+
+mod common;
+
+use common::measurement;
+use w7x_turbulence_control::controller::{BudgetConfig, BudgetGuard, ControlAction, Controller, FixedController};
+use w7x_turbulence_control::events::Event;
+
+const PULSE: f64 = 0.1;
+
+/// Guards a controller that asks for a pulse at every sample.
+fn guard(budget: BudgetConfig) -> BudgetGuard {
+    BudgetGuard::new(Box::new(FixedController(ControlAction::TriggerPulse)), budget, PULSE)
+}
+
+/// Decides at `time`, starting a pulse as the plant would if one passes.
+fn decide(guard: &mut BudgetGuard, time: f64) -> bool {
+    let pulse = guard.decide(&measurement(time, 1e18)) == ControlAction::TriggerPulse;
+    if pulse {
+        guard.pulse_started(time, PULSE);
+    }
+    pulse
+}
+
+fn blocked(guard: &mut BudgetGuard) -> Vec<String> {
+    guard
+        .take_events()
+        .into_iter()
+        .filter_map(|e| match e {
+            Event::PulseBlocked { constraint } => Some(constraint),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn pulse_count_is_limited_per_window() {
+    let mut guard = guard(BudgetConfig { max_pulses: Some(2), pulse_window: 1.0, ..BudgetConfig::default() });
+    assert!(decide(&mut guard, 0.0));
+    assert!(decide(&mut guard, 0.2));
+    assert!(!decide(&mut guard, 0.4));
+    assert!(!decide(&mut guard, 0.6));
+    // Recorded once for the episode
+    assert_eq!(blocked(&mut guard), ["max_pulses"]);
+    // The first start leaves the window
+    assert!(decide(&mut guard, 1.05));
+}
+
+#[test]
+fn spent_pulse_time_suppresses_further_pulses() {
+    let mut guard = guard(BudgetConfig { max_pulse_time: Some(0.15), ..BudgetConfig::default() });
+    assert!(decide(&mut guard, 0.0));
+    // A second pulse would end 0.05 s past the limit
+    for t in [0.5, 1.0, 2.0, 10.0, 100.0] {
+        assert!(!decide(&mut guard, t));
+    }
+    assert_eq!(blocked(&mut guard), ["max_pulse_time"]);
+}
+
+#[test]
+fn off_time_separates_pulses() {
+    let mut guard = guard(BudgetConfig { min_off_time: 0.3, ..BudgetConfig::default() });
+    assert!(decide(&mut guard, 0.0));
+    // Ends at 0.1; not again before 0.4
+    assert!(!decide(&mut guard, 0.3));
+    assert!(decide(&mut guard, 0.45));
+    assert_eq!(blocked(&mut guard), ["min_off_time"]);
+}
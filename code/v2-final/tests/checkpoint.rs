@@ -0,0 +1,150 @@
+//! Checkpoint save and resume.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::controller::{BudgetConfig, ControlAction};
+use w7x_turbulence_control::daq::DaqConfig;
+use w7x_turbulence_control::detection::{AlarmConfig, FeatureConfig, FilterConfig, LatchConfig, PipelineConfig, Signal};
+use w7x_turbulence_control::elm::Elms;
+use w7x_turbulence_control::events::Event;
+use w7x_turbulence_control::main_ions::MainIons;
+use w7x_turbulence_control::ramp::Ramp;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::sol::Sol;
+use w7x_turbulence_control::state::{ConfinementMode, StellaratorState};
+
+const STEPS: usize = 2000;
+
+/// Ramp, ELMs, SOL, and main ions on, so their run state must survive too.
+fn config() -> Config {
+    let mut elms = Elms::default();
+    elms.frequency = 200.0;
+    Config {
+        ramp: Ramp { ramp_up: 0.01, ..Ramp::default() },
+        elms,
+        sol: Sol { enabled: true, ..Sol::default() },
+        main_ions: MainIons { enabled: true, ..MainIons::default() },
+        ..Config::default()
+    }
+}
+
+/// Steps `from..to` with a pulse requested every 300 steps.
+fn advance(state: &mut StellaratorState, dt: f64, from: usize, to: usize) {
+    for step in from..to {
+        if step % 300 == 0 {
+            state.apply_action(ControlAction::TriggerPulse);
+        }
+        state.update(dt);
+    }
+}
+
+fn resumes_bit_identically(name: &str) {
+    let config = config();
+    let dt = config.simulation.dt;
+    let mut uninterrupted = StellaratorState::from_config(&config);
+    uninterrupted.verbose = false;
+    advance(&mut uninterrupted, dt, 0, STEPS);
+
+    let path = std::env::temp_dir().join(format!("w7x_checkpoint_{}_{}", std::process::id(), name));
+    let mut first = StellaratorState::from_config(&config);
+    first.verbose = false;
+    advance(&mut first, dt, 0, STEPS / 2 + 10);
+    assert_eq!(first.confinement_mode, ConfinementMode::TurbulencePulse);
+    first.save_checkpoint(&path).unwrap();
+    let mut resumed = StellaratorState::load_checkpoint(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    advance(&mut resumed, dt, STEPS / 2 + 10, STEPS);
+
+    assert!(uninterrupted.elms.bursts() > 0);
+    assert_eq!(resumed.time.to_bits(), uninterrupted.time.to_bits());
+    // Shortest round-trip formatting: equal text is equal bits
+    assert_eq!(serde_json::to_string(&resumed).unwrap(), serde_json::to_string(&uninterrupted).unwrap());
+}
+
+#[test]
+fn binary_checkpoint_resumes_bit_identically() {
+    resumes_bit_identically("run.ckpt");
+}
+
+#[test]
+fn json_checkpoint_resumes_bit_identically() {
+    resumes_bit_identically("run.json");
+}
+
+/// Short pulses under a pulse-time budget, a delayed DAQ, and a filtered
+/// level alarm beside a debounced rate alarm, so the loop's run state
+/// must survive as well as the plant's.
+fn closed_loop_config() -> Config {
+    let mut config = config();
+    config.plasma.pulse_duration = 0.01;
+    config.plasma.cooldown = 0.005;
+    config.daq = DaqConfig { cycle: 5e-4, latency: 1e-3 };
+    config.controller.budget = Some(BudgetConfig { max_pulse_time: Some(0.045), ..BudgetConfig::default() });
+    let alarm = |name: &str, filter, feature, persistence| AlarmConfig {
+        name: name.to_string(),
+        signal: Signal::CentralSxr,
+        filter,
+        feature,
+        threshold: 0.0,
+        release: None,
+        persistence,
+        latch: LatchConfig::None,
+        weight: None,
+    };
+    config.detection = PipelineConfig {
+        alarms: vec![
+            alarm("level", FilterConfig::LowPass { time_constant: 0.002 }, FeatureConfig::Level, 0),
+            alarm("growth", FilterConfig::MovingAverage { samples: 5 }, FeatureConfig::Rate { window: 0.002 }, 3),
+        ],
+        ..PipelineConfig::default()
+    };
+    config
+}
+
+/// One closed-loop step: the controller's action and whether the budget
+/// held a pulse back.
+fn step(sim: &mut Simulation) -> (Option<ControlAction>, bool) {
+    let action = sim.step();
+    let blocked = sim.state.drain_events().iter().any(|e| matches!(e.event, Event::PulseBlocked { .. }));
+    (action, blocked)
+}
+
+fn closed_loop_resumes_bit_identically(name: &str) {
+    const STEPS: usize = 5000;
+    const SPLIT: usize = 2610;
+    let config = closed_loop_config();
+    let mut uninterrupted = Simulation::from_config(&config);
+    uninterrupted.state.verbose = false;
+    let expected: Vec<_> = (0..STEPS).map(|_| step(&mut uninterrupted)).collect();
+    // Pulses before the split, the budget running out after it
+    assert!(expected[..SPLIT].iter().any(|(action, _)| action.is_some_and(|a| a.requests_pulse())));
+    assert!(expected[SPLIT..].iter().any(|&(_, blocked)| blocked));
+
+    let path = std::env::temp_dir().join(format!("w7x_checkpoint_{}_loop_{}", std::process::id(), name));
+    let mut first = Simulation::from_config(&config);
+    first.state.verbose = false;
+    let mut steps: Vec<_> = (0..SPLIT).map(|_| step(&mut first)).collect();
+    assert!(first.daq.pending() > 0);
+    first.save_checkpoint(&path).unwrap();
+    let (state, saved) = Simulation::load_checkpoint(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let mut resumed = Simulation::with_state(state, &config);
+    resumed.restore(saved).unwrap();
+    steps.extend((SPLIT..STEPS).map(|_| step(&mut resumed)));
+
+    assert_eq!(steps, expected);
+    assert_eq!(serde_json::to_string(&resumed.state).unwrap(), serde_json::to_string(&uninterrupted.state).unwrap());
+    assert_eq!(
+        serde_json::to_string(&resumed.loop_state()).unwrap(),
+        serde_json::to_string(&uninterrupted.loop_state()).unwrap()
+    );
+}
+
+#[test]
+fn binary_checkpoint_resumes_the_closed_loop() {
+    closed_loop_resumes_bit_identically("run.ckpt");
+}
+
+#[test]
+fn json_checkpoint_resumes_the_closed_loop() {
+    closed_loop_resumes_bit_identically("run.json");
+}
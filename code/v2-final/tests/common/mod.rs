@@ -0,0 +1,9 @@
+//! Helpers shared by the integration tests (`mod common;`).
+
+use w7x_turbulence_control::diagnostics::Measurement;
+
+/// A sample with the given central SXR level and fixed edge / turbulence
+/// channels.
+pub fn measurement(time: f64, central_sxr: f64) -> Measurement {
+    Measurement::new(time, central_sxr, 1e17, 0.5)
+}
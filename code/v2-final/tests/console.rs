@@ -0,0 +1,113 @@
+//! Pausing and retuning a run from the console.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::console::{set, Command, Console, Setting};
+use w7x_turbulence_control::controller::{PluginConfig, ScriptConfig};
+use w7x_turbulence_control::simulation::Simulation;
+
+fn simulation(config: &Config) -> Simulation {
+    let mut sim = Simulation::from_config(config);
+    sim.state.verbose = false;
+    sim.state.history.recording = false;
+    sim
+}
+
+#[test]
+fn commands_parse() {
+    assert_eq!(Command::parse(" pause "), Ok(Command::Pause));
+    assert_eq!(Command::parse("set cooldown 0.3"), Ok(Command::Set(Setting::Cooldown, 0.3)));
+    assert_eq!(
+        Command::parse("set threshold central_growth 2e18"),
+        Ok(Command::Set(Setting::Threshold(Some("central_growth".to_string())), 2e18))
+    );
+    assert!(Command::parse("set cooldown soon").is_err());
+    assert!(Command::parse("set d_neo 0.1").is_err());
+    assert!(Command::parse("stop").is_err());
+}
+
+/// Accepted edits reach both the plant and the config; rejected ones
+/// change neither.
+#[test]
+fn edits_are_validated() {
+    let mut config = Config::default();
+    let mut sim = simulation(&config);
+
+    set(&mut sim, &mut config, &Setting::PulseAmplitude, 7.0).unwrap();
+    assert_eq!(sim.state.pulse_amplitude, 7.0);
+    assert_eq!(config.plasma.pulse_amplitude, 7.0);
+    set(&mut sim, &mut config, &Setting::Threshold(None), 1e18).unwrap();
+    assert_eq!(config.detection.alarms[0].threshold, 1e18);
+
+    assert!(set(&mut sim, &mut config, &Setting::PulseDuration, -1.0).is_err());
+    assert_eq!(sim.state.pulse_duration, Config::default().plasma.pulse_duration);
+    assert!(set(&mut sim, &mut config, &Setting::Threshold(Some("missing".to_string())), 1.0).is_err());
+}
+
+/// A threshold edit would swap a scripted or plugin controller for the
+/// built-in one, so it is refused and the config is left alone.
+#[test]
+fn threshold_edits_keep_custom_controllers() {
+    let mut config = Config::default();
+    let mut sim = simulation(&config);
+    config.controller.script = Some(ScriptConfig::default());
+    let before = config.detection.alarms[0].threshold;
+
+    let error = set(&mut sim, &mut config, &Setting::Threshold(None), 1e18).unwrap_err();
+    assert!(error.contains("[controller.script]"));
+    assert_eq!(config.detection.alarms[0].threshold, before);
+    set(&mut sim, &mut config, &Setting::Cooldown, 0.3).unwrap();
+
+    config.controller.script = None;
+    config.controller.plugin = Some(PluginConfig { path: "libcontrol.so".to_string(), parameters: String::new() });
+    let error = set(&mut sim, &mut config, &Setting::Threshold(None), 1e18).unwrap_err();
+    assert!(error.contains("[controller.plugin]"));
+    assert_eq!(config.detection.alarms[0].threshold, before);
+}
+
+/// `pause` holds the run until `resume`; commands in between are applied
+/// before the next step.
+#[test]
+fn pause_holds_until_resume() {
+    let mut config = Config::default();
+    let mut sim = simulation(&config);
+    let (lines, receiver) = mpsc::channel();
+    let mut console = Console::new(receiver, Arc::new(AtomicBool::new(false)));
+    for line in ["pause", "show", "set cooldown 0.3", "resume"] {
+        lines.send(line.to_string()).unwrap();
+    }
+
+    let mut replies = Vec::new();
+    console.poll(&mut sim, &mut config, |line| replies.push(line.to_string()));
+    assert!(!console.paused());
+    assert!(replies[0].starts_with("⏸️ Paused at t=0.000s"));
+    assert!(replies.iter().any(|l| l.starts_with("n_Z(0) = ")));
+    assert!(replies.iter().any(|l| l.contains("cooldown 0.5 → 0.3")));
+    assert!(replies.last().unwrap().starts_with("▶️ Resumed"));
+    assert_eq!(sim.state.cooldown_duration, 0.3);
+}
+
+/// The signal flag toggles the pause; a held run with stdin gone waits
+/// for the next one.
+#[test]
+fn signal_toggles_pause() {
+    let mut config = Config::default();
+    let mut sim = simulation(&config);
+    let (lines, receiver) = mpsc::channel::<String>();
+    drop(lines);
+    let signal = Arc::new(AtomicBool::new(true));
+    let mut console = Console::new(receiver, Arc::clone(&signal));
+
+    let toggle = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        signal.store(true, Ordering::Relaxed);
+    });
+    let mut replies = Vec::new();
+    console.poll(&mut sim, &mut config, |line| replies.push(line.to_string()));
+    toggle.join().unwrap();
+    assert_eq!(replies.len(), 2);
+    assert!(replies[0].starts_with("⏸️"));
+    assert!(replies[1].starts_with("▶️"));
+}
@@ -0,0 +1,63 @@
+//! DAQ control cycle and processing latency.
+
+mod common;
+
+use common::measurement;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::daq::{Daq, DaqConfig};
+use w7x_turbulence_control::simulation::Simulation;
+
+/// Steps of 0.125 with a sample every 0.25 (exact in binary); returns
+/// (delivery time, sample time) of everything handed on.
+fn deliveries(config: &DaqConfig, t_max: f64) -> Vec<(f64, f64)> {
+    let mut daq = Daq::new(config);
+    let mut delivered = Vec::new();
+    let mut time = 0.0;
+    while time < t_max {
+        let taken = (time % 0.25 == 0.0).then(|| measurement(time, 1e17));
+        if let Some(m) = daq.transfer(taken, time) {
+            delivered.push((time, m.time));
+        }
+        time += 0.125;
+    }
+    delivered
+}
+
+#[test]
+fn default_passes_every_sample_at_once() {
+    let delivered = deliveries(&DaqConfig::default(), 1.0);
+    assert_eq!(delivered, [(0.0, 0.0), (0.25, 0.25), (0.5, 0.5), (0.75, 0.75)]);
+}
+
+#[test]
+fn cycle_and_latency_delay_the_controller() {
+    let delivered = deliveries(&DaqConfig { cycle: 1.0, latency: 0.5 }, 3.0);
+    // Newest sample at each tick, handed on half a cycle later
+    assert_eq!(delivered, [(0.5, 0.0), (1.5, 1.0), (2.5, 2.0)]);
+
+    let pipelined = deliveries(&DaqConfig { cycle: 0.5, latency: 1.0 }, 2.5);
+    assert_eq!(pipelined, [(1.0, 0.0), (1.5, 0.5), (2.0, 1.0)]);
+}
+
+#[test]
+fn controller_runs_once_per_cycle() {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.01;
+    config.daq = DaqConfig { cycle: 1e-3, latency: 3e-4 };
+    let mut sim = Simulation::try_from_config(&config).unwrap();
+    sim.state.verbose = false;
+    let mut decisions = 0;
+    while sim.state.time < config.simulation.t_max {
+        let decided_at = sim.state.time;
+        if sim.step().is_some() {
+            decisions += 1;
+            // Acquired at a tick from the newest sample, at most one sample old
+            let age = decided_at - sim.last_measurement().unwrap().time;
+            let oldest = 3e-4 + config.diagnostics.sample_interval + 2.0 * config.simulation.dt;
+            assert!(age >= 3e-4 - 1e-9 && age < oldest, "age {}", age);
+        }
+    }
+    assert!((9..=10).contains(&decisions), "{} decisions", decisions);
+
+    assert!(Config { daq: DaqConfig { cycle: -1e-3, latency: 0.0 }, ..Config::default() }.validate().is_err());
+}
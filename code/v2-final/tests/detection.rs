@@ -0,0 +1,155 @@
+//! Detection pipeline stages.
+
+mod common;
+
+use common::measurement;
+use w7x_turbulence_control::detection::{
+    AlarmConfig, DetectionPipeline, Detector, FeatureConfig, FilterConfig, LatchConfig, PipelineConfig, ResetCondition,
+    Signal, Voting,
+};
+use w7x_turbulence_control::events::Event;
+
+/// Level alarm on the central SXR channel.
+fn alarm(name: &str, threshold: f64) -> AlarmConfig {
+    AlarmConfig {
+        name: name.to_string(),
+        signal: Signal::CentralSxr,
+        filter: FilterConfig::None,
+        feature: FeatureConfig::Level,
+        threshold,
+        release: None,
+        persistence: 0,
+        latch: LatchConfig::None,
+        weight: None,
+    }
+}
+
+fn pipeline(alarms: Vec<AlarmConfig>) -> DetectionPipeline {
+    DetectionPipeline::new(&PipelineConfig { alarms, ..PipelineConfig::default() })
+}
+
+/// Alarm state after each of `signal`, sampled every ms.
+fn trace(pipeline: &mut DetectionPipeline, signal: &[f64]) -> Vec<bool> {
+    signal.iter().enumerate().map(|(k, &x)| pipeline.update(&measurement(k as f64 * 1e-3, x))).collect()
+}
+
+#[test]
+fn unlatched_alarm_clears_at_the_release_level() {
+    // No release: clears as soon as the level is back at the threshold
+    let mut plain = pipeline(vec![alarm("level", 1.0)]);
+    assert_eq!(trace(&mut plain, &[0.5, 1.5, 1.2, 1.0, 1.2]), [false, true, true, false, true]);
+
+    let mut hysteresis = pipeline(vec![AlarmConfig { release: Some(0.8), ..alarm("level", 1.0) }]);
+    assert_eq!(trace(&mut hysteresis, &[0.5, 1.5, 0.9, 0.8, 0.9, 1.1]), [false, true, true, false, false, true]);
+}
+
+#[test]
+fn latched_alarm_holds_until_reset() {
+    let manual = LatchConfig::Latched { reset: ResetCondition::Manual };
+    let mut pipeline = pipeline(vec![AlarmConfig { latch: manual, ..alarm("level", 1.0) }]);
+    assert_eq!(trace(&mut pipeline, &[0.5, 1.5, 0.0, 0.0]), [false, true, true, true]);
+    assert_eq!(pipeline.active_alarms(), ["level"]);
+
+    assert!(pipeline.reset("level"));
+    assert!(pipeline.active_alarms().is_empty());
+    assert!(!pipeline.update(&measurement(0.004, 0.0)));
+    // Sets again on the next crossing
+    assert!(pipeline.update(&measurement(0.005, 1.5)));
+    assert!(!pipeline.reset("no_such_alarm"));
+}
+
+#[test]
+fn latched_alarm_resets_below_a_level_or_after_a_timeout() {
+    let below = LatchConfig::Latched { reset: ResetCondition::Below { level: 0.5, hold: 0.0015 } };
+    let mut pipeline_below = pipeline(vec![AlarmConfig { latch: below, ..alarm("level", 1.0) }]);
+    // Below 0.5 from 2 ms; a rise at 4 ms restarts the hold, which ends at 7 ms
+    let signal = [1.5, 0.8, 0.2, 0.2, 0.6, 0.2, 0.2, 0.2];
+    assert_eq!(trace(&mut pipeline_below, &signal), [true, true, true, true, true, true, true, false]);
+
+    let timeout = LatchConfig::Latched { reset: ResetCondition::Timeout { duration: 0.0025 } };
+    let mut pipeline_timeout = pipeline(vec![AlarmConfig { latch: timeout, ..alarm("level", 1.0) }]);
+    // Clears at the timeout once below; still above, it re-arms for another
+    let signal = [1.5, 0.0, 0.0, 0.0, 1.5, 1.5, 1.5, 1.5, 0.0, 0.0, 0.0];
+    let expected = [true, true, true, false, true, true, true, true, true, true, false];
+    assert_eq!(trace(&mut pipeline_timeout, &signal), expected);
+}
+
+#[test]
+fn persistence_debounces_single_samples() {
+    let mut pipeline = pipeline(vec![AlarmConfig { persistence: 3, ..alarm("level", 1.0) }]);
+    // A spike, then two above, a dip restarting the count, then three above
+    let signal = [1.5, 0.5, 1.5, 1.5, 0.5, 1.5, 1.5, 1.5];
+    assert_eq!(trace(&mut pipeline, &signal), [false, false, false, false, false, false, false, true]);
+}
+
+/// Feature value of each alarm when it set, for a unit step at 1 ms.
+fn step_response(alarms: Vec<AlarmConfig>, samples: usize) -> Vec<(String, f64)> {
+    let mut pipeline = pipeline(alarms);
+    let step: Vec<f64> = (0..samples).map(|k| if k == 0 { 0.0 } else { 1.0 }).collect();
+    trace(&mut pipeline, &step);
+    pipeline
+        .take_events()
+        .into_iter()
+        .filter_map(|e| match e {
+            Event::ThresholdCrossed { alarm, value, .. } => Some((alarm, value)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn low_pass_follows_the_exponential_step_response() {
+    let tau = 0.01;
+    let low_pass = |threshold: f64| AlarmConfig {
+        filter: FilterConfig::LowPass { time_constant: tau },
+        ..alarm(&threshold.to_string(), threshold)
+    };
+    let crossed = step_response(vec![low_pass(0.2), low_pass(0.5), low_pass(0.9)], 50);
+    assert_eq!(crossed.len(), 3);
+    for (name, value) in crossed {
+        let threshold: f64 = name.parse().unwrap();
+        // First sample with 1 − e^(−t/τ) above the threshold
+        let n = (-tau * (1.0 - threshold).ln() / 1e-3).floor() + 1.0;
+        assert!((value - (1.0 - (-n * 1e-3 / tau).exp())).abs() < 1e-12, "{}: {}", name, value);
+    }
+}
+
+#[test]
+fn anomaly_score_matches_the_closed_form_ewma() {
+    // Mean 1 − q and variance q (1 − q) after k samples of the step, with
+    // q = e^(−k dt/τ): the next sample scores √(q / (1 − q))
+    let tau = 0.01;
+    let anomaly = |n: usize| AlarmConfig {
+        feature: FeatureConfig::Anomaly { time_constant: tau, warmup: n as f64 * 1e-3 },
+        ..alarm(&n.to_string(), f64::MIN)
+    };
+    let crossed = step_response([2, 5, 10, 20].into_iter().map(anomaly).collect(), 30);
+    assert_eq!(crossed.len(), 4);
+    for (name, score) in crossed {
+        let n: f64 = name.parse().unwrap();
+        let q = (-(n - 1.0) * 1e-3 / tau).exp();
+        let expected = (q / (1.0 - q)).sqrt();
+        assert!((score / expected - 1.0).abs() < 1e-9, "{}: {} vs {}", name, score, expected);
+    }
+}
+
+#[test]
+fn voting_at_the_count_boundary() {
+    let two_of_three = Voting::AtLeast { count: 2 };
+    assert!(!two_of_three.decide(&[(true, 1.0), (false, 1.0), (false, 1.0)]));
+    assert!(two_of_three.decide(&[(true, 1.0), (false, 1.0), (true, 1.0)]));
+    assert!(!Voting::All.decide(&[(true, 1.0), (true, 1.0), (false, 1.0)]));
+    assert!(Voting::All.decide(&[(true, 1.0), (true, 1.0), (true, 1.0)]));
+    let weighted = Voting::Weighted { threshold: 1.5 };
+    assert!(!weighted.decide(&[(true, 1.0), (false, 0.5), (true, 0.4)]));
+    assert!(weighted.decide(&[(true, 1.0), (true, 0.5), (false, 0.4)]));
+
+    // Thresholds 1, 2, 3: a level of 1.5 trips one alarm, 2.5 two
+    let config = PipelineConfig {
+        alarms: vec![alarm("low", 1.0), alarm("mid", 2.0), alarm("high", 3.0)],
+        voting: two_of_three,
+        model: None,
+    };
+    let mut pipeline = DetectionPipeline::new(&config);
+    assert_eq!(trace(&mut pipeline, &[1.5, 2.5, 3.5, 1.5]), [false, true, true, false]);
+}
@@ -0,0 +1,75 @@
+//! Edge-localized bursts as a disturbance.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::elm::Elms;
+use w7x_turbulence_control::events::Event;
+use w7x_turbulence_control::scan::run_quiet;
+use w7x_turbulence_control::simulation::Simulation;
+
+fn bursts(config: &Config) -> Vec<f64> {
+    let mut sim = Simulation::from_config(config);
+    sim.state.verbose = false;
+    let mut onsets = Vec::new();
+    while sim.state.time < config.simulation.t_max {
+        sim.step();
+        onsets.extend(
+            sim.state.drain_events().iter().filter(|e| matches!(e.event, Event::ElmBurst { .. })).map(|e| e.time),
+        );
+    }
+    onsets
+}
+
+fn config(frequency: f64) -> Config {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.2;
+    config.elms.frequency = frequency;
+    config
+}
+
+#[test]
+fn burst_rate_follows_the_frequency() {
+    let mut elms = Elms::default();
+    elms.frequency = 1000.0;
+    let mut count = 0;
+    for step in 0..1_000_000 {
+        if elms.step(step as f64 * 1e-5) {
+            count += 1;
+        }
+    }
+    // 10 s at 1 kHz: Poisson, σ ≈ 100
+    assert!((9_500..=10_500).contains(&count), "{} bursts", count);
+    assert_eq!(elms.bursts(), count);
+}
+
+#[test]
+fn bursts_enhance_only_the_edge_for_their_duration() {
+    let mut elms = Elms::default();
+    elms.frequency = 1.0;
+    let mut t = 0.0;
+    while !elms.step(t) {
+        t += 1e-4;
+    }
+    assert_eq!(elms.factor(0.95, t), elms.amplitude);
+    assert_eq!(elms.factor(0.5, t), 1.0);
+    assert_eq!(elms.factor(0.95, t + elms.duration), 1.0);
+}
+
+#[test]
+fn same_seed_same_bursts() {
+    let a = bursts(&config(200.0));
+    assert!(!a.is_empty());
+    assert_eq!(a, bursts(&config(200.0)));
+
+    let mut other = config(200.0);
+    other.elms.seed += 1;
+    assert_ne!(a, bursts(&other));
+    assert!(bursts(&config(0.0)).is_empty());
+}
+
+#[test]
+fn summary_counts_bursts() {
+    let config = config(200.0);
+    let summary = run_quiet(&config, |_| {}).unwrap();
+    assert_eq!(summary.elm_bursts as usize, bursts(&config).len());
+    assert!(summary.pulses_after_elm <= summary.pulses);
+}
@@ -0,0 +1,77 @@
+//! The C ABI called as a C host would (`cargo test --features ffi --test ffi`).
+//!
+//! `UPDATE_HEADER=1` copies the header the build generated over the
+//! checked-in `w7x_sim.h`.
+
+#![cfg(feature = "ffi")]
+
+use std::ffi::CStr;
+use w7x_turbulence_control::ffi::*;
+
+const GENERATED_HEADER: &str = include_str!(concat!(env!("OUT_DIR"), "/w7x_sim.h"));
+
+fn last_error() -> String {
+    unsafe { CStr::from_ptr(w7x_last_error()) }.to_string_lossy().into_owned()
+}
+
+/// Step, observe, read a profile, and command a pulse.
+#[test]
+fn plant_round_trip() {
+    unsafe {
+        let sim = w7x_sim_create(std::ptr::null());
+        assert!(!sim.is_null());
+        let nr = w7x_sim_get_profile(sim, W7X_PROFILE_RADIUS, std::ptr::null_mut(), 0);
+        assert!(nr > 2);
+        let mut radius = vec![f64::NAN; nr];
+        assert_eq!(w7x_sim_get_profile(sim, W7X_PROFILE_RADIUS, radius.as_mut_ptr(), nr), nr);
+        assert_eq!((radius[0], radius[nr - 1]), (0.0, 1.0));
+
+        assert_eq!(w7x_sim_step(sim, 0.01), 0);
+        assert!((w7x_sim_time(sim) - 0.01).abs() < 1e-9);
+        let mut observation = std::mem::zeroed::<W7xObservation>();
+        assert_eq!(w7x_sim_observe(sim, &mut observation), 0);
+        assert!(observation.central_sxr > 0.0);
+        assert!(!observation.pulse_active);
+
+        let mut before = vec![0.0; nr];
+        w7x_sim_get_profile(sim, W7X_PROFILE_TURBULENCE, before.as_mut_ptr(), nr);
+        assert_eq!(w7x_sim_apply_action(sim, W7X_ACTION_PULSE, 0, 8.0), 0);
+        w7x_sim_step(sim, 0.05); // past the actuator latency and rise
+        w7x_sim_observe(sim, &mut observation);
+        assert!(observation.pulse_active);
+        let mut during = vec![0.0; nr];
+        w7x_sim_get_profile(sim, W7X_PROFILE_TURBULENCE, during.as_mut_ptr(), nr);
+        let edge = (nr - 1) * 85 / 100; // Middle of the default window, r > 0.7
+        assert!(during[edge] > 2.0 * before[edge]);
+        w7x_sim_destroy(sim);
+    }
+}
+
+/// Bad arguments fail with a message instead of crashing.
+#[test]
+fn errors_are_reported() {
+    unsafe {
+        let path = c"/nonexistent/w7x.toml";
+        assert!(w7x_sim_create(path.as_ptr()).is_null());
+        assert!(last_error().contains("/nonexistent/w7x.toml"));
+
+        let sim = w7x_sim_create(std::ptr::null());
+        assert_eq!(w7x_sim_step(sim, -1.0), -1);
+        assert!(last_error().contains("duration"));
+        assert_eq!(w7x_sim_get_profile(sim, 99, std::ptr::null_mut(), 0), 0);
+        assert_eq!(w7x_sim_apply_action(sim, 7, 0, 0.0), -1);
+        assert_eq!(w7x_sim_step(std::ptr::null_mut(), 0.1), -1);
+        w7x_sim_destroy(sim);
+    }
+}
+
+/// The checked-in header matches `ffi.rs`.
+#[test]
+fn header_is_current() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("w7x_sim.h");
+    if std::env::var_os("UPDATE_HEADER").is_some() {
+        std::fs::write(&path, GENERATED_HEADER).unwrap();
+    }
+    let checked_in = std::fs::read_to_string(&path).unwrap();
+    assert!(checked_in == GENERATED_HEADER, "{} is stale (UPDATE_HEADER=1 rewrites it)", path.display());
+}
@@ -0,0 +1,82 @@
+//! Transport-coefficient fitting to a reference trace.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::fit::{self, Coefficients};
+use w7x_turbulence_control::metadata::RunMetadata;
+use w7x_turbulence_control::neoclassical::{Neoclassical, NeoclassicalTable};
+use w7x_turbulence_control::reference::{self, ReferenceTrace};
+
+#[test]
+fn nelder_mead_finds_a_quadratic_minimum() {
+    let (points, converged) = fit::nelder_mead(
+        |x| (x[0] - 1.0).powi(2) + 10.0 * (x[1] + 2.0).powi(2),
+        &[0.0, 0.0],
+        0.5,
+        500,
+        1e-12,
+    );
+    assert!(converged);
+    let (best, cost) = points.iter().min_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+    assert!(*cost < 1e-8, "cost {}", cost);
+    assert!((best[0] - 1.0).abs() < 1e-3 && (best[1] + 2.0).abs() < 1e-3);
+}
+
+fn base_config() -> Config {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.05;
+    config.fit.knots = vec![0.0, 1.0];
+    config.fit.max_evaluations = 40;
+    config
+}
+
+/// A trace from known coefficients pulls a detuned start back towards them.
+#[test]
+fn twin_experiment_reduces_the_mismatch() {
+    let config = base_config();
+    let truth = Coefficients {
+        source: 2.0 * fit::source_strength(&config),
+        ..Coefficients::from_config(&config, &config.fit.knots)
+    };
+    let time: Vec<f64> = (1..=10).map(|i| i as f64 * 0.005).collect();
+    let shell = ReferenceTrace { value: vec![0.0; time.len()], time };
+    let truth_config = truth.apply(&config, &config.fit.knots, "");
+    let value = reference::simulate(&truth_config, &shell, config.reference.channel, 1.0).unwrap();
+    let measured = ReferenceTrace { value, ..shell };
+
+    let result = fit::fit(&config, &measured).unwrap();
+    // One iteration may overshoot by a reflection, a contraction and a shrink
+    assert!(result.evaluations.len() <= 40 + 2 + 5);
+    assert!(result.best.cost < 0.5 * result.initial.cost,
+            "cost {} → {}", result.initial.cost, result.best.cost);
+    let source = result.best.coefficients.source;
+    assert!(source > fit::source_strength(&config), "source {}", source);
+}
+
+#[test]
+fn fitted_table_loads_as_neoclassical_table() {
+    let config = base_config();
+    let mut coefficients = Coefficients::from_config(&config, &config.fit.knots);
+    coefficients.d_neo = vec![0.05, 0.2];
+    coefficients.v_neo = vec![-0.1, -1.0];
+    let evaluation = fit::FitEvaluation { coefficients, cost: 0.0 };
+    let result = fit::Fit {
+        knots: config.fit.knots.clone(),
+        initial: evaluation.clone(),
+        best: evaluation,
+        evaluations: Vec::new(),
+        converged: true,
+    };
+    let path = std::env::temp_dir().join(format!("w7x_fit_{}.csv", std::process::id()));
+    result.write_table(&path, &RunMetadata::collect(&config)).unwrap();
+
+    let mut neoclassical = Neoclassical::Table(NeoclassicalTable {
+        path: path.to_string_lossy().into_owned(),
+        ..NeoclassicalTable::default()
+    });
+    neoclassical.load().unwrap();
+    let Neoclassical::Table(table) = neoclassical else { unreachable!() };
+    assert_eq!(table.rho, vec![0.0, 1.0]);
+    assert_eq!(table.d_neo, vec![0.05, 0.2]);
+    assert_eq!(table.v_neo, vec![-0.1, -1.0]);
+    std::fs::remove_file(path).ok();
+}
@@ -0,0 +1,113 @@
+//! Fuzzy inference controller.
+
+mod common;
+
+use common::measurement;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::controller::{ControlAction, Controller, PulseCommand};
+use w7x_turbulence_control::error::SimError;
+use w7x_turbulence_control::events::Event;
+use w7x_turbulence_control::fuzzy::{FuzzyConfig, FuzzyController, Membership};
+use w7x_turbulence_control::simulation::Simulation;
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9 * b.abs().max(1.0)
+}
+
+#[test]
+fn membership_shapes() {
+    let triangle = Membership::Triangle { a: 0.0, b: 1.0, c: 3.0 };
+    assert_eq!(triangle.degree(-1.0), 0.0);
+    assert_eq!(triangle.degree(0.5), 0.5);
+    assert_eq!(triangle.degree(1.0), 1.0);
+    assert_eq!(triangle.degree(2.5), 0.25);
+
+    let shoulder = Membership::Trapezoid { a: f64::NEG_INFINITY, b: f64::NEG_INFINITY, c: 1.0, d: 2.0 };
+    assert_eq!(shoulder.degree(-1e30), 1.0);
+    assert_eq!(shoulder.degree(1.5), 0.5);
+    assert_eq!(shoulder.degree(2.0), 0.0);
+
+    let gaussian = Membership::Gaussian { center: 1.0, width: 2.0 };
+    assert_eq!(gaussian.degree(1.0), 1.0);
+    assert!(close(gaussian.degree(3.0), (-0.5f64).exp()));
+    assert!(!Membership::Triangle { a: 2.0, b: 1.0, c: 3.0 }.is_valid());
+}
+
+#[test]
+fn default_rules_grade_the_response() {
+    let fuzzy = FuzzyConfig::default();
+    assert_eq!(fuzzy.infer(3e17, 0.0, 0.5), Some(0.0));
+    assert!(fuzzy.infer(6e17, 0.0, 0.5).unwrap() < fuzzy.pulse_above);
+    assert!(close(fuzzy.infer(7e17, 0.0, 0.5).unwrap(), 3.0));
+    assert!(close(fuzzy.infer(1e18, 0.0, 0.5).unwrap(), 5.5));
+    assert!(close(fuzzy.infer(2e18, 0.0, 0.5).unwrap(), 8.0));
+    // Fast growth at a low level
+    assert!(close(fuzzy.infer(2e17, 2e18, 0.5).unwrap(), 8.0));
+    // Medium accumulation already flushed by enhanced edge turbulence
+    assert!(fuzzy.infer(7e17, 0.0, 5.0).unwrap() < fuzzy.pulse_above);
+}
+
+#[test]
+fn controller_pulses_with_the_inferred_amplitude() {
+    let mut controller = FuzzyController::new(&FuzzyConfig::default(), 1);
+    match controller.decide(&measurement(0.0, 1e18)) {
+        ControlAction::Pulse(PulseCommand { window: 1, amplitude: Some(a), .. }) => assert!(close(a, 5.5)),
+        other => panic!("expected a pulse, got {:?}", other),
+    }
+
+    // Growth only counts once the rate window is covered
+    let mut controller = FuzzyController::new(&FuzzyConfig::default(), 0);
+    assert_eq!(controller.decide(&measurement(0.0, 1e17)), ControlAction::Hold);
+    assert_eq!(controller.decide(&measurement(0.001, 1.5e17)), ControlAction::Hold);
+    assert_eq!(
+        controller.decide(&measurement(0.002, 2e17)),
+        ControlAction::Pulse(PulseCommand::new(0, Some(8.0)))
+    );
+}
+
+#[test]
+fn parses_and_validates_the_rule_base() {
+    let text = r#"
+        [controller.fuzzy]
+        pulse_above = 1.0
+        outputs = { none = 0.0, pulse = 4.0 }
+        central_sxr.high = { type = "trapezoid", a = 5e17, b = 1e18, c = inf, d = inf }
+        rules = [{ when = { central_sxr = "high" }, then = "pulse" }]
+    "#;
+    let mut config: Config = toml::from_str(text).unwrap();
+    config.validate().unwrap();
+    let fuzzy = config.controller.fuzzy.as_ref().unwrap();
+    assert_eq!(fuzzy.rules[0].weight, 1.0);
+    assert_eq!(fuzzy.infer(2e18, 0.0, 0.5), Some(4.0));
+    // Tables not given keep the defaults
+    assert_eq!(fuzzy.growth, FuzzyConfig::default().growth);
+
+    let fuzzy = config.controller.fuzzy.as_mut().unwrap();
+    fuzzy.rules[0].then = "missing".to_string();
+    assert!(config.validate().is_err());
+    config.controller.fuzzy = Some(FuzzyConfig { rules: Vec::new(), ..FuzzyConfig::default() });
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn rejects_a_gain_schedule() {
+    let text = r#"
+        [controller.fuzzy]
+        [controller.schedule]
+        regimes = [{ name = "all" }]
+    "#;
+    let config: Config = toml::from_str(text).unwrap();
+    assert!(matches!(config.validate(), Err(SimError::InvalidParameter { name: "controller.fuzzy", .. })));
+}
+
+#[test]
+fn controls_a_discharge() {
+    let mut config = Config::default();
+    config.controller.fuzzy = Some(FuzzyConfig::default());
+    config.validate().unwrap();
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.run(2.0, |_| {}).unwrap();
+    let events = sim.state.drain_events();
+    assert!(events.iter().any(|e| matches!(e.event, Event::PulseStarted { .. })));
+}
@@ -0,0 +1,136 @@
+//! Gain scheduling by plasma regime.
+
+mod common;
+
+use common::measurement;
+use std::collections::BTreeMap;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::controller::{
+    ControlAction, Controller, ControllerConfig, PluginConfig, PulseCommand, ScriptConfig, ThresholdController,
+};
+use w7x_turbulence_control::events::Event;
+use w7x_turbulence_control::gain_schedule::{GainSchedule, Indicator, Regime, ScheduleTracker, ScheduledGains};
+use w7x_turbulence_control::simulation::Simulation;
+
+fn row(name: &str, below: Option<f64>, central_level: f64) -> ScheduledGains {
+    ScheduledGains {
+        name: name.to_string(),
+        below,
+        thresholds: BTreeMap::from([("central_level".to_string(), central_level)]),
+        ..ScheduledGains::default()
+    }
+}
+
+/// Low density: 1.2e18; high density: 5e17 and amplitude 6.
+fn schedule() -> GainSchedule {
+    let high = ScheduledGains { amplitude: Some(6.0), ..row("high_density", None, 5e17) };
+    GainSchedule { regimes: vec![row("low_density", Some(3e19), 1.2e18), high], ..GainSchedule::default() }
+}
+
+fn density(density: f64) -> Regime {
+    Regime { density, ..Regime::default() }
+}
+
+#[test]
+fn switches_with_hysteresis() {
+    let schedule = schedule();
+    assert_eq!(schedule.lookup(1e19), 0);
+    assert_eq!(schedule.lookup(3e19), 1);
+    let mut tracker = ScheduleTracker::default();
+    assert!(tracker.active(&schedule).is_none());
+
+    assert_eq!(tracker.update(&schedule, &density(2.9e19)).unwrap().name, "low_density");
+    // Inside the 5 % band above the bound: stay
+    assert!(tracker.update(&schedule, &density(3.1e19)).is_none());
+    assert_eq!(tracker.update(&schedule, &density(3.2e19)).unwrap().name, "high_density");
+    // And below it on the way back
+    assert!(tracker.update(&schedule, &density(2.9e19)).is_none());
+    assert_eq!(tracker.update(&schedule, &density(2.8e19)).unwrap().name, "low_density");
+    assert_eq!(tracker.active(&schedule).unwrap().name, "low_density");
+}
+
+#[test]
+fn retunes_the_threshold_controller() {
+    let config = ControllerConfig { schedule: Some(schedule()), ..ControllerConfig::default() };
+    let mut controller = ThresholdController::new().configured(&config);
+    // Unscheduled: the [detection] threshold 8e17
+    assert_eq!(controller.decide(&measurement(0.0, 1e18)), ControlAction::TriggerPulse);
+
+    controller.regime(&density(2e19));
+    assert_eq!(controller.decide(&measurement(0.01, 1e18)), ControlAction::Hold);
+    let events = controller.take_events();
+    assert!(events.iter().any(|e| matches!(e, Event::GainsScheduled { regime, .. } if regime == "low_density")));
+
+    controller.regime(&density(5e19));
+    assert_eq!(
+        controller.decide(&measurement(0.02, 1e18)),
+        ControlAction::Pulse(PulseCommand::new(0, Some(6.0)))
+    );
+    // Same row: nothing new
+    controller.take_events();
+    controller.regime(&density(6e19));
+    assert!(!controller.take_events().iter().any(|e| matches!(e, Event::GainsScheduled { .. })));
+}
+
+#[test]
+fn unnamed_alarms_keep_their_thresholds() {
+    // Only the low row retunes central_level; the high row restores 8e17
+    let rows = vec![row("low", Some(1.0), 2e18), ScheduledGains { name: "high".to_string(), ..ScheduledGains::default() }];
+    let schedule = GainSchedule { indicator: Indicator::HeatingPower, regimes: rows, ..GainSchedule::default() };
+    let config = ControllerConfig { schedule: Some(schedule), ..ControllerConfig::default() };
+    let mut controller = ThresholdController::new().configured(&config);
+    let power = |heating_power| Regime { heating_power, ..Regime::default() };
+
+    controller.regime(&power(0.5));
+    assert_eq!(controller.decide(&measurement(0.0, 1e18)), ControlAction::Hold);
+    controller.regime(&power(5.0));
+    assert_eq!(controller.decide(&measurement(0.01, 1e18)), ControlAction::TriggerPulse);
+}
+
+#[test]
+fn validation_checks_the_table() {
+    let mut config = Config::default();
+    config.controller.schedule = Some(schedule());
+    assert!(config.validate().is_ok());
+
+    let mut unordered = schedule();
+    unordered.regimes.swap(0, 1);
+    config.controller.schedule = Some(unordered);
+    assert!(config.validate().is_err());
+
+    let mut unknown = schedule();
+    unknown.regimes[0].thresholds.insert("no_such_alarm".to_string(), 1e18);
+    config.controller.schedule = Some(unknown);
+    assert!(config.validate().is_err());
+
+    config.controller.schedule = Some(GainSchedule::default());
+    assert!(config.validate().is_err());
+
+    // Only the threshold controller follows a schedule
+    config.controller.schedule = Some(schedule());
+    config.controller.script = Some(ScriptConfig::default());
+    assert!(config.validate().is_err());
+    config.controller.script = None;
+    config.controller.plugin = Some(PluginConfig::default());
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn simulation_reports_the_regime() {
+    let text = r#"
+        [controller.schedule]
+        indicator = "heating_power"
+        regimes = [
+            { name = "low_power", below = 2.0, thresholds = { central_level = 5e17 } },
+            { name = "high_power" },
+        ]
+    "#;
+    let config: Config = toml::from_str(text).unwrap();
+    config.validate().unwrap();
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.run(0.01, |_| {}).unwrap();
+    let events = sim.state.drain_events();
+    // Default heating power 5 MW
+    assert!(events.iter().any(|e| matches!(&e.event, Event::GainsScheduled { regime, indicator } if regime == "high_power" && *indicator == 5.0)));
+}
@@ -0,0 +1,121 @@
+//! Golden-file regression tests: short reference runs with the fixed
+//! diagnostics seed, compared channel by channel against the traces in
+//! `tests/golden/`. A physics change that moves them fails here and has
+//! to be made on purpose; regenerate the files with
+//!
+//! ```text
+//! UPDATE_GOLDEN=1 cargo test --test golden
+//! ```
+//!
+//! and commit them with the change.
+
+use std::path::PathBuf;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::controller::{ControlAction, FixedController};
+use w7x_turbulence_control::preset::Preset;
+use w7x_turbulence_control::scan;
+use w7x_turbulence_control::scenario::{ScenarioAction, ScenarioEvent};
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::state::{ConfinementMode, StellaratorState};
+
+const SAMPLE_INTERVAL: f64 = 0.01; // s
+const RELATIVE_TOLERANCE: f64 = 1e-6;
+const COLUMNS: [&str; 6] = ["time", "center_impurity", "edge_impurity", "mean_turbulence", "stored_energy", "pulse"];
+
+/// Traces sampled every `SAMPLE_INTERVAL`, one row per sample.
+fn run(config: &Config, uncontrolled: bool) -> Vec<[f64; 6]> {
+    let mut sim = Simulation::from_config(config);
+    if uncontrolled {
+        sim.controller = Box::new(FixedController(ControlAction::Hold));
+    }
+    let mut rows = Vec::new();
+    let mut next = 0.0;
+    scan::run_quiet_with(sim, config, |state: &StellaratorState| {
+        if state.time >= next {
+            let nr = state.nr;
+            rows.push([
+                state.time,
+                state.impurity_density[0],
+                state.impurity_density[nr - 1],
+                state.mean_turbulence(),
+                state.confinement.stored_energy(),
+                if state.confinement_mode == ConfinementMode::TurbulencePulse { 1.0 } else { 0.0 },
+            ]);
+            next += SAMPLE_INTERVAL;
+        }
+    })
+    .unwrap();
+    rows
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(format!("{}.csv", name))
+}
+
+fn check(name: &str, rows: &[[f64; 6]]) {
+    let path = golden_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let mut text = COLUMNS.join(",") + "\n";
+        for row in rows {
+            let fields: Vec<String> = row.iter().map(|v| format!("{:.12e}", v)).collect();
+            text += &(fields.join(",") + "\n");
+        }
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, text).unwrap();
+        return;
+    }
+
+    let text = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("{}: {} (UPDATE_GOLDEN=1 writes it)", path.display(), e));
+    let golden: Vec<Vec<f64>> = text
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').map(|f| f.parse().unwrap()).collect())
+        .collect();
+    assert_eq!(golden.len(), rows.len(), "{}: sample count", name);
+
+    // Absolute floor per channel so values near zero compare by the channel's scale
+    let scale: Vec<f64> = (0..COLUMNS.len())
+        .map(|c| golden.iter().fold(0.0_f64, |m, row| m.max(row[c].abs())).max(1e-300))
+        .collect();
+    for (expected, actual) in golden.iter().zip(rows) {
+        for (c, column) in COLUMNS.iter().enumerate() {
+            let tolerance = RELATIVE_TOLERANCE * expected[c].abs().max(1e-3 * scale[c]);
+            assert!(
+                (actual[c] - expected[c]).abs() <= tolerance,
+                "{}: {} at t = {:.3}s is {:.9e}, golden {:.9e}",
+                name, column, expected[0], actual[c], expected[c]
+            );
+        }
+    }
+}
+
+/// Default configuration under adaptive control.
+#[test]
+fn closed_loop() {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.6;
+    check("closed_loop", &run(&config, false));
+}
+
+/// Uncontrolled plant driven by a scripted source step, pulse, and density ramp.
+#[test]
+fn scripted() {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.8;
+    config.scenario.events = vec![
+        ScenarioEvent { time: 0.1, action: ScenarioAction::SourceStrength { value: 5e17 } },
+        ScenarioEvent { time: 0.3, action: ScenarioAction::Pulse { window: 0, amplitude: Some(3.0) } },
+        ScenarioEvent { time: 0.5, action: ScenarioAction::DensityRamp { factor: 1.3, duration: 0.2 } },
+    ];
+    check("scripted", &run(&config, true));
+}
+
+/// OP1.2 standard-configuration preset under adaptive control.
+#[test]
+fn op12_standard() {
+    let mut config = Config::default();
+    Preset::Op12Standard.apply(&mut config);
+    config.simulation.t_max = 0.3;
+    check("op12_standard", &run(&config, false));
+}
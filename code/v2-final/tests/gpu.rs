@@ -0,0 +1,54 @@
+//! The wgpu 2D transport kernel against the CPU solver
+//! (`cargo test --features gpu --test gpu`). Skipped without an adapter.
+
+#![cfg(feature = "gpu")]
+
+use w7x_turbulence_control::config::{Config, TransportGeometry};
+use w7x_turbulence_control::convection::Convection;
+use w7x_turbulence_control::gpu::PoloidalKernel;
+use w7x_turbulence_control::poloidal::Backend;
+use w7x_turbulence_control::state::StellaratorState;
+
+const STEPS: usize = 1000;
+
+fn state(backend: Backend, convection: Convection) -> StellaratorState {
+    let mut config = Config::default();
+    config.simulation.nr = 51;
+    config.simulation.geometry = TransportGeometry::Poloidal;
+    config.poloidal.ntheta = 32;
+    config.poloidal.backend = backend;
+    config.numerics.convection = convection;
+    let mut state = StellaratorState::from_config(&config);
+    state.verbose = false;
+    state.history.recording = false;
+    state
+}
+
+/// Same n_Z(r, θ) within f32 round-off after `STEPS` steps, with every
+/// convection scheme (at the default pinch, where central is stable and
+/// does not amplify the difference).
+#[test]
+fn gpu_kernel_matches_the_cpu_solver() {
+    if let Err(e) = PoloidalKernel::new(51, 32) {
+        eprintln!("skipped, no GPU: {}", e);
+        return;
+    }
+    for convection in [Convection::Central, Convection::Upwind, Convection::VanLeer, Convection::Koren] {
+        let mut cpu = state(Backend::Cpu, convection);
+        let mut gpu = state(Backend::Gpu, convection);
+        let dt = 0.5 * cpu.stability_limit().min(1e-3);
+        for _ in 0..STEPS {
+            cpu.update(dt);
+            gpu.update(dt);
+        }
+        let reference = cpu.poloidal.as_ref().unwrap().density();
+        let offloaded = gpu.poloidal.as_ref().unwrap();
+        assert_eq!(offloaded.backend, Backend::Gpu, "fell back to the CPU");
+        let scale = reference.iter().fold(0.0_f64, |m, &n| m.max(n));
+        let error = reference
+            .iter()
+            .zip(offloaded.density())
+            .fold(0.0_f64, |m, (a, b)| m.max((a - b).abs()));
+        assert!(error < 1e-4 * scale, "{:?}: {:.2e} of {:.2e}", convection, error, scale);
+    }
+}
@@ -0,0 +1,123 @@
+//! Time-trace history and windowed export.
+
+use w7x_turbulence_control::history::{Cadence, Channel, History, Sample};
+
+/// Samples at t = 0.1, 0.2, …, 1.0 s with n_Z(0) = 10 t.
+fn history() -> History {
+    let mut history = History::default();
+    for i in 1..=10 {
+        let time = 0.1 * i as f64;
+        history.push(&Sample { time, center_impurity: 10.0 * time, ..Sample::default() });
+    }
+    history
+}
+
+/// Offers `steps` samples at t = k dt under `cadence`; returns the history
+/// and how many pushes reported the sample as due.
+fn recorded(cadence: Cadence, steps: usize, dt: f64) -> (History, usize) {
+    let mut history = History::default();
+    history.cadence = cadence;
+    let due = (0..steps)
+        .filter(|&k| history.push(&Sample { time: k as f64 * dt, ..Sample::default() }))
+        .count();
+    (history, due)
+}
+
+fn times(history: &History, t0: f64, t1: f64, stride: usize) -> Vec<f64> {
+    history.export_range(t0, t1, &[Channel::CenterImpurity], stride).time
+}
+
+fn assert_times(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len(), "{actual:?} vs {expected:?}");
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-12, "{actual:?} vs {expected:?}");
+    }
+}
+
+#[test]
+fn range_before_the_first_sample() {
+    let history = history();
+    let export = history.export_range(-1.0, 0.35, &[Channel::CenterImpurity, Channel::Turbulence], 1);
+    assert_times(&export.time, &[0.1, 0.2, 0.3]);
+    assert_eq!(export.channels, [Channel::CenterImpurity, Channel::Turbulence]);
+    assert_eq!(export.values.len(), 2);
+    assert_times(&export.values[0], &[1.0, 2.0, 3.0]);
+    assert_eq!(export.values[1], [0.0; 3]);
+}
+
+/// Both ends are inclusive when they fall exactly on a sample.
+#[test]
+fn ends_on_a_sample() {
+    let history = history();
+    let t = times(&history, f64::NEG_INFINITY, f64::INFINITY, 1);
+    assert_eq!(t.len(), 10);
+    assert_times(&times(&history, t[2], t[4], 1), &t[2..=4]);
+    assert_times(&times(&history, t[4], t[4], 1), &t[4..=4]);
+    assert_times(&times(&history, t[0], t[9], 1), &t);
+}
+
+#[test]
+fn empty_ranges() {
+    let history = history();
+    assert!(history.export_range(0.42, 0.48, &[Channel::CenterImpurity], 1).is_empty());
+    assert!(times(&history, 2.0, 3.0, 1).is_empty());
+    assert!(times(&history, -2.0, 0.0, 1).is_empty());
+    assert!(times(&history, 0.6, 0.4, 1).is_empty());  // Reversed
+    assert!(times(&History::default(), 0.0, 1.0, 1).is_empty());
+}
+
+#[test]
+fn stride_keeps_every_nth_sample_from_the_start() {
+    let history = history();
+    let export = history.export_range(0.25, 1.0, &[Channel::CenterImpurity], 3);
+    assert_times(&export.time, &[0.3, 0.6, 0.9]);
+    assert_times(&export.values[0], &[3.0, 6.0, 9.0]);
+    assert_times(&times(&history, 0.0, 1.0, 20), &[0.1]);
+    // 0 is treated as 1
+    assert_eq!(times(&history, 0.0, 1.0, 0).len(), 10);
+}
+
+#[test]
+fn every_step_keeps_all_samples() {
+    let (history, due) = recorded(Cadence::EveryStep, 25, 1e-3);
+    assert_eq!((history.len(), due), (25, 25));
+}
+
+#[test]
+fn steps_cadence_keeps_every_nth_from_the_first() {
+    let (history, due) = recorded(Cadence::Steps { every: 10 }, 95, 1e-3);
+    assert_eq!((history.len(), due), (10, 10));
+    let expected = (0..10).map(|k| k as f64 * 10e-3).collect::<Vec<_>>();
+    assert_times(&times(&history, f64::NEG_INFINITY, f64::INFINITY, 1), &expected);
+
+    // 0 behaves like 1
+    assert_eq!(recorded(Cadence::Steps { every: 0 }, 7, 1e-3).0.len(), 7);
+}
+
+/// Samples are due once per `dt` of simulation time, on the grid started by
+/// the first one, despite round-off in the step times.
+#[test]
+fn interval_cadence_keeps_one_sample_per_interval() {
+    let (history, due) = recorded(Cadence::Interval { dt: 1e-3 }, 1000, 2e-5);
+    assert_eq!((history.len(), due), (20, 20));
+    let expected = (0..20).map(|k| k as f64 * 1e-3).collect::<Vec<_>>();
+    let kept = times(&history, f64::NEG_INFINITY, f64::INFINITY, 1);
+    for (t, e) in kept.iter().zip(&expected) {
+        assert!((t - e).abs() < 1e-9, "{kept:?}");
+    }
+
+    // Steps longer than the interval keep every sample, without catching up
+    let (history, due) = recorded(Cadence::Interval { dt: 1e-3 }, 10, 2.5e-3);
+    assert_eq!((history.len(), due), (10, 10));
+}
+
+/// With recording off the cadence still decides what goes to the streams.
+#[test]
+fn not_recording_still_reports_due_samples() {
+    let mut history = History::default();
+    history.recording = false;
+    history.cadence = Cadence::Steps { every: 4 };
+    let due = (0..12).filter(|&k| history.push(&Sample { time: k as f64, ..Sample::default() })).count();
+    assert_eq!(due, 3);
+    assert!(history.is_empty());
+}
@@ -0,0 +1,93 @@
+//! IMAS `core_profiles` / `core_transport` export.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::imas;
+use w7x_turbulence_control::metadata::RunMetadata;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::snapshots::ProfileSnapshots;
+
+fn run() -> (Simulation, ProfileSnapshots) {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.2;
+    config.output.profile_cadence = 0.05;
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    let mut snapshots = ProfileSnapshots::new(config.output.profile_cadence);
+    snapshots.record(&sim.state);
+    while sim.state.time < config.simulation.t_max {
+        sim.step();
+        snapshots.record(&sim.state);
+    }
+    (sim, snapshots)
+}
+
+/// One `profiles_1d` per snapshot on every time base, in IMAS units.
+#[test]
+fn profiles_follow_the_data_dictionary() {
+    let (sim, snapshots) = run();
+    let metadata = RunMetadata::collect(&Config::default());
+    let ids = imas::ids(&sim.state.history, &snapshots, sim.state.impurity_charge, &metadata);
+    let profiles = &ids["core_profiles"];
+    let n = snapshots.len();
+    assert!(n >= 4);
+    assert_eq!(profiles["ids_properties"]["homogeneous_time"], 1);
+    assert_eq!(profiles["time"].as_array().unwrap().len(), n);
+    assert_eq!(profiles["profiles_1d"].as_array().unwrap().len(), n);
+    assert_eq!(profiles["global_quantities"]["energy_diamagnetic"].as_array().unwrap().len(), n);
+
+    let last = &profiles["profiles_1d"][n - 1];
+    let t_e = last["electrons"]["temperature"][0].as_f64().unwrap();
+    assert!((t_e - snapshots.electron_temp[n - 1][0] * 1e3).abs() < 1e-9 * t_e);
+    assert_eq!(last["grid"]["rho_tor_norm"].as_array().unwrap().len(), sim.state.nr);
+    assert_eq!(last["ion"][1]["label"], "Fe");
+    assert_eq!(last["ion"][1]["density"][0].as_f64().unwrap(), snapshots.impurity_density[n - 1][0]);
+    let energy = profiles["global_quantities"]["energy_diamagnetic"][n - 1].as_f64().unwrap();
+    assert!(energy > 1e4, "stored energy {} J", energy);
+}
+
+/// The combined model carries D_neo + D_turb and the pinch.
+#[test]
+fn transport_models_add_up() {
+    let (sim, snapshots) = run();
+    let transport = imas::core_transport(&snapshots, sim.state.impurity_charge);
+    let models = transport["model"].as_array().unwrap();
+    let indices: Vec<i64> = models.iter().map(|m| m["identifier"]["index"].as_i64().unwrap()).collect();
+    assert_eq!(indices, [1, 5, 6]);
+
+    let d = |model: usize, i: usize| {
+        models[model]["profiles_1d"][0]["ion"][0]["particles"]["d"][i].as_f64().unwrap()
+    };
+    for i in [0, sim.state.nr / 2, sim.state.nr - 1] {
+        assert!((d(0, i) - d(1, i) - d(2, i)).abs() <= 1e-12 * d(0, i));
+    }
+    assert!(models[0]["profiles_1d"][0]["ion"][0]["particles"]["v"].is_array());
+    assert!(models[1]["profiles_1d"][0]["ion"][0]["particles"].get("v").is_none());
+}
+
+/// The JSON file holds both IDSs.
+#[test]
+fn json_holds_both_ids() {
+    let (sim, snapshots) = run();
+    let metadata = RunMetadata::collect(&Config::default());
+    let ids = imas::ids(&sim.state.history, &snapshots, sim.state.impurity_charge, &metadata);
+    let path = std::env::temp_dir().join(format!("w7x_imas_{}.json", std::process::id()));
+    imas::write_json(&path, &ids).unwrap();
+    let read: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    for name in ["core_profiles", "core_transport"] {
+        assert_eq!(read[name]["time"].as_array().unwrap().len(), snapshots.len());
+        assert_eq!(read[name]["ids_properties"], ids[name]["ids_properties"]);
+    }
+}
+
+/// Both IDSs carry the run's provenance in `code`.
+#[test]
+fn ids_carry_provenance() {
+    let (sim, snapshots) = run();
+    let metadata = RunMetadata::collect(&Config::default());
+    let ids = imas::ids(&sim.state.history, &snapshots, sim.state.impurity_charge, &metadata);
+    for name in ["core_profiles", "core_transport"] {
+        assert_eq!(ids[name]["code"]["version"], metadata.crate_version.as_str());
+        assert_eq!(ids[name]["ids_properties"]["creation_date"], metadata.started.as_str());
+    }
+}
@@ -0,0 +1,69 @@
+//! Lock-step controller comparison on shared noise realizations.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::controller::ControllerConfig;
+use w7x_turbulence_control::fuzzy::FuzzyConfig;
+use w7x_turbulence_control::lockstep::{self, Contender, BASELINE};
+use w7x_turbulence_control::rng;
+use w7x_turbulence_control::scan;
+
+fn contender(name: &str, controller: Option<ControllerConfig>) -> Contender {
+    Contender { name: name.to_string(), controller, detection: None }
+}
+
+fn config() -> Config {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.05;
+    config.seed = Some(7);
+    config.lockstep.realizations = 2;
+    config.lockstep.contenders = vec![contender("a", None), contender("b", None)];
+    config
+}
+
+/// Identical contenders see the same realization, which is that of a
+/// standalone run seeded as the same batch member.
+#[test]
+fn identical_contenders_give_identical_runs() {
+    let base = config();
+    let races = lockstep::run_lockstep(&base).unwrap();
+    assert_eq!(races.len(), 2);
+    for race in &races {
+        let names: Vec<&str> = race.runs.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, [BASELINE, "a", "b"]);
+        let (a, b) = (&race.runs[1], &race.runs[2]);
+        assert_eq!(format!("{:?}", a.summary), format!("{:?}", b.summary));
+        assert_eq!(a.center_impurity, b.center_impurity);
+        assert_eq!(a.pulsing, b.pulsing);
+        assert!(race.runs.iter().all(|r| r.center_impurity.len() == race.time.len()));
+
+        let mut alone = base.clone();
+        rng::seed_batch_member(&mut alone, race.realization);
+        let summary = scan::run_quiet(&alone, |_| {}).unwrap();
+        assert_eq!(format!("{:?}", summary), format!("{:?}", a.summary));
+    }
+    assert_eq!(races[1].realization, 1);
+}
+
+#[test]
+fn contenders_replace_the_controller() {
+    let mut base = config();
+    let fuzzy = ControllerConfig { fuzzy: Some(FuzzyConfig::default()), ..ControllerConfig::default() };
+    base.lockstep.contenders[1] = contender("fuzzy", Some(fuzzy));
+    base.lockstep.baseline = false;
+    let entrants = lockstep::entrants(&base, 0).unwrap();
+    assert_eq!(entrants.len(), 2);
+    assert!(entrants[0].config.controller.fuzzy.is_none());
+    assert!(entrants[1].config.controller.fuzzy.is_some());
+    assert_eq!(entrants[0].config.seed, entrants[1].config.seed);
+}
+
+#[test]
+fn rejects_duplicate_and_missing_contenders() {
+    let mut base = config();
+    base.lockstep.contenders[1].name = "a".to_string();
+    assert!(lockstep::entrants(&base, 0).is_err());
+    base.lockstep.contenders = vec![contender(BASELINE, None)];
+    assert!(lockstep::entrants(&base, 0).is_err());
+    base.lockstep.contenders.clear();
+    assert!(lockstep::entrants(&base, 0).is_err());
+}
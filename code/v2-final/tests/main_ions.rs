@@ -0,0 +1,92 @@
+//! Main-ion transport and quasineutrality.
+
+use ndarray::Array1;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::main_ions::MainIons;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::state::StellaratorState;
+
+fn enabled() -> Config {
+    Config { main_ions: MainIons { enabled: true, ..MainIons::default() }, ..Config::default() }
+}
+
+#[test]
+fn disabled_keeps_the_electron_density_frozen() {
+    let config = Config::default();
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    let initial = sim.state.electron_density.clone();
+    sim.run(0.05, |_| {}).unwrap();
+    assert_eq!(sim.state.electron_density, initial);
+    assert_eq!(MainIons::default().drive(0.5), 1.0);
+}
+
+#[test]
+fn flat_profile_without_pinch_does_not_move() {
+    let ions = MainIons { enabled: true, ..MainIons::default() };
+    let nr = 21;
+    let radius = Array1::linspace(0.0, 1.0, nr);
+    let rates = ions.rates(&radius, 1.0 / (nr - 1) as f64, &Array1::from_elem(nr, 5e19), &Array1::from_elem(nr, 0.5));
+    assert!(rates.iter().all(|r| r.abs() < 1e-6));
+}
+
+#[test]
+fn diffusion_flattens_a_peak_conserving_content() {
+    let ions = MainIons { enabled: true, ..MainIons::default() };
+    let nr = 41;
+    let dr = 1.0 / (nr - 1) as f64;
+    let radius = Array1::linspace(0.0, 1.0, nr);
+    let density = radius.mapv(|r: f64| 5e19 * (-(r / 0.2).powi(2)).exp() + 1e18);
+    let rates = ions.rates(&radius, dr, &density, &Array1::zeros(nr));
+    assert!(rates[0] < 0.0);
+    // ∫ r ∂n/∂t dr over the interior balances the edge outflow only
+    let interior: f64 = (1..nr - 1).map(|i| radius[i] * rates[i] * dr).sum::<f64>() + rates[0] * dr * dr / 8.0;
+    assert!(interior.abs() < 1e-3 * rates[0].abs() * dr);
+}
+
+#[test]
+fn quasineutrality_holds_every_step() {
+    let config = enabled();
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    let z = sim.state.impurity_charge;
+    sim.run(0.05, |state| {
+        let n_i = state.main_ion_density();
+        for i in 0..state.nr {
+            let n_e = state.electron_density[i];
+            assert!((n_i[i] + z * state.impurity_density[i] - n_e).abs() <= 1e-9 * n_e);
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn initial_main_ions_are_steady() {
+    let config = enabled();
+    let mut state = StellaratorState::from_config(&config);
+    state.verbose = false;
+    let initial = state.main_ion_density();
+    state.update(1e-4);
+    let after = state.main_ion_density();
+    // Relative to n_e: toward the edge Z n_Z can exceed n_e, clipping n_i to 0
+    for i in 0..state.nr {
+        assert!((after[i] - initial[i]).abs() <= 1e-3 * state.electron_density[i], "n_i[{}]", i);
+    }
+}
+
+#[test]
+fn accumulation_dilutes_and_reduces_the_drive() {
+    let mut states = [Config::default(), enabled()].map(|config| {
+        let mut state = StellaratorState::from_config(&config);
+        state.verbose = false;
+        state
+    });
+    for state in &mut states {
+        let n_e = state.electron_density.clone();
+        state.impurity_density = n_e.mapv(|n| 0.01 * n);
+    }
+    let mid = states[0].nr / 2;
+    let dilution = states[1].charge_balance(mid).1;
+    assert!(dilution < 0.8);
+    assert!(states[1].target_turbulence_level(mid) < states[0].target_turbulence_level(mid));
+}
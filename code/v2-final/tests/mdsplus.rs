@@ -0,0 +1,87 @@
+//! MDSplus-style signal tree export.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::history::Channel;
+use w7x_turbulence_control::mdsplus;
+use w7x_turbulence_control::metadata::RunMetadata;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::snapshots::ProfileSnapshots;
+
+fn run() -> (Simulation, ProfileSnapshots) {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.2;
+    config.output.profile_cadence = 0.05;
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    let mut snapshots = ProfileSnapshots::new(config.output.profile_cadence);
+    snapshots.record(&sim.state);
+    while sim.state.time < config.simulation.t_max {
+        sim.step();
+        snapshots.record(&sim.state);
+    }
+    (sim, snapshots)
+}
+
+/// Every snapshot field and trace lands on a node, with its dimensions.
+#[test]
+fn signals_sit_under_diagnostic_nodes() {
+    let (sim, snapshots) = run();
+    let metadata = RunMetadata::collect(&Config::default());
+    let tree = mdsplus::tree(&sim.state.history, &snapshots, &metadata);
+    for field in snapshots.fields() {
+        let path = mdsplus::profile_node(field.name).unwrap();
+        let node = mdsplus::get(&tree, path).unwrap_or_else(|| panic!("{} missing", path));
+        assert_eq!(node["usage"], "SIGNAL");
+        assert_eq!(node["data"].as_array().unwrap().len(), snapshots.len());
+        assert_eq!(node["dim_of"][0].as_array().unwrap().len(), sim.state.nr);
+        assert_eq!(node["dim_of"][1].as_array().unwrap().len(), snapshots.len());
+    }
+
+    let ne = mdsplus::get(&tree, "\\QTB::TOP.PROFILES:NE").unwrap();
+    assert_eq!(ne["data"][0][0].as_f64().unwrap(), snapshots.electron_density[0][0]);
+    assert!(tree["QTB"]["PROFILES"]["TE"].is_object());
+
+    let history = &sim.state.history;
+    let core = mdsplus::get(&tree, "\\QSK::TOP.TRACES:NZ_CORE").unwrap();
+    assert_eq!(core["dim_of"][0].as_array().unwrap().len(), history.len());
+    assert_eq!(core["data"][0].as_f64().unwrap(), history.channel(Channel::CenterImpurity)[0]);
+}
+
+/// Stored energy is in J, not the history's MJ.
+#[test]
+fn diamagnetic_energy_is_in_joules() {
+    let (sim, snapshots) = run();
+    let metadata = RunMetadata::collect(&Config::default());
+    let tree = mdsplus::tree(&sim.state.history, &snapshots, &metadata);
+    let wdia = mdsplus::get(&tree, "\\QMJ::TOP.TRACES:WDIA").unwrap();
+    assert_eq!(wdia["units"], "J");
+    let mj = *sim.state.history.channel(Channel::StoredEnergy).last().unwrap();
+    let j = wdia["data"].as_array().unwrap().last().unwrap().as_f64().unwrap();
+    assert!((j - mj * 1e6).abs() <= 1e-9 * j);
+}
+
+/// The file reads back to the same tree.
+#[test]
+fn json_round_trips() {
+    let (sim, snapshots) = run();
+    let metadata = RunMetadata::collect(&Config::default());
+    let tree = mdsplus::tree(&sim.state.history, &snapshots, &metadata);
+    let path = std::env::temp_dir().join(format!("w7x_mdsplus_{}.json", std::process::id()));
+    mdsplus::write_json(&path, &tree).unwrap();
+    let read: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(read["SIM"]["PROFILES"]["PINCH"]["units"], "m s-1");
+    assert_eq!(read["QSK"]["TRACES"]["NZ_EDGE"]["data"].as_array().unwrap().len(), sim.state.history.len());
+}
+
+/// The tree is built from the recorded history, so a run that keeps none
+/// is rejected up front instead of writing empty signals.
+#[test]
+fn needs_the_history() {
+    let mut config = Config::default();
+    config.output.keep_history = false;
+    assert!(config.validate().is_ok());
+    config.output.mdsplus = Some("w7x_tree.json".to_string());
+    let error = config.validate().unwrap_err();
+    assert!(error.to_string().contains("output.keep_history"));
+}
@@ -0,0 +1,101 @@
+//! Run metadata embedded in the output files.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::events::EventLog;
+use w7x_turbulence_control::metadata::RunMetadata;
+use w7x_turbulence_control::output::{create_sink, TraceFormat};
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::summary::SummaryTracker;
+
+fn temp(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("w7x_metadata_{}_{}", std::process::id(), name))
+}
+
+/// The resolved config and seed come back out of the stamp.
+#[test]
+fn records_config_and_seed() {
+    let mut config = Config::default();
+    config.diagnostics.seed = 1234;
+    config.simulation.t_max = 0.7;
+    let metadata = RunMetadata::collect(&config);
+    assert_eq!(metadata.seed, 1234);
+    assert_eq!(metadata.crate_version, env!("CARGO_PKG_VERSION"));
+    assert!(!metadata.hostname.is_empty());
+    assert_eq!(metadata.started.len(), "2026-01-01T00:00:00Z".len());
+    let restored: Config = serde_json::from_value(metadata.config.clone()).unwrap();
+    assert_eq!(restored.simulation.t_max, 0.7);
+}
+
+/// A CSV trace starts with the metadata line; a resumed one does not repeat it.
+#[test]
+fn csv_trace_starts_with_metadata() {
+    let config = Config::default();
+    let metadata = RunMetadata::collect(&config);
+    let path = temp("trace.csv");
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    for append in [false, true] {
+        let mut sink = create_sink(TraceFormat::Csv, &path, append, &metadata).unwrap();
+        for _ in 0..10 {
+            sim.step();
+            if let Some(sample) = sim.state.recorded_sample() {
+                sink.write_sample(sample).unwrap();
+            }
+        }
+        sink.finish().unwrap();
+    }
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    let read = RunMetadata::from_csv_line(lines[0]).unwrap();
+    assert_eq!(read.started, metadata.started);
+    assert!(lines[1].starts_with("time,"));
+    assert_eq!(lines.iter().filter(|l| l.starts_with('#')).count(), 1);
+    assert_eq!(lines.len(), 2 + 20);
+}
+
+/// The binary trace header is followed by the metadata JSON, then samples.
+#[test]
+fn binary_trace_holds_metadata() {
+    let config = Config::default();
+    let metadata = RunMetadata::collect(&config);
+    let path = temp("trace.bin");
+    let mut sink = create_sink(TraceFormat::Binary, &path, false, &metadata).unwrap();
+    sink.finish().unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(&bytes[0..4], b"W7XT");
+    assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), 2);
+    let n = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+    assert_eq!(bytes.len(), 16 + n);
+    let read: RunMetadata = serde_json::from_slice(&bytes[16..]).unwrap();
+    assert_eq!(read.seed, metadata.seed);
+}
+
+/// The summary and the event log carry a `metadata` object.
+#[test]
+fn json_outputs_hold_metadata() {
+    let config = Config::default();
+    let metadata = RunMetadata::collect(&config);
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    let mut tracker = SummaryTracker::new(&sim.state, config.output.critical_density);
+    for _ in 0..10 {
+        sim.step();
+        tracker.observe(&sim.state, sim.dt);
+    }
+
+    let summary_path = temp("summary.json");
+    tracker.finish(&sim.state, sim.dt).write_json(&summary_path, &metadata).unwrap();
+    let summary: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&summary_path).unwrap()).unwrap();
+    std::fs::remove_file(&summary_path).unwrap();
+    assert_eq!(summary["metadata"]["git_commit"], metadata.git_commit.as_str());
+    assert!(summary["pulses"].is_number());
+
+    let events_path = temp("events.jsonl");
+    EventLog::create(&events_path, false, &metadata).unwrap().finish().unwrap();
+    let text = std::fs::read_to_string(&events_path).unwrap();
+    let first: serde_json::Value = serde_json::from_str(text.lines().next().unwrap()).unwrap();
+    std::fs::remove_file(&events_path).unwrap();
+    assert_eq!(first["metadata"]["seed"], metadata.seed);
+}
@@ -0,0 +1,262 @@
+//! Thomas solver against known and random systems, the implicit
+//! diffusion operator against conservation and analytic steady states,
+//! and the parallel and single-precision transport rates against the
+//! pointwise f64 ones, and an f32 run against an f64 one.
+
+use ndarray::Array1;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use w7x_turbulence_control::boundary::BoundaryCondition;
+use w7x_turbulence_control::config::{Config, TransportGeometry};
+use w7x_turbulence_control::convection::Convection;
+use w7x_turbulence_control::geometry::Metric;
+use w7x_turbulence_control::numerics::{DiffusionOperator, Tridiagonal};
+use w7x_turbulence_control::poloidal::{RadialTerms, PARALLEL_MIN_POINTS};
+use w7x_turbulence_control::precision::{run_precision_check, Precision, Real};
+use w7x_turbulence_control::stencil::Stencil;
+
+fn max_abs(values: &Array1<f64>) -> f64 {
+    values.iter().fold(0.0_f64, |m, v| m.max(v.abs()))
+}
+
+fn planar(nr: usize) -> Metric {
+    let ones = Array1::ones(nr);
+    Metric {
+        vprime: ones.clone(),
+        vprime_outer: ones.clone(),
+        vprime_inner: ones.clone(),
+        grad_rho: ones.clone(),
+        grad_rho2: ones,
+    }
+}
+
+#[test]
+fn solves_a_known_system() {
+    let mut matrix = Tridiagonal::zeros(3);
+    matrix.diagonal.fill(2.0);
+    matrix.lower.fill(-1.0);
+    matrix.upper.fill(-1.0);
+    let x = matrix.solve(&Array1::from(vec![1.0, 0.0, 1.0])).unwrap();
+    assert!(max_abs(&(x - 1.0)) < 1e-14);
+}
+
+#[test]
+fn solves_random_diagonally_dominant_systems() {
+    let mut rng = StdRng::seed_from_u64(7);
+    for n in [1, 2, 5, 100, 1000] {
+        let mut matrix = Tridiagonal::zeros(n);
+        for i in 0..n {
+            matrix.lower[i] = rng.gen_range(-1.0..1.0);
+            matrix.upper[i] = rng.gen_range(-1.0..1.0);
+            matrix.diagonal[i] = (2.0 + rng.gen::<f64>()) * if rng.gen() { 1.0 } else { -1.0 };
+        }
+        let expected: Array1<f64> = (0..n).map(|_| rng.gen_range(-1e3..1e3)).collect();
+        let x = matrix.solve(&matrix.multiply(&expected)).unwrap();
+        assert!(max_abs(&(&x - &expected)) < 1e-10 * max_abs(&expected), "n = {}", n);
+    }
+}
+
+#[test]
+fn zero_pivot_is_reported() {
+    let mut matrix = Tridiagonal::identity(3);
+    matrix.diagonal[1] = 0.0;
+    assert!(matrix.solve(&Array1::ones(3)).is_none());
+}
+
+/// Neumann (zero-gradient) ends: backward Euler moves n_Z between cells
+/// but keeps Σ V' dr n over the interior.
+#[test]
+fn closed_ends_conserve_particles() {
+    let nr = 81;
+    let dr = 1.0 / (nr - 1) as f64;
+    let radius = Array1::linspace(0.0, 1.0, nr);
+    let metric = Metric::cylindrical(&radius, dr);
+    let diffusivity = radius.mapv(|r| 0.1 + 2.0 * r * r);
+    let closed = BoundaryCondition::Neumann { gradient: 0.0 };
+    let operator = DiffusionOperator { metric: &metric, dr, diffusivity: &diffusivity, core: closed, edge: closed };
+    let inventory = |n: &Array1<f64>| (1..nr - 1).map(|i| metric.vprime[i] * dr * n[i]).sum::<f64>();
+
+    let mut n = radius.mapv(|r| 1e18 * (1.0 + (-((r - 0.6) / 0.1).powi(2)).exp()));
+    n[0] = n[1];
+    n[nr - 1] = n[nr - 2];
+    let initial = inventory(&n);
+    let source = Array1::zeros(nr);
+    for _ in 0..50 {
+        n = operator.step(&n, &source, 1e-2).unwrap();
+    }
+    assert!((inventory(&n) - initial).abs() < 1e-12 * initial);
+    // and flattens the bump
+    assert!(max_abs(&(&n - n[nr / 2])) < 0.05 * n[nr / 2]);
+}
+
+/// Uniform source S, constant D, zero gradient on axis, n(1) = n1:
+/// n = n1 + S (1 − r²) / 4D in the cylinder. The axis row n_0 = n_1
+/// drops the source in [0, dr/2], so the error is O(dr² log dr).
+#[test]
+fn cylindrical_steady_state_converges_at_second_order() {
+    let (d, s, n1) = (0.5, 1e19, 1e18);
+    let error = |nr: usize| {
+        let dr = 1.0 / (nr - 1) as f64;
+        let radius = Array1::linspace(0.0, 1.0, nr);
+        let metric = Metric::cylindrical(&radius, dr);
+        let diffusivity = Array1::from_elem(nr, d);
+        let operator = DiffusionOperator {
+            metric: &metric,
+            dr,
+            diffusivity: &diffusivity,
+            core: BoundaryCondition::Neumann { gradient: 0.0 },
+            edge: BoundaryCondition::Dirichlet { value: n1 },
+        };
+        // One huge step is the discrete steady state
+        let n = operator.step(&Array1::zeros(nr), &Array1::from_elem(nr, s), 1e12).unwrap();
+        let exact = radius.mapv(|r| n1 + s * (1.0 - r * r) / (4.0 * d));
+        max_abs(&(&n - &exact)) / max_abs(&exact)
+    };
+    let (coarse, fine) = (error(41), error(81));
+    assert!(fine < 5e-4, "error {:.2e}", fine);
+    let order = (coarse / fine).log2();
+    assert!(order > 1.75, "order {:.2}", order);
+}
+
+/// Planar, constant D, no source: Dirichlet core with a fixed-gradient
+/// (Neumann) or Dirichlet edge gives a linear profile, exact on the grid.
+#[test]
+fn planar_linear_profiles_are_exact() {
+    let nr = 21;
+    let dr = 1.0 / (nr - 1) as f64;
+    let radius = Array1::linspace(0.0, 1.0, nr);
+    let metric = planar(nr);
+    let diffusivity = Array1::from_elem(nr, 1.5);
+    let cases = [
+        (BoundaryCondition::Neumann { gradient: -3.0 }, radius.mapv(|r| 5.0 - 3.0 * r)),
+        (BoundaryCondition::Dirichlet { value: 1.0 }, radius.mapv(|r| 5.0 - 4.0 * r)),
+    ];
+    for (edge, exact) in cases {
+        let operator = DiffusionOperator {
+            metric: &metric,
+            dr,
+            diffusivity: &diffusivity,
+            core: BoundaryCondition::Dirichlet { value: 5.0 },
+            edge,
+        };
+        let n = operator.step(&Array1::zeros(nr), &Array1::zeros(nr), 1e12).unwrap();
+        assert!(max_abs(&(&n - &exact)) < 1e-9, "{:?}: {:?}", edge, n);
+    }
+}
+
+/// One-sided fourth-order boundary values are exact for quartics.
+#[test]
+fn fourth_order_boundary_values_are_exact_for_quartics() {
+    // n(1) = 2, n'(1) = −6: Robin decay length 1/3 at the edge
+    let n = |r: f64| 3.0 + r * r - 2.0 * r.powi(4);
+    let dr = 0.1;
+    let cases = [
+        (0.0, -dr, BoundaryCondition::Neumann { gradient: 0.0 }),
+        (1.0, dr, BoundaryCondition::Neumann { gradient: -6.0 }),
+        (1.0, dr, BoundaryCondition::Robin { decay_length: 1.0 / 3.0 }),
+    ];
+    for (boundary, step, condition) in cases {
+        let neighbours = [1.0, 2.0, 3.0, 4.0].map(|k| n(boundary - k * step));
+        let value = condition.value_fourth(neighbours, step);
+        assert!((value - n(boundary)).abs() < 1e-12, "{:?}: {} vs {}", condition, value, n(boundary));
+    }
+}
+
+/// `rates` (parallel radial chunks on large grids) agrees with `rate`
+/// point by point.
+#[test]
+fn rates_match_pointwise_rates_on_large_grids() {
+    let nr = 2 * PARALLEL_MIN_POINTS + 1;
+    let dr = 1.0 / (nr - 1) as f64;
+    let radius = Array1::linspace(0.0, 1.0, nr);
+    let metric = Metric::cylindrical(&radius, dr);
+    let velocity = radius.mapv(|r| -2.0 * r);
+    let diffusivity = radius.mapv(|r| 0.5 * (1.0 + r * r));
+    let n = radius.mapv(|r| 1e19 * (1.0 - 0.8 * r * r));
+    for stencil in [Stencil::Second, Stencil::Fourth] {
+        let terms = RadialTerms {
+            radius: &radius,
+            dr,
+            metric: &metric,
+            velocity: &velocity,
+            diffusivity: &diffusivity,
+            source: 1e20,
+            source_radius: 0.8,
+            core: BoundaryCondition::Neumann { gradient: 0.0 },
+            edge: BoundaryCondition::Dirichlet { value: 2e18 },
+            convection: Convection::VanLeer,
+            stencil,
+        };
+        let rates = terms.rates(n.view());
+        assert_eq!((rates[0], rates[nr - 1]), (0.0, 0.0));
+        for i in 1..nr - 1 {
+            assert_eq!(rates[i], terms.rate(n.view(), i), "{:?} at {}", stencil, i);
+        }
+    }
+}
+
+fn transport<'a, T: Real>(
+    radius: &'a Array1<f64>,
+    metric: &'a Metric,
+    velocity: &'a Array1<T>,
+    diffusivity: &'a Array1<T>,
+    convection: Convection,
+    stencil: Stencil,
+) -> RadialTerms<'a, T> {
+    RadialTerms {
+        radius,
+        dr: radius[1] - radius[0],
+        metric,
+        velocity,
+        diffusivity,
+        source: 1e20,
+        source_radius: 0.8,
+        core: BoundaryCondition::Neumann { gradient: 0.0 },
+        edge: BoundaryCondition::Dirichlet { value: 2e18 },
+        convection,
+        stencil,
+    }
+}
+
+/// Rates computed in f32 agree with the f64 ones to single-precision
+/// round-off for every stencil and convection scheme.
+#[test]
+fn single_precision_rates_match_double_precision() {
+    let nr = 101;
+    let dr = 1.0 / (nr - 1) as f64;
+    let radius = Array1::linspace(0.0, 1.0, nr);
+    let metric = Metric::cylindrical(&radius, dr);
+    let velocity = radius.mapv(|r| -2.0 * r);
+    let diffusivity = radius.mapv(|r| 0.5 * (1.0 + r * r));
+    let n = radius.mapv(|r| 1e19 * (1.0 - 0.8 * r * r));
+    let single = |x: &Array1<f64>| x.mapv(|value| value as f32);
+    let (velocity_f32, diffusivity_f32, n_f32) = (single(&velocity), single(&diffusivity), single(&n));
+    for stencil in [Stencil::Second, Stencil::Fourth] {
+        for convection in [Convection::Central, Convection::Upwind, Convection::VanLeer, Convection::Koren] {
+            let reference = transport(&radius, &metric, &velocity, &diffusivity, convection, stencil).rates(n.view());
+            let reduced = transport(&radius, &metric, &velocity_f32, &diffusivity_f32, convection, stencil)
+                .rates(n_f32.view())
+                .mapv(f64::from);
+            // Terms of size D n / dr² cancel to the rate
+            let scale = 0.5 * n[0] / (dr * dr);
+            let error = max_abs(&(&reduced - &reference));
+            assert!(error < 1e-5 * scale, "{:?}/{:?}: {:.2e} of {:.2e}", stencil, convection, error, scale);
+        }
+    }
+}
+
+/// `numerics.precision = "f32"` changes an uncontrolled run, but only
+/// within the precision check's tolerance; the 2D solver refuses it.
+#[test]
+fn single_precision_runs_stay_within_tolerance() {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.2;
+    let check = run_precision_check(&config).unwrap();
+    let difference = check.max_relative_difference();
+    assert!(difference > 0.0 && difference < check.tolerance, "{:.2e}", difference);
+    assert_eq!(check.diverged_at(), None);
+
+    config.numerics.precision = Precision::F32;
+    config.simulation.geometry = TransportGeometry::Poloidal;
+    assert!(config.validate().is_err());
+}
@@ -0,0 +1,28 @@
+//! Simulated time held to the wall clock.
+
+use std::time::{Duration, Instant};
+use w7x_turbulence_control::pacing::{Pacer, MAX_LAG, SLACK};
+
+/// 0.2 s of simulated time at speed 4 takes 50 ms of wall time, less `SLACK`.
+#[test]
+fn waits_for_the_wall_clock() {
+    let start = Instant::now();
+    let mut pacer = Pacer::new(4.0, 1.0);
+    for step in 1..=100 {
+        pacer.wait(1.0 + step as f64 * 0.002);
+    }
+    let elapsed = start.elapsed();
+    assert!(elapsed + SLACK >= Duration::from_millis(50), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_millis(500), "{:?}", elapsed);
+}
+
+/// A run that fell behind does not race to catch up.
+#[test]
+fn lag_restarts_the_clock() {
+    let mut pacer = Pacer::new(1.0, 0.0);
+    std::thread::sleep(MAX_LAG * 2);
+    pacer.wait(0.0);
+    let start = Instant::now();
+    pacer.wait(0.05);
+    assert!(start.elapsed() >= Duration::from_millis(45), "{:?}", start.elapsed());
+}
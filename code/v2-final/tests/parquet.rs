@@ -0,0 +1,74 @@
+//! Parquet traces and profiles (`cargo test --features parquet --test parquet`).
+
+#![cfg(feature = "parquet")]
+
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::history::Channel;
+use w7x_turbulence_control::metadata::RunMetadata;
+use w7x_turbulence_control::output::{create_sink, TraceFormat};
+use w7x_turbulence_control::parquet_output::write_profiles;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::snapshots::ProfileSnapshots;
+
+fn read(path: &std::path::Path) -> SerializedFileReader<std::fs::File> {
+    SerializedFileReader::new(std::fs::File::open(path).unwrap()).unwrap()
+}
+
+/// The streamed trace holds every sample, with time plus one column per channel.
+#[test]
+fn trace_holds_every_sample() {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.05;
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    let path = std::env::temp_dir().join(format!("w7x_trace_{}.parquet", std::process::id()));
+    let mut sink = create_sink(TraceFormat::Parquet, &path, false, &RunMetadata::collect(&config)).unwrap();
+    let mut samples = 0;
+    while sim.state.time < config.simulation.t_max {
+        sim.step();
+        if let Some(sample) = sim.state.recorded_sample() {
+            sink.write_sample(sample).unwrap();
+            samples += 1;
+        }
+    }
+    sink.finish().unwrap();
+
+    let reader = read(&path);
+    std::fs::remove_file(&path).unwrap();
+    let metadata = reader.metadata().file_metadata();
+    assert_eq!(metadata.num_rows(), samples);
+    assert_eq!(metadata.schema_descr().num_columns(), 1 + Channel::ALL.len());
+    assert_eq!(metadata.schema_descr().column(1).name(), "center_impurity");
+}
+
+/// Resuming cannot append to a Parquet file.
+#[test]
+fn trace_refuses_append() {
+    let path = std::env::temp_dir().join(format!("w7x_append_{}.parquet", std::process::id()));
+    let metadata = RunMetadata::collect(&Config::default());
+    assert!(create_sink(TraceFormat::Parquet, &path, true, &metadata).is_err());
+}
+
+/// One row per snapshot and radius.
+#[test]
+fn profiles_are_long_format() {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.1;
+    config.output.profile_cadence = 0.02;
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    let mut snapshots = ProfileSnapshots::new(config.output.profile_cadence);
+    while sim.state.time < config.simulation.t_max {
+        sim.step();
+        snapshots.record(&sim.state);
+    }
+
+    let path = std::env::temp_dir().join(format!("w7x_profiles_{}.parquet", std::process::id()));
+    write_profiles(&path, &snapshots, &RunMetadata::collect(&config)).unwrap();
+    let reader = read(&path);
+    std::fs::remove_file(&path).unwrap();
+    let metadata = reader.metadata().file_metadata();
+    assert_eq!(metadata.num_rows() as usize, snapshots.len() * sim.state.nr);
+    assert_eq!(metadata.schema_descr().num_columns(), 2 + snapshots.fields().len());
+}
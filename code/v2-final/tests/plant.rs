@@ -0,0 +1,19 @@
+//! The embedding API (`Plant`).
+
+use w7x_turbulence_control::{Config, ControlAction, Plant, SimError};
+
+/// A config `Config::validate` rejects is an error, not a panic.
+#[test]
+fn invalid_configs_are_errors() {
+    let mut config = Config::default();
+    config.simulation.nr = 2;
+    assert!(matches!(Plant::try_new(&config), Err(SimError::InvalidParameter { name: "simulation.nr", .. })));
+    config = Config::default();
+    config.simulation.dt = 0.0;
+    assert!(Plant::try_new(&config).is_err());
+
+    let mut plant = Plant::try_new(&Config::default()).unwrap();
+    plant.step(0.01);
+    plant.actuate(ControlAction::Hold);
+    assert!(plant.observe().time > 0.0);
+}
@@ -0,0 +1,61 @@
+//! End-of-run figures (`cargo test --features plot --test plot`).
+
+#![cfg(feature = "plot")]
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::events::Event;
+use w7x_turbulence_control::plot::{write_animation, write_plot};
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::snapshots::ProfileSnapshots;
+
+/// A closed-loop run with its profile snapshots and pulse intervals.
+fn run(keep_history: bool) -> (Simulation, ProfileSnapshots, Vec<(f64, f64)>) {
+    let mut config = Config::default();
+    config.simulation.t_max = 1.0;
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.state.history.recording = keep_history;
+    let mut snapshots = ProfileSnapshots::new(config.output.profile_cadence);
+    let mut pulses = Vec::new();
+    while sim.state.time < config.simulation.t_max {
+        sim.step();
+        for event in sim.state.drain_events() {
+            match event.event {
+                Event::PulseStarted { .. } => pulses.push((event.time, f64::INFINITY)),
+                Event::PulseEnded { .. } => pulses.last_mut().unwrap().1 = event.time,
+                _ => {}
+            }
+        }
+        snapshots.record(&sim.state);
+    }
+    (sim, snapshots, pulses)
+}
+
+/// The figure and the animation are written as PNG and GIF.
+#[test]
+fn figures_are_written() {
+    let (sim, snapshots, pulses) = run(true);
+    assert!(!pulses.is_empty());
+    let dir = std::env::temp_dir();
+    let png = dir.join(format!("w7x_plot_{}.png", std::process::id()));
+    let gif = dir.join(format!("w7x_plot_{}.gif", std::process::id()));
+
+    write_plot(&png, &sim.state.history, &snapshots, &pulses, 1e19).unwrap();
+    write_animation(&gif, &snapshots).unwrap();
+    let png_bytes = std::fs::read(&png).unwrap();
+    let gif_bytes = std::fs::read(&gif).unwrap();
+    std::fs::remove_file(&png).unwrap();
+    std::fs::remove_file(&gif).unwrap();
+    assert!(png_bytes.starts_with(b"\x89PNG"));
+    assert!(gif_bytes.starts_with(b"GIF89a"));
+}
+
+/// Without recorded history there are no traces to draw.
+#[test]
+fn plot_needs_history() {
+    let (sim, snapshots, pulses) = run(false);
+    let png = std::env::temp_dir().join(format!("w7x_plot_empty_{}.png", std::process::id()));
+    let error = write_plot(&png, &sim.state.history, &snapshots, &pulses, 1e19).unwrap_err();
+    assert!(error.to_string().contains("keep_history"));
+    assert!(!png.exists());
+}
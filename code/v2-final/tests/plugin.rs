@@ -0,0 +1,183 @@
+//! Plugin ABI (`cargo test --features plugins --test plugin`). Builds the
+//! plugins with the system C compiler (`cc`) and skips without one.
+
+#![cfg(all(feature = "plugins", unix))]
+
+mod common;
+
+use common::measurement;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::controller::{ControlAction, Controller, PluginConfig, PulseCommand};
+use w7x_turbulence_control::plugin::PluginController;
+use w7x_turbulence_control::scan::run_scan;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::turbulence::{LocalProfiles, PluginModel, Turbulence, TurbulenceModel};
+
+/// Shared library from `source`, or `None` without a C compiler.
+fn build(name: &str, source: &Path, defines: &[&str]) -> Option<PathBuf> {
+    let library = std::env::temp_dir().join(format!("libw7x_plugin_{}_{}.so", std::process::id(), name));
+    let status = Command::new("cc")
+        .args(["-shared", "-fPIC", "-O2"])
+        .arg(format!("-I{}/examples", env!("CARGO_MANIFEST_DIR")))
+        .args(defines)
+        .arg(source)
+        .arg("-o")
+        .arg(&library)
+        .status();
+    match status {
+        Ok(status) if status.success() => Some(library),
+        _ => {
+            eprintln!("skipped: could not build {}", source.display());
+            None
+        }
+    }
+}
+
+fn example(name: &str) -> Option<PathBuf> {
+    build(name, &Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/plugin.c"), &[])
+}
+
+/// Turbulence-only plugin reporting `version` and failing every evaluation.
+fn broken(name: &str, version: u32) -> Option<PathBuf> {
+    let source = std::env::temp_dir().join(format!("w7x_plugin_{}_{}.c", std::process::id(), name));
+    std::fs::write(
+        &source,
+        r#"
+        #include <stdint.h>
+        uint32_t w7x_plugin_abi_version(void) { return VERSION; }
+        void *w7x_turbulence_create(const char *p) { static int m; return &m; }
+        int w7x_turbulence_evaluate(const void *m, const void *l, void *o) { return -1; }
+        void w7x_turbulence_destroy(void *m) {}
+        "#,
+    )
+    .unwrap();
+    let library = build(name, &source, &[&format!("-DVERSION={}", version)]);
+    std::fs::remove_file(source).ok();
+    library
+}
+
+fn plugin_model(path: &Path, parameters: &str) -> Turbulence {
+    Turbulence::Plugin(PluginModel {
+        path: path.to_string_lossy().into_owned(),
+        parameters: parameters.to_string(),
+        ..PluginModel::default()
+    })
+}
+
+fn local(temperature_gradient: f64) -> LocalProfiles {
+    LocalProfiles {
+        radius: 0.5,
+        electron_density: 5e19,
+        density_gradient: -5e19,
+        electron_temp: 2.0,
+        temperature_gradient,
+        d_turb_base: 0.5,
+        pulse_level: 0.0,
+    }
+}
+
+#[test]
+fn controller_decides_through_the_abi() {
+    let Some(path) = example("decide") else { return };
+    let config = PluginConfig { path: path.to_string_lossy().into_owned(), parameters: "sxr_limit=1e17".to_string() };
+    let mut controller = PluginController::load(&config).unwrap();
+    let pulse = ControlAction::Pulse(PulseCommand::new(0, None));
+    assert_eq!(controller.decide(&measurement(0.0, 5e16)), ControlAction::Hold);
+    assert_eq!(controller.decide(&measurement(0.1, 5e17)), pulse);
+    controller.pulse_started(0.1, 0.1);
+    assert_eq!(controller.decide(&measurement(0.15, 5e17)), ControlAction::Hold);
+    assert_eq!(controller.decide(&measurement(0.25, 5e17)), pulse);
+    drop(controller);
+    std::fs::remove_file(path).ok();
+}
+
+/// Batch modes run the configured plugin, not the threshold controller.
+#[test]
+fn scans_use_the_plugin() {
+    let Some(path) = example("scan") else { return };
+    let pulses = |parameters: &str| {
+        let mut config = Config::default();
+        config.simulation.t_max = 1.5;
+        let plugin = PluginConfig { path: path.to_string_lossy().into_owned(), parameters: parameters.to_string() };
+        config.controller.plugin = Some(plugin);
+        let results = run_scan(&config).unwrap();
+        results.into_iter().map(|r| r.summary.unwrap().pulses).collect::<Vec<_>>()
+    };
+    assert_eq!(pulses("sxr_limit=1e30"), [0]);
+    assert!(pulses("sxr_limit=0")[0] > 1);
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn refuses_missing_libraries_versions_and_parameters() {
+    let missing = PluginConfig { path: "/nonexistent/libw7x_plugin.so".to_string(), parameters: String::new() };
+    assert!(PluginController::load(&missing).is_err());
+
+    let Some(path) = example("refused") else { return };
+    let refused = PluginConfig { path: path.to_string_lossy().into_owned(), parameters: "nonsense".to_string() };
+    assert!(PluginController::load(&refused).is_err());
+
+    let Some(future) = broken("future", 2) else { return };
+    let error = plugin_model(&future, "").load().unwrap_err();
+    assert!(error.to_string().contains("ABI version 2"), "{}", error);
+    // Lacks the controller family
+    let Some(current) = broken("current", 1) else { return };
+    let config = PluginConfig { path: current.to_string_lossy().into_owned(), parameters: String::new() };
+    assert!(PluginController::load(&config).is_err());
+    for library in [path, future, current] {
+        std::fs::remove_file(library).ok();
+    }
+}
+
+#[test]
+fn turbulence_model_follows_the_library() {
+    let Some(path) = example("model") else { return };
+    let mut model = plugin_model(&path, "critical=2.0 stiffness=0.5");
+    assert_eq!(model.factor(&local(-4.0)), 1.0, "nominal until loaded");
+    model.load().unwrap();
+    // a/L_T = 2 at the threshold, 4 above it
+    assert!((model.factor(&local(-2.0)) - 0.3).abs() < 1e-12);
+    assert!((model.factor(&local(-8.0)) - 1.3).abs() < 1e-12);
+    assert_eq!(model.pinch(&local(-8.0)), 0.0);
+
+    let config = Config { turbulence: model, ..Config::default() };
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.run(0.05, |_| {}).unwrap();
+    assert!(sim.state.impurity_density.iter().all(|n| n.is_finite() && *n >= 0.0));
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn failing_evaluations_fall_back_to_nominal() {
+    let Some(path) = broken("failing", 1) else { return };
+    let mut model = plugin_model(&path, "");
+    model.load().unwrap();
+    assert_eq!(model.factor(&local(-4.0)), 1.0);
+    assert_eq!(model.pinch(&local(-4.0)), 0.0);
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn parses_and_rejects_two_custom_controllers() {
+    let text = r#"
+        [turbulence]
+        type = "plugin"
+        path = "libmodel.so"
+        parameters = "critical=3"
+
+        [controller]
+        plugin = { path = "libcontrol.so" }
+    "#;
+    let mut config: Config = toml::from_str(text).unwrap();
+    match &config.turbulence {
+        Turbulence::Plugin(model) => assert_eq!((model.path.as_str(), model.parameters.as_str()), ("libmodel.so", "critical=3")),
+        other => panic!("expected plugin, got {:?}", other),
+    }
+    assert_eq!(config.controller.plugin.as_ref().unwrap().path, "libcontrol.so");
+    assert!(config.validate().is_ok());
+    config.controller.script = Some(Default::default());
+    assert!(config.validate().is_err());
+}
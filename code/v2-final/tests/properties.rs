@@ -0,0 +1,132 @@
+//! Property tests of the transport update: for random plasma parameters
+//! and n_Z profiles, stepped at a random fraction of the explicit
+//! stability limit, n_Z and n_e stay finite, non-negative, and (n_Z)
+//! below the cap, with and without a pulse and in both geometries. Also
+//! that transport sub-steps keep a pulse stable at the Normal-mode dt.
+
+use proptest::prelude::*;
+use w7x_turbulence_control::config::{Config, TransportGeometry};
+use w7x_turbulence_control::controller::PulseCommand;
+use w7x_turbulence_control::events::Event;
+use w7x_turbulence_control::state::{StellaratorState, MAX_IMPURITY_DENSITY};
+
+const STEPS: usize = 150;
+
+#[derive(Clone, Debug)]
+struct Case {
+    nr: usize,
+    d_neo: f64,
+    d_turb_base: f64,
+    v_neo: f64,
+    impurity_source: f64,
+    pulse_amplitude: f64,
+    pulse_at: Option<usize>, // Step at which a pulse is forced
+    poloidal: bool,
+    courant: f64,            // dt / stability limit
+    profile: Vec<f64>,       // n_Z at evenly spaced radii, interpolated onto the grid
+}
+
+fn case() -> impl Strategy<Value = Case> {
+    (
+        (21usize..=81, 0.0..0.2, 0.0..3.0, -3.0..1.0, 0.0..1e19),
+        (1.0..10.0, proptest::option::of(0..STEPS), any::<bool>(), 0.05..0.95),
+        proptest::collection::vec(0.0..1e19, 2..8),
+    )
+        .prop_map(
+            |((nr, d_neo, d_turb_base, v_neo, impurity_source), (pulse_amplitude, pulse_at, poloidal, courant), profile)| Case {
+                nr,
+                d_neo,
+                d_turb_base,
+                v_neo,
+                impurity_source,
+                pulse_amplitude,
+                pulse_at,
+                poloidal,
+                courant,
+                profile,
+            },
+        )
+}
+
+fn state(case: &Case) -> StellaratorState {
+    let mut config = Config::default();
+    config.simulation.nr = case.nr;
+    config.plasma.d_neo = case.d_neo;
+    config.plasma.d_turb_base = case.d_turb_base;
+    config.plasma.v_neo = case.v_neo;
+    config.plasma.impurity_source = case.impurity_source;
+    config.plasma.pulse_amplitude = case.pulse_amplitude;
+    if case.poloidal {
+        config.simulation.geometry = TransportGeometry::Poloidal;
+    }
+    let mut state = StellaratorState::from_config(&config);
+    state.verbose = false;
+    state.history.recording = false;
+    let segments = (case.profile.len() - 1) as f64;
+    for (i, &r) in state.radius_grid.clone().iter().enumerate() {
+        let x = r * segments;
+        let j = (x as usize).min(case.profile.len() - 2);
+        let w = x - j as f64;
+        state.impurity_density[i] = case.profile[j] + w * (case.profile[j + 1] - case.profile[j]);
+    }
+    state
+}
+
+fn check(state: &StellaratorState) -> Result<(), TestCaseError> {
+    for (i, &n) in state.impurity_density.iter().enumerate() {
+        prop_assert!(n.is_finite(), "n_Z[{}] = {} at t = {}", i, n, state.time);
+        prop_assert!(n >= 0.0, "n_Z[{}] = {:e} at t = {}", i, n, state.time);
+        prop_assert!(n <= MAX_IMPURITY_DENSITY, "n_Z[{}] = {:e} at t = {}", i, n, state.time);
+    }
+    for (i, &n) in state.electron_density.iter().enumerate() {
+        prop_assert!(n.is_finite() && n >= 0.0, "n_e[{}] = {:e} at t = {}", i, n, state.time);
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(48))]
+
+    #[test]
+    fn transport_keeps_densities_finite_non_negative_and_capped(case in case()) {
+        let mut state = state(&case);
+        for step in 0..STEPS {
+            if case.pulse_at == Some(step) {
+                state.force_pulse(PulseCommand::new(0, None));
+            }
+            let limit = state.stability_limit();
+            let dt = if limit.is_finite() { case.courant * limit } else { 1e-4 };
+            state.update(dt.min(1e-3));
+            check(&state)?;
+        }
+    }
+}
+
+/// A pulse with 20x D_turb stepped at the Normal-mode limit: v2 hits the
+/// clamps, sub-stepping matches a run at a pulse-safe dt without a CFL
+/// violation.
+#[test]
+fn substeps_keep_pulses_stable_at_the_normal_mode_dt() {
+    let run = |max_substeps: usize, refine: usize| {
+        let mut config = Config::default();
+        config.plasma.pulse_amplitude = 20.0;
+        config.numerics.max_substeps = max_substeps;
+        let mut state = StellaratorState::from_config(&config);
+        state.verbose = false;
+        let dt = state.stability_limit() / refine as f64;
+        state.force_pulse(PulseCommand::new(0, None));
+        for _ in 0..(0.05 / dt).round() as usize {
+            state.update(dt);
+        }
+        let violations = state.drain_events().iter().filter(|e| matches!(e.event, Event::CflViolation { .. })).count();
+        (state.impurity_density, violations)
+    };
+    let (v2, _) = run(1, 1);
+    assert!(v2.iter().any(|&n| n == 0.0 || n == MAX_IMPURITY_DENSITY));
+    let (substepped, violations) = run(100, 1);
+    let (reference, _) = run(1, 64);
+    assert_eq!(violations, 0);
+    for (i, (n, expected)) in substepped.iter().zip(&reference).enumerate() {
+        assert!((n - expected).abs() < 0.01 * expected, "n_Z[{}] = {:e}, expected {:e}", i, n, expected);
+    }
+}
@@ -0,0 +1,110 @@
+//! Ramp-up and ramp-down phases.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::events::Event;
+use w7x_turbulence_control::ramp::{Phase, Ramp};
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::state::StellaratorState;
+
+fn ramp(ramp_up: f64, ramp_down_start: f64, ramp_down: f64) -> Ramp {
+    Ramp { ramp_up, ramp_down_start, ramp_down, ..Ramp::default() }
+}
+
+#[test]
+fn disabled_by_default_at_the_flat_top() {
+    let ramp = Ramp::default();
+    assert!(!ramp.enabled());
+    for t in [0.0, 1.0, 100.0] {
+        assert_eq!(ramp.phase(t), Phase::FlatTop);
+        let levels = ramp.levels(t);
+        assert_eq!((levels.heating, levels.density, levels.source), (1.0, 1.0, 1.0));
+    }
+}
+
+#[test]
+fn phases_and_linear_levels() {
+    let ramp = ramp(1.0, 3.0, 2.0);
+    assert_eq!(ramp.phase(0.0), Phase::RampUp);
+    assert_eq!(ramp.phase(2.0), Phase::FlatTop);
+    assert_eq!(ramp.phase(4.0), Phase::RampDown);
+    assert_eq!(ramp.phase(5.0), Phase::Ended);
+
+    let start = ramp.levels(0.0);
+    assert!((start.density - ramp.density_fraction).abs() < 1e-12);
+    assert!((start.source - ramp.source_fraction).abs() < 1e-12);
+    let half = ramp.levels(0.5).heating;
+    assert!((half - 0.5 * (1.0 + ramp.heating_fraction)).abs() < 1e-12);
+    assert_eq!(ramp.levels(2.0).density, 1.0);
+    assert!((ramp.levels(4.0).density - 0.5 * (1.0 + ramp.density_fraction)).abs() < 1e-12);
+    assert!((ramp.levels(6.0).source - ramp.source_fraction).abs() < 1e-12);
+}
+
+#[test]
+fn run_starts_from_the_ramp_start_profiles() {
+    let flat = StellaratorState::from_config(&Config::default());
+    let config = Config { ramp: ramp(0.5, 0.0, 0.0), ..Config::default() };
+    let ramped = StellaratorState::from_config(&config);
+
+    let fraction = config.ramp.density_fraction;
+    assert!((ramped.electron_density[0] - fraction * flat.electron_density[0]).abs() < 1e-6 * flat.electron_density[0]);
+    assert!(ramped.impurity_density[0] < flat.impurity_density[0]);
+    assert!(ramped.electron_temp[0] < flat.electron_temp[0]);
+    let edge = flat.nr - 1;
+    assert_eq!(ramped.electron_temp[edge], flat.electron_temp[edge]);
+    assert!((ramped.constant_source() - config.ramp.source_fraction * flat.impurity_source).abs() < 1e-12 * flat.impurity_source);
+}
+
+#[test]
+fn density_and_temperature_reach_the_flat_top() {
+    let config = Config { ramp: ramp(0.2, 0.0, 0.0), ..Config::default() };
+    let flat = StellaratorState::from_config(&Config::default());
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.run(0.5, |_| {}).unwrap();
+
+    assert!((sim.state.electron_density[0] / flat.electron_density[0] - 1.0).abs() < 1e-9);
+    assert!(sim.state.electron_temp[0] > 0.9 * flat.electron_temp[0]);
+    let phases: Vec<Phase> = sim
+        .state
+        .drain_events()
+        .into_iter()
+        .filter_map(|e| match e.event {
+            Event::PhaseStarted { phase } => Some(phase),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(phases, [Phase::FlatTop]);
+}
+
+#[test]
+fn ramp_down_returns_to_the_end_levels() {
+    let config = Config { ramp: ramp(0.0, 0.1, 0.1), ..Config::default() };
+    let flat = StellaratorState::from_config(&Config::default());
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.run(0.3, |_| {}).unwrap();
+
+    let ratio = sim.state.electron_density[0] / flat.electron_density[0];
+    assert!((ratio - config.ramp.density_fraction).abs() < 1e-9);
+    let phases: Vec<Phase> = sim
+        .state
+        .drain_events()
+        .into_iter()
+        .filter_map(|e| match e.event {
+            Event::PhaseStarted { phase } => Some(phase),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(phases, [Phase::RampDown, Phase::Ended]);
+}
+
+#[test]
+fn invalid_ramps_are_rejected() {
+    let mut config = Config { ramp: ramp(1.0, 0.5, 1.0), ..Config::default() };
+    assert!(config.validate().is_err());
+    config.ramp = ramp(1.0, 2.0, 1.0);
+    config.ramp.source_fraction = 1.5;
+    assert!(config.validate().is_err());
+    config.ramp.source_fraction = 0.0;
+    assert!(config.validate().is_ok());
+}
@@ -0,0 +1,51 @@
+//! Wall recycling.
+
+use ndarray::Array1;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::geometry::Metric;
+use w7x_turbulence_control::state::StellaratorState;
+
+/// V' = ρ (1 + ρ), a shaped rather than cylindrical volume.
+fn shaped(radius: &Array1<f64>, dr: f64) -> Metric {
+    let vprime = |r: f64| r * (1.0 + r);
+    Metric {
+        vprime: radius.mapv(vprime),
+        vprime_outer: radius.mapv(|r| vprime(r + 0.5 * dr)),
+        vprime_inner: radius.mapv(|r| vprime((r - 0.5 * dr).max(0.0))),
+        grad_rho: Array1::ones(radius.len()),
+        grad_rho2: Array1::ones(radius.len()),
+    }
+}
+
+#[test]
+fn full_recycling_conserves_impurities_in_shaped_geometry() {
+    let mut config = Config::default();
+    config.plasma.impurity_source = 0.0;
+    config.boundary.recycling = 1.0;
+    config.boundary.residence_time = 0.01;
+    let mut state = StellaratorState::from_config(&config);
+    state.verbose = false;
+    state.metric = shaped(&state.radius_grid, state.dr);
+    let total = |state: &StellaratorState| state.impurity_inventory() + state.recycling.inventory();
+
+    let start = total(&state);
+    for _ in 0..2000 {
+        state.update(1e-4);
+    }
+    assert!(state.recycling.inventory() > 0.0);
+    // Down to the discretization error of the transport step; a recycled
+    // flux in cylindrical units was off by percent
+    assert!((total(&state) / start - 1.0).abs() < 1e-3);
+}
+
+#[test]
+fn recycling_outside_the_unit_interval_is_rejected() {
+    let mut config = Config::default();
+    config.boundary.recycling = 1.2;
+    assert!(config.validate().is_err());
+    config.boundary.recycling = 1.0;
+    config.boundary.residence_time = -0.01;
+    assert!(config.validate().is_err());
+    config.boundary.residence_time = 0.0;
+    assert!(config.validate().is_ok());
+}
@@ -0,0 +1,75 @@
+//! Mismatch against measured reference traces.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::history::Channel;
+use w7x_turbulence_control::reference::{self, ReferenceConfig, ReferenceTrace};
+use w7x_turbulence_control::scan::run_quiet;
+
+fn sine(shift: f64) -> ReferenceTrace {
+    let time: Vec<f64> = (0..400).map(|i| i as f64 * 0.001).collect();
+    let value = time.iter().map(|t| (2.0 * std::f64::consts::PI * 5.0 * (t - shift)).sin()).collect();
+    ReferenceTrace { time, value }
+}
+
+#[test]
+fn identical_traces_match_exactly() {
+    let measured = sine(0.0);
+    let m = reference::mismatch(&measured, &measured.value, 0.02);
+    assert!(m.rmse < 1e-12 && m.bias.abs() < 1e-12);
+    assert!((m.correlation - 1.0).abs() < 1e-12);
+    assert_eq!(m.lag, 0.0);
+}
+
+/// A simulation running 10 ms late is found at lag +10 ms.
+#[test]
+fn cross_correlation_recovers_the_lag() {
+    let measured = sine(0.0);
+    let late = sine(0.01);
+    let m = reference::mismatch(&measured, &late.value, 0.02);
+    assert!((m.lag - 0.01).abs() < 1e-9, "lag {}", m.lag);
+    assert!(m.lag_correlation > 0.999);
+    assert!(m.correlation < m.lag_correlation);
+
+    let early = sine(-0.01);
+    assert!((reference::mismatch(&measured, &early.value, 0.02).lag + 0.01).abs() < 1e-9);
+}
+
+/// A reference taken from the model itself is reproduced on its own time base.
+#[test]
+fn simulation_reproduces_its_own_trace() {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.05;
+    let mut measured = ReferenceTrace::default();
+    run_quiet(&config, |state| {
+        let sample = state.last_sample();
+        if measured.time.last().is_none_or(|&t| sample.time >= t + 0.005) {
+            measured.time.push(sample.time);
+            measured.value.push(2.0 * sample.center_impurity);
+        }
+    })
+    .unwrap();
+
+    let simulated = reference::simulate(&config, &measured, Channel::CenterImpurity, 2.0).unwrap();
+    assert_eq!(simulated.len(), measured.len());
+    let m = reference::mismatch(&measured, &simulated, 0.0);
+    assert!(m.normalized_rmse < 1e-9, "normalized RMSE {}", m.normalized_rmse);
+}
+
+#[test]
+fn loads_csv_with_comments_and_offset() {
+    let path = std::env::temp_dir().join(format!("w7x_reference_{}.csv", std::process::id()));
+    std::fs::write(&path, "# shot 20180808.024\ntime,prad,nz\n-0.1,9,1\n0.0,1,2\n0.1,2,3\n0.2,3,4\n").unwrap();
+    let settings = ReferenceConfig {
+        path: path.to_string_lossy().into_owned(),
+        column: "nz".to_string(),
+        time_offset: 0.05,
+        ..ReferenceConfig::default()
+    };
+    let trace = reference::load(&settings).unwrap();
+    assert_eq!(trace.value, vec![2.0, 3.0, 4.0]);
+    assert!((trace.time[0] - 0.05).abs() < 1e-12);
+
+    let missing = ReferenceConfig { column: "bolo".to_string(), ..settings };
+    assert!(reference::load(&missing).is_err());
+    std::fs::remove_file(path).ok();
+}
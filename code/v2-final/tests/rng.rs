@@ -0,0 +1,97 @@
+//! Seed management: identical seeds give bit-identical runs.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::ensemble;
+use w7x_turbulence_control::metadata::RunMetadata;
+use w7x_turbulence_control::rng::{self, RngRegistry, Stream};
+use w7x_turbulence_control::simulation::Simulation;
+
+/// Every recorded channel and every measurement, as raw bits.
+fn trace(config: &Config) -> Vec<u64> {
+    let mut sim = Simulation::from_config(config);
+    sim.state.verbose = false;
+    let mut bits = Vec::new();
+    while sim.state.time < config.simulation.t_max {
+        sim.step();
+        let s = sim.state.last_sample();
+        bits.extend([s.time, s.center_impurity, s.edge_impurity, s.turbulence, s.stored_energy].map(f64::to_bits));
+        if let Some(m) = sim.last_measurement() {
+            bits.extend([m.central_sxr, m.edge_density, m.turbulence].map(f64::to_bits));
+        }
+    }
+    bits
+}
+
+fn config(seed: Option<u64>) -> Config {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.05;
+    config.seed = seed;
+    config
+}
+
+#[test]
+fn identical_seeds_give_bit_identical_traces() {
+    assert_eq!(trace(&config(Some(2024))), trace(&config(Some(2024))));
+    assert_eq!(trace(&config(None)), trace(&config(None)));
+    assert_ne!(trace(&config(Some(2024))), trace(&config(Some(2025))));
+}
+
+#[test]
+fn identical_seeds_give_identical_ensembles() {
+    let mut base = config(Some(99));
+    base.ensemble.replicas = 4;
+    let (a, b) = (ensemble::run_ensemble(&base).unwrap(), ensemble::run_ensemble(&base).unwrap());
+    for (x, y) in a.replicas.iter().zip(&b.replicas) {
+        assert_eq!(x.d_neo.to_bits(), y.d_neo.to_bits());
+        assert_eq!(x.impurity_source.to_bits(), y.impurity_source.to_bits());
+    }
+    for (x, y) in a.bands.iter().flatten().zip(b.bands.iter().flatten()) {
+        assert_eq!(x.to_bits(), y.to_bits());
+    }
+}
+
+#[test]
+fn master_seed_derives_distinct_streams() {
+    let registry = RngRegistry::new(&config(Some(1)));
+    assert_eq!(registry.master(), Some(1));
+    let mut seeds: Vec<u64> = Stream::ALL.iter().map(|&s| registry.seed(s)).collect();
+    seeds.sort_unstable();
+    seeds.dedup();
+    assert_eq!(seeds.len(), Stream::ALL.len());
+    // The derivation is part of the reproducibility contract
+    assert_eq!(registry.seed(Stream::Diagnostics), rng::derive(1, "diagnostics"));
+}
+
+/// Without a master seed the v2 section seeds apply unchanged.
+#[test]
+fn section_seeds_without_master() {
+    let mut config = config(None);
+    config.diagnostics.seed = 5;
+    config.evolve.seed = 6;
+    let registry = RngRegistry::new(&config);
+    assert_eq!(registry.seed(Stream::Diagnostics), 5);
+    assert_eq!(registry.seed(Stream::Evolve), 6);
+
+    rng::seed_batch_member(&mut config, 3);
+    assert_eq!(config.diagnostics.seed, 8);
+}
+
+#[test]
+fn metadata_records_master_and_streams() {
+    let config = config(Some(77));
+    let metadata = RunMetadata::new(&config, "2026-01-01T00:00:00Z".to_string(), "host".to_string());
+    assert_eq!(metadata.seed, 77);
+    assert_eq!(metadata.streams["ensemble"], RngRegistry::new(&config).seed(Stream::Ensemble));
+}
+
+#[test]
+fn streams_keep_the_v2_generator() {
+    use rand::rngs::StdRng;
+    use rand::{RngCore, SeedableRng};
+    let registry = RngRegistry::new(&config(Some(3)));
+    let mut stream = registry.rng(Stream::Elms);
+    let mut std = StdRng::seed_from_u64(registry.seed(Stream::Elms));
+    for _ in 0..64 {
+        assert_eq!(stream.next_u64(), std.next_u64());
+    }
+}
@@ -0,0 +1,82 @@
+//! Sawtooth core relaxation.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::events::Event;
+use w7x_turbulence_control::sawtooth::SawtoothTrigger;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::state::StellaratorState;
+
+fn crash_times(config: &Config) -> Vec<f64> {
+    let mut sim = Simulation::from_config(config);
+    sim.state.verbose = false;
+    let mut times = Vec::new();
+    while sim.state.time < config.simulation.t_max {
+        sim.step();
+        times.extend(
+            sim.state
+                .drain_events()
+                .iter()
+                .filter(|e| matches!(e.event, Event::SawtoothCrash { .. }))
+                .map(|e| e.time),
+        );
+    }
+    times
+}
+
+#[test]
+fn crash_flattens_the_core_and_conserves_content() {
+    let mut config = Config::default();
+    config.sawtooth.mixing_radius = 0.3;
+    let mut state = StellaratorState::from_config(&config);
+    state.verbose = false;
+    // Peaked n_Z and T_e
+    for (i, &r) in state.radius_grid.clone().iter().enumerate() {
+        state.impurity_density[i] = 1e18 * (1.0 - r * r) + 1e16;
+    }
+    let inventory = state.impurity_inventory();
+    let te_edge = state.electron_temp[state.nr - 1];
+
+    state.sawtooth_crash();
+    let inside: Vec<usize> = (0..state.nr).filter(|&i| state.radius_grid[i] <= 0.3).collect();
+    let core = state.impurity_density[0];
+    assert!(core < 1e18);
+    for &i in &inside {
+        assert_eq!(state.impurity_density[i], core);
+        assert_eq!(state.electron_temp[i], state.electron_temp[0]);
+    }
+    let outside = inside.len();
+    assert!((state.impurity_density[outside] - (1e18 * (1.0 - state.radius_grid[outside].powi(2)) + 1e16)).abs() < 1.0);
+    assert!((state.impurity_inventory() - inventory).abs() <= 1e-12 * inventory);
+    assert_eq!(state.electron_temp[state.nr - 1], te_edge);
+
+    let events = state.drain_events();
+    assert!(matches!(events.as_slice(), [e] if matches!(e.event, Event::SawtoothCrash { .. })));
+}
+
+#[test]
+fn periodic_trigger_crashes_every_period() {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.1;
+    config.sawtooth.trigger = SawtoothTrigger::Periodic;
+    config.sawtooth.period = 0.02;
+    let times = crash_times(&config);
+    assert!((4..=5).contains(&times.len()), "crashes at {:?}", times);
+    for pair in times.windows(2) {
+        assert!((pair[1] - pair[0] - 0.02).abs() < 2.0 * config.simulation.dt);
+    }
+}
+
+#[test]
+fn peaking_trigger_respects_min_interval() {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.1;
+    config.sawtooth.trigger = SawtoothTrigger::Peaking;
+    config.sawtooth.peaking_threshold = 1.0; // Any peaked core crashes
+    config.sawtooth.min_interval = 0.03;
+    let times = crash_times(&config);
+    assert!(!times.is_empty());
+    assert!(times.windows(2).all(|pair| pair[1] - pair[0] >= 0.03));
+
+    config.sawtooth.trigger = SawtoothTrigger::Off;
+    assert!(crash_times(&config).is_empty());
+}
@@ -0,0 +1,199 @@
+//! Rhai control laws (`cargo test --features scripting --test script`).
+
+#![cfg(feature = "scripting")]
+
+mod common;
+
+use common::measurement;
+use std::path::PathBuf;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::controller::{ControlAction, Controller, PulseCommand, ScriptConfig};
+use w7x_turbulence_control::events::Event;
+use w7x_turbulence_control::scan::run_scan;
+use w7x_turbulence_control::script::ScriptController;
+use w7x_turbulence_control::simulation::Simulation;
+
+fn script(name: &str, text: &str) -> (PathBuf, ScriptConfig) {
+    let path = std::env::temp_dir().join(format!("w7x_script_{}_{}.rhai", std::process::id(), name));
+    std::fs::write(&path, text).unwrap();
+    let config = ScriptConfig { path: path.to_string_lossy().into_owned(), ..ScriptConfig::default() };
+    (path, config)
+}
+
+#[test]
+fn results_map_to_actions() {
+    let (path, config) = script(
+        "actions",
+        r#"
+        fn decide(obs) {
+            if obs.central_sxr < 1e17 { false }
+            else if obs.central_sxr < 1e18 { true }
+            else if obs.central_sxr < 1e19 { "hold" }
+            else { #{ window: 1, amplitude: 4 } }
+        }
+        "#,
+    );
+    let mut controller = ScriptController::load(&config).unwrap();
+    assert_eq!(controller.decide(&measurement(0.0, 1e16)), ControlAction::Hold);
+    assert_eq!(controller.decide(&measurement(0.0, 5e17)), ControlAction::TriggerPulse);
+    assert_eq!(controller.decide(&measurement(0.0, 5e18)), ControlAction::Hold);
+    assert_eq!(
+        controller.decide(&measurement(0.0, 5e19)),
+        ControlAction::Pulse(PulseCommand::new(1, Some(4.0)))
+    );
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn state_persists_in_this_and_sees_pulses() {
+    let (path, config) = script(
+        "memory",
+        r#"
+        fn decide(obs) {
+            if this.calls == () { this.calls = 0; }
+            this.calls += 1;
+            this.calls == 3 && !obs.pulsing && obs.last_pulse_end == ()
+        }
+        "#,
+    );
+    let mut controller = ScriptController::load(&config).unwrap();
+    assert_eq!(controller.decide(&measurement(0.0, 0.0)), ControlAction::Hold);
+    assert_eq!(controller.decide(&measurement(0.1, 0.0)), ControlAction::Hold);
+    assert_eq!(controller.decide(&measurement(0.2, 0.0)), ControlAction::TriggerPulse);
+    controller.pulse_started(0.2, 0.1);
+    assert_eq!(controller.decide(&measurement(0.25, 0.0)), ControlAction::Hold);
+    std::fs::remove_file(path).ok();
+}
+
+/// A resumed script continues with its `this` map and last pulse.
+#[test]
+fn state_survives_a_checkpoint() {
+    let (path, config) = script(
+        "checkpoint",
+        r#"
+        fn decide(obs) {
+            if this.calls == () { this.calls = 0; }
+            this.calls += 1;
+            this.calls == 3 && obs.last_pulse_end != ()
+        }
+        "#,
+    );
+    let mut controller = ScriptController::load(&config).unwrap();
+    assert_eq!(controller.decide(&measurement(0.0, 0.0)), ControlAction::Hold);
+    assert_eq!(controller.decide(&measurement(0.1, 0.0)), ControlAction::Hold);
+    controller.pulse_started(0.1, 0.05);
+    let saved = controller.save_state().unwrap();
+
+    let mut resumed = ScriptController::load(&config).unwrap();
+    resumed.restore_state(saved).unwrap();
+    assert_eq!(resumed.decide(&measurement(0.2, 0.0)), ControlAction::TriggerPulse);
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn top_level_constants_are_visible_in_decide() {
+    let (path, config) = script(
+        "globals",
+        r#"
+        const LIMIT = 5e17;
+        fn decide(obs) { obs.central_sxr > global::LIMIT }
+        "#,
+    );
+    let mut controller = ScriptController::load(&config).unwrap();
+    assert_eq!(controller.decide(&measurement(0.0, 1e17)), ControlAction::Hold);
+    assert_eq!(controller.decide(&measurement(0.1, 6e17)), ControlAction::TriggerPulse);
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn runaway_and_failing_scripts_hold() {
+    let (path, config) = script("loop", "fn decide(obs) { loop { } }");
+    let mut controller = ScriptController::load(&config).unwrap();
+    assert_eq!(controller.decide(&measurement(0.0, 1e19)), ControlAction::Hold);
+
+    let (path2, config) = script("type", "fn decide(obs) { 42 }");
+    let mut controller = ScriptController::load(&config).unwrap();
+    assert_eq!(controller.decide(&measurement(0.0, 1e19)), ControlAction::Hold);
+    std::fs::remove_file(path).ok();
+    std::fs::remove_file(path2).ok();
+}
+
+#[test]
+fn sandbox_rejects_eval_and_missing_decide() {
+    let (path, config) = script("eval", r#"fn decide(obs) { eval("true") }"#);
+    if let Ok(mut controller) = ScriptController::load(&config) {
+        assert_eq!(controller.decide(&measurement(0.0, 0.0)), ControlAction::Hold);
+    }
+    let (path2, config) = script("missing", "fn other(obs) { true }");
+    assert!(ScriptController::load(&config).is_err());
+    std::fs::remove_file(path).ok();
+    std::fs::remove_file(path2).ok();
+}
+
+/// `import` cannot reach the file system, even for a readable script.
+#[test]
+fn sandbox_rejects_imports() {
+    let (module, _) = script("module", "fn limit() { 1e17 }");
+    let text = format!(r#"import "{}" as m; fn decide(obs) {{ true }}"#, module.with_extension("").display());
+    let (path, config) = script("import", &text);
+    assert!(ScriptController::load(&config).is_err());
+
+    let (path2, config) = script("import_in_call", r#"fn decide(obs) { import "x" as m; true }"#);
+    if let Ok(mut controller) = ScriptController::load(&config) {
+        assert_eq!(controller.decide(&measurement(0.0, 1e19)), ControlAction::Hold);
+    }
+    std::fs::remove_file(path).ok();
+    std::fs::remove_file(path2).ok();
+    std::fs::remove_file(module).ok();
+}
+
+#[test]
+fn reloads_when_the_file_changes() {
+    let (path, config) = script("reload", "fn decide(obs) { false }");
+    let mut controller = ScriptController::load(&config).unwrap();
+    assert_eq!(controller.decide(&measurement(0.0, 0.0)), ControlAction::Hold);
+
+    // Modification times can be coarse, and are checked once a second
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    std::fs::write(&path, "fn decide(obs) { true }").unwrap();
+    assert_eq!(controller.decide(&measurement(0.1, 0.0)), ControlAction::TriggerPulse);
+
+    // A broken edit keeps the previous law
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    std::fs::write(&path, "fn decide(obs) { ").unwrap();
+    assert_eq!(controller.decide(&measurement(0.2, 0.0)), ControlAction::TriggerPulse);
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn example_script_controls_a_run() {
+    let config = ScriptConfig { path: format!("{}/examples/control.rhai", env!("CARGO_MANIFEST_DIR")), ..ScriptConfig::default() };
+    let run = Config::default();
+    let mut sim = Simulation::from_config(&run);
+    sim.state.verbose = false;
+    sim.controller = run.controller.guarded(Box::new(ScriptController::load(&config).unwrap()), run.plasma.pulse_duration);
+    sim.run(2.0, |_| {}).unwrap();
+    let events = sim.state.drain_events();
+    assert!(events.iter().any(|e| matches!(e.event, Event::PulseStarted { .. })));
+}
+
+/// Batch modes run the configured script, not the threshold controller.
+#[test]
+fn scans_use_the_script() {
+    let pulses = |name: &str, text: &str| {
+        let (path, script) = script(name, text);
+        let mut config = Config::default();
+        config.simulation.t_max = 1.5;
+        config.controller.script = Some(script);
+        config.scan.cooldown = vec![0.2, 0.4];
+        let results = run_scan(&config).unwrap();
+        std::fs::remove_file(path).ok();
+        results.into_iter().map(|r| r.summary.unwrap().pulses).collect::<Vec<_>>()
+    };
+    assert!(pulses("scan_hold", "fn decide(obs) { false }").iter().all(|&p| p == 0));
+    assert!(pulses("scan_pulse", "fn decide(obs) { true }").iter().all(|&p| p > 1));
+
+    let mut config = Config::default();
+    config.controller.script = Some(ScriptConfig { path: "/nonexistent/control.rhai".to_string(), ..ScriptConfig::default() });
+    assert!(run_scan(&config).unwrap().iter().all(|r| r.summary.is_err()));
+}
@@ -0,0 +1,79 @@
+//! Scrape-off-layer reservoir.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::sol::{Sol, SolReservoir};
+
+fn sol(parallel_loss_time: f64, leakage_time: f64) -> Sol {
+    Sol { enabled: true, parallel_loss_time, leakage_time, ..Sol::default() }
+}
+
+// Σ V' dr of the source region the SOL leaks into
+const SOURCE_VOLUME: f64 = 0.14;
+
+fn reservoir(parallel_loss_time: f64, leakage_time: f64) -> SolReservoir {
+    SolReservoir::new(sol(parallel_loss_time, leakage_time), 1.0)
+}
+
+#[test]
+fn drains_to_divertor_and_leaks_in_proportion() {
+    let mut sol = reservoir(0.01, 0.03);
+    sol.fill(1e17);
+    let start = sol.inventory();
+    assert!((sol.density() - 1e17).abs() < 1.0);
+
+    let dt = 1e-3;
+    let exhaust = sol.step(0.0, dt, SOURCE_VOLUME);
+    let removed = start - sol.inventory();
+    assert!((removed / start - (1.0 - (-dt * (1.0 / 0.01 + 1.0 / 0.03)).exp())).abs() < 1e-12);
+    // τ_leak = 3 τ_∥: a quarter of the loss leaks back
+    let leaked = exhaust.leakage * dt * SOURCE_VOLUME;
+    assert!((leaked / removed - 0.25).abs() < 1e-12);
+    assert!((exhaust.parallel * dt / removed - 0.75).abs() < 1e-12);
+    assert!((sol.exhausted() - exhaust.parallel * dt).abs() < 1e-12 * start);
+}
+
+#[test]
+fn no_leakage_sends_everything_to_the_divertor() {
+    let mut sol = reservoir(0.01, 0.0);
+    let mut exhausted = 0.0;
+    for _ in 0..1000 {
+        let exhaust = sol.step(1e15, 1e-4, SOURCE_VOLUME);
+        assert_eq!(exhaust.leakage, 0.0);
+        exhausted += exhaust.parallel * 1e-4;
+    }
+    // Steady: N = Γ τ_∥, up to the O(dt / τ_∥) lag of the step
+    assert!((sol.inventory() / (1e15 * 0.01) - 1.0).abs() < 1e-2);
+    assert!((exhausted + sol.inventory() - 1e15 * 0.1).abs() < 1e-9 * 1e15 * 0.1);
+}
+
+#[test]
+fn edge_follows_the_reservoir() {
+    let config = Config { sol: sol(0.005, 0.05), ..Config::default() };
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    let edge = sim.state.nr - 1;
+    assert!((sim.state.sol.density() - sim.state.impurity_density[edge]).abs() <= 1e-9 * sim.state.impurity_density[edge]);
+
+    sim.run(0.05, |state| {
+        let edge_density = state.impurity_density[state.nr - 1];
+        assert!(edge_density >= 0.0);
+    })
+    .unwrap();
+    assert!(sim.state.sol.exhausted() > 0.0);
+}
+
+#[test]
+fn slow_exhaust_keeps_impurities_out_of_the_divertor() {
+    // (plasma + SOL, divertor)
+    let run = |parallel_loss_time: f64| {
+        let config = Config { sol: sol(parallel_loss_time, 0.01), ..Config::default() };
+        let mut sim = Simulation::from_config(&config);
+        sim.state.verbose = false;
+        sim.run(0.2, |_| {}).unwrap();
+        (sim.state.impurity_inventory() + sim.state.sol.inventory(), sim.state.sol.exhausted())
+    };
+    let (slow, fast) = (run(1.0), run(1e-3));
+    assert!(slow.0 > fast.0);
+    assert!(slow.1 < fast.1);
+}
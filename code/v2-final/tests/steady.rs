@@ -0,0 +1,57 @@
+//! Direct steady-state solve of the n_Z transport.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::state::StellaratorState;
+use w7x_turbulence_control::steady::{self, SteadyState};
+
+fn solve(config: &Config) -> SteadyState {
+    let state = StellaratorState::from_config(config);
+    steady::solve(&state, &config.steady_state).expect("edge losses keep the operator regular")
+}
+
+/// The default scenario has a bounded steady state; with a constant source
+/// one solve is exact, and a damped iteration reaches the same profile.
+#[test]
+fn default_scenario_reaches_steady_state() {
+    let config = Config::default();
+    let steady = solve(&config);
+    assert!(steady.converged && steady.bounded);
+    assert!(steady.iterations <= 2, "{} iterations", steady.iterations);
+    assert_eq!(steady.wall_source, config.plasma.impurity_source);
+    let state = StellaratorState::from_config(&config);
+    assert!(steady.peaking(&state) > 1.0);
+
+    let mut damped = config.clone();
+    damped.steady_state.relaxation = 0.5;
+    damped.steady_state.max_iterations = 200;
+    let relaxed = solve(&damped);
+    assert!(relaxed.converged && relaxed.iterations > steady.iterations);
+    for (a, b) in relaxed.impurity_density.iter().zip(&steady.impurity_density) {
+        assert!((a - b).abs() <= 1e-6 * b.abs(), "{a} vs {b}");
+    }
+}
+
+/// A pinch far stronger than the diffusion has no non-negative steady
+/// state: a time-dependent run accumulates instead of settling.
+#[test]
+fn strong_pinch_never_settles() {
+    let mut config = Config::default();
+    config.plasma.v_neo = -2.0;
+    config.plasma.d_neo = 1e-3;
+    config.plasma.d_turb_base = 0.0;
+    let steady = solve(&config);
+    assert!(!steady.bounded);
+    assert!(steady.impurity_density.iter().any(|&n| n < 0.0));
+}
+
+/// Too few damped iterations stop short and say so.
+#[test]
+fn iteration_limit_is_reported_as_not_converged() {
+    let mut config = Config::default();
+    config.steady_state.relaxation = 0.5;
+    config.steady_state.max_iterations = 3;
+    let steady = solve(&config);
+    assert!(!steady.converged);
+    assert_eq!(steady.iterations, 3);
+    assert!(steady.change > config.steady_state.tolerance);
+}
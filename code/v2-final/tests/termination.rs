@@ -0,0 +1,160 @@
+//! Radiative collapse, emergency flush, and termination.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::events::Event;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::splitting::Splitting;
+use w7x_turbulence_control::state::ConfinementMode;
+use w7x_turbulence_control::termination::{Observed, Termination};
+
+fn enabled() -> Termination {
+    Termination { enabled: true, ..Termination::default() }
+}
+
+fn observed(center_impurity: f64, center_temp: f64, time_in_mode: f64) -> Observed {
+    Observed { center_impurity, center_temp, time_in_mode }
+}
+
+fn next(termination: &Termination, mode: ConfinementMode, observed: Observed) -> Option<ConfinementMode> {
+    termination.transition(mode, observed).map(|(mode, _)| mode)
+}
+
+fn modes(sim: &mut Simulation, t_max: f64) -> Vec<ConfinementMode> {
+    let mut modes = Vec::new();
+    sim.run(t_max, |_| {}).unwrap();
+    for event in sim.state.drain_events() {
+        if let Event::ModeChanged { mode, .. } = event.event {
+            modes.push(mode);
+        }
+    }
+    modes
+}
+
+#[test]
+fn disabled_by_default() {
+    let termination = Termination::default();
+    assert!(!termination.enabled);
+    assert_eq!(next(&termination, ConfinementMode::Normal, observed(1e21, 0.0, 10.0)), None);
+}
+
+#[test]
+fn transition_table() {
+    let t = enabled();
+    let high = 2.0 * t.collapse_density;
+    let mid = 0.5 * (t.collapse_density + t.recovery_density);
+    let low = 0.5 * t.recovery_density;
+    let hot = 2.0;
+    let cold = 0.5 * t.termination_temperature;
+
+    assert_eq!(next(&t, ConfinementMode::Normal, observed(mid, hot, 1.0)), None);
+    assert_eq!(next(&t, ConfinementMode::Normal, observed(high, hot, 0.0)), Some(ConfinementMode::RadiativeCollapse));
+    assert_eq!(
+        next(&t, ConfinementMode::TurbulencePulse, observed(high, hot, 0.0)),
+        Some(ConfinementMode::RadiativeCollapse)
+    );
+
+    assert_eq!(next(&t, ConfinementMode::RadiativeCollapse, observed(high, hot, 0.0)), None);
+    assert_eq!(
+        next(&t, ConfinementMode::RadiativeCollapse, observed(high, hot, t.response_time)),
+        Some(ConfinementMode::EmergencyFlush)
+    );
+    assert_eq!(
+        next(&t, ConfinementMode::RadiativeCollapse, observed(high, cold, 0.0)),
+        Some(ConfinementMode::Terminated)
+    );
+
+    assert_eq!(next(&t, ConfinementMode::EmergencyFlush, observed(mid, hot, 0.0)), None);
+    assert_eq!(next(&t, ConfinementMode::EmergencyFlush, observed(low, hot, 0.0)), Some(ConfinementMode::Normal));
+    assert_eq!(
+        next(&t, ConfinementMode::EmergencyFlush, observed(mid, hot, t.flush_duration)),
+        Some(ConfinementMode::Terminated)
+    );
+    assert_eq!(next(&t, ConfinementMode::EmergencyFlush, observed(mid, cold, 0.0)), Some(ConfinementMode::Terminated));
+
+    // Terminated is absorbing
+    assert_eq!(next(&t, ConfinementMode::Terminated, observed(low, hot, 10.0)), None);
+}
+
+#[test]
+fn radiation_cools_in_proportion_to_impurity_density() {
+    let t = enabled();
+    assert_eq!(t.cooling_rate(2.0, 0.0), 0.0);
+    let rate = t.cooling_rate(2.0, t.collapse_density);
+    assert!((rate + 2.0 / t.cooling_time).abs() < 1e-12);
+    assert!((t.cooling_rate(2.0, 2.0 * t.collapse_density) - 2.0 * rate).abs() < 1e-9);
+}
+
+#[test]
+fn collapse_goes_through_the_flush() {
+    let config = Config { termination: enabled(), ..Config::default() };
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.state.impurity_density.mapv_inplace(|n| n.max(2.0 * config.termination.collapse_density));
+
+    let modes = modes(&mut sim, 1.0);
+    assert_eq!(modes[..2], [ConfinementMode::RadiativeCollapse, ConfinementMode::EmergencyFlush]);
+    assert!(matches!(modes.last(), Some(ConfinementMode::Normal | ConfinementMode::Terminated)));
+}
+
+#[test]
+fn run_stops_at_termination() {
+    // Radiation far faster than any reheating
+    let termination = Termination { cooling_time: 1e-4, response_time: 1.0, ..enabled() };
+    let config = Config { termination, ..Config::default() };
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.state.impurity_density.mapv_inplace(|n| n.max(2.0 * config.termination.collapse_density));
+
+    let modes = modes(&mut sim, 1.0);
+    assert_eq!(modes, [ConfinementMode::RadiativeCollapse, ConfinementMode::Terminated]);
+    assert!(sim.state.terminated());
+    assert!(sim.state.time < 0.5);
+
+    // Frozen from here
+    let profile = sim.state.impurity_density.clone();
+    sim.state.update(1e-3);
+    assert_eq!(sim.state.impurity_density, profile);
+}
+
+#[test]
+fn disabled_leaves_high_impurity_runs_alone() {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.05;
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.state.impurity_density.mapv_inplace(|n| n.max(1e21));
+
+    assert!(modes(&mut sim, config.simulation.t_max).is_empty());
+    assert!(sim.state.time >= config.simulation.t_max);
+}
+
+/// Central T_e after radiating at k dt = 1.5 for one step of dt, or over
+/// `steps` steps of dt / `steps`.
+fn radiated(splitting: Splitting, steps: usize) -> f64 {
+    let mut config = Config::default();
+    let dt = config.simulation.dt;
+    config.termination = Termination { cooling_time: dt, ..enabled() };
+    config.numerics.splitting = splitting;
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.state.impurity_density.fill(1.5 * config.termination.collapse_density);
+    sim.state.confinement_mode = ConfinementMode::RadiativeCollapse;
+    sim.state.mode_since = sim.state.time;
+    for _ in 0..steps {
+        sim.state.update(dt / steps as f64);
+    }
+    assert_eq!(sim.state.confinement_mode, ConfinementMode::RadiativeCollapse);
+    sim.state.electron_temp[0]
+}
+
+#[test]
+fn strang_splitting_survives_stiff_radiation() {
+    let reference = radiated(Splitting::Lie, 100);
+    assert!(reference > 0.0);
+    let lie = (radiated(Splitting::Lie, 1) - reference).abs();
+    let strang = (radiated(Splitting::Strang, 1) - reference).abs();
+    // Explicit radiation overshoots past k dt = 1 and clamps; the exact
+    // half steps stay within a few per cent
+    assert!(strang < 0.05 * reference, "strang error {strang} keV of {reference} keV");
+    assert!(strang < 0.2 * lie, "strang error {strang} keV, lie error {lie} keV");
+}
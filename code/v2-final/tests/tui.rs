@@ -0,0 +1,50 @@
+//! The dashboard rendered into a test buffer
+//! (`cargo test --features tui --test tui`).
+
+#![cfg(feature = "tui")]
+
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::tui::{render, View, TRACE_POINTS};
+
+fn screen(view: &View, sim: &Simulation) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+    terminal.draw(|frame| render(frame, view, sim)).unwrap();
+    let buffer = terminal.backend().buffer();
+    buffer.content().chunks(buffer.area.width as usize).map(|row| {
+        row.iter().map(|cell| cell.symbol()).collect::<String>() + "\n"
+    }).collect()
+}
+
+/// A closed-loop run shows its pulses, keeps `WINDOW` of trace, and
+/// renders the mode and timers.
+#[test]
+fn dashboard_follows_a_closed_loop_run() {
+    let mut config = Config::default();
+    config.simulation.t_max = 3.0;
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.state.history.recording = false;
+    let mut view = View::new(config.simulation.t_max, config.output.critical_density);
+    assert!(screen(&view, &sim).contains("mode: Normal"));
+
+    while sim.state.time < config.simulation.t_max {
+        sim.step();
+        for event in sim.state.drain_events() {
+            view.note(&event);
+        }
+        view.observe(&sim);
+    }
+    assert!(view.pulses > 0);
+    assert!(!view.events.is_empty());
+    // 3 s of run fill the 2 s window
+    assert_eq!(view.center_impurity.len(), TRACE_POINTS);
+    assert_eq!(view.turbulence.len(), TRACE_POINTS);
+
+    let text = screen(&view, &sim);
+    assert!(text.contains(&format!("pulses: {}", view.pulses)), "{}", text);
+    assert!(text.contains("n_Z(0) ="), "{}", text);
+    assert!(text.contains(" cooldown ") && text.contains(" pulse "), "{}", text);
+}
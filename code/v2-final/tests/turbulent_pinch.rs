@@ -0,0 +1,87 @@
+//! Turbulent convection of the analytic turbulence models.
+
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::state::StellaratorState;
+use w7x_turbulence_control::turbulence::{
+    ItgModel, LocalProfiles, Turbulence, TurbulenceModel, TurbulentPinch, MINOR_RADIUS,
+};
+
+fn local(temperature_gradient: f64) -> LocalProfiles {
+    LocalProfiles {
+        radius: 0.5,
+        electron_density: 5e19,
+        density_gradient: -5e19,
+        electron_temp: 2.0,
+        temperature_gradient,
+        d_turb_base: 0.5,
+        pulse_level: 0.0,
+    }
+}
+
+fn itg_with(pinch: TurbulentPinch) -> Turbulence {
+    Turbulence::Itg(ItgModel { pinch, ..ItgModel::default() })
+}
+
+#[test]
+fn velocity_follows_the_parameterization() {
+    let pinch = TurbulentPinch { curvature: -0.4, thermodiffusion: 0.1 };
+    let profiles = local(-4.0);
+    // a/L_T = 2
+    let expected = 0.5 * (-0.4 + 0.1 * 2.0) / MINOR_RADIUS;
+    assert!((pinch.velocity(0.5, &profiles) - expected).abs() < 1e-12);
+    assert_eq!(TurbulentPinch::default().velocity(0.5, &profiles), 0.0);
+}
+
+#[test]
+fn scales_with_the_model_diffusivity() {
+    let pinch = TurbulentPinch { curvature: -1.0, thermodiffusion: 0.0 };
+    let model = itg_with(pinch);
+    for gradient in [-0.5, -10.0] {
+        let profiles = local(gradient);
+        let d = model.factor(&profiles) * profiles.d_turb_base;
+        assert!((model.pinch(&profiles) - pinch.velocity(d, &profiles)).abs() < 1e-12);
+    }
+    let sum = Turbulence::Sum { channels: vec![model.clone(), model.clone()] };
+    let profiles = local(-4.0);
+    assert!((sum.pinch(&profiles) - 2.0 * model.pinch(&profiles)).abs() < 1e-12);
+}
+
+#[test]
+fn default_models_stay_diffusive() {
+    let profiles = local(-4.0);
+    assert_eq!(Turbulence::default().pinch(&profiles), 0.0);
+    let state = StellaratorState::from_config(&Config::default());
+    let mid = state.nr / 2;
+    assert_eq!(state.turbulence.pinch(&state.local_profiles(mid)), 0.0);
+}
+
+#[test]
+fn inward_pinch_peaks_the_impurities() {
+    let center = |pinch: TurbulentPinch| {
+        let config = Config { turbulence: itg_with(pinch), ..Config::default() };
+        let mut sim = Simulation::from_config(&config);
+        sim.state.verbose = false;
+        sim.run(0.1, |_| {}).unwrap();
+        sim.state.impurity_density[0]
+    };
+    let diffusive = center(TurbulentPinch::default());
+    let inward = center(TurbulentPinch { curvature: -1.0, thermodiffusion: 0.0 });
+    assert!(inward > diffusive);
+}
+
+#[test]
+fn parses_per_model() {
+    let text = r#"
+        [turbulence]
+        type = "tem"
+        pinch = { curvature = -0.3, thermodiffusion = 0.05 }
+    "#;
+    let config: Config = toml::from_str(text).unwrap();
+    match config.turbulence {
+        Turbulence::Tem(model) => {
+            assert_eq!(model.pinch, TurbulentPinch { curvature: -0.3, thermodiffusion: 0.05 });
+        }
+        other => panic!("expected tem, got {:?}", other),
+    }
+}
@@ -0,0 +1,101 @@
+//! The browser viewer's HTTP and WebSocket endpoints
+//! (`cargo test --features websocket --test web_dashboard`).
+
+#![cfg(feature = "websocket")]
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+use tungstenite::Message;
+use w7x_turbulence_control::config::Config;
+use w7x_turbulence_control::simulation::Simulation;
+use w7x_turbulence_control::web_dashboard::{WebDashboard, FRAME};
+
+fn simulation() -> Simulation {
+    let mut config = Config::default();
+    config.simulation.t_max = 0.1;
+    let mut sim = Simulation::from_config(&config);
+    sim.state.verbose = false;
+    sim.state.history.recording = false;
+    sim
+}
+
+fn json(message: Message) -> serde_json::Value {
+    serde_json::from_str(message.to_text().unwrap()).unwrap()
+}
+
+/// A viewer gets `start` on connect, frames while the run steps, and
+/// `end` when it finishes.
+#[test]
+fn viewer_receives_start_frames_and_end() {
+    let mut sim = simulation();
+    let mut web = WebDashboard::bind("127.0.0.1:0", &sim, 0.1, 1e17).unwrap();
+    let (mut socket, _) = tungstenite::connect(format!("ws://{}/ws", web.address())).unwrap();
+
+    let start = json(socket.read().unwrap());
+    assert_eq!(start["type"], "start");
+    assert_eq!(start["radius"].as_array().unwrap().len(), sim.state.radius_grid.len());
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while web.viewers() == 0 {
+        assert!(Instant::now() < deadline, "viewer was not registered");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    for _ in 0..10 {
+        sim.step();
+        web.refresh(&sim);
+    }
+    std::thread::sleep(FRAME);
+    sim.step();
+    web.refresh(&sim);
+    let frame = json(socket.read().unwrap());
+    assert_eq!(frame["type"], "frame");
+    assert_eq!(frame["mode"], "normal");
+    assert!(!frame["trace"].as_array().unwrap().is_empty());
+    assert_eq!(frame["trace"][0].as_array().unwrap().len(), 5);
+    assert_eq!(frame["profile"].as_array().unwrap().len(), sim.state.radius_grid.len());
+
+    web.finish(&sim);
+    let end = json(socket.read().unwrap());
+    assert_eq!(end["type"], "end");
+}
+
+/// `GET /` serves the bundled viewer; other paths are 404.
+#[test]
+fn viewer_page_is_served() {
+    let sim = simulation();
+    let web = WebDashboard::bind("127.0.0.1:0", &sim, 0.1, 1e17).unwrap();
+    let get = |path: &str| {
+        let mut stream = TcpStream::connect(web.address()).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+
+    let page = get("/");
+    assert!(page.starts_with("HTTP/1.1 200 OK"));
+    assert!(page.contains("new WebSocket"));
+    assert!(get("/missing").starts_with("HTTP/1.1 404"));
+}
+
+/// A request line split across TCP segments is routed by its whole path,
+/// also when the split falls inside the path.
+#[test]
+fn split_request_line_is_served() {
+    let sim = simulation();
+    let web = WebDashboard::bind("127.0.0.1:0", &sim, 0.1, 1e17).unwrap();
+    let get = |first: &[u8], rest: &[u8]| {
+        let mut stream = TcpStream::connect(web.address()).unwrap();
+        stream.set_nodelay(true).unwrap();
+        stream.write_all(first).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        stream.write_all(rest).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+
+    assert!(get(b"GET /", b" HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 200 OK"));
+    assert!(get(b"GET /index", b".html HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 200 OK"));
+}
@@ -0,0 +1,281 @@
+//! # Live Dashboard
+//!
+//! `--tui` (`tui` feature) replaces the status lines of a run with a
+//! full-screen view redrawn every `FRAME` of wall-clock time:
+//!
+//! - n_Z(0) and the edge D_turb over the last `WINDOW` seconds of plasma
+//!   time, with the latest diagnostic readings the detector saw;
+//! - the confinement mode with pulse and cooldown timers;
+//! - the n_Z(r) profile from axis to edge;
+//! - the most recent events (pulses, alarms, warnings).
+//!
+//! `q` or Esc ends the run early (outputs are still written), space
+//! pauses. Log lines are not printed while the dashboard is up.
+
+use crate::events::{Event, TimedEvent};
+use crate::simulation::Simulation;
+use crate::state::ConfinementMode;
+use ratatui::crossterm::event::{self, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Gauge, List, Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Wall-clock time between redraws.
+pub const FRAME: Duration = Duration::from_millis(50);
+/// Plasma time shown by the traces (s).
+pub const WINDOW: f64 = 2.0;
+/// Points per trace, spread over `WINDOW`.
+pub const TRACE_POINTS: usize = 400;
+const EVENT_LINES: usize = 8;
+/// Resolution of the sparkline bars.
+const LEVELS: f64 = 1000.0;
+
+/// What the dashboard shows besides the current state.
+pub struct View {
+    pub center_impurity: VecDeque<f64>, // m⁻³, every WINDOW / TRACE_POINTS
+    pub turbulence: VecDeque<f64>,      // m²/s, edge D_turb
+    pub events: VecDeque<String>,       // Newest last
+    pub pulses: usize,
+    pub t_max: f64,                     // s
+    pub critical_density: f64,          // m⁻³
+    pub paused: bool,
+    next_time: f64,
+}
+
+impl View {
+    pub fn new(t_max: f64, critical_density: f64) -> Self {
+        View {
+            center_impurity: VecDeque::with_capacity(TRACE_POINTS),
+            turbulence: VecDeque::with_capacity(TRACE_POINTS),
+            events: VecDeque::with_capacity(EVENT_LINES),
+            pulses: 0,
+            t_max,
+            critical_density,
+            paused: false,
+            next_time: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Adds a trace point when due.
+    pub fn observe(&mut self, sim: &Simulation) {
+        let state = &sim.state;
+        if state.time < self.next_time {
+            return;
+        }
+        self.next_time = state.time + WINDOW / TRACE_POINTS as f64;
+        if self.center_impurity.len() == TRACE_POINTS {
+            self.center_impurity.pop_front();
+            self.turbulence.pop_front();
+        }
+        let sample = state.last_sample();
+        self.center_impurity.push_back(sample.center_impurity);
+        self.turbulence.push_back(sample.turbulence);
+    }
+
+    pub fn note(&mut self, event: &TimedEvent) {
+        if matches!(event.event, Event::PulseStarted { .. }) {
+            self.pulses += 1;
+        }
+        if self.events.len() == EVENT_LINES {
+            self.events.pop_front();
+        }
+        self.events.push_back(event.message());
+    }
+}
+
+/// Sparkline bars for `values` scaled to their maximum, the newest `width`.
+fn bars(values: impl ExactSizeIterator<Item = f64> + Clone, width: usize) -> Vec<u64> {
+    let skip = values.len().saturating_sub(width);
+    let peak = values.clone().fold(0.0_f64, f64::max);
+    values
+        .skip(skip)
+        .map(|v| if peak > 0.0 { (v / peak * LEVELS).round() as u64 } else { 0 })
+        .collect()
+}
+
+/// Draws `view` and the current state of `sim`.
+pub fn render(frame: &mut Frame, view: &View, sim: &Simulation) {
+    let state = &sim.state;
+    let [header, impurity, turbulence, middle, events, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(6),
+        Constraint::Min(6),
+        Constraint::Length(8),
+        Constraint::Length(EVENT_LINES as u16 + 2),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let mode = match state.confinement_mode {
+        ConfinementMode::Normal => ("Normal", Color::Green),
+        ConfinementMode::TurbulencePulse => ("Turbulence pulse", Color::Yellow),
+        ConfinementMode::RadiativeCollapse => ("Radiative collapse", Color::Red),
+        ConfinementMode::EmergencyFlush => ("Emergency flush", Color::Magenta),
+        ConfinementMode::Terminated => ("Terminated", Color::Red),
+    };
+    let status = Line::from(format!(
+        "t = {:.3} / {:.1} s   mode: {}   pulses: {}{}",
+        state.time,
+        view.t_max,
+        mode.0,
+        view.pulses,
+        if view.paused { "   ⏸ paused" } else { "" }
+    ));
+    frame.render_widget(
+        Paragraph::new(status).style(Style::default().fg(mode.1)).block(Block::bordered().title(" W7-X ")),
+        header,
+    );
+
+    let measurement = sim.last_measurement();
+    let n0 = view.center_impurity.back().copied().unwrap_or(state.impurity_density[0]);
+    let over = n0 > view.critical_density;
+    let impurity_title = format!(
+        " n_Z(0) = {:.2e} m⁻³ (SXR {}, critical {:.1e}) over {:.0} s ",
+        n0,
+        measurement.map_or("-".to_string(), |m| format!("{:.2e}", m.central_sxr)),
+        view.critical_density,
+        WINDOW
+    );
+    let width = impurity.width.saturating_sub(2) as usize;
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::bordered().title(impurity_title))
+            .data(bars(view.center_impurity.iter().copied(), width))
+            .max(LEVELS as u64)
+            .style(Style::default().fg(if over { Color::Red } else { Color::Cyan })),
+        impurity,
+    );
+    let d_edge = view.turbulence.back().copied().unwrap_or(0.0);
+    let turbulence_title = format!(
+        " edge D_turb = {:.2} m²/s (measured {}) ",
+        d_edge,
+        measurement.map_or("-".to_string(), |m| format!("{:.2}", m.turbulence))
+    );
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::bordered().title(turbulence_title))
+            .data(bars(view.turbulence.iter().copied(), width))
+            .max(LEVELS as u64)
+            .style(Style::default().fg(Color::Magenta)),
+        turbulence,
+    );
+
+    let [timers, profile] = Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(middle);
+    let [pulse, cooldown] = Layout::vertical([Constraint::Length(4), Constraint::Length(4)]).areas(timers);
+    let (pulse_ratio, pulse_label) = match state.pulse_start_time {
+        Some(start) if state.pulse_duration > 0.0 => {
+            let elapsed = state.time - start;
+            ((elapsed / state.pulse_duration).clamp(0.0, 1.0), format!("{:.3} / {:.3} s", elapsed, state.pulse_duration))
+        }
+        _ => (0.0, "off".to_string()),
+    };
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::bordered().title(" pulse "))
+            .gauge_style(Style::default().fg(Color::Yellow))
+            .ratio(pulse_ratio)
+            .label(pulse_label),
+        pulse,
+    );
+    let remaining = match (state.confinement_mode, state.last_pulse_end_time) {
+        (ConfinementMode::Normal, Some(end)) => (state.cooldown_duration - (state.time - end)).max(0.0),
+        _ => 0.0,
+    };
+    let (cooldown_ratio, cooldown_label) = if remaining > 0.0 {
+        ((remaining / state.cooldown_duration).min(1.0), format!("{:.3} s left", remaining))
+    } else {
+        (0.0, "ready".to_string())
+    };
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::bordered().title(" cooldown "))
+            .gauge_style(Style::default().fg(Color::Blue))
+            .ratio(cooldown_ratio)
+            .label(cooldown_label),
+        cooldown,
+    );
+
+    // n_Z(r) resampled to the panel width, axis on the left
+    let columns = profile.width.saturating_sub(2).max(2) as usize;
+    let n = &state.impurity_density;
+    let last = n.len() - 1;
+    let resampled = (0..columns).map(|c| n[(c * last + (columns - 1) / 2) / (columns - 1)]);
+    let peak = n.iter().fold(0.0_f64, |m, &v| m.max(v));
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::bordered().title(format!(" n_Z(r), axis → edge, peak {:.2e} ", peak)))
+            .data(bars(resampled.collect::<Vec<_>>().into_iter(), columns))
+            .max(LEVELS as u64)
+            .style(Style::default().fg(Color::Cyan)),
+        profile,
+    );
+
+    frame.render_widget(
+        List::new(view.events.iter().map(String::as_str)).block(Block::bordered().title(" events ")),
+        events,
+    );
+    frame.render_widget(Line::from(" q / Esc: end run   space: pause"), footer);
+}
+
+/// The terminal while the dashboard is up.
+pub struct Dashboard {
+    terminal: DefaultTerminal,
+    pub view: View,
+    last_draw: Instant,
+}
+
+impl Dashboard {
+    /// Switches the terminal to the full-screen view.
+    pub fn start(t_max: f64, critical_density: f64) -> std::io::Result<Self> {
+        Ok(Dashboard {
+            terminal: ratatui::try_init()?,
+            view: View::new(t_max, critical_density),
+            last_draw: Instant::now(),
+        })
+    }
+
+    pub fn note(&mut self, event: &TimedEvent) {
+        self.view.note(event);
+    }
+
+    /// Records the step and redraws when a frame is due; `false` once
+    /// the user asked to end the run. Blocks while paused.
+    pub fn refresh(&mut self, sim: &Simulation) -> std::io::Result<bool> {
+        self.view.observe(sim);
+        if self.last_draw.elapsed() < FRAME {
+            return Ok(true);
+        }
+        loop {
+            self.terminal.draw(|frame| render(frame, &self.view, sim))?;
+            self.last_draw = Instant::now();
+            let wait = if self.view.paused { FRAME } else { Duration::ZERO };
+            while event::poll(wait)? {
+                if let event::Event::Key(key) = event::read()? {
+                    if key.kind != KeyEventKind::Press {
+                        continue;
+                    }
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                        KeyCode::Char(' ') => self.view.paused = !self.view.paused,
+                        _ => {}
+                    }
+                }
+                if !self.view.paused {
+                    break;
+                }
+            }
+            if !self.view.paused {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Restores the terminal.
+    pub fn finish(self) {
+        ratatui::restore();
+    }
+}
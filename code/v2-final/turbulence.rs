@@ -0,0 +1,643 @@
+//! # Turbulence Models
+//!
+//! A `TurbulenceModel` maps the local n_e and T_e profiles to the
+//! Normal-mode turbulent diffusivity as a multiple of `d_turb_base`. The
+//! state adds pulse enhancement and the fixed low-turbulence region near
+//! the axis and edge on top, so models only describe the physics drive.
+//! Models may also contribute a turbulent convection velocity, which is
+//! added to the neoclassical pinch: the table and surrogate models take it
+//! from their data, the analytic models from a `TurbulentPinch`
+//! parameterization of their own (none by default).
+//!
+//! The `table` model interpolates D and V from precomputed gyrokinetic
+//! scans (e.g. GENE) in local a/L_n, a/L_T and ν*_e, read at startup.
+//! The `surrogate` model evaluates a neural network that also sees the
+//! pulse state and so replaces the state's own pulse blending. The
+//! `plugin` model calls a shared library (feature `plugins`, see `plugin`).
+
+#[cfg(feature = "plugins")]
+use crate::plugin::TurbulencePlugin;
+use crate::surrogate::Mlp;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::io;
+#[cfg(feature = "fs")]
+use std::path::Path;
+#[cfg(feature = "plugins")]
+use std::sync::Arc;
+
+/// Geometry entering the collisionality (W7-X standard configuration).
+pub const MAJOR_RADIUS: f64 = 5.5; // m
+pub const MINOR_RADIUS: f64 = 0.53; // m
+const SAFETY_FACTOR: f64 = 1.0; // 1/ι
+const COULOMB_LOGARITHM: f64 = 15.0;
+
+/// n_e and T_e and their radial derivatives at one grid point.
+#[derive(Clone, Copy, Debug)]
+pub struct LocalProfiles {
+    pub radius: f64,
+    pub electron_density: f64,     // m⁻³
+    pub density_gradient: f64,     // m⁻³ per unit normalized radius
+    pub electron_temp: f64,        // keV
+    pub temperature_gradient: f64, // keV per unit normalized radius
+    pub d_turb_base: f64,          // m²/s, unit of `TurbulenceModel::factor`
+    pub pulse_level: f64,          // Actuator output inside the pulse region, 0 elsewhere
+}
+
+impl LocalProfiles {
+    /// η = L_n / L_T, clamped to [0.1, 10].
+    pub fn eta(&self) -> f64 {
+        let ln = (self.electron_density / self.density_gradient.abs().max(1e-10)).abs();
+        let lt = (self.electron_temp / self.temperature_gradient.abs().max(1e-10)).abs();
+        (ln / lt).clamp(0.1, 10.0)
+    }
+
+    /// a / L_n with a = 1 in normalized radius.
+    pub fn inverse_density_length(&self) -> f64 {
+        self.density_gradient.abs() / self.electron_density.max(1e-10)
+    }
+
+    /// a / L_T with a = 1 in normalized radius.
+    pub fn inverse_temperature_length(&self) -> f64 {
+        self.temperature_gradient.abs() / self.electron_temp.max(1e-10)
+    }
+
+    /// R / L_T, the drive of stiff ITG transport.
+    pub fn major_radius_temperature_gradient(&self) -> f64 {
+        MAJOR_RADIUS / MINOR_RADIUS * self.temperature_gradient.abs() / self.electron_temp.max(1e-10)
+    }
+
+    /// Electron collisionality ν*_e = ν_ei q R / (ε^{3/2} v_te).
+    pub fn collisionality(&self) -> f64 {
+        let epsilon = (self.radius * MINOR_RADIUS / MAJOR_RADIUS).max(1e-3);
+        let temp_ev = (self.electron_temp * 1000.0).max(1.0);
+        6.921e-18 * SAFETY_FACTOR * MAJOR_RADIUS * self.electron_density * COULOMB_LOGARITHM
+            / (temp_ev * temp_ev * epsilon.powf(1.5))
+    }
+}
+
+/// Turbulent convection of the analytic models, proportional to their
+/// Normal-mode D_turb:
+///
+/// ```text
+/// V_turb = D_turb · (curvature + thermodiffusion · a/L_T) / a
+/// ```
+///
+/// `curvature` < 0 is the inward curvature pinch (of order −2a/R);
+/// `thermodiffusion` > 0 the outward thermodiffusive term (∝ 1/Z for ITG),
+/// so a steeper T_e partially offsets the pinch. Both 0 (the default) keep
+/// the model purely diffusive.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TurbulentPinch {
+    pub curvature: f64,       // V a / D at a/L_T = 0
+    pub thermodiffusion: f64, // V a / D per unit a/L_T
+}
+
+impl TurbulentPinch {
+    /// V_turb (m/s, negative = inward) at diffusivity `d_turb` (m²/s).
+    pub fn velocity(&self, d_turb: f64, local: &LocalProfiles) -> f64 {
+        let peaking = self.curvature + self.thermodiffusion * local.inverse_temperature_length();
+        d_turb * peaking / MINOR_RADIUS
+    }
+}
+
+pub trait TurbulenceModel {
+    /// Normal-mode D_turb in units of `d_turb_base`.
+    fn factor(&self, local: &LocalProfiles) -> f64;
+
+    /// Turbulent convection velocity (m/s, negative = inward), added to
+    /// the neoclassical pinch. Most models are purely diffusive.
+    fn pinch(&self, _local: &LocalProfiles) -> f64 {
+        0.0
+    }
+
+    /// True if `factor` already contains the pulse response (from
+    /// `pulse_level`), so the state must not blend in `pulse_amplitude`.
+    fn resolves_pulse(&self) -> bool {
+        false
+    }
+}
+
+/// ITG drive switched by η: stable (reduced transport) inside
+/// `min_eta < η < critical_eta`. The default 0.8–1.2 band is the v2 model;
+/// `min_eta = 0` also stabilizes η ≪ 1, e.g. after a pellet steepens ∇n_e.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItgModel {
+    pub min_eta: f64,
+    pub critical_eta: f64,
+    pub stable_factor: f64, // Factor for min_eta < η < critical_eta; 1 outside
+    pub pinch: TurbulentPinch,
+}
+
+impl Default for ItgModel {
+    fn default() -> Self {
+        ItgModel {
+            min_eta: 0.8,
+            critical_eta: 1.2,
+            stable_factor: 0.3,
+            pinch: TurbulentPinch::default(),
+        }
+    }
+}
+
+impl TurbulenceModel for ItgModel {
+    fn factor(&self, local: &LocalProfiles) -> f64 {
+        let eta = local.eta();
+        if eta > self.min_eta && eta < self.critical_eta {
+            self.stable_factor
+        } else {
+            1.0
+        }
+    }
+
+    fn pinch(&self, local: &LocalProfiles) -> f64 {
+        self.pinch.velocity(self.factor(local) * local.d_turb_base, local)
+    }
+}
+
+/// Profile-independent D_turb.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConstantModel {
+    pub factor: f64,
+    pub pinch: TurbulentPinch,
+}
+
+impl Default for ConstantModel {
+    fn default() -> Self {
+        ConstantModel { factor: 0.3, pinch: TurbulentPinch::default() }
+    }
+}
+
+impl TurbulenceModel for ConstantModel {
+    fn factor(&self, _local: &LocalProfiles) -> f64 {
+        self.factor
+    }
+
+    fn pinch(&self, local: &LocalProfiles) -> f64 {
+        self.pinch.velocity(self.factor * local.d_turb_base, local)
+    }
+}
+
+/// Trapped electron modes: driven by a/L_n above a threshold, damped by
+/// collisions detrapping the electrons. Peaked (e.g. pellet-fuelled)
+/// density profiles that stabilize ITG can drive TEM instead.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TemModel {
+    pub threshold: f64,             // Critical a/L_n
+    pub stiffness: f64,             // Factor per unit a/L_n above threshold
+    pub collisionality_scale: f64,  // ν*_e at which the drive is halved
+    pub pinch: TurbulentPinch,
+}
+
+impl Default for TemModel {
+    fn default() -> Self {
+        TemModel {
+            threshold: 3.0,
+            stiffness: 0.2,
+            collisionality_scale: 0.1,
+            pinch: TurbulentPinch::default(),
+        }
+    }
+}
+
+impl TurbulenceModel for TemModel {
+    fn factor(&self, local: &LocalProfiles) -> f64 {
+        let drive = (local.inverse_density_length() - self.threshold).max(0.0);
+        let damping = 1.0 + local.collisionality() / self.collisionality_scale.max(1e-12);
+        self.stiffness * drive / damping
+    }
+
+    fn pinch(&self, local: &LocalProfiles) -> f64 {
+        self.pinch.velocity(self.factor(local) * local.d_turb_base, local)
+    }
+}
+
+/// Stiff (critical-gradient) transport as in reduced TGLF-style models:
+/// the background level below R/L_T,crit, then a steep rise above it.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CriticalGradientModel {
+    pub critical: f64,    // R/L_T,crit
+    pub base_factor: f64, // Below critical
+    pub stiffness: f64,   // Factor per (R/L_T − critical)^exponent
+    pub exponent: f64,    // 1 = linear stiffness, larger = sharper onset
+    pub max_factor: f64,  // Cap, keeps the explicit step stable
+    pub pinch: TurbulentPinch,
+}
+
+impl Default for CriticalGradientModel {
+    fn default() -> Self {
+        CriticalGradientModel {
+            critical: 20.0,
+            base_factor: 0.3,
+            stiffness: 0.1,
+            exponent: 1.5,
+            max_factor: 5.0,
+            pinch: TurbulentPinch::default(),
+        }
+    }
+}
+
+impl TurbulenceModel for CriticalGradientModel {
+    fn factor(&self, local: &LocalProfiles) -> f64 {
+        let excess = (local.major_radius_temperature_gradient() - self.critical).max(0.0);
+        (self.base_factor + self.stiffness * excess.powf(self.exponent)).min(self.max_factor)
+    }
+
+    fn pinch(&self, local: &LocalProfiles) -> f64 {
+        self.pinch.velocity(self.factor(local) * local.d_turb_base, local)
+    }
+}
+
+/// D and V on a regular (a/L_n, a/L_T, ν*_e) grid, axes ascending.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct GyrokineticTable {
+    pub a_ln: Vec<f64>,
+    pub a_lt: Vec<f64>,
+    pub collisionality: Vec<f64>,
+    pub diffusivity: Vec<f64>, // m²/s, index (i_ln · n_lt + i_lt) · n_nu + i_nu
+    pub velocity: Vec<f64>,    // m/s
+}
+
+impl GyrokineticTable {
+    fn index(&self, i_ln: usize, i_lt: usize, i_nu: usize) -> usize {
+        (i_ln * self.a_lt.len() + i_lt) * self.collisionality.len() + i_nu
+    }
+
+    /// Trilinear interpolation of `values`, clamped to the scanned range.
+    /// ν*_e is interpolated logarithmically when the axis is positive.
+    fn interpolate(&self, values: &[f64], local: &LocalProfiles) -> f64 {
+        let log_nu = self.collisionality.first().is_some_and(|&nu| nu > 0.0);
+        let nu_axis: Vec<f64> = if log_nu {
+            self.collisionality.iter().map(|nu| nu.ln()).collect()
+        } else {
+            self.collisionality.clone()
+        };
+        let nu = if log_nu {
+            local.collisionality().max(1e-30).ln()
+        } else {
+            local.collisionality()
+        };
+        let (l0, l1, wl) = bracket(&self.a_ln, local.inverse_density_length());
+        let (t0, t1, wt) = bracket(&self.a_lt, local.inverse_temperature_length());
+        let (n0, n1, wn) = bracket(&nu_axis, nu);
+
+        let mut result = 0.0;
+        for (il, fl) in [(l0, 1.0 - wl), (l1, wl)] {
+            for (it, ft) in [(t0, 1.0 - wt), (t1, wt)] {
+                for (inu, fn_) in [(n0, 1.0 - wn), (n1, wn)] {
+                    let weight = fl * ft * fn_;
+                    if weight != 0.0 {
+                        result += weight * values[self.index(il, it, inu)];
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    #[cfg(feature = "fs")]
+    fn read<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let text = std::fs::read_to_string(path)?;
+        let mut rows = Vec::new();
+        for (line_no, line) in text.lines().enumerate().skip(1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<f64> = line
+                .split(',')
+                .map(|f| f.trim().parse::<f64>())
+                .collect::<Result<_, _>>()
+                .map_err(|e| invalid(format!("line {}: {}", line_no + 1, e)))?;
+            let [a_ln, a_lt, nu, d, v] = fields[..] else {
+                return Err(invalid(format!("line {}: expected 5 columns", line_no + 1)));
+            };
+            rows.push([a_ln, a_lt, nu, d, v]);
+        }
+
+        let axis = |k: usize| {
+            let mut values: Vec<f64> = rows.iter().map(|row| row[k]).collect();
+            values.sort_by(f64::total_cmp);
+            values.dedup();
+            values
+        };
+        let mut table = GyrokineticTable {
+            a_ln: axis(0),
+            a_lt: axis(1),
+            collisionality: axis(2),
+            diffusivity: Vec::new(),
+            velocity: Vec::new(),
+        };
+        let cells = table.a_ln.len() * table.a_lt.len() * table.collisionality.len();
+        if cells == 0 || rows.len() != cells {
+            return Err(invalid(format!(
+                "{} rows, a full {}×{}×{} grid needs {}",
+                rows.len(), table.a_ln.len(), table.a_lt.len(), table.collisionality.len(), cells
+            )));
+        }
+        table.diffusivity = vec![f64::NAN; cells];
+        table.velocity = vec![f64::NAN; cells];
+        for row in &rows {
+            let position = |axis: &[f64], x: f64| axis.partition_point(|&a| a < x);
+            let k = table.index(
+                position(&table.a_ln, row[0]),
+                position(&table.a_lt, row[1]),
+                position(&table.collisionality, row[2]),
+            );
+            if !table.diffusivity[k].is_nan() {
+                return Err(invalid(format!(
+                    "repeated point a/L_n = {}, a/L_T = {}, ν* = {}",
+                    row[0], row[1], row[2]
+                )));
+            }
+            table.diffusivity[k] = row[3];
+            table.velocity[k] = row[4];
+        }
+        Ok(table)
+    }
+}
+
+/// Neighbouring indices and weight of the upper one; constant beyond the ends.
+fn bracket(axis: &[f64], x: f64) -> (usize, usize, f64) {
+    let n = axis.len();
+    if n == 1 || x <= axis[0] {
+        return (0, 0, 0.0);
+    }
+    if x >= axis[n - 1] {
+        return (n - 1, n - 1, 0.0);
+    }
+    let j = axis.partition_point(|&a| a <= x);
+    (j - 1, j, (x - axis[j - 1]) / (axis[j] - axis[j - 1]))
+}
+
+/// Gyrokinetic lookup table. `path` is a CSV with header
+/// `a_ln,a_lt,collisionality,d,v` (D in m²/s, V in m/s) covering a full
+/// grid in any row order; it is read by `Turbulence::load`.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct TableModel {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table: Option<GyrokineticTable>,
+}
+
+impl TurbulenceModel for TableModel {
+    /// Nominal (1) until the table is loaded.
+    fn factor(&self, local: &LocalProfiles) -> f64 {
+        match &self.table {
+            Some(table) => table.interpolate(&table.diffusivity, local) / local.d_turb_base.max(1e-12),
+            None => 1.0,
+        }
+    }
+
+    fn pinch(&self, local: &LocalProfiles) -> f64 {
+        match &self.table {
+            Some(table) => table.interpolate(&table.velocity, local),
+            None => 0.0,
+        }
+    }
+}
+
+/// MLP surrogate mapping (a/L_n, a/L_T, ln ν*_e, pulse level) to D and V;
+/// see `surrogate` for the weight file. Read by `Turbulence::load`.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct SurrogateModel {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<Mlp>,
+}
+
+impl SurrogateModel {
+    fn evaluate(&self, network: &Mlp, local: &LocalProfiles) -> [f64; 2] {
+        network.evaluate([
+            local.inverse_density_length(),
+            local.inverse_temperature_length(),
+            local.collisionality().max(1e-30).ln(),
+            local.pulse_level,
+        ])
+    }
+}
+
+impl TurbulenceModel for SurrogateModel {
+    /// Nominal (1) until the network is loaded.
+    fn factor(&self, local: &LocalProfiles) -> f64 {
+        match &self.network {
+            Some(network) => self.evaluate(network, local)[0].max(0.0) / local.d_turb_base.max(1e-12),
+            None => 1.0,
+        }
+    }
+
+    fn pinch(&self, local: &LocalProfiles) -> f64 {
+        match &self.network {
+            Some(network) => self.evaluate(network, local)[1],
+            None => 0.0,
+        }
+    }
+
+    fn resolves_pulse(&self) -> bool {
+        self.network.is_some()
+    }
+}
+
+/// D and V from a shared library implementing the turbulence ABI of
+/// `plugin`; `parameters` is passed to its constructor as is. Opened by
+/// `Turbulence::load` (again on resume: the library is not checkpointed).
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct PluginModel {
+    pub path: String,
+    #[serde(default)]
+    pub parameters: String,
+    #[serde(default)]
+    pub resolves_pulse: bool, // The library's D includes the pulse response
+    #[cfg(feature = "plugins")]
+    #[serde(skip)]
+    pub plugin: Option<Arc<TurbulencePlugin>>,
+}
+
+impl PluginModel {
+    #[cfg(feature = "plugins")]
+    fn evaluate(&self, local: &LocalProfiles) -> Option<(f64, f64)> {
+        self.plugin.as_ref()?.evaluate(local)
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    fn evaluate(&self, _local: &LocalProfiles) -> Option<(f64, f64)> {
+        None
+    }
+
+    #[cfg(feature = "plugins")]
+    fn load(&mut self) -> io::Result<()> {
+        let plugin = TurbulencePlugin::load(&self.path, &self.parameters)
+            .map_err(|e| io::Error::new(e.kind(), format!("turbulence plugin {}", e)))?;
+        self.plugin = Some(Arc::new(plugin));
+        Ok(())
+    }
+
+    #[cfg(all(feature = "fs", not(feature = "plugins")))]
+    fn load(&mut self) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("turbulence plugin {}: rebuild with `--features plugins`", self.path),
+        ))
+    }
+}
+
+impl TurbulenceModel for PluginModel {
+    /// Nominal (1) until the library is loaded, and where it fails.
+    fn factor(&self, local: &LocalProfiles) -> f64 {
+        match self.evaluate(local) {
+            Some((diffusivity, _)) => diffusivity / local.d_turb_base.max(1e-12),
+            None => 1.0,
+        }
+    }
+
+    fn pinch(&self, local: &LocalProfiles) -> f64 {
+        self.evaluate(local).map_or(0.0, |(_, pinch)| pinch)
+    }
+
+    fn resolves_pulse(&self) -> bool {
+        self.resolves_pulse
+    }
+}
+
+/// Finite response of D_turb: instead of following the model instantly,
+/// the field relaxes toward it over the correlation time and spreads
+/// radially,
+///
+/// ```text
+/// ∂D/∂t = (D_model − D) / τ + spreading · ∂²D/∂r²
+/// ```
+///
+/// Both zero (the default) is the instantaneous v2 behaviour.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TurbulenceDynamics {
+    pub correlation_time: f64, // τ, s
+    pub spreading: f64,        // Normalized radius² / s
+}
+
+impl TurbulenceDynamics {
+    pub fn enabled(&self) -> bool {
+        self.correlation_time > 0.0 || self.spreading > 0.0
+    }
+
+    /// Advances `field` toward `target` by `dt`; the ends are pinned to the target.
+    pub fn step(&self, field: &mut [f64], target: &[f64], dr: f64, dt: f64) {
+        let n = field.len();
+        let relax = if self.correlation_time > 0.0 {
+            1.0 - (-dt / self.correlation_time).exp()
+        } else {
+            1.0
+        };
+        let previous = field.to_vec();
+        for i in 1..n - 1 {
+            let spreading = self.spreading * (previous[i + 1] - 2.0 * previous[i] + previous[i - 1]) / (dr * dr);
+            let relaxed = previous[i] + (target[i] - previous[i]) * relax;
+            field[i] = (relaxed + spreading * dt).max(0.0);
+        }
+        field[0] = target[0];
+        field[n - 1] = target[n - 1];
+    }
+}
+
+/// Model selection for the config file and checkpoints.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Turbulence {
+    Itg(ItgModel),
+    Tem(TemModel),
+    CriticalGradient(CriticalGradientModel),
+    Constant(ConstantModel),
+    Table(TableModel),
+    Surrogate(SurrogateModel),
+    Plugin(PluginModel),
+    /// Independent channels whose diffusivities add, e.g. ITG + TEM.
+    Sum { channels: Vec<Turbulence> },
+}
+
+impl Default for Turbulence {
+    fn default() -> Self {
+        Turbulence::Itg(ItgModel::default())
+    }
+}
+
+impl Turbulence {
+    /// Reads the lookup tables of `table` and `surrogate` models and opens
+    /// the libraries of `plugin` models (including sum channels).
+    #[cfg(feature = "fs")]
+    pub fn load(&mut self) -> io::Result<()> {
+        match self {
+            Turbulence::Table(model) => {
+                let table = GyrokineticTable::read(&model.path).map_err(|e| {
+                    io::Error::new(e.kind(), format!("turbulence table {}: {}", model.path, e))
+                })?;
+                model.table = Some(table);
+                Ok(())
+            }
+            Turbulence::Surrogate(model) => {
+                let network = Mlp::read(&model.path).map_err(|e| {
+                    io::Error::new(e.kind(), format!("turbulence surrogate {}: {}", model.path, e))
+                })?;
+                model.network = Some(network);
+                Ok(())
+            }
+            Turbulence::Plugin(model) => model.load(),
+            Turbulence::Sum { channels } => channels.iter_mut().try_for_each(Turbulence::load),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reopens the libraries of `plugin` models, which checkpoints do not
+    /// keep; tables and networks are restored with the state.
+    #[cfg(feature = "fs")]
+    pub fn load_plugins(&mut self) -> io::Result<()> {
+        match self {
+            Turbulence::Plugin(model) => model.load(),
+            Turbulence::Sum { channels } => channels.iter_mut().try_for_each(Turbulence::load_plugins),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl TurbulenceModel for Turbulence {
+    fn factor(&self, local: &LocalProfiles) -> f64 {
+        match self {
+            Turbulence::Itg(model) => model.factor(local),
+            Turbulence::Tem(model) => model.factor(local),
+            Turbulence::CriticalGradient(model) => model.factor(local),
+            Turbulence::Constant(model) => model.factor(local),
+            Turbulence::Table(model) => model.factor(local),
+            Turbulence::Surrogate(model) => model.factor(local),
+            Turbulence::Plugin(model) => model.factor(local),
+            Turbulence::Sum { channels } => channels.iter().map(|c| c.factor(local)).sum(),
+        }
+    }
+
+    fn pinch(&self, local: &LocalProfiles) -> f64 {
+        match self {
+            Turbulence::Itg(model) => model.pinch(local),
+            Turbulence::Tem(model) => model.pinch(local),
+            Turbulence::CriticalGradient(model) => model.pinch(local),
+            Turbulence::Constant(model) => model.pinch(local),
+            Turbulence::Table(model) => model.pinch(local),
+            Turbulence::Surrogate(model) => model.pinch(local),
+            Turbulence::Plugin(model) => model.pinch(local),
+            Turbulence::Sum { channels } => channels.iter().map(|c| c.pinch(local)).sum(),
+        }
+    }
+
+    /// A sum containing a surrogate (or a plugin resolving the pulse) is
+    /// taken as the full pulse response.
+    fn resolves_pulse(&self) -> bool {
+        match self {
+            Turbulence::Surrogate(model) => model.resolves_pulse(),
+            Turbulence::Plugin(model) => model.resolves_pulse(),
+            Turbulence::Sum { channels } => channels.iter().any(|c| c.resolves_pulse()),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! # Physical Units
+//!
+//! Newtypes for the quantities most easily mixed up between versions:
+//! `Density` (m⁻³), `Temperature` (keV), `Diffusivity` (m²/s), and
+//! `Velocity` (m/s). Typed entry points (`SimulationBuilder::transport`)
+//! take them, so a swapped argument does not compile.
+//!
+//! Config fields stay `f64` in those units but are read through the
+//! newtypes: a bare number is taken in the field's unit, a string names
+//! its unit and is converted on load,
+//!
+//! ```toml
+//! density = "5e13 cm^-3"     # 5e19 m⁻³
+//! temperature = "2500 eV"    # 2.5 keV
+//! d_neo = "200 cm^2/s"       # 0.02 m²/s
+//! ```
+//!
+//! and a unit of another dimension ("2 keV" for a density) fails to load.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+
+pub trait Quantity: Copy + From<f64> {
+    const NAME: &'static str;
+    const UNIT: &'static str;
+    /// Accepted unit spellings and their factor to `UNIT`.
+    const UNITS: &'static [(&'static str, f64)];
+
+    fn value(self) -> f64;
+}
+
+macro_rules! quantity {
+    ($(#[$doc:meta])* $type:ident, $name:literal, $unit:literal, $units:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default, Serialize)]
+        #[serde(transparent)]
+        pub struct $type(pub f64);
+
+        impl Quantity for $type {
+            const NAME: &'static str = $name;
+            const UNIT: &'static str = $unit;
+            const UNITS: &'static [(&'static str, f64)] = $units;
+
+            fn value(self) -> f64 {
+                self.0
+            }
+        }
+
+        impl From<f64> for $type {
+            fn from(value: f64) -> Self {
+                $type(value)
+            }
+        }
+
+        impl fmt::Display for $type {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} {}", self.0, $unit)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                match Raw::deserialize(deserializer)? {
+                    Raw::Number(value) => Ok($type(value)),
+                    Raw::Text(text) => parse(&text).map_err(serde::de::Error::custom),
+                }
+            }
+        }
+    };
+}
+
+quantity!(
+    /// m⁻³
+    Density, "density", "m⁻³",
+    &[
+        ("m^-3", 1.0), ("m-3", 1.0), ("m⁻³", 1.0),
+        ("cm^-3", 1e6), ("cm-3", 1e6), ("cm⁻³", 1e6),
+        ("1e19 m^-3", 1e19), ("10^19 m^-3", 1e19), ("1e20 m^-3", 1e20), ("10^20 m^-3", 1e20),
+    ]
+);
+quantity!(
+    /// keV
+    Temperature, "temperature", "keV",
+    &[("keV", 1.0), ("eV", 1e-3)]
+);
+quantity!(
+    /// m²/s
+    Diffusivity, "diffusivity", "m²/s",
+    &[
+        ("m^2/s", 1.0), ("m2/s", 1.0), ("m²/s", 1.0),
+        ("cm^2/s", 1e-4), ("cm2/s", 1e-4), ("cm²/s", 1e-4),
+    ]
+);
+quantity!(
+    /// m/s, negative = inward
+    Velocity, "velocity", "m/s",
+    &[("m/s", 1.0), ("cm/s", 1e-2), ("mm/s", 1e-3), ("km/s", 1e3)]
+);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Raw {
+    Number(f64),
+    Text(String),
+}
+
+/// "<value> <unit>" in any of `Q::UNITS`.
+pub fn parse<Q: Quantity>(text: &str) -> Result<Q, String> {
+    let text = text.trim();
+    let (number, unit) = text
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| format!("{:?}: expected \"<value> <unit>\", e.g. \"1.5 {}\"", text, Q::UNIT))?;
+    let value: f64 = number.parse().map_err(|_| format!("{:?}: {:?} is not a number", text, number))?;
+    let unit = unit.trim();
+    match Q::UNITS.iter().find(|(name, _)| *name == unit) {
+        Some((_, factor)) => Ok(Q::from(value * factor)),
+        None => {
+            let known: Vec<&str> = Q::UNITS.iter().map(|(name, _)| *name).collect();
+            Err(format!("{:?}: {:?} is not a {} unit ({})", text, unit, Q::NAME, known.join(", ")))
+        }
+    }
+}
+
+/// `deserialize_with` helpers for `f64` config fields.
+pub mod as_f64 {
+    use super::*;
+
+    pub fn density<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Density::deserialize(deserializer).map(Quantity::value)
+    }
+
+    pub fn temperature<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Temperature::deserialize(deserializer).map(Quantity::value)
+    }
+
+    pub fn diffusivity<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Diffusivity::deserialize(deserializer).map(Quantity::value)
+    }
+
+    pub fn velocity<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Velocity::deserialize(deserializer).map(Quantity::value)
+    }
+}
@@ -0,0 +1,106 @@
+/* Generated by cbindgen from ffi.rs (UPDATE_HEADER=1 cargo test --features ffi --test ffi); do not edit. */
+
+#ifndef W7X_SIM_H
+#define W7X_SIM_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// `w7x_sim_get_profile` quantities, each on the radius grid: r / a,
+// n_Z and n_e (m⁻³), T_e (keV), D_turb (m²/s).
+#define W7X_PROFILE_RADIUS 0
+
+#define W7X_PROFILE_IMPURITY_DENSITY 1
+
+#define W7X_PROFILE_ELECTRON_DENSITY 2
+
+#define W7X_PROFILE_ELECTRON_TEMP 3
+
+#define W7X_PROFILE_TURBULENCE 4
+
+// `w7x_sim_apply_action` kinds.
+#define W7X_ACTION_HOLD 0
+
+#define W7X_ACTION_PULSE 1
+
+// Opaque simulator handle.
+typedef struct W7xSim W7xSim;
+
+// Latest diagnostic sample and plant mode.
+typedef struct W7xObservation {
+  // s
+  double time;
+  // Measured n_Z(0) (m⁻³)
+  double central_sxr;
+  // Measured edge n_Z (m⁻³)
+  double edge_density;
+  // Measured edge D_turb (m²/s)
+  double turbulence;
+  bool pulse_active;
+} W7xObservation;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Message of the last failed call on this thread, "" if none. Valid until
+// the next failing call on the same thread.
+const char *w7x_last_error(void);
+
+// Simulator from the run configuration at `config_path` (a `w7x.toml`),
+// or the defaults for NULL. NULL on error.
+//
+// # Safety
+// `config_path` is NULL or a NUL-terminated string.
+struct W7xSim *w7x_sim_create(const char *config_path);
+
+// Frees a simulator from `w7x_sim_create`; NULL is ignored.
+//
+// # Safety
+// `sim` is NULL or a live handle, not used afterwards.
+void w7x_sim_destroy(struct W7xSim *sim);
+
+// Advances the plasma by `duration` s of simulated time.
+//
+// # Safety
+// `sim` is a live handle.
+int w7x_sim_step(struct W7xSim *sim, double duration);
+
+// Simulated time (s); NaN for NULL.
+//
+// # Safety
+// `sim` is NULL or a live handle.
+double w7x_sim_time(const struct W7xSim *sim);
+
+// Writes the latest observation to `out`.
+//
+// # Safety
+// `sim` is a live handle and `out` points to a writable `W7xObservation`.
+int w7x_sim_observe(const struct W7xSim *sim, struct W7xObservation *out);
+
+// Copies up to `len` points of a `W7X_PROFILE_*` quantity, axis first,
+// into `out`. Returns the number of grid points (call with `len` 0 to
+// size the buffer), or 0 on error.
+//
+// # Safety
+// `sim` is a live handle and `out` has room for `len` doubles (or `len`
+// is 0).
+size_t w7x_sim_get_profile(const struct W7xSim *sim, int profile, double *out, size_t len);
+
+// Requests a `W7X_ACTION_*`. A pulse goes to `window` (index into
+// `plasma.pulse_windows`; unknown = the default) at `amplitude` × D_turb
+// (clamped to 1–10×; 0 = `plasma.pulse_amplitude`). The plant still
+// enforces its pulse length and cooldown: check `pulse_active`.
+//
+// # Safety
+// `sim` is a live handle.
+int w7x_sim_apply_action(struct W7xSim *sim, int action, int window, double amplitude);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* W7X_SIM_H */
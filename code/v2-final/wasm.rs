@@ -0,0 +1,124 @@
+//! # Browser Bindings
+//!
+//! `WasmSimulator` drives the closed-loop simulation step by step from
+//! JavaScript. Profiles come back as `Float64Array`s indexed like
+//! `radius()`:
+//!
+//! ```text
+//! import init, { WasmSimulator } from "./pkg/w7x_turbulence_control.js";
+//! await init();
+//! const sim = new WasmSimulator();
+//! function frame() {
+//!     sim.step(500);
+//!     plot(sim.radius(), sim.impurity_density());
+//!     requestAnimationFrame(frame);
+//! }
+//! ```
+
+use crate::config::Config;
+use crate::controller::ControlAction;
+use crate::simulation::Simulation;
+use crate::state::ConfinementMode;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct WasmSimulator {
+    sim: Simulation,
+    auto_control: bool,
+}
+
+#[wasm_bindgen]
+impl WasmSimulator {
+    /// Simulator with the default (v2) configuration.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmSimulator {
+        Self::with_config(&Config::default())
+    }
+
+    /// Simulator from the text of a `w7x.toml` run configuration.
+    pub fn from_toml(text: &str) -> Result<WasmSimulator, JsError> {
+        let config: Config = toml::from_str(text)?;
+        config.validate()?;
+        Ok(Self::with_config(&config))
+    }
+
+    fn with_config(config: &Config) -> WasmSimulator {
+        let mut sim = Simulation::from_config(config);
+        // The page keeps whatever it wants to plot; no unbounded growth
+        sim.state.history.recording = false;
+        WasmSimulator { sim, auto_control: true }
+    }
+
+    /// Advances `n_steps` solver steps. With auto control off the
+    /// controller is bypassed and pulses only come from `trigger_pulse`.
+    pub fn step(&mut self, n_steps: u32) {
+        for _ in 0..n_steps {
+            if self.auto_control {
+                self.sim.step();
+            } else {
+                self.sim.step_open_loop();
+            }
+        }
+    }
+
+    pub fn time(&self) -> f64 {
+        self.sim.state.time
+    }
+
+    pub fn dt(&self) -> f64 {
+        self.sim.dt
+    }
+
+    pub fn pulse_active(&self) -> bool {
+        self.sim.state.confinement_mode == ConfinementMode::TurbulencePulse
+    }
+
+    /// Latest measured central SXR signal (m⁻³), NaN before the first sample.
+    pub fn central_sxr(&self) -> f64 {
+        self.sim.last_measurement().map_or(f64::NAN, |m| m.central_sxr)
+    }
+
+    /// Manual pulse request; still subject to the plant's cooldown.
+    pub fn trigger_pulse(&mut self) {
+        self.sim.state.apply_action(ControlAction::TriggerPulse);
+    }
+
+    /// False while the injector is still reloading.
+    pub fn fire_pellet(&mut self) -> bool {
+        self.sim.state.fire_pellet()
+    }
+
+    pub fn set_auto_control(&mut self, enabled: bool) {
+        self.auto_control = enabled;
+    }
+
+    pub fn radius(&self) -> Vec<f64> {
+        self.sim.state.radius_grid.to_vec()
+    }
+
+    pub fn impurity_density(&self) -> Vec<f64> {
+        self.sim.state.impurity_density.to_vec()
+    }
+
+    pub fn electron_density(&self) -> Vec<f64> {
+        self.sim.state.electron_density.to_vec()
+    }
+
+    pub fn electron_temp(&self) -> Vec<f64> {
+        self.sim.state.electron_temp.to_vec()
+    }
+
+    pub fn turbulence_profile(&self) -> Vec<f64> {
+        self.sim.state.turbulence_profile().to_vec()
+    }
+
+    pub fn zeff_profile(&self) -> Vec<f64> {
+        self.sim.state.zeff_profile().to_vec()
+    }
+}
+
+impl Default for WasmSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,243 @@
+//! # Web Dashboard (feature `websocket`)
+//!
+//! `--serve-dashboard` serves live run state on `[serve] dashboard`, so a
+//! run on a shared machine can be watched from a browser:
+//!
+//! - `GET /` returns the bundled viewer (`dashboard.html`, no external
+//!   assets);
+//! - a WebSocket on `/ws` receives JSON text frames.
+//!
+//! ```text
+//! {"type": "start", "t_max", "critical_density", "radius": [..]}       on connect
+//! {"type": "frame", "time", "mode", "pulses", "measurement": {..} | null,
+//!  "trace": [[t, n_Z(0), n_Z(edge), D_turb, pulse 0/1], ..],  since the last frame, every TRACE_INTERVAL
+//!  "profile": [n_Z(r)..], "events": ["..", ..]}                       every FRAME of wall time
+//! {"type": "end", "time"}                                             when the run finishes
+//! ```
+//!
+//! Connections are accepted on a background thread; the simulation only
+//! pays for serializing a frame when one is due. A client that cannot take
+//! a frame within `SEND_TIMEOUT` is dropped.
+
+use crate::events::{Event, TimedEvent};
+use crate::simulation::Simulation;
+use crate::state::ConfinementMode;
+use serde::Serialize;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tungstenite::{Message, WebSocket};
+
+/// Wall-clock time between frames.
+pub const FRAME: Duration = Duration::from_millis(100);
+/// Plasma time between trace points (s).
+pub const TRACE_INTERVAL: f64 = 1e-3;
+pub const SEND_TIMEOUT: Duration = Duration::from_secs(1);
+const VIEWER: &str = include_str!("dashboard.html");
+
+#[derive(Serialize)]
+struct Start<'a> {
+    r#type: &'static str,
+    t_max: f64,
+    critical_density: f64,
+    radius: &'a [f64],
+}
+
+#[derive(Serialize)]
+struct Measured {
+    central_sxr: f64,
+    edge_density: f64,
+    turbulence: f64,
+}
+
+#[derive(Serialize)]
+struct Frame<'a> {
+    r#type: &'static str,
+    time: f64,
+    mode: &'static str,
+    pulses: usize,
+    measurement: Option<Measured>,
+    trace: &'a [[f64; 5]],
+    profile: &'a [f64],
+    events: &'a [String],
+}
+
+type Clients = Arc<Mutex<Vec<WebSocket<TcpStream>>>>;
+
+pub struct WebDashboard {
+    address: SocketAddr,
+    clients: Clients,
+    trace: Vec<[f64; 5]>,
+    events: Vec<String>,
+    pulses: usize,
+    next_trace: f64,
+    last_frame: Instant,
+}
+
+impl WebDashboard {
+    /// Binds `address` and starts accepting viewers.
+    pub fn bind(address: &str, sim: &Simulation, t_max: f64, critical_density: f64) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let clients = Clients::default();
+        let start = serde_json::to_string(&Start {
+            r#type: "start",
+            t_max,
+            critical_density,
+            radius: sim.state.radius_grid.as_slice().unwrap_or_default(),
+        })?;
+        let accepted = Arc::clone(&clients);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Ok(Some(socket)) = connect(stream, &start) {
+                    accepted.lock().unwrap().push(socket);
+                }
+            }
+        });
+        Ok(WebDashboard {
+            address,
+            clients,
+            trace: Vec::new(),
+            events: Vec::new(),
+            pulses: 0,
+            next_trace: f64::NEG_INFINITY,
+            last_frame: Instant::now(),
+        })
+    }
+
+    /// Where the viewer is served (the bound port when `:0` was asked for).
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn viewers(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    pub fn note(&mut self, event: &TimedEvent) {
+        if matches!(event.event, Event::PulseStarted { .. }) {
+            self.pulses += 1;
+        }
+        self.events.push(event.message());
+    }
+
+    /// Records the step and sends a frame when one is due.
+    pub fn refresh(&mut self, sim: &Simulation) {
+        let state = &sim.state;
+        if state.time >= self.next_trace {
+            self.next_trace = state.time + TRACE_INTERVAL;
+            let sample = state.last_sample();
+            let pulse = (state.confinement_mode == ConfinementMode::TurbulencePulse) as u8;
+            self.trace.push([
+                sample.time,
+                sample.center_impurity,
+                sample.edge_impurity,
+                sample.turbulence,
+                pulse.into(),
+            ]);
+        }
+        if self.last_frame.elapsed() < FRAME {
+            return;
+        }
+        self.last_frame = Instant::now();
+        let frame = Frame {
+            r#type: "frame",
+            time: state.time,
+            mode: state.confinement_mode.name(),
+            pulses: self.pulses,
+            measurement: sim.last_measurement().map(|m| Measured {
+                central_sxr: m.central_sxr,
+                edge_density: m.edge_density,
+                turbulence: m.turbulence,
+            }),
+            trace: &self.trace,
+            profile: state.impurity_density.as_slice().unwrap_or_default(),
+            events: &self.events,
+        };
+        if let Ok(text) = serde_json::to_string(&frame) {
+            self.broadcast(text);
+        }
+        self.trace.clear();
+        self.events.clear();
+    }
+
+    /// Sends the end-of-run message and closes every connection.
+    pub fn finish(self, sim: &Simulation) {
+        self.broadcast(format!(r#"{{"type":"end","time":{}}}"#, sim.state.time));
+        for mut socket in self.clients.lock().unwrap().drain(..) {
+            let _ = socket.close(None);
+            let _ = socket.flush();
+        }
+    }
+
+    fn broadcast(&self, text: String) {
+        self.clients
+            .lock()
+            .unwrap()
+            .retain_mut(|socket| socket.send(Message::Text(text.clone())).is_ok());
+    }
+}
+
+/// Serves the viewer for plain HTTP requests and upgrades `/ws`;
+/// `Some` for a new WebSocket viewer that has been sent `start`.
+fn connect(mut stream: TcpStream, start: &str) -> io::Result<Option<WebSocket<TcpStream>>> {
+    stream.set_read_timeout(Some(SEND_TIMEOUT))?;
+    stream.set_write_timeout(Some(SEND_TIMEOUT))?;
+    let request = peek_request_line(&stream)?;
+    let path = request.split_whitespace().nth(1).unwrap_or("");
+    match path {
+        "/ws" => {
+            let mut socket = tungstenite::accept(stream).map_err(|e| io::Error::other(e.to_string()))?;
+            socket.send(Message::Text(start.to_string())).map_err(io::Error::other)?;
+            Ok(Some(socket))
+        }
+        "/" | "/index.html" => {
+            drain_head(&mut stream);
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                VIEWER.len(),
+                VIEWER
+            )?;
+            Ok(None)
+        }
+        _ => {
+            drain_head(&mut stream);
+            stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?;
+            Ok(None)
+        }
+    }
+}
+
+/// The request line, peeked so the handshake still sees the whole request.
+/// The line may arrive in several segments; waits for its `\r\n` up to
+/// `SEND_TIMEOUT` or a full buffer.
+fn peek_request_line(stream: &TcpStream) -> io::Result<String> {
+    let deadline = Instant::now() + SEND_TIMEOUT;
+    let mut head = [0; 512];
+    loop {
+        let n = stream.peek(&mut head)?;
+        let end = head[..n].windows(2).position(|w| w == b"\r\n");
+        if let Some(end) = end {
+            return Ok(String::from_utf8_lossy(&head[..end]).into_owned());
+        }
+        if n == 0 || n == head.len() || Instant::now() >= deadline {
+            return Ok(String::from_utf8_lossy(&head[..n]).into_owned());
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Reads a plain HTTP request up to its blank line, so the connection is
+/// not closed on unread data (which resets it before the client reads).
+fn drain_head(stream: &mut TcpStream) {
+    let mut head = Vec::new();
+    let mut buffer = [0; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < 16 * 1024 {
+        match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => head.extend_from_slice(&buffer[..n]),
+        }
+    }
+}